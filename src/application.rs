@@ -19,6 +19,8 @@
  */
 
 use std::cell::{BorrowError, Cell, Ref, RefCell};
+use std::collections::HashSet;
+use std::rc::Rc;
 
 use adw::glib::g_warning;
 use adw::{prelude::*, subclass::prelude::*};
@@ -28,6 +30,12 @@ use gtk::{
 };
 
 use crate::about_system_dialog::AboutSystemDialog;
+use crate::dbus_service::DBusService;
+use crate::diagnostic_report::{self, ReportFormat};
+use crate::exit_notifications::ExitNotifier;
+use crate::keep_awake::KeepAwake;
+use crate::kill_list::KillListWatcher;
+use crate::service_restarts::ServiceRestartScheduler;
 use crate::{config::VERSION, i18n::i18n, magpie_client::Readings};
 
 pub const INTERVAL_STEP: f64 = 0.05;
@@ -57,7 +65,13 @@ mod imp {
     pub struct MissionCenterApplication {
         pub settings: Cell<Option<gio::Settings>>,
         pub sys_info: RefCell<Option<crate::magpie_client::MagpieClient>>,
-        pub window: RefCell<Option<crate::MissionCenterWindow>>,
+        pub windows: RefCell<Vec<crate::MissionCenterWindow>>,
+        pub dbus_service: RefCell<Option<DBusService>>,
+        pub kill_list: RefCell<KillListWatcher>,
+        pub keep_awake: RefCell<KeepAwake>,
+        pub exit_notifier: RefCell<ExitNotifier>,
+        pub service_restarts: RefCell<ServiceRestartScheduler>,
+        pub last_readings: RefCell<Option<Rc<Readings>>>,
     }
 
     impl Default for MissionCenterApplication {
@@ -65,7 +79,13 @@ mod imp {
             Self {
                 settings: Cell::new(None),
                 sys_info: RefCell::new(None),
-                window: RefCell::new(None),
+                windows: RefCell::new(Vec::new()),
+                dbus_service: RefCell::new(None),
+                kill_list: RefCell::new(KillListWatcher::default()),
+                keep_awake: RefCell::new(KeepAwake::default()),
+                exit_notifier: RefCell::new(ExitNotifier::default()),
+                service_restarts: RefCell::new(ServiceRestartScheduler::default()),
+                last_readings: RefCell::new(None),
             }
         }
     }
@@ -84,11 +104,22 @@ mod imp {
 
             obj.set_default();
 
-            self.settings
-                .set(Some(gio::Settings::new("io.missioncenter.MissionCenter")));
+            let settings = gio::Settings::new("io.missioncenter.MissionCenter");
+            obj.add_action(&settings.create_action("active-remote-host"));
+            settings.connect_changed(Some("active-remote-host"), {
+                let obj = obj.downgrade();
+                move |_, _| {
+                    if let Some(obj) = obj.upgrade() {
+                        obj.switch_data_source();
+                    }
+                }
+            });
+            self.settings.set(Some(settings));
 
             obj.setup_gactions();
             obj.set_accels_for_action("app.quit", &["<primary>q"]);
+
+            crate::command_line::add_main_options(&obj);
         }
     }
 
@@ -97,113 +128,73 @@ mod imp {
             use gtk::glib::*;
 
             let application = self.obj();
-            // Get the current window or create one if necessary
-            let window = if let Some(window) = application.window() {
-                window
-            } else {
-                let settings = unsafe { self.settings.take().unwrap_unchecked() };
-                self.settings.set(Some(settings.clone()));
-
-                let sys_info = crate::magpie_client::MagpieClient::new();
-
-                let window = crate::MissionCenterWindow::new(&*application, &settings, &sys_info);
-
-                setup_readable_settings_cache(&settings);
-
-                window.connect_default_height_notify({
-                    move |window| {
-                        let settings = settings!();
-                        settings
-                            .set_int("window-height", window.default_height())
-                            .unwrap_or_else(|err| {
-                                g_critical!(
-                                    "MissionCenter",
-                                    "Failed to save window height: {}",
-                                    err
-                                );
-                            });
-                    }
-                });
-                window.connect_default_width_notify({
-                    move |window| {
-                        let settings = settings!();
-                        settings
-                            .set_int("window-width", window.default_width())
-                            .unwrap_or_else(|err| {
-                                g_critical!(
-                                    "MissionCenter",
-                                    "Failed to save window width: {}",
-                                    err
-                                );
-                            });
-                    }
-                });
-
-                window
-                    .set_default_size(settings.int("window-width"), settings.int("window-height"));
-
-                window.connect_maximized_notify({
-                    move |window| {
-                        let settings = settings!();
-                        settings
-                            .set_boolean("is-maximized", window.is_maximized())
-                            .unwrap_or_else(|err| {
-                                g_critical!(
-                                    "MissionCenter",
-                                    "Failed to save window maximization: {}",
-                                    err
-                                );
-                            });
-                    }
-                });
+            if let Some(window) = application.window() {
+                window.present();
+                return;
+            }
 
-                window.set_maximized(settings.boolean("is-maximized"));
+            let settings = application.settings();
+            let sys_info = crate::magpie_client::MagpieClient::new(
+                crate::magpie_client::active_transport(&settings),
+            );
+            self.sys_info.set(Some(sys_info));
 
-                sys_info.set_core_count_affects_percentages(
-                    settings.boolean("apps-page-core-count-affects-percentages"),
-                );
+            setup_readable_settings_cache(&settings);
 
-                settings.connect_changed(
-                    Some("apps-page-core-count-affects-percentages"),
-                    move |settings, _| {
-                        let app = app!();
-                        match app.sys_info() {
-                            Ok(sys_info) => {
-                                sys_info.set_core_count_affects_percentages(
-                                    settings.boolean("apps-page-core-count-affects-percentages"),
-                                );
-                            }
-                            Err(e) => {
-                                g_critical!(
-                                    "MissionCenter",
-                                    "Failed to get sys_info from MissionCenterApplication: {}",
-                                    e
-                                );
-                            }
-                        };
-                    },
-                );
+            let provider = gtk::CssProvider::new();
+            provider.load_from_bytes(&Bytes::from_static(include_bytes!(
+                "../resources/ui/style.css"
+            )));
+
+            gtk::style_context_add_provider_for_display(
+                &gtk::gdk::Display::default().expect("Could not connect to a display."),
+                &provider,
+                gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+            );
 
-                self.sys_info.set(Some(sys_info));
+            application.open_window();
+        }
+
+        fn command_line(&self, command_line: &gio::ApplicationCommandLine) -> glib::ExitCode {
+            let application = self.obj();
+            if let Some(exit_code) = crate::command_line::handle(&application, command_line) {
+                return exit_code;
+            }
 
-                let provider = gtk::CssProvider::new();
-                provider.load_from_bytes(&Bytes::from_static(include_bytes!(
-                    "../resources/ui/style.css"
-                )));
+            application.activate();
+            glib::ExitCode::SUCCESS
+        }
 
-                gtk::style_context_add_provider_for_display(
-                    &gtk::gdk::Display::default().expect("Could not connect to a display."),
-                    &provider,
-                    gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
-                );
+        fn dbus_register(
+            &self,
+            connection: &gio::DBusConnection,
+            object_path: &str,
+        ) -> Result<(), glib::Error> {
+            self.parent_dbus_register(connection, object_path)?;
+
+            // Not being reachable over D-Bus should not keep the app from starting
+            match DBusService::register(connection, object_path) {
+                Ok(dbus_service) => {
+                    self.dbus_service.replace(Some(dbus_service));
+                }
+                Err(e) => {
+                    g_critical!(
+                        "MissionCenter::Application",
+                        "Failed to register D-Bus service: {}",
+                        e
+                    );
+                }
+            }
 
-                window.upcast()
-            };
+            Ok(())
+        }
 
-            window.present();
+        fn dbus_unregister(&self, connection: &gio::DBusConnection, object_path: &str) {
+            if let Some(dbus_service) = self.dbus_service.take() {
+                dbus_service.unregister();
+            }
 
-            self.window
-                .set(window.downcast_ref::<crate::MissionCenterWindow>().cloned());
+            self.parent_dbus_unregister(connection, object_path);
         }
     }
 
@@ -239,57 +230,119 @@ impl MissionCenterApplication {
     pub fn set_initial_readings(&self, readings: Readings) {
         use gtk::glib::*;
 
-        let Some(window) = self.window() else {
+        let windows = self.windows();
+        if windows.is_empty() {
             g_critical!(
                 "MissionCenter::Application",
                 "No active window, when trying to refresh data"
             );
             return;
-        };
+        }
+
+        self.imp()
+            .kill_list
+            .borrow_mut()
+            .check(self, &readings.running_processes);
+        self.imp()
+            .keep_awake
+            .borrow_mut()
+            .check(self, &readings.running_processes);
+        self.imp().exit_notifier.borrow_mut().check(
+            self,
+            &readings.running_processes,
+            readings.process_changes.events(),
+        );
+        self.imp().service_restarts.borrow_mut().check(self, &readings);
 
-        window.set_initial_readings(readings)
+        let readings = Rc::new(readings);
+        for window in &windows {
+            window.set_initial_readings(&readings);
+        }
+        self.imp().last_readings.replace(Some(readings));
+
+        match self.sys_info() {
+            Ok(sys_info) => sys_info.continue_reading(),
+            Err(e) => {
+                g_critical!(
+                    "MissionCenter::Application",
+                    "Failed to get sys_info from MissionCenterApplication: {}",
+                    e
+                );
+            }
+        }
     }
 
     pub fn setup_animations(&self) {
+        for window in self.windows() {
+            window.setup_animations();
+        }
+    }
+
+    /// Shows the readings in every window, returns whether that went well along with the
+    /// previous readings once no window shows them anymore
+    pub fn refresh_readings(&self, readings: Readings) -> (bool, Option<Readings>) {
         use gtk::glib::*;
 
-        let Some(window) = self.window() else {
+        let windows = self.windows();
+        if windows.is_empty() {
             g_critical!(
                 "MissionCenter::Application",
                 "No active window, when trying to refresh data"
             );
-            return;
-        };
+            return (false, Some(readings));
+        }
+
+        self.imp()
+            .kill_list
+            .borrow_mut()
+            .check(self, &readings.running_processes);
+        self.imp()
+            .keep_awake
+            .borrow_mut()
+            .check(self, &readings.running_processes);
+        self.imp().exit_notifier.borrow_mut().check(
+            self,
+            &readings.running_processes,
+            readings.process_changes.events(),
+        );
+        self.imp()
+            .service_restarts
+            .borrow_mut()
+            .check(self, &readings);
+
+        // The windows share the readings, each keeps them until the next ones come in
+        let readings = Rc::new(readings);
+        let mut result = true;
+        for window in &windows {
+            result &= Self::refresh_window(window, &readings);
+        }
 
-        window.setup_animations()
+        let previous = self.imp().last_readings.replace(Some(readings));
+        (
+            result,
+            previous.and_then(|previous| Rc::try_unwrap(previous).ok()),
+        )
     }
 
-    pub fn refresh_readings(&self, readings: &mut Readings) -> bool {
-        use gtk::glib::*;
+    fn refresh_window(window: &crate::MissionCenterWindow, readings: &Rc<Readings>) -> bool {
+        if !window.is_loading() {
+            return window.update_readings(readings);
+        }
 
-        let Some(window) = self.window() else {
-            g_critical!(
-                "MissionCenter::Application",
-                "No active window, when trying to refresh data"
-            );
-            return false;
-        };
+        // Opened after the first readings came in
+        window.set_initial_readings(readings);
+        window.setup_animations();
 
-        window.update_readings(readings)
+        true
     }
 
     pub fn refresh_animations(&self) -> bool {
-        use gtk::glib::*;
-
-        let Some(window) = self.window() else {
-            g_critical!(
-                "MissionCenter::Application",
-                "No active window, when trying to refresh data"
-            );
-            return false;
-        };
+        let mut result = true;
+        for window in self.windows() {
+            result &= window.update_animations();
+        }
 
-        window.update_animations()
+        result
     }
 
     pub fn settings(&self) -> gio::Settings {
@@ -308,14 +361,248 @@ impl MissionCenterApplication {
         }
     }
 
+    /// Replaces the windows with one showing the data of the host selected in the settings
+    fn switch_data_source(&self) {
+        let imp = self.imp();
+        let old_windows = imp.windows.take();
+        if old_windows.is_empty() {
+            return;
+        }
+
+        // Stops reading, along with the Magpie process or the tunnel started for it
+        drop(imp.sys_info.take());
+
+        // The processes kept track of belong to the previous host
+        imp.keep_awake.borrow_mut().clear(self);
+        imp.exit_notifier.borrow_mut().clear();
+        imp.last_readings.take();
+
+        // The new window is shown before the old ones go away, so that the app doesn't quit
+        self.activate();
+        for window in old_windows {
+            window.destroy();
+        }
+    }
+
+    /// Has the readings encoded into frames of the session recording while any window records one
+    pub fn update_session_recording(&self) {
+        use gtk::glib::*;
+
+        let recording = self
+            .windows()
+            .iter()
+            .any(|window| window.is_recording_session());
+        match self.sys_info() {
+            Ok(sys_info) => {
+                sys_info.set_recording_host(recording.then(crate::window::snapshot_host_name));
+            }
+            Err(e) => {
+                g_critical!(
+                    "MissionCenter::Application",
+                    "Failed to get sys_info from MissionCenterApplication: {}",
+                    e
+                );
+            }
+        }
+    }
+
+    /// Has the processes using the path filter of any Apps page looked for, the client looks for
+    /// all of them at once
+    pub fn update_path_filters(&self) {
+        use gtk::glib::*;
+
+        let path_filters = self
+            .windows()
+            .iter()
+            .map(|window| window.path_filter())
+            .filter(|path_filter| !path_filter.is_empty())
+            .collect::<HashSet<_>>();
+        match self.sys_info() {
+            Ok(sys_info) => sys_info.set_path_filters(path_filters),
+            Err(e) => {
+                g_critical!(
+                    "MissionCenter::Application",
+                    "Failed to get sys_info from MissionCenterApplication: {}",
+                    e
+                );
+            }
+        }
+    }
+
+    /// Tells the user that the host selected in the settings can't be reached, offering to
+    /// switch back to this machine
+    pub fn show_connection_error(&self, message: &str) {
+        let dialog = adw::AlertDialog::new(Some(&i18n("Could Not Connect")), Some(message));
+        dialog.add_responses(&[
+            ("close", &i18n("_Close")),
+            ("this-computer", &i18n("_Show This Computer")),
+        ]);
+        dialog.set_default_response(Some("close"));
+        dialog.set_close_response("close");
+
+        dialog.connect_response(Some("this-computer"), |_, _| {
+            if let Err(e) = settings!().set_string("active-remote-host", "") {
+                g_critical!(
+                    "MissionCenter::Application",
+                    "Failed to set active-remote-host setting: {}",
+                    e
+                );
+            }
+        });
+
+        dialog.present(self.window().as_ref());
+    }
+
+    /// Whether readings tagged with `generation` come from the client currently in use
+    pub fn is_current_source(&self, generation: u64) -> bool {
+        self.imp().sys_info.try_borrow().is_ok_and(|sys_info| {
+            sys_info
+                .as_ref()
+                .is_some_and(|sys_info| sys_info.generation() == generation)
+        })
+    }
+
+    /// Keeps the system from suspending until the process exits
+    pub fn keep_awake_while_running(&self, pid: u32, name: &str) -> bool {
+        self.imp().keep_awake.borrow_mut().start(self, pid, name)
+    }
+
+    pub fn stop_keeping_awake(&self, pid: u32) {
+        self.imp().keep_awake.borrow_mut().stop(self, pid);
+    }
+
+    pub fn is_keeping_awake(&self, pid: u32) -> bool {
+        self.imp().keep_awake.borrow().is_keeping_awake(pid)
+    }
+
+    /// Sends a notification once the process exits
+    pub fn notify_on_exit(&self, pid: u32, name: &str) {
+        self.imp().exit_notifier.borrow_mut().watch(pid, name);
+    }
+
+    pub fn cancel_exit_notification(&self, pid: u32) {
+        self.imp().exit_notifier.borrow_mut().unwatch(pid);
+    }
+
+    pub fn is_notifying_on_exit(&self, pid: u32) -> bool {
+        self.imp().exit_notifier.borrow().is_watching(pid)
+    }
+
+    /// The focused window, or any window if none of them are focused
     pub fn window(&self) -> Option<crate::MissionCenterWindow> {
-        unsafe { &*self.imp().window.as_ptr() }.clone()
+        let windows = self.imp().windows.borrow();
+        self.active_window()
+            .and_then(|window| window.downcast::<crate::MissionCenterWindow>().ok())
+            .filter(|window| windows.contains(window))
+            .or_else(|| windows.first().cloned())
+    }
+
+    pub fn windows(&self) -> Vec<crate::MissionCenterWindow> {
+        self.imp().windows.borrow().clone()
+    }
+
+    /// Like `window`, but only a window that shows the readings of this machine rather than a
+    /// snapshot, `None` if every window shows one
+    pub fn live_window(&self) -> Option<crate::MissionCenterWindow> {
+        self.window()
+            .filter(|window| !window.is_showing_snapshot())
+            .or_else(|| {
+                self.windows()
+                    .into_iter()
+                    .find(|window| !window.is_showing_snapshot())
+            })
+    }
+
+    /// Opens a window on the current data source. Windows opened after the first readings
+    /// start out with the next ones.
+    fn open_window(&self) {
+        use gtk::glib::*;
+
+        let settings = self.settings();
+        let window = match self.sys_info() {
+            Ok(sys_info) => crate::MissionCenterWindow::new(self, &settings, &sys_info),
+            Err(e) => {
+                g_critical!(
+                    "MissionCenter::Application",
+                    "Failed to get sys_info from MissionCenterApplication: {}",
+                    e
+                );
+                return;
+            }
+        };
+
+        window.connect_default_height_notify({
+            move |window| {
+                let settings = settings!();
+                settings
+                    .set_int("window-height", window.default_height())
+                    .unwrap_or_else(|err| {
+                        g_critical!("MissionCenter", "Failed to save window height: {}", err);
+                    });
+            }
+        });
+        window.connect_default_width_notify({
+            move |window| {
+                let settings = settings!();
+                settings
+                    .set_int("window-width", window.default_width())
+                    .unwrap_or_else(|err| {
+                        g_critical!("MissionCenter", "Failed to save window width: {}", err);
+                    });
+            }
+        });
+
+        window.set_default_size(settings.int("window-width"), settings.int("window-height"));
+
+        window.connect_maximized_notify({
+            move |window| {
+                let settings = settings!();
+                settings
+                    .set_boolean("is-maximized", window.is_maximized())
+                    .unwrap_or_else(|err| {
+                        g_critical!(
+                            "MissionCenter",
+                            "Failed to save window maximization: {}",
+                            err
+                        );
+                    });
+            }
+        });
+
+        window.set_maximized(settings.boolean("is-maximized"));
+
+        window.connect_destroy({
+            let this = self.downgrade();
+            move |window| {
+                if let Some(this) = this.upgrade() {
+                    this.imp().windows.borrow_mut().retain(|w| w != window);
+                    this.update_session_recording();
+                    this.update_path_filters();
+                }
+            }
+        });
+
+        crate::benchmark::log_frame_times(&window);
+
+        self.imp().windows.borrow_mut().push(window.clone());
+        window.present();
+    }
+
+    fn new_window(&self) {
+        if self.imp().windows.borrow().is_empty() {
+            self.activate();
+        } else {
+            self.open_window();
+        }
     }
 
     fn setup_gactions(&self) {
         let quit_action = gio::ActionEntry::builder("quit")
             .activate(move |app: &Self, _, _| app.quit())
             .build();
+        let new_window_action = gio::ActionEntry::builder("new-window")
+            .activate(move |app: &Self, _, _| app.new_window())
+            .build();
         let preferences_action = gio::ActionEntry::builder("preferences")
             .activate(move |app: &Self, _, _| {
                 app.show_preferences();
@@ -327,18 +614,36 @@ impl MissionCenterApplication {
         let about_system_action = gio::ActionEntry::builder("system-about")
             .activate(move |app: &Self, _, _| app.show_system_about())
             .build();
+        let diagnostic_report_action = gio::ActionEntry::builder("diagnostic-report")
+            .activate(move |app: &Self, _, _| app.show_diagnostic_report())
+            .build();
+        let remote_hosts_action = gio::ActionEntry::builder("remote-hosts")
+            .activate(move |app: &Self, _, _| app.show_remote_hosts())
+            .build();
+        let usage_history_action = gio::ActionEntry::builder("usage-history")
+            .activate(move |app: &Self, _, _| app.show_usage_history())
+            .build();
+        let startup_analysis_action = gio::ActionEntry::builder("startup-analysis")
+            .activate(move |app: &Self, _, _| app.show_startup_analysis())
+            .build();
         let keyboard_shortcuts_action = gio::ActionEntry::builder("keyboard-shortcuts")
             .activate(move |app: &Self, _, _| app.show_keyboard_shortcuts())
             .build();
 
         self.add_action_entries([
             quit_action,
+            new_window_action,
             preferences_action,
             about_action,
             about_system_action,
+            diagnostic_report_action,
+            remote_hosts_action,
+            usage_history_action,
+            startup_analysis_action,
             keyboard_shortcuts_action,
         ]);
 
+        self.set_accels_for_action("app.new-window", &["<Control>n"]);
         self.set_accels_for_action("app.preferences", &["<Control>comma"]);
         self.set_accels_for_action("app.keyboard-shortcuts", &["<Control>question"]);
     }
@@ -356,6 +661,45 @@ impl MissionCenterApplication {
         preferences.present(Some(&window));
     }
 
+    fn show_remote_hosts(&self) {
+        let Some(window) = self.window() else {
+            g_critical!(
+                "MissionCenter::Application",
+                "No active window, when trying to show remote hosts"
+            );
+            return;
+        };
+
+        let dialog = crate::remote_hosts_dialog::RemoteHostsDialog::new();
+        dialog.present(Some(&window));
+    }
+
+    fn show_usage_history(&self) {
+        let Some(window) = self.window() else {
+            g_critical!(
+                "MissionCenter::Application",
+                "No active window, when trying to show the usage history"
+            );
+            return;
+        };
+
+        let dialog = crate::usage_history_dialog::UsageHistoryDialog::new();
+        dialog.present(Some(&window));
+    }
+
+    fn show_startup_analysis(&self) {
+        let Some(window) = self.window() else {
+            g_critical!(
+                "MissionCenter::Application",
+                "No active window, when trying to show the startup analysis"
+            );
+            return;
+        };
+
+        let dialog = crate::startup_analysis_dialog::StartupAnalysisDialog::new();
+        dialog.present(Some(&window));
+    }
+
     fn show_keyboard_shortcuts(&self) {
         let Some(app_window) = self.window() else {
             return;
@@ -392,6 +736,77 @@ impl MissionCenterApplication {
         dialog.present(Some(&window));
     }
 
+    fn show_diagnostic_report(&self) {
+        let app = app!();
+        let Ok(magpie) = app.sys_info() else {
+            g_warning!("MissionCenter::Application", "Failed to get magpie client");
+            return;
+        };
+
+        let Some(window) = self.window() else {
+            g_critical!(
+                "MissionCenter::Application",
+                "No active window, when trying to show diagnostic report"
+            );
+            return;
+        };
+
+        // Both formats are put together right away, so that the report matches the moment it
+        // was asked for
+        let about = magpie.about_system();
+        let reports = [ReportFormat::Markdown, ReportFormat::Html].map(|format| {
+            (
+                format,
+                diagnostic_report::generate(
+                    format,
+                    &about,
+                    &window.system_snapshot(),
+                    &window.running_processes(),
+                ),
+            )
+        });
+
+        let format_selector = gtk::DropDown::from_strings(&["Markdown", "HTML"]);
+        format_selector.set_halign(gtk::Align::Center);
+
+        let dialog = adw::AlertDialog::new(
+            Some(&i18n("Diagnostic Report")),
+            Some(&i18n(
+                "A summary of the system, its current usage, the busiest processes and the failed services, for attaching to support requests.",
+            )),
+        );
+        dialog.set_extra_child(Some(&format_selector));
+        dialog.add_responses(&[
+            ("cancel", &i18n("_Cancel")),
+            ("copy", &i18n("_Copy")),
+            ("save", &i18n("_Save…")),
+        ]);
+        dialog.set_response_appearance("save", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("save"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(None, {
+            let window = window.downgrade();
+            move |_, response| {
+                let Some(window) = window.upgrade() else {
+                    return;
+                };
+                let Some((format, report)) = reports.get(format_selector.selected() as usize)
+                else {
+                    return;
+                };
+
+                match response {
+                    "copy" => window.clipboard().set_text(report),
+                    "save" => save_diagnostic_report(&window, *format, report.clone()),
+                    _ => {}
+                }
+            }
+        });
+
+        dialog.present(Some(&window));
+    }
+
     fn show_about(&self) {
         let Some(window) = self.window() else {
             g_critical!(
@@ -478,3 +893,39 @@ impl MissionCenterApplication {
         about.present(Some(&window));
     }
 }
+
+fn save_diagnostic_report(
+    window: &crate::MissionCenterWindow,
+    format: ReportFormat,
+    report: String,
+) {
+    let file_dialog = gtk::FileDialog::builder()
+        .title(i18n("Save Diagnostic Report"))
+        .initial_name(format!("mission-center-report.{}", format.file_extension()))
+        .build();
+
+    file_dialog.save(Some(window), None::<&gio::Cancellable>, move |file| {
+        let file = match file {
+            Ok(file) => file,
+            // Also the case when the dialog is dismissed
+            Err(_) => return,
+        };
+
+        file.replace_contents_async(
+            report.into_bytes(),
+            None,
+            false,
+            gio::FileCreateFlags::REPLACE_DESTINATION,
+            None::<&gio::Cancellable>,
+            |result| {
+                if let Err((_, e)) = result {
+                    g_critical!(
+                        "MissionCenter::Application",
+                        "Failed to save diagnostic report: {}",
+                        e
+                    );
+                }
+            },
+        );
+    });
+}
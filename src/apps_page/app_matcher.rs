@@ -0,0 +1,194 @@
+/* apps_page/app_matcher.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::rc::Rc;
+
+use gtk::gio;
+use gtk::glib::{self, g_debug};
+use gtk::prelude::*;
+
+use magpie_types::apps::icon::Icon;
+use magpie_types::apps::App;
+use magpie_types::processes::Process;
+
+// Executables that are shared by many unrelated programs and can't be used to identify an app
+const GENERIC_EXECUTABLES: &[&str] = &[
+    "bash",
+    "dbus-send",
+    "env",
+    "flatpak",
+    "gapplication",
+    "java",
+    "mono",
+    "node",
+    "perl",
+    "python",
+    "python2",
+    "python3",
+    "ruby",
+    "sh",
+    "snap",
+    "wine",
+    "xdg-open",
+    "zsh",
+];
+
+#[derive(Clone)]
+struct DesktopEntry {
+    id: String,
+    name: String,
+    icon: Option<String>,
+}
+
+/// Matches processes that were not launched through a desktop file (e.g. started from a
+/// terminal) against the `Exec` line of installed desktop files.
+#[derive(Default)]
+pub struct AppMatcher {
+    entries: HashMap<String, DesktopEntry>,
+    monitor: Option<gio::AppInfoMonitor>,
+    is_stale: Rc<Cell<bool>>,
+}
+
+impl AppMatcher {
+    /// Adds the PIDs of processes that match an installed app, and that are not already part of
+    /// an app, to `running_apps`. Apps that are not running according to Magpie are created.
+    pub fn group_processes(
+        &mut self,
+        running_apps: &mut HashMap<String, App>,
+        process_map: &HashMap<u32, Process>,
+    ) {
+        self.refresh();
+
+        if self.entries.is_empty() {
+            return;
+        }
+
+        let claimed = running_apps
+            .values()
+            .flat_map(|app| app.pids.iter().copied())
+            .collect::<HashSet<_>>();
+
+        for process in process_map.values() {
+            if claimed.contains(&process.pid) {
+                continue;
+            }
+
+            // Kernel threads and processes we are not allowed to inspect
+            if process.exe.is_empty() {
+                continue;
+            }
+
+            let Some(entry) = executable_name(&process.exe).and_then(|exe| self.entries.get(exe))
+            else {
+                continue;
+            };
+
+            running_apps
+                .entry(entry.id.clone())
+                .or_insert_with(|| App {
+                    id: entry.id.clone(),
+                    name: entry.name.clone(),
+                    icon: entry.icon.as_ref().map(|icon| magpie_types::apps::Icon {
+                        icon: Some(Icon::Id(icon.clone())),
+                    }),
+                    ..Default::default()
+                })
+                .pids
+                .push(process.pid);
+        }
+    }
+
+    fn refresh(&mut self) {
+        if self.monitor.is_none() {
+            let monitor = gio::AppInfoMonitor::get();
+            monitor.connect_changed({
+                let is_stale = self.is_stale.clone();
+                move |_| is_stale.set(true)
+            });
+            self.monitor = Some(monitor);
+            self.is_stale.set(true);
+        }
+
+        if !self.is_stale.replace(false) {
+            return;
+        }
+
+        self.entries.clear();
+
+        for app_info in gio::AppInfo::all() {
+            if !app_info.should_show() {
+                continue;
+            }
+
+            let Some(app_id) = app_info.id() else {
+                continue;
+            };
+            let app_id = app_id.trim_end_matches(".desktop");
+
+            let Some(exe) = app_info.commandline().and_then(|cmd| exec_name(&cmd)) else {
+                continue;
+            };
+
+            if GENERIC_EXECUTABLES.contains(&exe.as_str()) {
+                continue;
+            }
+
+            let icon = app_info
+                .icon()
+                .and_then(|icon| IconExt::to_string(&icon))
+                .map(|icon| icon.to_string());
+
+            // The first entry wins, this matches the lookup order of the desktop file directories
+            self.entries.entry(exe).or_insert_with(|| DesktopEntry {
+                id: app_id.to_string(),
+                name: app_info.name().to_string(),
+                icon,
+            });
+        }
+
+        g_debug!(
+            "MissionCenter::AppMatcher",
+            "Indexed {} desktop file executables",
+            self.entries.len()
+        );
+    }
+}
+
+// Extracts the name of the binary that is run by a desktop file `Exec` line, skipping over
+// any `env` invocation and its variable assignments
+fn exec_name(command_line: &Path) -> Option<String> {
+    let command_line = command_line.to_string_lossy();
+    let args = glib::shell_parse_argv(command_line.as_ref()).ok()?;
+
+    let mut args = args.iter().map(|arg| arg.to_string_lossy());
+    let mut exe = args.next()?;
+    if executable_name(&exe) == Some("env") {
+        exe = args.find(|arg| !arg.starts_with('-') && !arg.contains('='))?;
+    }
+
+    executable_name(&exe).map(str::to_string)
+}
+
+fn executable_name(path: &str) -> Option<&str> {
+    path.rsplit('/').next().filter(|name| !name.is_empty())
+}
@@ -0,0 +1,79 @@
+/* apps_page/desktop_file_drop.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use adw::prelude::*;
+use gtk::glib::{g_debug, g_warning};
+use gtk::{gdk, gio, glib};
+
+/// Accepts `.desktop` files dropped onto the page, e.g. from a file manager, and launches the
+/// apps they describe
+pub fn drop_target() -> gtk::DropTarget {
+    let drop_target = gtk::DropTarget::new(gdk::FileList::static_type(), gdk::DragAction::COPY);
+    drop_target.connect_drop(|drop_target, value, _, _| {
+        let Ok(files) = value.get::<gdk::FileList>() else {
+            return false;
+        };
+
+        let desktop_files = files
+            .files()
+            .into_iter()
+            .filter_map(|file| file.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "desktop")
+            })
+            .collect::<Vec<_>>();
+        if desktop_files.is_empty() {
+            return false;
+        }
+
+        let launch_context = drop_target
+            .widget()
+            .map(|widget| widget.display().app_launch_context());
+        for path in desktop_files {
+            let Some(app_info) = gio::DesktopAppInfo::from_filename(&path) else {
+                g_warning!(
+                    "MissionCenter::AppsPage",
+                    "Failed to read dropped desktop file {}",
+                    path.display()
+                );
+                continue;
+            };
+
+            g_debug!(
+                "MissionCenter::AppsPage",
+                "Launching {} from a dropped desktop file",
+                app_info.name()
+            );
+            if let Err(e) = app_info.launch(&[], launch_context.as_ref()) {
+                g_warning!(
+                    "MissionCenter::AppsPage",
+                    "Failed to launch {}: {}",
+                    path.display(),
+                    e
+                );
+            }
+        }
+
+        true
+    });
+
+    drop_target
+}
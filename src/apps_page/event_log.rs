@@ -0,0 +1,241 @@
+/* apps_page/event_log.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use adw::prelude::*;
+use gtk::{glib, subclass::prelude::*};
+
+use crate::i18n::i18n_k;
+use crate::magpie_client::{ProcessEvent, ProcessEventKind};
+
+/// Older events are dropped past this many
+const MAX_EVENTS: u32 = 1000;
+
+mod imp {
+    use super::*;
+
+    #[derive(gtk::CompositeTemplate)]
+    #[template(resource = "/io/missioncenter/MissionCenter/ui/apps_page/event_log.ui")]
+    pub struct ProcessEventLog {
+        #[template_child]
+        pub filter_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        pub copy_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub clear_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub scrolled_window: TemplateChild<gtk::ScrolledWindow>,
+        #[template_child]
+        pub event_list: TemplateChild<gtk::ListView>,
+
+        pub events: gtk::StringList,
+        pub filter: gtk::StringFilter,
+        pub filter_model: gtk::FilterListModel,
+    }
+
+    impl Default for ProcessEventLog {
+        fn default() -> Self {
+            let events = gtk::StringList::new(&[]);
+
+            let filter = gtk::StringFilter::new(Some(gtk::PropertyExpression::new(
+                gtk::StringObject::static_type(),
+                None::<gtk::Expression>,
+                "string",
+            )));
+            filter.set_ignore_case(true);
+            filter.set_match_mode(gtk::StringFilterMatchMode::Substring);
+
+            let filter_model =
+                gtk::FilterListModel::new(Some(events.clone()), Some(filter.clone()));
+
+            Self {
+                filter_entry: TemplateChild::default(),
+                copy_button: TemplateChild::default(),
+                clear_button: TemplateChild::default(),
+                scrolled_window: TemplateChild::default(),
+                event_list: TemplateChild::default(),
+
+                events,
+                filter,
+                filter_model,
+            }
+        }
+    }
+
+    impl ProcessEventLog {
+        fn shown_events(&self) -> String {
+            let mut result = String::new();
+            for event in self.filter_model.iter::<gtk::StringObject>().flatten() {
+                result.push_str(event.string().as_str());
+                result.push('\n');
+            }
+
+            result
+        }
+
+        fn is_scrolled_to_end(&self) -> bool {
+            let adjustment = self.scrolled_window.vadjustment();
+            adjustment.value() >= adjustment.upper() - adjustment.page_size() - 1.
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ProcessEventLog {
+        const NAME: &'static str = "ProcessEventLog";
+        type Type = super::ProcessEventLog;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ProcessEventLog {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let factory = gtk::SignalListItemFactory::new();
+            factory.connect_setup(|_, list_item| {
+                let Some(list_item) = list_item.downcast_ref::<gtk::ListItem>() else {
+                    return;
+                };
+
+                let label = gtk::Label::builder()
+                    .xalign(0.)
+                    .ellipsize(gtk::pango::EllipsizeMode::End)
+                    .build();
+                list_item.set_child(Some(&label));
+            });
+            factory.connect_bind(|_, list_item| {
+                let Some(list_item) = list_item.downcast_ref::<gtk::ListItem>() else {
+                    return;
+                };
+                let (Some(label), Some(event)) = (
+                    list_item
+                        .child()
+                        .and_then(|child| child.downcast::<gtk::Label>().ok()),
+                    list_item
+                        .item()
+                        .and_then(|item| item.downcast::<gtk::StringObject>().ok()),
+                ) else {
+                    return;
+                };
+
+                label.set_label(event.string().as_str());
+            });
+            self.event_list.set_factory(Some(&factory));
+            self.event_list.set_model(Some(&gtk::NoSelection::new(Some(
+                self.filter_model.clone(),
+            ))));
+
+            self.filter_entry.connect_search_changed({
+                let this = self.obj().downgrade();
+                move |entry| {
+                    if let Some(this) = this.upgrade() {
+                        let text = entry.text();
+                        this.imp()
+                            .filter
+                            .set_search(Some(text.as_str()).filter(|text| !text.is_empty()));
+                    }
+                }
+            });
+
+            self.copy_button.connect_clicked({
+                let this = self.obj().downgrade();
+                move |_| {
+                    if let Some(this) = this.upgrade() {
+                        this.clipboard().set_text(&this.imp().shown_events());
+                    }
+                }
+            });
+
+            self.clear_button.connect_clicked({
+                let this = self.obj().downgrade();
+                move |_| {
+                    if let Some(this) = this.upgrade() {
+                        let events = &this.imp().events;
+                        events.splice(0, events.n_items(), &[]);
+                    }
+                }
+            });
+        }
+    }
+
+    impl WidgetImpl for ProcessEventLog {}
+
+    impl BoxImpl for ProcessEventLog {}
+}
+
+glib::wrapper! {
+    pub struct ProcessEventLog(ObjectSubclass<imp::ProcessEventLog>)
+        @extends gtk::Box, gtk::Widget,
+        @implements gtk::Orientable, gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl ProcessEventLog {
+    /// Appends a line per event, stamped with the current time. The list keeps following the
+    /// newest events unless it was scrolled up.
+    pub fn add_events(&self, events: &[ProcessEvent]) {
+        if events.is_empty() {
+            return;
+        }
+
+        let imp = self.imp();
+
+        let time = glib::DateTime::now_local()
+            .and_then(|now| now.format("%T"))
+            .unwrap_or_default();
+        let lines = events
+            .iter()
+            .map(|event| {
+                let pid = event.pid.to_string();
+                let kwargs = [("name", event.name.as_str()), ("pid", pid.as_str())];
+                let description = match event.kind {
+                    ProcessEventKind::Started => i18n_k("{name} (PID {pid}) started", &kwargs),
+                    ProcessEventKind::Exited => i18n_k("{name} (PID {pid}) exited", &kwargs),
+                };
+                format!("{time}  {description}")
+            })
+            .collect::<Vec<_>>();
+
+        let follow = imp.is_scrolled_to_end();
+
+        let count = imp.events.n_items();
+        let overflow = (count + lines.len() as u32).saturating_sub(MAX_EVENTS);
+        imp.events.splice(
+            count,
+            0,
+            &lines.iter().map(String::as_str).collect::<Vec<_>>(),
+        );
+        if overflow > 0 {
+            imp.events
+                .splice(0, overflow.min(imp.events.n_items()), &[]);
+        }
+
+        let shown_count = imp.filter_model.n_items();
+        if follow && shown_count > 0 && self.is_visible() {
+            imp.event_list
+                .scroll_to(shown_count - 1, gtk::ListScrollFlags::NONE, None);
+        }
+    }
+}
@@ -19,8 +19,9 @@
  */
 
 use std::cell::{Cell, OnceCell, RefCell};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Write;
+use std::time::Duration;
 
 use adw::glib::g_critical;
 use adw::prelude::*;
@@ -28,13 +29,27 @@ use arrayvec::ArrayString;
 use gtk::{gio, glib, subclass::prelude::*};
 
 use crate::i18n::{i18n, ni18n_f};
-use crate::magpie_client::App;
+use crate::magpie_client::{is_kernel_threads_parent, App, KERNEL_THREADS_PID};
+use crate::table_view::actions::{
+    create_action_group, execute_action, ActionTarget, PROCESS_ACTIONS,
+};
 use crate::table_view::{
-    update_apps, update_processes, ContentType, ProcessActionBar, RowModel, RowModelBuilder,
-    SectionType, SettingsNamespace, TableView,
+    filter_presets, pinned, update_apps, update_processes, ChangedStats, ContentType,
+    ProcessActionBar, ProcessDetails, RowModel, RowModelBuilder, SectionType, SettingsNamespace,
+    TableView,
 };
+use crate::{app, settings};
+
+mod app_matcher;
+mod desktop_file_drop;
+mod event_log;
+mod pending_terminations;
+mod recent_exits;
+
+use event_log::ProcessEventLog;
+use recent_exits::RecentExits;
 
-pub mod actions;
+pub use recent_exits::exit_reason_text;
 
 mod imp {
     use super::*;
@@ -50,8 +65,36 @@ mod imp {
         #[template_child]
         pub collapse_label: TemplateChild<gtk::Label>,
         #[template_child]
+        pub path_filter_button: TemplateChild<gtk::MenuButton>,
+        #[template_child]
+        pub path_filter_entry: TemplateChild<gtk::Entry>,
+        #[template_child]
+        pub path_filter_status: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub filter_presets_button: TemplateChild<gtk::MenuButton>,
+        #[template_child]
+        pub toggle_problem_states: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        pub toggle_session: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        pub toggle_event_log: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        pub toggle_details_sidebar: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        pub split_view: TemplateChild<adw::NavigationSplitView>,
+        #[template_child]
         pub table_view: TemplateChild<TableView>,
         #[template_child]
+        pub details_page: TemplateChild<adw::NavigationPage>,
+        #[template_child]
+        pub details_stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub details: TemplateChild<ProcessDetails>,
+        #[template_child]
+        pub event_log: TemplateChild<ProcessEventLog>,
+        #[template_child]
+        pub recent_exits: TemplateChild<RecentExits>,
+        #[template_child]
         pub process_action_bar: TemplateChild<ProcessActionBar>,
 
         pub apps_section: RowModel,
@@ -62,8 +105,14 @@ mod imp {
 
         pub row_sorter: OnceCell<gtk::TreeListRowSorter>,
 
-        pub app_icons: RefCell<HashMap<u32, String>>,
         pub selected_item: RefCell<RowModel>,
+
+        pub app_matcher: RefCell<app_matcher::AppMatcher>,
+        pub pending_terminations: RefCell<pending_terminations::PendingTerminations>,
+
+        pub narrow: Cell<bool>,
+        // The row the details sidebar shows, so that it is only bound again for another row
+        pub details_model: RefCell<Option<RowModel>>,
     }
 
     impl Default for AppsPage {
@@ -72,7 +121,21 @@ mod imp {
                 h1: TemplateChild::default(),
                 h2: TemplateChild::default(),
                 collapse_label: TemplateChild::default(),
+                path_filter_button: TemplateChild::default(),
+                path_filter_entry: TemplateChild::default(),
+                path_filter_status: TemplateChild::default(),
+                filter_presets_button: TemplateChild::default(),
+                toggle_problem_states: TemplateChild::default(),
+                toggle_session: TemplateChild::default(),
+                toggle_event_log: TemplateChild::default(),
+                toggle_details_sidebar: TemplateChild::default(),
+                split_view: TemplateChild::default(),
                 table_view: TemplateChild::default(),
+                details_page: TemplateChild::default(),
+                details_stack: TemplateChild::default(),
+                details: TemplateChild::default(),
+                event_log: TemplateChild::default(),
+                recent_exits: TemplateChild::default(),
                 process_action_bar: TemplateChild::default(),
 
                 apps_section: RowModelBuilder::new()
@@ -91,8 +154,13 @@ mod imp {
 
                 row_sorter: OnceCell::new(),
 
-                app_icons: RefCell::new(HashMap::new()),
                 selected_item: RefCell::new(RowModelBuilder::new().build()),
+
+                app_matcher: RefCell::new(Default::default()),
+                pending_terminations: RefCell::new(Default::default()),
+
+                narrow: Cell::new(false),
+                details_model: RefCell::new(None),
             }
         }
     }
@@ -113,6 +181,95 @@ mod imp {
 
             self.process_action_bar.imp().expand();
         }
+
+        /// Side by side while there is room for both, one at a time otherwise
+        pub fn update_split_view(&self) {
+            let enabled = self.toggle_details_sidebar.is_active();
+            self.split_view.set_collapsed(!enabled || self.narrow.get());
+
+            if enabled {
+                self.show_selected_details();
+            } else {
+                self.split_view.set_show_content(false);
+                self.details.unbind();
+                self.details_model.replace(None);
+            }
+        }
+
+        fn show_selected_details(&self) {
+            let selected_item = self.table_view.selected_item();
+            if self.details_model.borrow().as_ref() == Some(&selected_item) {
+                return;
+            }
+
+            let content_type = selected_item.content_type();
+            if content_type != ContentType::Process && content_type != ContentType::App {
+                self.details.unbind();
+                self.details_model.replace(None);
+                self.details_page.set_title(&i18n("Details"));
+                self.details_stack.set_visible_child_name("empty");
+                return;
+            }
+
+            self.details_page.set_title(&selected_item.name());
+            self.details.set_model(
+                selected_item.clone(),
+                self.table_view.cpu_usage_mode(),
+                self.table_view.is_showing_snapshot(),
+            );
+            self.details_stack.set_visible_child_name("details");
+            self.details_model.replace(Some(selected_item));
+        }
+
+        fn choose_path_filter_folder(&self) {
+            let file_dialog = gtk::FileDialog::builder()
+                .title(i18n("Choose a Folder"))
+                .build();
+
+            let window = self.obj().root().and_downcast::<gtk::Window>();
+            file_dialog.select_folder(window.as_ref(), None::<&gio::Cancellable>, {
+                let this = self.obj().downgrade();
+                move |folder| {
+                    let (Some(this), Some(path)) =
+                        (this.upgrade(), folder.ok().and_then(|folder| folder.path()))
+                    else {
+                        return;
+                    };
+
+                    let imp = this.imp();
+                    imp.path_filter_entry.set_text(&path.to_string_lossy());
+                    imp.path_filter_entry.emit_activate();
+                }
+            });
+        }
+
+        /// Tells how many processes use the path, and marks the button while the filter is set
+        pub fn update_path_filter_status(&self) {
+            if self.table_view.path_filter().is_empty() {
+                self.path_filter_button.remove_css_class("accent");
+                self.path_filter_status.set_visible(false);
+                return;
+            }
+
+            self.path_filter_button.add_css_class("accent");
+            self.path_filter_status.set_visible(true);
+            if self.table_view.path_filter_pending() {
+                self.path_filter_status
+                    .set_label(&i18n("Looking for processes using it…"));
+                return;
+            }
+            match self.table_view.path_filter_match_count() {
+                Some(count) => self.path_filter_status.set_label(&ni18n_f(
+                    "Used by {} process",
+                    "Used by {} processes",
+                    count as u32,
+                    &[&count.to_string()],
+                )),
+                None => self.path_filter_status.set_label(&i18n(
+                    "Open files can only be looked for on this machine, outside of a sandbox",
+                )),
+            }
+        }
     }
 
     #[glib::object_subclass]
@@ -123,6 +280,9 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             RowModel::ensure_type();
+            ProcessEventLog::ensure_type();
+            RecentExits::ensure_type();
+            ProcessDetails::ensure_type();
 
             klass.bind_template();
         }
@@ -176,7 +336,10 @@ mod imp {
                             continue;
                         };
 
-                        if row_model.content_type() != ContentType::SectionHeader {
+                        // The pinned section is left as is, it only holds copies of entries
+                        if row_model.content_type() != ContentType::SectionHeader
+                            || row_model.section_type() == SectionType::PinnedSection
+                        {
                             continue;
                         }
 
@@ -191,21 +354,128 @@ mod imp {
             });
 
             page_actions.add_action(&action_collapse_all);
+
+            let action_track_termination = gio::SimpleAction::new(
+                "track-termination",
+                Some(&Vec::<u32>::static_variant_type()),
+            );
+            action_track_termination.connect_activate({
+                let this = self.obj().downgrade();
+                move |_action, pids| {
+                    let Some(this) = this.upgrade() else {
+                        return;
+                    };
+
+                    let Some(pids) = pids.and_then(|pids| pids.get::<Vec<u32>>()) else {
+                        g_critical!(
+                            "MissionCenter::AppsPage",
+                            "Failed to get PIDs for `track-termination` action"
+                        );
+                        return;
+                    };
+
+                    this.imp().pending_terminations.borrow_mut().add(&pids);
+                }
+            });
+            page_actions.add_action(&action_track_termination);
+
+            let action_show_details = gio::SimpleAction::new("show-details", None);
+            action_show_details.connect_activate({
+                let this = self.obj().downgrade();
+                move |_action, _| {
+                    let Some(this) = this.upgrade() else {
+                        return;
+                    };
+                    let imp = this.imp();
+
+                    imp.show_selected_details();
+                    imp.split_view.set_show_content(true);
+                }
+            });
+            page_actions.add_action(&action_show_details);
             self.obj()
                 .insert_action_group("apps-page", Some(&page_actions));
 
-            let process_actions = gio::SimpleActionGroup::new();
-            process_actions.add_action(&actions::action_stop(&self.table_view));
-            process_actions.add_action(&actions::action_force_stop(&self.table_view));
-            process_actions.add_action(&actions::action_suspend(&self.table_view));
-            process_actions.add_action(&actions::action_continue(&self.table_view));
-            process_actions.add_action(&actions::action_hangup(&self.table_view));
-            process_actions.add_action(&actions::action_interrupt(&self.table_view));
-            process_actions.add_action(&actions::action_user_one(&self.table_view));
-            process_actions.add_action(&actions::action_user_two(&self.table_view));
-            process_actions.add_action(&actions::action_details(&self.table_view));
+            let process_actions = create_action_group(&self.table_view, PROCESS_ACTIONS);
             self.obj()
-                .insert_action_group("process", Some(&process_actions));
+                .insert_action_group(ActionTarget::Process.group_name(), Some(&process_actions));
+
+            self.obj().add_controller(desktop_file_drop::drop_target());
+
+            self.path_filter_entry.connect_activate({
+                let this = self.obj().downgrade();
+                move |entry| {
+                    if let Some(this) = this.upgrade() {
+                        let imp = this.imp();
+                        imp.table_view.set_path_filter(entry.text().trim());
+                        imp.update_path_filter_status();
+                    }
+                }
+            });
+            // Clearing the path shows everything again right away
+            self.path_filter_entry.connect_changed({
+                let this = self.obj().downgrade();
+                move |entry| {
+                    if let Some(this) = this.upgrade() {
+                        if entry.text().is_empty() {
+                            let imp = this.imp();
+                            imp.table_view.set_path_filter("");
+                            imp.update_path_filter_status();
+                        }
+                    }
+                }
+            });
+            self.path_filter_entry.connect_icon_press({
+                let this = self.obj().downgrade();
+                move |_, _| {
+                    if let Some(this) = this.upgrade() {
+                        this.imp().choose_path_filter_folder();
+                    }
+                }
+            });
+
+            settings!()
+                .bind(
+                    "apps-page-show-event-log",
+                    &*self.toggle_event_log,
+                    "active",
+                )
+                .build();
+            settings!()
+                .bind(
+                    "apps-page-show-details-sidebar",
+                    &*self.toggle_details_sidebar,
+                    "active",
+                )
+                .build();
+            self.toggle_details_sidebar.connect_toggled({
+                let this = self.obj().downgrade();
+                move |_| {
+                    if let Some(this) = this.upgrade() {
+                        this.imp().update_split_view();
+                    }
+                }
+            });
+            self.table_view.connect_selected_item_notify({
+                let this = self.obj().downgrade();
+                move |_| {
+                    let Some(this) = this.upgrade() else {
+                        return;
+                    };
+                    let imp = this.imp();
+                    if imp.toggle_details_sidebar.is_active() {
+                        imp.show_selected_details();
+                    }
+                }
+            });
+            self.update_split_view();
+            settings!()
+                .bind(
+                    "apps-page-recent-exits-expanded",
+                    &self.recent_exits.expander(),
+                    "expanded",
+                )
+                .build();
         }
     }
 
@@ -225,7 +495,7 @@ glib::wrapper! {
 }
 
 impl AppsPage {
-    pub fn set_initial_readings(&self, readings: &mut crate::magpie_client::Readings) -> bool {
+    pub fn set_initial_readings(&self, readings: &crate::magpie_client::Readings) -> bool {
         let imp = self.imp();
 
         imp.table_view.imp().setup(
@@ -234,23 +504,43 @@ impl AppsPage {
             &imp.processes_section,
             Some(&imp.process_action_bar),
             None,
-            None::<[_; 0]>,
+            Some([
+                imp.toggle_problem_states.downgrade(),
+                imp.toggle_session.downgrade(),
+            ]),
         );
 
+        // Presets are stored per page, so this can only be done once the namespace is known
+        let filter_preset_actions = filter_presets::create_action_group(&imp.table_view);
+        self.insert_action_group(
+            filter_presets::ACTION_GROUP_NAME,
+            Some(&filter_preset_actions),
+        );
+        imp.filter_presets_button
+            .set_menu_model(Some(&filter_presets::menu_model(&imp.table_view)));
+
         self.update_common(readings);
+        imp.table_view.update_totals();
+        imp.table_view.restore_selection();
 
         true
     }
 
-    pub fn update_readings(&self, readings: &mut crate::magpie_client::Readings) -> bool {
+    pub fn update_readings(&self, readings: &crate::magpie_client::Readings) -> bool {
         let imp = self.imp();
 
-        self.update_common(readings);
+        self.update_events(readings);
 
-        if let Some(row_sorter) = imp.table_view.imp().row_sorter.get() {
-            row_sorter.changed(gtk::SorterChange::Different)
+        if imp.table_view.refresh_path_filter(readings) {
+            imp.update_path_filter_status();
         }
 
+        imp.table_view.begin_update();
+        let changed_stats = self.update_common(readings);
+        imp.table_view.sort_if_changed(changed_stats);
+        imp.table_view.end_update();
+        imp.table_view.update_totals();
+
         if readings.network_stats_error.is_some() {
             imp.table_view
                 .get()
@@ -262,8 +552,28 @@ impl AppsPage {
         true
     }
 
-    fn update_common(&self, readings: &mut crate::magpie_client::Readings) {
+    /// Keeps up with the processes that exited or did not end when asked to. Also called while
+    /// the window is hidden and the rows are not updated.
+    pub fn update_events(&self, readings: &crate::magpie_client::Readings) {
+        let imp = self.imp();
+
+        let grace_period =
+            Duration::from_secs(settings!().uint("apps-page-end-task-grace-period") as u64);
+        let overdue = imp
+            .pending_terminations
+            .borrow_mut()
+            .update(&readings.running_processes, grace_period);
+        if !overdue.is_empty() {
+            self.handle_overdue_terminations(overdue);
+        }
+
+        imp.event_log.add_events(readings.process_changes.events());
+        imp.recent_exits.add_events(readings.process_changes.events());
+    }
+
+    fn update_common(&self, readings: &crate::magpie_client::Readings) -> ChangedStats {
         let imp = self.imp();
+        let mut changed_stats = ChangedStats::default();
 
         let mut buffer = ArrayString::<64>::new();
         let running_apps_len = readings.running_apps.len() as u32;
@@ -287,35 +597,168 @@ impl AppsPage {
 
         imp.table_view.imp().update_column_titles(readings);
 
-        let mut process_model_map = HashMap::new();
+        // The readings are shared with the other windows, the apps are grouped by each of them
+        let mut running_apps = readings.running_apps.clone();
+        if settings!().boolean("apps-page-match-desktop-files") {
+            imp.app_matcher
+                .borrow_mut()
+                .group_processes(&mut running_apps, &readings.running_processes);
+        }
+
+        // App entries reuse the rows of their processes, so those rows have to exist even when
+        // one of their ancestors is collapsed in the process tree
+        let app_ancestors = readings.process_changes.ancestors(
+            running_apps
+                .values()
+                .flat_map(|app| app.pids.iter().copied()),
+        );
+
+        // Taken out while the rows are updated, in case one of them gets expanded meanwhile
+        let table_view = imp.table_view.imp();
+        let mut app_icons = table_view.app_icons.take();
+        let mut process_model_map = table_view.process_rows.take();
+        process_model_map.clear();
         let root_process = readings.running_processes.keys().min().unwrap_or(&1);
         if let Some(init) = readings.running_processes.get(root_process) {
+            // `kthreadd` is not a child of init, its row groups all kernel threads
+            let kernel_threads = readings
+                .running_processes
+                .get(&KERNEL_THREADS_PID)
+                .filter(|process| is_kernel_threads_parent(process))
+                .filter(|process| !init.children.contains(&process.pid));
+            let top_level_pids = init
+                .children
+                .iter()
+                .copied()
+                .chain(kernel_threads.map(|process| process.pid))
+                .collect::<Vec<_>>();
+
             update_processes(
                 &readings.running_processes,
-                init.children.clone().drain(..).collect(),
+                &readings.process_changes,
+                &top_level_pids,
                 &imp.processes_section.children(),
-                &imp.app_icons.borrow(),
+                &app_icons,
                 "application-x-executable-symbolic",
-                imp.table_view.imp().use_merged_stats.get(),
+                table_view.use_merged_stats.get(),
                 SectionType::SecondSection,
                 None,
+                true,
+                &app_ancestors,
                 &mut process_model_map,
+                &mut changed_stats,
             );
         }
         imp.root_process.set(*root_process);
 
+        let pinned = pinned::pinned(SettingsNamespace::AppsPage);
+        let pinned_pids = readings
+            .running_processes
+            .values()
+            .filter(|process| !process.exe.is_empty() && pinned.contains(&process.exe))
+            .map(|process| process.pid)
+            .collect::<Vec<_>>();
+        update_processes(
+            &readings.running_processes,
+            &readings.process_changes,
+            &pinned_pids,
+            &table_view.pinned_section.children(),
+            &app_icons,
+            "application-x-executable-symbolic",
+            table_view.use_merged_stats.get(),
+            SectionType::PinnedSection,
+            None,
+            true,
+            &HashSet::new(),
+            &mut HashMap::new(),
+            &mut changed_stats,
+        );
+
         update_apps(
-            &readings.running_apps,
+            &running_apps,
             &readings.running_processes,
+            &readings.process_changes,
             &process_model_map,
-            &mut imp.app_icons.borrow_mut(),
+            &mut app_icons,
             &imp.apps_section.children(),
+            &mut changed_stats,
         );
+        table_view.app_icons.replace(app_icons);
+        table_view.process_rows.replace(process_model_map);
 
-        let _ = std::mem::replace(
-            &mut *imp.running_apps.borrow_mut(),
-            std::mem::take(&mut readings.running_apps),
+        imp.running_apps.replace(running_apps);
+
+        changed_stats
+    }
+
+    // Processes that ignored SIGTERM are either killed right away, or after asking the user
+    fn handle_overdue_terminations(&self, pids: Vec<u32>) {
+        if settings!().boolean("apps-page-end-task-auto-kill") {
+            self.force_stop_overdue(&pids);
+            return;
+        }
+
+        let dialog = adw::AlertDialog::new(
+            Some(&i18n("Not Responding")),
+            Some(&ni18n_f(
+                "A process did not end within {} seconds. Force stopping it can lose unsaved data.",
+                "Some processes did not end within {} seconds. Force stopping them can lose unsaved data.",
+                pids.len() as u32,
+                &[&settings!()
+                    .uint("apps-page-end-task-grace-period")
+                    .to_string()],
+            )),
         );
+        dialog.add_responses(&[
+            ("wait", &i18n("_Wait")),
+            ("force-stop", &i18n("_Force Stop")),
+        ]);
+        dialog.set_response_appearance("force-stop", adw::ResponseAppearance::Destructive);
+        dialog.set_default_response(Some("wait"));
+        dialog.set_close_response("wait");
+
+        dialog.connect_response(None, {
+            let this = self.downgrade();
+            move |_, response| {
+                let Some(this) = this.upgrade() else {
+                    return;
+                };
+
+                if response == "force-stop" {
+                    this.force_stop_overdue(&pids);
+                } else {
+                    this.imp()
+                        .pending_terminations
+                        .borrow_mut()
+                        .take_overdue(&pids);
+                }
+            }
+        });
+
+        dialog.present(Some(self));
+    }
+
+    // Only the processes still running under the name and start time they had when asked to
+    // end, the PIDs of the others may belong to unrelated processes by now
+    fn force_stop_overdue(&self, pids: &[u32]) {
+        let pids = self
+            .imp()
+            .pending_terminations
+            .borrow_mut()
+            .take_overdue(pids);
+        if pids.is_empty() {
+            return;
+        }
+
+        match app!().sys_info() {
+            Ok(magpie_client) => magpie_client.kill_processes(pids),
+            Err(e) => {
+                g_critical!(
+                    "MissionCenter::AppsPage",
+                    "Failed to get sys_info from MissionCenterApplication: {e}",
+                );
+            }
+        }
     }
 
     #[inline]
@@ -323,6 +766,14 @@ impl AppsPage {
         self.imp().collapse();
     }
 
+    /// Shows the table as a list of names, with their usage below them
+    pub fn set_narrow(&self, narrow: bool) {
+        let imp = self.imp();
+        imp.table_view.set_narrow(narrow);
+        imp.narrow.set(narrow);
+        imp.update_split_view();
+    }
+
     #[inline]
     pub fn expand(&self) {
         self.imp().expand();
@@ -335,4 +786,51 @@ impl AppsPage {
     pub fn activate_table_view_action(&self, name: &str) -> Result<(), glib::error::BoolError> {
         WidgetExt::activate_action(&*self.imp().table_view, name, None)
     }
+
+    pub fn path_filter(&self) -> String {
+        self.imp().table_view.path_filter()
+    }
+
+    /// Runs the process action called `name` on `row_model`, as if it was picked from its menu
+    pub fn execute_action(&self, name: &str, row_model: &RowModel) -> bool {
+        execute_action(PROCESS_ACTIONS, name, &self.imp().table_view, row_model)
+    }
+
+    #[inline]
+    pub fn scroll_to_and_select(&self, id: &str) -> bool {
+        self.imp().table_view.scroll_to_and_select(id)
+    }
+
+    #[inline]
+    pub fn select_search_match(&self, forward: bool) -> bool {
+        self.imp().table_view.select_search_match(forward)
+    }
+
+    #[inline]
+    pub fn search_match_count(&self) -> u32 {
+        self.imp().table_view.search_match_count()
+    }
+
+    #[inline]
+    pub fn zoom(&self, steps: i32) {
+        self.imp().table_view.zoom(steps);
+    }
+
+    #[inline]
+    pub fn reset_zoom(&self) {
+        self.imp().table_view.reset_zoom();
+    }
+
+    #[inline]
+    pub fn copy_visible_rows(&self) {
+        self.imp().table_view.copy_visible_rows();
+    }
+
+    pub fn connect_search_match_count_notify(&self, f: impl Fn(u32) + 'static) {
+        self.imp()
+            .table_view
+            .connect_search_match_count_notify(
+                move |table_view| f(table_view.search_match_count()),
+            );
+    }
 }
@@ -0,0 +1,132 @@
+/* apps_page/pending_terminations.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use magpie_types::processes::Process;
+
+use crate::magpie_client::{can_inspect_host, read_start_time};
+
+// Guards against the PID being reused by an unrelated process before it is force stopped
+struct Identity {
+    name: String,
+    // `None` if `/proc` doesn't describe the processes of the readings
+    start_time: Option<u64>,
+}
+
+impl Identity {
+    fn of(process: &Process) -> Self {
+        Self {
+            name: process.name.clone(),
+            start_time: start_time(process.pid),
+        }
+    }
+}
+
+struct PendingTermination {
+    requested_at: Instant,
+    // Filled in with the first readings after the request
+    identity: Option<Identity>,
+}
+
+/// Keeps track of the processes that were asked to end (SIGTERM), until they either exit or
+/// outlive the grace period, and then until it is decided whether to force stop them
+#[derive(Default)]
+pub struct PendingTerminations {
+    pending: HashMap<u32, PendingTermination>,
+    overdue: HashMap<u32, Identity>,
+}
+
+impl PendingTerminations {
+    pub fn add(&mut self, pids: &[u32]) {
+        let now = Instant::now();
+        for pid in pids {
+            // Asking again does not restart the grace period
+            self.pending.entry(*pid).or_insert(PendingTermination {
+                requested_at: now,
+                identity: None,
+            });
+        }
+    }
+
+    /// Forgets the processes that exited, and returns the ones that are still running after
+    /// `grace_period`. Those are kept until `take_overdue` is called for them.
+    pub fn update(
+        &mut self,
+        processes: &HashMap<u32, Process>,
+        grace_period: Duration,
+    ) -> Vec<u32> {
+        let mut overdue = Vec::new();
+
+        // Waiting on the user doesn't keep a process that exited, or an unrelated one that got
+        // its PID, from being dropped
+        self.overdue.retain(|pid, identity| {
+            processes
+                .get(pid)
+                .is_some_and(|process| process.name == identity.name)
+        });
+
+        self.pending.retain(|pid, pending| {
+            let Some(process) = processes.get(pid) else {
+                return false;
+            };
+            let identity = pending
+                .identity
+                .get_or_insert_with(|| Identity::of(process));
+            if identity.name != process.name {
+                return false;
+            }
+
+            if pending.requested_at.elapsed() >= grace_period {
+                overdue.push(*pid);
+                if let Some(identity) = pending.identity.take() {
+                    self.overdue.insert(*pid, identity);
+                }
+                return false;
+            }
+
+            true
+        });
+
+        overdue
+    }
+
+    /// Those of `pids` that are still the processes that did not end in time, to be force
+    /// stopped. All of them are forgotten.
+    pub fn take_overdue(&mut self, pids: &[u32]) -> Vec<u32> {
+        pids.iter()
+            .copied()
+            .filter(|pid| {
+                self.overdue
+                    .remove(pid)
+                    .is_some_and(|identity| identity.start_time == start_time(*pid))
+            })
+            .collect()
+    }
+}
+
+fn start_time(pid: u32) -> Option<u64> {
+    if !can_inspect_host() {
+        return None;
+    }
+
+    read_start_time(pid)
+}
@@ -0,0 +1,179 @@
+/* apps_page/recent_exits.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use adw::prelude::*;
+use gtk::{glib, subclass::prelude::*};
+
+use crate::i18n::{i18n_f, i18n_k, ni18n_f};
+use crate::magpie_client::{ExitReason, ProcessEvent, ProcessEventKind};
+
+/// Exits are listed for this long
+const KEEP_FOR: Duration = Duration::from_secs(15 * 60);
+
+mod imp {
+    use super::*;
+
+    #[derive(gtk::CompositeTemplate, Default)]
+    #[template(resource = "/io/missioncenter/MissionCenter/ui/apps_page/recent_exits.ui")]
+    pub struct RecentExits {
+        #[template_child]
+        pub expander: TemplateChild<gtk::Expander>,
+        #[template_child]
+        pub heading: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub exit_list: TemplateChild<gtk::ListBox>,
+
+        // Newest first, like the rows of the list
+        pub exits: RefCell<VecDeque<(Instant, adw::ActionRow)>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for RecentExits {
+        const NAME: &'static str = "RecentExits";
+        type Type = super::RecentExits;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for RecentExits {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().set_visible(false);
+        }
+    }
+
+    impl WidgetImpl for RecentExits {}
+
+    impl BoxImpl for RecentExits {}
+}
+
+glib::wrapper! {
+    pub struct RecentExits(ObjectSubclass<imp::RecentExits>)
+        @extends gtk::Box, gtk::Widget,
+        @implements gtk::Orientable, gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl RecentExits {
+    pub fn expander(&self) -> gtk::Expander {
+        self.imp().expander.get()
+    }
+
+    /// Lists the processes among `events` that crashed or exited with an error, and drops the
+    /// ones that exited too long ago. Only shown while there are any.
+    pub fn add_events(&self, events: &[ProcessEvent]) {
+        let imp = self.imp();
+        let mut exits = imp.exits.borrow_mut();
+
+        let now = Instant::now();
+        while exits
+            .back()
+            .is_some_and(|(time, _)| now.duration_since(*time) > KEEP_FOR)
+        {
+            if let Some((_, row)) = exits.pop_back() {
+                imp.exit_list.remove(&row);
+            }
+        }
+
+        let time = glib::DateTime::now_local()
+            .and_then(|now| now.format("%T"))
+            .unwrap_or_default();
+        for event in events {
+            let Some(reason) = event
+                .exit_reason
+                .filter(|reason| event.kind == ProcessEventKind::Exited && reason.is_failure())
+            else {
+                continue;
+            };
+
+            let pid = event.pid.to_string();
+            let row = adw::ActionRow::builder()
+                .title(i18n_k(
+                    "{name} (PID {pid})",
+                    &[("name", event.name.as_str()), ("pid", pid.as_str())],
+                ))
+                .subtitle(exit_reason_text(reason))
+                .build();
+            row.add_suffix(
+                &gtk::Label::builder()
+                    .label(time.as_str())
+                    .css_classes(["dim-label", "numeric"])
+                    .build(),
+            );
+
+            imp.exit_list.prepend(&row);
+            exits.push_front((now, row));
+        }
+
+        let count = exits.len() as u32;
+        imp.heading.set_label(&ni18n_f(
+            "{} Process Exited Abnormally in the Last 15 Minutes",
+            "{} Processes Exited Abnormally in the Last 15 Minutes",
+            count,
+            &[&count.to_string()],
+        ));
+        self.set_visible(count > 0);
+    }
+}
+
+/// Describes how a process ended, crashes are called out as such
+pub fn exit_reason_text(reason: ExitReason) -> String {
+    match reason {
+        ExitReason::Status(status) => i18n_f("Exited with status {}", &[&status.to_string()]),
+        ExitReason::Signal {
+            signal,
+            core_dumped,
+        } => {
+            let name = signal_name(signal);
+            match signal {
+                // Illegal instructions, traps, aborts, bus errors, arithmetic errors and
+                // segmentation faults
+                4 | 5 | 6 | 7 | 8 | 11 if core_dumped => {
+                    i18n_f("Crashed with {}, core dumped", &[&name])
+                }
+                4 | 5 | 6 | 7 | 8 | 11 => i18n_f("Crashed with {}", &[&name]),
+                _ => i18n_f("Killed by {}", &[&name]),
+            }
+        }
+    }
+}
+
+fn signal_name(signal: i32) -> String {
+    const NAMES: &[&str] = &[
+        "SIGHUP", "SIGINT", "SIGQUIT", "SIGILL", "SIGTRAP", "SIGABRT", "SIGBUS", "SIGFPE",
+        "SIGKILL", "SIGUSR1", "SIGSEGV", "SIGUSR2", "SIGPIPE", "SIGALRM", "SIGTERM",
+    ];
+
+    usize::try_from(signal - 1)
+        .ok()
+        .and_then(|index| NAMES.get(index))
+        .map(|name| (*name).to_owned())
+        .unwrap_or_else(|| i18n_f("signal {}", &[&signal.to_string()]))
+}
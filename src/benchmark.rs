@@ -0,0 +1,182 @@
+/* benchmark.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use gtk::glib::{self, g_message};
+use gtk::prelude::*;
+
+use crate::magpie_client::Process;
+
+// Far above the PIDs the kernel hands out by default, so that fake processes never replace
+// real ones
+const FIRST_PID: u32 = 1 << 30;
+// Every group of fake processes is a parent with its children, so that the process tree is
+// exercised as well as the flat lists
+const GROUP_SIZE: usize = 8;
+const REPORT_INTERVAL: Duration = Duration::from_secs(5);
+// A frame at 60 Hz
+const FRAME_BUDGET: Duration = Duration::from_micros(16_667);
+
+static PROCESS_COUNT: AtomicU32 = AtomicU32::new(0);
+static CHURN_PERCENT: AtomicU32 = AtomicU32::new(0);
+static ROW_MODELS_REUSED: AtomicU32 = AtomicU32::new(0);
+static ROW_MODELS_BUILT: AtomicU32 = AtomicU32::new(0);
+
+/// A developer mode that adds `process_count` fake processes to every reading, `churn_percent`
+/// of which exit and are replaced by new ones from one reading to the next. Along with the frame
+/// times it logs, the cost of updating the tables can be measured the same way on every machine.
+pub fn enable(process_count: u32, churn_percent: u32) {
+    PROCESS_COUNT.store(process_count, Ordering::Relaxed);
+    CHURN_PERCENT.store(churn_percent.min(100), Ordering::Relaxed);
+
+    g_message!(
+        "MissionCenter::Benchmark",
+        "Adding {} synthetic processes, {}% of them replaced with every reading",
+        process_count,
+        churn_percent.min(100)
+    );
+}
+
+pub fn is_enabled() -> bool {
+    PROCESS_COUNT.load(Ordering::Relaxed) > 0
+}
+
+/// The fake processes, kept from one reading to the next on the thread that gathers them
+pub struct SyntheticLoad {
+    pids: Vec<u32>,
+    next_pid: u32,
+}
+
+impl SyntheticLoad {
+    /// `None` unless the benchmark mode is enabled
+    pub fn new() -> Option<Self> {
+        is_enabled().then(|| Self {
+            pids: vec![],
+            next_pid: FIRST_PID,
+        })
+    }
+
+    /// Adds the fake processes to the reading, as descendants of its first process
+    pub fn add_to(&mut self, processes: &mut HashMap<u32, Process>) {
+        let Some(root) = processes.keys().min().copied() else {
+            return;
+        };
+
+        let count = PROCESS_COUNT.load(Ordering::Relaxed) as usize;
+        let churn = self.pids.len() * CHURN_PERCENT.load(Ordering::Relaxed) as usize / 100;
+        for _ in 0..churn {
+            let index = rand::random_range(0..self.pids.len());
+            self.pids[index] = self.next_pid();
+        }
+        while self.pids.len() < count {
+            let pid = self.next_pid();
+            self.pids.push(pid);
+        }
+
+        for (index, pid) in self.pids.iter().copied().enumerate() {
+            let parent = match index % GROUP_SIZE {
+                0 => root,
+                offset => self.pids[index - offset],
+            };
+
+            let mut process = Process::default();
+            process.pid = pid;
+            process.name = format!("synthetic-{}", index % GROUP_SIZE);
+            process.cmd = vec![format!("synthetic-{}", index % GROUP_SIZE), pid.to_string()];
+            process.usage_stats.cpu_usage = rand::random_range(0.0..5.0);
+            process.usage_stats.memory_usage = rand::random_range((1 << 20)..(256 << 20));
+            process.usage_stats.network_usage = rand::random_range(0.0..(1 << 16) as f32);
+            processes.insert(pid, process);
+
+            if let Some(parent) = processes.get_mut(&parent) {
+                parent.children.push(pid);
+            }
+        }
+    }
+
+    fn next_pid(&mut self) -> u32 {
+        let pid = self.next_pid;
+        self.next_pid = self.next_pid.checked_add(1).unwrap_or(FIRST_PID);
+        pid
+    }
+}
+
+/// Counts whether a new row of the process tables was taken from the pool of recycled rows,
+/// which is reported along with the frame times
+pub fn count_row_model_reuse(reused: bool) {
+    if !is_enabled() {
+        return;
+    }
+
+    if reused {
+        ROW_MODELS_REUSED.fetch_add(1, Ordering::Relaxed);
+    } else {
+        ROW_MODELS_BUILT.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Logs how long the frames of the widget took, every few seconds while the benchmark mode is
+/// enabled. Frames are drawn continuously meanwhile, so a stall shows up as a slow frame.
+pub fn log_frame_times(widget: &impl IsA<gtk::Widget>) {
+    if !is_enabled() {
+        return;
+    }
+
+    let mut previous_frame = None;
+    let mut report_start = 0;
+    let mut frames = vec![];
+    widget.add_tick_callback(move |_, frame_clock| {
+        // In microseconds
+        let frame_time = frame_clock.frame_time();
+        if let Some(previous_frame) = previous_frame.replace(frame_time) {
+            frames.push(Duration::from_micros((frame_time - previous_frame).max(0) as u64));
+        } else {
+            report_start = frame_time;
+        }
+
+        if frame_time - report_start >= REPORT_INTERVAL.as_micros() as i64 && !frames.is_empty() {
+            let total = frames.iter().sum::<Duration>();
+            let slowest = frames.iter().max().copied().unwrap_or_default();
+            let over_budget = frames.iter().filter(|frame| **frame > FRAME_BUDGET).count();
+            g_message!(
+                "MissionCenter::Benchmark",
+                "{} frames, average {:.1} ms, slowest {:.1} ms, {} over budget",
+                frames.len(),
+                total.as_secs_f64() * 1000. / frames.len() as f64,
+                slowest.as_secs_f64() * 1000.,
+                over_budget
+            );
+            g_message!(
+                "MissionCenter::Benchmark",
+                "{} rows reused from the pool, {} built",
+                ROW_MODELS_REUSED.swap(0, Ordering::Relaxed),
+                ROW_MODELS_BUILT.swap(0, Ordering::Relaxed)
+            );
+
+            frames.clear();
+            report_start = frame_time;
+        }
+
+        glib::ControlFlow::Continue
+    });
+}
@@ -0,0 +1,322 @@
+/* command_line.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+use gtk::gio;
+use gtk::glib;
+use gtk::prelude::*;
+
+use crate::application::{MissionCenterApplication, BASE_INTERVAL};
+use crate::i18n::{i18n, i18n_f};
+use crate::magpie_client::{Client, LocalTransport, Process};
+
+const OPTION_LIST_PROCESSES: &str = "list-processes";
+const OPTION_SORT: &str = "sort";
+const OPTION_TOP: &str = "top";
+const OPTION_KILL: &str = "kill";
+const OPTION_FORCE: &str = "force";
+const OPTION_SYNTHETIC_PROCS: &str = "synthetic-procs";
+const OPTION_SYNTHETIC_CHURN: &str = "synthetic-churn";
+
+// Percentage of the synthetic processes replaced with every reading, unless told otherwise
+const DEFAULT_SYNTHETIC_CHURN: i32 = 10;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum SortKey {
+    Cpu,
+    Memory,
+    Pid,
+    Name,
+}
+
+impl SortKey {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "cpu" => Some(SortKey::Cpu),
+            "memory" => Some(SortKey::Memory),
+            "pid" => Some(SortKey::Pid),
+            "name" => Some(SortKey::Name),
+            _ => None,
+        }
+    }
+}
+
+pub fn add_main_options(app: &MissionCenterApplication) {
+    let no_short_name = glib::Char::from(b'\0');
+
+    app.add_main_option(
+        OPTION_LIST_PROCESSES,
+        no_short_name,
+        glib::OptionFlags::NONE,
+        glib::OptionArg::None,
+        &i18n("Print the running processes and exit"),
+        None,
+    );
+    app.add_main_option(
+        OPTION_SORT,
+        no_short_name,
+        glib::OptionFlags::NONE,
+        glib::OptionArg::String,
+        &i18n("Order of the listed processes: cpu, memory, pid or name"),
+        Some("KEY"),
+    );
+    app.add_main_option(
+        OPTION_TOP,
+        no_short_name,
+        glib::OptionFlags::NONE,
+        glib::OptionArg::Int,
+        &i18n("Only list the first COUNT processes"),
+        Some("COUNT"),
+    );
+    app.add_main_option(
+        OPTION_KILL,
+        no_short_name,
+        glib::OptionFlags::NONE,
+        glib::OptionArg::Int,
+        &i18n("Ask the process with the given PID to exit"),
+        Some("PID"),
+    );
+    app.add_main_option(
+        OPTION_FORCE,
+        no_short_name,
+        glib::OptionFlags::NONE,
+        glib::OptionArg::None,
+        &i18n("With --kill, kill the process instead of asking it to exit"),
+        None,
+    );
+
+    // Developer options, to measure how the views cope with many processes
+    app.add_main_option(
+        OPTION_SYNTHETIC_PROCS,
+        no_short_name,
+        glib::OptionFlags::HIDDEN,
+        glib::OptionArg::Int,
+        "Add COUNT fake processes to every reading and log frame times",
+        Some("COUNT"),
+    );
+    app.add_main_option(
+        OPTION_SYNTHETIC_CHURN,
+        no_short_name,
+        glib::OptionFlags::HIDDEN,
+        glib::OptionArg::Int,
+        "With --synthetic-procs, replace PERCENT of the fake processes with every reading",
+        Some("PERCENT"),
+    );
+}
+
+/// Handles the options that are answered without showing a window. Returns `None` when there
+/// were none, and the window should be shown as usual.
+pub fn handle(
+    app: &MissionCenterApplication,
+    command_line: &gio::ApplicationCommandLine,
+) -> Option<glib::ExitCode> {
+    let options = command_line.options_dict();
+
+    if let Some(count) = options.lookup::<i32>(OPTION_SYNTHETIC_PROCS).ok().flatten() {
+        let churn = options
+            .lookup::<i32>(OPTION_SYNTHETIC_CHURN)
+            .ok()
+            .flatten()
+            .unwrap_or(DEFAULT_SYNTHETIC_CHURN);
+        crate::benchmark::enable(count.max(0) as u32, churn.clamp(0, 100) as u32);
+    }
+
+    let list_processes = options.contains(OPTION_LIST_PROCESSES);
+    let kill = options.lookup::<i32>(OPTION_KILL).ok().flatten();
+    if !list_processes && kill.is_none() {
+        return None;
+    }
+
+    let sort_key = match options.lookup::<String>(OPTION_SORT).ok().flatten() {
+        Some(name) => match SortKey::from_name(&name) {
+            Some(sort_key) => sort_key,
+            None => {
+                command_line
+                    .printerr_literal(&format!("{}\n", i18n_f("Unknown sort key: {}", &[&name])));
+                return Some(glib::ExitCode::FAILURE);
+            }
+        },
+        None => SortKey::Cpu,
+    };
+    let top = options
+        .lookup::<i32>(OPTION_TOP)
+        .ok()
+        .flatten()
+        .map(|top| top.max(0) as usize);
+
+    let Some(source) = ProcessSource::new(app) else {
+        command_line.printerr_literal(&format!(
+            "{}\n",
+            i18n("Every window shows a snapshot, close one of them to see the running processes")
+        ));
+        return Some(glib::ExitCode::FAILURE);
+    };
+
+    if let Some(pid) = kill {
+        let pid = pid.max(0) as u32;
+        if !source.processes().contains_key(&pid) {
+            command_line.printerr_literal(&format!(
+                "{}\n",
+                i18n_f("No process with PID {}", &[&pid.to_string()])
+            ));
+            return Some(glib::ExitCode::FAILURE);
+        }
+
+        source.signal(pid, options.contains(OPTION_FORCE));
+    }
+
+    if list_processes {
+        command_line.print_literal(&format_processes(source.processes(), sort_key, top));
+    }
+
+    Some(glib::ExitCode::SUCCESS)
+}
+
+/// Where the processes come from: the running instance when there is one, since it is already
+/// gathering data, or else a Magpie instance started just for this invocation. The processes of
+/// a snapshot are never used, their PIDs can belong to other processes by now.
+enum ProcessSource {
+    Running {
+        app: MissionCenterApplication,
+        processes: HashMap<u32, Process>,
+    },
+    Standalone {
+        client: Client,
+        processes: HashMap<u32, Process>,
+    },
+}
+
+impl ProcessSource {
+    /// `None` if the running instance only shows snapshots
+    fn new(app: &MissionCenterApplication) -> Option<Self> {
+        if app.window().is_some() {
+            let window = app.live_window()?;
+            return Some(ProcessSource::Running {
+                app: app.clone(),
+                processes: window.running_processes().clone(),
+            });
+        }
+
+        let client = Client::new(Box::new(LocalTransport::new()));
+        client.start();
+        // Sets the core count the process usage is scaled by
+        let _ = client.cpu();
+
+        // CPU usage is worked out from the difference between two readings
+        let _ = client.processes();
+        std::thread::sleep(Duration::from_secs_f64(BASE_INTERVAL));
+        let (processes, _) = client.processes();
+
+        Some(ProcessSource::Standalone { client, processes })
+    }
+
+    fn processes(&self) -> &HashMap<u32, Process> {
+        match self {
+            ProcessSource::Running { processes, .. } => processes,
+            ProcessSource::Standalone { processes, .. } => processes,
+        }
+    }
+
+    fn signal(&self, pid: u32, force: bool) {
+        match self {
+            ProcessSource::Running { app, .. } => {
+                let Ok(magpie) = app.sys_info() else {
+                    return;
+                };
+                if force {
+                    magpie.kill_process(pid);
+                } else {
+                    magpie.terminate_process(pid);
+                }
+            }
+            ProcessSource::Standalone { client, .. } => {
+                if force {
+                    client.kill_processes(vec![pid]);
+                } else {
+                    client.terminate_processes(vec![pid]);
+                }
+            }
+        }
+    }
+}
+
+/// Lays out the processes as a plain text table, meant to be easy to read and to parse
+fn format_processes(
+    processes: &HashMap<u32, Process>,
+    sort_key: SortKey,
+    top: Option<usize>,
+) -> String {
+    let mut processes = processes.values().collect::<Vec<_>>();
+    processes.sort_unstable_by(|a, b| {
+        match sort_key {
+            SortKey::Cpu => b.usage_stats.cpu_usage.total_cmp(&a.usage_stats.cpu_usage),
+            SortKey::Memory => b.usage_stats.memory_usage.cmp(&a.usage_stats.memory_usage),
+            SortKey::Pid => a.pid.cmp(&b.pid),
+            SortKey::Name => a.name.cmp(&b.name),
+        }
+        .then(a.pid.cmp(&b.pid))
+    });
+    if let Some(top) = top {
+        processes.truncate(top);
+    }
+
+    let mut result = String::new();
+    let _ = writeln!(
+        result,
+        "{:>8}  {:>6}  {:>10}  {}",
+        i18n("PID"),
+        i18n("CPU"),
+        i18n("Memory"),
+        i18n("Name")
+    );
+    for process in processes {
+        let _ = writeln!(
+            result,
+            "{:>8}  {:>5.1}%  {:>10}  {}",
+            process.pid,
+            process.usage_stats.cpu_usage,
+            format_memory(process.usage_stats.memory_usage),
+            process.name
+        );
+    }
+
+    result
+}
+
+// The units the views use depend on the preferences, which should not change what scripts get
+fn format_memory(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024. && unit < UNITS.len() - 1 {
+        value /= 1024.;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
@@ -0,0 +1,220 @@
+/* dbus_service.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use gtk::gio;
+use gtk::glib::{self, g_critical, g_debug};
+use gtk::prelude::*;
+
+use crate::app;
+
+const INTERFACE_NAME: &str = "org.missioncenter.TaskManager";
+
+const INTERFACE_XML: &str = r#"
+<node>
+  <interface name="org.missioncenter.TaskManager">
+    <!-- PID, name, executable, CPU usage in percent, memory usage in bytes -->
+    <method name="ListProcesses">
+      <arg name="processes" type="a(ussdt)" direction="out"/>
+    </method>
+    <!-- Asks the process to exit, or kills it outright when `force` is set -->
+    <method name="KillProcess">
+      <arg name="pid" type="u" direction="in"/>
+      <arg name="force" type="b" direction="in"/>
+    </method>
+    <!-- Accepts the name of the service with or without the `.service` suffix. Like on the
+         Services page, only running services can be restarted. -->
+    <method name="RestartService">
+      <arg name="name" type="s" direction="in"/>
+    </method>
+    <!-- System totals as of the last update -->
+    <method name="GetReadingsSnapshot">
+      <arg name="snapshot" type="a{sv}" direction="out"/>
+    </method>
+  </interface>
+</node>
+"#;
+
+const ERROR_NOT_READY: &str = "org.missioncenter.TaskManager.Error.NotReady";
+const ERROR_NOT_FOUND: &str = "org.missioncenter.TaskManager.Error.NotFound";
+const ERROR_NOT_APPLICABLE: &str = "org.missioncenter.TaskManager.Error.NotApplicable";
+
+/// Exposes what the Apps and Services pages can do on the session bus, next to the application
+/// object. Method calls are answered on the main thread, from the last readings, and run the same
+/// actions as the menus of the rows.
+pub struct DBusService {
+    connection: gio::DBusConnection,
+    registration_id: gio::RegistrationId,
+}
+
+impl DBusService {
+    pub fn register(
+        connection: &gio::DBusConnection,
+        object_path: &str,
+    ) -> Result<Self, glib::Error> {
+        let node_info = gio::DBusNodeInfo::for_xml(INTERFACE_XML)?;
+        let Some(interface_info) = node_info.lookup_interface(INTERFACE_NAME) else {
+            return Err(glib::Error::new(
+                gio::IOErrorEnum::InvalidData,
+                "Missing interface in D-Bus introspection data",
+            ));
+        };
+
+        let registration_id = connection
+            .register_object(object_path, &interface_info)
+            .method_call(
+                |_connection,
+                 sender,
+                 _object_path,
+                 _interface_name,
+                 method_name,
+                 parameters,
+                 invocation| {
+                    g_debug!(
+                        "MissionCenter::DBusService",
+                        "{} called by {}",
+                        method_name,
+                        sender.unwrap_or("unknown sender")
+                    );
+                    handle_method_call(method_name, parameters, invocation);
+                },
+            )
+            .build()?;
+
+        Ok(Self {
+            connection: connection.clone(),
+            registration_id,
+        })
+    }
+
+    pub fn unregister(self) {
+        if let Err(e) = self.connection.unregister_object(self.registration_id) {
+            g_critical!(
+                "MissionCenter::DBusService",
+                "Failed to unregister D-Bus object: {}",
+                e
+            );
+        }
+    }
+}
+
+fn handle_method_call(
+    method_name: &str,
+    parameters: glib::Variant,
+    invocation: gio::DBusMethodInvocation,
+) {
+    let app = app!();
+    // Calls can come in before the first window is shown, or while it is being closed
+    if app.window().is_none() {
+        invocation.return_dbus_error(ERROR_NOT_READY, "Mission Center is not running");
+        return;
+    }
+    // The readings of a snapshot replace the live ones in its window, neither the PIDs nor the
+    // services in them need to exist on this system
+    let Some(window) = app.live_window() else {
+        invocation.return_dbus_error(ERROR_NOT_READY, "Mission Center is showing a snapshot");
+        return;
+    };
+
+    match method_name {
+        "ListProcesses" => {
+            let mut processes = window
+                .running_processes()
+                .values()
+                .map(|process| {
+                    (
+                        process.pid,
+                        process.name.clone(),
+                        process.exe.clone(),
+                        process.usage_stats.cpu_usage as f64,
+                        process.usage_stats.memory_usage,
+                    )
+                })
+                .collect::<Vec<_>>();
+            processes.sort_unstable_by_key(|process| process.0);
+
+            invocation.return_value(Some(&(processes,).to_variant()));
+        }
+        "KillProcess" => {
+            let Some((pid, force)) = parameters.get::<(u32, bool)>() else {
+                invocation.return_dbus_error(
+                    "org.freedesktop.DBus.Error.InvalidArgs",
+                    "Expected a PID and a boolean",
+                );
+                return;
+            };
+
+            let action = if force { "force-stop" } else { "stop" };
+            match window.execute_process_action(action, pid) {
+                Some(true) => invocation.return_value(None),
+                Some(false) => invocation.return_dbus_error(
+                    ERROR_NOT_APPLICABLE,
+                    &format!("The process with PID {pid} can't be stopped"),
+                ),
+                None => invocation
+                    .return_dbus_error(ERROR_NOT_FOUND, &format!("No process with PID {pid}")),
+            }
+        }
+        "RestartService" => {
+            let Some((name,)) = parameters.get::<(String,)>() else {
+                invocation.return_dbus_error(
+                    "org.freedesktop.DBus.Error.InvalidArgs",
+                    "Expected a service name",
+                );
+                return;
+            };
+
+            let Some(service_id) = window.service_id(&name) else {
+                invocation.return_dbus_error(ERROR_NOT_FOUND, &format!("No service named {name}"));
+                return;
+            };
+
+            match window.execute_service_action("restart", service_id) {
+                Some(true) => invocation.return_value(None),
+                Some(false) => invocation.return_dbus_error(
+                    ERROR_NOT_APPLICABLE,
+                    &format!("The service {name} is not running, or is already changing"),
+                ),
+                None => invocation
+                    .return_dbus_error(ERROR_NOT_FOUND, &format!("No service named {name}")),
+            }
+        }
+        "GetReadingsSnapshot" => {
+            let snapshot = window.system_snapshot();
+
+            let dict = glib::VariantDict::new(None);
+            dict.insert("cpu_usage_percent", snapshot.cpu_usage as f64);
+            dict.insert("core_count", snapshot.core_count as u32);
+            dict.insert("memory_used_bytes", snapshot.memory_used);
+            dict.insert("memory_total_bytes", snapshot.memory_total);
+            dict.insert("swap_used_bytes", snapshot.swap_used);
+            dict.insert("swap_total_bytes", snapshot.swap_total);
+            dict.insert("process_count", window.running_processes().len() as u32);
+            dict.insert_value("failed_services", &snapshot.failed_services.to_variant());
+
+            invocation.return_value(Some(&glib::Variant::tuple_from_iter([dict.end()])));
+        }
+        _ => {
+            invocation.return_dbus_error(
+                "org.freedesktop.DBus.Error.UnknownMethod",
+                &format!("Unknown method {method_name}"),
+            );
+        }
+    }
+}
@@ -0,0 +1,425 @@
+/* diagnostic_report.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use gtk::glib;
+use magpie_types::about::About;
+use magpie_types::processes::Process;
+
+use crate::config::VERSION;
+use crate::i18n::i18n;
+use crate::magpie_client::Readings;
+use crate::DataType;
+
+const TOP_PROCESS_COUNT: usize = 20;
+
+/// The parts of the readings that go into a report, besides the processes which the window keeps
+/// around anyway
+#[derive(Debug, Default)]
+pub struct SystemSnapshot {
+    pub cpu_usage: f32,
+    pub core_count: usize,
+    pub memory_used: u64,
+    pub memory_total: u64,
+    pub swap_used: u64,
+    pub swap_total: u64,
+    // Name, busy percentage
+    disks: Vec<(String, f32)>,
+    // Name, sent and received bytes per second
+    network: Vec<(String, f32, f32)>,
+    // Name, utilization percentage
+    gpus: Vec<(String, Option<f32>)>,
+    pub failed_services: Vec<String>,
+}
+
+impl SystemSnapshot {
+    pub fn new(readings: &Readings) -> Self {
+        let mem_info = &readings.mem_info;
+        let mem_available = if mem_info.mem_available > mem_info.mem_total {
+            mem_info.mem_free
+        } else {
+            mem_info.mem_available
+        };
+
+        let mut gpus = readings
+            .gpus
+            .values()
+            .map(|gpu| {
+                (
+                    gpu.device_name.clone().unwrap_or_else(|| gpu.id.clone()),
+                    gpu.utilization_percent,
+                )
+            })
+            .collect::<Vec<_>>();
+        gpus.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut failed_services = readings
+            .system_services
+            .values()
+            .chain(readings.user_services.values())
+            .filter(|service| service.failed)
+            .map(|service| service.name.clone())
+            .collect::<Vec<_>>();
+        failed_services.sort();
+        failed_services.dedup();
+
+        Self {
+            cpu_usage: readings.cpu.total_usage_percent,
+            core_count: readings.cpu.core_usage_percent.len(),
+            memory_used: mem_info.mem_total.saturating_sub(mem_available),
+            memory_total: mem_info.mem_total,
+            swap_used: mem_info.swap_total.saturating_sub(mem_info.swap_free),
+            swap_total: mem_info.swap_total,
+            disks: readings
+                .disks_info
+                .iter()
+                .map(|disk| {
+                    let name = match disk.model.as_ref() {
+                        Some(model) => format!("{} ({})", disk.id, model),
+                        None => disk.id.clone(),
+                    };
+                    (name, disk.busy_percent)
+                })
+                .collect(),
+            network: readings
+                .network_connections
+                .iter()
+                .map(|connection| {
+                    (
+                        connection
+                            .device_name
+                            .clone()
+                            .unwrap_or_else(|| connection.id.clone()),
+                        connection.tx_rate_bytes_ps as f32,
+                        connection.rx_rate_bytes_ps as f32,
+                    )
+                })
+                .collect(),
+            gpus,
+            failed_services,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "md",
+            ReportFormat::Html => "html",
+        }
+    }
+}
+
+struct Section {
+    title: String,
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+/// Puts together a report to attach to support requests, from the system information and the
+/// last readings
+pub fn generate(
+    format: ReportFormat,
+    about: &About,
+    snapshot: &SystemSnapshot,
+    processes: &HashMap<u32, Process>,
+) -> String {
+    let sections = [
+        system_section(about),
+        usage_section(snapshot),
+        devices_section(snapshot),
+        top_processes_section(i18n("Top Processes by CPU"), processes, |a, b| {
+            b.usage_stats.cpu_usage.total_cmp(&a.usage_stats.cpu_usage)
+        }),
+        top_processes_section(i18n("Top Processes by Memory"), processes, |a, b| {
+            b.usage_stats.memory_usage.cmp(&a.usage_stats.memory_usage)
+        }),
+        failed_services_section(snapshot),
+    ];
+
+    let title = i18n("Mission Center Diagnostic Report");
+    let generated = glib::DateTime::now_local()
+        .and_then(|now| now.format("%F %T"))
+        .map(|now| now.to_string())
+        .unwrap_or_default();
+
+    match format {
+        ReportFormat::Markdown => to_markdown(&title, &generated, &sections),
+        ReportFormat::Html => to_html(&title, &generated, &sections),
+    }
+}
+
+fn system_section(about: &About) -> Section {
+    let os_info = &about.os_info;
+    let de_info = &about.de_info;
+
+    let kernel = match (&os_info.os_type, &os_info.kernel_release) {
+        (Some(kernel), Some(release)) => Some(format!("{kernel} {release}")),
+        (None, release) => release.clone(),
+        (kernel, None) => kernel.clone(),
+    };
+
+    let fields = [
+        (i18n("Mission Center"), Some(VERSION.to_string())),
+        (
+            i18n("OS Name"),
+            os_info.pretty_name.clone().or(os_info.name.clone()),
+        ),
+        (
+            i18n("OS Version"),
+            os_info.version_id.clone().or(os_info.version.clone()),
+        ),
+        (i18n("Kernel"), kernel),
+        (i18n("Kernel Version"), os_info.kernel_version.clone()),
+        (
+            i18n("Package Manager"),
+            match (&os_info.package_manager, &os_info.package_manager_version) {
+                (Some(name), Some(version)) => Some(format!("{name} {version}")),
+                (name, _) => name.clone(),
+            },
+        ),
+        (
+            i18n("Desktop Environment"),
+            match (&de_info.desktop_environment, &de_info.version) {
+                (Some(name), Some(version)) => Some(format!("{name} {version}")),
+                (name, _) => name.clone(),
+            },
+        ),
+        (i18n("Windowing System"), de_info.windowing_system.clone()),
+        (i18n("Flatpak"), Some(crate::is_flatpak().to_string())),
+    ];
+
+    Section {
+        title: i18n("System"),
+        headers: vec![i18n("Property"), i18n("Value")],
+        rows: fields
+            .into_iter()
+            .map(|(name, value)| vec![name, value.unwrap_or_else(|| i18n("Unknown"))])
+            .collect(),
+    }
+}
+
+fn usage_section(snapshot: &SystemSnapshot) -> Section {
+    let bytes = |value: u64| crate::to_human_readable_nice(value as f32, &DataType::MemoryBytes);
+    let percent = |used: u64, total: u64| {
+        if total == 0 {
+            0.
+        } else {
+            used as f32 * 100. / total as f32
+        }
+    };
+
+    let mut rows = vec![
+        vec![
+            i18n("CPU"),
+            format!("{:.1}%", snapshot.cpu_usage),
+            snapshot.core_count.to_string(),
+        ],
+        vec![
+            i18n("Memory"),
+            format!(
+                "{:.1}%",
+                percent(snapshot.memory_used, snapshot.memory_total)
+            ),
+            format!(
+                "{} / {}",
+                bytes(snapshot.memory_used),
+                bytes(snapshot.memory_total)
+            ),
+        ],
+    ];
+    if snapshot.swap_total > 0 {
+        rows.push(vec![
+            i18n("Swap"),
+            format!("{:.1}%", percent(snapshot.swap_used, snapshot.swap_total)),
+            format!(
+                "{} / {}",
+                bytes(snapshot.swap_used),
+                bytes(snapshot.swap_total)
+            ),
+        ]);
+    }
+
+    Section {
+        title: i18n("Usage"),
+        headers: vec![i18n("Resource"), i18n("Usage"), i18n("Details")],
+        rows,
+    }
+}
+
+fn devices_section(snapshot: &SystemSnapshot) -> Section {
+    let rate = |value: f32| crate::to_human_readable_nice(value, &DataType::NetworkBytesPerSecond);
+
+    let disks = snapshot.disks.iter().map(|(name, busy)| {
+        vec![
+            i18n("Drive"),
+            name.clone(),
+            format!("{:.1}% {}", busy, i18n("busy")),
+        ]
+    });
+    let network = snapshot.network.iter().map(|(name, sent, received)| {
+        vec![
+            i18n("Network"),
+            name.clone(),
+            format!("↑ {} ↓ {}", rate(*sent), rate(*received)),
+        ]
+    });
+    let gpus = snapshot.gpus.iter().map(|(name, utilization)| {
+        vec![
+            i18n("GPU"),
+            name.clone(),
+            utilization
+                .map(|utilization| format!("{utilization:.1}%"))
+                .unwrap_or_else(|| i18n("Unknown")),
+        ]
+    });
+
+    Section {
+        title: i18n("Devices"),
+        headers: vec![i18n("Type"), i18n("Name"), i18n("Usage")],
+        rows: disks.chain(network).chain(gpus).collect(),
+    }
+}
+
+fn top_processes_section(
+    title: String,
+    processes: &HashMap<u32, Process>,
+    compare: impl Fn(&Process, &Process) -> std::cmp::Ordering,
+) -> Section {
+    let mut processes = processes.values().collect::<Vec<_>>();
+    processes.sort_unstable_by(|a, b| compare(a, b).then(a.pid.cmp(&b.pid)));
+
+    Section {
+        title,
+        headers: vec![i18n("PID"), i18n("Name"), i18n("CPU"), i18n("Memory")],
+        rows: processes
+            .into_iter()
+            .take(TOP_PROCESS_COUNT)
+            .map(|process| {
+                vec![
+                    process.pid.to_string(),
+                    process.name.clone(),
+                    format!("{:.1}%", process.usage_stats.cpu_usage),
+                    crate::to_human_readable_nice(
+                        process.usage_stats.memory_usage as f32,
+                        &DataType::MemoryBytes,
+                    ),
+                ]
+            })
+            .collect(),
+    }
+}
+
+fn failed_services_section(snapshot: &SystemSnapshot) -> Section {
+    Section {
+        title: i18n("Failed Services"),
+        headers: vec![i18n("Name")],
+        rows: snapshot
+            .failed_services
+            .iter()
+            .map(|name| vec![name.clone()])
+            .collect(),
+    }
+}
+
+fn to_markdown(title: &str, generated: &str, sections: &[Section]) -> String {
+    // Table cells can't contain pipes or line breaks
+    let escape = |text: &str| text.replace('|', "\\|").replace('\n', " ");
+
+    let mut result = String::new();
+    let _ = writeln!(result, "# {title}\n\n{generated}");
+
+    for section in sections {
+        let _ = write!(result, "\n## {}\n\n", section.title);
+        if section.rows.is_empty() {
+            let _ = writeln!(result, "{}", i18n("None"));
+            continue;
+        }
+
+        let _ = writeln!(
+            result,
+            "| {} |",
+            section
+                .headers
+                .iter()
+                .map(|header| escape(header))
+                .collect::<Vec<_>>()
+                .join(" | ")
+        );
+        let _ = writeln!(result, "|{}", " --- |".repeat(section.headers.len()));
+        for row in &section.rows {
+            let _ = writeln!(
+                result,
+                "| {} |",
+                row.iter()
+                    .map(|cell| escape(cell))
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            );
+        }
+    }
+
+    result
+}
+
+fn to_html(title: &str, generated: &str, sections: &[Section]) -> String {
+    let escape = |text: &str| glib::markup_escape_text(text);
+
+    let mut result = String::new();
+    let _ = writeln!(
+        result,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n</head>\n<body>\n<h1>{0}</h1>\n<p>{1}</p>",
+        escape(title),
+        escape(generated)
+    );
+
+    for section in sections {
+        let _ = writeln!(result, "<h2>{}</h2>", escape(&section.title));
+        if section.rows.is_empty() {
+            let _ = writeln!(result, "<p>{}</p>", escape(&i18n("None")));
+            continue;
+        }
+
+        result.push_str("<table>\n<tr>");
+        for header in &section.headers {
+            let _ = write!(result, "<th>{}</th>", escape(header));
+        }
+        result.push_str("</tr>\n");
+        for row in &section.rows {
+            result.push_str("<tr>");
+            for cell in row {
+                let _ = write!(result, "<td>{}</td>", escape(cell));
+            }
+            result.push_str("</tr>\n");
+        }
+        result.push_str("</table>\n");
+    }
+
+    result.push_str("</body>\n</html>\n");
+    result
+}
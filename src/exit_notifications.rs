@@ -0,0 +1,126 @@
+/* exit_notifications.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use gtk::gio;
+use gtk::glib::g_debug;
+use gtk::prelude::*;
+
+use crate::application::MissionCenterApplication;
+use crate::apps_page::exit_reason_text;
+use crate::i18n::{i18n, i18n_f};
+use crate::magpie_client::{ExitReason, Process, ProcessEvent, ProcessEventKind};
+
+struct WatchedProcess {
+    name: String,
+    since: Instant,
+}
+
+/// Tells the user with a notification when the processes they asked about exit, e.g. a long
+/// build or render they are waiting on
+#[derive(Default)]
+pub struct ExitNotifier {
+    watched: HashMap<u32, WatchedProcess>,
+}
+
+impl ExitNotifier {
+    pub fn is_watching(&self, pid: u32) -> bool {
+        self.watched.contains_key(&pid)
+    }
+
+    pub fn watch(&mut self, pid: u32, name: &str) {
+        self.watched.entry(pid).or_insert_with(|| WatchedProcess {
+            name: name.to_owned(),
+            since: Instant::now(),
+        });
+    }
+
+    pub fn unwatch(&mut self, pid: u32) {
+        self.watched.remove(&pid);
+    }
+
+    /// Stops watching without telling the user, e.g. when the readings start coming from
+    /// another host
+    pub fn clear(&mut self) {
+        self.watched.clear();
+    }
+
+    pub fn check(
+        &mut self,
+        app: &MissionCenterApplication,
+        processes: &HashMap<u32, Process>,
+        events: &[ProcessEvent],
+    ) {
+        if self.watched.is_empty() {
+            return;
+        }
+
+        self.watched.retain(|pid, watched| {
+            // A PID that was reused by another process counts as the process having exited
+            let running = processes
+                .get(pid)
+                .is_some_and(|process| process.name == watched.name);
+            if running {
+                return true;
+            }
+
+            let exit_reason = events
+                .iter()
+                .find(|event| event.kind == ProcessEventKind::Exited && event.pid == *pid)
+                .and_then(|event| event.exit_reason);
+            g_debug!(
+                "MissionCenter::ExitNotifier",
+                "{} ({}) exited: {:?}",
+                watched.name,
+                pid,
+                exit_reason
+            );
+            notify(app, *pid, watched, exit_reason);
+
+            false
+        });
+    }
+}
+
+fn notify(
+    app: &MissionCenterApplication,
+    pid: u32,
+    watched: &WatchedProcess,
+    exit_reason: Option<ExitReason>,
+) {
+    let notification = gio::Notification::new(&i18n_f("{} Has Ended", &[&watched.name]));
+
+    let exit = match exit_reason {
+        Some(ExitReason::Status(0)) => i18n("Exited successfully"),
+        Some(reason) => exit_reason_text(reason),
+        None => i18n("It is no longer running"),
+    };
+    // Hours, minutes and seconds like the CPU Time column
+    let seconds = watched.since.elapsed().as_secs();
+    let elapsed = format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60);
+    notification.set_body(Some(&i18n_f("{}, watched for {}", &[&exit, &elapsed])));
+    if exit_reason.is_some_and(|reason| reason.is_failure()) {
+        notification.set_priority(gio::NotificationPriority::High);
+    }
+
+    app.send_notification(Some(&format!("process-exit-{pid}")), &notification);
+}
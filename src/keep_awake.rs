@@ -0,0 +1,105 @@
+/* keep_awake.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::HashMap;
+
+use gtk::glib::{g_debug, g_warning};
+use gtk::prelude::*;
+
+use crate::application::MissionCenterApplication;
+use crate::i18n::i18n_f;
+use crate::magpie_client::Process;
+
+/// Keeps the system from suspending while the processes the user asked for are running. Each
+/// process holds its own inhibit lock, which is released once it is gone from the readings.
+#[derive(Default)]
+pub struct KeepAwake {
+    // The inhibit cookie of each process, along with its name to tell a PID that was reused
+    // by another process apart
+    cookies: HashMap<u32, (u32, String)>,
+}
+
+impl KeepAwake {
+    pub fn is_keeping_awake(&self, pid: u32) -> bool {
+        self.cookies.contains_key(&pid)
+    }
+
+    /// Takes an inhibit lock for the process, returns `false` if the session refused it
+    pub fn start(&mut self, app: &MissionCenterApplication, pid: u32, name: &str) -> bool {
+        if self.cookies.contains_key(&pid) {
+            return true;
+        }
+
+        let window = app.window();
+        let cookie = app.inhibit(
+            window.as_ref(),
+            gtk::ApplicationInhibitFlags::SUSPEND,
+            Some(&i18n_f("Waiting for {} to finish", &[name])),
+        );
+        if cookie == 0 {
+            g_warning!(
+                "MissionCenter::KeepAwake",
+                "Failed to keep the system awake while {} ({}) is running",
+                name,
+                pid
+            );
+            return false;
+        }
+
+        self.cookies.insert(pid, (cookie, name.to_owned()));
+        true
+    }
+
+    pub fn stop(&mut self, app: &MissionCenterApplication, pid: u32) {
+        if let Some((cookie, _)) = self.cookies.remove(&pid) {
+            app.uninhibit(cookie);
+        }
+    }
+
+    /// Releases the locks of the processes that exited since the previous readings
+    pub fn check(&mut self, app: &MissionCenterApplication, processes: &HashMap<u32, Process>) {
+        if self.cookies.is_empty() {
+            return;
+        }
+
+        self.cookies.retain(|pid, (cookie, name)| {
+            let running = processes
+                .get(pid)
+                .is_some_and(|process| process.name == *name);
+            if !running {
+                g_debug!(
+                    "MissionCenter::KeepAwake",
+                    "{} ({}) is gone, the system may suspend again",
+                    name,
+                    pid
+                );
+                app.uninhibit(*cookie);
+            }
+            running
+        });
+    }
+
+    /// Releases all locks, e.g. when the readings start coming from another host
+    pub fn clear(&mut self, app: &MissionCenterApplication) {
+        for (_, (cookie, _)) in self.cookies.drain() {
+            app.uninhibit(cookie);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+/* kill_list.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use gtk::gio;
+use gtk::glib::{g_critical, g_debug};
+use gtk::prelude::*;
+
+use crate::application::MissionCenterApplication;
+use crate::i18n::{i18n, ni18n_k};
+use crate::magpie_client::Process;
+
+/// Asks the processes whose executable is on the `kill-list` setting to exit as soon as they
+/// show up in the readings, and tells the user about it with a notification
+#[derive(Default)]
+pub struct KillListWatcher {
+    // Processes that were already asked to exit, so that they are not signaled and reported
+    // again while they shut down
+    handled: HashSet<u32>,
+}
+
+impl KillListWatcher {
+    pub fn check(&mut self, app: &MissionCenterApplication, processes: &HashMap<u32, Process>) {
+        self.handled.retain(|pid| processes.contains_key(pid));
+
+        // The list is meant for this machine, not for the ones it connects to
+        if crate::magpie_client::is_remote() {
+            return;
+        }
+
+        let kill_list = app.settings().strv("kill-list");
+        if kill_list.is_empty() {
+            return;
+        }
+
+        let own_pid = std::process::id();
+        let mut matches = BTreeMap::<&str, Vec<u32>>::new();
+        for process in processes.values() {
+            if process.pid == own_pid || self.handled.contains(&process.pid) {
+                continue;
+            }
+
+            let name = executable_name(process);
+            if kill_list.iter().any(|entry| entry.as_str() == name) {
+                matches.entry(name).or_default().push(process.pid);
+            }
+        }
+        if matches.is_empty() {
+            return;
+        }
+
+        let pids = matches.values().flatten().copied().collect::<Vec<_>>();
+        match app.sys_info() {
+            Ok(magpie) => magpie.terminate_processes(pids.clone()),
+            Err(e) => {
+                g_critical!(
+                    "MissionCenter::KillList",
+                    "Failed to get magpie client: {}",
+                    e
+                );
+                return;
+            }
+        }
+        g_debug!(
+            "MissionCenter::KillList",
+            "Asked {:?} to exit, since they are on the kill list",
+            pids
+        );
+        self.handled.extend(pids);
+
+        for (name, pids) in matches {
+            notify(app, name, pids.len());
+        }
+    }
+}
+
+/// The name entries on the list are compared with: the file name of the executable, or the
+/// process name for processes without one, like kernel threads
+fn executable_name(process: &Process) -> &str {
+    match process.exe.rsplit_once('/') {
+        Some((_, name)) if !name.is_empty() => name,
+        _ => process.name.as_str(),
+    }
+}
+
+fn notify(app: &MissionCenterApplication, name: &str, count: usize) {
+    let notification = gio::Notification::new(&ni18n_k(
+        "Ended {name}",
+        "Ended {count} processes of {name}",
+        count as u32,
+        &[("name", name), ("count", &count.to_string())],
+    ));
+    notification.set_body(Some(&i18n(
+        "It is on the kill list, which can be changed in the preferences",
+    )));
+    notification.set_default_action("app.preferences");
+
+    // Replaces the previous notification about the same executable, should it keep restarting
+    app.send_notification(Some(&format!("kill-list-{name}")), &notification);
+}
@@ -0,0 +1,171 @@
+/* magpie_client/boot_analysis.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use super::unit_states::run_on_host;
+
+/// When a unit was activated while the system booted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitActivation {
+    pub name: String,
+    /// When the unit started activating, since userspace started
+    pub started: Duration,
+    /// When the unit became active, since userspace started
+    pub activated: Duration,
+    /// Whether the unit is on the chain of units the end of the boot had to wait for
+    pub critical: bool,
+}
+
+impl UnitActivation {
+    /// How long the unit took to become active, what `systemd-analyze blame` shows
+    pub fn duration(&self) -> Duration {
+        self.activated.saturating_sub(self.started)
+    }
+}
+
+/// How the system booted, as far as systemd recorded it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BootAnalysis {
+    /// How long userspace took to finish booting
+    pub userspace_time: Duration,
+    /// The units that were activated during the boot, in the order they started activating
+    pub units: Vec<UnitActivation>,
+}
+
+/// Reads when each unit was activated during boot from systemd and passes it to `on_done`, along
+/// with the units on the critical chain like `systemd-analyze critical-chain` tells them.
+/// Nothing is passed when connected to another machine, or when the machine isn't managed by
+/// systemd.
+pub fn boot_analysis(on_done: impl FnOnce(Option<BootAnalysis>) + 'static) {
+    if super::is_remote() {
+        on_done(None);
+        return;
+    }
+
+    run_on_host(
+        "systemctl",
+        &[
+            "show",
+            "--property=UserspaceTimestampMonotonic,FinishTimestampMonotonic",
+        ],
+        false,
+        move |output| {
+            let properties = parse_properties(&output);
+            let timestamp = |key: &str| {
+                properties
+                    .get(key)
+                    .and_then(|value| value.parse::<u64>().ok())
+                    .filter(|usec| *usec > 0)
+            };
+            // Not finished booting yet, or not booted with systemd
+            let (Some(userspace), Some(finish)) = (
+                timestamp("UserspaceTimestampMonotonic"),
+                timestamp("FinishTimestampMonotonic"),
+            ) else {
+                on_done(None);
+                return;
+            };
+
+            read_units(userspace, finish, on_done);
+        },
+    );
+}
+
+fn read_units(userspace: u64, finish: u64, on_done: impl FnOnce(Option<BootAnalysis>) + 'static) {
+    run_on_host(
+        "systemctl",
+        &[
+            "show",
+            "--property=Id,InactiveExitTimestampMonotonic,ActiveEnterTimestampMonotonic",
+            "--",
+            "*",
+        ],
+        false,
+        move |output| {
+            let mut units = output
+                .split("\n\n")
+                .filter_map(|unit| {
+                    let properties = parse_properties(unit);
+                    let timestamp = |key: &str| {
+                        properties
+                            .get(key)
+                            .and_then(|value| value.parse::<u64>().ok())
+                            // Units that were started again since then don't tell about the boot
+                            .filter(|usec| (userspace..=finish).contains(usec))
+                    };
+
+                    let started = timestamp("InactiveExitTimestampMonotonic")?;
+                    let activated = timestamp("ActiveEnterTimestampMonotonic")?;
+                    if activated < started {
+                        return None;
+                    }
+
+                    Some(UnitActivation {
+                        name: properties.get("Id")?.to_string(),
+                        started: Duration::from_micros(started - userspace),
+                        activated: Duration::from_micros(activated - userspace),
+                        critical: false,
+                    })
+                })
+                .collect::<Vec<_>>();
+            units.sort_by(|a, b| a.started.cmp(&b.started).then(a.activated.cmp(&b.activated)));
+
+            let userspace_time = Duration::from_micros(finish - userspace);
+            run_on_host(
+                "systemd-analyze",
+                &["critical-chain", "--no-pager"],
+                false,
+                move |output| {
+                    let critical = critical_units(&output);
+                    for unit in &mut units {
+                        unit.critical = critical.contains(unit.name.as_str());
+                    }
+
+                    on_done(Some(BootAnalysis {
+                        userspace_time,
+                        units,
+                    }));
+                },
+            );
+        },
+    );
+}
+
+fn parse_properties(output: &str) -> HashMap<&str, &str> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .collect()
+}
+
+// The chain is printed as a tree below the default target, like `└─multi-user.target @4.2s`,
+// after a few lines explaining it
+fn critical_units(output: &str) -> HashSet<&str> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_start_matches([' ', '│', '├', '└', '─', '|', '`', '-']);
+            let (unit, time) = line.split_once(' ')?;
+            time.starts_with('@').then_some(unit)
+        })
+        .collect()
+}
@@ -18,7 +18,6 @@
  * SPDX-License-Identifier: GPL-3.0-or-later
  */
 
-use arrayvec::ArrayString;
 use gtk::glib::{g_critical, g_debug};
 use magpie_types::about::{about_response, About};
 use magpie_types::apps::apps_response;
@@ -53,12 +52,13 @@ use magpie_types::services::services_response;
 use magpie_types::services::services_response::ServiceList;
 pub use magpie_types::services::Service;
 use std::num::NonZeroU32;
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::time::Duration;
 use std::{cell::RefCell, collections::HashMap, sync::Arc};
 
-use crate::magpie_client::flatpak_app_path;
-use crate::{flatpak_data_dir, is_flatpak, show_error_dialog_and_exit};
+use super::is_remote;
+use super::transport::Transport;
+use crate::show_error_dialog_and_exit;
 
 mod nng {
     pub use nng_c_sys::nng_errno_enum::*;
@@ -76,8 +76,6 @@ type ConnectionsResponse = connections_response::Response;
 type ProcessesResponse = processes_response::Response;
 type ServicesResponse = services_response::Response;
 
-const ENV_MC_DEBUG_MAGPIE_PROCESS_SOCK: &str = "MC_DEBUG_MAGPIE_PROCESS_SOCK";
-
 macro_rules! parse_response {
     ($response: ident, $body_kind: path, $response_kind_ok: path, $response_kind_err: path, $do: expr) => {{
         let expected_type = stringify!($response_kind_ok);
@@ -141,104 +139,7 @@ macro_rules! parse_response_with_err {
     }};
 }
 
-fn random_string<const CAP: usize>() -> ArrayString<CAP> {
-    let mut result = ArrayString::new();
-    for _ in 0..CAP {
-        if rand::random::<bool>() {
-            result.push(rand::random_range(b'a'..=b'z') as char);
-        } else {
-            result.push(rand::random_range(b'0'..=b'9') as char);
-        }
-    }
-
-    result
-}
-
-fn magpie_command(socket_addr: &str) -> std::process::Command {
-    fn executable() -> String {
-        use gtk::glib::g_debug;
-
-        let exe_simple = "missioncenter-magpie".to_owned();
-
-        if is_flatpak() {
-            let flatpak_app_path = flatpak_app_path();
-
-            let cmd_glibc_status = cmd_flatpak_host!(&format!(
-                "{}/bin/missioncenter-magpie-glibc --test",
-                flatpak_app_path
-            ))
-            .status()
-            .is_ok_and(|exit_status| exit_status.success());
-            if cmd_glibc_status {
-                let exe_glibc = format!("{}/bin/missioncenter-magpie-glibc", flatpak_app_path);
-                g_debug!(
-                    "MissionCenter::Gatherer",
-                    "Magpie executable name: {}",
-                    &exe_glibc
-                );
-                return exe_glibc;
-            }
-
-            let cmd_musl_status = cmd_flatpak_host!(&format!(
-                "{}/bin/missioncenter-magpie-musl --test",
-                flatpak_app_path
-            ))
-            .status()
-            .is_ok_and(|exit_status| exit_status.success());
-            if cmd_musl_status {
-                let exe_musl = format!("{}/bin/missioncenter-magpie-musl", flatpak_app_path);
-                g_debug!(
-                    "MissionCenter::Gatherer",
-                    "Magpie executable name: {}",
-                    &exe_musl
-                );
-                return exe_musl;
-            }
-        }
-
-        g_debug!(
-            "MissionCenter::Gatherer",
-            "Magpie executable name: {}",
-            &exe_simple
-        );
-
-        exe_simple
-    }
-
-    let mut command = if is_flatpak() {
-        let mut cmd = std::process::Command::new("/app/bin/missioncenter-spawner");
-        cmd.arg("-v")
-            .arg("--env=LD_PRELOAD=")
-            .arg(format!(
-                "--env=MC_MAGPIE_HW_DB={}/share/missioncenter/hw.db",
-                flatpak_app_path()
-            ))
-            .arg(format!(
-                "--env=RUST_LOG={}",
-                std::env::var("RUST_LOG").unwrap_or_default()
-            ))
-            .arg(executable());
-        cmd
-    } else {
-        let mut cmd = std::process::Command::new(executable());
-
-        if let Some(mut appdir) = std::env::var_os("APPDIR") {
-            appdir.push("/runtime/default");
-            cmd.current_dir(appdir);
-        }
-
-        cmd
-    };
-    command
-        .env_remove("LD_PRELOAD")
-        .stdout(std::process::Stdio::inherit())
-        .stderr(std::process::Stdio::inherit())
-        .arg("--addr")
-        .arg(socket_addr);
-
-    command
-}
-
+// Magpie processes started by the app listen on a Unix socket, remote ones are reached over TCP
 fn connect_socket(socket: &mut nng_c::Socket, socket_addr: &str) -> bool {
     let _ = socket.close();
     socket.id = 0;
@@ -306,9 +207,11 @@ fn make_request(
             return;
         }
 
-        show_error_dialog_and_exit(
-            "Lost connection to Magpie and failed to reconnect after 5 tries. Giving up.",
-        );
+        if !is_remote() {
+            show_error_dialog_and_exit(
+                "Lost connection to Magpie and failed to reconnect after 5 tries. Giving up.",
+            );
+        }
     }
 
     let mut req_buf = Vec::new();
@@ -473,11 +376,9 @@ pub struct Client {
     socket: RefCell<nng_c::Socket>,
 
     socket_addr: Arc<str>,
-    child_thread: RefCell<std::thread::JoinHandle<()>>,
-    stop_requested: Arc<AtomicBool>,
+    transport: RefCell<Box<dyn Transport>>,
 
     core_count: AtomicU32,
-    scale_cpu_usage_to_core_count: AtomicBool,
 }
 
 impl Drop for Client {
@@ -487,21 +388,8 @@ impl Drop for Client {
 }
 
 impl Client {
-    pub fn new() -> Self {
-        let socket_addr =
-            if let Ok(mut existing_sock) = std::env::var(ENV_MC_DEBUG_MAGPIE_PROCESS_SOCK) {
-                existing_sock.push('\0');
-                Arc::from(existing_sock)
-            } else {
-                if is_flatpak() {
-                    Arc::from(format!(
-                        "ipc://{}/magpie.ipc\0",
-                        flatpak_data_dir().display()
-                    ))
-                } else {
-                    Arc::from(format!("ipc:///tmp/magpie_{}.ipc\0", random_string::<8>()))
-                }
-            };
+    pub fn new(transport: Box<dyn Transport>) -> Self {
+        let socket_addr = Arc::from(format!("{}\0", transport.address()));
 
         let socket = nng_c::Socket::req0().expect("Could not create initial socket");
 
@@ -509,84 +397,19 @@ impl Client {
             socket: RefCell::new(socket),
 
             socket_addr,
-            child_thread: RefCell::new(std::thread::spawn(|| {})),
-            stop_requested: Arc::new(AtomicBool::new(false)),
+            transport: RefCell::new(transport),
 
             core_count: AtomicU32::new(1),
-            scale_cpu_usage_to_core_count: AtomicBool::new(false),
         }
     }
 
     pub fn start(&self) {
-        fn start_magpie_process_thread(
-            socket_addr: Arc<str>,
-            stop_requested: Arc<AtomicBool>,
-        ) -> std::thread::JoinHandle<()> {
-            std::thread::spawn(move || {
-                fn spawn_child(socket_addr: &str) -> std::process::Child {
-                    match magpie_command(socket_addr.trim_end_matches('\0')).spawn() {
-                        Ok(child) => child,
-                        Err(e) => {
-                            g_critical!(
-                                "MissionCenter::Gatherer",
-                                "Failed to spawn Magpie process: {}",
-                                &e
-                            );
-                            show_error_dialog_and_exit(&format!(
-                                "Failed to spawn Magpie process: {}",
-                                e
-                            ));
-                        }
-                    }
-                }
-
-                let mut child = spawn_child(&socket_addr);
-
-                while !stop_requested.load(Ordering::Relaxed) {
-                    match child.try_wait() {
-                        Ok(Some(exit_status)) => {
-                            let _ = std::fs::remove_file(&socket_addr[6..]);
-
-                            if !stop_requested.load(Ordering::Relaxed) {
-                                g_critical!(
-                                    "MissionCenter::Gatherer",
-                                    "Magpie process exited unexpectedly: {}. Restarting...",
-                                    exit_status
-                                );
-                                std::mem::swap(&mut child, &mut spawn_child(&socket_addr));
-                            }
-                        }
-                        Ok(None) => {
-                            std::thread::sleep(Duration::from_millis(100));
-                            continue;
-                        }
-                        Err(e) => {
-                            g_critical!(
-                                "MissionCenter::Gatherer",
-                                "Failed to wait for Gatherer process to stop: {}",
-                                &e
-                            );
-                            show_error_dialog_and_exit(&format!(
-                                "Failed to wait for Gatherer process to stop: {}",
-                                e
-                            ));
-                        }
-                    }
-                }
-
-                let _ = child.kill();
-            })
-        }
-
-        if !std::env::var(ENV_MC_DEBUG_MAGPIE_PROCESS_SOCK).is_ok() {
-            *self.child_thread.borrow_mut() =
-                start_magpie_process_thread(self.socket_addr.clone(), self.stop_requested.clone());
-        }
+        self.transport.borrow_mut().start();
 
         const START_WAIT_TIME_MS: u64 = 300;
         const RETRY_COUNT: i32 = 50;
 
-        // Let the child process start up
+        // Let the child process or the tunnel start up
         for _ in 0..RETRY_COUNT {
             std::thread::sleep(Duration::from_millis(START_WAIT_TIME_MS / 2));
 
@@ -597,25 +420,25 @@ impl Client {
             std::thread::sleep(Duration::from_millis(START_WAIT_TIME_MS / 2));
         }
 
-        show_error_dialog_and_exit("Failed to connect to Gatherer socket");
+        if !is_remote() {
+            show_error_dialog_and_exit("Failed to connect to Gatherer socket");
+        }
+
+        // Another machine being unreachable should not take down the app, requests keep
+        // trying to reconnect
+        g_critical!(
+            "MissionCenter::Gatherer",
+            "Failed to connect to Magpie at {}",
+            self.socket_addr.trim_end_matches('\0')
+        );
     }
 
     pub fn stop(&self) {
-        self.stop_requested.store(true, Ordering::Relaxed);
-        let child_thread = std::mem::replace(
-            &mut *self.child_thread.borrow_mut(),
-            std::thread::spawn(|| {}),
-        );
-        let _ = child_thread.join();
+        self.transport.borrow_mut().stop();
     }
 }
 
 impl Client {
-    pub fn set_scale_cpu_usage_to_core_count(&self, v: bool) {
-        self.scale_cpu_usage_to_core_count
-            .store(v, Ordering::Relaxed);
-    }
-
     pub fn about(&self) -> About {
         let mut socket = self.socket.borrow_mut();
 
@@ -851,13 +674,8 @@ impl Client {
             }
         );
 
-        let scale_cpu_usage_to_core_count =
-            self.scale_cpu_usage_to_core_count.load(Ordering::Relaxed);
-        let factor = if !scale_cpu_usage_to_core_count {
-            self.core_count.load(Ordering::Relaxed) as f32
-        } else {
-            1.
-        };
+        // Magpie reports 100% per core, the views decide whether to show it that way
+        let factor = self.core_count.load(Ordering::Relaxed).max(1) as f32;
         for process in processes.values_mut() {
             process.usage_stats.cpu_usage /= factor;
         }
@@ -0,0 +1,153 @@
+/* magpie_client/ignore_list.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::{HashMap, HashSet};
+
+use gtk::gio;
+use gtk::prelude::*;
+
+use super::{is_kernel_threads_parent, App, Process, Service};
+
+/// The processes and services the user never wants to see, from the `ignore-list` setting,
+/// and the kernel threads if they are hidden. They are dropped from the readings before anything
+/// is worked out from them, so they don't count towards the usage of their parents, apps or the
+/// column totals either.
+#[derive(Debug, Default, Clone)]
+pub struct IgnoreList {
+    entries: HashSet<String>,
+    hide_kernel_threads: bool,
+}
+
+impl IgnoreList {
+    pub fn from_settings(settings: &gio::Settings) -> Self {
+        Self {
+            entries: settings
+                .strv("ignore-list")
+                .iter()
+                .map(|entry| entry.to_string())
+                .collect(),
+            hide_kernel_threads: settings.boolean("apps-page-hide-kernel-threads"),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.entries.is_empty() && !self.hide_kernel_threads
+    }
+
+    /// The entry that ignores a process: the path of its executable, or the name of a kernel
+    /// thread in brackets, without the CPU or worker it runs for, so that ignoring one of them
+    /// ignores them all. Other processes without an executable can't be told apart.
+    pub fn process_entry(exe: &str, command_line: &str, name: &str) -> Option<String> {
+        if !exe.is_empty() {
+            return Some(exe.to_owned());
+        }
+        if !command_line.is_empty() || name.is_empty() {
+            return None;
+        }
+
+        let name = name.split('/').next().unwrap_or(name);
+        Some(format!("[{name}]"))
+    }
+
+    /// Services are ignored by name
+    pub fn service_entry(name: &str) -> String {
+        name.to_owned()
+    }
+
+    fn ignores_process(&self, process: &Process) -> bool {
+        Self::process_entry(&process.exe, &process.cmd.join(" "), &process.name)
+            .is_some_and(|entry| self.entries.contains(&entry))
+    }
+
+    /// Removes the ignored processes. Their children are not ignored along with them, they
+    /// move up to the closest ancestor that is still there. Hidden kernel threads go away with
+    /// their parent as a whole.
+    pub fn filter_processes(&self, processes: &mut HashMap<u32, Process>) {
+        if self.is_empty() {
+            return;
+        }
+
+        if self.hide_kernel_threads {
+            let mut pending = processes
+                .values()
+                .filter(|process| is_kernel_threads_parent(process))
+                .map(|process| process.pid)
+                .collect::<Vec<_>>();
+            while let Some(pid) = pending.pop() {
+                if let Some(process) = processes.remove(&pid) {
+                    pending.extend(process.children);
+                }
+            }
+        }
+
+        let ignored_pids = processes
+            .values()
+            .filter(|process| self.ignores_process(process))
+            .map(|process| process.pid)
+            .collect::<Vec<_>>();
+        if ignored_pids.is_empty() && !self.hide_kernel_threads {
+            return;
+        }
+
+        let ignored = ignored_pids
+            .into_iter()
+            .filter_map(|pid| processes.remove(&pid))
+            .map(|process| (process.pid, process.children))
+            .collect::<HashMap<_, _>>();
+        let pids = processes.keys().copied().collect::<HashSet<_>>();
+
+        for process in processes.values_mut() {
+            let shown = |pid: &u32| !ignored.contains_key(pid) && pids.contains(pid);
+            if process.children.iter().all(shown) {
+                continue;
+            }
+
+            let mut pending = std::mem::take(&mut process.children);
+            while let Some(pid) = pending.pop() {
+                match ignored.get(&pid) {
+                    Some(children) => pending.extend_from_slice(children),
+                    None if pids.contains(&pid) => process.children.push(pid),
+                    // A hidden kernel thread
+                    None => {}
+                }
+            }
+        }
+    }
+
+    /// Drops the ignored processes from the apps, and the apps that are left without any
+    pub fn filter_apps(&self, apps: &mut HashMap<String, App>, processes: &HashMap<u32, Process>) {
+        if self.is_empty() {
+            return;
+        }
+
+        apps.retain(|_, app| {
+            app.pids.retain(|pid| processes.contains_key(pid));
+            !app.pids.is_empty()
+        });
+    }
+
+    pub fn filter_services(&self, services: &mut HashMap<u64, Service>) {
+        if self.entries.is_empty() {
+            return;
+        }
+
+        services.retain(|_, service| !self.entries.contains(&Self::service_entry(&service.name)));
+    }
+}
@@ -0,0 +1,89 @@
+/* magpie_client/io_priority.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use gtk::glib::{g_critical, g_debug};
+
+/// The I/O scheduling class and level a process can be given, from the lowest to the highest
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IoPriority {
+    /// Only gets disk time when no other process asks for it
+    Idle,
+    /// Best-effort, with a level from 0 (highest) to 7 (lowest)
+    BestEffort(u8),
+    /// Always served first, at the default level
+    Realtime,
+}
+
+impl IoPriority {
+    fn ionice_args(&self) -> Vec<String> {
+        match self {
+            IoPriority::Idle => vec!["-c".into(), "3".into()],
+            IoPriority::BestEffort(level) => vec![
+                "-c".into(),
+                "2".into(),
+                "-n".into(),
+                level.min(&7).to_string(),
+            ],
+            IoPriority::Realtime => vec!["-c".into(), "1".into(), "-n".into(), "4".into()],
+        }
+    }
+
+    /// Raising the priority above that of other processes needs administrator rights
+    fn needs_authorization(&self) -> bool {
+        *self == IoPriority::Realtime
+    }
+}
+
+/// Runs `ionice` for the processes, on the host when running in a Flatpak. It is waited on
+/// from a separate thread, since asking for authorization can take a while.
+pub fn set_io_priority(pids: Vec<u32>, priority: IoPriority) {
+    let mut args = vec![];
+    if priority.needs_authorization() {
+        args.push("pkexec".to_owned());
+    }
+    args.push("ionice".to_owned());
+    args.extend(priority.ionice_args());
+    args.push("-p".to_owned());
+    args.extend(pids.iter().map(|pid| pid.to_string()));
+
+    let mut cmd = super::host_command(&args.iter().map(String::as_str).collect::<Vec<_>>());
+
+    std::thread::spawn(move || match cmd.output() {
+        Ok(output) if output.status.success() => {
+            g_debug!(
+                "MissionCenter::IoPriority",
+                "Set I/O priority of {:?} to {:?}",
+                pids,
+                priority
+            );
+        }
+        Ok(output) => {
+            g_critical!(
+                "MissionCenter::IoPriority",
+                "Failed to set I/O priority of {:?}: {}",
+                pids,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Err(e) => {
+            g_critical!("MissionCenter::IoPriority", "Failed to run ionice: {}", e);
+        }
+    });
+}
@@ -0,0 +1,409 @@
+/* magpie_client/metrics_exporter.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use gtk::glib::{g_debug, g_warning};
+
+use super::Readings;
+
+const METRICS_PATH: &str = "/metrics";
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+// How often the server thread checks whether it should stop, while no one is scraping
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+
+/// Serves the last readings in the Prometheus text format on `/metrics`, from a thread of its
+/// own. The server stops when this is dropped.
+pub struct MetricsExporter {
+    address: SocketAddr,
+    latest: Arc<RwLock<String>>,
+
+    running: Arc<AtomicBool>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl Drop for MetricsExporter {
+    fn drop(&mut self) {
+        self.running.store(false, atomic::Ordering::Release);
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl MetricsExporter {
+    pub fn start(address: SocketAddr) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+
+        let latest = Arc::new(RwLock::new(String::new()));
+        let running = Arc::new(AtomicBool::new(true));
+
+        let thread = std::thread::Builder::new()
+            .name("metrics-exporter".to_owned())
+            .spawn({
+                let latest = latest.clone();
+                let running = running.clone();
+                move || serve(listener, latest, running)
+            })?;
+
+        Ok(Self {
+            address,
+            latest,
+            running,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    pub fn publish(&self, readings: &Readings) {
+        let metrics = render(readings);
+        if let Ok(mut latest) = self.latest.write() {
+            *latest = metrics;
+        }
+    }
+}
+
+fn serve(listener: TcpListener, latest: Arc<RwLock<String>>, running: Arc<AtomicBool>) {
+    while running.load(atomic::Ordering::Acquire) {
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                if let Err(e) = respond(stream, &latest) {
+                    g_debug!(
+                        "MissionCenter::MetricsExporter",
+                        "Failed to answer {}: {}",
+                        peer,
+                        e
+                    );
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => std::thread::sleep(POLL_INTERVAL),
+            Err(e) => {
+                g_warning!(
+                    "MissionCenter::MetricsExporter",
+                    "Failed to accept connection: {}",
+                    e
+                );
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+}
+
+// Scrapers send one small request per connection, so only the request line is looked at and
+// the connection is closed after answering
+fn respond(mut stream: TcpStream, latest: &RwLock<String>) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+
+    let mut request = Vec::with_capacity(1024);
+    let mut buffer = [0; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = stream.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..read]);
+        if request.len() > MAX_REQUEST_SIZE {
+            return write_response(&mut stream, "431 Request Header Fields Too Large", "");
+        }
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let mut request_line = request.lines().next().unwrap_or_default().split(' ');
+    let (method, path) = (request_line.next(), request_line.next());
+
+    // Query strings are ignored, Prometheus can be configured to add some
+    let path = path.map(|path| path.split('?').next().unwrap_or_default());
+    match (method, path) {
+        (Some("GET"), Some(METRICS_PATH)) => {
+            let metrics = latest
+                .read()
+                .map(|latest| latest.clone())
+                .unwrap_or_default();
+            write_response(&mut stream, "200 OK", &metrics)
+        }
+        (Some("GET"), _) => write_response(&mut stream, "404 Not Found", ""),
+        _ => write_response(&mut stream, "405 Method Not Allowed", ""),
+    }
+}
+
+fn write_response(stream: &mut TcpStream, status: &str, body: &str) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {CONTENT_TYPE}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+struct Metric {
+    name: &'static str,
+    help: &'static str,
+    samples: Vec<(Vec<(&'static str, String)>, f64)>,
+}
+
+impl Metric {
+    fn new(name: &'static str, help: &'static str) -> Self {
+        Self {
+            name,
+            help,
+            samples: Vec::new(),
+        }
+    }
+
+    fn with_value(mut self, value: f64) -> Self {
+        self.samples.push((Vec::new(), value));
+        self
+    }
+
+    fn add(&mut self, labels: Vec<(&'static str, String)>, value: f64) {
+        self.samples.push((labels, value));
+    }
+}
+
+#[derive(Default)]
+struct ProcessAggregate {
+    count: u32,
+    cpu_usage: f64,
+    memory_usage: f64,
+    disk_read: f64,
+    disk_write: f64,
+}
+
+/// Formats the readings in the Prometheus text exposition format. Processes are aggregated by
+/// name, to keep the number of series from growing with every PID that comes and goes.
+fn render(readings: &Readings) -> String {
+    let mem_info = &readings.mem_info;
+    let mem_available = if mem_info.mem_available > mem_info.mem_total {
+        mem_info.mem_free
+    } else {
+        mem_info.mem_available
+    };
+
+    let mut metrics = vec![
+        Metric::new(
+            "missioncenter_cpu_usage_percent",
+            "Overall CPU usage, across all cores",
+        )
+        .with_value(readings.cpu.total_usage_percent as f64),
+        Metric::new(
+            "missioncenter_memory_total_bytes",
+            "Total usable physical memory",
+        )
+        .with_value(mem_info.mem_total as f64),
+        Metric::new(
+            "missioncenter_memory_used_bytes",
+            "Physical memory in use, excluding what can be reclaimed",
+        )
+        .with_value(mem_info.mem_total.saturating_sub(mem_available) as f64),
+        Metric::new("missioncenter_swap_total_bytes", "Total swap space")
+            .with_value(mem_info.swap_total as f64),
+        Metric::new("missioncenter_swap_used_bytes", "Swap space in use")
+            .with_value(mem_info.swap_total.saturating_sub(mem_info.swap_free) as f64),
+    ];
+
+    let mut core_usage = Metric::new(
+        "missioncenter_cpu_core_usage_percent",
+        "Usage of each logical CPU core",
+    );
+    for (core, usage) in readings.cpu.core_usage_percent.iter().enumerate() {
+        core_usage.add(vec![("core", core.to_string())], *usage as f64);
+    }
+    metrics.push(core_usage);
+
+    let mut disk_busy = Metric::new(
+        "missioncenter_disk_busy_percent",
+        "Share of time the drive was busy",
+    );
+    for disk in &readings.disks_info {
+        disk_busy.add(vec![("disk", disk.id.clone())], disk.busy_percent as f64);
+    }
+    metrics.push(disk_busy);
+
+    let mut network_sent = Metric::new(
+        "missioncenter_network_transmit_bytes_per_second",
+        "Data sent by the network interface",
+    );
+    let mut network_received = Metric::new(
+        "missioncenter_network_receive_bytes_per_second",
+        "Data received by the network interface",
+    );
+    for connection in &readings.network_connections {
+        let labels = vec![("device", connection.id.clone())];
+        network_sent.add(labels.clone(), connection.tx_rate_bytes_ps as f64);
+        network_received.add(labels, connection.rx_rate_bytes_ps as f64);
+    }
+    metrics.extend([network_sent, network_received]);
+
+    let mut gpu_utilization = Metric::new(
+        "missioncenter_gpu_utilization_percent",
+        "Overall GPU utilization",
+    );
+    for gpu in readings.gpus.values() {
+        if let Some(utilization) = gpu.utilization_percent {
+            gpu_utilization.add(vec![("gpu", gpu.id.clone())], utilization as f64);
+        }
+    }
+    metrics.push(gpu_utilization);
+
+    let mut processes = BTreeMap::<&str, ProcessAggregate>::new();
+    for process in readings.running_processes.values() {
+        let aggregate = processes.entry(process.name.as_str()).or_default();
+        aggregate.count += 1;
+        aggregate.cpu_usage += process.usage_stats.cpu_usage as f64;
+        aggregate.memory_usage += process.usage_stats.memory_usage as f64;
+        aggregate.disk_read += process.usage_stats.disk_read_usage as f64;
+        aggregate.disk_write += process.usage_stats.disk_write_usage as f64;
+    }
+
+    let mut process_count = Metric::new(
+        "missioncenter_process_count",
+        "Number of running processes with this name",
+    );
+    let mut process_cpu = Metric::new(
+        "missioncenter_process_cpu_usage_percent",
+        "CPU usage of the processes with this name",
+    );
+    let mut process_memory = Metric::new(
+        "missioncenter_process_memory_bytes",
+        "Memory used by the processes with this name",
+    );
+    let mut process_disk_read = Metric::new(
+        "missioncenter_process_disk_read_bytes_per_second",
+        "Data read from drives by the processes with this name",
+    );
+    let mut process_disk_write = Metric::new(
+        "missioncenter_process_disk_write_bytes_per_second",
+        "Data written to drives by the processes with this name",
+    );
+    for (name, aggregate) in processes {
+        let labels = vec![("name", name.to_owned())];
+        process_count.add(labels.clone(), aggregate.count as f64);
+        process_cpu.add(labels.clone(), aggregate.cpu_usage);
+        process_memory.add(labels.clone(), aggregate.memory_usage);
+        process_disk_read.add(labels.clone(), aggregate.disk_read);
+        process_disk_write.add(labels, aggregate.disk_write);
+    }
+    metrics.extend([
+        process_count,
+        process_cpu,
+        process_memory,
+        process_disk_read,
+        process_disk_write,
+    ]);
+
+    let mut services = readings
+        .system_services
+        .values()
+        .map(|service| ("system", service))
+        .chain(
+            readings
+                .user_services
+                .values()
+                .map(|service| ("user", service)),
+        )
+        .collect::<Vec<_>>();
+    services.sort_unstable_by(|(scope1, s1), (scope2, s2)| {
+        scope1.cmp(scope2).then_with(|| s1.name.cmp(&s2.name))
+    });
+
+    // Every state is exported for every service, so that a series does not just disappear when
+    // the state changes
+    let mut service_state = Metric::new(
+        "missioncenter_service_state",
+        "Whether the service is in the given state",
+    );
+    let mut service_enabled = Metric::new(
+        "missioncenter_service_enabled",
+        "Whether the service is started automatically",
+    );
+    for (scope, service) in services {
+        let current = if service.running {
+            "running"
+        } else if service.failed {
+            "failed"
+        } else {
+            "stopped"
+        };
+        for state in ["running", "failed", "stopped"] {
+            service_state.add(
+                vec![
+                    ("service", service.name.clone()),
+                    ("scope", scope.to_owned()),
+                    ("state", state.to_owned()),
+                ],
+                if state == current { 1. } else { 0. },
+            );
+        }
+
+        service_enabled.add(
+            vec![
+                ("service", service.name.clone()),
+                ("scope", scope.to_owned()),
+            ],
+            if service.enabled { 1. } else { 0. },
+        );
+    }
+    metrics.extend([service_state, service_enabled]);
+
+    let mut result = String::new();
+    for metric in metrics {
+        let _ = writeln!(result, "# HELP {} {}", metric.name, metric.help);
+        let _ = writeln!(result, "# TYPE {} gauge", metric.name);
+        for (labels, value) in metric.samples {
+            result.push_str(metric.name);
+            if !labels.is_empty() {
+                result.push('{');
+                for (i, (name, value)) in labels.iter().enumerate() {
+                    if i > 0 {
+                        result.push(',');
+                    }
+                    let _ = write!(result, "{}=\"{}\"", name, escape_label_value(value));
+                }
+                result.push('}');
+            }
+            let _ = writeln!(result, " {}", value);
+        }
+    }
+
+    result
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
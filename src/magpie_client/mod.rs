@@ -18,26 +18,48 @@
  * SPDX-License-Identifier: GPL-3.0-or-later
  */
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
 use std::num::NonZeroU32;
 use std::sync::atomic;
 use std::sync::atomic::{AtomicBool, AtomicU64};
 use std::sync::mpsc;
 use std::sync::mpsc::{Receiver, Sender};
-use std::sync::{Arc, OnceLock};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
 use std::time::Duration;
 
-use gtk::glib::{g_critical, g_debug, g_warning, idle_add_once};
+use gtk::gio;
+use gtk::glib::{self, g_critical, g_debug, g_warning, idle_add_once};
 
 use crate::app;
 use crate::application::{BASE_INTERVAL, INTERVAL_STEP};
+use crate::session_recording::Frame;
 
+pub use boot_analysis::{boot_analysis, BootAnalysis, UnitActivation};
 pub use client::{
     App, Client, Connection, Cpu, Disk, DiskKind, ErrorEjectFailed, Fan, Gpu, Memory, MemoryDevice,
     Process, Service, SmartData,
 };
+pub use ignore_list::IgnoreList;
+pub use io_priority::IoPriority;
 use magpie_types::about::About;
 use magpie_types::processes::processes_response::process_map::NetworkStatsError;
+use metrics_exporter::MetricsExporter;
+pub use proc_fields::{read_start_time, FieldAvailability, ProcField, ProcessState};
+use process_changes::ProcessDiffer;
+use service_backend::{InitSystem, ServiceBackend};
+pub use process_changes::{ExitReason, ProcessChangeSet, ProcessEvent, ProcessEventKind};
+pub use snapshot::{Snapshot, SnapshotError};
+use thermal_pressure::ThermalTracker;
+pub use thermal_pressure::{smooth, ThermalPressure};
+pub use transport::{active_transport, LocalTransport, RemoteHost, RemoteKind, Transport};
+use unit_files::UnitFiles;
+pub use unit_states::{
+    service_dependencies, service_resource_limits, service_start_outcome, service_unit_file,
+    set_service_resource_limits, ResourceLimits, ServiceStats, ServiceTransition, StartOutcome,
+    UnitDependency,
+};
+pub use usage_history::{app_totals, AppUsage};
 
 macro_rules! cmd_flatpak_host {
     ($cmd: expr) => {{
@@ -53,10 +75,95 @@ macro_rules! cmd_flatpak_host {
     }};
 }
 
+/// Builds a command that runs `args` on the host. Inside a Flatpak sandbox it goes through
+/// `flatpak-spawn --host`, with the arguments quoted for the shell that runs them there.
+pub fn host_command(args: &[&str]) -> std::process::Command {
+    if crate::is_flatpak() {
+        let args = args
+            .iter()
+            .map(|arg| glib::shell_quote(arg).to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        cmd_flatpak_host!(args.join(" "))
+    } else {
+        let mut cmd = std::process::Command::new(args[0]);
+        cmd.args(&args[1..]);
+        cmd
+    }
+}
+
+/// Starts `host_command(args)` as a `gio::Subprocess`, for commands that are waited on from the
+/// main loop
+pub fn host_subprocess(
+    args: &[&str],
+    flags: gio::SubprocessFlags,
+) -> Result<gio::Subprocess, glib::Error> {
+    let cmd = host_command(args);
+    let argv = std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .collect::<Vec<_>>();
+
+    gio::Subprocess::newv(&argv, flags)
+}
+
+mod boot_analysis;
 mod client;
+mod ignore_list;
+mod io_priority;
+mod metrics_exporter;
+mod proc_fields;
+mod process_changes;
+mod service_backend;
+mod snapshot;
+mod thermal_pressure;
+mod transport;
+mod unit_files;
+mod unit_states;
+mod usage_history;
 
 pub type Pid = u32;
 
+/// `kthreadd`, the parent of all kernel threads
+pub const KERNEL_THREADS_PID: Pid = 2;
+
+pub fn is_kernel_threads_parent(process: &Process) -> bool {
+    process.pid == KERNEL_THREADS_PID && process.name == "kthreadd"
+}
+
+/// How much longer the wait between readings is while the window is hidden
+const THROTTLED_INTERVAL_FACTOR: f64 = 5.;
+
+static REMOTE_SOURCE: AtomicBool = AtomicBool::new(false);
+static LAST_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Whether the readings come from another machine
+pub fn is_remote() -> bool {
+    REMOTE_SOURCE.load(atomic::Ordering::Relaxed)
+}
+
+// Inside a Flatpak or Snap sandbox `/proc` does not show the processes of the host, and the
+// unit files are those of the runtime. Neither of them says anything about another machine.
+pub fn can_inspect_host() -> bool {
+    !crate::is_flatpak() && std::env::var_os("SNAP").is_none() && !is_remote()
+}
+
+// Going through the open files of every process takes a while, so it is done here rather than
+// on the UI thread
+fn find_path_filter_users(path_filters: &RwLock<HashSet<String>>, readings: &mut Readings) {
+    readings.path_filter_pids.clear();
+    let Ok(path_filters) = path_filters.read() else {
+        return;
+    };
+
+    for path_filter in path_filters.iter() {
+        let pids = if can_inspect_host() {
+            proc_fields::processes_using(std::path::Path::new(path_filter))
+        } else {
+            None
+        };
+        readings.path_filter_pids.insert(path_filter.clone(), pids);
+    }
+}
+
 fn flatpak_app_path() -> &'static str {
     static FLATPAK_APP_PATH: OnceLock<String> = OnceLock::new();
 
@@ -84,7 +191,6 @@ fn flatpak_app_path() -> &'static str {
 
 enum Message {
     ContinueReading,
-    UpdateCoreCountAffectsPercentages(bool),
     TerminateProcesses(Vec<Pid>),
     KillProcesses(Vec<Pid>),
     InterruptProcesses(Vec<Pid>),
@@ -111,7 +217,7 @@ enum Response {
     AboutResult(About),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Readings {
     pub cpu: Cpu,
     pub mem_info: Memory,
@@ -123,11 +229,27 @@ pub struct Readings {
 
     pub running_apps: HashMap<String, App>,
     pub running_processes: HashMap<u32, Process>,
+    pub process_changes: ProcessChangeSet,
 
     pub network_stats_error: Option<NetworkStatsError>,
 
     pub user_services: HashMap<u64, Service>,
     pub system_services: HashMap<u64, Service>,
+    /// The `ExecStart` command lines of the user and system services, by service ID
+    pub service_commands: HashMap<u64, String>,
+    /// The services that are starting or stopping, by service ID
+    pub service_transitions: HashMap<u64, ServiceTransition>,
+    /// How long the services took to start and how often they were restarted, by service ID
+    pub service_stats: HashMap<u64, ServiceStats>,
+    /// The services whose unit file is linked to `/dev/null`, by service ID
+    pub masked_services: HashSet<u64>,
+
+    /// The processes using each of the path filters of the Apps pages, by path. `None` if they
+    /// can't be looked for on this machine.
+    pub path_filter_pids: HashMap<String, Option<HashSet<u32>>>,
+
+    /// These readings as a frame of the session recording, `None` while no window records one
+    pub session_frame: Option<Frame>,
 }
 
 impl Readings {
@@ -143,16 +265,43 @@ impl Readings {
 
             running_apps: HashMap::new(),
             running_processes: HashMap::new(),
+            process_changes: ProcessChangeSet::default(),
             network_stats_error: None,
 
             user_services: HashMap::new(),
             system_services: HashMap::new(),
+            service_commands: HashMap::new(),
+            service_transitions: HashMap::new(),
+            service_stats: HashMap::new(),
+            masked_services: HashSet::new(),
+
+            path_filter_pids: HashMap::new(),
+
+            session_frame: None,
         }
     }
+
+    // Takes over the maps of readings the UI is done with, the next readings are gathered into
+    // them instead of into new ones. Everything else they hold is freed.
+    fn reuse(&mut self, stale: Readings) {
+        self.process_changes = stale.process_changes;
+        self.service_commands = stale.service_commands;
+        self.service_transitions = stale.service_transitions;
+        self.service_stats = stale.service_stats;
+        self.masked_services = stale.masked_services;
+    }
 }
 
 pub struct MagpieClient {
+    generation: u64,
     speed: Arc<AtomicU64>,
+    throttled: Arc<AtomicBool>,
+    custom_fields: Arc<RwLock<Vec<ProcField>>>,
+    read_wait_channels: Arc<AtomicBool>,
+    recording_host: Arc<RwLock<Option<String>>>,
+    path_filters: Arc<RwLock<HashSet<String>>>,
+    ignore_list: Arc<RwLock<IgnoreList>>,
+    metrics_exporter: Arc<Mutex<Option<MetricsExporter>>>,
 
     refresh_thread: Option<std::thread::JoinHandle<()>>,
     refresh_thread_running: Arc<AtomicBool>,
@@ -180,7 +329,15 @@ impl Default for MagpieClient {
         let (_, resp_rx) = mpsc::channel::<Response>();
 
         Self {
+            generation: 0,
             speed: Arc::new(0.into()),
+            throttled: Arc::new(false.into()),
+            custom_fields: Arc::new(RwLock::new(vec![])),
+            read_wait_channels: Arc::new(false.into()),
+            recording_host: Arc::new(RwLock::new(None)),
+            path_filters: Arc::new(RwLock::new(HashSet::new())),
+            ignore_list: Arc::new(RwLock::new(IgnoreList::default())),
+            metrics_exporter: Arc::new(Mutex::new(None)),
 
             refresh_thread: None,
             refresh_thread_running: Arc::new(true.into()),
@@ -192,21 +349,60 @@ impl Default for MagpieClient {
 }
 
 impl MagpieClient {
-    pub fn new() -> Self {
+    pub fn new(transport: Box<dyn Transport>) -> Self {
+        REMOTE_SOURCE.store(!transport.is_local(), atomic::Ordering::Relaxed);
+        let generation = LAST_GENERATION.fetch_add(1, atomic::Ordering::Relaxed) + 1;
+
         let speed = Arc::new(AtomicU64::new(
             (BASE_INTERVAL / INTERVAL_STEP).round() as u64
         ));
+        let throttled = Arc::new(AtomicBool::new(false));
+        let custom_fields = Arc::new(RwLock::new(vec![]));
+        let read_wait_channels = Arc::new(AtomicBool::new(false));
+        let recording_host = Arc::new(RwLock::new(None));
+        let path_filters = Arc::new(RwLock::new(HashSet::new()));
+        let ignore_list = Arc::new(RwLock::new(IgnoreList::default()));
+        let metrics_exporter = Arc::new(Mutex::new(None));
         let refresh_thread_running = Arc::new(AtomicBool::new(true));
 
         let s = speed.clone();
+        let t = throttled.clone();
+        let fields = custom_fields.clone();
+        let wait_channels = read_wait_channels.clone();
+        let recording = recording_host.clone();
+        let filters = path_filters.clone();
+        let ignored = ignore_list.clone();
+        let exporter = metrics_exporter.clone();
         let run = refresh_thread_running.clone();
 
         let (tx, rx) = mpsc::channel::<Message>();
         let (resp_tx, resp_rx) = mpsc::channel::<Response>();
         Self {
+            generation,
             speed,
+            throttled,
+            custom_fields,
+            read_wait_channels,
+            recording_host,
+            path_filters,
+            ignore_list,
+            metrics_exporter,
             refresh_thread: Some(std::thread::spawn(move || {
-                Self::gather_and_proxy(rx, resp_tx, run, s);
+                Self::gather_and_proxy(
+                    transport,
+                    generation,
+                    rx,
+                    resp_tx,
+                    run,
+                    s,
+                    t,
+                    fields,
+                    wait_channels,
+                    recording,
+                    filters,
+                    ignored,
+                    exporter,
+                );
             })),
             refresh_thread_running,
             sender: tx,
@@ -214,25 +410,106 @@ impl MagpieClient {
         }
     }
 
+    /// Tells the readings of this client apart from those of a client it replaced, which can
+    /// still be queued up for the UI thread after switching to another machine
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
     pub fn set_update_speed(&self, speed: u64) {
         self.speed.store(speed, atomic::Ordering::Release);
     }
 
-    pub fn set_core_count_affects_percentages(&self, show: bool) {
-        match self
-            .sender
-            .send(Message::UpdateCoreCountAffectsPercentages(show))
-        {
+    /// Gathers readings `THROTTLED_INTERVAL_FACTOR` times less often, for when the window can't
+    /// be seen. Switching back cuts the wait for the next readings short.
+    pub fn set_throttled(&self, throttled: bool) {
+        self.throttled.store(throttled, atomic::Ordering::Release);
+    }
+
+    /// Sets the extra per-process fields that are read with every update
+    pub fn set_custom_fields(&self, fields: Vec<ProcField>) {
+        match self.custom_fields.write() {
+            Ok(mut custom_fields) => *custom_fields = fields,
             Err(e) => {
                 g_critical!(
                     "MissionCenter::SysInfo",
-                    "Error sending UpdateCoreCountAffectsPercentages to Gatherer: {e}"
+                    "Failed to update the custom process fields: {e}"
+                );
+            }
+        }
+    }
+
+    /// Whether the wait channels of the processes are read with every update, for while a view
+    /// shows them
+    pub fn set_read_wait_channels(&self, read: bool) {
+        self.read_wait_channels
+            .store(read, atomic::Ordering::Release);
+    }
+
+    /// Encodes the readings into a frame of the session recording with every update, under the
+    /// name of `host`, `None` once no window records one anymore
+    pub fn set_recording_host(&self, host: Option<String>) {
+        match self.recording_host.write() {
+            Ok(mut recording_host) => *recording_host = host,
+            Err(e) => {
+                g_critical!(
+                    "MissionCenter::SysInfo",
+                    "Failed to update the session recording: {e}"
+                );
+            }
+        }
+    }
+
+    /// Sets the files and directories that the processes using them are looked for with every
+    /// update, the path filters of all Apps pages
+    pub fn set_path_filters(&self, paths: HashSet<String>) {
+        match self.path_filters.write() {
+            Ok(mut path_filters) => *path_filters = paths,
+            Err(e) => {
+                g_critical!(
+                    "MissionCenter::SysInfo",
+                    "Failed to update the path filters: {e}"
                 );
             }
-            _ => {}
         }
     }
 
+    /// Sets the processes and services that are left out of the readings
+    pub fn set_ignore_list(&self, ignore_list: IgnoreList) {
+        match self.ignore_list.write() {
+            Ok(mut ignored) => *ignored = ignore_list,
+            Err(e) => {
+                g_critical!(
+                    "MissionCenter::SysInfo",
+                    "Failed to update the ignored processes and services: {e}"
+                );
+            }
+        }
+    }
+
+    /// Starts serving the readings for Prometheus on `address`, or stops doing so if `None`
+    pub fn set_metrics_exporter(&self, address: Option<SocketAddr>) -> std::io::Result<()> {
+        let Ok(mut metrics_exporter) = self.metrics_exporter.lock() else {
+            g_critical!(
+                "MissionCenter::SysInfo",
+                "Failed to update the metrics exporter, lock poisoned"
+            );
+            return Ok(());
+        };
+
+        if metrics_exporter.as_ref().map(|exporter| exporter.address()) == address {
+            return Ok(());
+        }
+
+        // The previous server has to let go of the port first, in case only the interface changed
+        *metrics_exporter = None;
+        if let Some(address) = address {
+            *metrics_exporter = Some(MetricsExporter::start(address)?);
+        }
+
+        Ok(())
+    }
+
     pub fn continue_reading(&self) {
         match self.sender.send(Message::ContinueReading) {
             Err(e) => {
@@ -382,6 +659,20 @@ impl MagpieClient {
         }
     }
 
+    /// Changes how the processes are scheduled for disk access. Magpie has no request for
+    /// this, so it only works for processes on this machine.
+    pub fn set_io_priority(&self, pids: Vec<u32>, priority: IoPriority) {
+        if is_remote() {
+            g_warning!(
+                "MissionCenter::SysInfo",
+                "Can't set the I/O priority of processes on a remote host"
+            );
+            return;
+        }
+
+        io_priority::set_io_priority(pids, priority);
+    }
+
     pub fn service_logs(&self, service_id: u64, pid: Option<NonZeroU32>) -> String {
         let sid = service_id.clone();
         match self.sender.send(Message::GetServiceLogs(service_id, pid)) {
@@ -586,6 +877,7 @@ impl MagpieClient {
 impl MagpieClient {
     fn handle_incoming_message(
         magpie: &Client,
+        services: &dyn ServiceBackend,
         rx: &mut Receiver<Message>,
         tx: &mut Sender<Response>,
         timeout: Duration,
@@ -598,9 +890,6 @@ impl MagpieClient {
                         "Received ContinueReading message while not reading"
                     );
                 }
-                Message::UpdateCoreCountAffectsPercentages(show) => {
-                    magpie.set_scale_cpu_usage_to_core_count(show);
-                }
                 Message::TerminateProcesses(pid) => {
                     magpie.terminate_processes(pid);
                 }
@@ -625,23 +914,23 @@ impl MagpieClient {
                 Message::User2Processes(pids) => {
                     magpie.signal_user_two_processes(pids);
                 }
-                Message::StartService(name) => {
-                    magpie.start_service(name);
+                Message::StartService(service_id) => {
+                    services.start(magpie, service_id);
                 }
-                Message::StopService(name) => {
-                    magpie.stop_service(name);
+                Message::StopService(service_id) => {
+                    services.stop(magpie, service_id);
                 }
-                Message::RestartService(name) => {
-                    magpie.restart_service(name);
+                Message::RestartService(service_id) => {
+                    services.restart(magpie, service_id);
                 }
-                Message::EnableService(name) => {
-                    magpie.enable_service(name);
+                Message::EnableService(service_id) => {
+                    services.enable(magpie, service_id);
                 }
-                Message::DisableService(name) => {
-                    magpie.disable_service(name);
+                Message::DisableService(service_id) => {
+                    services.disable(magpie, service_id);
                 }
-                Message::GetServiceLogs(name, pid) => {
-                    let resp = magpie.service_logs(name, pid);
+                Message::GetServiceLogs(service_id, pid) => {
+                    let resp = services.logs(magpie, service_id, pid);
                     if let Err(e) = tx.send(Response::String(resp)) {
                         g_critical!(
                             "MissionCenter::SysInfo",
@@ -684,16 +973,57 @@ impl MagpieClient {
     }
 
     fn gather_and_proxy(
+        transport: Box<dyn Transport>,
+        generation: u64,
         mut rx: Receiver<Message>,
         mut tx: Sender<Response>,
         running: Arc<AtomicBool>,
         speed: Arc<AtomicU64>,
+        throttled: Arc<AtomicBool>,
+        custom_fields: Arc<RwLock<Vec<ProcField>>>,
+        read_wait_channels: Arc<AtomicBool>,
+        recording_host: Arc<RwLock<Option<String>>>,
+        path_filters: Arc<RwLock<HashSet<String>>>,
+        ignore_list: Arc<RwLock<IgnoreList>>,
+        metrics_exporter: Arc<Mutex<Option<MetricsExporter>>>,
     ) {
-        let magpie = Client::new();
+        // The usage history is kept for this machine only
+        let record_usage = transport.is_local();
+        let mut services = service_backend::create(InitSystem::detect(transport.is_local()));
+
+        let magpie = Client::new(transport);
         magpie.start();
 
-        let (running_processes, network_stats_error) = magpie.processes();
+        let mut process_differ = ProcessDiffer::default();
+        let mut synthetic_load = crate::benchmark::SyntheticLoad::new();
+        let mut thermal_tracker = ThermalTracker::default();
+        let mut unit_files = UnitFiles::default();
+        let read_custom_fields = || {
+            custom_fields
+                .read()
+                .map(|fields| fields.clone())
+                .unwrap_or_default()
+        };
+        let read_ignore_list = || {
+            ignore_list
+                .read()
+                .map(|ignore_list| ignore_list.clone())
+                .unwrap_or_default()
+        };
+
+        let ignored = read_ignore_list();
+        let (mut running_processes, network_stats_error) = magpie.processes();
+        if let Some(synthetic_load) = synthetic_load.as_mut() {
+            synthetic_load.add_to(&mut running_processes);
+        }
+        ignored.filter_processes(&mut running_processes);
         let mut readings = Readings {
+            process_changes: process_differ.diff(
+                &running_processes,
+                &read_custom_fields(),
+                read_wait_channels.load(atomic::Ordering::Acquire),
+                ProcessChangeSet::default(),
+            ),
             running_processes,
             network_stats_error,
             running_apps: magpie.apps(),
@@ -704,9 +1034,43 @@ impl MagpieClient {
             mem_devices: magpie.memory_devices(),
             fans: magpie.fans_info(),
             network_connections: magpie.network_connections(),
-            user_services: magpie.user_services(),
-            system_services: magpie.system_services(),
+            user_services: services.user_services(&magpie),
+            system_services: services.system_services(&magpie),
+            service_commands: HashMap::new(),
+            service_transitions: HashMap::new(),
+            service_stats: HashMap::new(),
+            masked_services: HashSet::new(),
+            path_filter_pids: HashMap::new(),
+            session_frame: None,
         };
+        find_path_filter_users(&path_filters, &mut readings);
+        ignored.filter_apps(&mut readings.running_apps, &readings.running_processes);
+        ignored.filter_services(&mut readings.user_services);
+        ignored.filter_services(&mut readings.system_services);
+        unit_files.commands(
+            readings
+                .user_services
+                .values()
+                .chain(readings.system_services.values()),
+            &mut readings.service_commands,
+        );
+        unit_files::masked(
+            readings
+                .user_services
+                .values()
+                .chain(readings.system_services.values()),
+            &mut readings.masked_services,
+        );
+        services.transitions(
+            &readings.user_services,
+            &readings.system_services,
+            &mut readings.service_transitions,
+        );
+        services.stats(
+            &readings.user_services,
+            &readings.system_services,
+            &mut readings.service_stats,
+        );
 
         readings
             .disks_info
@@ -726,12 +1090,23 @@ impl MagpieClient {
                 gpus: std::mem::take(&mut readings.gpus),
                 running_apps: std::mem::take(&mut readings.running_apps),
                 running_processes: std::mem::take(&mut readings.running_processes),
+                process_changes: std::mem::take(&mut readings.process_changes),
                 network_stats_error: std::mem::take(&mut readings.network_stats_error),
                 user_services: std::mem::take(&mut readings.user_services),
                 system_services: std::mem::take(&mut readings.system_services),
+                service_commands: std::mem::take(&mut readings.service_commands),
+                service_transitions: std::mem::take(&mut readings.service_transitions),
+                service_stats: std::mem::take(&mut readings.service_stats),
+                masked_services: std::mem::take(&mut readings.masked_services),
+                path_filter_pids: std::mem::take(&mut readings.path_filter_pids),
+                session_frame: None,
             };
 
             move || {
+                if !app!().is_current_source(generation) {
+                    return;
+                }
+
                 app!().set_initial_readings(initial_readings);
                 app!().setup_animations();
             }
@@ -743,9 +1118,6 @@ impl MagpieClient {
                     Message::ContinueReading => {
                         break;
                     }
-                    Message::UpdateCoreCountAffectsPercentages(show) => {
-                        magpie.set_scale_cpu_usage_to_core_count(show);
-                    }
                     _ => {}
                 },
                 Err(_) => {
@@ -758,19 +1130,67 @@ impl MagpieClient {
             }
         }
 
+        // Readings are handed back once the UI is done with them, so that the process and
+        // service maps are freed on this thread instead of stalling the UI thread, and the
+        // maps filled here are reused
+        let (recycle_tx, recycle_rx) = mpsc::channel::<Readings>();
+
         'read_loop: while running.load(atomic::Ordering::Acquire) {
             let loop_start = std::time::Instant::now();
 
+            let timer = std::time::Instant::now();
+            let mut recycled_count = 0;
+            for stale_readings in recycle_rx.try_iter() {
+                readings.reuse(stale_readings);
+                recycled_count += 1;
+            }
+            if recycled_count > 0 {
+                g_debug!(
+                    "MissionCenter::Perf",
+                    "Recycling {} stale readings took: {:?}",
+                    recycled_count,
+                    timer.elapsed()
+                );
+            }
+
+            let ignored = read_ignore_list();
+
             let timer = std::time::Instant::now();
             (readings.running_processes, readings.network_stats_error) = magpie.processes();
+            if let Some(synthetic_load) = synthetic_load.as_mut() {
+                synthetic_load.add_to(&mut readings.running_processes);
+            }
+            ignored.filter_processes(&mut readings.running_processes);
             g_debug!(
                 "MissionCenter::Perf",
                 "Process load load took: {:?}",
                 timer.elapsed()
             );
 
+            let timer = std::time::Instant::now();
+            readings.process_changes = process_differ.diff(
+                &readings.running_processes,
+                &read_custom_fields(),
+                read_wait_channels.load(atomic::Ordering::Acquire),
+                std::mem::take(&mut readings.process_changes),
+            );
+            g_debug!(
+                "MissionCenter::Perf",
+                "Process diff took: {:?}",
+                timer.elapsed()
+            );
+
+            let timer = std::time::Instant::now();
+            find_path_filter_users(&path_filters, &mut readings);
+            g_debug!(
+                "MissionCenter::Perf",
+                "Path filter lookup took: {:?}",
+                timer.elapsed()
+            );
+
             let timer = std::time::Instant::now();
             readings.running_apps = magpie.apps();
+            ignored.filter_apps(&mut readings.running_apps, &readings.running_processes);
             g_debug!(
                 "MissionCenter::Perf",
                 "Running apps load took: {:?}",
@@ -801,6 +1221,9 @@ impl MagpieClient {
                 timer.elapsed()
             );
 
+            readings.process_changes.thermal_pressure =
+                thermal_tracker.update(&readings.cpu, &readings.running_processes);
+
             let timer = std::time::Instant::now();
             readings.mem_info = magpie.memory();
             g_debug!(
@@ -826,7 +1249,8 @@ impl MagpieClient {
             );
 
             let timer = std::time::Instant::now();
-            readings.user_services = magpie.user_services();
+            readings.user_services = services.user_services(&magpie);
+            ignored.filter_services(&mut readings.user_services);
             g_debug!(
                 "MissionCenter::Perf",
                 "User services load took: {:?}",
@@ -834,13 +1258,46 @@ impl MagpieClient {
             );
 
             let timer = std::time::Instant::now();
-            readings.system_services = magpie.system_services();
+            readings.system_services = services.system_services(&magpie);
+            ignored.filter_services(&mut readings.system_services);
             g_debug!(
                 "MissionCenter::Perf",
                 "System services load took: {:?}",
                 timer.elapsed()
             );
 
+            readings.service_commands.clear();
+            unit_files.commands(
+                readings
+                    .user_services
+                    .values()
+                    .chain(readings.system_services.values()),
+                &mut readings.service_commands,
+            );
+
+            readings.masked_services.clear();
+            unit_files::masked(
+                readings
+                    .user_services
+                    .values()
+                    .chain(readings.system_services.values()),
+                &mut readings.masked_services,
+            );
+
+            readings.service_transitions.clear();
+            services.transitions(
+                &readings.user_services,
+                &readings.system_services,
+                &mut readings.service_transitions,
+            );
+
+            readings.service_stats.clear();
+            services.stats(
+                &readings.user_services,
+                &readings.system_services,
+                &mut readings.service_stats,
+            );
+
             readings
                 .disks_info
                 .sort_unstable_by(|d1, d2| d1.id.cmp(&d2.id));
@@ -848,12 +1305,45 @@ impl MagpieClient {
                 .network_connections
                 .sort_unstable_by(|n1, n2| n1.id.cmp(&n2.id));
 
+            if let Ok(metrics_exporter) = metrics_exporter.lock() {
+                if let Some(metrics_exporter) = metrics_exporter.as_ref() {
+                    let timer = std::time::Instant::now();
+                    metrics_exporter.publish(&readings);
+                    g_debug!(
+                        "MissionCenter::Perf",
+                        "Metrics export took: {:?}",
+                        timer.elapsed()
+                    );
+                }
+            }
+
+            if record_usage {
+                let timer = std::time::Instant::now();
+                usage_history::record(&readings);
+                g_debug!(
+                    "MissionCenter::Perf",
+                    "Usage history update took: {:?}",
+                    timer.elapsed()
+                );
+            }
+
+            let host = recording_host.read().ok().and_then(|host| host.clone());
+            if let Some(host) = host {
+                let timer = std::time::Instant::now();
+                readings.session_frame = Frame::capture(&readings, &host);
+                g_debug!(
+                    "MissionCenter::Perf",
+                    "Session recording took: {:?}",
+                    timer.elapsed()
+                );
+            }
+
             if !running.load(atomic::Ordering::Acquire) {
                 break 'read_loop;
             }
 
             idle_add_once({
-                let mut new_readings = Readings {
+                let new_readings = Readings {
                     cpu: readings.cpu.clone(),
                     mem_info: readings.mem_info.clone(),
                     mem_devices: readings.mem_devices.clone(),
@@ -863,16 +1353,30 @@ impl MagpieClient {
                     gpus: std::mem::take(&mut readings.gpus),
                     running_apps: std::mem::take(&mut readings.running_apps),
                     running_processes: std::mem::take(&mut readings.running_processes),
+                    process_changes: std::mem::take(&mut readings.process_changes),
                     network_stats_error: std::mem::take(&mut readings.network_stats_error),
                     user_services: std::mem::take(&mut readings.user_services),
                     system_services: std::mem::take(&mut readings.system_services),
+                    service_commands: std::mem::take(&mut readings.service_commands),
+                    service_transitions: std::mem::take(&mut readings.service_transitions),
+                    service_stats: std::mem::take(&mut readings.service_stats),
+                    masked_services: std::mem::take(&mut readings.masked_services),
+                    path_filter_pids: std::mem::take(&mut readings.path_filter_pids),
+                    session_frame: readings.session_frame.take(),
                 };
 
+                let recycle_tx = recycle_tx.clone();
+
                 move || {
                     let app = app!();
+                    if !app.is_current_source(generation) {
+                        return;
+                    }
+
                     let now = std::time::Instant::now();
                     let timer = std::time::Instant::now();
-                    if !app.refresh_readings(&mut new_readings) {
+                    let (refreshed, stale_readings) = app.refresh_readings(new_readings);
+                    if !refreshed {
                         g_critical!(
                             "MissionCenter::SysInfo",
                             "Readings were not completely refreshed, stale readings will be displayed"
@@ -888,11 +1392,22 @@ impl MagpieClient {
                         "Refreshed readings in {:?}",
                         now.elapsed()
                     );
+
+                    if let Some(stale_readings) = stale_readings {
+                        let _ = recycle_tx.send(stale_readings);
+                    }
                 }
             });
 
+            let was_throttled = throttled.load(atomic::Ordering::Acquire);
+            let interval_factor = if was_throttled {
+                THROTTLED_INTERVAL_FACTOR
+            } else {
+                1.
+            };
             let mut wait_time = Duration::from_millis(
-                ((speed.load(atomic::Ordering::Relaxed) as f64 * INTERVAL_STEP) * 1000.) as u64,
+                ((speed.load(atomic::Ordering::Relaxed) as f64 * INTERVAL_STEP * interval_factor)
+                    * 1000.) as u64,
             )
             .saturating_sub(loop_start.elapsed());
 
@@ -902,7 +1417,13 @@ impl MagpieClient {
             for _ in 0..ITERATIONS_COUNT {
                 let wait_timer = std::time::Instant::now();
 
-                if !Self::handle_incoming_message(&magpie, &mut rx, &mut tx, wait_time_fraction) {
+                if !Self::handle_incoming_message(
+                    &magpie,
+                    services.as_ref(),
+                    &mut rx,
+                    &mut tx,
+                    wait_time_fraction,
+                ) {
                     break 'read_loop;
                 }
 
@@ -910,13 +1431,24 @@ impl MagpieClient {
                     break 'read_loop;
                 }
 
+                if was_throttled && !throttled.load(atomic::Ordering::Acquire) {
+                    wait_time = Duration::ZERO;
+                    break;
+                }
+
                 wait_time = wait_time.saturating_sub(wait_timer.elapsed());
                 if wait_time.is_zero() {
                     break;
                 }
             }
 
-            if !Self::handle_incoming_message(&magpie, &mut rx, &mut tx, wait_time) {
+            if !Self::handle_incoming_message(
+                &magpie,
+                services.as_ref(),
+                &mut rx,
+                &mut tx,
+                wait_time,
+            ) {
                 break 'read_loop;
             }
 
@@ -926,5 +1458,9 @@ impl MagpieClient {
                 "Full read-publish cycle took {elapsed_since_start:?}",
             );
         }
+
+        if record_usage {
+            usage_history::save();
+        }
     }
 }
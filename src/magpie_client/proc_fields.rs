@@ -0,0 +1,499 @@
+/* magpie_client/proc_fields.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, OnceLock};
+
+use gtk::prelude::*;
+use gtk::{gio, glib};
+
+use crate::i18n::i18n;
+
+/// Per-process values that are not provided by Magpie, but can be shown as extra columns.
+/// They are read from `/proc` on the gather thread.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ProcField {
+    OomScore,
+    OomScoreAdjustment,
+    Priority,
+    Nice,
+    Threads,
+    ContextSwitches,
+}
+
+impl ProcField {
+    pub const ALL: &'static [ProcField] = &[
+        ProcField::OomScore,
+        ProcField::OomScoreAdjustment,
+        ProcField::Priority,
+        ProcField::Nice,
+        ProcField::Threads,
+        ProcField::ContextSwitches,
+    ];
+
+    /// The name the field is stored as in the settings
+    pub fn id(&self) -> &'static str {
+        match self {
+            ProcField::OomScore => "oom_score",
+            ProcField::OomScoreAdjustment => "oom_score_adj",
+            ProcField::Priority => "priority",
+            ProcField::Nice => "nice",
+            ProcField::Threads => "threads",
+            ProcField::ContextSwitches => "context_switches",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|field| field.id() == id)
+    }
+
+    /// The fields the user added as extra columns, unknown fields are skipped
+    pub fn enabled(settings: &gio::Settings) -> Vec<Self> {
+        settings
+            .strv("apps-page-custom-columns")
+            .iter()
+            .filter_map(|id| Self::from_id(id.as_str()))
+            .collect()
+    }
+
+    pub fn title(&self) -> String {
+        match self {
+            ProcField::OomScore => i18n("OOM Score"),
+            ProcField::OomScoreAdjustment => i18n("OOM Score\nAdjustment"),
+            ProcField::Priority => i18n("Priority"),
+            ProcField::Nice => i18n("Nice"),
+            ProcField::Threads => i18n("Threads"),
+            ProcField::ContextSwitches => i18n("Context\nSwitches"),
+        }
+    }
+}
+
+/// The scheduling state of a process, as the single letter code in `/proc/<pid>/stat`
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash, glib::Enum)]
+#[enum_type(name = "ProcessState")]
+pub enum ProcessState {
+    #[default]
+    Unknown,
+    Idle,
+    Sleeping,
+    Running,
+    Stopped,
+    TracingStop,
+    DiskSleep,
+    Zombie,
+    Dead,
+}
+
+impl ProcessState {
+    pub fn from_code(code: char) -> Self {
+        match code {
+            'R' => ProcessState::Running,
+            'S' => ProcessState::Sleeping,
+            'D' => ProcessState::DiskSleep,
+            'Z' => ProcessState::Zombie,
+            'T' => ProcessState::Stopped,
+            't' => ProcessState::TracingStop,
+            'I' => ProcessState::Idle,
+            'X' | 'x' => ProcessState::Dead,
+            _ => ProcessState::Unknown,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ProcessState::Unknown => "",
+            ProcessState::Idle => "I",
+            ProcessState::Sleeping => "S",
+            ProcessState::Running => "R",
+            ProcessState::Stopped => "T",
+            ProcessState::TracingStop => "t",
+            ProcessState::DiskSleep => "D",
+            ProcessState::Zombie => "Z",
+            ProcessState::Dead => "X",
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            ProcessState::Unknown => i18n("Unknown"),
+            ProcessState::Idle => i18n("Idle kernel thread"),
+            ProcessState::Sleeping => i18n("Sleeping"),
+            ProcessState::Running => i18n("Running"),
+            ProcessState::Stopped => i18n("Stopped"),
+            ProcessState::TracingStop => i18n("Stopped by a debugger"),
+            ProcessState::DiskSleep => {
+                i18n("Waiting for I/O, can't be interrupted or killed until it completes")
+            }
+            ProcessState::Zombie => {
+                i18n("Zombie, exited but was not yet collected by its parent process")
+            }
+            ProcessState::Dead => i18n("Dead"),
+        }
+    }
+
+    /// Zombies and processes stuck waiting for I/O usually point to a problem elsewhere, and
+    /// can't be ended like other processes
+    pub fn is_problem(&self) -> bool {
+        matches!(self, ProcessState::DiskSleep | ProcessState::Zombie)
+    }
+}
+
+// The unit of the CPU times in `/proc`, `USER_HZ`, which is 100 on every architecture but Alpha
+const CLOCK_TICKS_PER_SECOND: f64 = 100.;
+
+/// What `read_stat_fields` reads from `/proc/<pid>/stat`
+pub struct StatFields {
+    pub state: ProcessState,
+    /// Seconds of CPU time used so far, in user and in kernel mode
+    pub cpu_time: f64,
+    /// Page faults so far that had to read from disk
+    pub major_faults: u64,
+    /// Seconds spent waiting for block I/O so far, only counted when the kernel has delay
+    /// accounting enabled
+    pub io_wait_time: f64,
+    /// When the process started, in clock ticks since boot. Tells apart processes that got the
+    /// same PID one after the other.
+    pub start_time: u64,
+}
+
+/// Reads the state of the process `pid`, the field right after the command name, along with the
+/// counters the columns of the process turn into totals and rates
+pub fn read_stat_fields(pid: u32) -> Option<StatFields> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    let (_, fields) = stat.rsplit_once(')')?;
+    let mut fields = fields.split_ascii_whitespace();
+
+    let code = fields.next()?.chars().next()?;
+    // `majflt`, field 12, 9 places after the state
+    let major_faults = fields.nth(8)?.parse::<u64>().ok()?;
+    // `utime` and `stime`, fields 14 and 15
+    let user_time = fields.nth(1)?.parse::<u64>().ok()?;
+    let system_time = fields.next()?.parse::<u64>().ok()?;
+    // `starttime`, field 22
+    let start_time = fields.nth(6)?.parse::<u64>().ok()?;
+    // `delayacct_blkio_ticks`, field 42, missing from older kernels
+    let io_wait_ticks = fields
+        .nth(19)
+        .and_then(|field| field.parse::<u64>().ok())
+        .unwrap_or_default();
+
+    Some(StatFields {
+        state: ProcessState::from_code(code),
+        cpu_time: (user_time + system_time) as f64 / CLOCK_TICKS_PER_SECOND,
+        major_faults,
+        io_wait_time: io_wait_ticks as f64 / CLOCK_TICKS_PER_SECOND,
+        start_time,
+    })
+}
+
+/// When the process `pid` started, in clock ticks since boot
+pub fn read_start_time(pid: u32) -> Option<u64> {
+    read_stat_fields(pid).map(|stat| stat.start_time)
+}
+
+/// Reads how the process `pid` ended, as a wait status. Only zombies have one, the kernel keeps
+/// it until their parent collects them.
+pub fn read_exit_status(pid: u32) -> Option<i32> {
+    // `exit_code` is field 52, counting from the state which is the third field
+    let status = read_stat(pid)?.get(49).copied()?;
+    i32::try_from(status).ok()
+}
+
+/// Reads `fields` for the process `pid`, each file in `/proc` is read at most once
+pub fn read_fields(pid: u32, fields: &[ProcField]) -> Vec<(ProcField, i64)> {
+    let mut stat = None;
+    let mut status = None;
+
+    let mut result = Vec::with_capacity(fields.len());
+    for field in fields {
+        let value = match field {
+            ProcField::OomScore => read_number(pid, "oom_score"),
+            ProcField::OomScoreAdjustment => read_number(pid, "oom_score_adj"),
+            ProcField::Priority | ProcField::Nice | ProcField::Threads => {
+                let stat = stat.get_or_insert_with(|| read_stat(pid));
+                // Fields 18, 19 and 20 of `/proc/<pid>/stat`, counting from the state which is
+                // the third field
+                let index = match field {
+                    ProcField::Priority => 15,
+                    ProcField::Nice => 16,
+                    _ => 17,
+                };
+                stat.as_ref().and_then(|stat| stat.get(index).copied())
+            }
+            ProcField::ContextSwitches => {
+                let status = status.get_or_insert_with(|| read_status(pid));
+                status.as_deref().and_then(context_switches)
+            }
+        };
+
+        if let Some(value) = value {
+            result.push((*field, value));
+        }
+    }
+
+    result
+}
+
+fn read_number(pid: u32, file: &str) -> Option<i64> {
+    std::fs::read_to_string(format!("/proc/{pid}/{file}"))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+// The fields following the command name, which is the only one that can contain spaces
+fn read_stat(pid: u32) -> Option<Vec<i64>> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    let (_, fields) = stat.rsplit_once(')')?;
+
+    Some(
+        fields
+            .split_ascii_whitespace()
+            .map(|field| field.parse().unwrap_or_default())
+            .collect(),
+    )
+}
+
+/// The contents of `/proc/<pid>/status`, which `swap_usage` and `context_switches` read from
+pub fn read_status(pid: u32) -> Option<String> {
+    std::fs::read_to_string(format!("/proc/{pid}/status")).ok()
+}
+
+/// How much of the memory of the process is swapped out, in bytes
+pub fn swap_usage(status: &str) -> Option<u64> {
+    let value = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmSwap:"))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+
+    Some(value * 1024)
+}
+
+/// The voluntary and involuntary context switches of the process so far
+pub fn context_switches(status: &str) -> Option<i64> {
+    let mut total = None;
+    for line in status.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        if key != "voluntary_ctxt_switches" && key != "nonvoluntary_ctxt_switches" {
+            continue;
+        }
+
+        if let Ok(value) = value.trim().parse::<i64>() {
+            *total.get_or_insert(0) += value;
+        }
+    }
+
+    total
+}
+
+/// Which of the per-process fields could be read for a process. Some of them are only
+/// readable by the owner of the process or by a privileged user.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct FieldAvailability {
+    pub command_line: bool,
+    pub io_stats: bool,
+}
+
+impl Default for FieldAvailability {
+    fn default() -> Self {
+        Self {
+            command_line: true,
+            io_stats: true,
+        }
+    }
+}
+
+pub fn read_field_availability(pid: u32) -> FieldAvailability {
+    let proc_path = format!("/proc/{pid}");
+    if std::fs::metadata(&proc_path).is_err() {
+        return FieldAvailability::default();
+    }
+
+    // The command line itself can be read by everyone, but not the executable it runs. Kernel
+    // threads have none, which is not a matter of permissions.
+    let executable_denied = std::fs::read_link(format!("{proc_path}/exe"))
+        .is_err_and(|e| e.kind() == std::io::ErrorKind::PermissionDenied);
+
+    FieldAvailability {
+        command_line: !executable_denied
+            && std::fs::File::open(format!("{proc_path}/cmdline")).is_ok(),
+        io_stats: std::fs::File::open(format!("{proc_path}/io")).is_ok(),
+    }
+}
+
+/// The name of the user the process runs as, `None` if the process is gone. Users that are not
+/// listed in `/etc/passwd`, like those of a directory service, are named by their ID.
+pub fn read_user_name(pid: u32) -> Option<Arc<str>> {
+    use std::os::unix::fs::MetadataExt;
+
+    static USER_NAMES: OnceLock<HashMap<u32, Arc<str>>> = OnceLock::new();
+
+    let uid = std::fs::metadata(format!("/proc/{pid}")).ok()?.uid();
+    let user_names = USER_NAMES.get_or_init(|| {
+        std::fs::read_to_string("/etc/passwd")
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split(':');
+                let name = fields.next()?;
+                let uid = fields.nth(1)?.parse().ok()?;
+                Some((uid, Arc::from(name)))
+            })
+            .collect()
+    });
+
+    Some(
+        user_names
+            .get(&uid)
+            .cloned()
+            .unwrap_or_else(|| Arc::from(uid.to_string())),
+    )
+}
+
+// Calls whose first argument is a file descriptor, and that can block on the file behind it
+#[cfg(target_arch = "x86_64")]
+const FD_SYSCALLS: &[u64] = &[0, 1, 16, 17, 18, 19, 20, 72, 73, 74, 75, 217];
+#[cfg(target_arch = "aarch64")]
+const FD_SYSCALLS: &[u64] = &[25, 29, 32, 61, 63, 64, 65, 66, 67, 68, 82, 83];
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+const FD_SYSCALLS: &[u64] = &[];
+
+/// What the process is waiting on: the kernel function it sleeps in and, for processes
+/// stuck waiting for I/O, the file they are blocked on if it can be told. Empty for running
+/// processes and when nothing can be read.
+pub fn read_wait_channel(pid: u32, disk_sleep: bool) -> String {
+    // Running processes have a wait channel of `0`
+    let wait_channel = std::fs::read_to_string(format!("/proc/{pid}/wchan"))
+        .ok()
+        .map(|wait_channel| wait_channel.trim().to_owned())
+        .filter(|wait_channel| !wait_channel.is_empty() && wait_channel != "0")
+        .unwrap_or_default();
+    if !disk_sleep {
+        return wait_channel;
+    }
+
+    match (blocked_on_file(pid), wait_channel.is_empty()) {
+        (Some(file), true) => file,
+        (Some(file), false) => format!("{} ({})", wait_channel, file),
+        (None, _) => wait_channel,
+    }
+}
+
+// `/proc/<pid>/syscall` holds the number of the call the process is in, followed by its
+// arguments in hexadecimal. It is only readable by the owner of the process.
+fn blocked_on_file(pid: u32) -> Option<String> {
+    let syscall = std::fs::read_to_string(format!("/proc/{pid}/syscall")).ok()?;
+    let mut fields = syscall.split_ascii_whitespace();
+
+    let number = fields.next()?.parse::<u64>().ok()?;
+    if !FD_SYSCALLS.contains(&number) {
+        return None;
+    }
+
+    let fd = u64::from_str_radix(fields.next()?.trim_start_matches("0x"), 16).ok()?;
+    let path = std::fs::read_link(format!("/proc/{pid}/fd/{fd}")).ok()?;
+    Some(path.to_string_lossy().into_owned())
+}
+
+/// The PCI addresses of the GPUs the process `pid` has open, as reported by the DRM drivers in
+/// `/proc/<pid>/fdinfo`. Drivers that don't report `drm-pdev` are not listed.
+pub fn read_gpu_devices(pid: u32) -> Vec<String> {
+    let Ok(fds) = std::fs::read_dir(format!("/proc/{pid}/fd")) else {
+        return vec![];
+    };
+
+    let mut devices = vec![];
+    for fd in fds.flatten() {
+        let is_drm_device =
+            std::fs::read_link(fd.path()).is_ok_and(|target| target.starts_with("/dev/dri/"));
+        if !is_drm_device {
+            continue;
+        }
+
+        let Ok(fdinfo) = std::fs::read_to_string(format!(
+            "/proc/{pid}/fdinfo/{}",
+            fd.file_name().to_string_lossy()
+        )) else {
+            continue;
+        };
+        let Some(device) = fdinfo
+            .lines()
+            .find_map(|line| line.strip_prefix("drm-pdev:"))
+            .map(str::trim)
+        else {
+            continue;
+        };
+
+        if !devices.iter().any(|known| known == device) {
+            devices.push(device.to_owned());
+        }
+    }
+    devices.sort_unstable();
+
+    devices
+}
+
+/// The processes that have a file under `path` open, mapped into memory, as their executable or
+/// as their working directory. `path` can be a single file or a directory, such as the mount
+/// point of a drive. `None` if `/proc` can't be read.
+///
+/// Only the processes of the user are seen, unless Mission Center runs as root.
+pub fn processes_using(path: &std::path::Path) -> Option<HashSet<u32>> {
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let is_under_path = |target: &std::path::Path| target.starts_with(&path);
+
+    let mut pids = HashSet::new();
+    for entry in std::fs::read_dir("/proc").ok()?.flatten() {
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        let proc_path = entry.path();
+
+        let uses_path = ["cwd", "exe"].iter().any(|link| {
+            std::fs::read_link(proc_path.join(link)).is_ok_and(|target| is_under_path(&target))
+        }) || std::fs::read_dir(proc_path.join("fd")).is_ok_and(|fds| {
+            fds.flatten()
+                .any(|fd| std::fs::read_link(fd.path()).is_ok_and(|target| is_under_path(&target)))
+        }) || std::fs::read_to_string(proc_path.join("maps")).is_ok_and(|maps| {
+            // The path is the last field, none of the address, permissions, offset, device and
+            // inode before it contains a slash
+            maps.lines()
+                .filter_map(|line| line.find('/').map(|start| &line[start..]))
+                .any(|target| is_under_path(std::path::Path::new(target)))
+        });
+        if uses_path {
+            pids.insert(pid);
+        }
+    }
+
+    Some(pids)
+}
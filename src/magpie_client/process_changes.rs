@@ -0,0 +1,788 @@
+/* magpie_client/process_changes.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+
+use magpie_types::processes::{Process, ProcessUsageStats};
+
+use super::proc_fields::{
+    context_switches, read_exit_status, read_field_availability, read_fields, read_gpu_devices,
+    read_stat_fields, read_status, read_user_name, read_wait_channel, swap_usage,
+    FieldAvailability, ProcField, ProcessState,
+};
+use super::thermal_pressure::ThermalPressure;
+
+/// Everything the process views need that can be worked out from the readings alone. It is
+/// computed on the gather thread, so that the UI thread only has to apply it to the models.
+#[derive(Debug, Default, Clone)]
+pub struct ProcessChangeSet {
+    merged_usage_stats: HashMap<u32, ProcessUsageStats>,
+    parents: HashMap<u32, u32>,
+    children_changed: HashSet<u32>,
+    // What is read from `/proc` besides the readings is also saved with snapshots
+    pub(super) unit_processes: HashMap<String, Vec<u32>>,
+    custom_values: HashMap<u32, Vec<(ProcField, i64)>>,
+    pub(super) swap_usage: HashMap<u32, u64>,
+    merged_swap_usage: HashMap<u32, u64>,
+    cpu_times: HashMap<u32, f64>,
+    merged_cpu_times: HashMap<u32, f64>,
+    context_switch_rates: HashMap<u32, f32>,
+    merged_context_switch_rates: HashMap<u32, f32>,
+    major_fault_rates: HashMap<u32, f32>,
+    merged_major_fault_rates: HashMap<u32, f32>,
+    io_wait: HashMap<u32, f32>,
+    merged_io_wait: HashMap<u32, f32>,
+    pub(super) states: HashMap<u32, ProcessState>,
+    problem_subtrees: HashSet<u32>,
+    descendant_counts: HashMap<u32, u32>,
+    pub(super) gpu_devices: HashMap<u32, Vec<String>>,
+    merged_gpu_devices: HashMap<u32, Vec<String>>,
+    events: Vec<ProcessEvent>,
+    pub(super) thermal_pressure: Option<ThermalPressure>,
+    outside_login_session: HashSet<u32>,
+    // Only the processes with fields that can't be read are listed
+    restricted_fields: HashMap<u32, FieldAvailability>,
+    user_names: HashMap<u32, Arc<str>>,
+    wait_channels: HashMap<u32, String>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ProcessEventKind {
+    Started,
+    Exited,
+}
+
+/// How a process ended
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExitReason {
+    /// Returned from `main` or called `exit` with this status
+    Status(i32),
+    /// Was killed by this signal
+    Signal { signal: i32, core_dumped: bool },
+}
+
+impl ExitReason {
+    // Decodes a status as returned by `waitpid`
+    fn from_wait_status(status: i32) -> Self {
+        match status & 0x7f {
+            0 => ExitReason::Status((status >> 8) & 0xff),
+            signal => ExitReason::Signal {
+                signal,
+                core_dumped: status & 0x80 != 0,
+            },
+        }
+    }
+
+    /// Anything but exiting with status 0
+    pub fn is_failure(&self) -> bool {
+        *self != ExitReason::Status(0)
+    }
+}
+
+/// A process that showed up or went away since the previous readings. Processes that started
+/// and exited in between are never seen.
+#[derive(Debug, Clone)]
+pub struct ProcessEvent {
+    pub kind: ProcessEventKind,
+    pub pid: u32,
+    pub name: String,
+    /// Magpie does not say how a process exited, so this is only known for processes that were
+    /// seen as zombies before their parent collected them
+    pub exit_reason: Option<ExitReason>,
+}
+
+impl ProcessChangeSet {
+    /// The usage of `process` including that of all of its descendants
+    pub fn merged_usage_stats<'a>(&'a self, process: &'a Process) -> &'a ProcessUsageStats {
+        self.merged_usage_stats
+            .get(&process.pid)
+            .unwrap_or(&process.usage_stats)
+    }
+
+    /// Whether the process is new, or gained or lost children since the previous readings
+    pub fn children_changed(&self, pid: u32) -> bool {
+        self.children_changed.contains(&pid)
+    }
+
+    pub fn parent(&self, pid: u32) -> Option<u32> {
+        self.parents.get(&pid).copied()
+    }
+
+    /// All processes that run in the cgroup of the systemd service `service_name`, empty if
+    /// this is not known
+    pub fn service_processes(&self, service_name: &str) -> &[u32] {
+        self.unit_processes
+            .get(service_name)
+            .or_else(|| self.unit_processes.get(&format!("{service_name}.service")))
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// The values of the extra columns that could be read for the process
+    pub fn custom_values(&self, pid: u32) -> &[(ProcField, i64)] {
+        self.custom_values
+            .get(&pid)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// Bytes of the memory of the process that are swapped out, 0 if this is not known
+    pub fn swap_usage(&self, pid: u32) -> u64 {
+        self.swap_usage.get(&pid).copied().unwrap_or_default()
+    }
+
+    /// Like `swap_usage`, but including all of the descendants of the process
+    pub fn merged_swap_usage(&self, pid: u32) -> u64 {
+        self.merged_swap_usage
+            .get(&pid)
+            .copied()
+            .unwrap_or_else(|| self.swap_usage(pid))
+    }
+
+    /// Seconds of CPU time the process used since it started, 0 if this is not known
+    pub fn cpu_time(&self, pid: u32) -> f64 {
+        self.cpu_times.get(&pid).copied().unwrap_or_default()
+    }
+
+    /// Like `cpu_time`, but including the descendants of the process that are still running
+    pub fn merged_cpu_time(&self, pid: u32) -> f64 {
+        self.merged_cpu_times
+            .get(&pid)
+            .copied()
+            .unwrap_or_else(|| self.cpu_time(pid))
+    }
+
+    /// Context switches per second since the previous readings, 0 if this is not known
+    pub fn context_switch_rate(&self, pid: u32) -> f32 {
+        self.context_switch_rates
+            .get(&pid)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn merged_context_switch_rate(&self, pid: u32) -> f32 {
+        self.merged_context_switch_rates
+            .get(&pid)
+            .copied()
+            .unwrap_or_else(|| self.context_switch_rate(pid))
+    }
+
+    /// Page faults per second since the previous readings that had to read from disk, 0 if this
+    /// is not known
+    pub fn major_fault_rate(&self, pid: u32) -> f32 {
+        self.major_fault_rates
+            .get(&pid)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Like `major_fault_rate`, but including all of the descendants of the process
+    pub fn merged_major_fault_rate(&self, pid: u32) -> f32 {
+        self.merged_major_fault_rates
+            .get(&pid)
+            .copied()
+            .unwrap_or_else(|| self.major_fault_rate(pid))
+    }
+
+    /// The percentage of the time since the previous readings the process spent waiting for
+    /// block I/O, 0 if this is not known
+    pub fn io_wait(&self, pid: u32) -> f32 {
+        self.io_wait.get(&pid).copied().unwrap_or_default()
+    }
+
+    /// Like `io_wait`, but adding up the waits of all of the descendants of the process
+    pub fn merged_io_wait(&self, pid: u32) -> f32 {
+        self.merged_io_wait
+            .get(&pid)
+            .copied()
+            .unwrap_or_else(|| self.io_wait(pid))
+    }
+
+    /// The number of processes below the process in the process tree
+    pub fn descendant_count(&self, pid: u32) -> u32 {
+        self.descendant_counts
+            .get(&pid)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The PCI addresses of the GPUs the process uses, sorted
+    pub fn gpu_devices(&self, pid: u32) -> &[String] {
+        self.gpu_devices.get(&pid).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Like `gpu_devices`, but including the GPUs used by the descendants of the process
+    pub fn merged_gpu_devices(&self, pid: u32) -> &[String] {
+        self.merged_gpu_devices
+            .get(&pid)
+            .map(Vec::as_slice)
+            .unwrap_or_else(|| self.gpu_devices(pid))
+    }
+
+    pub fn state(&self, pid: u32) -> ProcessState {
+        self.states.get(&pid).copied().unwrap_or_default()
+    }
+
+    /// Whether the process, or one of its descendants, is a zombie or stuck waiting for I/O
+    pub fn has_problem_state(&self, pid: u32) -> bool {
+        self.problem_subtrees.contains(&pid)
+    }
+
+    /// Whether the process runs in the login session of the user, that is in one of the cgroups
+    /// of their sessions or of their service manager. Kernel threads, system services and the
+    /// processes of other users don't. Everything counts as part of the session when this can't
+    /// be told, such as from inside a sandbox.
+    pub fn in_login_session(&self, pid: u32) -> bool {
+        !self.outside_login_session.contains(&pid)
+    }
+
+    /// Which of the fields of the process could be read, all of them if this can't be told
+    pub fn field_availability(&self, pid: u32) -> FieldAvailability {
+        self.restricted_fields
+            .get(&pid)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The name of the user the process runs as, empty if this can't be told
+    pub fn user_name(&self, pid: u32) -> &str {
+        self.user_names.get(&pid).map(|name| &**name).unwrap_or("")
+    }
+
+    /// What the process is waiting on, empty if it is not waiting or wait channels are not read
+    pub fn wait_channel(&self, pid: u32) -> &str {
+        self.wait_channels
+            .get(&pid)
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+
+    /// `None` while the CPU temperature is unknown
+    pub fn thermal_pressure(&self) -> Option<&ThermalPressure> {
+        self.thermal_pressure.as_ref()
+    }
+
+    /// Processes that started or exited since the previous readings, by PID
+    pub fn events(&self) -> &[ProcessEvent] {
+        &self.events
+    }
+
+    /// Makes every process count as having new children, so that the views rebuild their
+    /// trees, e.g. after they showed other readings in between
+    pub fn mark_children_changed(&mut self, processes: &HashMap<u32, Process>) {
+        self.children_changed.extend(processes.keys().copied());
+    }
+
+    /// Puts together the change set of saved readings, out of what was read from `/proc` when
+    /// they were saved. Nothing is read from this machine.
+    pub(super) fn restore(
+        processes: &HashMap<u32, Process>,
+        states: HashMap<u32, ProcessState>,
+        swap_usage: HashMap<u32, u64>,
+        gpu_devices: HashMap<u32, Vec<String>>,
+        unit_processes: HashMap<String, Vec<u32>>,
+    ) -> Self {
+        let mut change_set = ProcessChangeSet {
+            states,
+            swap_usage,
+            gpu_devices,
+            unit_processes,
+            ..Default::default()
+        };
+
+        for process in processes.values() {
+            for child in &process.children {
+                change_set.parents.insert(*child, process.pid);
+            }
+            change_set.children_changed.insert(process.pid);
+
+            merge_usage_stats(processes, process, &mut change_set.merged_usage_stats);
+            count_descendants(processes, process, &mut change_set.descendant_counts);
+        }
+        change_set.merge_subtrees(processes);
+
+        change_set
+    }
+
+    // Sums up what was read for each process over its subtree
+    fn merge_subtrees(&mut self, processes: &HashMap<u32, Process>) {
+        let problem_pids = self
+            .states
+            .iter()
+            .filter(|(_, state)| state.is_problem())
+            .map(|(pid, _)| *pid)
+            .collect::<Vec<_>>();
+        if !problem_pids.is_empty() {
+            self.problem_subtrees = self.ancestors(problem_pids.iter().copied());
+            self.problem_subtrees.extend(problem_pids);
+        }
+
+        self.merged_swap_usage = merge_subtrees(processes, &self.swap_usage);
+        self.merged_cpu_times = merge_subtrees(processes, &self.cpu_times);
+        self.merged_context_switch_rates = merge_subtrees(processes, &self.context_switch_rates);
+        self.merged_major_fault_rates = merge_subtrees(processes, &self.major_fault_rates);
+        self.merged_io_wait = merge_subtrees(processes, &self.io_wait);
+
+        if !self.gpu_devices.is_empty() {
+            self.merged_gpu_devices = merge_gpu_devices(self);
+        }
+    }
+
+    // The maps keep their capacity
+    fn clear(&mut self) {
+        self.merged_usage_stats.clear();
+        self.parents.clear();
+        self.children_changed.clear();
+        self.unit_processes.clear();
+        self.custom_values.clear();
+        self.swap_usage.clear();
+        self.merged_swap_usage.clear();
+        self.cpu_times.clear();
+        self.merged_cpu_times.clear();
+        self.context_switch_rates.clear();
+        self.merged_context_switch_rates.clear();
+        self.major_fault_rates.clear();
+        self.merged_major_fault_rates.clear();
+        self.io_wait.clear();
+        self.merged_io_wait.clear();
+        self.states.clear();
+        self.problem_subtrees.clear();
+        self.descendant_counts.clear();
+        self.gpu_devices.clear();
+        self.merged_gpu_devices.clear();
+        self.events.clear();
+        self.thermal_pressure = None;
+        self.outside_login_session.clear();
+        self.restricted_fields.clear();
+        self.user_names.clear();
+        self.wait_channels.clear();
+    }
+
+    /// Returns the PIDs of all processes that have one of `pids` as a descendant
+    pub fn ancestors(&self, pids: impl IntoIterator<Item = u32>) -> HashSet<u32> {
+        let mut result = HashSet::new();
+        for mut pid in pids {
+            while let Some(parent) = self.parents.get(&pid) {
+                if !result.insert(*parent) {
+                    break;
+                }
+                pid = *parent;
+            }
+        }
+
+        result
+    }
+}
+
+/// Produces a `ProcessChangeSet` for every new set of readings, remembering what is needed
+/// from the previous ones
+#[derive(Default)]
+pub struct ProcessDiffer {
+    previous_children: HashMap<u32, Vec<u32>>,
+    // Kept to name the processes that exited
+    previous_names: HashMap<u32, String>,
+    // Read while processes are zombies, which they only are until their parent collects them
+    exit_reasons: HashMap<u32, ExitReason>,
+    // Processes rarely move to a different cgroup, and neither their user nor what they let be
+    // read changes, so all of it is only read once per process. Kept with the start time of the
+    // process, in case its PID gets used again.
+    process_infos: HashMap<u32, ProcessInfo>,
+    // The context switch counts of the previous readings, to turn them into rates
+    previous_context_switches: HashMap<u32, i64>,
+    // Same for the major page faults and the seconds waited for block I/O
+    previous_major_faults: HashMap<u32, u64>,
+    previous_io_wait_times: HashMap<u32, f64>,
+    previous_diff: Option<Instant>,
+}
+
+impl ProcessDiffer {
+    /// `reused` is a change set the UI is done with, it is cleared and filled again so that its
+    /// maps don't have to be allocated anew with every readings. Wait channels are only read
+    /// with `read_wait_channels`, as they are only needed while a view shows them.
+    pub fn diff(
+        &mut self,
+        processes: &HashMap<u32, Process>,
+        custom_fields: &[ProcField],
+        read_wait_channels: bool,
+        reused: ProcessChangeSet,
+    ) -> ProcessChangeSet {
+        let mut change_set = reused;
+        change_set.clear();
+        change_set.merged_usage_stats.reserve(processes.len());
+        change_set.parents.reserve(processes.len());
+        change_set.cpu_times.reserve(processes.len());
+        change_set.context_switch_rates.reserve(processes.len());
+        change_set.major_fault_rates.reserve(processes.len());
+        change_set.io_wait.reserve(processes.len());
+        change_set.states.reserve(processes.len());
+        change_set.descendant_counts.reserve(processes.len());
+
+        let can_read_units = super::can_inspect_host();
+
+        let now = Instant::now();
+        let elapsed = self
+            .previous_diff
+            .replace(now)
+            .map(|previous_diff| now.duration_since(previous_diff).as_secs_f32())
+            .filter(|elapsed| *elapsed > 0.);
+        let mut context_switch_counts = HashMap::with_capacity(processes.len());
+        let mut major_fault_counts = HashMap::with_capacity(processes.len());
+        let mut io_wait_times = HashMap::with_capacity(processes.len());
+
+        for process in processes.values() {
+            for child in &process.children {
+                change_set.parents.insert(*child, process.pid);
+            }
+
+            if self.previous_children.get(&process.pid) != Some(&process.children) {
+                change_set.children_changed.insert(process.pid);
+            }
+
+            merge_usage_stats(processes, process, &mut change_set.merged_usage_stats);
+            count_descendants(processes, process, &mut change_set.descendant_counts);
+
+            // None of these are sent by Magpie, so they are read here like the extra columns
+            let mut start_time = None;
+            if can_read_units {
+                if let Some(status) = read_status(process.pid) {
+                    if let Some(swap_usage) = swap_usage(&status) {
+                        change_set.swap_usage.insert(process.pid, swap_usage);
+                    }
+                    if let Some(count) = context_switches(&status) {
+                        context_switch_counts.insert(process.pid, count);
+
+                        let previous = self.previous_context_switches.get(&process.pid);
+                        if let (Some(previous), Some(elapsed)) = (previous, elapsed) {
+                            let rate = (count - previous).max(0) as f32 / elapsed;
+                            change_set.context_switch_rates.insert(process.pid, rate);
+                        }
+                    }
+                }
+                if let Some(stat) = read_stat_fields(process.pid) {
+                    let state = stat.state;
+                    start_time = Some(stat.start_time);
+                    change_set.states.insert(process.pid, state);
+                    change_set.cpu_times.insert(process.pid, stat.cpu_time);
+                    major_fault_counts.insert(process.pid, stat.major_faults);
+                    io_wait_times.insert(process.pid, stat.io_wait_time);
+
+                    if let Some(elapsed) = elapsed {
+                        if let Some(previous) = self.previous_major_faults.get(&process.pid) {
+                            let rate = stat.major_faults.saturating_sub(*previous) as f32 / elapsed;
+                            change_set.major_fault_rates.insert(process.pid, rate);
+                        }
+                        if let Some(previous) = self.previous_io_wait_times.get(&process.pid) {
+                            let waited = (stat.io_wait_time - previous).max(0.) as f32;
+                            let percent = (waited / elapsed * 100.).min(100.);
+                            change_set.io_wait.insert(process.pid, percent);
+                        }
+                    }
+
+                    if read_wait_channels && state != ProcessState::Running {
+                        let wait_channel =
+                            read_wait_channel(process.pid, state == ProcessState::DiskSleep);
+                        if !wait_channel.is_empty() {
+                            change_set.wait_channels.insert(process.pid, wait_channel);
+                        }
+                    }
+
+                    if state == ProcessState::Zombie {
+                        if let Some(status) = read_exit_status(process.pid) {
+                            self.exit_reasons
+                                .insert(process.pid, ExitReason::from_wait_status(status));
+                        }
+                    }
+                }
+
+                // Going through the open files is only worth it for the few processes that
+                // use a GPU at all
+                let usage_stats = &process.usage_stats;
+                if usage_stats.gpu_usage > 0. || usage_stats.gpu_memory_usage > 0 {
+                    let devices = read_gpu_devices(process.pid);
+                    if !devices.is_empty() {
+                        change_set.gpu_devices.insert(process.pid, devices);
+                    }
+                }
+            }
+
+            if can_read_units && !custom_fields.is_empty() {
+                change_set
+                    .custom_values
+                    .insert(process.pid, read_fields(process.pid, custom_fields));
+            }
+
+            if can_read_units {
+                let stale = self.process_infos.get(&process.pid).map_or(true, |info| {
+                    start_time.is_some_and(|start_time| start_time != info.start_time)
+                });
+                if stale {
+                    let info = ProcessInfo::read(process.pid, start_time.unwrap_or_default());
+                    self.process_infos.insert(process.pid, info);
+                }
+                if let Some(info) = self.process_infos.get(&process.pid) {
+                    if let Some(unit) = &info.unit {
+                        change_set
+                            .unit_processes
+                            .entry(unit.clone())
+                            .or_default()
+                            .push(process.pid);
+                    }
+                    if !info.in_login_session {
+                        change_set.outside_login_session.insert(process.pid);
+                    }
+                    if info.field_availability != FieldAvailability::default() {
+                        change_set
+                            .restricted_fields
+                            .insert(process.pid, info.field_availability);
+                    }
+                    if let Some(user_name) = &info.user_name {
+                        change_set
+                            .user_names
+                            .insert(process.pid, Arc::clone(user_name));
+                    }
+                }
+            }
+        }
+        self.process_infos.retain(|pid, _| processes.contains_key(pid));
+        self.previous_context_switches = context_switch_counts;
+        self.previous_major_faults = major_fault_counts;
+        self.previous_io_wait_times = io_wait_times;
+
+        change_set.merge_subtrees(processes);
+
+        // Everything would count as started with the first readings
+        if !self.previous_names.is_empty() {
+            change_set.events = self.events(processes);
+        }
+        self.exit_reasons.retain(|pid, _| processes.contains_key(pid));
+        self.previous_names.clear();
+        self.previous_names.extend(
+            processes
+                .values()
+                .map(|process| (process.pid, process.name.clone())),
+        );
+
+        self.previous_children.clear();
+        self.previous_children.extend(
+            processes
+                .values()
+                .map(|process| (process.pid, process.children.clone())),
+        );
+
+        change_set
+    }
+
+    fn events(&self, processes: &HashMap<u32, Process>) -> Vec<ProcessEvent> {
+        let mut events = self
+            .previous_names
+            .iter()
+            .filter(|(pid, _)| !processes.contains_key(pid))
+            .map(|(pid, name)| ProcessEvent {
+                kind: ProcessEventKind::Exited,
+                pid: *pid,
+                name: name.clone(),
+                exit_reason: self.exit_reasons.get(pid).copied(),
+            })
+            .chain(
+                processes
+                    .values()
+                    .filter(|process| !self.previous_names.contains_key(&process.pid))
+                    .map(|process| ProcessEvent {
+                        kind: ProcessEventKind::Started,
+                        pid: process.pid,
+                        name: process.name.clone(),
+                        exit_reason: None,
+                    }),
+            )
+            .collect::<Vec<_>>();
+        events.sort_unstable_by_key(|event| event.pid);
+
+        events
+    }
+}
+
+// Every subtree is only summed up once, instead of once for each of its ancestors
+fn merge_usage_stats<'a>(
+    processes: &HashMap<u32, Process>,
+    process: &Process,
+    merged: &'a mut HashMap<u32, ProcessUsageStats>,
+) -> &'a ProcessUsageStats {
+    if !merged.contains_key(&process.pid) {
+        let mut usage_stats = process.usage_stats.clone();
+        for child in process.children.iter().filter_map(|pid| processes.get(pid)) {
+            usage_stats.merge(merge_usage_stats(processes, child, merged));
+        }
+        merged.insert(process.pid, usage_stats);
+    }
+
+    &merged[&process.pid]
+}
+
+fn count_descendants(
+    processes: &HashMap<u32, Process>,
+    process: &Process,
+    counts: &mut HashMap<u32, u32>,
+) -> u32 {
+    if let Some(count) = counts.get(&process.pid) {
+        return *count;
+    }
+
+    let mut count = 0;
+    for child in process.children.iter().filter_map(|pid| processes.get(pid)) {
+        count += 1 + count_descendants(processes, child, counts);
+    }
+    counts.insert(process.pid, count);
+
+    count
+}
+
+// Sums up a value that was read for each process over the subtree of every process
+fn merge_subtrees<T>(processes: &HashMap<u32, Process>, values: &HashMap<u32, T>) -> HashMap<u32, T>
+where
+    T: Copy + Default + std::ops::AddAssign,
+{
+    let mut merged = HashMap::new();
+    if values.is_empty() {
+        return merged;
+    }
+
+    merged.reserve(processes.len());
+    for process in processes.values() {
+        merge_subtree(processes, values, process, &mut merged);
+    }
+
+    merged
+}
+
+fn merge_subtree<T>(
+    processes: &HashMap<u32, Process>,
+    values: &HashMap<u32, T>,
+    process: &Process,
+    merged: &mut HashMap<u32, T>,
+) -> T
+where
+    T: Copy + Default + std::ops::AddAssign,
+{
+    if let Some(merged_value) = merged.get(&process.pid) {
+        return *merged_value;
+    }
+
+    let mut total = values.get(&process.pid).copied().unwrap_or_default();
+    for child in process.children.iter().filter_map(|pid| processes.get(pid)) {
+        total += merge_subtree(processes, values, child, merged);
+    }
+    merged.insert(process.pid, total);
+
+    total
+}
+
+// Only a handful of processes use a GPU, so their devices are added to their ancestors instead
+// of walking every subtree
+fn merge_gpu_devices(change_set: &ProcessChangeSet) -> HashMap<u32, Vec<String>> {
+    let mut merged = change_set.gpu_devices.clone();
+    for (pid, devices) in &change_set.gpu_devices {
+        let mut pid = *pid;
+        while let Some(parent) = change_set.parents.get(&pid) {
+            let parent_devices = merged.entry(*parent).or_default();
+            for device in devices {
+                if !parent_devices.contains(device) {
+                    parent_devices.push(device.clone());
+                }
+            }
+            pid = *parent;
+        }
+    }
+    for devices in merged.values_mut() {
+        devices.sort_unstable();
+    }
+
+    merged
+}
+
+// What is read about a process once, mostly what its cgroups tell about it
+struct ProcessInfo {
+    start_time: u64,
+    unit: Option<String>,
+    in_login_session: bool,
+    field_availability: FieldAvailability,
+    user_name: Option<Arc<str>>,
+}
+
+impl ProcessInfo {
+    fn read(pid: u32, start_time: u64) -> Self {
+        let cgroups = std::fs::read_to_string(format!("/proc/{pid}/cgroup")).ok();
+        let cgroups = cgroups.as_deref();
+
+        Self {
+            start_time,
+            unit: cgroups.and_then(systemd_unit),
+            in_login_session: match own_user_slice() {
+                Some(user_slice) => cgroups.is_some_and(|cgroups| {
+                    cgroups
+                        .lines()
+                        .filter_map(|line| line.splitn(3, ':').nth(2))
+                        .any(|path| path.starts_with(user_slice))
+                }),
+                None => true,
+            },
+            field_availability: read_field_availability(pid),
+            user_name: read_user_name(pid),
+        }
+    }
+}
+
+// The service a process belongs to is the innermost `.service` in its cgroup path, which also
+// covers processes that a service moved into a sub-cgroup of its own
+fn systemd_unit(cgroups: &str) -> Option<String> {
+    // The unified hierarchy is used with cgroup v2, the named systemd hierarchy with v1
+    let path = cgroups.lines().find_map(|line| {
+        let mut fields = line.splitn(3, ':');
+        let _hierarchy_id = fields.next()?;
+        match fields.next()? {
+            "" | "name=systemd" => fields.next(),
+            _ => None,
+        }
+    })?;
+
+    path.rsplit('/')
+        .find(|component| component.ends_with(".service"))
+        .map(str::to_string)
+}
+
+// The cgroup systemd puts the sessions and the service manager of the user running Mission
+// Center in, e.g. `/user.slice/user-1000.slice/`
+fn own_user_slice() -> Option<&'static str> {
+    static OWN_USER_SLICE: OnceLock<Option<String>> = OnceLock::new();
+
+    OWN_USER_SLICE
+        .get_or_init(|| {
+            let cgroup = std::fs::read_to_string("/proc/self/cgroup").ok()?;
+            cgroup.lines().find_map(|line| {
+                let path = line.splitn(3, ':').nth(2)?;
+                let (user_slice, _) = path.strip_prefix("/user.slice/")?.split_once('/')?;
+                user_slice
+                    .starts_with("user-")
+                    .then(|| format!("/user.slice/{}/", user_slice))
+            })
+        })
+        .as_deref()
+}
@@ -0,0 +1,637 @@
+/* magpie_client/service_backend.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use gtk::glib::{g_debug, g_warning};
+use magpie_types::services::Service;
+
+use super::client::Client;
+use super::host_command;
+use super::unit_states::{self, ServiceStats, ServiceTransition, UnitStats};
+
+// The last lines of a log file are shown, like `journalctl` does for systemd services
+const LOG_LINE_COUNT: usize = 1000;
+// How often the services of the init systems other than systemd are listed again
+const LISTING_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The init systems services can be listed and managed for
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InitSystem {
+    Systemd,
+    OpenRc,
+    Runit,
+}
+
+impl InitSystem {
+    /// Looks for the runtime directories each init system creates while it runs. Magpie on
+    /// another machine is always asked, it is the only thing that knows about that machine.
+    pub fn detect(is_local: bool) -> Self {
+        if !is_local || host_path_exists("/run/systemd/system") {
+            return InitSystem::Systemd;
+        }
+
+        if host_path_exists("/run/openrc") {
+            return InitSystem::OpenRc;
+        }
+
+        if host_path_exists("/run/runit") || host_path_exists("/etc/runit/runsvdir") {
+            return InitSystem::Runit;
+        }
+
+        InitSystem::Systemd
+    }
+}
+
+/// Lists the services of the init system and carries out what the Services page asks for.
+/// Services are identified by the same IDs the lists hand out.
+pub trait ServiceBackend {
+    fn user_services(&mut self, magpie: &Client) -> HashMap<u64, Service>;
+
+    fn system_services(&mut self, magpie: &Client) -> HashMap<u64, Service>;
+
+    /// Adds the services that are starting or stopping to `transitions`, not every init system
+    /// can tell
+    fn transitions(
+        &self,
+        _user_services: &HashMap<u64, Service>,
+        _system_services: &HashMap<u64, Service>,
+        _transitions: &mut HashMap<u64, ServiceTransition>,
+    ) {
+    }
+
+    /// Adds how long services took to start and how often they were restarted to `stats`, not
+    /// every init system keeps track of that
+    fn stats(
+        &mut self,
+        _user_services: &HashMap<u64, Service>,
+        _system_services: &HashMap<u64, Service>,
+        _stats: &mut HashMap<u64, ServiceStats>,
+    ) {
+    }
+
+    fn start(&self, magpie: &Client, service_id: u64);
+
+    fn stop(&self, magpie: &Client, service_id: u64);
+
+    fn restart(&self, magpie: &Client, service_id: u64);
+
+    fn enable(&self, magpie: &Client, service_id: u64);
+
+    fn disable(&self, magpie: &Client, service_id: u64);
+
+    fn logs(&self, magpie: &Client, service_id: u64, pid: Option<NonZeroU32>) -> String;
+}
+
+pub fn create(init_system: InitSystem) -> Box<dyn ServiceBackend> {
+    g_debug!("MissionCenter::ServiceBackend", "Managing services with {:?}", init_system);
+
+    match init_system {
+        InitSystem::Systemd => Box::new(Systemd::default()),
+        InitSystem::OpenRc => Box::new(OpenRc::default()),
+        InitSystem::Runit => Box::new(Runit::new()),
+    }
+}
+
+/// Magpie talks to systemd itself
+#[derive(Default)]
+struct Systemd {
+    unit_stats: UnitStats,
+}
+
+impl ServiceBackend for Systemd {
+    fn user_services(&mut self, magpie: &Client) -> HashMap<u64, Service> {
+        magpie.user_services()
+    }
+
+    fn system_services(&mut self, magpie: &Client) -> HashMap<u64, Service> {
+        magpie.system_services()
+    }
+
+    fn transitions(
+        &self,
+        user_services: &HashMap<u64, Service>,
+        system_services: &HashMap<u64, Service>,
+        transitions: &mut HashMap<u64, ServiceTransition>,
+    ) {
+        unit_states::transitions(user_services.values(), system_services.values(), transitions);
+    }
+
+    fn stats(
+        &mut self,
+        user_services: &HashMap<u64, Service>,
+        system_services: &HashMap<u64, Service>,
+        stats: &mut HashMap<u64, ServiceStats>,
+    ) {
+        self.unit_stats
+            .stats(user_services.values(), system_services.values(), stats);
+    }
+
+    fn start(&self, magpie: &Client, service_id: u64) {
+        magpie.start_service(service_id);
+    }
+
+    fn stop(&self, magpie: &Client, service_id: u64) {
+        magpie.stop_service(service_id);
+    }
+
+    fn restart(&self, magpie: &Client, service_id: u64) {
+        magpie.restart_service(service_id);
+    }
+
+    fn enable(&self, magpie: &Client, service_id: u64) {
+        magpie.enable_service(service_id);
+    }
+
+    fn disable(&self, magpie: &Client, service_id: u64) {
+        magpie.disable_service(service_id);
+    }
+
+    fn logs(&self, magpie: &Client, service_id: u64, pid: Option<NonZeroU32>) -> String {
+        magpie.service_logs(service_id, pid)
+    }
+}
+
+/// Listing services takes a few commands, which are slow to run from inside a Flatpak sandbox.
+/// They are listed again every `LISTING_INTERVAL`, and once a change made from the app is done.
+#[derive(Default)]
+struct Listing {
+    listed_at: Option<Instant>,
+    /// Set by `run_privileged` once the command it ran exited
+    changed: Arc<AtomicBool>,
+}
+
+impl Listing {
+    fn is_due(&mut self) -> bool {
+        let changed = self.changed.swap(false, Ordering::AcqRel);
+        let due = changed
+            || self
+                .listed_at
+                .map_or(true, |listed_at| listed_at.elapsed() >= LISTING_INTERVAL);
+        if due {
+            self.listed_at = Some(Instant::now());
+        }
+
+        due
+    }
+}
+
+/// Services are the scripts in `/etc/init.d`, enabled by adding them to a runlevel
+#[derive(Default)]
+struct OpenRc {
+    /// The name of each listed service, and the runlevel it was added to
+    services: HashMap<u64, (String, Option<String>)>,
+    /// The services as of the last time they were listed, `rc-status` also tells their state
+    listed: HashMap<u64, Service>,
+    listing: Listing,
+}
+
+impl OpenRc {
+    const INIT_DIR: &'static str = "/etc/init.d";
+    const DEFAULT_RUNLEVEL: &'static str = "default";
+
+    fn run(&self, service_id: u64, action: &str) {
+        if let Some((name, _)) = self.services.get(&service_id) {
+            run_privileged(&["rc-service", name, action], &self.listing.changed);
+        }
+    }
+}
+
+impl ServiceBackend for OpenRc {
+    // OpenRC only recently got user services, and hardly anyone uses them yet
+    fn user_services(&mut self, _: &Client) -> HashMap<u64, Service> {
+        HashMap::new()
+    }
+
+    fn system_services(&mut self, _: &Client) -> HashMap<u64, Service> {
+        if !self.listing.is_due() {
+            return self.listed.clone();
+        }
+
+        self.services.clear();
+        self.listed.clear();
+
+        let Some(names) = host_output(&["rc-service", "--list"]) else {
+            return HashMap::new();
+        };
+        let status = host_output(&["rc-status", "--all", "--nocolor"]).unwrap_or_default();
+        let states = parse_rc_status(&status);
+
+        let names = names
+            .lines()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<_>>();
+        let pid_files = names
+            .iter()
+            .map(|name| format!("/run/{}.pid", name))
+            .collect::<Vec<_>>();
+        let pids = read_pid_files(&pid_files);
+
+        for (name, pid) in names.into_iter().zip(pids) {
+            let (state, runlevel) = states
+                .get(name)
+                .map(|(state, runlevel)| (state.as_str(), runlevel.clone()))
+                .unwrap_or(("stopped", None));
+
+            let id = service_id(name);
+            self.services.insert(id, (name.to_string(), runlevel.clone()));
+
+            let service = Service {
+                id,
+                name: name.to_string(),
+                enabled: runlevel.is_some(),
+                running: state == "started",
+                failed: state == "crashed" || state == "failed",
+                pid,
+                file_path: Some(format!("{}/{}", Self::INIT_DIR, name)),
+                ..Default::default()
+            };
+            self.listed.insert(id, service);
+        }
+
+        self.listed.clone()
+    }
+
+    fn start(&self, _: &Client, service_id: u64) {
+        self.run(service_id, "start");
+    }
+
+    fn stop(&self, _: &Client, service_id: u64) {
+        self.run(service_id, "stop");
+    }
+
+    fn restart(&self, _: &Client, service_id: u64) {
+        self.run(service_id, "restart");
+    }
+
+    fn enable(&self, _: &Client, service_id: u64) {
+        if let Some((name, _)) = self.services.get(&service_id) {
+            run_privileged(
+                &["rc-update", "add", name, Self::DEFAULT_RUNLEVEL],
+                &self.listing.changed,
+            );
+        }
+    }
+
+    fn disable(&self, _: &Client, service_id: u64) {
+        if let Some((name, runlevel)) = self.services.get(&service_id) {
+            let runlevel = runlevel.as_deref().unwrap_or(Self::DEFAULT_RUNLEVEL);
+            run_privileged(&["rc-update", "del", name, runlevel], &self.listing.changed);
+        }
+    }
+
+    // Services log to syslog unless their script sets `output_log`, which usually points here
+    fn logs(&self, _: &Client, service_id: u64, _: Option<NonZeroU32>) -> String {
+        self.services
+            .get(&service_id)
+            .map(|(name, _)| read_log_tail(&[format!("/var/log/{}.log", name)]))
+            .unwrap_or_default()
+    }
+}
+
+// `rc-status` lists the services of each runlevel under a `Runlevel: <name>` heading, with their
+// state in brackets. The dynamic runlevels hold the services started as dependencies or by hand,
+// they are not enabled.
+fn parse_rc_status(status: &str) -> HashMap<String, (String, Option<String>)> {
+    let mut states = HashMap::new();
+
+    let mut runlevel = None;
+    for line in status.lines() {
+        if let Some(name) = line.strip_prefix("Runlevel:") {
+            runlevel = Some(name.trim().to_string());
+            continue;
+        }
+        if line.starts_with("Dynamic Runlevel:") {
+            runlevel = None;
+            continue;
+        }
+
+        let Some((name, state)) = line.split_once('[') else {
+            continue;
+        };
+        let name = name.trim();
+        let state = state.trim_end().trim_end_matches(']').trim();
+        if name.is_empty() || state.is_empty() {
+            continue;
+        }
+
+        let entry = states
+            .entry(name.to_string())
+            .or_insert_with(|| (state.to_string(), None));
+        // The shutdown runlevel runs services to stop the system, that is not being enabled
+        if entry.1.is_none() && runlevel.as_deref() != Some("shutdown") {
+            entry.1 = runlevel.clone();
+        }
+    }
+
+    states
+}
+
+/// Services are directories in `/etc/sv`, enabled by linking them into the directory `runsvdir`
+/// watches. Only enabled services are supervised, so only they can be started.
+struct Runit {
+    available_dir: &'static str,
+    enabled_dir: &'static str,
+    /// The name of each listed service
+    services: HashMap<u64, String>,
+    /// The names in both directories as of the last time they were listed, the state of the
+    /// services is read with every update
+    available: Vec<String>,
+    enabled: Vec<String>,
+    listing: Listing,
+}
+
+impl Runit {
+    // Void Linux uses the first of each, Artix the second
+    const AVAILABLE_DIRS: [&'static str; 2] = ["/etc/sv", "/etc/runit/sv"];
+    const ENABLED_DIRS: [&'static str; 3] =
+        ["/var/service", "/run/runit/service", "/etc/runit/runsvdir/default"];
+
+    fn new() -> Self {
+        let first_existing = |dirs: &[&'static str]| {
+            dirs.iter()
+                .copied()
+                .find(|dir| host_path_exists(dir))
+                .unwrap_or(dirs[0])
+        };
+
+        Self {
+            available_dir: first_existing(&Self::AVAILABLE_DIRS),
+            enabled_dir: first_existing(&Self::ENABLED_DIRS),
+            services: HashMap::new(),
+            available: Vec::new(),
+            enabled: Vec::new(),
+            listing: Listing::default(),
+        }
+    }
+
+    fn run(&self, service_id: u64, action: &str) {
+        if let Some(name) = self.services.get(&service_id) {
+            let path = format!("{}/{}", self.enabled_dir, name);
+            run_privileged(&["sv", action, &path], &self.listing.changed);
+        }
+    }
+}
+
+impl ServiceBackend for Runit {
+    fn user_services(&mut self, _: &Client) -> HashMap<u64, Service> {
+        HashMap::new()
+    }
+
+    fn system_services(&mut self, _: &Client) -> HashMap<u64, Service> {
+        if self.listing.is_due() {
+            let list = |dir| {
+                host_output(&["ls", "-1", dir]).map(|names| {
+                    names
+                        .lines()
+                        .map(str::trim)
+                        .filter(|name| !name.is_empty())
+                        .map(str::to_string)
+                        .collect::<Vec<_>>()
+                })
+            };
+            self.available = list(self.available_dir).unwrap_or_default();
+            self.enabled = list(self.enabled_dir).unwrap_or_default();
+        }
+        if self.available.is_empty() {
+            self.services.clear();
+            return HashMap::new();
+        }
+        let enabled = &self.enabled;
+
+        let enabled_paths = enabled
+            .iter()
+            .map(|name| format!("{}/{}", self.enabled_dir, name))
+            .collect::<Vec<_>>();
+        let mut args = vec!["sv", "status"];
+        args.extend(enabled_paths.iter().map(String::as_str));
+        let status = if enabled.is_empty() {
+            String::new()
+        } else {
+            host_output(&args).unwrap_or_default()
+        };
+        let states = parse_sv_status(&status);
+
+        self.services.clear();
+        self.available
+            .iter()
+            .map(|name| {
+                let (state, pid) = states.get(name).cloned().unwrap_or(("down".into(), None));
+
+                let id = service_id(name);
+                self.services.insert(id, name.to_string());
+
+                let service = Service {
+                    id,
+                    name: name.to_string(),
+                    enabled: enabled.contains(name),
+                    running: state == "run",
+                    failed: state == "fail",
+                    pid,
+                    file_path: Some(format!("{}/{}/run", self.available_dir, name)),
+                    ..Default::default()
+                };
+                (id, service)
+            })
+            .collect()
+    }
+
+    fn start(&self, _: &Client, service_id: u64) {
+        self.run(service_id, "up");
+    }
+
+    fn stop(&self, _: &Client, service_id: u64) {
+        self.run(service_id, "down");
+    }
+
+    fn restart(&self, _: &Client, service_id: u64) {
+        self.run(service_id, "restart");
+    }
+
+    fn enable(&self, _: &Client, service_id: u64) {
+        if let Some(name) = self.services.get(&service_id) {
+            let source = format!("{}/{}", self.available_dir, name);
+            run_privileged(&["ln", "-s", &source, self.enabled_dir], &self.listing.changed);
+        }
+    }
+
+    fn disable(&self, _: &Client, service_id: u64) {
+        if let Some(name) = self.services.get(&service_id) {
+            let link = format!("{}/{}", self.enabled_dir, name);
+            run_privileged(&["rm", &link], &self.listing.changed);
+        }
+    }
+
+    // `svlogd` writes the current log of a service to a `current` file in its log directory
+    fn logs(&self, _: &Client, service_id: u64, _: Option<NonZeroU32>) -> String {
+        self.services
+            .get(&service_id)
+            .map(|name| {
+                read_log_tail(&[
+                    format!("/var/log/{}/current", name),
+                    format!("/var/log/sv/{}/current", name),
+                ])
+            })
+            .unwrap_or_default()
+    }
+}
+
+// `sv status` prints a line per service, e.g. `run: /var/service/sshd: (pid 1234) 567s; run: log:
+// (pid 1233) 567s`, `down: /var/service/ntpd: 12s, normally up` or `fail: /var/service/foo: ...`.
+fn parse_sv_status(status: &str) -> HashMap<String, (String, Option<u32>)> {
+    status
+        .lines()
+        .filter_map(|line| {
+            let (state, rest) = line.split_once(": ")?;
+            let (path, rest) = rest.split_once(':')?;
+            let name = Path::new(path.trim()).file_name()?.to_string_lossy();
+
+            let pid = rest
+                .split(';')
+                .next()
+                .and_then(|main| main.split_once("(pid "))
+                .and_then(|(_, pid)| pid.split_once(')'))
+                .and_then(|(pid, _)| pid.trim().parse().ok());
+
+            Some((name.to_string(), (state.trim().to_string(), pid)))
+        })
+        .collect()
+}
+
+// Names are hashed, so a service keeps its ID from one reading to the next and across restarts
+fn service_id(name: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    name.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn host_output(args: &[&str]) -> Option<String> {
+    match host_command(args).output() {
+        Ok(output) if output.status.success() => {
+            Some(String::from_utf8_lossy(&output.stdout).into_owned())
+        }
+        Ok(output) => {
+            g_debug!(
+                "MissionCenter::ServiceBackend",
+                "{:?} failed: {}",
+                args,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            None
+        }
+        Err(e) => {
+            g_debug!("MissionCenter::ServiceBackend", "Failed to run {:?}: {}", args, e);
+            None
+        }
+    }
+}
+
+fn host_path_exists(path: &str) -> bool {
+    if crate::is_flatpak() {
+        host_output(&["test", "-e", path]).is_some()
+    } else {
+        Path::new(path).exists()
+    }
+}
+
+// The files of the host are out of reach inside a Flatpak sandbox
+fn read_host_file(path: &str) -> Option<String> {
+    if crate::is_flatpak() {
+        host_output(&["cat", path])
+    } else {
+        std::fs::read_to_string(path).ok()
+    }
+}
+
+// Unlike systemd, these init systems have no polkit rules, so changes are made through `pkexec`
+// unless the app runs as root. The command is waited on from a separate thread, since asking for
+// authorization can take a while; `done` is set once it exited.
+fn run_privileged(args: &[&str], done: &Arc<AtomicBool>) {
+    extern "C" {
+        fn geteuid() -> u32;
+    }
+    // Safe to call at any time, it only returns the effective user ID of the process
+    let is_root = unsafe { geteuid() } == 0;
+
+    let mut command = vec!["pkexec"];
+    command.extend_from_slice(args);
+    let args = if is_root { args } else { &command[..] };
+
+    let mut cmd = host_command(args);
+    let args = args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+    let done = done.clone();
+    std::thread::spawn(move || {
+        match cmd.status() {
+            Ok(status) if status.success() => {}
+            Ok(status) => {
+                g_warning!("MissionCenter::ServiceBackend", "{:?} exited with {}", args, status);
+            }
+            Err(e) => {
+                g_warning!("MissionCenter::ServiceBackend", "Failed to run {:?}: {}", args, e);
+            }
+        }
+        done.store(true, Ordering::Release);
+    });
+}
+
+// Reading each file with a command of its own would be slow from inside a Flatpak sandbox, so a
+// single shell reads all of them there
+fn read_pid_files(paths: &[String]) -> Vec<Option<u32>> {
+    let contents = if crate::is_flatpak() {
+        let mut args = vec![
+            "sh",
+            "-c",
+            r#"for path do printf '%s\0' "$(cat "$path" 2>/dev/null)"; done"#,
+            "sh",
+        ];
+        args.extend(paths.iter().map(String::as_str));
+
+        let output = host_output(&args).unwrap_or_default();
+        let mut contents = output.split('\0').map(str::to_string).collect::<Vec<_>>();
+        contents.resize(paths.len(), String::new());
+        contents
+    } else {
+        paths
+            .iter()
+            .map(|path| std::fs::read_to_string(path).unwrap_or_default())
+            .collect()
+    };
+
+    contents
+        .iter()
+        .map(|contents| contents.trim().parse().ok())
+        .collect()
+}
+
+fn read_log_tail(paths: &[String]) -> String {
+    let Some(contents) = paths.iter().find_map(|path| read_host_file(path)) else {
+        return String::new();
+    };
+
+    let lines = contents.lines().collect::<Vec<_>>();
+    lines[lines.len().saturating_sub(LOG_LINE_COUNT)..].join("\n")
+}
@@ -0,0 +1,496 @@
+/* magpie_client/snapshot.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use magpie_types::prost::{self, Message};
+
+use super::{ProcessChangeSet, ProcessState, Readings, ServiceStats, ServiceTransition};
+
+const MAGIC: &[u8] = b"MCSNAPSHOT\x01";
+
+// Every record starts with one of these, followed by the length of the rest as a little endian
+// `u32`, the length of its key as another and the key. Records with an unknown kind are
+// skipped, so that newer versions can add to the format.
+mod kind {
+    pub const INFO: u8 = 0;
+    pub const CPU: u8 = 1;
+    pub const MEMORY: u8 = 2;
+    pub const MEMORY_DEVICE: u8 = 3;
+    pub const DISK: u8 = 4;
+    pub const CONNECTION: u8 = 5;
+    pub const GPU: u8 = 6;
+    pub const FAN: u8 = 7;
+    pub const APP: u8 = 8;
+    pub const PROCESS: u8 = 9;
+    pub const USER_SERVICE: u8 = 10;
+    pub const SYSTEM_SERVICE: u8 = 11;
+    pub const SERVICE_COMMAND: u8 = 12;
+    pub const SERVICE_TRANSITION: u8 = 13;
+    pub const PROCESS_STATE: u8 = 14;
+    pub const SWAP_USAGE: u8 = 15;
+    pub const GPU_DEVICE: u8 = 16;
+    pub const UNIT: u8 = 17;
+    pub const SERVICE_STATS: u8 = 18;
+}
+
+/// Readings that were saved to a file, to look at the state of a machine later or elsewhere
+pub struct Snapshot {
+    /// The name of the machine the readings were taken on
+    pub host: String,
+    /// When the readings were taken, in ISO 8601
+    pub taken_at: String,
+    pub readings: Readings,
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    NotASnapshot,
+    Truncated,
+    Invalid(prost::DecodeError),
+}
+
+impl std::fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::NotASnapshot => write!(f, "not a snapshot"),
+            SnapshotError::Truncated => write!(f, "the file ends early"),
+            SnapshotError::Invalid(e) => write!(f, "invalid readings: {}", e),
+        }
+    }
+}
+
+impl From<prost::DecodeError> for SnapshotError {
+    fn from(e: prost::DecodeError) -> Self {
+        SnapshotError::Invalid(e)
+    }
+}
+
+impl Snapshot {
+    pub fn encode(readings: &Readings, host: &str, taken_at: &str) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+
+        push(&mut out, kind::INFO, "host", host.as_bytes());
+        push(&mut out, kind::INFO, "taken-at", taken_at.as_bytes());
+
+        push_message(&mut out, kind::CPU, "", &readings.cpu);
+        push_message(&mut out, kind::MEMORY, "", &readings.mem_info);
+        for device in &readings.mem_devices {
+            push_message(&mut out, kind::MEMORY_DEVICE, "", device);
+        }
+        for disk in &readings.disks_info {
+            push_message(&mut out, kind::DISK, "", disk);
+        }
+        for connection in &readings.network_connections {
+            push_message(&mut out, kind::CONNECTION, "", connection);
+        }
+        for (id, gpu) in &readings.gpus {
+            push_message(&mut out, kind::GPU, id, gpu);
+        }
+        for fan in &readings.fans {
+            push_message(&mut out, kind::FAN, "", fan);
+        }
+
+        for app in readings.running_apps.values() {
+            push_message(&mut out, kind::APP, "", app);
+        }
+        for process in readings.running_processes.values() {
+            push_message(&mut out, kind::PROCESS, "", process);
+        }
+        for service in readings.user_services.values() {
+            push_message(&mut out, kind::USER_SERVICE, "", service);
+        }
+        for service in readings.system_services.values() {
+            push_message(&mut out, kind::SYSTEM_SERVICE, "", service);
+        }
+        for (id, command) in &readings.service_commands {
+            push(
+                &mut out,
+                kind::SERVICE_COMMAND,
+                &id.to_string(),
+                command.as_bytes(),
+            );
+        }
+        for (id, transition) in &readings.service_transitions {
+            let transition: &[u8] = match transition {
+                ServiceTransition::None => continue,
+                ServiceTransition::Activating => b"activating",
+                ServiceTransition::Deactivating => b"deactivating",
+            };
+            push(
+                &mut out,
+                kind::SERVICE_TRANSITION,
+                &id.to_string(),
+                transition,
+            );
+        }
+
+        for (id, stats) in &readings.service_stats {
+            // The start time in microseconds, left empty when unknown, and the restart count
+            let stats = format!(
+                "{} {}",
+                stats
+                    .start_time
+                    .map(|time| time.as_micros().to_string())
+                    .unwrap_or_default(),
+                stats.restarts
+            );
+            push(
+                &mut out,
+                kind::SERVICE_STATS,
+                &id.to_string(),
+                stats.as_bytes(),
+            );
+        }
+
+        let changes = &readings.process_changes;
+        for (pid, state) in &changes.states {
+            push(
+                &mut out,
+                kind::PROCESS_STATE,
+                &pid.to_string(),
+                state.code().as_bytes(),
+            );
+        }
+        for (pid, swap_usage) in &changes.swap_usage {
+            let swap_usage = swap_usage.to_string();
+            push(
+                &mut out,
+                kind::SWAP_USAGE,
+                &pid.to_string(),
+                swap_usage.as_bytes(),
+            );
+        }
+        for (pid, devices) in &changes.gpu_devices {
+            for device in devices {
+                push(
+                    &mut out,
+                    kind::GPU_DEVICE,
+                    &pid.to_string(),
+                    device.as_bytes(),
+                );
+            }
+        }
+        for (unit, pids) in &changes.unit_processes {
+            for pid in pids {
+                push(&mut out, kind::UNIT, &pid.to_string(), unit.as_bytes());
+            }
+        }
+
+        out
+    }
+
+    pub fn decode(mut bytes: &[u8]) -> Result<Self, SnapshotError> {
+        bytes = bytes
+            .strip_prefix(MAGIC)
+            .ok_or(SnapshotError::NotASnapshot)?;
+
+        let mut snapshot = Snapshot {
+            host: String::new(),
+            taken_at: String::new(),
+            readings: Readings::new(),
+        };
+        let readings = &mut snapshot.readings;
+
+        let mut states = HashMap::new();
+        let mut swap_usage = HashMap::new();
+        let mut gpu_devices = HashMap::<u32, Vec<String>>::new();
+        let mut unit_processes = HashMap::<String, Vec<u32>>::new();
+
+        while let Some((&kind, rest)) = bytes.split_first() {
+            let (key, value, rest) = split_record(rest).ok_or(SnapshotError::Truncated)?;
+            bytes = rest;
+
+            let key = String::from_utf8_lossy(key);
+            let text = || String::from_utf8_lossy(value).into_owned();
+            match kind {
+                kind::INFO => match key.as_ref() {
+                    "host" => snapshot.host = text(),
+                    "taken-at" => snapshot.taken_at = text(),
+                    _ => {}
+                },
+                kind::CPU => readings.cpu = Message::decode(value)?,
+                kind::MEMORY => readings.mem_info = Message::decode(value)?,
+                kind::MEMORY_DEVICE => readings.mem_devices.push(Message::decode(value)?),
+                kind::DISK => readings.disks_info.push(Message::decode(value)?),
+                kind::CONNECTION => readings.network_connections.push(Message::decode(value)?),
+                kind::GPU => {
+                    readings
+                        .gpus
+                        .insert(key.into_owned(), Message::decode(value)?);
+                }
+                kind::FAN => readings.fans.push(Message::decode(value)?),
+                kind::APP => {
+                    let app: super::App = Message::decode(value)?;
+                    readings.running_apps.insert(app.id.clone(), app);
+                }
+                kind::PROCESS => {
+                    let process: super::Process = Message::decode(value)?;
+                    readings.running_processes.insert(process.pid, process);
+                }
+                kind::USER_SERVICE => {
+                    let service: super::Service = Message::decode(value)?;
+                    readings.user_services.insert(service.id, service);
+                }
+                kind::SYSTEM_SERVICE => {
+                    let service: super::Service = Message::decode(value)?;
+                    readings.system_services.insert(service.id, service);
+                }
+                kind::SERVICE_COMMAND => {
+                    if let Ok(id) = key.parse() {
+                        readings.service_commands.insert(id, text());
+                    }
+                }
+                kind::SERVICE_TRANSITION => {
+                    let transition = match value {
+                        b"activating" => ServiceTransition::Activating,
+                        b"deactivating" => ServiceTransition::Deactivating,
+                        _ => continue,
+                    };
+                    if let Ok(id) = key.parse() {
+                        readings.service_transitions.insert(id, transition);
+                    }
+                }
+                kind::SERVICE_STATS => {
+                    let text = text();
+                    let Some((start_time, restarts)) = text.split_once(' ') else {
+                        continue;
+                    };
+                    let stats = ServiceStats {
+                        start_time: start_time.parse().ok().map(Duration::from_micros),
+                        restarts: restarts.parse().unwrap_or_default(),
+                    };
+                    if let Ok(id) = key.parse() {
+                        readings.service_stats.insert(id, stats);
+                    }
+                }
+                kind::PROCESS_STATE => {
+                    if let (Ok(pid), Some(code)) = (key.parse(), text().chars().next()) {
+                        states.insert(pid, ProcessState::from_code(code));
+                    }
+                }
+                kind::SWAP_USAGE => {
+                    if let (Ok(pid), Ok(usage)) = (key.parse(), text().parse()) {
+                        swap_usage.insert(pid, usage);
+                    }
+                }
+                kind::GPU_DEVICE => {
+                    if let Ok(pid) = key.parse() {
+                        gpu_devices.entry(pid).or_default().push(text());
+                    }
+                }
+                kind::UNIT => {
+                    if let Ok(pid) = key.parse() {
+                        unit_processes.entry(text()).or_default().push(pid);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for devices in gpu_devices.values_mut() {
+            devices.sort_unstable();
+        }
+        readings.process_changes = ProcessChangeSet::restore(
+            &readings.running_processes,
+            states,
+            swap_usage,
+            gpu_devices,
+            unit_processes,
+        );
+
+        Ok(snapshot)
+    }
+}
+
+fn push(out: &mut Vec<u8>, kind: u8, key: &str, value: &[u8]) {
+    out.push(kind);
+    out.extend_from_slice(&((4 + key.len() + value.len()) as u32).to_le_bytes());
+    out.extend_from_slice(&(key.len() as u32).to_le_bytes());
+    out.extend_from_slice(key.as_bytes());
+    out.extend_from_slice(value);
+}
+
+fn push_message(out: &mut Vec<u8>, kind: u8, key: &str, message: &impl Message) {
+    push(out, kind, key, &message.encode_to_vec());
+}
+
+// Returns the key and value of the record at the start of `bytes`, and what follows it
+fn split_record(bytes: &[u8]) -> Option<(&[u8], &[u8], &[u8])> {
+    let (length, rest) = split_length(bytes)?;
+    if rest.len() < length {
+        return None;
+    }
+    let (record, rest) = rest.split_at(length);
+
+    let (key_length, record) = split_length(record)?;
+    if record.len() < key_length {
+        return None;
+    }
+    let (key, value) = record.split_at(key_length);
+
+    Some((key, value, rest))
+}
+
+fn split_length(bytes: &[u8]) -> Option<(usize, &[u8])> {
+    let (length, rest) = bytes.split_first_chunk::<4>()?;
+    Some((u32::from_le_bytes(*length) as usize, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Process, Service};
+    use super::*;
+
+    fn readings() -> Readings {
+        let mut readings = Readings::new();
+        readings.running_processes.insert(
+            1,
+            Process {
+                pid: 1,
+                name: "init".into(),
+                children: vec![2],
+                ..Default::default()
+            },
+        );
+        readings.running_processes.insert(
+            2,
+            Process {
+                pid: 2,
+                name: "worker".into(),
+                ..Default::default()
+            },
+        );
+        readings.user_services.insert(
+            7,
+            Service {
+                id: 7,
+                name: "worker.service".into(),
+                ..Default::default()
+            },
+        );
+        readings
+            .service_commands
+            .insert(7, "/usr/bin/worker --serve".into());
+        readings
+            .service_transitions
+            .insert(7, ServiceTransition::Activating);
+        readings.service_stats.insert(
+            7,
+            ServiceStats {
+                start_time: Some(Duration::from_micros(1_500)),
+                restarts: 2,
+            },
+        );
+        readings.process_changes = ProcessChangeSet::restore(
+            &readings.running_processes,
+            HashMap::from([(2, ProcessState::DiskSleep)]),
+            HashMap::from([(2, 4096)]),
+            HashMap::from([(1, vec!["0000:01:00.0".to_owned()])]),
+            HashMap::from([("worker.service".to_owned(), vec![2])]),
+        );
+
+        readings
+    }
+
+    #[test]
+    fn decodes_what_was_encoded() {
+        let bytes = Snapshot::encode(&readings(), "box", "2025-06-01T12:00:00Z");
+        let snapshot = Snapshot::decode(&bytes).unwrap();
+
+        assert_eq!(snapshot.host, "box");
+        assert_eq!(snapshot.taken_at, "2025-06-01T12:00:00Z");
+
+        let readings = &snapshot.readings;
+        assert_eq!(readings.running_processes.len(), 2);
+        assert_eq!(readings.running_processes[&1].children, vec![2]);
+        assert_eq!(readings.running_processes[&2].name, "worker");
+        assert_eq!(readings.user_services[&7].name, "worker.service");
+        assert!(readings.system_services.is_empty());
+        assert_eq!(readings.service_commands[&7], "/usr/bin/worker --serve");
+        assert_eq!(
+            readings.service_transitions[&7],
+            ServiceTransition::Activating
+        );
+        assert_eq!(
+            readings.service_stats[&7],
+            ServiceStats {
+                start_time: Some(Duration::from_micros(1_500)),
+                restarts: 2,
+            }
+        );
+
+        let changes = &readings.process_changes;
+        assert_eq!(changes.state(2), ProcessState::DiskSleep);
+        assert_eq!(changes.swap_usage(2), 4096);
+        assert_eq!(changes.gpu_devices(1), ["0000:01:00.0"]);
+        assert_eq!(changes.service_processes("worker"), [2]);
+        assert_eq!(changes.parent(2), Some(1));
+        assert!(changes.has_problem_state(1));
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(matches!(
+            Snapshot::decode(b"[Unit]\nDescription=Not a snapshot\n"),
+            Err(SnapshotError::NotASnapshot)
+        ));
+        assert!(matches!(
+            Snapshot::decode(&MAGIC[..MAGIC.len() - 1]),
+            Err(SnapshotError::NotASnapshot)
+        ));
+    }
+
+    #[test]
+    fn rejects_files_that_end_within_a_record() {
+        let bytes = Snapshot::encode(&readings(), "box", "2025-06-01T12:00:00Z");
+
+        assert!(matches!(
+            Snapshot::decode(&bytes[..bytes.len() - 1]),
+            Err(SnapshotError::Truncated)
+        ));
+        // Only the kind of the first record is left
+        assert!(matches!(
+            Snapshot::decode(&bytes[..MAGIC.len() + 1]),
+            Err(SnapshotError::Truncated)
+        ));
+    }
+
+    #[test]
+    fn decodes_every_prefix_without_panicking() {
+        let bytes = Snapshot::encode(&readings(), "box", "2025-06-01T12:00:00Z");
+
+        for end in 0..bytes.len() {
+            // Prefixes that end between two records are valid snapshots with fewer readings
+            let _ = Snapshot::decode(&bytes[..end]);
+        }
+    }
+
+    #[test]
+    fn skips_records_of_unknown_kinds() {
+        let mut bytes = MAGIC.to_vec();
+        push(&mut bytes, u8::MAX, "from-a-newer-version", b"\x00\x01\x02");
+        push(&mut bytes, kind::INFO, "host", b"box");
+
+        let snapshot = Snapshot::decode(&bytes).unwrap();
+        assert_eq!(snapshot.host, "box");
+    }
+}
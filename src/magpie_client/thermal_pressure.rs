@@ -0,0 +1,105 @@
+/* magpie_client/thermal_pressure.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::HashMap;
+
+use super::{Cpu, Process};
+
+/// How much of each new reading goes into the smoothed values, so that short bursts neither
+/// count as heating up the CPU nor as causing it
+const SMOOTHING: f32 = 0.2;
+/// The idle temperature follows the temperature down right away, but only creeps up by this
+/// many °C per reading, so that a long running load is not mistaken for idling
+const IDLE_CREEP: f32 = 0.002;
+
+/// How far the CPU is above the temperature it sits at when idle, along with the CPU usage of
+/// all processes that heats it up. Both are smoothed over the last few readings.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct ThermalPressure {
+    pub rise_celsius: f32,
+    pub total_cpu_usage: f32,
+}
+
+impl ThermalPressure {
+    /// The share of the heating caused by `cpu_usage`, which has to be smoothed like the rows
+    /// do it, and how many degrees that share amounts to
+    pub fn attribute(&self, cpu_usage: f32) -> (f32, f32) {
+        if self.total_cpu_usage <= 0. {
+            return (0., 0.);
+        }
+
+        let share = (cpu_usage / self.total_cpu_usage).clamp(0., 1.);
+        (share, share * self.rise_celsius)
+    }
+}
+
+/// Lives on the gather thread and keeps the history the smoothing needs. Only the CPU package
+/// temperature is known, so the processes share the heating by how much CPU they use.
+#[derive(Default)]
+pub struct ThermalTracker {
+    temperature: Option<f32>,
+    idle_temperature: Option<f32>,
+    total_cpu_usage: Option<f32>,
+}
+
+impl ThermalTracker {
+    pub fn update(
+        &mut self,
+        cpu: &Cpu,
+        processes: &HashMap<u32, Process>,
+    ) -> Option<ThermalPressure> {
+        let Some(current) = cpu
+            .temperature_celsius
+            .filter(|temperature| temperature.is_finite() && *temperature > 0.)
+        else {
+            *self = Self::default();
+            return None;
+        };
+
+        let temperature = smooth(self.temperature, current);
+        let idle_temperature = self
+            .idle_temperature
+            .map_or(temperature, |idle| (idle + IDLE_CREEP).min(temperature));
+        let total_cpu_usage = smooth(
+            self.total_cpu_usage,
+            processes
+                .values()
+                .map(|process| process.usage_stats.cpu_usage)
+                .sum(),
+        );
+
+        self.temperature = Some(temperature);
+        self.idle_temperature = Some(idle_temperature);
+        self.total_cpu_usage = Some(total_cpu_usage);
+
+        Some(ThermalPressure {
+            rise_celsius: temperature - idle_temperature,
+            total_cpu_usage,
+        })
+    }
+}
+
+/// Moves `previous` part of the way towards `current`
+pub fn smooth(previous: Option<f32>, current: f32) -> f32 {
+    match previous {
+        Some(previous) => previous + (current - previous) * SMOOTHING,
+        None => current,
+    }
+}
@@ -0,0 +1,493 @@
+/* magpie_client/transport.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use arrayvec::ArrayString;
+use gtk::gio;
+use gtk::glib::{self, g_critical, g_debug};
+use gtk::prelude::*;
+
+use crate::i18n::i18n_f;
+use crate::magpie_client::{flatpak_app_path, host_command};
+use crate::{app, flatpak_data_dir, is_flatpak, show_error_dialog_and_exit};
+
+const ENV_MC_DEBUG_MAGPIE_PROCESS_SOCK: &str = "MC_DEBUG_MAGPIE_PROCESS_SOCK";
+
+/// How the requests reach a Magpie instance
+pub trait Transport: Send {
+    /// The NNG address Magpie accepts requests on
+    fn address(&self) -> &str;
+
+    /// Brings up whatever is needed for `address` to accept connections
+    fn start(&mut self);
+
+    fn stop(&mut self);
+
+    /// Whether Magpie runs on this machine, so that `/proc` and the unit files describe the
+    /// same processes and services it does
+    fn is_local(&self) -> bool;
+}
+
+/// A Magpie process started by the app, restarted whenever it exits unexpectedly
+pub struct LocalTransport {
+    address: String,
+    child_thread: Option<std::thread::JoinHandle<()>>,
+    stop_requested: Arc<AtomicBool>,
+}
+
+impl LocalTransport {
+    pub fn new() -> Self {
+        let address = if let Ok(existing_sock) = std::env::var(ENV_MC_DEBUG_MAGPIE_PROCESS_SOCK) {
+            existing_sock
+        } else if is_flatpak() {
+            format!("ipc://{}/magpie.ipc", flatpak_data_dir().display())
+        } else {
+            format!("ipc:///tmp/magpie_{}.ipc", random_string::<8>())
+        };
+
+        Self {
+            address,
+            child_thread: None,
+            stop_requested: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+impl Transport for LocalTransport {
+    fn address(&self) -> &str {
+        &self.address
+    }
+
+    fn start(&mut self) {
+        // Magpie is already running, for debugging
+        if std::env::var(ENV_MC_DEBUG_MAGPIE_PROCESS_SOCK).is_ok() {
+            return;
+        }
+
+        let socket_addr = self.address.clone();
+        let stop_requested = self.stop_requested.clone();
+        self.child_thread = Some(std::thread::spawn(move || {
+            fn spawn_child(socket_addr: &str) -> std::process::Child {
+                match magpie_command(socket_addr).spawn() {
+                    Ok(child) => child,
+                    Err(e) => {
+                        g_critical!(
+                            "MissionCenter::Gatherer",
+                            "Failed to spawn Magpie process: {}",
+                            &e
+                        );
+                        show_error_dialog_and_exit(&format!(
+                            "Failed to spawn Magpie process: {}",
+                            e
+                        ));
+                    }
+                }
+            }
+
+            let mut child = spawn_child(&socket_addr);
+
+            while !stop_requested.load(Ordering::Relaxed) {
+                match child.try_wait() {
+                    Ok(Some(exit_status)) => {
+                        let _ = std::fs::remove_file(&socket_addr[6..]);
+
+                        if !stop_requested.load(Ordering::Relaxed) {
+                            g_critical!(
+                                "MissionCenter::Gatherer",
+                                "Magpie process exited unexpectedly: {}. Restarting...",
+                                exit_status
+                            );
+                            std::mem::swap(&mut child, &mut spawn_child(&socket_addr));
+                        }
+                    }
+                    Ok(None) => {
+                        std::thread::sleep(Duration::from_millis(100));
+                        continue;
+                    }
+                    Err(e) => {
+                        g_critical!(
+                            "MissionCenter::Gatherer",
+                            "Failed to wait for Gatherer process to stop: {}",
+                            &e
+                        );
+                        show_error_dialog_and_exit(&format!(
+                            "Failed to wait for Gatherer process to stop: {}",
+                            e
+                        ));
+                    }
+                }
+            }
+
+            let _ = child.kill();
+        }));
+    }
+
+    fn stop(&mut self) {
+        self.stop_requested.store(true, Ordering::Relaxed);
+        if let Some(child_thread) = self.child_thread.take() {
+            let _ = child_thread.join();
+        }
+    }
+
+    fn is_local(&self) -> bool {
+        true
+    }
+}
+
+/// Magpie running on another machine, listening on a `tcp://` address. Nothing is encrypted,
+/// this is meant for trusted networks only.
+pub struct TcpTransport {
+    address: String,
+}
+
+impl TcpTransport {
+    pub fn new(address: &str) -> Self {
+        Self {
+            address: address.to_owned(),
+        }
+    }
+}
+
+impl Transport for TcpTransport {
+    fn address(&self) -> &str {
+        &self.address
+    }
+
+    fn start(&mut self) {}
+
+    fn stop(&mut self) {}
+
+    fn is_local(&self) -> bool {
+        false
+    }
+}
+
+/// Magpie running on another machine, listening on a Unix socket there. The socket is forwarded
+/// to a local one with `ssh`, which has to be able to log in without asking for a password.
+pub struct SshTransport {
+    destination: String,
+    remote_socket: String,
+    local_socket: String,
+    address: String,
+    tunnel: Option<std::process::Child>,
+}
+
+impl SshTransport {
+    pub fn new(destination: &str, remote_socket: &str) -> Self {
+        // Unlike a local port, which can be taken by another process before `ssh` gets to listen
+        // on it, a socket with a random name is only ever used by this tunnel
+        let local_socket = if is_flatpak() {
+            format!(
+                "{}/magpie_ssh_{}.ipc",
+                flatpak_data_dir().display(),
+                random_string::<8>()
+            )
+        } else {
+            format!("/tmp/magpie_ssh_{}.ipc", random_string::<8>())
+        };
+
+        Self {
+            destination: destination.to_owned(),
+            remote_socket: remote_socket.to_owned(),
+            address: format!("ipc://{local_socket}"),
+            local_socket,
+            tunnel: None,
+        }
+    }
+}
+
+impl Transport for SshTransport {
+    fn address(&self) -> &str {
+        &self.address
+    }
+
+    fn start(&mut self) {
+        let forward = format!("{}:{}", self.local_socket, self.remote_socket);
+        let mut command = host_command(&[
+            "ssh",
+            "-N",
+            "-o",
+            "BatchMode=yes",
+            "-o",
+            "ExitOnForwardFailure=yes",
+            "-L",
+            &forward,
+            // A destination starting with `-` would be taken for an option
+            "--",
+            &self.destination,
+        ]);
+        command.stdin(std::process::Stdio::null());
+
+        g_debug!(
+            "MissionCenter::Gatherer",
+            "Opening SSH tunnel to {}:{} at {}",
+            self.destination,
+            self.remote_socket,
+            self.local_socket
+        );
+
+        match command.spawn() {
+            Ok(tunnel) => self.tunnel = Some(tunnel),
+            Err(e) => {
+                g_critical!(
+                    "MissionCenter::Gatherer",
+                    "Failed to start SSH tunnel to {}: {}",
+                    self.destination,
+                    e
+                );
+
+                let message = i18n_f(
+                    "Failed to start ssh to connect to {}: {}",
+                    &[&self.destination, &e.to_string()],
+                );
+                glib::idle_add_once(move || app!().show_connection_error(&message));
+            }
+        }
+    }
+
+    fn stop(&mut self) {
+        if let Some(mut tunnel) = self.tunnel.take() {
+            let _ = tunnel.kill();
+            let _ = tunnel.wait();
+        }
+        let _ = std::fs::remove_file(&self.local_socket);
+    }
+
+    fn is_local(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RemoteKind {
+    Ssh,
+    Tcp,
+}
+
+impl RemoteKind {
+    fn id(&self) -> &'static str {
+        match self {
+            RemoteKind::Ssh => "ssh",
+            RemoteKind::Tcp => "tcp",
+        }
+    }
+
+    fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "ssh" => Some(RemoteKind::Ssh),
+            "tcp" => Some(RemoteKind::Tcp),
+            _ => None,
+        }
+    }
+}
+
+/// Another machine running Magpie, as added by the user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteHost {
+    pub name: String,
+    pub kind: RemoteKind,
+    /// The SSH destination, or the NNG address when connecting directly
+    pub address: String,
+    /// The path of the socket Magpie listens on, on the remote machine. Only used with SSH.
+    pub remote_socket: String,
+}
+
+impl RemoteHost {
+    /// Whether `address` is something a host of `kind` can be reached at. Direct connections
+    /// are plain `tcp://` only, Magpie is reached through SSH when it needs to be encrypted.
+    pub fn is_valid_address(kind: RemoteKind, address: &str) -> bool {
+        match kind {
+            RemoteKind::Ssh => !address.is_empty(),
+            RemoteKind::Tcp => address
+                .strip_prefix("tcp://")
+                .is_some_and(|host_port| !host_port.is_empty()),
+        }
+    }
+
+    /// The hosts stored in the settings, entries of an unknown kind or with an address that
+    /// can't be connected to are skipped
+    pub fn all(settings: &gio::Settings) -> Vec<Self> {
+        settings
+            .value("remote-hosts")
+            .get::<Vec<(String, String, String, String)>>()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(name, kind, address, remote_socket)| {
+                Some(Self {
+                    name,
+                    kind: RemoteKind::from_id(&kind)?,
+                    address,
+                    remote_socket,
+                })
+            })
+            .filter(|host| Self::is_valid_address(host.kind, &host.address))
+            .collect()
+    }
+
+    pub fn store_all(settings: &gio::Settings, hosts: &[Self]) {
+        let value = hosts
+            .iter()
+            .map(|host| {
+                (
+                    host.name.clone(),
+                    host.kind.id().to_owned(),
+                    host.address.clone(),
+                    host.remote_socket.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if let Err(e) = settings.set_value("remote-hosts", &value.to_variant()) {
+            g_critical!(
+                "MissionCenter::Gatherer",
+                "Failed to set remote-hosts setting: {}",
+                e
+            );
+        }
+    }
+
+    /// The host the window shows the data of, `None` for this machine
+    pub fn active(settings: &gio::Settings) -> Option<Self> {
+        let name = settings.string("active-remote-host");
+        if name.is_empty() {
+            return None;
+        }
+
+        Self::all(settings)
+            .into_iter()
+            .find(|host| host.name == name.as_str())
+    }
+
+    pub fn transport(&self) -> Box<dyn Transport> {
+        match self.kind {
+            RemoteKind::Ssh => Box::new(SshTransport::new(&self.address, &self.remote_socket)),
+            RemoteKind::Tcp => Box::new(TcpTransport::new(&self.address)),
+        }
+    }
+}
+
+/// The transport for the host selected in the settings
+pub fn active_transport(settings: &gio::Settings) -> Box<dyn Transport> {
+    match RemoteHost::active(settings) {
+        Some(host) => host.transport(),
+        None => Box::new(LocalTransport::new()),
+    }
+}
+
+fn random_string<const CAP: usize>() -> ArrayString<CAP> {
+    let mut result = ArrayString::new();
+    for _ in 0..CAP {
+        if rand::random::<bool>() {
+            result.push(rand::random_range(b'a'..=b'z') as char);
+        } else {
+            result.push(rand::random_range(b'0'..=b'9') as char);
+        }
+    }
+
+    result
+}
+
+fn magpie_command(socket_addr: &str) -> std::process::Command {
+    fn executable() -> String {
+        use gtk::glib::g_debug;
+
+        let exe_simple = "missioncenter-magpie".to_owned();
+
+        if is_flatpak() {
+            let flatpak_app_path = flatpak_app_path();
+
+            let cmd_glibc_status = cmd_flatpak_host!(&format!(
+                "{}/bin/missioncenter-magpie-glibc --test",
+                flatpak_app_path
+            ))
+            .status()
+            .is_ok_and(|exit_status| exit_status.success());
+            if cmd_glibc_status {
+                let exe_glibc = format!("{}/bin/missioncenter-magpie-glibc", flatpak_app_path);
+                g_debug!(
+                    "MissionCenter::Gatherer",
+                    "Magpie executable name: {}",
+                    &exe_glibc
+                );
+                return exe_glibc;
+            }
+
+            let cmd_musl_status = cmd_flatpak_host!(&format!(
+                "{}/bin/missioncenter-magpie-musl --test",
+                flatpak_app_path
+            ))
+            .status()
+            .is_ok_and(|exit_status| exit_status.success());
+            if cmd_musl_status {
+                let exe_musl = format!("{}/bin/missioncenter-magpie-musl", flatpak_app_path);
+                g_debug!(
+                    "MissionCenter::Gatherer",
+                    "Magpie executable name: {}",
+                    &exe_musl
+                );
+                return exe_musl;
+            }
+        }
+
+        g_debug!(
+            "MissionCenter::Gatherer",
+            "Magpie executable name: {}",
+            &exe_simple
+        );
+
+        exe_simple
+    }
+
+    let mut command = if is_flatpak() {
+        let mut cmd = std::process::Command::new("/app/bin/missioncenter-spawner");
+        cmd.arg("-v")
+            .arg("--env=LD_PRELOAD=")
+            .arg(format!(
+                "--env=MC_MAGPIE_HW_DB={}/share/missioncenter/hw.db",
+                flatpak_app_path()
+            ))
+            .arg(format!(
+                "--env=RUST_LOG={}",
+                std::env::var("RUST_LOG").unwrap_or_default()
+            ))
+            .arg(executable());
+        cmd
+    } else {
+        let mut cmd = std::process::Command::new(executable());
+
+        if let Some(mut appdir) = std::env::var_os("APPDIR") {
+            appdir.push("/runtime/default");
+            cmd.current_dir(appdir);
+        }
+
+        cmd
+    };
+    command
+        .env_remove("LD_PRELOAD")
+        .stdout(std::process::Stdio::inherit())
+        .stderr(std::process::Stdio::inherit())
+        .arg("--addr")
+        .arg(socket_addr);
+
+    command
+}
@@ -0,0 +1,111 @@
+/* magpie_client/unit_files.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::{HashMap, HashSet};
+
+use magpie_types::services::Service;
+
+/// Reads the `ExecStart` lines of unit files, so that services can be searched by the command
+/// they run. Unit files rarely change, so each one is only read once.
+#[derive(Default)]
+pub struct UnitFiles {
+    exec_start: HashMap<String, Option<String>>,
+}
+
+impl UnitFiles {
+    /// Adds the commands of `services` to `commands`, keyed by service ID
+    pub fn commands<'a>(
+        &mut self,
+        services: impl IntoIterator<Item = &'a Service>,
+        commands: &mut HashMap<u64, String>,
+    ) {
+        if !super::can_inspect_host() {
+            return;
+        }
+
+        for service in services {
+            let Some(file_path) = service.file_path.as_ref() else {
+                continue;
+            };
+
+            let exec_start = self
+                .exec_start
+                .entry(file_path.clone())
+                .or_insert_with(|| read_exec_start(file_path));
+            if let Some(exec_start) = exec_start {
+                commands.insert(service.id, exec_start.clone());
+            }
+        }
+    }
+}
+
+/// Adds the IDs of the services in `services` that are masked to `masked`. Masking a unit
+/// links it to `/dev/null` below `/etc`, which is read again with every update as units get
+/// masked and unmasked.
+pub fn masked<'a>(services: impl IntoIterator<Item = &'a Service>, masked: &mut HashSet<u64>) {
+    if !super::can_inspect_host() {
+        return;
+    }
+
+    let dev_null = std::path::Path::new("/dev/null");
+    masked.extend(
+        services
+            .into_iter()
+            .filter(|service| {
+                service.file_path.as_ref().is_some_and(|file_path| {
+                    std::fs::read_link(file_path).is_ok_and(|target| target == dev_null)
+                })
+            })
+            .map(|service| service.id),
+    );
+}
+
+// A unit can have several `ExecStart` lines, e.g. for oneshot services, they are joined with
+// newlines. Prefixes like `-` or `@` are kept, they are part of what the user wrote.
+fn read_exec_start(file_path: &str) -> Option<String> {
+    let contents = std::fs::read_to_string(file_path).ok()?;
+
+    let mut in_service_section = false;
+    let mut exec_start = Vec::new();
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_service_section = line == "[Service]";
+            continue;
+        }
+
+        if !in_service_section {
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        if key.trim() == "ExecStart" && !value.trim().is_empty() {
+            exec_start.push(value.trim());
+        }
+    }
+
+    if exec_start.is_empty() {
+        None
+    } else {
+        Some(exec_start.join("\n"))
+    }
+}
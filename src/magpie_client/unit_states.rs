@@ -0,0 +1,653 @@
+/* magpie_client/unit_states.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use gtk::gio;
+use gtk::glib::{self, g_debug};
+use magpie_types::services::Service;
+
+/// What systemd is in the middle of doing with a service, Magpie only tells whether it is
+/// running or failed
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ServiceTransition {
+    #[default]
+    None,
+    Activating,
+    Deactivating,
+}
+
+/// How a service fared since boot, as far as systemd keeps track of it
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ServiceStats {
+    /// How long its last start took, from leaving the inactive state until it was active. `None`
+    /// if it did not start since boot.
+    pub start_time: Option<Duration>,
+    /// How often systemd restarted it after it failed or exited, see `NRestarts=`. Restarts
+    /// asked for by the user don't count.
+    pub restarts: u32,
+}
+
+/// Reads the `ServiceStats` of all services. They only change when a service starts or stops,
+/// so they are read again when one of the services changed its state, or once in a while to
+/// catch the restarts that happened in between two readings.
+#[derive(Default)]
+pub struct UnitStats {
+    // Whether each service was running and whether it failed, as of the last read
+    states: HashMap<u64, (bool, bool)>,
+    stats: HashMap<u64, ServiceStats>,
+    read_at: Option<Instant>,
+}
+
+impl UnitStats {
+    const MAX_AGE: Duration = Duration::from_secs(60);
+
+    /// Adds the stats of the services to `stats`, keyed by service ID
+    pub fn stats<'a>(
+        &mut self,
+        user_services: impl IntoIterator<Item = &'a Service>,
+        system_services: impl IntoIterator<Item = &'a Service>,
+        stats: &mut HashMap<u64, ServiceStats>,
+    ) {
+        if super::is_remote() {
+            return;
+        }
+
+        let user_services = user_services.into_iter().collect::<Vec<_>>();
+        let system_services = system_services.into_iter().collect::<Vec<_>>();
+
+        let states = user_services
+            .iter()
+            .chain(system_services.iter())
+            .map(|service| (service.id, (service.running, service.failed)))
+            .collect::<HashMap<_, _>>();
+        let outdated = self
+            .read_at
+            .is_none_or(|read_at| read_at.elapsed() >= Self::MAX_AGE);
+        if outdated || states != self.states {
+            self.states = states;
+            self.stats.clear();
+            self.read_at = Some(Instant::now());
+
+            for (services, user) in [(user_services, true), (system_services, false)] {
+                let units = unit_stats(user);
+                for service in services {
+                    if let Some(unit_stats) = units.get(&unit_name(&service.name)) {
+                        self.stats.insert(service.id, *unit_stats);
+                    }
+                }
+            }
+        }
+
+        stats.extend(self.stats.iter().map(|(id, stats)| (*id, *stats)));
+    }
+}
+
+/// How a start request turned out, as far as systemd can tell shortly after it was made
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StartOutcome {
+    Started,
+    /// Still starting, e.g. a oneshot service that has not finished yet
+    Activating,
+    /// Not running, but its socket is, so it starts with the first connection
+    WaitingForSocket,
+    /// Skipped since one of its `Condition...=` settings did not hold
+    ConditionFailed,
+    Failed,
+    /// The state could not be read
+    Unknown,
+}
+
+/// Adds the services that are starting or stopping to `transitions`, keyed by service ID.
+/// Asking `systemctl` for only the units in those states keeps this cheap.
+pub fn transitions<'a>(
+    user_services: impl IntoIterator<Item = &'a Service>,
+    system_services: impl IntoIterator<Item = &'a Service>,
+    transitions: &mut HashMap<u64, ServiceTransition>,
+) {
+    if super::is_remote() {
+        return;
+    }
+
+    let user_services = user_services.into_iter().collect::<Vec<_>>();
+    let system_services = system_services.into_iter().collect::<Vec<_>>();
+    for (services, user) in [(user_services, true), (system_services, false)] {
+        let units = transitional_units(user);
+        if units.is_empty() {
+            continue;
+        }
+
+        for service in services {
+            if let Some(transition) = units.get(&unit_name(&service.name)) {
+                transitions.insert(service.id, *transition);
+            }
+        }
+    }
+}
+
+/// Looks up the state of the service named `name` and passes it to `on_done`. User services are
+/// only looked for if there is no system service by that name.
+pub fn service_start_outcome(name: &str, on_done: impl FnOnce(StartOutcome) + 'static) {
+    let unit = unit_name(name);
+    show_unit(&unit, false, move |properties| {
+        if properties.get("LoadState").map(String::as_str) != Some("not-found") {
+            on_done(outcome(&properties));
+            return;
+        }
+
+        show_unit(&unit, true, move |properties| on_done(outcome(&properties)));
+    });
+}
+
+/// Reads the unit file of the service named `name` along with its drop-ins, each preceded by a
+/// comment with its path, and passes them to `on_done`. Nothing is passed when it can't be read,
+/// e.g. when connected to another machine.
+pub fn service_unit_file(name: &str, on_done: impl FnOnce(Option<String>) + 'static) {
+    if super::is_remote() {
+        on_done(None);
+        return;
+    }
+
+    let unit = unit_name(name);
+    cat_unit(&unit, false, move |contents| {
+        if contents.is_some() {
+            on_done(contents);
+            return;
+        }
+
+        cat_unit(&unit, true, on_done);
+    });
+}
+
+/// A unit in the dependency tree of a service
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitDependency {
+    pub name: String,
+    /// The index of the unit that pulls this one in, `None` for the service itself
+    pub parent: Option<usize>,
+    pub failed: bool,
+}
+
+/// Reads the units the service named `name` depends on, as far as `systemctl list-dependencies`
+/// expands them, and passes them to `on_done` with the service first and parents before their
+/// children. Nothing is passed when connected to another machine.
+pub fn service_dependencies(name: &str, on_done: impl FnOnce(Vec<UnitDependency>) + 'static) {
+    if super::is_remote() {
+        on_done(vec![]);
+        return;
+    }
+
+    let unit = unit_name(name);
+    list_dependencies(&unit, false, move |units| {
+        // Units that are not found are still listed, just without anything below them
+        if units.len() > 1 {
+            on_done(units);
+            return;
+        }
+
+        list_dependencies(&unit, true, move |user_units| {
+            on_done(if user_units.len() > 1 {
+                user_units
+            } else {
+                units
+            })
+        });
+    });
+}
+
+/// The limits systemd enforces on the cgroup of a service, `None` where there is no limit
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResourceLimits {
+    /// `MemoryMax=`, in bytes
+    pub memory_max: Option<u64>,
+    /// `CPUQuota=`, in percent of a single core
+    pub cpu_quota: Option<f64>,
+    /// `TasksMax=`, the number of processes and threads
+    pub tasks_max: Option<u64>,
+    /// Whether the service is run by the service manager of the user
+    pub user: bool,
+}
+
+impl ResourceLimits {
+    fn assignments(&self) -> [String; 3] {
+        [
+            match self.memory_max {
+                Some(bytes) => format!("MemoryMax={}", bytes),
+                None => "MemoryMax=infinity".to_owned(),
+            },
+            match self.cpu_quota {
+                Some(percent) => format!("CPUQuota={}%", percent.round().max(1.)),
+                // An empty value removes the quota
+                None => "CPUQuota=".to_owned(),
+            },
+            match self.tasks_max {
+                Some(tasks) => format!("TasksMax={}", tasks),
+                None => "TasksMax=infinity".to_owned(),
+            },
+        ]
+    }
+}
+
+/// Reads the resource limits of the service named `name` and passes them to `on_done`. Nothing
+/// is passed when they can't be read, e.g. when connected to another machine.
+pub fn service_resource_limits(
+    name: &str,
+    on_done: impl FnOnce(Option<ResourceLimits>) + 'static,
+) {
+    if super::is_remote() {
+        on_done(None);
+        return;
+    }
+
+    let unit = unit_name(name);
+    show_limits(&unit, false, move |limits| {
+        if limits.is_some() {
+            on_done(limits);
+            return;
+        }
+
+        show_limits(&unit, true, on_done);
+    });
+}
+
+/// Changes the resource limits of the service named `name` with `systemctl set-property`,
+/// which asks for authorization for system services. The limits apply right away and are kept
+/// across restarts. `on_done` gets whether they were changed.
+pub fn set_service_resource_limits(
+    name: &str,
+    limits: ResourceLimits,
+    on_done: impl FnOnce(bool) + 'static,
+) {
+    if super::is_remote() {
+        on_done(false);
+        return;
+    }
+
+    let unit = unit_name(name);
+    let assignments = limits.assignments();
+    let mut args = vec!["set-property", "--", unit.as_str()];
+    args.extend(assignments.iter().map(String::as_str));
+
+    systemctl_succeeded(&args, limits.user, on_done);
+}
+
+fn show_limits(unit: &str, user: bool, on_done: impl FnOnce(Option<ResourceLimits>) + 'static) {
+    systemctl(
+        &[
+            "show",
+            "--property=LoadState,MemoryMax,CPUQuotaPerSecUSec,TasksMax",
+            unit,
+        ],
+        user,
+        move |output| {
+            let properties = output
+                .lines()
+                .filter_map(|line| line.split_once('='))
+                .collect::<HashMap<_, _>>();
+            if properties.get("LoadState").is_none_or(|state| *state == "not-found") {
+                on_done(None);
+                return;
+            }
+
+            let limit = |key: &str| {
+                properties
+                    .get(key)
+                    .filter(|value| **value != "infinity")
+                    .and_then(|value| value.parse::<u64>().ok())
+            };
+            on_done(Some(ResourceLimits {
+                memory_max: limit("MemoryMax"),
+                cpu_quota: properties
+                    .get("CPUQuotaPerSecUSec")
+                    .and_then(|value| parse_timespan_usec(value))
+                    // The CPU time a service may use per second of wall time
+                    .map(|usec| usec as f64 / 10_000.),
+                tasks_max: limit("TasksMax"),
+                user,
+            }));
+        },
+    );
+}
+
+// `systemctl show` prints durations like `1.500s` or `200ms`, and `infinity` when unset
+fn parse_timespan_usec(value: &str) -> Option<u64> {
+    const UNITS: &[(&str, f64)] = &[
+        ("us", 1.),
+        ("\u{b5}s", 1.),
+        ("ms", 1_000.),
+        ("min", 60_000_000.),
+        ("s", 1_000_000.),
+    ];
+
+    UNITS.iter().find_map(|(suffix, factor)| {
+        let number = value.strip_suffix(suffix)?.parse::<f64>().ok()?;
+        Some((number * factor).round() as u64)
+    })
+}
+
+// Magpie leaves the suffix off of some names
+fn unit_name(name: &str) -> String {
+    if name.contains('.') {
+        name.to_owned()
+    } else {
+        format!("{name}.service")
+    }
+}
+
+fn transitional_units(user: bool) -> HashMap<String, ServiceTransition> {
+    let mut args = vec![
+        "systemctl",
+        "list-units",
+        "--type=service",
+        "--state=activating,deactivating",
+        "--all",
+        "--plain",
+        "--no-legend",
+        "--no-pager",
+    ];
+    if user {
+        args.insert(1, "--user");
+    }
+
+    let output = match super::host_command(&args).output() {
+        Ok(output) if output.status.success() => output,
+        Ok(_) => return HashMap::new(),
+        Err(e) => {
+            g_debug!(
+                "MissionCenter::UnitStates",
+                "Failed to list starting and stopping units: {}",
+                e
+            );
+            return HashMap::new();
+        }
+    };
+
+    // Lines hold the unit name, load state, active state, sub state and description
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let unit = fields.next()?;
+            let transition = match fields.nth(1)? {
+                "activating" => ServiceTransition::Activating,
+                "deactivating" => ServiceTransition::Deactivating,
+                _ => return None,
+            };
+            Some((unit.to_owned(), transition))
+        })
+        .collect()
+}
+
+fn unit_stats(user: bool) -> HashMap<String, ServiceStats> {
+    let mut args = vec![
+        "systemctl",
+        "show",
+        "--property=Id,InactiveExitTimestampMonotonic,ActiveEnterTimestampMonotonic,NRestarts",
+        "--",
+        "*.service",
+    ];
+    if user {
+        args.insert(1, "--user");
+    }
+
+    let output = match super::host_command(&args).output() {
+        Ok(output) if output.status.success() => output,
+        Ok(_) => return HashMap::new(),
+        Err(e) => {
+            g_debug!(
+                "MissionCenter::UnitStates",
+                "Failed to read the start times of units: {}",
+                e
+            );
+            return HashMap::new();
+        }
+    };
+
+    // The properties of each unit are followed by an empty line
+    String::from_utf8_lossy(&output.stdout)
+        .split("\n\n")
+        .filter_map(|unit| {
+            let properties = unit
+                .lines()
+                .filter_map(|line| line.split_once('='))
+                .collect::<HashMap<_, _>>();
+            let timestamp = |key: &str| {
+                properties
+                    .get(key)
+                    .and_then(|value| value.parse::<u64>().ok())
+                    .filter(|usec| *usec > 0)
+            };
+
+            let start_time = match (
+                timestamp("InactiveExitTimestampMonotonic"),
+                timestamp("ActiveEnterTimestampMonotonic"),
+            ) {
+                (Some(start), Some(end)) if end >= start => {
+                    Some(Duration::from_micros(end - start))
+                }
+                _ => None,
+            };
+            let stats = ServiceStats {
+                start_time,
+                restarts: properties
+                    .get("NRestarts")
+                    .and_then(|value| value.parse().ok())
+                    .unwrap_or_default(),
+            };
+
+            Some((properties.get("Id")?.to_string(), stats))
+        })
+        .collect()
+}
+
+fn show_unit(unit: &str, user: bool, on_done: impl FnOnce(HashMap<String, String>) + 'static) {
+    systemctl(
+        &[
+            "show",
+            "--property=LoadState,ActiveState,Result,ConditionResult,TriggeredBy",
+            unit,
+        ],
+        user,
+        move |output| {
+            on_done(
+                output
+                    .lines()
+                    .filter_map(|line| line.split_once('='))
+                    .map(|(key, value)| (key.to_owned(), value.to_owned()))
+                    .collect(),
+            );
+        },
+    );
+}
+
+fn cat_unit(unit: &str, user: bool, on_done: impl FnOnce(Option<String>) + 'static) {
+    systemctl(&["cat", "--no-pager", "--", unit], user, move |output| {
+        // Units that are not found only get an error message, which is not read
+        on_done(Some(output.to_string()).filter(|contents| !contents.trim().is_empty()));
+    });
+}
+
+fn list_dependencies(unit: &str, user: bool, on_done: impl FnOnce(Vec<UnitDependency>) + 'static) {
+    systemctl(
+        &["list-dependencies", "--plain", "--no-pager", "--", unit],
+        user,
+        move |output| {
+            let mut units = parse_dependencies(&output);
+            if units.len() <= 1 {
+                on_done(units);
+                return;
+            }
+
+            systemctl(
+                &[
+                    "list-units",
+                    "--state=failed",
+                    "--all",
+                    "--plain",
+                    "--no-legend",
+                    "--no-pager",
+                ],
+                user,
+                move |output| {
+                    let failed = output
+                        .lines()
+                        .filter_map(|line| line.split_whitespace().next())
+                        .collect::<Vec<_>>();
+                    for unit in &mut units {
+                        unit.failed = failed.contains(&unit.name.as_str());
+                    }
+                    on_done(units);
+                },
+            );
+        },
+    );
+}
+
+// `--plain` indents the units by two spaces for every level below the service
+fn parse_dependencies(output: &str) -> Vec<UnitDependency> {
+    let mut units = Vec::<UnitDependency>::new();
+    // The last unit seen on each level, which the units on the next level belong to
+    let mut ancestors = Vec::<usize>::new();
+
+    for line in output.lines() {
+        let name = line.trim();
+        if name.is_empty() {
+            continue;
+        }
+
+        let depth = (line.len() - line.trim_start().len()) / 2;
+        if (depth == 0) != units.is_empty() {
+            // The service comes first, and only one tree is asked for
+            break;
+        }
+        ancestors.truncate(depth);
+        let parent = ancestors.last().copied();
+
+        units.push(UnitDependency {
+            name: name.to_owned(),
+            parent,
+            failed: false,
+        });
+        ancestors.push(units.len() - 1);
+    }
+
+    units
+}
+
+/// Runs `systemctl` on the host with `args` and passes what it printed to `on_done`
+fn systemctl(args: &[&str], user: bool, on_done: impl FnOnce(glib::GString) + 'static) {
+    run_on_host("systemctl", args, user, on_done);
+}
+
+/// Runs `program` on the host with `args`, and `--user` before them if `user` is set, and passes
+/// what it printed to `on_done`. Nothing is passed if it could not be run.
+pub(super) fn run_on_host(
+    program: &str,
+    args: &[&str],
+    user: bool,
+    on_done: impl FnOnce(glib::GString) + 'static,
+) {
+    let mut argv = vec![program];
+    if user {
+        argv.push("--user");
+    }
+    argv.extend_from_slice(args);
+
+    let subprocess = match super::host_subprocess(
+        &argv,
+        gio::SubprocessFlags::STDOUT_PIPE | gio::SubprocessFlags::STDERR_SILENCE,
+    ) {
+        Ok(subprocess) => subprocess,
+        Err(e) => {
+            g_debug!(
+                "MissionCenter::UnitStates",
+                "Failed to run {} {}: {}",
+                program,
+                args.join(" "),
+                e
+            );
+            on_done(glib::GString::default());
+            return;
+        }
+    };
+
+    subprocess.communicate_utf8_async(None, None::<&gio::Cancellable>, move |result| {
+        let output = match result {
+            Ok((Some(output), _)) => output,
+            _ => glib::GString::default(),
+        };
+
+        on_done(output);
+    });
+}
+
+/// Runs `systemctl` on the host with `args` and passes whether it succeeded to `on_done`. It is
+/// allowed to ask for authorization, which can take a while.
+fn systemctl_succeeded(args: &[&str], user: bool, on_done: impl FnOnce(bool) + 'static) {
+    let mut argv = vec!["systemctl"];
+    if user {
+        argv.push("--user");
+    }
+    argv.extend_from_slice(args);
+
+    let subprocess = match super::host_subprocess(
+        &argv,
+        gio::SubprocessFlags::STDOUT_SILENCE | gio::SubprocessFlags::STDERR_SILENCE,
+    ) {
+        Ok(subprocess) => subprocess,
+        Err(e) => {
+            g_debug!(
+                "MissionCenter::UnitStates",
+                "Failed to run systemctl {}: {}",
+                args.join(" "),
+                e
+            );
+            on_done(false);
+            return;
+        }
+    };
+
+    subprocess.wait_check_async(None::<&gio::Cancellable>, move |result| {
+        on_done(result.is_ok());
+    });
+}
+
+fn outcome(properties: &HashMap<String, String>) -> StartOutcome {
+    let property = |key: &str| properties.get(key).map(String::as_str).unwrap_or_default();
+
+    match property("ActiveState") {
+        "active" | "reloading" => StartOutcome::Started,
+        "activating" => StartOutcome::Activating,
+        "failed" => StartOutcome::Failed,
+        "inactive" if property("ConditionResult") == "no" => StartOutcome::ConditionFailed,
+        "inactive"
+            if property("TriggeredBy")
+                .split_whitespace()
+                .any(|unit| unit.ends_with(".socket")) =>
+        {
+            StartOutcome::WaitingForSocket
+        }
+        // Oneshot services are inactive again once they ran
+        "inactive" if property("Result") == "success" => StartOutcome::Started,
+        "inactive" | "deactivating" => StartOutcome::Failed,
+        _ => StartOutcome::Unknown,
+    }
+}
@@ -0,0 +1,278 @@
+/* magpie_client/usage_history.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use gtk::glib::{self, g_critical, g_debug};
+
+use super::Readings;
+
+/// Days of totals kept on disk, older ones are dropped
+const KEPT_DAYS: usize = 30;
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+/// Gaps between readings, e.g. while the machine was suspended, only count up to this long
+const MAX_READING_GAP: Duration = Duration::from_secs(60);
+
+/// What an app used over some number of days
+#[derive(Debug, Clone, Default)]
+pub struct AppUsage {
+    pub name: String,
+    pub cpu_seconds: f64,
+    pub disk_written: f64,
+    pub network_transferred: f64,
+}
+
+impl AppUsage {
+    fn add(&mut self, other: &AppUsage) {
+        self.cpu_seconds += other.cpu_seconds;
+        self.disk_written += other.disk_written;
+        self.network_transferred += other.network_transferred;
+    }
+}
+
+struct UsageHistory {
+    // Keyed by the local date as YYYY-MM-DD, which sorts by time, then by app ID
+    days: BTreeMap<String, HashMap<String, AppUsage>>,
+    last_reading: Option<Instant>,
+    last_save: Instant,
+    dirty: bool,
+}
+
+impl UsageHistory {
+    fn path() -> PathBuf {
+        glib::user_data_dir()
+            .join("missioncenter")
+            .join("usage-history.tsv")
+    }
+
+    fn load() -> Self {
+        let mut days = BTreeMap::<String, HashMap<String, AppUsage>>::new();
+
+        match std::fs::read_to_string(Self::path()) {
+            Ok(contents) => {
+                for line in contents.lines() {
+                    let Some((date, id, usage)) = parse_line(line) else {
+                        continue;
+                    };
+                    days.entry(date.to_owned())
+                        .or_default()
+                        .insert(id.to_owned(), usage);
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                g_critical!(
+                    "MissionCenter::UsageHistory",
+                    "Failed to read usage history: {}",
+                    e
+                );
+            }
+        }
+
+        Self {
+            days,
+            last_reading: None,
+            last_save: Instant::now(),
+            dirty: false,
+        }
+    }
+
+    /// Adds what the apps used since the previous readings to today's totals. Only the processes
+    /// Magpie assigns to an app are counted, not the ones they started outside of it.
+    fn record(&mut self, readings: &Readings) {
+        let now = Instant::now();
+        let Some(last_reading) = self.last_reading.replace(now) else {
+            return;
+        };
+        let elapsed = now
+            .duration_since(last_reading)
+            .min(MAX_READING_GAP)
+            .as_secs_f64();
+
+        let Some(today) = glib::DateTime::now_local()
+            .and_then(|now| now.format("%F"))
+            .ok()
+        else {
+            return;
+        };
+
+        // Process CPU usage is a share of all cores
+        let core_count = readings.cpu.core_usage_percent.len().max(1) as f64;
+
+        let day = self.days.entry(today.to_string()).or_default();
+        for app in readings.running_apps.values() {
+            let usage = day.entry(app.id.clone()).or_default();
+            if usage.name != app.name {
+                usage.name = app.name.clone();
+            }
+
+            for process in app
+                .pids
+                .iter()
+                .filter_map(|pid| readings.running_processes.get(pid))
+            {
+                let stats = &process.usage_stats;
+                usage.cpu_seconds += stats.cpu_usage as f64 / 100. * core_count * elapsed;
+                usage.disk_written += stats.disk_write_usage as f64 * elapsed;
+                usage.network_transferred += stats.network_usage as f64 * elapsed;
+            }
+        }
+
+        while self.days.len() > KEPT_DAYS {
+            self.days.pop_first();
+        }
+        self.dirty = true;
+    }
+
+    fn save(&mut self) {
+        self.last_save = Instant::now();
+        if !self.dirty {
+            return;
+        }
+        self.dirty = false;
+
+        let mut contents = String::new();
+        for (date, apps) in &self.days {
+            for (id, usage) in apps {
+                let _ = writeln!(
+                    contents,
+                    "{}\t{}\t{:.3}\t{:.0}\t{:.0}\t{}",
+                    date,
+                    id.replace(['\t', '\n'], " "),
+                    usage.cpu_seconds,
+                    usage.disk_written,
+                    usage.network_transferred,
+                    usage.name.replace(['\t', '\n'], " ")
+                );
+            }
+        }
+
+        // Written next to the old file and moved over it, so it is never left half written
+        let path = Self::path();
+        let temp_path = path.with_extension("tsv.new");
+        let result = path
+            .parent()
+            .map(std::fs::create_dir_all)
+            .unwrap_or(Ok(()))
+            .and_then(|_| std::fs::write(&temp_path, contents))
+            .and_then(|_| std::fs::rename(&temp_path, &path));
+        match result {
+            Ok(_) => {
+                g_debug!(
+                    "MissionCenter::UsageHistory",
+                    "Saved usage history to {}",
+                    path.display()
+                );
+            }
+            Err(e) => {
+                g_critical!(
+                    "MissionCenter::UsageHistory",
+                    "Failed to save usage history: {}",
+                    e
+                );
+            }
+        }
+    }
+}
+
+fn history() -> &'static Mutex<UsageHistory> {
+    static HISTORY: OnceLock<Mutex<UsageHistory>> = OnceLock::new();
+    HISTORY.get_or_init(|| Mutex::new(UsageHistory::load()))
+}
+
+// Lines hold the date, app ID, CPU seconds, bytes written, bytes transferred and app name
+fn parse_line(line: &str) -> Option<(&str, &str, AppUsage)> {
+    let mut fields = line.splitn(6, '\t');
+    let date = fields.next()?;
+    let id = fields.next()?;
+    let cpu_seconds = fields.next()?.parse().ok()?;
+    let disk_written = fields.next()?.parse().ok()?;
+    let network_transferred = fields.next()?.parse().ok()?;
+    let name = fields.next()?.to_owned();
+
+    Some((
+        date,
+        id,
+        AppUsage {
+            name,
+            cpu_seconds,
+            disk_written,
+            network_transferred,
+        },
+    ))
+}
+
+/// Called on the gather thread with every new readings, saves the totals once a minute
+pub(super) fn record(readings: &Readings) {
+    let Ok(mut history) = history().lock() else {
+        return;
+    };
+
+    history.record(readings);
+    if history.last_save.elapsed() >= SAVE_INTERVAL {
+        history.save();
+    }
+}
+
+pub(super) fn save() {
+    if let Ok(mut history) = history().lock() {
+        history.save();
+    }
+}
+
+/// The totals of each app over the last `days` days, today included, by the most CPU time
+pub fn app_totals(days: u32) -> Vec<AppUsage> {
+    let Some(first_day) = glib::DateTime::now_local()
+        .and_then(|now| now.add_days(1 - days.max(1) as i32))
+        .and_then(|first_day| first_day.format("%F"))
+        .ok()
+    else {
+        return vec![];
+    };
+
+    let Ok(history) = history().lock() else {
+        return vec![];
+    };
+
+    let mut totals = HashMap::<&str, AppUsage>::new();
+    for apps in history
+        .days
+        .range(first_day.to_string()..)
+        .rev()
+        .map(|(_, apps)| apps)
+    {
+        for (id, usage) in apps {
+            let total = totals.entry(id.as_str()).or_default();
+            if total.name.is_empty() {
+                // Days are visited from the newest, which has the name the app goes by now
+                total.name = usage.name.clone();
+            }
+            total.add(usage);
+        }
+    }
+
+    let mut totals = totals.into_values().collect::<Vec<_>>();
+    totals.sort_unstable_by(|a, b| b.cpu_seconds.total_cmp(&a.cpu_seconds));
+    totals
+}
@@ -38,12 +38,24 @@ use crate::i18n::ni18n_f;
 mod about_system_dialog;
 mod application;
 mod apps_page;
+mod benchmark;
+mod command_line;
+mod dbus_service;
+mod diagnostic_report;
+mod exit_notifications;
 mod i18n;
+mod keep_awake;
+mod kill_list;
 mod magpie_client;
 mod performance_page;
 mod preferences;
+mod remote_hosts_dialog;
+mod service_restarts;
 mod services_page;
+mod session_recording;
+mod startup_analysis_dialog;
 mod table_view;
+mod usage_history_dialog;
 mod widgets;
 mod window;
 
@@ -431,7 +443,7 @@ fn main() {
 
     let app = MissionCenterApplication::new(
         "io.missioncenter.MissionCenter",
-        &gio::ApplicationFlags::empty(),
+        &gio::ApplicationFlags::HANDLES_COMMAND_LINE,
     );
     gtk::Application::set_default(app.upcast_ref::<gtk::Application>());
 
@@ -18,10 +18,16 @@
  * SPDX-License-Identifier: GPL-3.0-or-later
  */
 
+use std::cell::RefCell;
+
 use adw::{prelude::*, subclass::prelude::*, SpinRow, SwitchRow};
 use gtk::{gio, glib, Scale};
 
+use crate::i18n::{i18n, i18n_f};
 use crate::settings;
+use crate::service_restarts::{self, RestartCondition, RestartRule};
+use crate::table_view::highlight_rules::{self, HighlightColor, HighlightRule, RuleField};
+use crate::table_view::ignored;
 
 const MAX_INTERVAL_TICKS: u64 = 200;
 const MIN_INTERVAL_TICKS: u64 = 10;
@@ -71,6 +77,23 @@ macro_rules! connect_toggle_pair_to_setting {
     };
 }
 
+macro_rules! connect_entry_to_setting {
+    ($this: expr, $entry_row: expr, $setting: literal) => {
+        $entry_row.connect_apply({
+            move |entry_row| {
+                if let Err(e) = settings!().set_string($setting, entry_row.text().trim()) {
+                    gtk::glib::g_critical!(
+                        "MissionCenter::Preferences",
+                        "Failed to set {} setting: {}",
+                        $setting,
+                        e
+                    );
+                }
+            }
+        });
+    };
+}
+
 mod imp {
     use super::*;
 
@@ -104,13 +127,79 @@ mod imp {
         #[template_child]
         pub merged_process_stats: TemplateChild<SwitchRow>,
         #[template_child]
+        pub match_desktop_files: TemplateChild<SwitchRow>,
+        #[template_child]
+        pub hide_kernel_threads: TemplateChild<SwitchRow>,
+        #[template_child]
         pub remember_sorting: TemplateChild<SwitchRow>,
         #[template_child]
+        pub stable_sorting: TemplateChild<SwitchRow>,
+        #[template_child]
         pub remember_column_order: TemplateChild<SwitchRow>,
         #[template_child]
         pub core_count_affects_percentages: TemplateChild<SwitchRow>,
         #[template_child]
         pub show_column_separators: TemplateChild<SwitchRow>,
+        #[template_child]
+        pub show_summary: TemplateChild<SwitchRow>,
+        #[template_child]
+        pub show_totals: TemplateChild<SwitchRow>,
+        #[template_child]
+        pub show_top_process_in_title: TemplateChild<SwitchRow>,
+        #[template_child]
+        pub row_density: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub heat_map: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub apps_page_hide_sections: TemplateChild<SwitchRow>,
+        #[template_child]
+        pub services_page_hide_sections: TemplateChild<SwitchRow>,
+        #[template_child]
+        pub search_unit_files: TemplateChild<SwitchRow>,
+        #[template_child]
+        pub end_task_grace_period: TemplateChild<SpinRow>,
+        #[template_child]
+        pub end_task_auto_kill: TemplateChild<SwitchRow>,
+        #[template_child]
+        pub metrics_exporter_enabled: TemplateChild<SwitchRow>,
+        #[template_child]
+        pub metrics_exporter_port: TemplateChild<SpinRow>,
+        #[template_child]
+        pub metrics_exporter_listen_all: TemplateChild<SwitchRow>,
+        #[template_child]
+        pub developer_mode: TemplateChild<SwitchRow>,
+        #[template_child]
+        pub debugger_command: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub profiler_command: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub trace_command: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub kill_list_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub kill_list_entry: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub ignore_list_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub ignore_list_entry: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub highlight_rules_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub highlight_rule_field: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub highlight_rule_color: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub highlight_rule_bold: TemplateChild<SwitchRow>,
+        #[template_child]
+        pub highlight_rule_entry: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub service_restart_rules_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub service_restart_rule_service: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub service_restart_rule_condition: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub service_restart_rule_entry: TemplateChild<adw::EntryRow>,
 
         #[template_child]
         pub toggle_group_memory_unit: TemplateChild<adw::ToggleGroup>,
@@ -148,6 +237,11 @@ mod imp {
         pub toggle_net_base_2: TemplateChild<adw::Toggle>,
         #[template_child]
         pub toggle_net_base_10: TemplateChild<adw::Toggle>,
+
+        kill_list_rows: RefCell<Vec<adw::ActionRow>>,
+        ignore_list_rows: RefCell<Vec<adw::ActionRow>>,
+        highlight_rule_rows: RefCell<Vec<adw::ActionRow>>,
+        service_restart_rule_rows: RefCell<Vec<adw::ExpanderRow>>,
     }
 
     impl PreferencesPage {
@@ -194,6 +288,334 @@ mod imp {
                 );
             }
         }
+
+        pub fn populate_kill_list(&self) {
+            for row in self.kill_list_rows.take() {
+                self.kill_list_group.remove(&row);
+            }
+
+            let mut rows = vec![];
+            for name in settings!().strv("kill-list") {
+                let row = adw::ActionRow::builder()
+                    .title(glib::markup_escape_text(name.as_str()))
+                    .build();
+
+                let remove_button = gtk::Button::builder()
+                    .icon_name("user-trash-symbolic")
+                    .tooltip_text(i18n("Remove"))
+                    .valign(gtk::Align::Center)
+                    .css_classes(["flat"])
+                    .build();
+                remove_button.connect_clicked({
+                    let this = self.obj().downgrade();
+                    let name = name.to_string();
+                    move |_| {
+                        if let Some(this) = this.upgrade() {
+                            this.imp().remove_from_kill_list(&name);
+                        }
+                    }
+                });
+                row.add_suffix(&remove_button);
+
+                self.kill_list_group.add(&row);
+                rows.push(row);
+            }
+            self.kill_list_rows.replace(rows);
+        }
+
+        fn add_to_kill_list(&self) {
+            let name = self.kill_list_entry.text().trim().to_owned();
+
+            let settings = settings!();
+            let mut names = settings
+                .strv("kill-list")
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>();
+
+            // Entries are compared with the file name of the executable, never with a path
+            if name.is_empty() || name.contains('/') || names.contains(&name) {
+                self.kill_list_entry.add_css_class("error");
+                return;
+            }
+            self.kill_list_entry.remove_css_class("error");
+
+            names.push(name);
+            self.store_kill_list(names);
+            self.kill_list_entry.set_text("");
+        }
+
+        fn remove_from_kill_list(&self, name: &str) {
+            let names = settings!()
+                .strv("kill-list")
+                .iter()
+                .filter(|entry| entry.as_str() != name)
+                .map(|entry| entry.to_string())
+                .collect::<Vec<_>>();
+            self.store_kill_list(names);
+        }
+
+        fn store_kill_list(&self, names: Vec<String>) {
+            if let Err(e) = settings!().set_strv("kill-list", names) {
+                glib::g_critical!(
+                    "MissionCenter::Preferences",
+                    "Failed to set kill-list setting: {}",
+                    e
+                );
+            }
+            self.populate_kill_list();
+        }
+    }
+
+    impl PreferencesPage {
+        pub fn populate_ignore_list(&self) {
+            for row in self.ignore_list_rows.take() {
+                self.ignore_list_group.remove(&row);
+            }
+
+            let mut rows = vec![];
+            for entry in settings!().strv("ignore-list") {
+                let row = adw::ActionRow::builder()
+                    .title(glib::markup_escape_text(entry.as_str()))
+                    .build();
+
+                let remove_button = gtk::Button::builder()
+                    .icon_name("user-trash-symbolic")
+                    .tooltip_text(i18n("Show Again"))
+                    .valign(gtk::Align::Center)
+                    .css_classes(["flat"])
+                    .build();
+                remove_button.connect_clicked({
+                    let this = self.obj().downgrade();
+                    let entry = entry.to_string();
+                    move |_| {
+                        if let Some(this) = this.upgrade() {
+                            ignored::set_ignored(&entry, false);
+                            this.imp().populate_ignore_list();
+                        }
+                    }
+                });
+                row.add_suffix(&remove_button);
+
+                self.ignore_list_group.add(&row);
+                rows.push(row);
+            }
+            self.ignore_list_rows.replace(rows);
+        }
+
+        fn add_to_ignore_list(&self) {
+            let entry = self.ignore_list_entry.text().trim().to_owned();
+
+            let known = settings!()
+                .strv("ignore-list")
+                .iter()
+                .any(|ignored| ignored.as_str() == entry);
+            if entry.is_empty() || known {
+                self.ignore_list_entry.add_css_class("error");
+                return;
+            }
+            self.ignore_list_entry.remove_css_class("error");
+
+            ignored::set_ignored(&entry, true);
+            self.ignore_list_entry.set_text("");
+            self.populate_ignore_list();
+        }
+    }
+
+    impl PreferencesPage {
+        pub fn populate_highlight_rules(&self) {
+            for row in self.highlight_rule_rows.take() {
+                self.highlight_rules_group.remove(&row);
+            }
+
+            let mut rows = vec![];
+            for (index, rule) in highlight_rules::load().into_iter().enumerate() {
+                let field = match rule.field {
+                    RuleField::Name => i18n("Name"),
+                    RuleField::CommandLine => i18n("Command Line"),
+                };
+                let color = match rule.color {
+                    HighlightColor::Accent => i18n("Accent"),
+                    HighlightColor::Success => i18n("Green"),
+                    HighlightColor::Warning => i18n("Yellow"),
+                    HighlightColor::Error => i18n("Red"),
+                };
+                let subtitle = if rule.bold {
+                    format!("{} · {} · {}", field, color, i18n("Bold"))
+                } else {
+                    format!("{} · {}", field, color)
+                };
+
+                let row = adw::ActionRow::builder()
+                    .title(glib::markup_escape_text(&rule.pattern))
+                    .subtitle(subtitle)
+                    .build();
+                // The rows preview the highlight they add
+                for css_class in rule.css_classes() {
+                    row.add_css_class(css_class);
+                }
+
+                let remove_button = gtk::Button::builder()
+                    .icon_name("user-trash-symbolic")
+                    .tooltip_text(i18n("Remove"))
+                    .valign(gtk::Align::Center)
+                    .css_classes(["flat"])
+                    .build();
+                remove_button.connect_clicked({
+                    let this = self.obj().downgrade();
+                    move |_| {
+                        if let Some(this) = this.upgrade() {
+                            this.imp().remove_highlight_rule(index);
+                        }
+                    }
+                });
+                row.add_suffix(&remove_button);
+
+                self.highlight_rules_group.add(&row);
+                rows.push(row);
+            }
+            self.highlight_rule_rows.replace(rows);
+        }
+
+        fn add_highlight_rule(&self) {
+            let pattern = self.highlight_rule_entry.text().trim().to_owned();
+
+            let mut rules = highlight_rules::load();
+            let invalid = match highlight_rules::validate(&pattern) {
+                _ if pattern.is_empty() => Some(String::new()),
+                Err(e) => Some(e),
+                Ok(()) => None,
+            };
+            if let Some(error) = invalid {
+                self.highlight_rule_entry.add_css_class("error");
+                self.highlight_rule_entry
+                    .set_tooltip_text((!error.is_empty()).then_some(error.as_str()));
+                return;
+            }
+            self.highlight_rule_entry.remove_css_class("error");
+            self.highlight_rule_entry.set_tooltip_text(None);
+
+            let field = RuleField::ALL
+                .get(self.highlight_rule_field.selected() as usize)
+                .copied()
+                .unwrap_or(RuleField::Name);
+            let color = HighlightColor::ALL
+                .get(self.highlight_rule_color.selected() as usize)
+                .copied()
+                .unwrap_or(HighlightColor::Accent);
+            rules.push(HighlightRule {
+                pattern,
+                field,
+                color,
+                bold: self.highlight_rule_bold.is_active(),
+            });
+            highlight_rules::save(&rules);
+
+            self.highlight_rule_entry.set_text("");
+            self.populate_highlight_rules();
+        }
+
+        fn remove_highlight_rule(&self, index: usize) {
+            let mut rules = highlight_rules::load();
+            if index < rules.len() {
+                rules.remove(index);
+            }
+            highlight_rules::save(&rules);
+            self.populate_highlight_rules();
+        }
+    }
+
+    impl PreferencesPage {
+        pub fn populate_service_restart_rules(&self) {
+            for row in self.service_restart_rule_rows.take() {
+                self.service_restart_rules_group.remove(&row);
+            }
+
+            let mut rows = vec![];
+            for (index, rule) in service_restarts::load().into_iter().enumerate() {
+                let history = service_restarts::history(&rule);
+                let subtitle = match history.first() {
+                    Some(trigger) => i18n_f("Last restarted {}", &[&trigger.description()]),
+                    None => i18n("Not triggered yet"),
+                };
+
+                let row = adw::ExpanderRow::builder()
+                    .title(glib::markup_escape_text(&format!(
+                        "{} · {}",
+                        rule.service,
+                        rule.condition.description()
+                    )))
+                    .subtitle(glib::markup_escape_text(&subtitle))
+                    .enable_expansion(!history.is_empty())
+                    .build();
+                for trigger in &history {
+                    row.add_row(
+                        &adw::ActionRow::builder()
+                            .title(glib::markup_escape_text(&trigger.description()))
+                            .css_classes(["numeric"])
+                            .build(),
+                    );
+                }
+
+                let remove_button = gtk::Button::builder()
+                    .icon_name("user-trash-symbolic")
+                    .tooltip_text(i18n("Remove"))
+                    .valign(gtk::Align::Center)
+                    .css_classes(["flat"])
+                    .build();
+                remove_button.connect_clicked({
+                    let this = self.obj().downgrade();
+                    move |_| {
+                        if let Some(this) = this.upgrade() {
+                            this.imp().remove_service_restart_rule(index);
+                        }
+                    }
+                });
+                row.add_suffix(&remove_button);
+
+                self.service_restart_rules_group.add(&row);
+                rows.push(row);
+            }
+            self.service_restart_rule_rows.replace(rows);
+        }
+
+        fn add_service_restart_rule(&self) {
+            let service = self.service_restart_rule_service.text().trim().to_owned();
+            if service.is_empty() {
+                self.service_restart_rule_service.add_css_class("error");
+                return;
+            }
+            self.service_restart_rule_service.remove_css_class("error");
+
+            let Some(condition) = RestartCondition::parse(
+                self.service_restart_rule_condition.selected() as usize,
+                &self.service_restart_rule_entry.text(),
+            ) else {
+                self.service_restart_rule_entry.add_css_class("error");
+                return;
+            };
+            self.service_restart_rule_entry.remove_css_class("error");
+
+            let rule = RestartRule { service, condition };
+            let mut rules = service_restarts::load();
+            if !rules.contains(&rule) {
+                rules.push(rule);
+                service_restarts::save(&rules);
+            }
+
+            self.service_restart_rule_service.set_text("");
+            self.service_restart_rule_entry.set_text("");
+            self.populate_service_restart_rules();
+        }
+
+        fn remove_service_restart_rule(&self, index: usize) {
+            let mut rules = service_restarts::load();
+            if index < rules.len() {
+                rules.remove(index);
+            }
+            service_restarts::save(&rules);
+            self.populate_service_restart_rules();
+        }
     }
 
     #[glib::object_subclass]
@@ -260,7 +682,18 @@ mod imp {
                 self.merged_process_stats,
                 "apps-page-merged-process-stats"
             );
+            connect_switch_to_setting!(
+                self,
+                self.match_desktop_files,
+                "apps-page-match-desktop-files"
+            );
+            connect_switch_to_setting!(
+                self,
+                self.hide_kernel_threads,
+                "apps-page-hide-kernel-threads"
+            );
             connect_switch_to_setting!(self, self.remember_sorting, "apps-page-remember-sorting");
+            connect_switch_to_setting!(self, self.stable_sorting, "apps-page-stable-sorting");
             connect_switch_to_setting!(
                 self,
                 self.remember_column_order,
@@ -276,6 +709,131 @@ mod imp {
                 self.show_column_separators,
                 "apps-page-show-column-separators"
             );
+            connect_switch_to_setting!(self, self.show_summary, "apps-page-show-summary");
+            connect_switch_to_setting!(self, self.show_totals, "apps-page-show-totals");
+            connect_switch_to_setting!(
+                self,
+                self.show_top_process_in_title,
+                "apps-page-show-top-process-in-title"
+            );
+            self.row_density.connect_selected_notify(|combo_row| {
+                if let Err(e) =
+                    settings!().set_enum("apps-page-row-density", combo_row.selected() as i32)
+                {
+                    gtk::glib::g_critical!(
+                        "MissionCenter::Preferences",
+                        "Failed to set apps-page-row-density setting: {}",
+                        e
+                    );
+                }
+            });
+            self.heat_map.connect_selected_notify(|combo_row| {
+                if let Err(e) =
+                    settings!().set_enum("apps-page-heat-map", combo_row.selected() as i32)
+                {
+                    gtk::glib::g_critical!(
+                        "MissionCenter::Preferences",
+                        "Failed to set apps-page-heat-map setting: {}",
+                        e
+                    );
+                }
+            });
+            connect_switch_to_setting!(
+                self,
+                self.apps_page_hide_sections,
+                "apps-page-hide-sections"
+            );
+            connect_switch_to_setting!(
+                self,
+                self.services_page_hide_sections,
+                "services-page-hide-sections"
+            );
+            connect_switch_to_setting!(
+                self,
+                self.search_unit_files,
+                "services-page-search-unit-files"
+            );
+            self.end_task_grace_period.connect_value_notify(|spin_row| {
+                if let Err(e) =
+                    settings!().set_uint("apps-page-end-task-grace-period", spin_row.value() as u32)
+                {
+                    gtk::glib::g_critical!(
+                        "MissionCenter::Preferences",
+                        "Failed to set apps-page-end-task-grace-period setting: {}",
+                        e
+                    );
+                }
+            });
+            connect_switch_to_setting!(
+                self,
+                self.end_task_auto_kill,
+                "apps-page-end-task-auto-kill"
+            );
+            connect_switch_to_setting!(
+                self,
+                self.metrics_exporter_enabled,
+                "metrics-exporter-enabled"
+            );
+            self.metrics_exporter_port.connect_value_notify(|spin_row| {
+                if let Err(e) =
+                    settings!().set_uint("metrics-exporter-port", spin_row.value() as u32)
+                {
+                    gtk::glib::g_critical!(
+                        "MissionCenter::Preferences",
+                        "Failed to set metrics-exporter-port setting: {}",
+                        e
+                    );
+                }
+            });
+            connect_switch_to_setting!(
+                self,
+                self.metrics_exporter_listen_all,
+                "metrics-exporter-listen-all"
+            );
+            connect_switch_to_setting!(self, self.developer_mode, "developer-mode");
+            connect_entry_to_setting!(
+                self,
+                self.debugger_command,
+                "developer-tools-debugger-command"
+            );
+            connect_entry_to_setting!(
+                self,
+                self.profiler_command,
+                "developer-tools-profiler-command"
+            );
+            connect_entry_to_setting!(self, self.trace_command, "developer-tools-trace-command");
+            self.kill_list_entry.connect_apply({
+                let this = self.obj().downgrade();
+                move |_| {
+                    if let Some(this) = this.upgrade() {
+                        this.imp().add_to_kill_list();
+                    }
+                }
+            });
+            self.ignore_list_entry.connect_apply({
+                let this = self.obj().downgrade();
+                move |_| {
+                    if let Some(this) = this.upgrade() {
+                        this.imp().add_to_ignore_list();
+                    }
+                }
+            });
+            self.highlight_rule_entry.connect_apply({
+                let this = self.obj().downgrade();
+                move |_| {
+                    if let Some(this) = this.upgrade() {
+                        this.imp().add_highlight_rule();
+                    }
+                }
+            });
+            self.service_restart_rule_entry.connect_apply({
+                let this = self.obj().downgrade();
+                move |_| {
+                    if let Some(this) = this.upgrade() {
+                        this.imp().add_service_restart_rule();
+                    }
+                }
+            });
 
             connect_toggle_pair_to_setting!(
                 self,
@@ -359,12 +917,56 @@ impl PreferencesPage {
             .set_active(settings.boolean("apps-page-merged-process-stats"));
         imp.remember_sorting
             .set_active(settings.boolean("apps-page-remember-sorting"));
+        imp.stable_sorting
+            .set_active(settings.boolean("apps-page-stable-sorting"));
+        imp.match_desktop_files
+            .set_active(settings.boolean("apps-page-match-desktop-files"));
+        imp.hide_kernel_threads
+            .set_active(settings.boolean("apps-page-hide-kernel-threads"));
         imp.remember_column_order
             .set_active(settings.boolean("apps-page-remember-column-order"));
         imp.core_count_affects_percentages
             .set_active(settings.boolean("apps-page-core-count-affects-percentages"));
         imp.show_column_separators
             .set_active(settings.boolean("apps-page-show-column-separators"));
+        imp.show_summary
+            .set_active(settings.boolean("apps-page-show-summary"));
+        imp.show_totals
+            .set_active(settings.boolean("apps-page-show-totals"));
+        imp.show_top_process_in_title
+            .set_active(settings.boolean("apps-page-show-top-process-in-title"));
+        imp.row_density
+            .set_selected(settings.enum_("apps-page-row-density") as u32);
+        imp.heat_map
+            .set_selected(settings.enum_("apps-page-heat-map") as u32);
+        imp.apps_page_hide_sections
+            .set_active(settings.boolean("apps-page-hide-sections"));
+        imp.services_page_hide_sections
+            .set_active(settings.boolean("services-page-hide-sections"));
+        imp.search_unit_files
+            .set_active(settings.boolean("services-page-search-unit-files"));
+        imp.end_task_grace_period
+            .set_value(settings.uint("apps-page-end-task-grace-period") as f64);
+        imp.end_task_auto_kill
+            .set_active(settings.boolean("apps-page-end-task-auto-kill"));
+        imp.metrics_exporter_enabled
+            .set_active(settings.boolean("metrics-exporter-enabled"));
+        imp.metrics_exporter_port
+            .set_value(settings.uint("metrics-exporter-port") as f64);
+        imp.metrics_exporter_listen_all
+            .set_active(settings.boolean("metrics-exporter-listen-all"));
+        imp.developer_mode
+            .set_active(settings.boolean("developer-mode"));
+        imp.debugger_command
+            .set_text(&settings.string("developer-tools-debugger-command"));
+        imp.profiler_command
+            .set_text(&settings.string("developer-tools-profiler-command"));
+        imp.trace_command
+            .set_text(&settings.string("developer-tools-trace-command"));
+        imp.populate_kill_list();
+        imp.populate_ignore_list();
+        imp.populate_highlight_rules();
+        imp.populate_service_restart_rules();
 
         imp.toggle_group_memory_unit
             .set_active(!settings.boolean("performance-page-memory2-use-bytes") as u32);
@@ -0,0 +1,226 @@
+/* remote_hosts_dialog.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib::{self, g_critical};
+
+use crate::i18n::i18n;
+use crate::magpie_client::{RemoteHost, RemoteKind};
+use crate::settings;
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/missioncenter/MissionCenter/ui/remote_hosts_dialog.ui")]
+    pub struct RemoteHostsDialog {
+        #[template_child]
+        host_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        name_entry: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        kind_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        address_entry: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        remote_socket_entry: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        add_button: TemplateChild<gtk::Button>,
+    }
+
+    impl RemoteHostsDialog {
+        fn selected_kind(&self) -> RemoteKind {
+            match self.kind_row.selected() {
+                0 => RemoteKind::Ssh,
+                _ => RemoteKind::Tcp,
+            }
+        }
+
+        fn update_kind(&self) {
+            let kind = self.selected_kind();
+            self.remote_socket_entry
+                .set_visible(kind == RemoteKind::Ssh);
+            self.address_entry.set_title(&match kind {
+                RemoteKind::Ssh => i18n("SSH Destination"),
+                RemoteKind::Tcp => i18n("Magpie Address"),
+            });
+        }
+
+        fn populate(&self) {
+            self.host_list.remove_all();
+
+            let settings = settings!();
+            for host in RemoteHost::all(&settings) {
+                let subtitle = match host.kind {
+                    RemoteKind::Ssh => format!("{}:{}", host.address, host.remote_socket),
+                    RemoteKind::Tcp => host.address.clone(),
+                };
+
+                let row = adw::ActionRow::builder()
+                    .title(glib::markup_escape_text(&host.name))
+                    .subtitle(glib::markup_escape_text(&subtitle))
+                    .build();
+
+                let remove_button = gtk::Button::builder()
+                    .icon_name("user-trash-symbolic")
+                    .tooltip_text(i18n("Remove"))
+                    .valign(gtk::Align::Center)
+                    .css_classes(["flat"])
+                    .build();
+                remove_button.connect_clicked({
+                    let this = self.obj().downgrade();
+                    move |_| {
+                        if let Some(this) = this.upgrade() {
+                            this.imp().remove_host(&host.name);
+                        }
+                    }
+                });
+                row.add_suffix(&remove_button);
+
+                self.host_list.append(&row);
+            }
+        }
+
+        fn add_host(&self) {
+            let name = self.name_entry.text().trim().to_owned();
+            let address = self.address_entry.text().trim().to_owned();
+            let remote_socket = self.remote_socket_entry.text().trim().to_owned();
+            let kind = self.selected_kind();
+
+            let settings = settings!();
+            let mut hosts = RemoteHost::all(&settings);
+
+            let name_valid = !name.is_empty() && hosts.iter().all(|host| host.name != name);
+            let address_valid = RemoteHost::is_valid_address(kind, &address);
+            let remote_socket_valid = kind != RemoteKind::Ssh || !remote_socket.is_empty();
+            for (entry, valid) in [
+                (&self.name_entry, name_valid),
+                (&self.address_entry, address_valid),
+                (&self.remote_socket_entry, remote_socket_valid),
+            ] {
+                if valid {
+                    entry.remove_css_class("error");
+                } else {
+                    entry.add_css_class("error");
+                }
+            }
+            if !(name_valid && address_valid && remote_socket_valid) {
+                return;
+            }
+
+            hosts.push(RemoteHost {
+                name,
+                kind,
+                address,
+                remote_socket: match kind {
+                    RemoteKind::Ssh => remote_socket,
+                    RemoteKind::Tcp => String::new(),
+                },
+            });
+            RemoteHost::store_all(&settings, &hosts);
+
+            self.name_entry.set_text("");
+            self.address_entry.set_text("");
+            self.populate();
+        }
+
+        fn remove_host(&self, name: &str) {
+            let settings = settings!();
+
+            // Removing the host being shown switches back to this machine
+            if settings.string("active-remote-host").as_str() == name {
+                if let Err(e) = settings.set_string("active-remote-host", "") {
+                    g_critical!(
+                        "MissionCenter::RemoteHosts",
+                        "Failed to set active-remote-host setting: {}",
+                        e
+                    );
+                }
+            }
+
+            let hosts = RemoteHost::all(&settings)
+                .into_iter()
+                .filter(|host| host.name != name)
+                .collect::<Vec<_>>();
+            RemoteHost::store_all(&settings, &hosts);
+
+            self.populate();
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for RemoteHostsDialog {
+        const NAME: &'static str = "RemoteHostsDialog";
+        type Type = super::RemoteHostsDialog;
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for RemoteHostsDialog {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.kind_row.connect_selected_notify({
+                let this = self.obj().downgrade();
+                move |_| {
+                    if let Some(this) = this.upgrade() {
+                        this.imp().update_kind();
+                    }
+                }
+            });
+
+            self.add_button.connect_clicked({
+                let this = self.obj().downgrade();
+                move |_| {
+                    if let Some(this) = this.upgrade() {
+                        this.imp().add_host();
+                    }
+                }
+            });
+
+            self.update_kind();
+            self.populate();
+        }
+    }
+
+    impl WidgetImpl for RemoteHostsDialog {}
+
+    impl AdwDialogImpl for RemoteHostsDialog {}
+}
+
+glib::wrapper! {
+    pub struct RemoteHostsDialog(ObjectSubclass<imp::RemoteHostsDialog>)
+        @extends adw::Dialog, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl RemoteHostsDialog {
+    pub fn new() -> Self {
+        glib::Object::builder().build()
+    }
+}
@@ -0,0 +1,376 @@
+/* service_restarts.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use gtk::glib::{self, g_critical, g_debug};
+use gtk::prelude::*;
+
+use crate::application::MissionCenterApplication;
+use crate::i18n::i18n_f;
+use crate::magpie_client::{Readings, Service};
+use crate::{settings, DataType};
+
+pub const RULES_KEY: &str = "service-restart-rules";
+pub const HISTORY_KEY: &str = "service-restart-history";
+
+// Only the most recent triggers of each rule are kept
+const HISTORY_LENGTH: usize = 10;
+// Gives a restarted service time to settle, before its memory counts again
+const MEMORY_COOLDOWN: Duration = Duration::from_secs(5 * 60);
+
+/// When a rule restarts its service
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RestartCondition {
+    /// Every day at this many minutes after midnight, local time
+    Daily(u32),
+    /// Whenever the processes of the service use more than this many bytes of memory
+    MemoryAbove(u64),
+}
+
+impl RestartCondition {
+    /// The conditions in the order the preferences list them
+    pub const KINDS: [&'static str; 2] = ["daily", "memory"];
+
+    fn kind(&self) -> &'static str {
+        match self {
+            RestartCondition::Daily(_) => "daily",
+            RestartCondition::MemoryAbove(_) => "memory",
+        }
+    }
+
+    fn value(&self) -> u64 {
+        match self {
+            RestartCondition::Daily(minutes) => *minutes as u64,
+            RestartCondition::MemoryAbove(bytes) => *bytes,
+        }
+    }
+
+    fn from_kind(kind: &str, value: u64) -> Option<Self> {
+        match kind {
+            "daily" if value < 24 * 60 => Some(RestartCondition::Daily(value as u32)),
+            "memory" if value > 0 => Some(RestartCondition::MemoryAbove(value)),
+            _ => None,
+        }
+    }
+
+    /// Reads what the user typed for the condition at `index` of `KINDS`, a time like `03:00`
+    /// for daily restarts and an amount like `2 GB` or `512M` for memory limits
+    pub fn parse(index: usize, text: &str) -> Option<Self> {
+        let text = text.trim();
+        match Self::KINDS.get(index)? {
+            &"daily" => {
+                let (hours, minutes) = text.split_once(':')?;
+                let hours = hours.trim().parse::<u32>().ok().filter(|h| *h < 24)?;
+                let minutes = minutes.trim().parse::<u32>().ok().filter(|m| *m < 60)?;
+                Some(RestartCondition::Daily(hours * 60 + minutes))
+            }
+            _ => parse_bytes(text).map(RestartCondition::MemoryAbove),
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            RestartCondition::Daily(minutes) => i18n_f(
+                "Every day at {}",
+                &[&format!("{:02}:{:02}", minutes / 60, minutes % 60)],
+            ),
+            RestartCondition::MemoryAbove(bytes) => i18n_f(
+                "When using more than {} of memory",
+                &[&crate::to_human_readable_nice(*bytes as f32, &DataType::MemoryBytes)],
+            ),
+        }
+    }
+}
+
+/// Restarts a service when its condition is met, see `ServiceRestartScheduler`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RestartRule {
+    pub service: String,
+    pub condition: RestartCondition,
+}
+
+impl RestartRule {
+    /// Identifies the rule in the history, so that it survives rules being added or removed
+    pub fn key(&self) -> String {
+        format!("{}:{}:{}", self.service, self.condition.kind(), self.condition.value())
+    }
+
+    fn matches(&self, service: &Service) -> bool {
+        service.name == self.service || service.name == format!("{}.service", self.service)
+    }
+}
+
+/// A time a rule restarted its service
+#[derive(Clone, Debug)]
+pub struct Trigger {
+    /// Seconds since the Unix epoch
+    pub time: i64,
+    /// The memory the service used, 0 for daily restarts
+    pub memory: u64,
+}
+
+impl Trigger {
+    pub fn description(&self) -> String {
+        let time = glib::DateTime::from_unix_local(self.time)
+            .and_then(|time| time.format("%x %X"))
+            .unwrap_or_default();
+        if self.memory == 0 {
+            return time.to_string();
+        }
+
+        i18n_f(
+            "{}, using {}",
+            &[
+                &time,
+                &crate::to_human_readable_nice(self.memory as f32, &DataType::MemoryBytes),
+            ],
+        )
+    }
+}
+
+pub fn load() -> Vec<RestartRule> {
+    settings!()
+        .value(RULES_KEY)
+        .get::<Vec<(String, String, u64)>>()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(service, kind, value)| {
+            Some(RestartRule {
+                service,
+                condition: RestartCondition::from_kind(&kind, value)?,
+            })
+        })
+        .collect()
+}
+
+pub fn save(rules: &[RestartRule]) {
+    let value = rules
+        .iter()
+        .map(|rule| {
+            (
+                rule.service.clone(),
+                rule.condition.kind().to_owned(),
+                rule.condition.value(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    if let Err(e) = settings!().set_value(RULES_KEY, &value.to_variant()) {
+        g_critical!(
+            "MissionCenter::ServiceRestarts",
+            "Failed to set {} setting: {}",
+            RULES_KEY,
+            e
+        );
+    }
+}
+
+/// The times the rule restarted its service, the most recent first
+pub fn history(rule: &RestartRule) -> Vec<Trigger> {
+    let key = rule.key();
+    let mut triggers = load_history()
+        .into_iter()
+        .filter(|(rule_key, _, _)| *rule_key == key)
+        .map(|(_, time, memory)| Trigger { time, memory })
+        .collect::<Vec<_>>();
+    triggers.sort_by(|a, b| b.time.cmp(&a.time));
+    triggers
+}
+
+fn load_history() -> Vec<(String, i64, u64)> {
+    settings!()
+        .value(HISTORY_KEY)
+        .get::<Vec<(String, i64, u64)>>()
+        .unwrap_or_default()
+}
+
+fn record(rule: &RestartRule, memory: u64) {
+    let key = rule.key();
+    let time = glib::DateTime::now_utc()
+        .map(|now| now.to_unix())
+        .unwrap_or_default();
+
+    // The history of rules that were removed goes away with the next trigger of any rule
+    let rule_keys = load().iter().map(RestartRule::key).collect::<Vec<_>>();
+    let mut history = load_history()
+        .into_iter()
+        .filter(|(rule_key, _, _)| rule_keys.contains(rule_key))
+        .collect::<Vec<_>>();
+    history.push((key.clone(), time, memory));
+
+    let count = history.iter().filter(|(rule_key, _, _)| *rule_key == key).count();
+    let mut excess = count.saturating_sub(HISTORY_LENGTH);
+    history.retain(|(rule_key, _, _)| {
+        if excess > 0 && *rule_key == key {
+            excess -= 1;
+            return false;
+        }
+        true
+    });
+
+    if let Err(e) = settings!().set_value(HISTORY_KEY, &history.to_variant()) {
+        g_critical!(
+            "MissionCenter::ServiceRestarts",
+            "Failed to set {} setting: {}",
+            HISTORY_KEY,
+            e
+        );
+    }
+}
+
+// Accepts `2 GB`, `2G`, `1.5 GiB`, `512M` and plain bytes. Units are powers of 1024, like
+// the memory the processes are shown with by default.
+fn parse_bytes(text: &str) -> Option<u64> {
+    let split = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number = number.parse::<f64>().ok().filter(|n| *n > 0.)?;
+
+    let unit = unit.trim().to_lowercase();
+    let multiplier = match unit.trim_end_matches("ib").trim_end_matches('b') {
+        "" => 1u64,
+        "k" => 1 << 10,
+        "m" => 1 << 20,
+        "g" => 1 << 30,
+        "t" => 1 << 40,
+        _ => return None,
+    };
+
+    Some((number * multiplier as f64) as u64)
+}
+
+/// Evaluates the restart rules with every update of the readings
+#[derive(Default)]
+pub struct ServiceRestartScheduler {
+    // Minutes after midnight of the previous check, daily rules fire when their time is
+    // passed in between two checks. Rules whose time passed while the app was not running
+    // wait for the next day.
+    previous_check: Option<(i32, u32)>,
+    memory_restarts: HashMap<String, Instant>,
+}
+
+impl ServiceRestartScheduler {
+    pub fn check(&mut self, app: &MissionCenterApplication, readings: &Readings) {
+        // Rules are meant for this machine. The readings are always the live ones, whatever the
+        // windows show.
+        if crate::magpie_client::is_remote() {
+            self.previous_check = None;
+            return;
+        }
+
+        let Ok(now) = glib::DateTime::now_local() else {
+            return;
+        };
+        let today = (now.day_of_year(), (now.hour() * 60 + now.minute()) as u32);
+        let previous_check = self.previous_check.replace(today);
+
+        let rules = load();
+        if rules.is_empty() {
+            return;
+        }
+
+        let services = readings
+            .user_services
+            .values()
+            .chain(readings.system_services.values())
+            .collect::<Vec<_>>();
+        for rule in &rules {
+            let Some(service) = services.iter().find(|service| rule.matches(service)) else {
+                continue;
+            };
+
+            let memory = match rule.condition {
+                RestartCondition::Daily(minutes) => {
+                    let Some((day, previous_minutes)) = previous_check else {
+                        continue;
+                    };
+                    let passed = if day == today.0 {
+                        previous_minutes < minutes && minutes <= today.1
+                    } else {
+                        // Midnight was passed in between
+                        previous_minutes < minutes || minutes <= today.1
+                    };
+                    if !passed {
+                        continue;
+                    }
+
+                    0
+                }
+                RestartCondition::MemoryAbove(limit) => {
+                    let memory = service_memory(service, readings);
+                    let cooling_down = self
+                        .memory_restarts
+                        .get(&rule.service)
+                        .is_some_and(|restarted| restarted.elapsed() < MEMORY_COOLDOWN);
+                    if !service.running || memory <= limit || cooling_down {
+                        continue;
+                    }
+
+                    self.memory_restarts.insert(rule.service.clone(), Instant::now());
+                    memory
+                }
+            };
+
+            match app.sys_info() {
+                Ok(magpie) => magpie.restart_service(service.id),
+                Err(e) => {
+                    g_critical!(
+                        "MissionCenter::ServiceRestarts",
+                        "Failed to get magpie client: {}",
+                        e
+                    );
+                    return;
+                }
+            }
+            g_debug!(
+                "MissionCenter::ServiceRestarts",
+                "Restarted {}: {}",
+                service.name,
+                rule.condition.description()
+            );
+            record(rule, memory);
+        }
+    }
+}
+
+// The memory of every process in the cgroup of the service, or of the main process and its
+// descendants if the cgroup is not known, like the Services page shows it
+fn service_memory(service: &Service, readings: &Readings) -> u64 {
+    let changes = &readings.process_changes;
+    let processes = &readings.running_processes;
+
+    let unit_pids = changes.service_processes(&service.name);
+    if !unit_pids.is_empty() {
+        return unit_pids
+            .iter()
+            .filter_map(|pid| processes.get(pid))
+            .map(|process| process.usage_stats.memory_usage)
+            .sum();
+    }
+
+    service
+        .pid
+        .and_then(|pid| processes.get(&pid))
+        .map(|process| changes.merged_usage_stats(process).memory_usage)
+        .unwrap_or_default()
+}
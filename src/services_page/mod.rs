@@ -26,14 +26,17 @@ use adw::prelude::*;
 use glib::{g_critical, ParamSpec, Properties, Value, WeakRef};
 use gtk::{gio, glib, subclass::prelude::*};
 
-use crate::i18n::{i18n, ni18n_f};
+use crate::i18n::{i18n, i18n_f, ni18n_f};
+use crate::table_view::actions::{
+    create_action_group, execute_action, ActionTarget, PROCESS_ACTIONS, SERVICE_ACTIONS,
+};
+use crate::table_view::columns::CpuUsageMode;
 use crate::table_view::{
-    update_services, ContentType, ProcessActionBar, RowModel, RowModelBuilder, SectionType,
-    ServiceActionBar, SettingsNamespace, TableView,
+    filter_presets, pinned, update_services, ChangedStats, ContentType, ProcessActionBar,
+    RowModel, RowModelBuilder, SectionType, ServiceActionBar, SettingsNamespace, TableView,
+    UnitOrigin,
 };
 
-pub mod actions;
-
 mod imp {
     use super::*;
 
@@ -57,7 +60,22 @@ mod imp {
         pub toggle_stopped: TemplateChild<gtk::ToggleButton>,
         #[template_child]
         pub toggle_disabled: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        pub toggle_activating: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        pub toggle_deactivating: TemplateChild<gtk::ToggleButton>,
 
+        #[template_child]
+        pub toggle_origin_vendor: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        pub toggle_origin_local: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        pub toggle_origin_user: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        pub toggle_origin_masked: TemplateChild<gtk::ToggleButton>,
+
+        #[template_child]
+        pub filter_presets_button: TemplateChild<gtk::MenuButton>,
         #[template_child]
         pub table_view: TemplateChild<TableView>,
 
@@ -76,9 +94,72 @@ mod imp {
         pub failed_services: Cell<u32>,
         pub stopped_services: Cell<u32>,
         pub disabled_services: Cell<u32>,
+        pub activating_services: Cell<u32>,
+        pub deactivating_services: Cell<u32>,
+
+        pub initialized: Cell<bool>,
+        pub initialization_requested: Cell<bool>,
     }
 
     impl ServicesPage {
+        fn origin_toggles(&self) -> [(&gtk::ToggleButton, UnitOrigin); 4] {
+            [
+                (&*self.toggle_origin_vendor, UnitOrigin::Vendor),
+                (&*self.toggle_origin_local, UnitOrigin::Local),
+                (&*self.toggle_origin_user, UnitOrigin::User),
+                (&*self.toggle_origin_masked, UnitOrigin::Masked),
+            ]
+        }
+
+        /// Counts the services in each state, only those of the origins filtered by if there
+        /// are any
+        pub fn count_services(&self) {
+            let origins = self
+                .origin_toggles()
+                .into_iter()
+                .filter(|(toggle, _)| toggle.is_active())
+                .map(|(_, origin)| origin)
+                .collect::<Vec<_>>();
+
+            let mut total_services = 0;
+            let mut disabled_services = 0;
+            let mut running_services = 0;
+            let mut stopped_services = 0;
+            let mut failed_services = 0;
+            let mut activating_services = 0;
+            let mut deactivating_services = 0;
+            for section in [&self.user_section, &self.system_section] {
+                for service in section.children().iter::<RowModel>().flatten() {
+                    total_services += 1;
+                    if !origins.is_empty() && !origins.contains(&service.unit_origin()) {
+                        continue;
+                    }
+
+                    if service.service_activating() {
+                        activating_services += 1;
+                    } else if service.service_deactivating() {
+                        deactivating_services += 1;
+                    } else if service.service_running() {
+                        running_services += 1;
+                    } else if service.service_failed() {
+                        failed_services += 1;
+                    } else if service.service_enabled() {
+                        stopped_services += 1;
+                    } else {
+                        disabled_services += 1;
+                    }
+                }
+            }
+
+            self.total_services.set(total_services);
+            self.running_services.set(running_services);
+            self.stopped_services.set(stopped_services);
+            self.failed_services.set(failed_services);
+            self.disabled_services.set(disabled_services);
+            self.activating_services.set(activating_services);
+            self.deactivating_services.set(deactivating_services);
+        }
+
         pub fn update_headers(&self) {
             let mut fmt_buffer = arrayvec::ArrayString::<12>::new();
 
@@ -87,6 +168,8 @@ mod imp {
             let stopped = self.stopped_services.get();
             let failed = self.failed_services.get();
             let disabled = self.disabled_services.get();
+            let activating = self.activating_services.get();
+            let deactivating = self.deactivating_services.get();
 
             fmt_buffer.clear();
             let _ = write!(fmt_buffer, "{}", total);
@@ -133,6 +216,42 @@ mod imp {
                 types.push_str(&i18n("Disabled"));
             }
 
+            if self.toggle_activating.is_active() {
+                any_active = true;
+                filtered += activating;
+                if !types.is_empty() {
+                    types.push_str(", ");
+                }
+                types.push_str(&i18n("Activating"));
+            }
+
+            if self.toggle_deactivating.is_active() {
+                any_active = true;
+                filtered += deactivating;
+                if !types.is_empty() {
+                    types.push_str(", ");
+                }
+                types.push_str(&i18n("Deactivating"));
+            }
+
+            let mut origins_active = false;
+            for (toggle, origin) in self.origin_toggles() {
+                if !toggle.is_active() {
+                    continue;
+                }
+
+                origins_active = true;
+                if !types.is_empty() {
+                    types.push_str(", ");
+                }
+                types.push_str(&origin.label());
+            }
+            // Without state filters, all of the services of the filtered origins are shown
+            if origins_active && !any_active {
+                filtered = running + failed + stopped + disabled + activating + deactivating;
+            }
+            let any_active = any_active || origins_active;
+
             if filtered == 0 {
                 if any_active {
                     self.h2
@@ -143,7 +262,7 @@ mod imp {
             } else {
                 fmt_buffer.clear();
                 let _ = write!(fmt_buffer, "{}", filtered);
-                // TRANSLATORS: {0} is a number, {1} is a comma-separated list of service states, i.e. "Running", "Failed", "Stopped", "Disabled"
+                // TRANSLATORS: {0} is a number, {1} is a comma-separated list of service states, i.e. "Running", "Failed", "Stopped", "Disabled", "Activating", "Deactivating"
                 self.h2.set_label(&ni18n_f(
                     "{} {} Service",
                     "{} {} Services",
@@ -166,7 +285,15 @@ mod imp {
                 toggle_failed: Default::default(),
                 toggle_stopped: Default::default(),
                 toggle_disabled: Default::default(),
+                toggle_activating: Default::default(),
+                toggle_deactivating: Default::default(),
+
+                toggle_origin_vendor: Default::default(),
+                toggle_origin_local: Default::default(),
+                toggle_origin_user: Default::default(),
+                toggle_origin_masked: Default::default(),
 
+                filter_presets_button: Default::default(),
                 table_view: Default::default(),
 
                 process_action_bar: Default::default(),
@@ -190,6 +317,11 @@ mod imp {
                 failed_services: Cell::new(0),
                 stopped_services: Cell::new(0),
                 disabled_services: Cell::new(0),
+                activating_services: Cell::new(0),
+                deactivating_services: Cell::new(0),
+
+                initialized: Cell::new(false),
+                initialization_requested: Cell::new(false),
             }
         }
     }
@@ -282,6 +414,34 @@ mod imp {
                 }
             });
 
+            self.toggle_activating.connect_toggled({
+                let this = self.obj().downgrade();
+                move |_| {
+                    update_headers(&this);
+                }
+            });
+
+            self.toggle_deactivating.connect_toggled({
+                let this = self.obj().downgrade();
+                move |_| {
+                    update_headers(&this);
+                }
+            });
+
+            for (toggle, _) in self.origin_toggles() {
+                toggle.connect_toggled({
+                    let this = self.obj().downgrade();
+                    move |_| {
+                        let Some(this) = this.upgrade() else {
+                            return;
+                        };
+                        let this = this.imp();
+                        this.count_services();
+                        this.update_headers();
+                    }
+                });
+            }
+
             let actions = gio::SimpleActionGroup::new();
 
             let action_collapse_all = gio::SimpleAction::new("collapse-all", None);
@@ -323,7 +483,10 @@ mod imp {
                             continue;
                         };
 
-                        if row_model.content_type() != ContentType::SectionHeader {
+                        // The pinned section is left as is, it only holds copies of entries
+                        if row_model.content_type() != ContentType::SectionHeader
+                            || row_model.section_type() == SectionType::PinnedSection
+                        {
                             continue;
                         }
 
@@ -351,32 +514,24 @@ mod imp {
                     imp.toggle_failed.set_active(false);
                     imp.toggle_stopped.set_active(false);
                     imp.toggle_disabled.set_active(false);
+                    imp.toggle_activating.set_active(false);
+                    imp.toggle_deactivating.set_active(false);
+                    for (toggle, _) in imp.origin_toggles() {
+                        toggle.set_active(false);
+                    }
                 }
             });
 
             self.obj()
                 .insert_action_group("services-page", Some(&actions));
 
-            let service_actions = gio::SimpleActionGroup::new();
-            service_actions.add_action(&actions::action_start(&self.table_view));
-            service_actions.add_action(&actions::action_stop(&self.table_view));
-            service_actions.add_action(&actions::action_restart(&self.table_view));
-            service_actions.add_action(&actions::action_details(&self.table_view));
+            let service_actions = create_action_group(&self.table_view, SERVICE_ACTIONS);
             self.obj()
-                .insert_action_group("service", Some(&service_actions));
-
-            let process_actions = gio::SimpleActionGroup::new();
-            process_actions.add_action(&actions::apps::action_stop(&self.table_view));
-            process_actions.add_action(&actions::apps::action_force_stop(&self.table_view));
-            process_actions.add_action(&actions::apps::action_suspend(&self.table_view));
-            process_actions.add_action(&actions::apps::action_continue(&self.table_view));
-            process_actions.add_action(&actions::apps::action_hangup(&self.table_view));
-            process_actions.add_action(&actions::apps::action_interrupt(&self.table_view));
-            process_actions.add_action(&actions::apps::action_user_one(&self.table_view));
-            process_actions.add_action(&actions::apps::action_user_two(&self.table_view));
-            process_actions.add_action(&actions::apps::action_details(&self.table_view));
+                .insert_action_group(ActionTarget::Service.group_name(), Some(&service_actions));
+
+            let process_actions = create_action_group(&self.table_view, PROCESS_ACTIONS);
             self.obj()
-                .insert_action_group("process", Some(&process_actions));
+                .insert_action_group(ActionTarget::Process.group_name(), Some(&process_actions));
         }
     }
 
@@ -396,7 +551,7 @@ glib::wrapper! {
 }
 
 impl ServicesPage {
-    pub fn set_initial_readings(&self, readings: &mut crate::magpie_client::Readings) -> bool {
+    pub fn set_initial_readings(&self, readings: &crate::magpie_client::Readings) -> bool {
         let imp = self.imp();
 
         let toggle_group = [
@@ -404,6 +559,12 @@ impl ServicesPage {
             imp.toggle_failed.downgrade(),
             imp.toggle_stopped.downgrade(),
             imp.toggle_disabled.downgrade(),
+            imp.toggle_activating.downgrade(),
+            imp.toggle_deactivating.downgrade(),
+            imp.toggle_origin_vendor.downgrade(),
+            imp.toggle_origin_local.downgrade(),
+            imp.toggle_origin_user.downgrade(),
+            imp.toggle_origin_masked.downgrade(),
         ];
 
         // Set up the models here since we need access to the main application window
@@ -417,72 +578,120 @@ impl ServicesPage {
             Some(toggle_group),
         );
 
+        // Presets are stored per page, so this can only be done once the namespace is known
+        let filter_preset_actions = filter_presets::create_action_group(&imp.table_view);
+        self.insert_action_group(
+            filter_presets::ACTION_GROUP_NAME,
+            Some(&filter_preset_actions),
+        );
+        imp.filter_presets_button
+            .set_menu_model(Some(&filter_presets::menu_model(&imp.table_view)));
+
         self.update_common(readings);
+        imp.table_view.update_totals();
+        imp.table_view.restore_selection();
+
+        imp.initialized.set(true);
 
         true
     }
 
-    fn update_common(&self, readings: &mut crate::magpie_client::Readings) {
+    /// Setting up the page is deferred until it is first shown, since building the models is
+    /// expensive on systems with a lot of units. The page is populated on the next update.
+    pub fn request_initialization(&self) {
+        self.imp().initialization_requested.set(true);
+    }
+
+    fn update_common(&self, readings: &crate::magpie_client::Readings) -> ChangedStats {
         let imp = self.imp();
+        let mut changed_stats = ChangedStats::default();
 
         update_services(
             &readings.running_processes,
+            &readings.process_changes,
             &readings.user_services,
+            &readings.service_commands,
+            &readings.service_transitions,
+            &readings.service_stats,
+            &readings.masked_services,
             &imp.user_section.children(),
             &HashMap::new(),
             "application-x-executable-symbolic",
             imp.table_view.imp().use_merged_stats.get(),
             SectionType::FirstSection,
+            &mut changed_stats,
         );
 
         update_services(
             &readings.running_processes,
+            &readings.process_changes,
             &readings.system_services,
+            &readings.service_commands,
+            &readings.service_transitions,
+            &readings.service_stats,
+            &readings.masked_services,
             &imp.system_section.children(),
             &HashMap::new(),
             "application-x-executable-symbolic",
             imp.table_view.imp().use_merged_stats.get(),
             SectionType::SecondSection,
+            &mut changed_stats,
         );
 
-        let mut services = readings.user_services.values().collect::<Vec<_>>();
-        services.extend(readings.system_services.values());
-
-        let total_services = services.len();
-        let mut disabled_services = 0;
-        let mut running_services = 0;
-        let mut stopped_services = 0;
-        let mut failed_services = 0;
-        for service in services {
-            if service.running {
-                running_services += 1;
-            } else if service.failed {
-                failed_services += 1;
-            } else if service.enabled {
-                stopped_services += 1;
-            } else {
-                disabled_services += 1;
+        let pinned = pinned::pinned(SettingsNamespace::ServicesPage);
+        let pinned_services = readings
+            .user_services
+            .iter()
+            .chain(readings.system_services.iter())
+            .filter(|(_, service)| pinned.contains(&service.name))
+            .map(|(id, service)| (*id, service.clone()))
+            .collect::<HashMap<_, _>>();
+        update_services(
+            &readings.running_processes,
+            &readings.process_changes,
+            &pinned_services,
+            &readings.service_commands,
+            &readings.service_transitions,
+            &readings.service_stats,
+            &readings.masked_services,
+            &imp.table_view.imp().pinned_section.children(),
+            &HashMap::new(),
+            "application-x-executable-symbolic",
+            imp.table_view.imp().use_merged_stats.get(),
+            SectionType::PinnedSection,
+            &mut changed_stats,
+        );
+
+        imp.count_services();
+        imp.update_headers();
+
+        let cpu_usage_mode = imp.table_view.cpu_usage_mode();
+        for section in [&imp.user_section, &imp.system_section] {
+            let summary = section_summary(section, cpu_usage_mode);
+            if section.summary() != summary {
+                section.set_summary(&summary);
             }
         }
 
-        imp.total_services.set(total_services as u32);
-        imp.running_services.set(running_services);
-        imp.stopped_services.set(stopped_services);
-        imp.failed_services.set(failed_services);
-        imp.disabled_services.set(disabled_services);
-
-        imp.update_headers();
+        changed_stats
     }
 
-    pub fn update_readings(&self, readings: &mut crate::magpie_client::Readings) -> bool {
+    pub fn update_readings(&self, readings: &crate::magpie_client::Readings) -> bool {
         let imp = self.imp();
 
-        self.update_common(readings);
-
-        if let Some(row_sorter) = imp.table_view.imp().row_sorter.get() {
-            row_sorter.changed(gtk::SorterChange::Different)
+        if !imp.initialized.get() {
+            if imp.initialization_requested.get() {
+                return self.set_initial_readings(readings);
+            }
+            return true;
         }
 
+        imp.table_view.begin_update();
+        let changed_stats = self.update_common(readings);
+        imp.table_view.sort_if_changed(changed_stats);
+        imp.table_view.end_update();
+        imp.table_view.update_totals();
+
         if readings.network_stats_error.is_some() {
             imp.table_view
                 .get()
@@ -499,6 +708,11 @@ impl ServicesPage {
         self.imp().collapse();
     }
 
+    /// Shows the table as a list of names, with their usage below them
+    pub fn set_narrow(&self, narrow: bool) {
+        self.imp().table_view.set_narrow(narrow);
+    }
+
     #[inline]
     pub fn expand(&self) {
         self.imp().expand();
@@ -507,4 +721,96 @@ impl ServicesPage {
     pub fn activate_table_view_action(&self, name: &str) -> Result<(), glib::error::BoolError> {
         WidgetExt::activate_action(&*self.imp().table_view, name, None)
     }
+
+    /// The row of the service `service_id`, `None` until the page shows it
+    pub fn service_row(&self, service_id: u64) -> Option<RowModel> {
+        self.imp().table_view.find_row(&service_id.to_string())
+    }
+
+    /// Runs the service action called `name` on `row_model`, as if it was picked from its menu
+    pub fn execute_action(&self, name: &str, row_model: &RowModel) -> bool {
+        execute_action(SERVICE_ACTIONS, name, &self.imp().table_view, row_model)
+    }
+
+    #[inline]
+    pub fn scroll_to_and_select(&self, id: &str) -> bool {
+        self.imp().table_view.scroll_to_and_select(id)
+    }
+
+    #[inline]
+    pub fn select_search_match(&self, forward: bool) -> bool {
+        self.imp().table_view.select_search_match(forward)
+    }
+
+    #[inline]
+    pub fn search_match_count(&self) -> u32 {
+        self.imp().table_view.search_match_count()
+    }
+
+    #[inline]
+    pub fn zoom(&self, steps: i32) {
+        self.imp().table_view.zoom(steps);
+    }
+
+    #[inline]
+    pub fn reset_zoom(&self) {
+        self.imp().table_view.reset_zoom();
+    }
+
+    #[inline]
+    pub fn copy_visible_rows(&self) {
+        self.imp().table_view.copy_visible_rows();
+    }
+
+    pub fn connect_search_match_count_notify(&self, f: impl Fn(u32) + 'static) {
+        self.imp()
+            .table_view
+            .connect_search_match_count_notify(
+                move |table_view| f(table_view.search_match_count()),
+            );
+    }
+}
+
+/// The number of units in a section, how many of them failed, and what they use together
+fn section_summary(section: &RowModel, cpu_usage_mode: CpuUsageMode) -> String {
+    let children = section.children();
+
+    let mut failed = 0;
+    let mut cpu_usage = 0.;
+    let mut memory_usage = 0;
+    for service in children.iter::<RowModel>().flatten() {
+        if service.service_failed() {
+            failed += 1;
+        }
+        cpu_usage += service.cpu_usage();
+        memory_usage += service.memory_usage();
+    }
+
+    let unit_count = children.n_items();
+    let mut result = ni18n_f(
+        "{} unit",
+        "{} units",
+        unit_count,
+        &[&unit_count.to_string()],
+    );
+    if failed > 0 {
+        result.push_str(", ");
+        result.push_str(&ni18n_f(
+            "{} failed",
+            "{} failed",
+            failed,
+            &[&failed.to_string()],
+        ));
+    }
+
+    let cpu_usage = format!(
+        "{}%",
+        cpu_usage_mode.display_value(cpu_usage).round() as u32
+    );
+    let memory_usage =
+        crate::to_human_readable_nice(memory_usage as f32, &crate::DataType::MemoryBytes);
+    result.push_str(" \u{2014} ");
+    result.push_str(&i18n_f("{} CPU, {} memory", &[&cpu_usage, &memory_usage]));
+
+    result
 }
@@ -0,0 +1,152 @@
+/* session_recording.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use gtk::glib::{self, g_warning};
+use gtk::{gio, prelude::*};
+
+use crate::magpie_client::{Readings, Snapshot};
+
+// Enough to look back at what happened a while ago, without the recording growing unbounded
+const MAX_AGE: Duration = Duration::from_secs(15 * 60);
+const MAX_SIZE: usize = 64 * 1024 * 1024;
+
+/// Readings encoded like a snapshot and compressed, on the thread that gathers them rather than
+/// on the UI thread. Cloning a frame shares its data, so each recording window can keep it.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    taken_at: glib::DateTime,
+    data: glib::Bytes,
+}
+
+impl Frame {
+    pub fn capture(readings: &Readings, host: &str) -> Option<Self> {
+        let taken_at = glib::DateTime::now_local().ok()?;
+        let timestamp = taken_at.format_iso8601().unwrap_or_default();
+
+        match compress(&Snapshot::encode(readings, host, &timestamp)) {
+            Ok(data) => Some(Self { taken_at, data }),
+            Err(e) => {
+                g_warning!(
+                    "MissionCenter::SessionRecording",
+                    "Failed to record readings: {}",
+                    e
+                );
+                None
+            }
+        }
+    }
+}
+
+/// The readings of the last few minutes, each of them stored like a snapshot, so that the tables
+/// can be stepped back through them. Frames are numbered in the order they were recorded, the
+/// numbers stay the same when older frames are dropped.
+#[derive(Default)]
+pub struct SessionRecording {
+    frames: VecDeque<Frame>,
+    first_frame: u64,
+    size: usize,
+}
+
+impl SessionRecording {
+    pub fn record(&mut self, frame: Frame) {
+        let now = frame.taken_at.clone();
+        self.size += frame.data.len();
+        self.frames.push_back(frame);
+
+        while let Some(oldest) = self.frames.front() {
+            let too_old = now.to_unix() - oldest.taken_at.to_unix() > MAX_AGE.as_secs() as i64;
+            if !too_old && self.size <= MAX_SIZE {
+                break;
+            }
+
+            self.size -= oldest.data.len();
+            self.frames.pop_front();
+            self.first_frame += 1;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The numbers of the oldest and the most recent frames
+    pub fn range(&self) -> Option<(u64, u64)> {
+        let count = self.frames.len() as u64;
+        (count > 0).then(|| (self.first_frame, self.first_frame + count - 1))
+    }
+
+    pub fn taken_at(&self, frame: u64) -> Option<&glib::DateTime> {
+        self.frame(frame).map(|frame| &frame.taken_at)
+    }
+
+    pub fn load(&self, frame: u64) -> Option<Snapshot> {
+        let frame = self.frame(frame)?;
+        let snapshot = decompress(&frame.data)
+            .map_err(|e| e.to_string())
+            .and_then(|data| Snapshot::decode(&data).map_err(|e| e.to_string()));
+        match snapshot {
+            Ok(snapshot) => Some(snapshot),
+            Err(e) => {
+                g_warning!(
+                    "MissionCenter::SessionRecording",
+                    "Failed to load recorded readings: {}",
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    fn frame(&self, frame: u64) -> Option<&Frame> {
+        let index = frame.checked_sub(self.first_frame)?;
+        self.frames.get(index as usize)
+    }
+}
+
+fn compress(data: &[u8]) -> Result<glib::Bytes, glib::Error> {
+    let output = gio::MemoryOutputStream::new_resizable();
+    let compressor = gio::ZlibCompressor::new(gio::ZlibCompressorFormat::Raw, -1);
+    let stream = gio::ConverterOutputStream::new(&output, &compressor);
+    stream.write_all(data, gio::Cancellable::NONE)?;
+    stream.close(gio::Cancellable::NONE)?;
+
+    Ok(output.steal_as_bytes())
+}
+
+fn decompress(data: &glib::Bytes) -> Result<Vec<u8>, glib::Error> {
+    let input = gio::MemoryInputStream::from_bytes(data);
+    let decompressor = gio::ZlibDecompressor::new(gio::ZlibCompressorFormat::Raw);
+    let stream = gio::ConverterInputStream::new(&input, &decompressor);
+
+    let mut result = vec![];
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = stream.read(&mut buffer, gio::Cancellable::NONE)?;
+        if read == 0 {
+            break;
+        }
+        result.extend_from_slice(&buffer[..read]);
+    }
+
+    Ok(result)
+}
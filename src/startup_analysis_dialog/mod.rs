@@ -0,0 +1,149 @@
+/* startup_analysis_dialog/mod.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use crate::i18n::{i18n, i18n_f};
+use crate::magpie_client::{boot_analysis, BootAnalysis};
+
+use waterfall::StartupWaterfall;
+
+mod waterfall;
+
+// Like `systemd-analyze blame`, which lists every unit, but only the ones worth looking at
+const MAX_UNITS: usize = 50;
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/missioncenter/MissionCenter/ui/startup_analysis_dialog.ui")]
+    pub struct StartupAnalysisDialog {
+        #[template_child]
+        view: TemplateChild<adw::ToggleGroup>,
+        #[template_child]
+        content_stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        loading_spinner: TemplateChild<adw::Spinner>,
+        #[template_child]
+        unavailable_page: TemplateChild<adw::StatusPage>,
+        #[template_child]
+        analysis_stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        summary_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        unit_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        waterfall: TemplateChild<StartupWaterfall>,
+    }
+
+    impl StartupAnalysisDialog {
+        fn populate(&self, analysis: Option<BootAnalysis>) {
+            let Some(analysis) = analysis.filter(|analysis| !analysis.units.is_empty()) else {
+                self.view.set_sensitive(false);
+                self.content_stack
+                    .set_visible_child(&*self.unavailable_page);
+                return;
+            };
+
+            self.summary_group.set_description(Some(&i18n_f(
+                "Userspace finished starting up after {} s. Units the startup had to wait for are highlighted.",
+                &[&format!("{:.1}", analysis.userspace_time.as_secs_f32())],
+            )));
+
+            let mut slowest = analysis.units.iter().collect::<Vec<_>>();
+            slowest.sort_by(|a, b| b.duration().cmp(&a.duration()));
+            for unit in slowest.into_iter().take(MAX_UNITS) {
+                let row = adw::ActionRow::builder()
+                    .title(glib::markup_escape_text(&unit.name))
+                    .build();
+                if unit.critical {
+                    row.set_subtitle(&i18n("On the critical chain"));
+                }
+
+                let duration = gtk::Label::new(Some(&i18n_f(
+                    "{} s",
+                    &[&format!("{:.2}", unit.duration().as_secs_f32())],
+                )));
+                duration.add_css_class("numeric");
+                duration.add_css_class("dim-label");
+                row.add_suffix(&duration);
+
+                self.unit_list.append(&row);
+            }
+
+            self.waterfall
+                .set_units(analysis.units, analysis.userspace_time);
+            self.content_stack.set_visible_child(&*self.analysis_stack);
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for StartupAnalysisDialog {
+        const NAME: &'static str = "StartupAnalysisDialog";
+        type Type = super::StartupAnalysisDialog;
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            StartupWaterfall::ensure_type();
+
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for StartupAnalysisDialog {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.content_stack.set_visible_child(&*self.loading_spinner);
+
+            boot_analysis({
+                let this = self.obj().downgrade();
+                move |analysis| {
+                    if let Some(this) = this.upgrade() {
+                        this.imp().populate(analysis);
+                    }
+                }
+            });
+        }
+    }
+
+    impl WidgetImpl for StartupAnalysisDialog {}
+
+    impl AdwDialogImpl for StartupAnalysisDialog {}
+}
+
+glib::wrapper! {
+    pub struct StartupAnalysisDialog(ObjectSubclass<imp::StartupAnalysisDialog>)
+        @extends adw::Dialog, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl StartupAnalysisDialog {
+    pub fn new() -> Self {
+        glib::Object::builder().build()
+    }
+}
@@ -0,0 +1,209 @@
+/* startup_analysis_dialog/waterfall.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::cell::{Cell, RefCell};
+use std::time::Duration;
+
+use gtk::{gdk, glib, graphene, gsk, pango, prelude::*, subclass::prelude::*, Snapshot};
+
+use crate::i18n::i18n_f;
+use crate::magpie_client::UnitActivation;
+
+const ROW_HEIGHT: f32 = 22.;
+const BAR_HEIGHT: f32 = 14.;
+const PIXELS_PER_SECOND: f32 = 60.;
+const LABEL_SPACING: f32 = 12.;
+const RADIUS: f32 = 3.;
+
+mod imp {
+    use super::*;
+
+    #[derive(Default)]
+    pub struct StartupWaterfall {
+        pub units: RefCell<Vec<UnitActivation>>,
+        pub labels: RefCell<Vec<pango::Layout>>,
+        pub label_width: Cell<f32>,
+        pub end: Cell<Duration>,
+    }
+
+    impl StartupWaterfall {
+        pub fn update_labels(&self) {
+            let obj = self.obj();
+
+            let labels = self
+                .units
+                .borrow()
+                .iter()
+                .map(|unit| obj.create_pango_layout(Some(&unit.name)))
+                .collect::<Vec<_>>();
+            let label_width = labels
+                .iter()
+                .map(|label| label.pixel_size().0)
+                .max()
+                .unwrap_or_default();
+
+            self.label_width.set(label_width as f32 + LABEL_SPACING);
+            self.labels.replace(labels);
+
+            obj.queue_resize();
+        }
+
+        fn x_of(&self, time: Duration) -> f32 {
+            self.label_width.get() + time.as_secs_f32() * PIXELS_PER_SECOND
+        }
+
+        fn unit_at(&self, y: f64) -> Option<usize> {
+            let row = (y as f32 / ROW_HEIGHT).floor();
+            (row >= 0. && (row as usize) < self.units.borrow().len()).then_some(row as usize)
+        }
+
+        // A faint line every second, and a stronger one every ten
+        fn draw_grid(&self, snapshot: &Snapshot, color: &gdk::RGBA, height: f32) {
+            let seconds = self.end.get().as_secs() + 1;
+            for second in 0..=seconds {
+                let x = self.x_of(Duration::from_secs(second));
+                let alpha = if second % 10 == 0 { 0.3 } else { 0.1 };
+                snapshot.append_color(
+                    &color.with_alpha(alpha),
+                    &graphene::Rect::new(x, 0., 1., height),
+                );
+            }
+        }
+
+        fn draw_units(&self, snapshot: &Snapshot, foreground: &gdk::RGBA, accent: &gdk::RGBA) {
+            let units = self.units.borrow();
+            let labels = self.labels.borrow();
+
+            for (row, (unit, label)) in units.iter().zip(labels.iter()).enumerate() {
+                let y = row as f32 * ROW_HEIGHT;
+
+                let (_, label_height) = label.pixel_size();
+                snapshot.save();
+                snapshot.translate(&graphene::Point::new(
+                    0.,
+                    y + (ROW_HEIGHT - label_height as f32) / 2.,
+                ));
+                snapshot.append_layout(label, foreground);
+                snapshot.restore();
+
+                let start = self.x_of(unit.started);
+                // Units that activate at once still get a sliver
+                let width = (self.x_of(unit.activated) - start).max(2.);
+                let bounds = graphene::Rect::new(
+                    start,
+                    y + (ROW_HEIGHT - BAR_HEIGHT) / 2.,
+                    width,
+                    BAR_HEIGHT,
+                );
+                let color = if unit.critical {
+                    *accent
+                } else {
+                    foreground.with_alpha(0.4)
+                };
+
+                let path_builder = gsk::PathBuilder::new();
+                path_builder.add_rounded_rect(&gsk::RoundedRect::from_rect(bounds, RADIUS));
+                snapshot.append_fill(&path_builder.to_path(), gsk::FillRule::Winding, &color);
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for StartupWaterfall {
+        const NAME: &'static str = "StartupWaterfall";
+        type Type = super::StartupWaterfall;
+        type ParentType = gtk::Widget;
+    }
+
+    impl ObjectImpl for StartupWaterfall {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.obj().set_has_tooltip(true);
+        }
+    }
+
+    impl WidgetImpl for StartupWaterfall {
+        fn measure(&self, orientation: gtk::Orientation, _for_size: i32) -> (i32, i32, i32, i32) {
+            let size = match orientation {
+                gtk::Orientation::Horizontal => {
+                    // Room for the grid line of the last second
+                    self.x_of(self.end.get() + Duration::from_secs(1)) + 1.
+                }
+                _ => self.units.borrow().len() as f32 * ROW_HEIGHT,
+            }
+            .ceil() as i32;
+
+            (size, size, -1, -1)
+        }
+
+        fn snapshot(&self, snapshot: &Snapshot) {
+            let obj = self.obj();
+
+            let foreground = obj.color();
+            let accent = adw::StyleManager::default().accent_color_rgba();
+
+            self.draw_grid(snapshot, &foreground, obj.height() as f32);
+            self.draw_units(snapshot, &foreground, &accent);
+        }
+
+        fn query_tooltip(
+            &self,
+            _x: i32,
+            y: i32,
+            _keyboard_tooltip: bool,
+            tooltip: &gtk::Tooltip,
+        ) -> bool {
+            let Some(row) = self.unit_at(y as f64) else {
+                return false;
+            };
+            let units = self.units.borrow();
+            let unit = &units[row];
+
+            tooltip.set_text(Some(&i18n_f(
+                "{}\nStarted after {} s, took {} s",
+                &[
+                    &unit.name,
+                    &format!("{:.2}", unit.started.as_secs_f32()),
+                    &format!("{:.2}", unit.duration().as_secs_f32()),
+                ],
+            )));
+            true
+        }
+    }
+}
+
+glib::wrapper! {
+    pub struct StartupWaterfall(ObjectSubclass<imp::StartupWaterfall>)
+        @extends gtk::Widget,
+        @implements gtk::ConstraintTarget, gtk::Accessible, gtk::Buildable;
+}
+
+impl StartupWaterfall {
+    /// Shows a bar for each unit, from when it started activating until it was active, on a
+    /// timeline that ends at `end`. Units on the critical chain stand out.
+    pub fn set_units(&self, units: Vec<UnitActivation>, end: Duration) {
+        let imp = self.imp();
+
+        imp.units.replace(units);
+        imp.end.set(end);
+        imp.update_labels();
+    }
+}
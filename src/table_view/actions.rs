@@ -0,0 +1,1033 @@
+/* table_view/actions.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::time::Duration;
+
+use adw::prelude::*;
+use gtk::glib::g_critical;
+use gtk::{gdk, gio, glib};
+
+use crate::app;
+use crate::i18n::{i18n, i18n_f, ni18n_f};
+use crate::magpie_client::{service_start_outcome, IoPriority, MagpieClient, StartOutcome};
+use crate::table_view::{
+    app_windows, debug_tools, file_location, ignored, pinned, proc_fs, terminal, ContentType,
+    ProcessDetailsDialog, RowModel, ServiceDetailsDialog, TableView,
+};
+use crate::table_view::debug_tools::DebugTool;
+use crate::{settings, DataType};
+
+/// The kind of row an action operates on
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ActionTarget {
+    /// Processes and apps, apps act on all of their processes
+    Process,
+    Service,
+}
+
+impl ActionTarget {
+    /// The name of the action group the actions for this target are inserted as
+    pub fn group_name(&self) -> &'static str {
+        match self {
+            ActionTarget::Process => "process",
+            ActionTarget::Service => "service",
+        }
+    }
+
+    pub fn accepts(&self, content_type: ContentType) -> bool {
+        match self {
+            ActionTarget::Process => {
+                content_type == ContentType::Process || content_type == ContentType::App
+            }
+            ActionTarget::Service => content_type == ContentType::Service,
+        }
+    }
+}
+
+/// Describes an action that can be run on a row. Menus, action bars and keyboard shortcuts
+/// all refer to the action as `<group>.<name>`, so an action only needs to be defined here.
+pub struct ActionDescriptor {
+    pub name: &'static str,
+    pub target: ActionTarget,
+    /// Whether the action only looks at the row, rather than acting on its processes or service.
+    /// Only these apply to the rows of a snapshot and to template rows.
+    pub read_only: bool,
+    /// Whether the action applies to the row, in addition to the row being of the right kind
+    pub is_enabled: fn(&RowModel) -> bool,
+    pub execute: fn(&TableView, &RowModel),
+}
+
+impl ActionDescriptor {
+    pub fn can_execute(&self, table_view: &TableView, row_model: &RowModel) -> bool {
+        // The processes and services of a snapshot can only be looked at
+        if table_view.is_showing_snapshot() && !self.read_only {
+            return false;
+        }
+
+        // Template rows only group their instances, the instances are what can be acted on
+        if row_model.service_template() && !self.read_only {
+            return false;
+        }
+
+        self.target.accepts(row_model.content_type()) && (self.is_enabled)(row_model)
+    }
+
+    /// Runs the action for `row_model`, returns `false` if the action does not apply to it
+    pub fn execute(&self, table_view: &TableView, row_model: &RowModel) -> bool {
+        if !self.can_execute(table_view, row_model) {
+            return false;
+        }
+
+        (self.execute)(table_view, row_model);
+        true
+    }
+}
+
+pub const PROCESS_ACTIONS: &[ActionDescriptor] = &[
+    ActionDescriptor {
+        name: "end-task",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: always,
+        execute: |table_view, row_model| {
+            signal_processes(row_model, MagpieClient::terminate_processes);
+
+            // The page that shows the process decides what happens if it does not exit
+            let pids = process_pids(row_model);
+            let _ = WidgetExt::activate_action(
+                table_view,
+                "apps-page.track-termination",
+                Some(&pids.to_variant()),
+            );
+        },
+    },
+    ActionDescriptor {
+        name: "end-all-instances",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: is_app,
+        execute: confirm_end_all_instances,
+    },
+    ActionDescriptor {
+        name: "stop",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: always,
+        execute: |_, row_model| signal_processes(row_model, MagpieClient::terminate_processes),
+    },
+    ActionDescriptor {
+        name: "force-stop",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: always,
+        execute: |_, row_model| signal_processes(row_model, MagpieClient::kill_processes),
+    },
+    ActionDescriptor {
+        name: "suspend",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: always,
+        execute: |_, row_model| signal_processes(row_model, MagpieClient::suspend_processes),
+    },
+    ActionDescriptor {
+        name: "continue",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: always,
+        execute: |_, row_model| signal_processes(row_model, MagpieClient::continue_processes),
+    },
+    ActionDescriptor {
+        name: "hangup",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: always,
+        execute: |_, row_model| signal_processes(row_model, MagpieClient::hangup_processes),
+    },
+    ActionDescriptor {
+        name: "interrupt",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: always,
+        execute: |_, row_model| signal_processes(row_model, MagpieClient::interrupt_processes),
+    },
+    ActionDescriptor {
+        name: "user-one",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: always,
+        execute: |_, row_model| {
+            signal_processes(row_model, MagpieClient::user_signal_one_processes)
+        },
+    },
+    ActionDescriptor {
+        name: "user-two",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: always,
+        execute: |_, row_model| {
+            signal_processes(row_model, MagpieClient::user_signal_two_processes)
+        },
+    },
+    ActionDescriptor {
+        name: "io-priority-idle",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: can_set_io_priority,
+        execute: |_, row_model| set_io_priority(row_model, IoPriority::Idle),
+    },
+    ActionDescriptor {
+        name: "io-priority-low",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: can_set_io_priority,
+        execute: |_, row_model| set_io_priority(row_model, IoPriority::BestEffort(7)),
+    },
+    ActionDescriptor {
+        name: "io-priority-normal",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: can_set_io_priority,
+        execute: |_, row_model| set_io_priority(row_model, IoPriority::BestEffort(4)),
+    },
+    ActionDescriptor {
+        name: "io-priority-high",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: can_set_io_priority,
+        execute: |_, row_model| set_io_priority(row_model, IoPriority::BestEffort(0)),
+    },
+    ActionDescriptor {
+        name: "io-priority-realtime",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: can_set_io_priority,
+        execute: |_, row_model| set_io_priority(row_model, IoPriority::Realtime),
+    },
+    ActionDescriptor {
+        name: "bring-to-front",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: has_windows,
+        execute: |_, row_model| {
+            app_windows::list_windows(process_pids(row_model), |windows| {
+                if let Some(window) = windows.first() {
+                    app_windows::activate_window(window);
+                }
+            })
+        },
+    },
+    ActionDescriptor {
+        name: "close-windows",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: has_windows,
+        execute: |_, row_model| {
+            app_windows::list_windows(process_pids(row_model), |windows| {
+                windows.iter().for_each(app_windows::close_window)
+            })
+        },
+    },
+    ActionDescriptor {
+        name: "open-file-location",
+        target: ActionTarget::Process,
+        read_only: true,
+        is_enabled: |row_model| {
+            !crate::magpie_client::is_remote()
+                && executable_row(row_model).is_some_and(|row_model| !row_model.exe().is_empty())
+        },
+        execute: |table_view, row_model| {
+            let Some(process) = executable_row(row_model) else {
+                return;
+            };
+            let app_id = (row_model.content_type() == ContentType::App).then(|| row_model.id());
+            // The process that has the PID of a snapshot row now is not the one that was saved
+            let pid = (!table_view.is_showing_snapshot()).then(|| process.pid());
+            let path = file_location::executable_path(pid, &process.exe(), app_id.as_deref());
+
+            let table_view_weak = table_view.downgrade();
+            file_location::show(
+                &path,
+                table_view.root().and_downcast::<gtk::Window>(),
+                move || {
+                    if let Some(table_view) = table_view_weak.upgrade() {
+                        show_toast(&table_view, &i18n("The file location could not be opened"));
+                    }
+                },
+            );
+        },
+    },
+    ActionDescriptor {
+        name: "open-terminal",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: |row_model| process_working_directory(row_model).is_some(),
+        execute: |table_view, row_model| {
+            let Some(directory) = process_working_directory(row_model) else {
+                return;
+            };
+
+            if let Err(e) = terminal::open(&directory) {
+                g_critical!(
+                    "MissionCenter::Actions",
+                    "Failed to open a terminal in {}: {}",
+                    directory.display(),
+                    e
+                );
+                show_toast(
+                    table_view,
+                    &i18n("No terminal could be opened, install one to use this"),
+                );
+            }
+        },
+    },
+    ActionDescriptor {
+        // Runs nothing, only shows the Debug submenu while developer mode is enabled
+        name: "debug",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: can_debug,
+        execute: |_, _| {},
+    },
+    ActionDescriptor {
+        name: "attach-debugger",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: can_debug,
+        execute: |table_view, row_model| run_debug_tool(table_view, row_model, DebugTool::Debugger),
+    },
+    ActionDescriptor {
+        name: "profile",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: can_debug,
+        execute: |table_view, row_model| run_debug_tool(table_view, row_model, DebugTool::Profiler),
+    },
+    ActionDescriptor {
+        name: "trace-system-calls",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: can_debug,
+        execute: |table_view, row_model| {
+            let table_view_weak = table_view.downgrade();
+            let result = debug_tools::trace_system_calls(
+                row_model.pid(),
+                &row_model.name(),
+                move |file| {
+                    let Some(table_view) = table_view_weak.upgrade() else {
+                        return;
+                    };
+                    match file {
+                        Some(file) => show_trace_toast(&table_view, file),
+                        None => show_toast(
+                            &table_view,
+                            &i18n("No system calls could be traced, check the trace command"),
+                        ),
+                    }
+                },
+            );
+
+            match result {
+                Ok(()) => show_toast(table_view, &i18n("Tracing system calls for 10 seconds…")),
+                Err(e) => {
+                    g_critical!(
+                        "MissionCenter::Actions",
+                        "Failed to trace the system calls of {}: {}",
+                        row_model.pid(),
+                        e
+                    );
+                    show_toast(table_view, &i18n("The trace command could not be run"));
+                }
+            }
+        },
+    },
+    ActionDescriptor {
+        name: "copy-name",
+        target: ActionTarget::Process,
+        read_only: true,
+        is_enabled: always,
+        execute: |_, row_model| copy_to_clipboard(&row_model.name()),
+    },
+    ActionDescriptor {
+        name: "copy-pid",
+        target: ActionTarget::Process,
+        read_only: true,
+        is_enabled: |row_model| row_model.pid() != 0,
+        execute: |_, row_model| copy_to_clipboard(&row_model.pid().to_string()),
+    },
+    ActionDescriptor {
+        name: "copy-command-line",
+        target: ActionTarget::Process,
+        read_only: true,
+        is_enabled: |row_model| !row_model.command_line().is_empty(),
+        execute: |_, row_model| copy_to_clipboard(&row_model.command_line()),
+    },
+    ActionDescriptor {
+        name: "pin",
+        target: ActionTarget::Process,
+        read_only: true,
+        is_enabled: pinned::can_pin,
+        execute: |table_view, row_model| set_pinned(table_view, row_model, true),
+    },
+    ActionDescriptor {
+        name: "unpin",
+        target: ActionTarget::Process,
+        read_only: true,
+        is_enabled: pinned::can_unpin,
+        execute: |table_view, row_model| set_pinned(table_view, row_model, false),
+    },
+    ActionDescriptor {
+        name: "ignore",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: ignored::can_ignore,
+        execute: ignore,
+    },
+    ActionDescriptor {
+        name: "keep-awake",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: |row_model| can_keep_awake(row_model) && !is_keeping_awake(row_model),
+        execute: |table_view, row_model| set_keeping_awake(table_view, row_model, true),
+    },
+    ActionDescriptor {
+        name: "stop-keeping-awake",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: |row_model| can_keep_awake(row_model) && is_keeping_awake(row_model),
+        execute: |table_view, row_model| set_keeping_awake(table_view, row_model, false),
+    },
+    ActionDescriptor {
+        name: "notify-on-exit",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: |row_model| can_watch_exit(row_model) && !is_notifying_on_exit(row_model),
+        execute: |table_view, row_model| set_notifying_on_exit(table_view, row_model, true),
+    },
+    ActionDescriptor {
+        name: "cancel-exit-notification",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: |row_model| can_watch_exit(row_model) && is_notifying_on_exit(row_model),
+        execute: |table_view, row_model| set_notifying_on_exit(table_view, row_model, false),
+    },
+    ActionDescriptor {
+        name: "read-restricted-details",
+        target: ActionTarget::Process,
+        read_only: false,
+        is_enabled: |row_model| {
+            row_model.content_type() == ContentType::Process
+                && row_model.is_restricted()
+                && !crate::magpie_client::is_remote()
+        },
+        execute: read_restricted_details,
+    },
+    ActionDescriptor {
+        name: "details",
+        target: ActionTarget::Process,
+        read_only: true,
+        is_enabled: always,
+        execute: |table_view, row_model| {
+            // The Apps page shows the details next to the list, if the user chose so
+            if settings!().boolean("apps-page-show-details-sidebar")
+                && WidgetExt::activate_action(table_view, "apps-page.show-details", None).is_ok()
+            {
+                return;
+            }
+
+            let dialog = ProcessDetailsDialog::new(
+                row_model.clone(),
+                table_view.cpu_usage_mode(),
+                table_view.is_showing_snapshot(),
+            );
+            dialog.present(Some(table_view));
+        },
+    },
+];
+
+pub const SERVICE_ACTIONS: &[ActionDescriptor] = &[
+    ActionDescriptor {
+        name: "start",
+        target: ActionTarget::Service,
+        read_only: false,
+        is_enabled: |row_model| {
+            !row_model.service_running()
+                && !row_model.service_activating()
+                && !row_model.service_pending()
+        },
+        execute: |table_view, row_model| {
+            service_request(row_model, MagpieClient::start_service);
+            report_start_outcome(table_view, row_model);
+        },
+    },
+    ActionDescriptor {
+        name: "stop",
+        target: ActionTarget::Service,
+        read_only: false,
+        is_enabled: |row_model| row_model.service_running() && !row_model.service_pending(),
+        execute: |_, row_model| service_request(row_model, MagpieClient::stop_service),
+    },
+    ActionDescriptor {
+        name: "restart",
+        target: ActionTarget::Service,
+        read_only: false,
+        is_enabled: |row_model| row_model.service_running() && !row_model.service_pending(),
+        execute: |_, row_model| service_request(row_model, MagpieClient::restart_service),
+    },
+    ActionDescriptor {
+        name: "copy-name",
+        target: ActionTarget::Service,
+        read_only: true,
+        is_enabled: always,
+        execute: |_, row_model| copy_to_clipboard(&row_model.name()),
+    },
+    ActionDescriptor {
+        name: "copy-pid",
+        target: ActionTarget::Service,
+        read_only: true,
+        is_enabled: |row_model| row_model.pid() != 0,
+        execute: |_, row_model| copy_to_clipboard(&row_model.pid().to_string()),
+    },
+    ActionDescriptor {
+        name: "copy-command-line",
+        target: ActionTarget::Service,
+        read_only: true,
+        is_enabled: |row_model| !row_model.exec_start().is_empty(),
+        execute: |_, row_model| copy_to_clipboard(&row_model.exec_start()),
+    },
+    ActionDescriptor {
+        name: "copy-file-path",
+        target: ActionTarget::Service,
+        read_only: true,
+        is_enabled: |row_model| !row_model.file_path().is_empty(),
+        execute: |_, row_model| copy_to_clipboard(&row_model.file_path()),
+    },
+    ActionDescriptor {
+        name: "pin",
+        target: ActionTarget::Service,
+        read_only: true,
+        is_enabled: pinned::can_pin,
+        execute: |table_view, row_model| set_pinned(table_view, row_model, true),
+    },
+    ActionDescriptor {
+        name: "unpin",
+        target: ActionTarget::Service,
+        read_only: true,
+        is_enabled: pinned::can_unpin,
+        execute: |table_view, row_model| set_pinned(table_view, row_model, false),
+    },
+    ActionDescriptor {
+        name: "ignore",
+        target: ActionTarget::Service,
+        read_only: false,
+        is_enabled: ignored::can_ignore,
+        execute: ignore,
+    },
+    ActionDescriptor {
+        name: "details",
+        target: ActionTarget::Service,
+        read_only: true,
+        // Template rows only group their instances, and have no service of their own
+        is_enabled: |row_model| !row_model.service_template(),
+        execute: |table_view, _| {
+            let dialog = ServiceDetailsDialog::new(table_view);
+            dialog.present(Some(table_view));
+        },
+    },
+];
+
+/// Creates a `gio::SimpleAction` that runs `descriptor` on the selected row of `table_view`,
+/// and that is only enabled while the action applies to the selected row
+pub fn create_action(
+    table_view: &TableView,
+    descriptor: &'static ActionDescriptor,
+) -> gio::SimpleAction {
+    let action = gio::SimpleAction::new(descriptor.name, None);
+    action.set_enabled(descriptor.can_execute(table_view, &table_view.selected_item()));
+
+    let update_enabled = {
+        let action = action.downgrade();
+        move |table_view: &TableView| {
+            let Some(action) = action.upgrade() else {
+                return;
+            };
+            action.set_enabled(descriptor.can_execute(table_view, &table_view.selected_item()));
+        }
+    };
+    table_view.connect_selected_item_notify(update_enabled.clone());
+    table_view.connect_selected_item_running_notify(update_enabled);
+
+    action.connect_activate({
+        let table_view = table_view.downgrade();
+        move |_action, _| {
+            let Some(table_view) = table_view.upgrade() else {
+                g_critical!(
+                    "MissionCenter::Actions",
+                    "Failed to get TableView instance for action `{}`",
+                    descriptor.name
+                );
+                return;
+            };
+
+            let selected_item = table_view.selected_item();
+            descriptor.execute(&table_view, &selected_item);
+        }
+    });
+
+    action
+}
+
+/// Runs the action called `name` out of `descriptors` on `row_model`, for callers that act on a
+/// row without it being selected. Returns `false` if there is no such action, or if it does not
+/// apply to the row.
+pub fn execute_action(
+    descriptors: &[ActionDescriptor],
+    name: &str,
+    table_view: &TableView,
+    row_model: &RowModel,
+) -> bool {
+    descriptors
+        .iter()
+        .find(|descriptor| descriptor.name == name)
+        .is_some_and(|descriptor| descriptor.execute(table_view, row_model))
+}
+
+pub fn create_action_group(
+    table_view: &TableView,
+    descriptors: impl IntoIterator<Item = &'static ActionDescriptor>,
+) -> gio::SimpleActionGroup {
+    let action_group = gio::SimpleActionGroup::new();
+    for descriptor in descriptors {
+        action_group.add_action(&create_action(table_view, descriptor));
+    }
+
+    action_group
+}
+
+fn always(_: &RowModel) -> bool {
+    true
+}
+
+fn signal_processes(row_model: &RowModel, request: fn(&MagpieClient, Vec<u32>)) {
+    let pids = process_pids(row_model);
+    if pids.is_empty() {
+        return;
+    }
+
+    match app!().sys_info() {
+        Ok(magpie_client) => request(&magpie_client, pids),
+        Err(e) => {
+            g_critical!(
+                "MissionCenter::Actions",
+                "Failed to get sys_info from MissionCenterApplication: {e}",
+            );
+        }
+    }
+}
+
+fn is_app(row_model: &RowModel) -> bool {
+    row_model.content_type() == ContentType::App
+}
+
+// Every process of the app, not just the first of each tree like `process_pids`. Helpers and
+// background instances the tree hides under other processes are ended too.
+fn all_instance_pids(row_model: &RowModel) -> Vec<u32> {
+    let Some(window) = app!().window() else {
+        return vec![];
+    };
+
+    let mut pids = window
+        .imp()
+        .apps_page
+        .running_apps()
+        .get(row_model.id().as_str())
+        .map(|app| app.pids.clone())
+        .unwrap_or_default();
+    pids.sort_unstable();
+    pids.dedup();
+    pids
+}
+
+fn confirm_end_all_instances(table_view: &TableView, row_model: &RowModel) {
+    let pids = all_instance_pids(row_model);
+    if pids.is_empty() {
+        return;
+    }
+
+    let name = row_model.name();
+    let dialog = adw::AlertDialog::new(
+        Some(&i18n_f("End All Instances of {}?", &[&name])),
+        Some(&ni18n_f(
+            "{} process will be asked to exit. Unsaved data may be lost.",
+            "{} processes will be asked to exit. Unsaved data may be lost.",
+            pids.len() as u32,
+            &[&pids.len().to_string()],
+        )),
+    );
+    dialog.add_responses(&[("cancel", &i18n("_Cancel")), ("end", &i18n("_End All"))]);
+    dialog.set_response_appearance("end", adw::ResponseAppearance::Destructive);
+    dialog.set_default_response(Some("cancel"));
+    dialog.set_close_response("cancel");
+
+    dialog.connect_response(Some("end"), {
+        let table_view = table_view.downgrade();
+        move |_, _| {
+            match app!().sys_info() {
+                Ok(magpie_client) => magpie_client.terminate_processes(pids.clone()),
+                Err(e) => {
+                    g_critical!(
+                        "MissionCenter::Actions",
+                        "Failed to get sys_info from MissionCenterApplication: {e}",
+                    );
+                    return;
+                }
+            }
+
+            // Processes that ignore the request are handled like those of End Task
+            if let Some(table_view) = table_view.upgrade() {
+                let _ = WidgetExt::activate_action(
+                    &table_view,
+                    "apps-page.track-termination",
+                    Some(&pids.to_variant()),
+                );
+            }
+        }
+    });
+
+    dialog.present(Some(table_view));
+}
+
+// Whether there actually are windows is only known once they were listed
+fn has_windows(row_model: &RowModel) -> bool {
+    row_model.content_type() == ContentType::App && app_windows::is_supported()
+}
+
+fn can_set_io_priority(_: &RowModel) -> bool {
+    !crate::magpie_client::is_remote()
+}
+
+fn set_io_priority(row_model: &RowModel, priority: IoPriority) {
+    let pids = process_pids(row_model);
+    if pids.is_empty() {
+        return;
+    }
+
+    match app!().sys_info() {
+        Ok(magpie_client) => magpie_client.set_io_priority(pids, priority),
+        Err(e) => {
+            g_critical!(
+                "MissionCenter::Actions",
+                "Failed to get sys_info from MissionCenterApplication: {e}",
+            );
+        }
+    }
+}
+
+// The row shows that the request is pending until the next readings show the service change
+fn service_request(row_model: &RowModel, request: fn(&MagpieClient, u64)) {
+    match app!().sys_info() {
+        Ok(magpie_client) => {
+            request(&magpie_client, row_model.service_id());
+            row_model.begin_service_request();
+        }
+        Err(e) => {
+            g_critical!(
+                "MissionCenter::Actions",
+                "Failed to get sys_info from MissionCenterApplication: {e}",
+            );
+        }
+    }
+}
+
+// Systemd queues the start job, so how it went is only known a moment later. Starting can
+// also succeed without the service running, e.g. when it waits for its socket.
+fn report_start_outcome(table_view: &TableView, row_model: &RowModel) {
+    if crate::magpie_client::is_remote() {
+        return;
+    }
+
+    let name = row_model.name();
+    let table_view = table_view.downgrade();
+    glib::timeout_add_local_once(Duration::from_secs(2), move || {
+        let unit = name.to_string();
+        service_start_outcome(&unit, move |outcome| {
+            let message = match outcome {
+                StartOutcome::Started | StartOutcome::Unknown => return,
+                StartOutcome::Activating => i18n_f("{} is still starting", &[&name]),
+                StartOutcome::WaitingForSocket => {
+                    i18n_f("{} will start once its socket is used", &[&name])
+                }
+                StartOutcome::ConditionFailed => i18n_f(
+                    "{} was not started because one of its conditions was not met",
+                    &[&name],
+                ),
+                StartOutcome::Failed => i18n_f("{} failed to start", &[&name]),
+            };
+
+            if let Some(table_view) = table_view.upgrade() {
+                show_toast(&table_view, &message);
+            }
+        });
+    });
+}
+
+fn show_toast(table_view: &TableView, message: &str) {
+    let Some(toast_overlay) = table_view
+        .ancestor(adw::ToastOverlay::static_type())
+        .and_downcast::<adw::ToastOverlay>()
+    else {
+        return;
+    };
+    toast_overlay.add_toast(adw::Toast::new(message));
+}
+
+fn read_restricted_details(table_view: &TableView, row_model: &RowModel) {
+    let name = row_model.name();
+    let pid = row_model.pid();
+    let table_view = table_view.downgrade();
+    proc_fs::read_restricted_details(pid, move |result| {
+        let Some(table_view) = table_view.upgrade() else {
+            return;
+        };
+
+        let details = match result {
+            Ok(details) => details,
+            Err(e) => {
+                g_critical!(
+                    "MissionCenter::Actions",
+                    "Failed to read the restricted details of {}: {}",
+                    pid,
+                    e
+                );
+                show_toast(&table_view, &i18n_f("Could not read the details of {}", &[&name]));
+                return;
+            }
+        };
+
+        let unknown = || i18n("Unknown");
+        let bytes = |bytes: Option<u64>| {
+            bytes
+                .map(|bytes| crate::to_human_readable_nice(bytes as f32, &DataType::DriveBytes))
+                .unwrap_or_else(unknown)
+        };
+        let body = [
+            i18n_f("Executable: {}", &[&details.executable.unwrap_or_else(unknown)]),
+            i18n_f("Read from drives: {}", &[&bytes(details.bytes_read)]),
+            i18n_f("Written to drives: {}", &[&bytes(details.bytes_written)]),
+        ]
+        .join("\n");
+
+        let dialog = adw::AlertDialog::new(Some(&name), Some(&body));
+        dialog.add_response("close", &i18n("_Close"));
+        dialog.set_default_response(Some("close"));
+        dialog.set_close_response("close");
+        dialog.present(Some(&table_view));
+    });
+}
+
+// Apps show the executable of their first process
+fn executable_row(row_model: &RowModel) -> Option<RowModel> {
+    match row_model.content_type() {
+        ContentType::Process => Some(row_model.clone()),
+        ContentType::App => row_model
+            .children()
+            .item(0)
+            .and_downcast::<RowModel>()
+            .and_then(|child| find_stoppable_child(&child)),
+        _ => None,
+    }
+}
+
+// Apps open the terminal where their first process works
+fn process_working_directory(row_model: &RowModel) -> Option<std::path::PathBuf> {
+    proc_fs::working_directory(*process_pids(row_model).first()?)
+}
+
+// Debuggers attach to a single process, so apps can't be debugged as a whole
+fn can_debug(row_model: &RowModel) -> bool {
+    row_model.content_type() == ContentType::Process && debug_tools::is_available(row_model.pid())
+}
+
+fn run_debug_tool(table_view: &TableView, row_model: &RowModel, tool: DebugTool) {
+    if let Err(e) = debug_tools::run_in_terminal(tool, row_model.pid()) {
+        g_critical!(
+            "MissionCenter::Actions",
+            "Failed to run {:?} for {}: {}",
+            tool,
+            row_model.pid(),
+            e
+        );
+        show_toast(
+            table_view,
+            &i18n("The command could not be run, check it in the preferences"),
+        );
+    }
+}
+
+fn show_trace_toast(table_view: &TableView, file: std::path::PathBuf) {
+    let Some(toast_overlay) = table_view
+        .ancestor(adw::ToastOverlay::static_type())
+        .and_downcast::<adw::ToastOverlay>()
+    else {
+        return;
+    };
+
+    let file_name = file.file_name().unwrap_or_default().to_string_lossy().into_owned();
+    let toast = adw::Toast::builder()
+        .title(i18n_f("System calls saved to {}", &[&file_name]))
+        .button_label(i18n("Show"))
+        .build();
+    toast.connect_button_clicked({
+        let table_view = table_view.downgrade();
+        move |_| {
+            let parent = table_view
+                .upgrade()
+                .and_then(|table_view| table_view.root())
+                .and_downcast::<gtk::Window>();
+            file_location::show(&file, parent, || {});
+        }
+    });
+    toast_overlay.add_toast(toast);
+}
+
+fn set_pinned(table_view: &TableView, row_model: &RowModel, pin: bool) {
+    pinned::set_pinned(row_model, pin);
+
+    // The selection stays the same, but whether it can be pinned or unpinned changed
+    table_view.notify_selected_item();
+}
+
+// Hidden with the next readings, the toast is the only way back short of the preferences
+fn ignore(table_view: &TableView, row_model: &RowModel) {
+    let Some(entry) = ignored::ignore_entry(row_model) else {
+        return;
+    };
+    ignored::set_ignored(&entry, true);
+
+    let Some(toast_overlay) = table_view
+        .ancestor(adw::ToastOverlay::static_type())
+        .and_downcast::<adw::ToastOverlay>()
+    else {
+        return;
+    };
+
+    let toast = adw::Toast::builder()
+        .title(i18n_f("{} is ignored", &[&row_model.name()]))
+        .button_label(i18n("Undo"))
+        .build();
+    toast.connect_button_clicked(move |_| ignored::set_ignored(&entry, false));
+    toast_overlay.add_toast(toast);
+}
+
+fn can_keep_awake(row_model: &RowModel) -> bool {
+    row_model.content_type() == ContentType::Process && row_model.pid() != 0
+}
+
+fn is_keeping_awake(row_model: &RowModel) -> bool {
+    app!().is_keeping_awake(row_model.pid())
+}
+
+fn set_keeping_awake(table_view: &TableView, row_model: &RowModel, keep_awake: bool) {
+    let app = app!();
+    if !keep_awake {
+        app.stop_keeping_awake(row_model.pid());
+    } else if !app.keep_awake_while_running(row_model.pid(), &row_model.name()) {
+        show_toast(table_view, &i18n("The system does not allow keeping it awake"));
+    }
+
+    // The selection stays the same, but which of the two actions applies changed
+    table_view.notify_selected_item();
+}
+
+fn can_watch_exit(row_model: &RowModel) -> bool {
+    row_model.content_type() == ContentType::Process && row_model.pid() != 0
+}
+
+fn is_notifying_on_exit(row_model: &RowModel) -> bool {
+    app!().is_notifying_on_exit(row_model.pid())
+}
+
+fn set_notifying_on_exit(table_view: &TableView, row_model: &RowModel, notify: bool) {
+    let app = app!();
+    if notify {
+        app.notify_on_exit(row_model.pid(), &row_model.name());
+        show_toast(table_view, &i18n_f("You will be notified when {} ends", &[&row_model.name()]));
+    } else {
+        app.cancel_exit_notification(row_model.pid());
+    }
+
+    table_view.notify_selected_item();
+}
+
+fn copy_to_clipboard(text: &str) {
+    match gdk::Display::default() {
+        Some(display) => display.clipboard().set_text(text),
+        None => {
+            g_critical!(
+                "MissionCenter::Actions",
+                "Failed to get the default display, nothing was copied",
+            );
+        }
+    }
+}
+
+/// The processes an action on the row applies to, all of its processes for apps
+pub fn process_pids(row_model: &RowModel) -> Vec<u32> {
+    match row_model.content_type() {
+        ContentType::Process => vec![row_model.pid()],
+        ContentType::App => app_pids(row_model),
+        _ => vec![],
+    }
+}
+
+fn app_pids(row_model: &RowModel) -> Vec<u32> {
+    let children = row_model.children();
+    let mut result = Vec::with_capacity(children.n_items() as usize);
+
+    for i in 0..children.n_items() {
+        let Some(child) = children
+            .item(i)
+            .and_then(|i| i.downcast::<RowModel>().ok())
+            .and_then(|rm| find_stoppable_child(&rm))
+        else {
+            continue;
+        };
+        result.push(child.pid());
+    }
+
+    result
+}
+
+fn find_stoppable_child(row_model: &RowModel) -> Option<RowModel> {
+    if row_model.name() != "bwrap" {
+        return Some(row_model.clone());
+    }
+
+    let children = row_model.children();
+    for i in 0..children.n_items() {
+        let Some(child) = children.item(i).and_then(|i| i.downcast::<RowModel>().ok()) else {
+            continue;
+        };
+        if let Some(rm) = find_stoppable_child(&child) {
+            return Some(rm);
+        }
+    }
+
+    None
+}
@@ -0,0 +1,131 @@
+/* table_view/app_windows.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use gtk::glib::{g_debug, g_warning};
+use gtk::{gio, glib};
+
+use crate::magpie_client::host_subprocess;
+
+/// A toplevel window as listed by `wmctrl`, which only knows about the windows of X11 clients,
+/// including those running under XWayland
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppWindow {
+    pub id: String,
+    pub pid: u32,
+    pub title: String,
+}
+
+/// Windows can only be found on the machine the app runs on
+pub fn is_supported() -> bool {
+    !crate::magpie_client::is_remote()
+}
+
+/// Lists the windows that belong to one of `pids`, `on_done` gets an empty list if there are
+/// none or if they could not be listed
+pub fn list_windows(pids: Vec<u32>, on_done: impl FnOnce(Vec<AppWindow>) + 'static) {
+    let subprocess = match spawn(&["wmctrl", "-l", "-p"], gio::SubprocessFlags::STDOUT_PIPE) {
+        Ok(subprocess) => subprocess,
+        Err(e) => {
+            g_debug!("MissionCenter::AppWindows", "Failed to list windows: {}", e);
+            on_done(vec![]);
+            return;
+        }
+    };
+
+    subprocess.communicate_utf8_async(None, None::<&gio::Cancellable>, move |result| {
+        let output = match result {
+            Ok((Some(output), _)) => output,
+            Ok((None, _)) => Default::default(),
+            Err(e) => {
+                g_debug!("MissionCenter::AppWindows", "Failed to list windows: {}", e);
+                Default::default()
+            }
+        };
+
+        on_done(
+            output
+                .lines()
+                .filter_map(parse_window)
+                .filter(|window| pids.contains(&window.pid))
+                .collect(),
+        );
+    });
+}
+
+/// Switches to the desktop of the window and raises it
+pub fn activate_window(window: &AppWindow) {
+    run(&["wmctrl", "-i", "-a", &window.id]);
+}
+
+/// Asks the window to close, the same as clicking its close button
+pub fn close_window(window: &AppWindow) {
+    run(&["wmctrl", "-i", "-c", &window.id]);
+}
+
+fn run(args: &[&str]) {
+    let description = args.join(" ");
+    match spawn(args, gio::SubprocessFlags::NONE) {
+        Ok(subprocess) => subprocess.wait_check_async(None::<&gio::Cancellable>, move |result| {
+            if let Err(e) = result {
+                g_warning!(
+                    "MissionCenter::AppWindows",
+                    "Failed to run `{}`: {}",
+                    description,
+                    e
+                );
+            }
+        }),
+        Err(e) => {
+            g_warning!(
+                "MissionCenter::AppWindows",
+                "Failed to run `{}`: {}",
+                description,
+                e
+            );
+        }
+    }
+}
+
+// Windows live on the host, not in the sandbox
+fn spawn(args: &[&str], flags: gio::SubprocessFlags) -> Result<gio::Subprocess, glib::Error> {
+    host_subprocess(args, flags | gio::SubprocessFlags::STDERR_SILENCE)
+}
+
+// Lines are made of the window ID, desktop, PID, host name and title, separated by one or more
+// spaces. Only the title can contain spaces.
+fn parse_window(line: &str) -> Option<AppWindow> {
+    let mut rest = line;
+    let mut fields = [""; 4];
+    for field in fields.iter_mut() {
+        rest = rest.trim_start();
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        if end == 0 {
+            return None;
+        }
+        *field = &rest[..end];
+        rest = &rest[end..];
+    }
+
+    Some(AppWindow {
+        id: fields[0].to_string(),
+        pid: fields[2].parse().ok()?,
+        title: rest.trim().to_string(),
+    })
+}
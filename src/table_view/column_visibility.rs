@@ -0,0 +1,398 @@
+/* table_view/column_visibility.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use adw::prelude::*;
+use gtk::glib::g_critical;
+use gtk::{gio, glib};
+
+use crate::i18n::i18n;
+use crate::settings;
+use crate::table_view::sections::{self, SectionSort};
+use crate::table_view::{SectionType, SettingsNamespace, SettingsValues, TableView};
+
+/// The name of the action group the column toggles are inserted as
+const ACTION_GROUP_NAME: &str = "columns";
+
+// The name identifies the entries, so it can't be hidden
+const ALWAYS_VISIBLE_COLUMN: &str = "name";
+
+const GPU_DEVICE_COLUMN: &str = "gpu_device";
+
+const WAIT_CHANNEL_COLUMN: &str = "wait_channel";
+
+/// Applies the hidden columns setting and gives every column header a menu to sort by the
+/// column, hide or autosize it, show or hide the other columns, copy the rows and reset the
+/// layout. Needs to run
+/// again whenever columns are added or removed, and when the table turns narrow or wide.
+pub fn update(table_view: &TableView) {
+    let hidden_columns_key = table_view.format_settings_key(&SettingsValues::HiddenColumns);
+    let hidden_columns = settings!().strv(&hidden_columns_key);
+
+    let actions = gio::SimpleActionGroup::new();
+    // Shared by the header menus of all the columns
+    let columns_menu = gio::Menu::new();
+    let reset_section = gio::Menu::new();
+
+    let columns = table_view.column_view().columns();
+    for column in (0..columns.n_items())
+        .filter_map(|i| columns.item(i))
+        .filter_map(|item| item.downcast::<gtk::ColumnViewColumn>().ok())
+    {
+        let Some(id) = column.id() else {
+            continue;
+        };
+
+        column.set_header_menu(Some(&header_menu(
+            table_view,
+            &column,
+            &id,
+            &columns_menu,
+            &reset_section,
+        )));
+
+        if id == ALWAYS_VISIBLE_COLUMN {
+            continue;
+        }
+
+        let visible = !hidden_columns
+            .iter()
+            .any(|hidden| hidden.as_str() == id.as_str());
+        column.set_visible(visible && !table_view.narrow());
+
+        let action = gio::SimpleAction::new_stateful(&id, None, &visible.to_variant());
+        action.connect_change_state({
+            let table_view = table_view.downgrade();
+            let column = column.downgrade();
+            let hidden_columns_key = hidden_columns_key.clone();
+            move |action, state| {
+                let (Some(table_view), Some(column), Some(visible)) = (
+                    table_view.upgrade(),
+                    column.upgrade(),
+                    state.and_then(|state| state.get::<bool>()),
+                ) else {
+                    return;
+                };
+
+                action.set_state(&visible.to_variant());
+                // Remembered for when the table is wide enough again
+                column.set_visible(visible && !table_view.narrow());
+                store_visibility(&hidden_columns_key, &action.name(), visible);
+            }
+        });
+        actions.add_action(&action);
+
+        let title = column.title().unwrap_or_default().replace('\n', " ");
+        columns_menu.append(Some(&title), Some(&format!("{ACTION_GROUP_NAME}.{id}")));
+    }
+
+    add_column_actions(table_view, &actions);
+
+    let action_reset_layout = gio::SimpleAction::new("reset-layout", None);
+    action_reset_layout.connect_activate({
+        let table_view = table_view.downgrade();
+        move |_, _| {
+            if let Some(table_view) = table_view.upgrade() {
+                table_view.reset_column_layout();
+            }
+        }
+    });
+    actions.add_action(&action_reset_layout);
+
+    reset_section.append(
+        Some(&i18n("Reset Column Layout")),
+        Some(&format!("{ACTION_GROUP_NAME}.reset-layout")),
+    );
+
+    table_view.insert_action_group(ACTION_GROUP_NAME, Some(&actions));
+}
+
+fn header_menu(
+    table_view: &TableView,
+    column: &gtk::ColumnViewColumn,
+    id: &str,
+    columns_menu: &gio::Menu,
+    reset_section: &gio::Menu,
+) -> gio::Menu {
+    let menu = gio::Menu::new();
+
+    if column.sorter().is_some() {
+        let sort_section = gio::Menu::new();
+        sort_section.append(
+            Some(&i18n("Sort Ascending")),
+            Some(&format!("{ACTION_GROUP_NAME}.sort-ascending::{id}")),
+        );
+        sort_section.append(
+            Some(&i18n("Sort Descending")),
+            Some(&format!("{ACTION_GROUP_NAME}.sort-descending::{id}")),
+        );
+        sort_section.append_submenu(
+            Some(&i18n("Sort One Section")),
+            &section_sort_menu(table_view, id),
+        );
+        menu.append_section(None, &sort_section);
+    }
+
+    let column_section = gio::Menu::new();
+    if id != ALWAYS_VISIBLE_COLUMN {
+        column_section.append(
+            Some(&i18n("Hide Column")),
+            Some(&format!("{ACTION_GROUP_NAME}.hide::{id}")),
+        );
+    }
+    column_section.append(
+        Some(&i18n("Autosize")),
+        Some(&format!("{ACTION_GROUP_NAME}.autosize::{id}")),
+    );
+    column_section.append_submenu(Some(&i18n("Columns")), columns_menu);
+    menu.append_section(None, &column_section);
+
+    if id == GPU_DEVICE_COLUMN {
+        menu.append_section(Some(&i18n("Show Only")), table_view.gpu_filter_menu());
+    }
+
+    let copy_section = gio::Menu::new();
+    copy_section.append(
+        Some(&i18n("Copy Visible Rows")),
+        Some(&format!("{ACTION_GROUP_NAME}.copy-visible-rows")),
+    );
+    menu.append_section(None, &copy_section);
+
+    menu.append_section(None, reset_section);
+
+    menu
+}
+
+/// Lets the rows of each section be sorted by the column, whatever the column headers say
+fn section_sort_menu(table_view: &TableView, id: &str) -> gio::Menu {
+    let menu = gio::Menu::new();
+
+    for section_type in [SectionType::FirstSection, SectionType::SecondSection] {
+        let (Some(key), Some(name)) = (
+            sections::section_key(section_type),
+            table_view.section_name(section_type),
+        ) else {
+            continue;
+        };
+
+        let section = gio::Menu::new();
+        for (label, descending) in [
+            (i18n("Sort Ascending"), false),
+            (i18n("Sort Descending"), true),
+        ] {
+            let item = gio::MenuItem::new(Some(&label), None);
+            item.set_action_and_target_value(
+                Some(&format!("{ACTION_GROUP_NAME}.sort-section")),
+                Some(&(key, id, descending).to_variant()),
+            );
+            section.append_item(&item);
+        }
+        section.append(
+            Some(&i18n("Follow Column Headers")),
+            Some(&format!("{ACTION_GROUP_NAME}.unsort-section::{key}")),
+        );
+        menu.append_section(Some(&name), &section);
+    }
+
+    menu
+}
+
+/// Adds the actions that act on the column whose id they are activated with
+fn add_column_actions(table_view: &TableView, actions: &gio::SimpleActionGroup) {
+    for (name, sort_type) in [
+        ("sort-ascending", gtk::SortType::Ascending),
+        ("sort-descending", gtk::SortType::Descending),
+    ] {
+        let action = gio::SimpleAction::new(name, Some(glib::VariantTy::STRING));
+        action.connect_activate({
+            let table_view = table_view.downgrade();
+            move |_, id| {
+                let Some(table_view) = table_view.upgrade() else {
+                    return;
+                };
+                if let Some(column) = find_column(&table_view, id) {
+                    table_view
+                        .column_view()
+                        .sort_by_column(Some(&column), sort_type);
+                }
+            }
+        });
+        actions.add_action(&action);
+    }
+
+    let action_hide = gio::SimpleAction::new("hide", Some(glib::VariantTy::STRING));
+    action_hide.connect_activate({
+        let actions = actions.downgrade();
+        move |_, id| {
+            let (Some(actions), Some(id)) = (actions.upgrade(), id.and_then(|id| id.str())) else {
+                return;
+            };
+            // Goes through the toggle, so the setting and the menu stay in sync
+            if let Some(action) = actions.lookup_action(id) {
+                action.change_state(&false.to_variant());
+            }
+        }
+    });
+    actions.add_action(&action_hide);
+
+    let action_autosize = gio::SimpleAction::new("autosize", Some(glib::VariantTy::STRING));
+    action_autosize.connect_activate({
+        let table_view = table_view.downgrade();
+        move |_, id| {
+            let Some(table_view) = table_view.upgrade() else {
+                return;
+            };
+            if let Some(column) = find_column(&table_view, id) {
+                column.set_fixed_width(-1);
+            }
+        }
+    });
+    actions.add_action(&action_autosize);
+
+    let action_copy_visible_rows = gio::SimpleAction::new("copy-visible-rows", None);
+    action_copy_visible_rows.connect_activate({
+        let table_view = table_view.downgrade();
+        move |_, _| {
+            if let Some(table_view) = table_view.upgrade() {
+                table_view.copy_visible_rows();
+            }
+        }
+    });
+    actions.add_action(&action_copy_visible_rows);
+
+    let action_filter_gpu = gio::SimpleAction::new_stateful(
+        "filter-gpu",
+        Some(glib::VariantTy::STRING),
+        &table_view.gpu_filter().to_variant(),
+    );
+    action_filter_gpu.connect_change_state({
+        let table_view = table_view.downgrade();
+        move |action, state| {
+            let (Some(table_view), Some(id)) =
+                (table_view.upgrade(), state.and_then(|state| state.str()))
+            else {
+                return;
+            };
+
+            action.set_state(&id.to_variant());
+            table_view.set_gpu_filter(id);
+        }
+    });
+    actions.add_action(&action_filter_gpu);
+
+    let action_sort_section =
+        gio::SimpleAction::new("sort-section", Some(glib::VariantTy::new("(ssb)").unwrap()));
+    action_sort_section.connect_activate({
+        let table_view = table_view.downgrade();
+        move |_, parameter| {
+            let (Some(table_view), Some((key, column_id, descending))) = (
+                table_view.upgrade(),
+                parameter.and_then(|parameter| parameter.get::<(String, String, bool)>()),
+            ) else {
+                return;
+            };
+            let Some(section_type) = sections::section_from_key(&key) else {
+                return;
+            };
+
+            let order = if descending {
+                gtk::SortType::Descending
+            } else {
+                gtk::SortType::Ascending
+            };
+            table_view.set_section_sort(section_type, Some(SectionSort { column_id, order }));
+        }
+    });
+    actions.add_action(&action_sort_section);
+
+    let action_unsort_section =
+        gio::SimpleAction::new("unsort-section", Some(glib::VariantTy::STRING));
+    action_unsort_section.connect_activate({
+        let table_view = table_view.downgrade();
+        move |_, key| {
+            let (Some(table_view), Some(section_type)) = (
+                table_view.upgrade(),
+                key.and_then(|key| key.str())
+                    .and_then(sections::section_from_key),
+            ) else {
+                return;
+            };
+            table_view.set_section_sort(section_type, None);
+        }
+    });
+    actions.add_action(&action_unsort_section);
+
+    // There are no sections to sort while they are hidden
+    for action in [&action_sort_section, &action_unsort_section] {
+        table_view
+            .bind_property("hide-sections", action, "enabled")
+            .invert_boolean()
+            .sync_create()
+            .build();
+    }
+}
+
+fn find_column(
+    table_view: &TableView,
+    id: Option<&glib::Variant>,
+) -> Option<gtk::ColumnViewColumn> {
+    let id = id.and_then(|id| id.str())?;
+
+    let columns = table_view.column_view().columns();
+    (0..columns.n_items())
+        .filter_map(|i| columns.item(i))
+        .filter_map(|item| item.downcast::<gtk::ColumnViewColumn>().ok())
+        .find(|column| column.id().as_deref() == Some(id))
+}
+
+/// Whether the wait channel column is shown on one of the pages, which is when the wait
+/// channels need to be read
+pub fn wait_channels_shown(settings: &gio::Settings) -> bool {
+    [SettingsNamespace::AppsPage, SettingsNamespace::ServicesPage]
+        .iter()
+        .any(|namespace| {
+            !settings
+                .strv(&namespace.format_value(&SettingsValues::HiddenColumns))
+                .iter()
+                .any(|hidden| hidden.as_str() == WAIT_CHANNEL_COLUMN)
+        })
+}
+
+fn store_visibility(hidden_columns_key: &str, id: &glib::GString, visible: bool) {
+    let settings = settings!();
+
+    let mut hidden_columns = settings
+        .strv(hidden_columns_key)
+        .iter()
+        .map(|hidden| hidden.to_string())
+        .filter(|hidden| hidden != id.as_str())
+        .collect::<Vec<_>>();
+    if !visible {
+        hidden_columns.push(id.to_string());
+    }
+
+    if let Err(e) = settings.set_strv(hidden_columns_key, hidden_columns) {
+        g_critical!(
+            "MissionCenter::TableView",
+            "Failed to set {} setting: {}",
+            hidden_columns_key,
+            e
+        );
+    }
+}
@@ -19,29 +19,64 @@
  */
 
 use std::cmp::Ordering;
+use std::rc::Rc;
 use std::fmt::Write;
 
 use arrayvec::ArrayString;
 use gtk::glib;
 use gtk::prelude::*;
 
-use super::{compare_column_entries_by, sort_order, LabelCell};
+use super::{compare_column_entries_by, HeatLevel, LabelCell, SortFlags};
+use crate::i18n::{i18n, i18n_f};
 use crate::label_cell_factory;
 
-pub fn list_item_factory() -> gtk::SignalListItemFactory {
-    label_cell_factory!("cpu-usage", label_formatter)
+/// How the CPU usage of an entry is shown. The rows always hold the usage as a share of the
+/// whole machine, so that both modes sort the same way.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum CpuUsageMode {
+    /// The share of all cores, at most 100%
+    #[default]
+    Normalized,
+    /// 100% per core, so a busy process can go above 100%. Holds the number of cores.
+    PerCore(u32),
 }
 
-pub fn sorter(column_view: &gtk::ColumnView) -> impl IsA<gtk::Sorter> {
-    let column_view = column_view.downgrade();
-    gtk::CustomSorter::new(move |lhs, rhs| {
-        let Some(column_view) = column_view.upgrade() else {
-            return Ordering::Equal.into();
-        };
+impl CpuUsageMode {
+    pub fn new(core_count_affects_percentages: bool, core_count: u32) -> Self {
+        if core_count_affects_percentages {
+            CpuUsageMode::PerCore(core_count.max(1))
+        } else {
+            CpuUsageMode::Normalized
+        }
+    }
+
+    /// Converts the usage held by the rows to the one that is shown
+    pub fn display_value(&self, cpu_usage: f32) -> f32 {
+        match self {
+            CpuUsageMode::Normalized => cpu_usage,
+            CpuUsageMode::PerCore(core_count) => cpu_usage * *core_count as f32,
+        }
+    }
+
+    pub fn format(&self, label: &LabelCell, cpu_usage: f32) {
+        label_formatter(label, self.display_value(cpu_usage).into());
+    }
+}
 
-        compare_column_entries_by(lhs, rhs, sort_order(&column_view), |lhs, rhs| {
-            let lhs = lhs.cpu_usage();
-            let rhs = rhs.cpu_usage();
+pub fn list_item_factory(mode: CpuUsageMode) -> gtk::SignalListItemFactory {
+    label_cell_factory!(
+        "cpu-usage",
+        i18n("CPU usage"),
+        move |label: &LabelCell, value: glib::Value| { cell_formatter(mode, label, value) }
+    )
+}
+
+pub fn sorter(sort_flags: &Rc<SortFlags>, mode: CpuUsageMode) -> impl IsA<gtk::Sorter> {
+    let sort_flags = sort_flags.clone();
+    gtk::CustomSorter::new(move |lhs, rhs| {
+        compare_column_entries_by(lhs, rhs, &sort_flags, |lhs, rhs| {
+            let lhs = mode.display_value(lhs.cpu_usage());
+            let rhs = mode.display_value(rhs.cpu_usage());
 
             lhs.partial_cmp(&rhs).unwrap_or(Ordering::Equal)
         })
@@ -49,6 +84,33 @@ pub fn sorter(column_view: &gtk::ColumnView) -> impl IsA<gtk::Sorter> {
     })
 }
 
+// Only the table cells are tinted, the totals bar shares `label_formatter`. In per-core mode a
+// process that keeps a single core busy is shown as hot.
+fn cell_formatter(mode: CpuUsageMode, label: &LabelCell, value: glib::Value) {
+    let cpu_usage = mode.display_value(value.get().unwrap());
+    label.set_heat_level(HeatLevel::from_fraction(cpu_usage / 100.));
+    label.set_hint(thermal_hint(label).as_deref());
+    label_formatter(label, cpu_usage.into());
+}
+
+// Entries that barely warm up the CPU, or a CPU that is about as cool as when idle, are not
+// worth pointing out
+fn thermal_hint(label: &LabelCell) -> Option<String> {
+    const MIN_SHARE: f32 = 0.05;
+    const MIN_RISE: f32 = 1.;
+
+    let model = label.model()?;
+    let (share, rise) = (model.thermal_share(), model.thermal_rise());
+    if share < MIN_SHARE || rise < MIN_RISE {
+        return None;
+    }
+
+    Some(i18n_f(
+        "Causes about {}% of the CPU heating up, around {} °C above idle",
+        &[&format!("{:.0}", share * 100.), &format!("{:.1}", rise)],
+    ))
+}
+
 pub fn label_formatter(label: &LabelCell, value: glib::Value) {
     let cpu_usage: f32 = value.get().unwrap();
     let mut buffer = ArrayString::<128>::new();
@@ -0,0 +1,55 @@
+/* table_view/columns/cpu_time.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::rc::Rc;
+
+use gtk::glib;
+use gtk::prelude::*;
+
+use super::{compare_column_entries_by, LabelCell, SortFlags};
+use crate::i18n::i18n;
+use crate::label_cell_factory;
+
+pub fn list_item_factory() -> gtk::SignalListItemFactory {
+    label_cell_factory!("cpu-time", i18n("CPU time"), label_formatter)
+}
+
+pub fn sorter(sort_flags: &Rc<SortFlags>) -> impl IsA<gtk::Sorter> {
+    let sort_flags = sort_flags.clone();
+    gtk::CustomSorter::new(move |lhs, rhs| {
+        compare_column_entries_by(lhs, rhs, &sort_flags, |lhs, rhs| {
+            lhs.cpu_time().total_cmp(&rhs.cpu_time())
+        })
+        .into()
+    })
+}
+
+// Hours, minutes and seconds like `ps` and `top` show it, hours are not wrapped into days
+pub fn label_formatter(label: &LabelCell, value: glib::Value) {
+    let cpu_time: f64 = value.get().unwrap();
+    let seconds = cpu_time as u64;
+
+    label.set_label(&format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    ));
+}
@@ -0,0 +1,169 @@
+/* table_view/columns/custom.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::rc::Rc;
+
+use gtk::prelude::*;
+
+use super::{compare_column_entries_by, LabelCell, SortFlags};
+use crate::magpie_client::ProcField;
+use crate::table_view::row_model::{ContentType, RowModel};
+
+/// Prefix of the IDs of the columns that show a `ProcField`
+pub const COLUMN_ID_PREFIX: &str = "custom_";
+
+pub fn column(sort_flags: &Rc<SortFlags>, field: ProcField) -> gtk::ColumnViewColumn {
+    let column = gtk::ColumnViewColumn::new(Some(&field.title()), Some(list_item_factory(field)));
+    column.set_id(Some(&format!("{}{}", COLUMN_ID_PREFIX, field.id())));
+    column.set_resizable(true);
+    column.set_sorter(Some(&sorter(sort_flags, field)));
+
+    column
+}
+
+fn list_item_factory(field: ProcField) -> gtk::SignalListItemFactory {
+    let factory = gtk::SignalListItemFactory::new();
+
+    factory.connect_setup(move |_, list_item| {
+        let Some(list_item) = list_item.downcast_ref::<gtk::ListItem>() else {
+            return;
+        };
+
+        let label = LabelCell::new();
+        label.set_value_description(&field.title());
+        let expander = gtk::TreeExpander::new();
+        expander.set_child(Some(&label));
+
+        expander.set_hide_expander(true);
+        expander.set_indent_for_icon(false);
+        expander.set_indent_for_depth(false);
+        expander.set_halign(gtk::Align::End);
+
+        list_item.set_child(Some(&expander));
+
+        unsafe {
+            list_item.set_data("expander", expander);
+            list_item.set_data("label", label);
+        }
+    });
+
+    factory.connect_bind(move |_, list_item| {
+        let Some(list_item) = list_item.downcast_ref::<gtk::ListItem>() else {
+            return;
+        };
+
+        let Some(row) = list_item
+            .item()
+            .and_then(|item| item.downcast::<gtk::TreeListRow>().ok())
+        else {
+            return;
+        };
+
+        let expander = unsafe {
+            list_item
+                .data::<gtk::TreeExpander>("expander")
+                .unwrap_unchecked()
+                .as_ref()
+        };
+        expander.set_list_row(Some(&row));
+
+        let Some(model) = expander
+            .item()
+            .and_then(|item| item.downcast::<RowModel>().ok())
+        else {
+            return;
+        };
+
+        let label = unsafe {
+            list_item
+                .data::<LabelCell>("label")
+                .unwrap_unchecked()
+                .as_ref()
+        };
+
+        label.set_row_name(&model.name());
+        if model.content_type() != ContentType::Process {
+            label.set_label("");
+            return;
+        }
+
+        set_label(label, &model, field);
+        label.bind(&model, "custom-values-serial", {
+            let model = model.downgrade();
+            move |label, _| {
+                if let Some(model) = model.upgrade() {
+                    set_label(label, &model, field);
+                }
+            }
+        });
+    });
+
+    factory.connect_unbind(|_, list_item| {
+        let Some(list_item) = list_item.downcast_ref::<gtk::ListItem>() else {
+            return;
+        };
+
+        let expander = unsafe {
+            list_item
+                .data::<gtk::TreeExpander>("expander")
+                .unwrap_unchecked()
+                .as_ref()
+        };
+        expander.set_list_row(None);
+
+        let label = unsafe {
+            list_item
+                .data::<LabelCell>("label")
+                .unwrap_unchecked()
+                .as_ref()
+        };
+        label.unbind();
+    });
+
+    factory.connect_teardown(|_, list_item| {
+        let Some(list_item) = list_item.downcast_ref::<gtk::ListItem>() else {
+            return;
+        };
+
+        unsafe {
+            let _ = list_item.steal_data::<gtk::TreeExpander>("expander");
+            let _ = list_item.steal_data::<LabelCell>("label");
+        }
+    });
+
+    factory
+}
+
+fn sorter(sort_flags: &Rc<SortFlags>, field: ProcField) -> impl IsA<gtk::Sorter> {
+    let sort_flags = sort_flags.clone();
+    gtk::CustomSorter::new(move |lhs, rhs| {
+        compare_column_entries_by(lhs, rhs, &sort_flags, |lhs, rhs| {
+            lhs.custom_value(field).cmp(&rhs.custom_value(field))
+        })
+        .into()
+    })
+}
+
+fn set_label(label: &LabelCell, model: &RowModel, field: ProcField) {
+    match model.custom_value(field) {
+        Some(value) => label.set_label(&value.to_string()),
+        None => label.set_label(""),
+    }
+}
@@ -19,25 +19,29 @@
  */
 
 use std::cmp::Ordering;
+use std::rc::Rc;
 
 use gtk::glib;
 use gtk::prelude::*;
 
-use super::{compare_column_entries_by, sort_order, LabelCell};
+use super::{compare_column_entries_by, HeatLevel, LabelCell, SortFlags};
+use crate::i18n::i18n;
 use crate::{label_cell_factory, DataType};
 
 pub fn list_item_factory() -> gtk::SignalListItemFactory {
-    label_cell_factory!("disk-usage", label_formatter)
+    label_cell_factory!(
+        "disk-usage",
+        i18n("Drive usage"),
+        ContentType::SectionHeader,
+        cell_formatter,
+        |model: &RowModel| model.io_restricted()
+    )
 }
 
-pub fn sorter(column_view: &gtk::ColumnView) -> impl IsA<gtk::Sorter> {
-    let column_view = column_view.downgrade();
+pub fn sorter(sort_flags: &Rc<SortFlags>) -> impl IsA<gtk::Sorter> {
+    let sort_flags = sort_flags.clone();
     gtk::CustomSorter::new(move |lhs, rhs| {
-        let Some(column_view) = column_view.upgrade() else {
-            return Ordering::Equal.into();
-        };
-
-        compare_column_entries_by(lhs, rhs, sort_order(&column_view), |lhs, rhs| {
+        compare_column_entries_by(lhs, rhs, &sort_flags, |lhs, rhs| {
             let lhs = lhs.disk_usage();
             let rhs = rhs.disk_usage();
 
@@ -47,6 +51,12 @@ pub fn sorter(column_view: &gtk::ColumnView) -> impl IsA<gtk::Sorter> {
     })
 }
 
+fn cell_formatter(label: &LabelCell, value: glib::Value) {
+    let disk_usage: f32 = value.get().unwrap();
+    label.set_heat_level(HeatLevel::from_rate(disk_usage));
+    label_formatter(label, value);
+}
+
 pub fn label_formatter(label: &LabelCell, value: glib::Value) {
     let disk_usage: f32 = value.get().unwrap();
     label.set_label(&crate::to_human_readable_nice(
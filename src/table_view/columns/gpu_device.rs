@@ -0,0 +1,104 @@
+/* table_view/columns/gpu_device.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::collections::HashMap;
+
+use gtk::glib;
+use gtk::prelude::*;
+
+use super::{compare_column_entries_by, LabelCell, SortFlags};
+use crate::i18n::{i18n, i18n_f};
+use crate::label_cell_factory;
+use crate::magpie_client::Gpu;
+
+thread_local! {
+    // The names GPUs are shown with, by PCI address
+    static GPU_LABELS: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+}
+
+pub fn list_item_factory() -> gtk::SignalListItemFactory {
+    label_cell_factory!("gpu-devices", i18n("GPU device"), label_formatter)
+}
+
+pub fn sorter(sort_flags: &Rc<SortFlags>) -> impl IsA<gtk::Sorter> {
+    let sort_flags = sort_flags.clone();
+    gtk::CustomSorter::new(move |lhs, rhs| {
+        compare_column_entries_by(lhs, rhs, &sort_flags, |lhs, rhs| {
+            lhs.gpu_devices().cmp(&rhs.gpu_devices())
+        })
+        .into()
+    })
+}
+
+pub fn label_formatter(label: &LabelCell, value: glib::Value) {
+    let gpu_devices: glib::GString = value.get().unwrap();
+    let labels = gpu_devices
+        .split(',')
+        .filter(|device| !device.is_empty())
+        .map(gpu_label)
+        .collect::<Vec<_>>();
+    label.set_long_text(&labels.join(", "));
+}
+
+/// Numbers the GPUs like the Performance page does, the name of the device is enough when there
+/// is only one. Returns whether the names changed.
+pub fn update_gpu_labels(gpus: &HashMap<String, Gpu>) -> bool {
+    let mut ids = gpus.keys().collect::<Vec<_>>();
+    ids.sort();
+
+    let labels = ids
+        .iter()
+        .enumerate()
+        .map(|(index, id)| {
+            let name = gpus[*id]
+                .device_name
+                .clone()
+                .unwrap_or_else(|| i18n("Unknown"));
+            let label = if ids.len() == 1 {
+                name
+            } else {
+                i18n_f("GPU {}: {}", &[&index.to_string(), &name])
+            };
+            ((*id).clone(), label)
+        })
+        .collect::<HashMap<_, _>>();
+
+    GPU_LABELS.with(|gpu_labels| {
+        let mut gpu_labels = gpu_labels.borrow_mut();
+        if *gpu_labels == labels {
+            return false;
+        }
+        *gpu_labels = labels;
+        true
+    })
+}
+
+/// The name GPU `id` is shown with, its PCI address if Magpie does not know about it
+pub fn gpu_label(id: &str) -> String {
+    GPU_LABELS.with(|gpu_labels| {
+        gpu_labels
+            .borrow()
+            .get(id)
+            .cloned()
+            .unwrap_or_else(|| id.to_owned())
+    })
+}
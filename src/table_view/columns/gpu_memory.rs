@@ -19,25 +19,27 @@
  */
 
 use std::cmp::Ordering;
+use std::rc::Rc;
 
 use gtk::prelude::*;
 
 pub use super::memory_label_formatter as label_formatter;
-use super::{compare_column_entries_by, sort_order, LabelCell};
+use super::{compare_column_entries_by, LabelCell, SortFlags};
+use crate::i18n::i18n;
 use crate::label_cell_factory;
 
 pub fn list_item_factory() -> gtk::SignalListItemFactory {
-    label_cell_factory!("gpu-memory-usage", label_formatter)
+    label_cell_factory!(
+        "gpu-memory-usage",
+        i18n("GPU memory usage"),
+        label_formatter
+    )
 }
 
-pub fn sorter(column_view: &gtk::ColumnView) -> impl IsA<gtk::Sorter> {
-    let column_view = column_view.downgrade();
+pub fn sorter(sort_flags: &Rc<SortFlags>) -> impl IsA<gtk::Sorter> {
+    let sort_flags = sort_flags.clone();
     gtk::CustomSorter::new(move |lhs, rhs| {
-        let Some(column_view) = column_view.upgrade() else {
-            return Ordering::Equal.into();
-        };
-
-        compare_column_entries_by(lhs, rhs, sort_order(&column_view), |lhs, rhs| {
+        compare_column_entries_by(lhs, rhs, &sort_flags, |lhs, rhs| {
             let lhs = lhs.gpu_memory_usage();
             let rhs = rhs.gpu_memory_usage();
 
@@ -0,0 +1,147 @@
+/* table_view/columns/heat_map.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::sync::OnceLock;
+
+// Rates at or above which network and disk cells reach the next heat level
+const RATE_THRESHOLDS: [f32; 4] = [
+    100. * 1024.,
+    1024. * 1024.,
+    10. * 1024. * 1024.,
+    100. * 1024. * 1024.,
+];
+
+/// How strongly a usage cell is tinted when the heat map is enabled. The color itself depends
+/// on the palette the `TableView` uses, the level only selects how intense it is.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum HeatLevel {
+    #[default]
+    None,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl HeatLevel {
+    pub const CSS_CLASSES: [&'static str; 4] =
+        ["heat-low", "heat-medium", "heat-high", "heat-critical"];
+
+    /// `fraction` is the share of the available resource that is in use, from 0 to 1
+    pub fn from_fraction(fraction: f32) -> Self {
+        match fraction {
+            f if f >= 0.75 => HeatLevel::Critical,
+            f if f >= 0.5 => HeatLevel::High,
+            f if f >= 0.25 => HeatLevel::Medium,
+            f if f >= 0.05 => HeatLevel::Low,
+            _ => HeatLevel::None,
+        }
+    }
+
+    /// Disks and network links have no meaningful maximum, so rates are bucketed by order of
+    /// magnitude instead
+    pub fn from_rate(bytes_per_second: f32) -> Self {
+        let level = RATE_THRESHOLDS
+            .iter()
+            .take_while(|threshold| bytes_per_second >= **threshold)
+            .count();
+
+        match level {
+            0 => HeatLevel::None,
+            1 => HeatLevel::Low,
+            2 => HeatLevel::Medium,
+            3 => HeatLevel::High,
+            _ => HeatLevel::Critical,
+        }
+    }
+
+    pub fn from_memory(bytes: u64) -> Self {
+        match memory_total() {
+            0 => HeatLevel::None,
+            total => Self::from_fraction(bytes as f32 / total as f32),
+        }
+    }
+
+    pub fn css_class(&self) -> Option<&'static str> {
+        match self {
+            HeatLevel::None => None,
+            HeatLevel::Low => Some(Self::CSS_CLASSES[0]),
+            HeatLevel::Medium => Some(Self::CSS_CLASSES[1]),
+            HeatLevel::High => Some(Self::CSS_CLASSES[2]),
+            HeatLevel::Critical => Some(Self::CSS_CLASSES[3]),
+        }
+    }
+}
+
+/// The palettes the heat map can be drawn with, stored as `apps-page-heat-map`
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum HeatMapPalette {
+    Off = 0,
+    Warm = 1,
+    /// Shades of a single blue, which stay distinguishable with all common forms of color
+    /// blindness
+    Blue = 2,
+}
+
+impl HeatMapPalette {
+    pub const CSS_CLASSES: [&'static str; 2] = ["heat-map-warm", "heat-map-blue"];
+
+    pub fn from_setting(value: i32) -> Self {
+        match value {
+            1 => HeatMapPalette::Warm,
+            2 => HeatMapPalette::Blue,
+            _ => HeatMapPalette::Off,
+        }
+    }
+
+    pub fn css_class(&self) -> Option<&'static str> {
+        match self {
+            HeatMapPalette::Off => None,
+            HeatMapPalette::Warm => Some(Self::CSS_CLASSES[0]),
+            HeatMapPalette::Blue => Some(Self::CSS_CLASSES[1]),
+        }
+    }
+}
+
+// The total amount of memory does not change while the app is running, `/proc/meminfo` is
+// also accurate inside a sandbox
+fn memory_total() -> u64 {
+    static MEMORY_TOTAL: OnceLock<u64> = OnceLock::new();
+
+    *MEMORY_TOTAL.get_or_init(|| {
+        let Ok(meminfo) = std::fs::read_to_string("/proc/meminfo") else {
+            return 0;
+        };
+
+        meminfo
+            .lines()
+            .find_map(|line| line.strip_prefix("MemTotal:"))
+            .and_then(|value| {
+                value
+                    .trim()
+                    .trim_end_matches("kB")
+                    .trim()
+                    .parse::<u64>()
+                    .ok()
+            })
+            .map(|kib| kib * 1024)
+            .unwrap_or(0)
+    })
+}
@@ -18,11 +18,13 @@
  * SPDX-License-Identifier: GPL-3.0-or-later
  */
 
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 
 use adw::subclass::prelude::*;
 use gtk::{glib, prelude::*};
 
+use super::HeatLevel;
+use crate::i18n::{i18n, i18n_f};
 use crate::table_view::row_model::RowModel;
 
 mod imp {
@@ -31,6 +33,10 @@ mod imp {
 
     pub struct LabelCell {
         pub label: gtk::Label,
+        pub heat_level: Cell<HeatLevel>,
+        // What the value is and whose it is, read out by screen readers along with the value
+        pub value_description: RefCell<String>,
+        pub row_name: RefCell<String>,
 
         sig_handler: Cell<Option<glib::SignalHandlerId>>,
         model: Cell<glib::WeakRef<RowModel>>,
@@ -40,6 +46,9 @@ mod imp {
         fn default() -> Self {
             Self {
                 label: gtk::Label::new(None),
+                heat_level: Cell::new(HeatLevel::None),
+                value_description: RefCell::new(String::new()),
+                row_name: RefCell::new(String::new()),
 
                 sig_handler: Cell::new(None),
                 model: Cell::new(glib::WeakRef::default()),
@@ -70,6 +79,14 @@ mod imp {
             self.sig_handler.set(Some(sig_handler));
         }
 
+        pub fn model(&self) -> Option<RowModel> {
+            let model = self.model.take();
+            let result = model.upgrade();
+            self.model.set(model);
+
+            result
+        }
+
         pub fn unbind(&self) {
             let Some(model) = self.model.take().upgrade() else {
                 return;
@@ -124,8 +141,97 @@ impl LabelCell {
         glib::Object::builder().build()
     }
 
+    pub fn label(&self) -> glib::GString {
+        self.imp().label.label()
+    }
+
     pub fn set_label(&self, label: &str) {
         self.imp().label.set_label(label);
+        self.update_accessible_label();
+    }
+
+    /// Names the value for screen readers, e.g. "CPU usage". Cells without a description are read
+    /// out as they are shown.
+    pub fn set_value_description(&self, description: &str) {
+        self.imp().value_description.replace(description.to_owned());
+        self.update_accessible_label();
+    }
+
+    /// The entry the value belongs to, e.g. the name of the app
+    pub fn set_row_name(&self, row_name: &str) {
+        self.imp().row_name.replace(row_name.to_owned());
+        self.update_accessible_label();
+    }
+
+    /// Shows text that is likely to be ellipsized, the full text is shown as a tooltip
+    pub fn set_long_text(&self, text: &str) {
+        let label = &self.imp().label;
+        label.set_halign(gtk::Align::Start);
+        label.set_label(text);
+        label.set_tooltip_text(if text.is_empty() { None } else { Some(text) });
+        self.update_accessible_label();
+    }
+
+    /// Explains the value in a tooltip, cleared again by `set_restricted`
+    pub fn set_hint(&self, hint: Option<&str>) {
+        self.imp().label.set_tooltip_text(hint);
+    }
+
+    /// The row the cell is bound to
+    pub fn model(&self) -> Option<RowModel> {
+        self.imp().model()
+    }
+
+    /// Shows that the value could not be read, instead of showing a misleading zero
+    pub fn set_restricted(&self, restricted: bool) {
+        let label = &self.imp().label;
+        if restricted {
+            label.set_label(&i18n("Restricted"));
+            label.add_css_class("dim-label");
+            label.set_tooltip_text(Some(&i18n(
+                "This value can only be read by the owner of the process or an administrator",
+            )));
+        } else {
+            label.remove_css_class("dim-label");
+            label.set_tooltip_text(None);
+        }
+        self.update_accessible_label();
+    }
+
+    /// Tints the cell, the tint is only visible if the heat map is enabled for the table
+    pub fn set_heat_level(&self, heat_level: HeatLevel) {
+        let previous = self.imp().heat_level.replace(heat_level);
+        if previous == heat_level {
+            return;
+        }
+
+        if let Some(css_class) = previous.css_class() {
+            self.remove_css_class(css_class);
+        }
+        if let Some(css_class) = heat_level.css_class() {
+            self.add_css_class(css_class);
+        }
+    }
+
+    // Reads out as "CPU usage 43% for Firefox" instead of just the number
+    fn update_accessible_label(&self) {
+        let imp = self.imp();
+
+        let description = imp.value_description.borrow();
+        let value = imp.label.label();
+        if description.is_empty() || value.is_empty() {
+            imp.label.reset_property(gtk::AccessibleProperty::Label);
+            return;
+        }
+
+        let row_name = imp.row_name.borrow();
+        let accessible_label = if row_name.is_empty() {
+            format!("{description} {value}")
+        } else {
+            i18n_f("{} {} for {}", &[&description, &value, &row_name])
+        };
+        imp.label
+            .update_property(&[gtk::accessible::Property::Label(&accessible_label)]);
     }
 
     pub fn bind(
@@ -19,25 +19,23 @@
  */
 
 use std::cmp::Ordering;
+use std::rc::Rc;
 
 use gtk::glib;
 use gtk::prelude::*;
 
-use super::{compare_column_entries_by, sort_order, LabelCell};
+use super::{compare_column_entries_by, HeatLevel, LabelCell, SortFlags};
+use crate::i18n::i18n;
 use crate::{label_cell_factory, DataType};
 
 pub fn list_item_factory() -> gtk::SignalListItemFactory {
-    label_cell_factory!("memory-usage", label_formatter)
+    label_cell_factory!("memory-usage", i18n("Memory usage"), cell_formatter)
 }
 
-pub fn sorter(column_view: &gtk::ColumnView) -> impl IsA<gtk::Sorter> {
-    let column_view = column_view.downgrade();
+pub fn sorter(sort_flags: &Rc<SortFlags>) -> impl IsA<gtk::Sorter> {
+    let sort_flags = sort_flags.clone();
     gtk::CustomSorter::new(move |lhs, rhs| {
-        let Some(column_view) = column_view.upgrade() else {
-            return Ordering::Equal.into();
-        };
-
-        compare_column_entries_by(lhs, rhs, sort_order(&column_view), |lhs, rhs| {
+        compare_column_entries_by(lhs, rhs, &sort_flags, |lhs, rhs| {
             let lhs = lhs.memory_usage();
             let rhs = rhs.memory_usage();
 
@@ -47,6 +45,12 @@ pub fn sorter(column_view: &gtk::ColumnView) -> impl IsA<gtk::Sorter> {
     })
 }
 
+fn cell_formatter(label: &LabelCell, value: glib::Value) {
+    let memory_usage: u64 = value.get().unwrap();
+    label.set_heat_level(HeatLevel::from_memory(memory_usage));
+    label_formatter(label, value);
+}
+
 pub fn label_formatter(label: &LabelCell, value: glib::Value) {
     let memory_usage: u64 = value.get().unwrap();
     label.set_label(
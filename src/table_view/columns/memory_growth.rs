@@ -0,0 +1,67 @@
+/* table_view/columns/memory_growth.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use gtk::glib;
+use gtk::prelude::*;
+
+use super::{compare_column_entries_by, LabelCell, SortFlags};
+use crate::i18n::{i18n, i18n_f};
+use crate::{label_cell_factory, DataType};
+
+pub fn list_item_factory() -> gtk::SignalListItemFactory {
+    label_cell_factory!(
+        "memory-growth",
+        i18n("Memory growth per minute"),
+        label_formatter
+    )
+}
+
+pub fn sorter(sort_flags: &Rc<SortFlags>) -> impl IsA<gtk::Sorter> {
+    let sort_flags = sort_flags.clone();
+    gtk::CustomSorter::new(move |lhs, rhs| {
+        compare_column_entries_by(lhs, rhs, &sort_flags, |lhs, rhs| {
+            let lhs = lhs.memory_growth();
+            let rhs = rhs.memory_growth();
+
+            lhs.partial_cmp(&rhs).unwrap_or(Ordering::Equal)
+        })
+        .into()
+    })
+}
+
+// Rows whose memory usage did not only go up lately are left blank
+pub fn label_formatter(label: &LabelCell, value: glib::Value) {
+    let memory_growth: f32 = value.get().unwrap();
+    if memory_growth <= 0. {
+        label.set_label("");
+        return;
+    }
+
+    label.set_label(&i18n_f(
+        "{}/min",
+        &[&crate::to_human_readable_nice(
+            memory_growth,
+            &DataType::MemoryBytes,
+        )],
+    ));
+}
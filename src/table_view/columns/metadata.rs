@@ -0,0 +1,134 @@
+/* table_view/columns/metadata.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::rc::Rc;
+
+use gtk::glib;
+use gtk::prelude::*;
+
+use super::CpuUsageMode;
+use crate::i18n::i18n;
+
+/// What the values of a built-in column mean, shown as the tooltip of its header. The extra
+/// columns read from `/proc` have none.
+pub fn description(column_id: &str, cpu_usage_mode: CpuUsageMode) -> Option<String> {
+    let description = match column_id {
+        "name" => i18n(
+            "Apps add up the usage of all of their processes, services the one of all processes in their cgroup",
+        ),
+        "pid" => i18n("The process ID, for services the one of their main process"),
+        "command_line" => i18n("The program and the arguments the process was started with"),
+        "cpu" => match cpu_usage_mode {
+            CpuUsageMode::Normalized => i18n(
+                "Share of all CPU cores together, at most 100%. Shown per core instead when “Core count affects percentages” is enabled.",
+            ),
+            CpuUsageMode::PerCore(_) => i18n(
+                "Usage of a single CPU core, so processes using several cores go above 100%. Shown as a share of all cores when “Core count affects percentages” is disabled.",
+            ),
+        },
+        "cpu_time" => i18n(
+            "CPU time used since the process started, in hours, minutes and seconds. Reveals processes that used a lot over time even while they are idle now.",
+        ),
+        "context_switches" => i18n(
+            "How often per second the process stopped running to let another one run, by waiting or by being preempted",
+        ),
+        "memory" => i18n(
+            "Memory only the process uses, without the shared memory it has in common with other processes",
+        ),
+        "memory_growth" => i18n(
+            "How fast the memory usage went up over the last few minutes, only shown while it keeps going up",
+        ),
+        "shared_memory" => i18n(
+            "Memory the process shares with others, like libraries. It counts for every process that maps it, so it doesn't add up.",
+        ),
+        "swap" => i18n("Memory of the process that was moved out to swap space"),
+        "major_faults" => i18n(
+            "How often per second the process touched memory that had to be read back from disk first. Many of them point to memory pressure.",
+        ),
+        "state" => i18n("Whether the process is running, sleeping, stopped or waiting for I/O"),
+        "wait_channel" => i18n(
+            "The kernel function the process sleeps in, and the file it is blocked on while it waits for I/O",
+        ),
+        "unit_origin" => i18n("Where the unit file of the service comes from"),
+        "start_time" => i18n(
+            "How long the last start of the service took, until systemd considered it active",
+        ),
+        "restarts" => i18n(
+            "How often systemd restarted the service since boot, after it failed or exited. Restarts asked for by hand don't count.",
+        ),
+        "drive" => i18n("How fast the process reads from and writes to drives, together"),
+        "io_wait" => i18n(
+            "Share of the time the process was blocked waiting for drives to read or write. Only known when the kernel has delay accounting enabled.",
+        ),
+        "network" => i18n(
+            "How fast the process sends and receives over all network interfaces, together",
+        ),
+        "gpu" => i18n("Share of the GPU the process keeps busy"),
+        "gpu_memory" => i18n("Video memory the process allocated on the GPU"),
+        "gpu_device" => i18n("The GPUs the process has open"),
+        _ => return None,
+    };
+
+    Some(description)
+}
+
+/// Gives the headers of the columns the description of their values as a tooltip. Needs to run
+/// again when columns are added, removed or moved, the headers follow the order of the columns.
+pub fn update_header_tooltips(column_view: &gtk::ColumnView, cpu_usage_mode: CpuUsageMode) {
+    let columns = column_view.columns();
+    let mut header = column_view.first_child().and_then(|titlebar| titlebar.first_child());
+
+    for column in (0..columns.n_items())
+        .filter_map(|i| columns.item(i))
+        .filter_map(|item| item.downcast::<gtk::ColumnViewColumn>().ok())
+    {
+        let Some(title) = header.take() else {
+            break;
+        };
+        header = title.next_sibling();
+
+        let description = column.id().and_then(|id| description(&id, cpu_usage_mode));
+        title.set_tooltip_text(description.as_deref());
+    }
+}
+
+/// Updates the tooltips whenever the columns change, once GTK created their headers
+pub fn connect_header_tooltips(
+    column_view: &gtk::ColumnView,
+    cpu_usage_mode: impl Fn() -> CpuUsageMode + 'static,
+) {
+    let cpu_usage_mode = Rc::new(cpu_usage_mode);
+    let update = {
+        let column_view = column_view.downgrade();
+        move || {
+            let (column_view, cpu_usage_mode) = (column_view.clone(), cpu_usage_mode.clone());
+            glib::idle_add_local_once(move || {
+                if let Some(column_view) = column_view.upgrade() {
+                    update_header_tooltips(&column_view, cpu_usage_mode());
+                }
+            });
+        }
+    };
+
+    update();
+    column_view
+        .columns()
+        .connect_items_changed(move |_, _, _, _| update());
+}
@@ -19,60 +19,135 @@
  */
 use gtk::glib;
 use gtk::prelude::*;
+use std::cell::Cell;
 use std::cmp::Ordering;
 
 use crate::i18n::i18n;
 use crate::table_view::row_model::RowModel;
 
+pub use command_line::label_formatter as command_line_label_formatter;
+pub use command_line::list_item_factory as command_line_list_item_factory;
+pub use command_line::sorter as command_line_sorter;
+pub use context_switches::label_formatter as context_switches_label_formatter;
+pub use context_switches::list_item_factory as context_switches_list_item_factory;
+pub use context_switches::sorter as context_switches_sorter;
 pub use cpu::label_formatter as cpu_label_formatter;
 pub use cpu::list_item_factory as cpu_list_item_factory;
 pub use cpu::sorter as cpu_sorter;
+pub use cpu::CpuUsageMode;
+pub use cpu_time::label_formatter as cpu_time_label_formatter;
+pub use cpu_time::list_item_factory as cpu_time_list_item_factory;
+pub use cpu_time::sorter as cpu_time_sorter;
+pub use custom::column as custom_column;
+pub use custom::COLUMN_ID_PREFIX as CUSTOM_COLUMN_ID_PREFIX;
 pub use drive::label_formatter as drive_label_formatter;
 pub use drive::list_item_factory as drive_list_item_factory;
 pub use drive::sorter as drive_sorter;
 pub use gpu::label_formatter as gpu_label_formatter;
 pub use gpu::list_item_factory as gpu_list_item_factory;
 pub use gpu::sorter as gpu_sorter;
+pub use gpu_device::label_formatter as gpu_device_label_formatter;
+pub use gpu_device::list_item_factory as gpu_device_list_item_factory;
+pub use gpu_device::sorter as gpu_device_sorter;
+pub use gpu_device::{gpu_label, update_gpu_labels};
 pub use gpu_memory::label_formatter as gpu_memory_label_formatter;
 pub use gpu_memory::list_item_factory as gpu_memory_list_item_factory;
 pub use gpu_memory::sorter as gpu_memory_sorter;
+pub use heat_map::{HeatLevel, HeatMapPalette};
+pub use io_wait::label_formatter as io_wait_label_formatter;
+pub use io_wait::list_item_factory as io_wait_list_item_factory;
+pub use io_wait::sorter as io_wait_sorter;
 pub use label_cell::LabelCell;
+pub use major_faults::label_formatter as major_faults_label_formatter;
+pub use major_faults::list_item_factory as major_faults_list_item_factory;
+pub use major_faults::sorter as major_faults_sorter;
 pub use memory::label_formatter as memory_label_formatter;
 pub use memory::list_item_factory as memory_list_item_factory;
 pub use memory::sorter as memory_sorter;
+pub use memory_growth::label_formatter as memory_growth_label_formatter;
+pub use memory_growth::list_item_factory as memory_growth_list_item_factory;
+pub use memory_growth::sorter as memory_growth_sorter;
+pub use metadata::{connect_header_tooltips, update_header_tooltips};
 pub use name::list_item_factory as name_list_item_factory;
 pub use name::sorter as name_sorter;
 pub use name_cell::NameCell;
 pub use network::label_formatter as network_label_formatter;
 pub use network::list_item_factory as network_list_item_factory;
 pub use network::sorter as network_sorter;
+pub use pid::label_formatter as pid_label_formatter;
 pub use pid::list_item_factory as pid_list_item_factory;
 pub use pid::sorter as pid_sorter;
+pub use restarts::label_formatter as restarts_label_formatter;
+pub use restarts::list_item_factory as restarts_list_item_factory;
+pub use restarts::sorter as restarts_sorter;
 pub use shared_memory::label_formatter as shared_memory_label_formatter;
 pub use shared_memory::list_item_factory as shared_memory_list_item_factory;
 pub use shared_memory::sorter as shared_memory_sorter;
-
+pub use start_time::format as format_start_time;
+pub use start_time::label_formatter as start_time_label_formatter;
+pub use start_time::list_item_factory as start_time_list_item_factory;
+pub use start_time::sorter as start_time_sorter;
+pub use state::list_item_factory as state_list_item_factory;
+pub use state::sorter as state_sorter;
+pub use state::PROBLEM_STATE_CSS_CLASS;
+pub use swap::label_formatter as swap_label_formatter;
+pub use swap::list_item_factory as swap_list_item_factory;
+pub use swap::sorter as swap_sorter;
+pub use unit_origin::label_formatter as unit_origin_label_formatter;
+pub use unit_origin::list_item_factory as unit_origin_list_item_factory;
+pub use unit_origin::sorter as unit_origin_sorter;
+pub use wait_channel::label_formatter as wait_channel_label_formatter;
+pub use wait_channel::list_item_factory as wait_channel_list_item_factory;
+pub use wait_channel::sorter as wait_channel_sorter;
+
+mod command_line;
+mod context_switches;
 mod cpu;
+mod cpu_time;
+mod custom;
 mod drive;
 mod gpu;
+mod gpu_device;
 mod gpu_memory;
+mod heat_map;
+mod io_wait;
 mod label_cell;
+mod major_faults;
 mod memory;
+mod memory_growth;
+mod metadata;
 mod name;
 mod name_cell;
 mod network;
 mod pid;
+mod restarts;
 mod shared_memory;
+mod start_time;
+mod state;
+mod swap;
+mod unit_origin;
+mod wait_channel;
 
 #[macro_export]
 macro_rules! label_cell_factory {
-    ($property: literal, $setter: expr) => {{
-        label_cell_factory!($property, ContentType::SectionHeader, $setter)
+    ($property: literal, $description: expr, $setter: expr) => {{
+        label_cell_factory!($property, $description, ContentType::SectionHeader, $setter)
+    }};
+
+    ($property: literal, $description: expr, $skip_content: pat, $setter: expr) => {{
+        label_cell_factory!(
+            $property,
+            $description,
+            $skip_content,
+            $setter,
+            |_: &RowModel| false
+        )
     }};
 
-    ($property: literal, $skip_content: pat, $setter: expr) => {{
+    ($property: literal, $description: expr, $skip_content: pat, $setter: expr, $restricted: expr) => {{
         use gtk::prelude::*;
 
+        use crate::table_view::columns::HeatLevel;
         use crate::table_view::row_model::{ContentType, RowModel};
 
         let factory = gtk::SignalListItemFactory::new();
@@ -83,13 +158,15 @@ macro_rules! label_cell_factory {
             };
 
             let label = LabelCell::new();
+            label.set_value_description(&$description);
             let expander = gtk::TreeExpander::new();
             expander.set_child(Some(&label));
 
             expander.set_hide_expander(true);
             expander.set_indent_for_icon(false);
             expander.set_indent_for_depth(false);
-            expander.set_halign(gtk::Align::End);
+            // Fill the cell so that the heat map tint covers all of it
+            expander.set_halign(gtk::Align::Fill);
 
             list_item.set_child(Some(&expander));
 
@@ -133,18 +210,30 @@ macro_rules! label_cell_factory {
                     .as_ref()
             };
 
+            label.set_row_name(&model.name());
+
             match model.content_type() {
                 $skip_content => {
+                    label.set_restricted(false);
+                    label.set_heat_level(HeatLevel::None);
                     label.set_label("");
                     return;
                 }
                 _ => {}
             }
 
-            let value = model.property_value($property);
-            ($setter)(&label, value);
+            if ($restricted)(&model) {
+                label.set_restricted(true);
+                label.set_heat_level(HeatLevel::None);
+                return;
+            }
+            label.set_restricted(false);
 
+            // Bound first, so that the setter can already look at the model
             label.bind(&model, $property, $setter);
+
+            let value = model.property_value($property);
+            ($setter)(&label, value);
         });
 
         factory.connect_unbind(|_, list_item| {
@@ -246,11 +335,34 @@ fn convert_order(sort_order: gtk::SortType, ordering: Ordering) -> Ordering {
     }
 }
 
+/// What the column sorters need to know about the table, kept up to date by the table so that
+/// it isn't looked up for every comparison
+pub struct SortFlags {
+    pub stable_sorting: Cell<bool>,
+    pub sort_order: Cell<gtk::SortType>,
+}
+
+impl Default for SortFlags {
+    fn default() -> Self {
+        Self {
+            stable_sorting: Cell::new(true),
+            sort_order: Cell::new(gtk::SortType::Ascending),
+        }
+    }
+}
+
+/// Compares names regardless of case, without making lowercase copies of them
+pub fn compare_names(lhs: &str, rhs: &str) -> Ordering {
+    lhs.chars()
+        .flat_map(char::to_lowercase)
+        .cmp(rhs.chars().flat_map(char::to_lowercase))
+}
+
 fn compare_column_entries_by(
     lhs: &glib::Object,
     rhs: &glib::Object,
-    sort_order: gtk::SortType,
-    compare_fn: fn(&RowModel, &RowModel) -> Ordering,
+    sort_flags: &SortFlags,
+    compare_fn: impl Fn(&RowModel, &RowModel) -> Ordering,
 ) -> Ordering {
     let Some(lhs) = lhs.downcast_ref::<RowModel>() else {
         return Ordering::Equal.into();
@@ -260,23 +372,17 @@ fn compare_column_entries_by(
         return Ordering::Equal.into();
     };
 
-    match lhs.section_type().cmp(&rhs.section_type()) {
-        Ordering::Equal => {
-            // continue
+    // Sections are ordered by the row sorter of the table before the columns are asked, see
+    // `sections::compare_sections`
+    match compare_fn(lhs, rhs) {
+        Ordering::Equal if sort_flags.stable_sorting.get() => {
+            // Break ties by name and then by PID, always in ascending order, so that entries
+            // with equal values don't swap places on every update
+            let order = compare_names(&lhs.name(), &rhs.name())
+                .then_with(|| lhs.pid().cmp(&rhs.pid()))
+                .then_with(|| lhs.id().cmp(&rhs.id()));
+            convert_order(sort_flags.sort_order.get(), order)
         }
-        order => return convert_order(sort_order, order),
-    }
-
-    match lhs.content_type().cmp(&rhs.content_type()) {
-        Ordering::Equal => compare_fn(lhs, rhs),
-        order => convert_order(sort_order, order),
+        order => order,
     }
 }
-
-fn sort_order(column_view: &gtk::ColumnView) -> gtk::SortType {
-    column_view
-        .sorter()
-        .and_downcast_ref::<gtk::ColumnViewSorter>()
-        .and_then(|sorter| Some(sorter.primary_sort_order()))
-        .unwrap_or(gtk::SortType::Ascending)
-}
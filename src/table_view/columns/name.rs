@@ -18,23 +18,31 @@
  * SPDX-License-Identifier: GPL-3.0-or-later
  */
 
-use std::cmp::Ordering;
+use std::rc::Rc;
 
 use adw::prelude::*;
+use gtk::{gdk, glib};
 
-use crate::table_view::columns::{compare_column_entries_by, sort_order, NameCell};
-use crate::table_view::row_model::RowModel;
+use crate::magpie_client::KERNEL_THREADS_PID;
+use crate::table_view::columns::{compare_column_entries_by, compare_names, NameCell, SortFlags};
+use crate::table_view::row_model::{ContentType, RowModel};
+use crate::table_view::TableView;
 use crate::widgets::ListCell;
 
-pub fn list_item_factory() -> gtk::SignalListItemFactory {
+pub fn list_item_factory(table_view: &TableView) -> gtk::SignalListItemFactory {
     let factory = gtk::SignalListItemFactory::new();
 
-    factory.connect_setup(|_, list_item| {
+    // The factory is recreated when the density or the layout of the table changes
+    let show_icon = !table_view.compact();
+    let details_mode = table_view.narrow().then(|| table_view.cpu_usage_mode());
+    factory.connect_setup(move |_, list_item| {
         let Some(list_item) = list_item.downcast_ref::<gtk::ListItem>() else {
             return;
         };
 
         let name_cell = NameCell::new();
+        name_cell.set_show_icon(show_icon);
+        name_cell.set_details_mode(details_mode);
 
         let list_cell = ListCell::new("column-view.show-context-menu");
         list_cell.set_is_tree_view(true);
@@ -47,7 +55,34 @@ pub fn list_item_factory() -> gtk::SignalListItemFactory {
         expander.set_indent_for_icon(true);
         expander.set_indent_for_depth(true);
         expander.set_halign(gtk::Align::Start);
-        expander.set_width_request(218);
+        if details_mode.is_none() {
+            expander.set_width_request(218);
+        }
+
+        // Rows can be dragged into terminals and text editors
+        let drag_source = gtk::DragSource::new();
+        drag_source.set_actions(gdk::DragAction::COPY);
+        drag_source.connect_prepare({
+            let expander = expander.downgrade();
+            move |_, _, _| {
+                let model = expander
+                    .upgrade()?
+                    .item()
+                    .and_then(|item| item.downcast::<RowModel>().ok())?;
+                let text = drag_text(&model)?;
+                Some(gdk::ContentProvider::for_value(&text.to_value()))
+            }
+        });
+        drag_source.connect_drag_begin({
+            let name_cell = name_cell.downgrade();
+            move |drag_source, _| {
+                if let Some(name_cell) = name_cell.upgrade() {
+                    let icon = gtk::WidgetPaintable::new(Some(&name_cell));
+                    drag_source.set_icon(Some(&icon), 0, 0);
+                }
+            }
+        });
+        expander.add_controller(drag_source);
 
         list_item.set_child(Some(&expander));
 
@@ -58,7 +93,8 @@ pub fn list_item_factory() -> gtk::SignalListItemFactory {
         }
     });
 
-    factory.connect_bind(|_, list_item| {
+    let table_view = table_view.downgrade();
+    factory.connect_bind(move |_, list_item| {
         let Some(list_item) = list_item.downcast_ref::<gtk::ListItem>() else {
             return;
         };
@@ -100,6 +136,65 @@ pub fn list_item_factory() -> gtk::SignalListItemFactory {
         };
 
         name_cell.bind(&model, list_cell, expander);
+        name_cell.set_expanded(row.is_expanded());
+
+        // The tree model expands new rows, the row of the kernel threads is collapsed instead
+        // until it is expanded by hand, there are too many of them
+        if row.is_expanded()
+            && model.content_type() == ContentType::Process
+            && model.pid() == KERNEL_THREADS_PID
+            && !model.children_requested()
+        {
+            row.set_expanded(false);
+        }
+
+        // Rows are recreated collapsed when their parent is collapsed, so sync the state
+        // every time a row is shown
+        if let Some(table_view) = table_view.upgrade() {
+            if row.is_expanded() {
+                table_view.row_expanded(&model);
+            } else {
+                model.set_expanded(false);
+            }
+        }
+
+        let sig_expanded = row.connect_expanded_notify({
+            let table_view = table_view.clone();
+            let model = model.downgrade();
+            let name_cell = name_cell.downgrade();
+            move |row| {
+                let Some(model) = model.upgrade() else {
+                    return;
+                };
+
+                if let Some(name_cell) = name_cell.upgrade() {
+                    name_cell.set_expanded(row.is_expanded());
+                }
+
+                if !row.is_expanded() {
+                    model.set_expanded(false);
+                    return;
+                }
+
+                if let Some(table_view) = table_view.upgrade() {
+                    table_view.row_expanded(&model);
+                }
+            }
+        });
+        unsafe {
+            list_item.set_data("sig_expanded", sig_expanded);
+        }
+
+        // Top level entries lose their section header when sections are hidden, so show the
+        // section they belong to next to their name instead. Section headers keep the badge
+        // set when binding, for their summary.
+        if model.content_type() != ContentType::SectionHeader {
+            let badge = table_view
+                .upgrade()
+                .filter(|table_view| table_view.hide_sections() && row.depth() == 0)
+                .and_then(|table_view| table_view.section_name(model.section_type()));
+            name_cell.set_badge(badge.as_deref());
+        }
     });
 
     factory.connect_unbind(|_, list_item| {
@@ -113,6 +208,11 @@ pub fn list_item_factory() -> gtk::SignalListItemFactory {
                 .unwrap_unchecked()
                 .as_ref()
         };
+
+        let sig_expanded = unsafe { list_item.steal_data::<glib::SignalHandlerId>("sig_expanded") };
+        if let (Some(row), Some(sig_expanded)) = (expander.list_row(), sig_expanded) {
+            row.disconnect(sig_expanded);
+        }
         expander.set_list_row(None);
 
         let name_cell = unsafe {
@@ -139,16 +239,34 @@ pub fn list_item_factory() -> gtk::SignalListItemFactory {
     factory
 }
 
-pub fn sorter(column_view: &gtk::ColumnView) -> impl IsA<gtk::Sorter> {
-    let column_view = column_view.downgrade();
+pub fn sorter(sort_flags: &Rc<SortFlags>) -> impl IsA<gtk::Sorter> {
+    let sort_flags = sort_flags.clone();
     gtk::CustomSorter::new(move |lhs, rhs| {
-        let Some(column_view) = column_view.upgrade() else {
-            return Ordering::Equal.into();
-        };
-
-        compare_column_entries_by(lhs, rhs, sort_order(&column_view), |lhs, rhs| {
-            lhs.name().to_lowercase().cmp(&rhs.name().to_lowercase())
+        compare_column_entries_by(lhs, rhs, &sort_flags, |lhs, rhs| {
+            compare_names(&lhs.name(), &rhs.name())
         })
         .into()
     })
 }
+
+// The name, PID and command line of processes, or the name of apps. Section headers and
+// services are not dragged.
+fn drag_text(model: &RowModel) -> Option<String> {
+    match model.content_type() {
+        ContentType::Process => {
+            let command_line = model.command_line();
+            if model.command_line_restricted() || command_line.is_empty() {
+                Some(format!("{} (PID {})", model.name(), model.pid()))
+            } else {
+                Some(format!(
+                    "{} (PID {}): {}",
+                    model.name(),
+                    model.pid(),
+                    command_line
+                ))
+            }
+        }
+        ContentType::App => Some(model.name().to_string()),
+        ContentType::SectionHeader | ContentType::Service => None,
+    }
+}
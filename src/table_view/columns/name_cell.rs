@@ -25,8 +25,14 @@ use gdk::pango::EllipsizeMode;
 use glib::{g_critical, g_debug, FileError};
 use gtk::{gdk, glib, prelude::*, subclass::prelude::*};
 
+use crate::i18n::{i18n, i18n_f, ni18n_f};
+use crate::table_view::columns::{CpuUsageMode, PROBLEM_STATE_CSS_CLASS};
+use crate::table_view::highlight_rules;
 use crate::table_view::row_model::{ContentType, RowModel};
 use crate::widgets::ListCell;
+use crate::{settings, DataType};
+
+const RECENTLY_CHANGED_CSS_CLASS: &str = "recently-changed";
 
 mod icon_cache {
     use super::*;
@@ -59,13 +65,34 @@ mod imp {
 
     pub struct NameCell {
         icon: gtk::Image,
+        /// Takes the place of the icon while a service request is pending
+        pending: adw::Spinner,
         name: gtk::Label,
+        details: gtk::Label,
+        badge: gtk::Label,
+        descendants: gtk::Label,
+        restricted: gtk::Image,
+        leak_warning: gtk::Image,
+        pub show_icon: Cell<bool>,
+        /// Set when the usage is shown below the name, in place of the other columns
+        pub details_mode: Cell<Option<CpuUsageMode>>,
+        expanded: Cell<bool>,
 
         sig_id: Cell<Option<glib::SignalHandlerId>>,
         sig_icon: Cell<Option<glib::SignalHandlerId>>,
         sig_name: Cell<Option<glib::SignalHandlerId>>,
+        sig_summary: Cell<Option<glib::SignalHandlerId>>,
         sig_content_type: Cell<Option<glib::SignalHandlerId>>,
+        sig_process_state: Cell<Option<glib::SignalHandlerId>>,
+        sig_service_pending: Cell<Option<glib::SignalHandlerId>>,
+        sig_recently_changed: Cell<Option<glib::SignalHandlerId>>,
+        sig_memory_leak_suspected: Cell<Option<glib::SignalHandlerId>>,
         sig_children_changed: Cell<Option<glib::SignalHandlerId>>,
+        sig_has_children: Cell<Option<glib::SignalHandlerId>>,
+        sig_descendant_count: Cell<Option<glib::SignalHandlerId>>,
+        sig_cpu_usage: Cell<Option<glib::SignalHandlerId>>,
+        sig_memory_usage: Cell<Option<glib::SignalHandlerId>>,
+        sig_highlight_rules: Cell<Option<glib::SignalHandlerId>>,
 
         model: Cell<glib::WeakRef<RowModel>>,
         expander: RefCell<glib::WeakRef<gtk::TreeExpander>>,
@@ -75,13 +102,32 @@ mod imp {
         fn default() -> Self {
             Self {
                 icon: gtk::Image::new(),
+                pending: adw::Spinner::new(),
                 name: gtk::Label::new(None),
+                details: gtk::Label::new(None),
+                badge: gtk::Label::new(None),
+                descendants: gtk::Label::new(None),
+                restricted: gtk::Image::from_icon_name("changes-prevent-symbolic"),
+                leak_warning: gtk::Image::from_icon_name("dialog-warning-symbolic"),
+                show_icon: Cell::new(true),
+                details_mode: Cell::new(None),
+                expanded: Cell::new(false),
 
                 sig_id: Cell::new(None),
                 sig_icon: Cell::new(None),
                 sig_name: Cell::new(None),
+                sig_summary: Cell::new(None),
                 sig_content_type: Cell::new(None),
+                sig_process_state: Cell::new(None),
+                sig_service_pending: Cell::new(None),
+                sig_recently_changed: Cell::new(None),
+                sig_memory_leak_suspected: Cell::new(None),
                 sig_children_changed: Cell::new(None),
+                sig_has_children: Cell::new(None),
+                sig_descendant_count: Cell::new(None),
+                sig_cpu_usage: Cell::new(None),
+                sig_memory_usage: Cell::new(None),
+                sig_highlight_rules: Cell::new(None),
 
                 model: Cell::new(glib::WeakRef::default()),
                 expander: RefCell::new(glib::WeakRef::default()),
@@ -129,10 +175,27 @@ mod imp {
                     };
                     let this = this.imp();
                     this.name.set_label(&model.name());
+                    this.update_highlight(model);
                 }
             });
             self.sig_name.set(Some(sig_name));
             self.name.set_label(&model.name());
+            self.update_highlight(model);
+
+            // Section headers show their summary where entries show their section
+            if model.content_type() == ContentType::SectionHeader {
+                let sig_summary = model.connect_summary_notify({
+                    let this = this.clone();
+                    move |model| {
+                        let Some(this) = this.upgrade() else {
+                            return;
+                        };
+                        this.imp().set_summary(&model.summary());
+                    }
+                });
+                self.sig_summary.set(Some(sig_summary));
+                self.set_summary(&model.summary());
+            }
 
             let sig_content_type = model.connect_content_type_notify({
                 let this = this.clone();
@@ -147,17 +210,126 @@ mod imp {
             self.sig_content_type.set(Some(sig_content_type));
             self.set_content_type(model.content_type());
 
+            let sig_service_pending = model.connect_service_pending_notify({
+                let this = this.clone();
+                move |model| {
+                    let Some(this) = this.upgrade() else {
+                        return;
+                    };
+                    this.imp().set_pending(model);
+                }
+            });
+            self.sig_service_pending.set(Some(sig_service_pending));
+            self.set_pending(model);
+
+            self.restricted
+                .set_visible(model.content_type() == ContentType::Process && model.is_restricted());
+
+            let sig_process_state = model.connect_process_state_notify({
+                let this = this.clone();
+                move |model| {
+                    let Some(this) = this.upgrade() else {
+                        return;
+                    };
+                    this.imp()
+                        .set_problem_state(model.process_state().is_problem());
+                }
+            });
+            self.sig_process_state.set(Some(sig_process_state));
+            self.set_problem_state(model.process_state().is_problem());
+
+            let sig_recently_changed = model.connect_recently_changed_notify({
+                let this = this.clone();
+                move |model| {
+                    let Some(this) = this.upgrade() else {
+                        return;
+                    };
+                    this.imp().set_recently_changed(model.recently_changed());
+                }
+            });
+            self.sig_recently_changed.set(Some(sig_recently_changed));
+            self.set_recently_changed(model.recently_changed());
+
+            let sig_memory_leak_suspected = model.connect_memory_leak_suspected_notify({
+                let this = this.clone();
+                move |model| {
+                    if let Some(this) = this.upgrade() {
+                        this.imp()
+                            .leak_warning
+                            .set_visible(model.memory_leak_suspected());
+                    }
+                }
+            });
+            self.sig_memory_leak_suspected
+                .set(Some(sig_memory_leak_suspected));
+            self.leak_warning.set_visible(model.memory_leak_suspected());
+
+            // Children of collapsed processes are only populated on demand
             let sig_children_changed = model.children().connect_items_changed({
                 let expander = expander.downgrade();
+                let model = model.downgrade();
                 move |children, _, _, _| {
-                    let Some(expander) = expander.upgrade() else {
+                    let (Some(expander), Some(model)) = (expander.upgrade(), model.upgrade())
+                    else {
                         return;
                     };
-                    expander.set_hide_expander(children.n_items() == 0);
+                    expander.set_hide_expander(children.n_items() == 0 && !model.has_children());
                 }
             });
             self.sig_children_changed.set(Some(sig_children_changed));
-            expander.set_hide_expander(model.children().n_items() == 0);
+
+            let sig_has_children = model.connect_has_children_notify({
+                let expander = expander.downgrade();
+                move |model| {
+                    let Some(expander) = expander.upgrade() else {
+                        return;
+                    };
+                    expander.set_hide_expander(
+                        model.children().n_items() == 0 && !model.has_children(),
+                    );
+                }
+            });
+            self.sig_has_children.set(Some(sig_has_children));
+
+            expander.set_hide_expander(model.children().n_items() == 0 && !model.has_children());
+
+            let sig_descendant_count = model.connect_descendant_count_notify({
+                let this = this.clone();
+                move |_| {
+                    if let Some(this) = this.upgrade() {
+                        this.imp().update_descendants();
+                    }
+                }
+            });
+            self.sig_descendant_count.set(Some(sig_descendant_count));
+            self.update_descendants();
+
+            let show_details = self.details_mode.get().is_some()
+                && model.content_type() != ContentType::SectionHeader;
+            self.details.set_visible(show_details);
+            if show_details {
+                let sig_cpu_usage = model.connect_cpu_usage_notify({
+                    let this = this.clone();
+                    move |model| {
+                        if let Some(this) = this.upgrade() {
+                            this.imp().update_details(model);
+                        }
+                    }
+                });
+                self.sig_cpu_usage.set(Some(sig_cpu_usage));
+
+                let sig_memory_usage = model.connect_memory_usage_notify({
+                    let this = this.clone();
+                    move |model| {
+                        if let Some(this) = this.upgrade() {
+                            this.imp().update_details(model);
+                        }
+                    }
+                });
+                self.sig_memory_usage.set(Some(sig_memory_usage));
+
+                self.update_details(model);
+            }
         }
 
         pub fn unbind(&self) {
@@ -178,13 +350,150 @@ mod imp {
                 model.disconnect(sig_id);
             }
 
+            if let Some(sig_id) = self.sig_summary.take() {
+                model.disconnect(sig_id);
+            }
+
             if let Some(sig_id) = self.sig_content_type.take() {
                 model.disconnect(sig_id);
             }
 
+            if let Some(sig_id) = self.sig_process_state.take() {
+                model.disconnect(sig_id);
+            }
+
+            if let Some(sig_id) = self.sig_service_pending.take() {
+                model.disconnect(sig_id);
+            }
+
+            if let Some(sig_id) = self.sig_recently_changed.take() {
+                model.disconnect(sig_id);
+            }
+
+            if let Some(sig_id) = self.sig_memory_leak_suspected.take() {
+                model.disconnect(sig_id);
+            }
+
             if let Some(sig_id) = self.sig_children_changed.take() {
                 model.children().disconnect(sig_id);
             }
+
+            if let Some(sig_id) = self.sig_has_children.take() {
+                model.disconnect(sig_id);
+            }
+
+            if let Some(sig_id) = self.sig_descendant_count.take() {
+                model.disconnect(sig_id);
+            }
+
+            if let Some(sig_id) = self.sig_cpu_usage.take() {
+                model.disconnect(sig_id);
+            }
+
+            if let Some(sig_id) = self.sig_memory_usage.take() {
+                model.disconnect(sig_id);
+            }
+        }
+
+        pub fn set_badge(&self, badge: Option<&str>) {
+            match badge {
+                Some(badge) => {
+                    self.badge.set_label(badge);
+                    self.badge.set_visible(true);
+                }
+                None => {
+                    self.badge.set_label("");
+                    self.badge.set_visible(false);
+                }
+            }
+        }
+
+        pub fn set_expanded(&self, expanded: bool) {
+            self.expanded.set(expanded);
+            self.update_descendants();
+        }
+
+        // Only collapsed rows hide processes, section headers show their summary instead
+        fn update_descendants(&self) {
+            let model = self.model.take();
+            let count = model
+                .upgrade()
+                .filter(|model| model.content_type() != ContentType::SectionHeader)
+                .map(|model| model.descendant_count())
+                .unwrap_or(0);
+            self.model.set(model);
+
+            if count == 0 || self.expanded.get() {
+                self.descendants.set_visible(false);
+                return;
+            }
+
+            let description = ni18n_f(
+                "{} hidden process",
+                "{} hidden processes",
+                count,
+                &[&count.to_string()],
+            );
+            self.descendants.set_label(&format!("(+{count})"));
+            self.descendants.set_tooltip_text(Some(&description));
+            self.descendants
+                .update_property(&[gtk::accessible::Property::Label(&description)]);
+            self.descendants.set_visible(true);
+        }
+
+        fn update_details(&self, model: &RowModel) {
+            let Some(cpu_usage_mode) = self.details_mode.get() else {
+                return;
+            };
+
+            let cpu_usage = cpu_usage_mode.display_value(model.cpu_usage()).round() as u32;
+            let memory_usage =
+                crate::to_human_readable_nice(model.memory_usage() as f32, &DataType::MemoryBytes);
+            self.details.set_label(&i18n_f(
+                "CPU {}% · Memory {}",
+                &[&cpu_usage.to_string(), &memory_usage],
+            ));
+        }
+
+        fn update_highlight(&self, model: &RowModel) {
+            let css_classes = highlight_rules::css_classes(model);
+            for css_class in highlight_rules::HIGHLIGHT_CSS_CLASSES {
+                if css_classes.contains(&css_class) {
+                    self.name.add_css_class(css_class);
+                } else {
+                    self.name.remove_css_class(css_class);
+                }
+            }
+        }
+
+        fn set_recently_changed(&self, recently_changed: bool) {
+            if recently_changed {
+                self.obj().add_css_class(RECENTLY_CHANGED_CSS_CLASS);
+            } else {
+                self.obj().remove_css_class(RECENTLY_CHANGED_CSS_CLASS);
+            }
+        }
+
+        fn set_problem_state(&self, problem_state: bool) {
+            if problem_state {
+                self.obj().add_css_class(PROBLEM_STATE_CSS_CLASS);
+            } else {
+                self.obj().remove_css_class(PROBLEM_STATE_CSS_CLASS);
+            }
+        }
+
+        fn set_pending(&self, model: &RowModel) {
+            let pending = model.service_pending();
+            self.pending.set_visible(pending);
+            self.icon.set_visible(
+                self.show_icon.get()
+                    && !pending
+                    && model.content_type() != ContentType::SectionHeader,
+            );
+        }
+
+        fn set_summary(&self, summary: &str) {
+            self.set_badge((!summary.is_empty()).then_some(summary));
         }
 
         #[allow(deprecated)]
@@ -240,7 +549,7 @@ mod imp {
                     };
                 }
                 ContentType::Service => {
-                    self.icon.set_visible(true);
+                    self.icon.set_visible(self.show_icon.get());
                     self.icon.set_margin_end(10);
                     self.icon.set_pixel_size(16);
                     self.name.remove_css_class("heading");
@@ -269,7 +578,7 @@ mod imp {
                     };
                 }
                 ContentType::Process => {
-                    self.icon.set_visible(true);
+                    self.icon.set_visible(self.show_icon.get());
                     self.icon.set_margin_end(10);
                     self.icon.set_pixel_size(16);
                     self.name.remove_css_class("heading");
@@ -284,7 +593,7 @@ mod imp {
                     };
                 }
                 ContentType::App => {
-                    self.icon.set_visible(true);
+                    self.icon.set_visible(self.show_icon.get());
                     self.icon.set_margin_end(10);
                     self.icon.set_pixel_size(24);
                     self.name.remove_css_class("heading");
@@ -333,8 +642,91 @@ mod imp {
 
             self.name.set_ellipsize(EllipsizeMode::Middle);
 
+            self.pending.set_size_request(16, 16);
+            self.pending.set_margin_end(10);
+            self.pending.set_visible(false);
+            let pending_description = i18n("Waiting for the service to start or stop");
+            self.pending.set_tooltip_text(Some(&pending_description));
+            self.pending
+                .update_property(&[gtk::accessible::Property::Label(&pending_description)]);
+
+            self.restricted.add_css_class("dim-label");
+            self.restricted.set_margin_start(6);
+            self.restricted.set_visible(false);
+            let restricted_description = i18n(
+                "Some details of this process can only be read by its owner or an administrator, choose Read Restricted Details from its menu to read them anyway",
+            );
+            self.restricted
+                .set_tooltip_text(Some(&restricted_description));
+            self.restricted
+                .update_property(&[gtk::accessible::Property::Label(&restricted_description)]);
+
+            self.leak_warning.add_css_class("warning");
+            self.leak_warning.set_margin_start(6);
+            self.leak_warning.set_visible(false);
+            let leak_warning_description = i18n(
+                "Memory usage has only gone up for the last few minutes, which may be a memory leak",
+            );
+            self.leak_warning
+                .set_tooltip_text(Some(&leak_warning_description));
+            self.leak_warning
+                .update_property(&[gtk::accessible::Property::Label(&leak_warning_description)]);
+
+            self.descendants.add_css_class("caption");
+            self.descendants.add_css_class("dim-label");
+            self.descendants.set_margin_start(6);
+            self.descendants.set_visible(false);
+
+            self.badge.add_css_class("caption");
+            self.badge.add_css_class("dim-label");
+            self.badge.set_margin_start(6);
+            self.badge.set_visible(false);
+
+            self.details.add_css_class("caption");
+            self.details.add_css_class("dim-label");
+            self.details.set_xalign(0.);
+            self.details.set_ellipsize(EllipsizeMode::End);
+            self.details.set_visible(false);
+
+            self.name.set_xalign(0.);
+
+            // The details go below the name, for the narrow layout
+            let text = gtk::Box::new(gtk::Orientation::Vertical, 0);
+            text.set_valign(gtk::Align::Center);
+            text.append(&self.name);
+            text.append(&self.details);
+
             let _ = self.obj().append(&self.icon);
-            let _ = self.obj().append(&self.name);
+            let _ = self.obj().append(&self.pending);
+            let _ = self.obj().append(&text);
+            let _ = self.obj().append(&self.descendants);
+            let _ = self.obj().append(&self.restricted);
+            let _ = self.obj().append(&self.leak_warning);
+            let _ = self.obj().append(&self.badge);
+
+            // Rules edited in the preferences apply to the rows already shown
+            let sig_highlight_rules =
+                settings!().connect_changed(Some(highlight_rules::SETTINGS_KEY), {
+                    let this = self.obj().downgrade();
+                    move |_, _| {
+                        let Some(this) = this.upgrade() else {
+                            return;
+                        };
+                        let this = this.imp();
+                        let model = this.model.take();
+                        if let Some(model) = model.upgrade() {
+                            this.update_highlight(&model);
+                        }
+                        this.model.set(model);
+                    }
+                });
+            self.sig_highlight_rules.set(Some(sig_highlight_rules));
+        }
+
+        fn dispose(&self) {
+            if let Some(sig_id) = self.sig_highlight_rules.take() {
+                settings!().disconnect(sig_id);
+            }
         }
     }
 
@@ -365,4 +757,23 @@ impl NameCell {
     pub fn unbind(&self) {
         self.imp().unbind();
     }
+
+    pub fn set_badge(&self, badge: Option<&str>) {
+        self.imp().set_badge(badge);
+    }
+
+    /// Whether the row is expanded, collapsed rows show how many processes they hide
+    pub fn set_expanded(&self, expanded: bool) {
+        self.imp().set_expanded(expanded);
+    }
+
+    /// Whether rows show the icon of the app, process or service, only used before binding
+    pub fn set_show_icon(&self, show_icon: bool) {
+        self.imp().show_icon.set(show_icon);
+    }
+
+    /// Shows the CPU and memory usage below the name, `None` to only show the name
+    pub fn set_details_mode(&self, cpu_usage_mode: Option<CpuUsageMode>) {
+        self.imp().details_mode.set(cpu_usage_mode);
+    }
 }
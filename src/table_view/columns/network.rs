@@ -19,25 +19,32 @@
  */
 
 use std::cmp::Ordering;
+use std::rc::Rc;
 
 use gtk::glib;
 use gtk::prelude::*;
 
-use super::{compare_column_entries_by, sort_order, LabelCell};
-use crate::{label_cell_factory, DataType};
+use super::{compare_column_entries_by, HeatLevel, LabelCell, SortFlags};
+use crate::i18n::i18n;
+use crate::{label_cell_factory, settings, DataType};
 
 pub fn list_item_factory() -> gtk::SignalListItemFactory {
-    label_cell_factory!("network-usage", label_formatter)
+    label_cell_factory!("network-usage", description(), cell_formatter)
 }
 
-pub fn sorter(column_view: &gtk::ColumnView) -> impl IsA<gtk::Sorter> {
-    let column_view = column_view.downgrade();
-    gtk::CustomSorter::new(move |lhs, rhs| {
-        let Some(column_view) = column_view.upgrade() else {
-            return Ordering::Equal.into();
-        };
+// Rates in bits and in bytes are easily mistaken for one another, so the unit is spelled out
+fn description() -> String {
+    if settings!().boolean("performance-page-network-use-bytes") {
+        i18n("Network usage in bytes per second")
+    } else {
+        i18n("Network usage in bits per second")
+    }
+}
 
-        compare_column_entries_by(lhs, rhs, sort_order(&column_view), |lhs, rhs| {
+pub fn sorter(sort_flags: &Rc<SortFlags>) -> impl IsA<gtk::Sorter> {
+    let sort_flags = sort_flags.clone();
+    gtk::CustomSorter::new(move |lhs, rhs| {
+        compare_column_entries_by(lhs, rhs, &sort_flags, |lhs, rhs| {
             let lhs = lhs.network_usage();
             let rhs = rhs.network_usage();
 
@@ -47,6 +54,12 @@ pub fn sorter(column_view: &gtk::ColumnView) -> impl IsA<gtk::Sorter> {
     })
 }
 
+fn cell_formatter(label: &LabelCell, value: glib::Value) {
+    let network_usage: f32 = value.get().unwrap();
+    label.set_heat_level(HeatLevel::from_rate(network_usage));
+    label_formatter(label, value);
+}
+
 pub fn label_formatter(label: &LabelCell, value: glib::Value) {
     let network_usage: f32 = value.get().unwrap();
     label.set_label(
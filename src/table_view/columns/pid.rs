@@ -18,33 +18,29 @@
  * SPDX-License-Identifier: GPL-3.0-or-later
  */
 
-use std::cmp::Ordering;
+use std::rc::Rc;
 
 use gtk::glib;
 use gtk::prelude::*;
 
-use super::{compare_column_entries_by, sort_order, LabelCell};
+use super::{compare_column_entries_by, LabelCell, SortFlags};
+use crate::i18n::i18n;
 use crate::label_cell_factory;
 
 pub fn list_item_factory() -> gtk::SignalListItemFactory {
     label_cell_factory!(
         "pid",
+        i18n("PID"),
         ContentType::SectionHeader | ContentType::Service | ContentType::App,
         label_formatter
     )
 }
 
-pub fn sorter(column_view: &gtk::ColumnView) -> impl IsA<gtk::Sorter> {
-    let column_view = column_view.downgrade();
+pub fn sorter(sort_flags: &Rc<SortFlags>) -> impl IsA<gtk::Sorter> {
+    let sort_flags = sort_flags.clone();
     gtk::CustomSorter::new(move |lhs, rhs| {
-        let Some(column_view) = column_view.upgrade() else {
-            return Ordering::Equal.into();
-        };
-
-        compare_column_entries_by(lhs, rhs, sort_order(&column_view), |lhs, rhs| {
-            lhs.pid().cmp(&rhs.pid())
-        })
-        .into()
+        compare_column_entries_by(lhs, rhs, &sort_flags, |lhs, rhs| lhs.pid().cmp(&rhs.pid()))
+            .into()
     })
 }
 
@@ -0,0 +1,66 @@
+/* table_view/columns/start_time.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::rc::Rc;
+
+use gtk::glib;
+use gtk::prelude::*;
+
+use super::{compare_column_entries_by, LabelCell, SortFlags};
+use crate::i18n::{i18n, i18n_f};
+use crate::label_cell_factory;
+
+pub fn list_item_factory() -> gtk::SignalListItemFactory {
+    label_cell_factory!(
+        "service-start-time",
+        i18n("Start time"),
+        ContentType::SectionHeader | ContentType::App | ContentType::Process,
+        label_formatter
+    )
+}
+
+pub fn sorter(sort_flags: &Rc<SortFlags>) -> impl IsA<gtk::Sorter> {
+    let sort_flags = sort_flags.clone();
+    gtk::CustomSorter::new(move |lhs, rhs| {
+        compare_column_entries_by(lhs, rhs, &sort_flags, |lhs, rhs| {
+            lhs.service_start_time().cmp(&rhs.service_start_time())
+        })
+        .into()
+    })
+}
+
+pub fn label_formatter(label: &LabelCell, value: glib::Value) {
+    let start_time: u64 = value.get().unwrap();
+    if start_time == 0 {
+        label.set_label("");
+    } else {
+        label.set_label(&format(start_time));
+    }
+}
+
+/// Milliseconds for quick starts, seconds with a decimal for slow ones. `start_time` is in
+/// microseconds.
+pub fn format(start_time: u64) -> String {
+    if start_time < 1_000_000 {
+        i18n_f("{} ms", &[&(start_time / 1000).to_string()])
+    } else {
+        i18n_f("{} s", &[&format!("{:.1}", start_time as f64 / 1_000_000.)])
+    }
+}
@@ -0,0 +1,175 @@
+/* table_view/columns/state.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::rc::Rc;
+
+use gtk::glib;
+use gtk::prelude::*;
+
+use super::{compare_column_entries_by, SortFlags};
+use crate::i18n::i18n_f;
+use crate::table_view::row_model::{ContentType, RowModel};
+
+/// The CSS class of the cells of processes that are zombies or stuck waiting for I/O
+pub const PROBLEM_STATE_CSS_CLASS: &str = "problem-state";
+
+pub fn list_item_factory() -> gtk::SignalListItemFactory {
+    let factory = gtk::SignalListItemFactory::new();
+
+    factory.connect_setup(|_, list_item| {
+        let Some(list_item) = list_item.downcast_ref::<gtk::ListItem>() else {
+            return;
+        };
+
+        let icon = gtk::Image::from_icon_name("dialog-warning-symbolic");
+        icon.set_visible(false);
+        let label = gtk::Label::new(None);
+
+        let cell = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+        cell.set_halign(gtk::Align::End);
+        cell.append(&icon);
+        cell.append(&label);
+
+        list_item.set_child(Some(&cell));
+
+        unsafe {
+            list_item.set_data("icon", icon);
+            list_item.set_data("label", label);
+        }
+    });
+
+    factory.connect_bind(|_, list_item| {
+        let Some(list_item) = list_item.downcast_ref::<gtk::ListItem>() else {
+            return;
+        };
+
+        let Some(model) = list_item
+            .item()
+            .and_then(|item| item.downcast::<gtk::TreeListRow>().ok())
+            .and_then(|row| row.item())
+            .and_then(|item| item.downcast::<RowModel>().ok())
+        else {
+            return;
+        };
+
+        let Some(cell) = list_item.child() else {
+            return;
+        };
+        let (icon, label) = unsafe {
+            (
+                list_item
+                    .data::<gtk::Image>("icon")
+                    .unwrap_unchecked()
+                    .as_ref(),
+                list_item
+                    .data::<gtk::Label>("label")
+                    .unwrap_unchecked()
+                    .as_ref(),
+            )
+        };
+
+        set_state(&cell, icon, label, &model);
+        if model.content_type() != ContentType::Process {
+            return;
+        }
+
+        let sig_state = model.connect_process_state_notify({
+            let cell = cell.downgrade();
+            let icon = icon.downgrade();
+            let label = label.downgrade();
+            move |model| {
+                let (Some(cell), Some(icon), Some(label)) =
+                    (cell.upgrade(), icon.upgrade(), label.upgrade())
+                else {
+                    return;
+                };
+                set_state(&cell, &icon, &label, model);
+            }
+        });
+
+        unsafe {
+            list_item.set_data("model", model.downgrade());
+            list_item.set_data("sig_state", sig_state);
+        }
+    });
+
+    factory.connect_unbind(|_, list_item| {
+        let Some(list_item) = list_item.downcast_ref::<gtk::ListItem>() else {
+            return;
+        };
+
+        unsafe {
+            let model = list_item.steal_data::<glib::WeakRef<RowModel>>("model");
+            let sig_state = list_item.steal_data::<glib::SignalHandlerId>("sig_state");
+            if let (Some(model), Some(sig_state)) = (model.and_then(|m| m.upgrade()), sig_state) {
+                model.disconnect(sig_state);
+            }
+        }
+    });
+
+    factory.connect_teardown(|_, list_item| {
+        let Some(list_item) = list_item.downcast_ref::<gtk::ListItem>() else {
+            return;
+        };
+
+        unsafe {
+            let _ = list_item.steal_data::<gtk::Image>("icon");
+            let _ = list_item.steal_data::<gtk::Label>("label");
+        }
+    });
+
+    factory
+}
+
+pub fn sorter(sort_flags: &Rc<SortFlags>) -> impl IsA<gtk::Sorter> {
+    let sort_flags = sort_flags.clone();
+    gtk::CustomSorter::new(move |lhs, rhs| {
+        compare_column_entries_by(lhs, rhs, &sort_flags, |lhs, rhs| {
+            lhs.process_state().cmp(&rhs.process_state())
+        })
+        .into()
+    })
+}
+
+fn set_state(cell: &gtk::Widget, icon: &gtk::Image, label: &gtk::Label, model: &RowModel) {
+    let state = model.process_state();
+    let is_process = model.content_type() == ContentType::Process;
+
+    label.set_label(if is_process { state.code() } else { "" });
+    icon.set_visible(is_process && state.is_problem());
+
+    if is_process && state.is_problem() {
+        cell.add_css_class(PROBLEM_STATE_CSS_CLASS);
+    } else {
+        cell.remove_css_class(PROBLEM_STATE_CSS_CLASS);
+    }
+
+    let tooltip = (is_process && !state.code().is_empty()).then(|| state.description());
+    cell.set_tooltip_text(tooltip.as_deref());
+
+    // The code alone means little when read out
+    match tooltip {
+        Some(description) => {
+            let accessible_label = i18n_f("State {} for {}", &[&description, &model.name()]);
+            label.update_property(&[gtk::accessible::Property::Label(&accessible_label)]);
+        }
+        None => label.reset_property(gtk::AccessibleProperty::Label),
+    }
+}
@@ -0,0 +1,153 @@
+/* table_view/copy_table.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use adw::prelude::*;
+use gtk::glib;
+
+use crate::i18n::{i18n, ni18n_f};
+use crate::magpie_client::ProcField;
+use crate::table_view::columns::{
+    command_line_label_formatter, context_switches_label_formatter, cpu_time_label_formatter,
+    drive_label_formatter, gpu_device_label_formatter, gpu_label_formatter,
+    gpu_memory_label_formatter, io_wait_label_formatter, major_faults_label_formatter,
+    memory_growth_label_formatter, memory_label_formatter, network_label_formatter,
+    pid_label_formatter, restarts_label_formatter, shared_memory_label_formatter,
+    start_time_label_formatter, swap_label_formatter, unit_origin_label_formatter,
+    wait_channel_label_formatter, CpuUsageMode, LabelCell, CUSTOM_COLUMN_ID_PREFIX,
+};
+use crate::table_view::row_model::{ContentType, RowModel};
+use crate::table_view::TableView;
+
+/// Copies the rows the table shows, as filtered and sorted, with its visible columns in their
+/// current order. Cells are separated by tabs and rows by new lines, so that pasting into a
+/// spreadsheet fills one cell for each.
+pub fn copy_visible_rows(table_view: &TableView) {
+    let columns = table_view.column_view().columns();
+    let columns = (0..columns.n_items())
+        .filter_map(|i| columns.item(i))
+        .filter_map(|item| item.downcast::<gtk::ColumnViewColumn>().ok())
+        .filter(|column| column.is_visible())
+        .filter_map(|column| {
+            let id = column.id()?;
+            // Without the totals the header shows below the name
+            let title = column.title().unwrap_or_default();
+            let title = title.lines().next().unwrap_or_default().to_owned();
+            Some((id, title))
+        })
+        .collect::<Vec<_>>();
+
+    let mut lines = vec![columns
+        .iter()
+        .map(|(_, title)| cell(title))
+        .collect::<Vec<_>>()
+        .join("\t")];
+
+    let cpu_usage_mode = table_view.cpu_usage_mode();
+    // A scratch cell, so values are formatted exactly like the table formats them
+    let label = LabelCell::new();
+
+    let mut row_count = 0_u32;
+    if let Some(model) = table_view.column_view().model() {
+        for row_model in (0..model.n_items())
+            .filter_map(|i| model.item(i))
+            .filter_map(|item| item.downcast::<gtk::TreeListRow>().ok())
+            .filter_map(|row| row.item())
+            .filter_map(|item| item.downcast::<RowModel>().ok())
+            .filter(|row_model| row_model.content_type() != ContentType::SectionHeader)
+        {
+            lines.push(
+                columns
+                    .iter()
+                    .map(|(id, _)| cell(&cell_text(&label, id, &row_model, cpu_usage_mode)))
+                    .collect::<Vec<_>>()
+                    .join("\t"),
+            );
+            row_count += 1;
+        }
+    }
+
+    table_view.clipboard().set_text(&lines.join("\n"));
+
+    if let Some(toast_overlay) = table_view
+        .ancestor(adw::ToastOverlay::static_type())
+        .and_downcast::<adw::ToastOverlay>()
+    {
+        toast_overlay.add_toast(adw::Toast::new(&ni18n_f(
+            "Copied {} row",
+            "Copied {} rows",
+            row_count,
+            &[&row_count.to_string()],
+        )));
+    }
+}
+
+// Tabs and new lines would start a new cell or row
+fn cell(text: &str) -> String {
+    text.replace(['\t', '\n'], " ")
+}
+
+fn cell_text(label: &LabelCell, id: &str, model: &RowModel, mode: CpuUsageMode) -> String {
+    let content_type = model.content_type();
+    let is_process = content_type == ContentType::Process;
+
+    let (property, formatter): (&str, fn(&LabelCell, glib::Value)) = match id {
+        "name" => return model.name().to_string(),
+        "state" if is_process => return model.process_state().code().to_owned(),
+        "state" => return String::new(),
+        "cpu" => {
+            mode.format(label, model.cpu_usage());
+            return label.label().to_string();
+        }
+        "pid" | "command_line" if !is_process => return String::new(),
+        "command_line" if model.command_line_restricted() => return i18n("Restricted"),
+        "drive" if model.io_restricted() => return i18n("Restricted"),
+        "pid" => ("pid", pid_label_formatter),
+        "command_line" => ("command-line", command_line_label_formatter),
+        "wait_channel" => ("wait-channel", wait_channel_label_formatter),
+        "unit_origin" => ("unit-origin", unit_origin_label_formatter),
+        "start_time" | "restarts" if is_process => return String::new(),
+        "start_time" => ("service-start-time", start_time_label_formatter),
+        "restarts" => ("service-restarts", restarts_label_formatter),
+        "cpu_time" => ("cpu-time", cpu_time_label_formatter),
+        "context_switches" => ("context-switch-rate", context_switches_label_formatter),
+        "memory" => ("memory-usage", memory_label_formatter),
+        "memory_growth" => ("memory-growth", memory_growth_label_formatter),
+        "shared_memory" => ("shared-memory-usage", shared_memory_label_formatter),
+        "swap" => ("swap-usage", swap_label_formatter),
+        "major_faults" => ("major-fault-rate", major_faults_label_formatter),
+        "drive" => ("disk-usage", drive_label_formatter),
+        "io_wait" => ("io-wait", io_wait_label_formatter),
+        "network" => ("network-usage", network_label_formatter),
+        "gpu" => ("gpu-usage", gpu_label_formatter),
+        "gpu_memory" => ("gpu-memory-usage", gpu_memory_label_formatter),
+        "gpu_device" => ("gpu-devices", gpu_device_label_formatter),
+        id => {
+            return id
+                .strip_prefix(CUSTOM_COLUMN_ID_PREFIX)
+                .and_then(ProcField::from_id)
+                .and_then(|field| model.custom_value(field))
+                .map(|value| value.to_string())
+                .unwrap_or_default();
+        }
+    };
+
+    formatter(label, model.property_value(property));
+    label.label().to_string()
+}
@@ -0,0 +1,133 @@
+/* table_view/debug_tools.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use gtk::gio;
+use gtk::gio::prelude::*;
+use gtk::glib::{self, g_debug};
+
+use crate::settings;
+use crate::table_view::{proc_fs, terminal};
+
+// How long the system calls of a process are traced for
+const TRACE_DURATION: Duration = Duration::from_secs(10);
+
+const SIGINT: i32 = 2;
+
+/// The developer tools the Debug submenu of processes runs. Their commands are read from the
+/// settings, with `{pid}` replaced by the PID of the process and `{file}` by the file the
+/// output is written to.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DebugTool {
+    /// Attaches a debugger, in a terminal
+    Debugger,
+    /// Shows where the process spends its time, in a terminal
+    Profiler,
+    /// Writes the system calls of the process to a file, in the background
+    SystemCallTrace,
+}
+
+impl DebugTool {
+    fn settings_key(&self) -> &'static str {
+        match self {
+            DebugTool::Debugger => "developer-tools-debugger-command",
+            DebugTool::Profiler => "developer-tools-profiler-command",
+            DebugTool::SystemCallTrace => "developer-tools-trace-command",
+        }
+    }
+
+    fn command(&self, pid: u32, file: Option<&str>) -> Result<Vec<String>, glib::Error> {
+        let template = settings!().string(self.settings_key());
+        let pid = pid.to_string();
+
+        Ok(glib::shell_parse_argv(template.as_str())?
+            .into_iter()
+            .map(|arg| {
+                let arg = arg.to_string_lossy().replace("{pid}", &pid);
+                match file {
+                    Some(file) => arg.replace("{file}", file),
+                    None => arg,
+                }
+            })
+            .collect())
+    }
+}
+
+/// Whether the Debug submenu is shown. The tools run on this machine, so they can only attach
+/// to the processes that can be inspected from here.
+pub fn is_available(pid: u32) -> bool {
+    pid != 0
+        && settings!().boolean("developer-mode")
+        && proc_fs::working_directory(pid).is_some()
+}
+
+/// Runs the debugger or profiler for `pid` in a terminal
+pub fn run_in_terminal(tool: DebugTool, pid: u32) -> Result<(), glib::Error> {
+    let command = tool.command(pid, None)?;
+    terminal::run(&command)
+}
+
+/// Traces the system calls of `pid` for a few seconds. `on_finished` gets the file the trace
+/// was written to, or `None` if nothing could be traced.
+pub fn trace_system_calls(
+    pid: u32,
+    process_name: &str,
+    on_finished: impl FnOnce(Option<PathBuf>) + 'static,
+) -> Result<(), glib::Error> {
+    let directory = glib::user_cache_dir().join("mission-center").join("traces");
+    std::fs::create_dir_all(&directory).map_err(|e| {
+        glib::Error::new(
+            gio::IOErrorEnum::Failed,
+            &format!("Failed to create {}: {}", directory.display(), e),
+        )
+    })?;
+
+    let timestamp = glib::DateTime::now_local()
+        .and_then(|now| now.format("%Y%m%d-%H%M%S"))
+        .map(|timestamp| timestamp.to_string())
+        .unwrap_or_default();
+    let process_name = process_name.replace('/', "_");
+    let file = directory.join(format!("{}-{}-{}.txt", process_name, pid, timestamp));
+
+    let command = DebugTool::SystemCallTrace.command(pid, Some(&file.to_string_lossy()))?;
+    let argv = command.iter().map(std::ffi::OsStr::new).collect::<Vec<_>>();
+    g_debug!("MissionCenter::DebugTools", "Running {:?}", argv);
+
+    let process = gio::Subprocess::newv(&argv, gio::SubprocessFlags::STDERR_SILENCE)?;
+
+    // Interrupting the tracer makes it detach from the process and finish the file
+    glib::timeout_add_local_once(TRACE_DURATION, {
+        let process = process.downgrade();
+        move || {
+            if let Some(process) = process.upgrade() {
+                process.send_signal(SIGINT);
+            }
+        }
+    });
+
+    process.wait_async(None::<&gio::Cancellable>, move |_| {
+        let written = std::fs::metadata(&file).is_ok_and(|metadata| metadata.len() > 0);
+        on_finished(written.then_some(file));
+    });
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+/* table_view/dependency_graph/layout.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use gtk::graphene;
+
+/// Space between the columns of the graph, which the edges are drawn in
+pub const COLUMN_SPACING: f32 = 48.;
+pub const ROW_SPACING: f32 = 8.;
+
+/// Where each node of a tree goes, from left to right by depth, and how much space they take
+/// up together
+#[derive(Debug, Default)]
+pub struct Layout {
+    pub bounds: Vec<graphene::Rect>,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Lays out the tree given by the parent of each node, with the root being the first node and
+/// parents coming before their children. Leaves are stacked from top to bottom in the order
+/// they appear, and every parent is centered next to its children.
+pub fn layout(parents: &[Option<usize>], sizes: &[(f32, f32)]) -> Layout {
+    let count = parents.len().min(sizes.len());
+    if count == 0 {
+        return Layout::default();
+    }
+
+    let mut children = vec![Vec::new(); count];
+    let mut depths = vec![0; count];
+    for node in 1..count {
+        // Anything that does not point back to an earlier node hangs off of the root
+        let parent = parents[node].filter(|parent| *parent < node).unwrap_or(0);
+        children[parent].push(node);
+        depths[node] = depths[parent] + 1;
+    }
+
+    let column_count = depths.iter().max().copied().unwrap_or_default() + 1;
+    let mut column_widths = vec![0_f32; column_count];
+    for node in 0..count {
+        column_widths[depths[node]] = column_widths[depths[node]].max(sizes[node].0);
+    }
+    let mut column_offsets = Vec::with_capacity(column_count);
+    let mut x = 0.;
+    for width in &column_widths {
+        column_offsets.push(x);
+        x += width + COLUMN_SPACING;
+    }
+
+    let mut result = Layout {
+        bounds: vec![graphene::Rect::zero(); count],
+        width: x - COLUMN_SPACING,
+        height: 0.,
+    };
+
+    // Children are placed before their parents, which need to know where they ended up
+    let mut next_y = 0.;
+    let mut stack = vec![(0, false)];
+    while let Some((node, children_placed)) = stack.pop() {
+        let (width, height) = sizes[node];
+        let x = column_offsets[depths[node]];
+
+        if !children[node].is_empty() && !children_placed {
+            stack.push((node, true));
+            stack.extend(children[node].iter().rev().map(|child| (*child, false)));
+            continue;
+        }
+
+        let center_y = match (children[node].first(), children[node].last()) {
+            (Some(first), Some(last)) => {
+                let first = &result.bounds[*first];
+                let last = &result.bounds[*last];
+                (first.y() + first.height() / 2. + last.y() + last.height() / 2.) / 2.
+            }
+            _ => {
+                let center_y = next_y + height / 2.;
+                next_y += height + ROW_SPACING;
+                center_y
+            }
+        };
+
+        result.bounds[node] = graphene::Rect::new(x, center_y - height / 2., width, height);
+    }
+    result.height = (next_y - ROW_SPACING).max(sizes[0].1);
+
+    result
+}
@@ -0,0 +1,296 @@
+/* table_view/dependency_graph/mod.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::cell::{Cell, RefCell};
+
+use glib::subclass::Signal;
+use gtk::{gdk, glib, graphene, gsk, pango, prelude::*, subclass::prelude::*, Snapshot};
+
+use crate::magpie_client::UnitDependency;
+
+use layout::Layout;
+
+mod layout;
+
+const PADDING_X: f32 = 10.;
+const PADDING_Y: f32 = 6.;
+const RADIUS: f32 = 6.;
+
+// The red of the Adwaita palette, which failed services are shown in elsewhere too
+const FAILED_COLOR: gdk::RGBA = gdk::RGBA::new(0.878, 0.106, 0.141, 1.);
+
+mod imp {
+    use super::*;
+
+    #[derive(Default)]
+    pub struct DependencyGraph {
+        pub units: RefCell<Vec<UnitDependency>>,
+        /// Whether each unit has a row in the table that it can be shown in
+        pub linked: RefCell<Vec<bool>>,
+        pub labels: RefCell<Vec<pango::Layout>>,
+        pub layout: RefCell<Layout>,
+        pub hovered: Cell<Option<usize>>,
+    }
+
+    impl DependencyGraph {
+        pub fn update_layout(&self) {
+            let obj = self.obj();
+
+            let units = self.units.borrow();
+            let labels = units
+                .iter()
+                .map(|unit| obj.create_pango_layout(Some(&unit.name)))
+                .collect::<Vec<_>>();
+            let sizes = labels
+                .iter()
+                .map(|label| {
+                    let (width, height) = label.pixel_size();
+                    (
+                        width as f32 + 2. * PADDING_X,
+                        height as f32 + 2. * PADDING_Y,
+                    )
+                })
+                .collect::<Vec<_>>();
+            let parents = units.iter().map(|unit| unit.parent).collect::<Vec<_>>();
+
+            self.layout.replace(layout::layout(&parents, &sizes));
+            self.labels.replace(labels);
+            self.hovered.set(None);
+
+            obj.queue_resize();
+        }
+
+        fn node_at(&self, x: f64, y: f64) -> Option<usize> {
+            let point = graphene::Point::new(x as f32, y as f32);
+            self.layout
+                .borrow()
+                .bounds
+                .iter()
+                .position(|bounds| bounds.contains_point(&point))
+        }
+
+        fn is_linked(&self, node: usize) -> bool {
+            self.linked.borrow().get(node).copied().unwrap_or(false)
+        }
+
+        fn set_hovered(&self, node: Option<usize>) {
+            if self.hovered.replace(node) == node {
+                return;
+            }
+
+            let obj = self.obj();
+            let clickable = node.is_some_and(|node| self.is_linked(node));
+            obj.set_cursor_from_name(clickable.then_some("pointer"));
+            obj.queue_draw();
+        }
+
+        fn draw_edges(&self, snapshot: &Snapshot, color: &gdk::RGBA) {
+            let layout = self.layout.borrow();
+            let units = self.units.borrow();
+
+            let path_builder = gsk::PathBuilder::new();
+            for (node, unit) in units.iter().enumerate() {
+                let (Some(bounds), Some(parent_bounds)) = (
+                    layout.bounds.get(node),
+                    unit.parent.and_then(|parent| layout.bounds.get(parent)),
+                ) else {
+                    continue;
+                };
+
+                let start_x = parent_bounds.x() + parent_bounds.width();
+                let start_y = parent_bounds.y() + parent_bounds.height() / 2.;
+                let end_x = bounds.x();
+                let end_y = bounds.y() + bounds.height() / 2.;
+                let middle_x = (start_x + end_x) / 2.;
+
+                path_builder.move_to(start_x, start_y);
+                path_builder.cubic_to(middle_x, start_y, middle_x, end_y, end_x, end_y);
+            }
+
+            snapshot.append_stroke(&path_builder.to_path(), &gsk::Stroke::new(1.), color);
+        }
+
+        fn draw_nodes(&self, snapshot: &Snapshot, foreground: &gdk::RGBA, accent: &gdk::RGBA) {
+            let layout = self.layout.borrow();
+            let units = self.units.borrow();
+            let labels = self.labels.borrow();
+            let hovered = self.hovered.get();
+
+            for (node, ((unit, bounds), label)) in units
+                .iter()
+                .zip(&layout.bounds)
+                .zip(labels.iter())
+                .enumerate()
+            {
+                let linked = self.is_linked(node);
+                let base = if unit.failed {
+                    FAILED_COLOR
+                } else if linked {
+                    *accent
+                } else {
+                    *foreground
+                };
+                let fill_alpha = if linked && hovered == Some(node) {
+                    0.3
+                } else {
+                    0.1
+                };
+
+                let path_builder = gsk::PathBuilder::new();
+                path_builder.add_rounded_rect(&gsk::RoundedRect::from_rect(*bounds, RADIUS));
+                let path = path_builder.to_path();
+
+                snapshot.append_fill(&path, gsk::FillRule::Winding, &base.with_alpha(fill_alpha));
+                if linked || unit.failed {
+                    snapshot.append_stroke(&path, &gsk::Stroke::new(1.), &base);
+                }
+
+                snapshot.save();
+                snapshot.translate(&graphene::Point::new(
+                    bounds.x() + PADDING_X,
+                    bounds.y() + PADDING_Y,
+                ));
+                let text_color = if unit.failed {
+                    FAILED_COLOR
+                } else {
+                    *foreground
+                };
+                snapshot.append_layout(label, &text_color);
+                snapshot.restore();
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for DependencyGraph {
+        const NAME: &'static str = "DependencyGraph";
+        type Type = super::DependencyGraph;
+        type ParentType = gtk::Widget;
+    }
+
+    impl ObjectImpl for DependencyGraph {
+        fn signals() -> &'static [Signal] {
+            use std::sync::OnceLock;
+            static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
+            SIGNALS.get_or_init(|| {
+                vec![Signal::builder("unit-activated")
+                    .param_types([String::static_type()])
+                    .build()]
+            })
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+
+            let click = gtk::GestureClick::new();
+            click.connect_released({
+                let this = obj.downgrade();
+                move |_, _, x, y| {
+                    let Some(this) = this.upgrade() else {
+                        return;
+                    };
+                    let imp = this.imp();
+
+                    let Some(node) = imp.node_at(x, y).filter(|node| imp.is_linked(*node)) else {
+                        return;
+                    };
+                    let name = imp.units.borrow()[node].name.clone();
+                    this.emit_by_name::<()>("unit-activated", &[&name]);
+                }
+            });
+            obj.add_controller(click);
+
+            let motion = gtk::EventControllerMotion::new();
+            motion.connect_motion({
+                let this = obj.downgrade();
+                move |_, x, y| {
+                    if let Some(this) = this.upgrade() {
+                        let imp = this.imp();
+                        imp.set_hovered(imp.node_at(x, y));
+                    }
+                }
+            });
+            motion.connect_leave({
+                let this = obj.downgrade();
+                move |_| {
+                    if let Some(this) = this.upgrade() {
+                        this.imp().set_hovered(None);
+                    }
+                }
+            });
+            obj.add_controller(motion);
+        }
+    }
+
+    impl WidgetImpl for DependencyGraph {
+        fn measure(&self, orientation: gtk::Orientation, _for_size: i32) -> (i32, i32, i32, i32) {
+            let layout = self.layout.borrow();
+            let size = match orientation {
+                gtk::Orientation::Horizontal => layout.width,
+                _ => layout.height,
+            }
+            .ceil() as i32;
+
+            (size, size, -1, -1)
+        }
+
+        fn snapshot(&self, snapshot: &Snapshot) {
+            let obj = self.obj();
+
+            let foreground = obj.color();
+            let accent = adw::StyleManager::default().accent_color_rgba();
+
+            self.draw_edges(snapshot, &foreground.with_alpha(0.4));
+            self.draw_nodes(snapshot, &foreground, &accent);
+        }
+    }
+}
+
+glib::wrapper! {
+    pub struct DependencyGraph(ObjectSubclass<imp::DependencyGraph>)
+        @extends gtk::Widget,
+        @implements gtk::ConstraintTarget, gtk::Accessible, gtk::Buildable;
+}
+
+impl DependencyGraph {
+    /// Shows `units` as a tree, the units that have a row in the table can be clicked
+    pub fn set_units(&self, units: Vec<UnitDependency>, linked: Vec<bool>) {
+        let imp = self.imp();
+
+        imp.units.replace(units);
+        imp.linked.replace(linked);
+        imp.update_layout();
+    }
+
+    pub fn connect_unit_activated(
+        &self,
+        f: impl Fn(&Self, &str) + 'static,
+    ) -> glib::SignalHandlerId {
+        self.connect_local("unit-activated", false, move |values| {
+            let this = values[0].get::<Self>().ok()?;
+            let name = values[1].get::<String>().ok()?;
+            f(&this, &name);
+
+            None
+        })
+    }
+}
@@ -0,0 +1,138 @@
+/* table_view/file_location.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::path::{Path, PathBuf};
+
+use gtk::glib::{g_debug, g_warning};
+use gtk::{gio, prelude::*};
+
+use crate::table_view::{flatpak_permissions, proc_fs};
+
+/// Where the executable `exe` of the process `pid` is on the host. Flatpak apps see their own
+/// files at `/app` and the ones of their runtime at `/usr`, which live elsewhere on the host.
+/// `app_id` is the ID of the app the process belongs to, if it is known. `pid` is `None` when
+/// the process can't be looked at, e.g. because it is from a snapshot.
+pub fn executable_path(pid: Option<u32>, exe: &str, app_id: Option<&str>) -> PathBuf {
+    if let Some((app_path, runtime_path)) = pid.and_then(proc_fs::flatpak_instance_paths) {
+        for (prefix, path) in [("/app/", app_path), ("/usr/", runtime_path)] {
+            if let Some(relative) = exe.strip_prefix(prefix) {
+                return path.join(relative);
+            }
+        }
+    }
+
+    // From inside a sandbox the instance can't be looked at, but the installed app can be
+    if let (Some(relative), Some(app_id)) = (exe.strip_prefix("/app/"), app_id) {
+        let deployed = flatpak_permissions::installations()
+            .into_iter()
+            .map(|installation| {
+                installation
+                    .join("app")
+                    .join(app_id)
+                    .join("current/active/files")
+                    .join(relative)
+            })
+            .find(|path| path.exists());
+        if let Some(deployed) = deployed {
+            return deployed;
+        }
+    }
+
+    PathBuf::from(exe)
+}
+
+/// Opens the folder of `path` in the file manager with the file selected. `on_failed` is
+/// called if neither the file manager nor the portal could show it.
+pub fn show(path: &Path, parent: Option<gtk::Window>, on_failed: impl FnOnce() + 'static) {
+    let path = path.to_owned();
+    let uri = gio::File::for_path(&path).uri();
+
+    gio::bus_get(
+        gio::BusType::Session,
+        None::<&gio::Cancellable>,
+        move |connection| {
+            let connection = match connection {
+                Ok(connection) => connection,
+                Err(e) => {
+                    g_debug!(
+                        "MissionCenter::FileLocation",
+                        "Failed to connect to the session bus: {}",
+                        e
+                    );
+                    open_containing_folder(&path, parent, on_failed);
+                    return;
+                }
+            };
+
+            connection.call(
+                Some("org.freedesktop.FileManager1"),
+                "/org/freedesktop/FileManager1",
+                "org.freedesktop.FileManager1",
+                "ShowItems",
+                Some(&(vec![uri.to_string()], "").to_variant()),
+                None,
+                gio::DBusCallFlags::NONE,
+                -1,
+                None::<&gio::Cancellable>,
+                move |result| {
+                    if let Err(e) = result {
+                        g_debug!(
+                            "MissionCenter::FileLocation",
+                            "The file manager could not show {}: {}",
+                            path.display(),
+                            e
+                        );
+                        open_containing_folder(&path, parent, on_failed);
+                    }
+                },
+            );
+        },
+    );
+}
+
+// Goes through the OpenURI portal when sandboxed, which looks the path up in the sandbox. There
+// `/app` and `/usr` hold the files of Mission Center and its runtime, not those of the host.
+fn open_containing_folder(
+    path: &Path,
+    parent: Option<gtk::Window>,
+    on_failed: impl FnOnce() + 'static,
+) {
+    if crate::is_flatpak() && (path.starts_with("/app") || path.starts_with("/usr")) {
+        on_failed();
+        return;
+    }
+
+    let description = path.display().to_string();
+    gtk::FileLauncher::new(Some(&gio::File::for_path(path))).open_containing_folder(
+        parent.as_ref(),
+        None::<&gio::Cancellable>,
+        move |result| {
+            if let Err(e) = result {
+                g_warning!(
+                    "MissionCenter::FileLocation",
+                    "Failed to open the folder of {}: {}",
+                    description,
+                    e
+                );
+                on_failed();
+            }
+        },
+    );
+}
@@ -0,0 +1,283 @@
+/* table_view/filter_presets.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use adw::prelude::*;
+use gtk::glib::{g_critical, g_warning};
+use gtk::{gio, glib};
+
+use crate::i18n::i18n;
+use crate::settings;
+use crate::table_view::{SettingsValues, TableView};
+
+/// The name of the action group the preset actions are inserted as
+pub const ACTION_GROUP_NAME: &str = "filter-presets";
+
+/// A named combination of search text, active state toggles and sorting, that can be
+/// restored later. Presets are stored per page in the `<page>-filter-presets` setting.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FilterPreset {
+    pub name: String,
+    pub search: String,
+    /// Widget names of the toggle buttons that are active
+    pub active_toggles: Vec<String>,
+    /// ID of the sorted column, empty if the entries are not sorted
+    pub sort_column: String,
+    pub sort_descending: bool,
+}
+
+type StoredPreset = (String, String, Vec<String>, String, bool);
+
+impl From<StoredPreset> for FilterPreset {
+    fn from(value: StoredPreset) -> Self {
+        let (name, search, active_toggles, sort_column, sort_descending) = value;
+        Self {
+            name,
+            search,
+            active_toggles,
+            sort_column,
+            sort_descending,
+        }
+    }
+}
+
+impl From<&FilterPreset> for StoredPreset {
+    fn from(value: &FilterPreset) -> Self {
+        (
+            value.name.clone(),
+            value.search.clone(),
+            value.active_toggles.clone(),
+            value.sort_column.clone(),
+            value.sort_descending,
+        )
+    }
+}
+
+pub fn load(table_view: &TableView) -> Vec<FilterPreset> {
+    let key = table_view.format_settings_key(&SettingsValues::FilterPresets);
+    let Some(presets) = settings!().value(&key).get::<Vec<StoredPreset>>() else {
+        g_warning!(
+            "MissionCenter::FilterPresets",
+            "Invalid value for {} setting, ignoring saved presets",
+            key
+        );
+        return vec![];
+    };
+
+    presets.into_iter().map(FilterPreset::from).collect()
+}
+
+fn store(table_view: &TableView, presets: &[FilterPreset]) {
+    let key = table_view.format_settings_key(&SettingsValues::FilterPresets);
+    let presets = presets.iter().map(StoredPreset::from).collect::<Vec<_>>();
+    if let Err(e) = settings!().set_value(&key, &presets.to_variant()) {
+        g_critical!(
+            "MissionCenter::FilterPresets",
+            "Failed to set {} setting: {}",
+            key,
+            e
+        );
+    }
+}
+
+/// Saves the current filters as `name`, replacing a preset with the same name
+pub fn save(table_view: &TableView, name: &str) {
+    let mut preset = table_view.current_filter_preset();
+    preset.name = name.to_string();
+
+    let mut presets = load(table_view);
+    match presets.iter_mut().find(|p| p.name == name) {
+        Some(existing) => *existing = preset,
+        None => presets.push(preset),
+    }
+
+    store(table_view, &presets);
+}
+
+pub fn delete(table_view: &TableView, name: &str) {
+    let mut presets = load(table_view);
+    presets.retain(|preset| preset.name != name);
+    store(table_view, &presets);
+}
+
+pub fn apply(table_view: &TableView, name: &str) {
+    match load(table_view)
+        .into_iter()
+        .find(|preset| preset.name == name)
+    {
+        Some(preset) => table_view.apply_filter_preset(&preset),
+        None => {
+            g_warning!(
+                "MissionCenter::FilterPresets",
+                "No filter preset named `{}`",
+                name
+            );
+        }
+    }
+}
+
+/// Creates the `apply`, `delete` and `save` actions, the first two take the name of the preset
+/// as their target
+pub fn create_action_group(table_view: &TableView) -> gio::SimpleActionGroup {
+    let action_group = gio::SimpleActionGroup::new();
+
+    let action_apply = gio::SimpleAction::new("apply", Some(glib::VariantTy::STRING));
+    action_apply.connect_activate({
+        let table_view = table_view.downgrade();
+        move |_, name| {
+            let (Some(table_view), Some(name)) = (
+                table_view.upgrade(),
+                name.and_then(|name| name.get::<String>()),
+            ) else {
+                return;
+            };
+            apply(&table_view, &name);
+        }
+    });
+    action_group.add_action(&action_apply);
+
+    let action_delete = gio::SimpleAction::new("delete", Some(glib::VariantTy::STRING));
+    action_delete.connect_activate({
+        let table_view = table_view.downgrade();
+        move |_, name| {
+            let (Some(table_view), Some(name)) = (
+                table_view.upgrade(),
+                name.and_then(|name| name.get::<String>()),
+            ) else {
+                return;
+            };
+            delete(&table_view, &name);
+        }
+    });
+    action_group.add_action(&action_delete);
+
+    let action_save = gio::SimpleAction::new("save", None);
+    action_save.connect_activate({
+        let table_view = table_view.downgrade();
+        move |_, _| {
+            if let Some(table_view) = table_view.upgrade() {
+                show_save_dialog(&table_view);
+            }
+        }
+    });
+    action_group.add_action(&action_save);
+
+    action_group
+}
+
+/// A menu listing the saved presets, that is kept up to date when presets are added or removed
+pub fn menu_model(table_view: &TableView) -> gio::Menu {
+    let menu = gio::Menu::new();
+    update_menu(&menu, table_view);
+
+    let key = table_view.format_settings_key(&SettingsValues::FilterPresets);
+    settings!().connect_changed(Some(&key), {
+        let menu = menu.downgrade();
+        let table_view = table_view.downgrade();
+        move |_, _| {
+            if let (Some(menu), Some(table_view)) = (menu.upgrade(), table_view.upgrade()) {
+                update_menu(&menu, &table_view);
+            }
+        }
+    });
+
+    menu
+}
+
+fn update_menu(menu: &gio::Menu, table_view: &TableView) {
+    menu.remove_all();
+
+    let presets = load(table_view);
+
+    let apply_section = gio::Menu::new();
+    let delete_submenu = gio::Menu::new();
+    for preset in &presets {
+        apply_section.append(
+            Some(&preset.name),
+            Some(&action_name_with_target("apply", &preset.name)),
+        );
+        delete_submenu.append(
+            Some(&preset.name),
+            Some(&action_name_with_target("delete", &preset.name)),
+        );
+    }
+    if !presets.is_empty() {
+        menu.append_section(None, &apply_section);
+    }
+
+    let manage_section = gio::Menu::new();
+    manage_section.append(
+        Some(&i18n("Save Current Filters…")),
+        Some(&format!("{ACTION_GROUP_NAME}.save")),
+    );
+    if !presets.is_empty() {
+        manage_section.append_submenu(Some(&i18n("Delete Preset")), &delete_submenu);
+    }
+    menu.append_section(None, &manage_section);
+}
+
+fn action_name_with_target(action: &str, name: &str) -> String {
+    gio::Action::print_detailed_name(
+        &format!("{ACTION_GROUP_NAME}.{action}"),
+        Some(&name.to_variant()),
+    )
+    .to_string()
+}
+
+fn show_save_dialog(table_view: &TableView) {
+    let dialog = adw::AlertDialog::new(
+        Some(&i18n("Save Filter Preset")),
+        Some(&i18n(
+            "The search text, filters and sorting can be restored from the presets menu. A preset with the same name is replaced.",
+        )),
+    );
+
+    let entry = gtk::Entry::new();
+    entry.set_placeholder_text(Some(&i18n("Name")));
+    entry.set_activates_default(true);
+    dialog.set_extra_child(Some(&entry));
+
+    dialog.add_responses(&[("cancel", &i18n("_Cancel")), ("save", &i18n("_Save"))]);
+    dialog.set_response_appearance("save", adw::ResponseAppearance::Suggested);
+    dialog.set_default_response(Some("save"));
+    dialog.set_close_response("cancel");
+    dialog.set_response_enabled("save", false);
+
+    entry.connect_changed({
+        let dialog = dialog.downgrade();
+        move |entry| {
+            if let Some(dialog) = dialog.upgrade() {
+                dialog.set_response_enabled("save", !entry.text().trim().is_empty());
+            }
+        }
+    });
+
+    dialog.connect_response(Some("save"), {
+        let table_view = table_view.downgrade();
+        let entry = entry.downgrade();
+        move |_, _| {
+            let (Some(table_view), Some(entry)) = (table_view.upgrade(), entry.upgrade()) else {
+                return;
+            };
+            save(&table_view, entry.text().trim());
+        }
+    });
+
+    dialog.present(Some(table_view));
+}
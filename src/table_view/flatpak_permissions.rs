@@ -0,0 +1,191 @@
+/* table_view/flatpak_permissions.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use gtk::glib::g_warning;
+use gtk::{gio, glib};
+
+use crate::i18n::{i18n, i18n_f};
+use crate::magpie_client::host_subprocess;
+
+const FLATSEAL_APP_ID: &str = "com.github.tchx84.Flatseal";
+
+/// What the sandbox of a Flatpak app gives it access to, with the overrides applied
+#[derive(Debug, Default, Clone)]
+pub struct Permissions {
+    pub filesystems: BTreeSet<String>,
+    pub devices: BTreeSet<String>,
+    pub shared: BTreeSet<String>,
+}
+
+impl Permissions {
+    pub fn network(&self) -> bool {
+        self.shared.contains("network")
+    }
+
+    // Entries are separated by semicolons, those starting with `!` take away access that
+    // was granted before
+    fn apply(&mut self, keyfile: &ini::Ini) {
+        let Some(context) = keyfile.section(Some("Context")) else {
+            return;
+        };
+
+        for (key, entries) in [
+            ("filesystems", &mut self.filesystems),
+            ("devices", &mut self.devices),
+            ("shared", &mut self.shared),
+        ] {
+            let Some(value) = context.get(key) else {
+                continue;
+            };
+
+            for entry in value.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+                match entry.strip_prefix('!') {
+                    // Filesystems can end in `:ro` or `:create`, taking one away removes all
+                    Some(removed) => entries.retain(|e| e.split(':').next() != Some(removed)),
+                    None => {
+                        entries.insert(entry.to_owned());
+                    }
+                }
+            }
+        }
+    }
+}
+
+// The per-user installation is listed first, it is the one `flatpak run` prefers
+pub(super) fn installations() -> [PathBuf; 2] {
+    [
+        glib::home_dir().join(".local/share/flatpak"),
+        PathBuf::from("/var/lib/flatpak"),
+    ]
+}
+
+fn is_installed(app_id: &str) -> bool {
+    installations()
+        .iter()
+        .any(|installation| installation.join("app").join(app_id).exists())
+}
+
+/// The permissions of the app with the given ID, `None` if it isn't an installed Flatpak
+pub fn permissions(app_id: &str) -> Option<Permissions> {
+    if crate::magpie_client::is_remote() {
+        return None;
+    }
+
+    let app_id = app_id.strip_suffix(".desktop").unwrap_or(app_id);
+    let metadata = installations().iter().find_map(|installation| {
+        ini::Ini::load_from_file(
+            installation
+                .join("app")
+                .join(app_id)
+                .join("current/active/metadata"),
+        )
+        .ok()
+    })?;
+
+    let mut permissions = Permissions::default();
+    permissions.apply(&metadata);
+
+    // Flatpak applies the overrides of the system before those of the user
+    for installation in installations().iter().rev() {
+        for name in ["global", app_id] {
+            if let Ok(overrides) =
+                ini::Ini::load_from_file(installation.join("overrides").join(name))
+            {
+                permissions.apply(&overrides);
+            }
+        }
+    }
+
+    Some(permissions)
+}
+
+/// A readable name for an entry of the `filesystems` permission
+pub fn filesystem_label(filesystem: &str) -> String {
+    let (location, mode) = filesystem.split_once(':').unwrap_or((filesystem, ""));
+    let label = match location {
+        "host" => i18n("All files"),
+        "host-os" => i18n("System libraries and executables"),
+        "host-etc" => i18n("System configuration"),
+        "home" => i18n("Home folder"),
+        "xdg-desktop" => i18n("Desktop folder"),
+        "xdg-documents" => i18n("Documents folder"),
+        "xdg-download" => i18n("Downloads folder"),
+        "xdg-music" => i18n("Music folder"),
+        "xdg-pictures" => i18n("Pictures folder"),
+        "xdg-videos" => i18n("Videos folder"),
+        _ => location.to_owned(),
+    };
+
+    if mode == "ro" {
+        i18n_f("{} (read-only)", &[&label])
+    } else {
+        label
+    }
+}
+
+/// A readable name for an entry of the `devices` permission
+pub fn device_label(device: &str) -> String {
+    match device {
+        "all" => i18n("All devices"),
+        "dri" => i18n("GPU acceleration"),
+        "kvm" => i18n("Virtualization"),
+        "input" => i18n("Input devices"),
+        "usb" => i18n("USB devices"),
+        "shm" => i18n("Shared memory"),
+        _ => device.to_owned(),
+    }
+}
+
+/// Opens Flatseal on the permissions of the app when it is installed, or the app settings of
+/// GNOME otherwise
+pub fn manage_permissions(app_id: &str) {
+    let app_id = app_id.strip_suffix(".desktop").unwrap_or(app_id);
+    let args = if is_installed(FLATSEAL_APP_ID) {
+        ["flatpak", "run", FLATSEAL_APP_ID, app_id].to_vec()
+    } else {
+        ["gnome-control-center", "applications", app_id].to_vec()
+    };
+
+    // Both live on the host, not in the sandbox
+    let description = args.join(" ");
+    match host_subprocess(&args, gio::SubprocessFlags::STDERR_SILENCE) {
+        Ok(subprocess) => subprocess.wait_check_async(None::<&gio::Cancellable>, move |result| {
+            if let Err(e) = result {
+                g_warning!(
+                    "MissionCenter::FlatpakPermissions",
+                    "Failed to run `{}`: {}",
+                    description,
+                    e
+                );
+            }
+        }),
+        Err(e) => {
+            g_warning!(
+                "MissionCenter::FlatpakPermissions",
+                "Failed to run `{}`: {}",
+                description,
+                e
+            );
+        }
+    }
+}
@@ -0,0 +1,242 @@
+/* table_view/highlight_rules.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gtk::glib::{self, g_critical, g_warning};
+use gtk::prelude::*;
+use regex::Regex;
+
+use crate::settings;
+use crate::table_view::row_model::{ContentType, RowModel};
+
+pub const SETTINGS_KEY: &str = "highlight-rules";
+
+/// The CSS classes a highlight can add to the name of a row, see `style.css`
+pub const HIGHLIGHT_CSS_CLASSES: [&str; 5] = [
+    "highlight-accent",
+    "highlight-success",
+    "highlight-warning",
+    "highlight-error",
+    "highlight-bold",
+];
+
+thread_local! {
+    static COMPILED: RefCell<Option<(glib::Variant, Rc<Vec<CompiledRule>>)>> = RefCell::new(None);
+}
+
+/// What the pattern of a rule is matched against
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RuleField {
+    Name,
+    CommandLine,
+}
+
+impl RuleField {
+    pub const ALL: [RuleField; 2] = [RuleField::Name, RuleField::CommandLine];
+
+    fn key(&self) -> &'static str {
+        match self {
+            RuleField::Name => "name",
+            RuleField::CommandLine => "command-line",
+        }
+    }
+
+    fn from_key(key: &str) -> Self {
+        match key {
+            "command-line" => RuleField::CommandLine,
+            _ => RuleField::Name,
+        }
+    }
+}
+
+/// The colors of the style, so highlights work with both the light and the dark style
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum HighlightColor {
+    Accent,
+    Success,
+    Warning,
+    Error,
+}
+
+impl HighlightColor {
+    pub const ALL: [HighlightColor; 4] = [
+        HighlightColor::Accent,
+        HighlightColor::Success,
+        HighlightColor::Warning,
+        HighlightColor::Error,
+    ];
+
+    fn key(&self) -> &'static str {
+        match self {
+            HighlightColor::Accent => "accent",
+            HighlightColor::Success => "success",
+            HighlightColor::Warning => "warning",
+            HighlightColor::Error => "error",
+        }
+    }
+
+    fn from_key(key: &str) -> Self {
+        match key {
+            "success" => HighlightColor::Success,
+            "warning" => HighlightColor::Warning,
+            "error" => HighlightColor::Error,
+            _ => HighlightColor::Accent,
+        }
+    }
+
+    fn css_class(&self) -> &'static str {
+        match self {
+            HighlightColor::Accent => "highlight-accent",
+            HighlightColor::Success => "highlight-success",
+            HighlightColor::Warning => "highlight-warning",
+            HighlightColor::Error => "highlight-error",
+        }
+    }
+}
+
+/// Makes the processes whose name or command line match `pattern` stand out, without hiding
+/// any of the others
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HighlightRule {
+    pub pattern: String,
+    pub field: RuleField,
+    pub color: HighlightColor,
+    pub bold: bool,
+}
+
+impl HighlightRule {
+    /// The CSS classes of the highlight
+    pub fn css_classes(&self) -> Vec<&'static str> {
+        let mut classes = vec![self.color.css_class()];
+        if self.bold {
+            classes.push("highlight-bold");
+        }
+        classes
+    }
+}
+
+struct CompiledRule {
+    regex: Regex,
+    rule: HighlightRule,
+}
+
+/// Whether `pattern` can be used for a rule, the error message if not
+pub fn validate(pattern: &str) -> Result<(), String> {
+    Regex::new(pattern).map(|_| ()).map_err(|e| e.to_string())
+}
+
+pub fn load() -> Vec<HighlightRule> {
+    settings!()
+        .value(SETTINGS_KEY)
+        .get::<Vec<(String, String, String, bool)>>()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(pattern, field, color, bold)| HighlightRule {
+            pattern,
+            field: RuleField::from_key(&field),
+            color: HighlightColor::from_key(&color),
+            bold,
+        })
+        .collect()
+}
+
+pub fn save(rules: &[HighlightRule]) {
+    let value = rules
+        .iter()
+        .map(|rule| {
+            (
+                rule.pattern.clone(),
+                rule.field.key().to_owned(),
+                rule.color.key().to_owned(),
+                rule.bold,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    if let Err(e) = settings!().set_value(SETTINGS_KEY, &value.to_variant()) {
+        g_critical!(
+            "MissionCenter::HighlightRules",
+            "Failed to set {} setting: {}",
+            SETTINGS_KEY,
+            e
+        );
+    }
+}
+
+// Rules are compiled once for each change of the setting, and skipped if they stopped compiling,
+// e.g. after an update of the regex crate
+fn compiled_rules() -> Rc<Vec<CompiledRule>> {
+    let value = settings!().value(SETTINGS_KEY);
+
+    COMPILED.with(|compiled| {
+        if let Some((cached_value, rules)) = compiled.borrow().as_ref() {
+            if *cached_value == value {
+                return rules.clone();
+            }
+        }
+
+        let rules = Rc::new(
+            load()
+                .into_iter()
+                .filter_map(|rule| match Regex::new(&rule.pattern) {
+                    Ok(regex) => Some(CompiledRule { regex, rule }),
+                    Err(e) => {
+                        g_warning!(
+                            "MissionCenter::HighlightRules",
+                            "Skipping highlight rule `{}`: {}",
+                            rule.pattern,
+                            e
+                        );
+                        None
+                    }
+                })
+                .collect(),
+        );
+        compiled.replace(Some((value, rules.clone())));
+        rules
+    })
+}
+
+/// The CSS classes of the first rule that matches the row, empty if none does. Only processes
+/// and apps are highlighted, services are matched by their processes.
+pub fn css_classes(row_model: &RowModel) -> Vec<&'static str> {
+    let content_type = row_model.content_type();
+    if content_type != ContentType::Process && content_type != ContentType::App {
+        return vec![];
+    }
+
+    let rules = compiled_rules();
+    if rules.is_empty() {
+        return vec![];
+    }
+
+    let name = row_model.name();
+    let command_line = row_model.command_line();
+    rules
+        .iter()
+        .find(|compiled| match compiled.rule.field {
+            RuleField::Name => compiled.regex.is_match(&name),
+            RuleField::CommandLine => compiled.regex.is_match(&command_line),
+        })
+        .map(|compiled| compiled.rule.css_classes())
+        .unwrap_or_default()
+}
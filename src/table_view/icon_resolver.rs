@@ -0,0 +1,191 @@
+/* table_view/icon_resolver.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use gtk::prelude::*;
+use gtk::{gdk, gio};
+use magpie_types::processes::Process;
+
+// Enough for every distinct executable on a busy system, lookups are only repeated for the
+// ones that dropped out
+const CACHE_CAPACITY: usize = 1024;
+
+thread_local! {
+    static CACHE: RefCell<IconCache> = RefCell::new(IconCache::default());
+    static DESKTOP_ICONS: RefCell<Option<Rc<HashMap<String, String>>>> = RefCell::new(None);
+    static MONITORING_APPS: Cell<bool> = Cell::new(false);
+}
+
+#[derive(Default)]
+struct IconCache {
+    entries: HashMap<String, (Option<Rc<str>>, Cell<u64>)>,
+    clock: u64,
+}
+
+impl IconCache {
+    fn get_or_insert_with(
+        &mut self,
+        key: &str,
+        resolve: impl FnOnce() -> Option<Rc<str>>,
+    ) -> Option<Rc<str>> {
+        self.clock += 1;
+
+        if let Some((icon, last_used)) = self.entries.get(key) {
+            last_used.set(self.clock);
+            return icon.clone();
+        }
+
+        if self.entries.len() >= CACHE_CAPACITY {
+            let least_recently_used = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| last_used.get())
+                .map(|(key, _)| key.clone());
+            if let Some(key) = least_recently_used {
+                self.entries.remove(&key);
+            }
+        }
+
+        let icon = resolve();
+        self.entries.insert(key.to_owned(), (icon.clone(), Cell::new(self.clock)));
+        icon
+    }
+}
+
+/// The icon of the app a process that is not part of a running app belongs to, e.g. a
+/// background service or a command line tool. `None` if nothing better than the generic
+/// executable icon is found.
+pub fn process_icon(process: &Process) -> Option<Rc<str>> {
+    let exe_name = process.exe.rsplit('/').next().unwrap_or_default();
+    let command_name = process
+        .cmd
+        .first()
+        .and_then(|cmd| cmd.rsplit('/').next())
+        .unwrap_or_default();
+    let key = if process.exe.is_empty() {
+        process.name.as_str()
+    } else {
+        process.exe.as_str()
+    };
+
+    CACHE.with(|cache| {
+        cache.borrow_mut().get_or_insert_with(key, || {
+            resolve(&[exe_name, process.name.trim(), command_name])
+        })
+    })
+}
+
+/// The icon of an app Magpie did not find an icon for, looked up by its ID
+pub fn app_icon(app_id: &str) -> Option<Rc<str>> {
+    let key = format!("app:{}", app_id);
+    CACHE.with(|cache| cache.borrow_mut().get_or_insert_with(&key, || resolve(&[app_id])))
+}
+
+// Desktop files know the icon of their app by its ID, executable or window class, which is
+// usually the name of the executable too. What is left is tried against the icon theme.
+fn resolve(candidates: &[&str]) -> Option<Rc<str>> {
+    let candidates = candidates
+        .iter()
+        .map(|candidate| candidate.trim().to_lowercase())
+        .filter(|candidate| !candidate.is_empty())
+        .collect::<Vec<_>>();
+
+    let desktop_icons = desktop_icons();
+    if let Some(icon) = candidates
+        .iter()
+        .find_map(|candidate| desktop_icons.get(candidate))
+    {
+        return Some(icon.as_str().into());
+    }
+
+    let icon_theme = gtk::IconTheme::for_display(&gdk::Display::default()?);
+    candidates
+        .iter()
+        .find(|candidate| icon_theme.has_icon(candidate))
+        .map(|candidate| candidate.as_str().into())
+}
+
+fn desktop_icons() -> Rc<HashMap<String, String>> {
+    DESKTOP_ICONS.with(|desktop_icons| {
+        if let Some(icons) = desktop_icons.borrow().as_ref() {
+            return icons.clone();
+        }
+
+        // Apps installed or removed later change what processes resolve to
+        if !MONITORING_APPS.replace(true) {
+            gio::AppInfoMonitor::get().connect_changed(|_| {
+                DESKTOP_ICONS.with(|desktop_icons| desktop_icons.replace(None));
+                CACHE.with(|cache| cache.replace(IconCache::default()));
+            });
+        }
+
+        let icons = Rc::new(index_desktop_files());
+        desktop_icons.replace(Some(icons.clone()));
+        icons
+    })
+}
+
+fn index_desktop_files() -> HashMap<String, String> {
+    let mut icons = HashMap::new();
+
+    for app_info in gio::AppInfo::all() {
+        let Some(icon) = app_info.icon().and_then(|icon| icon_name(&icon)) else {
+            continue;
+        };
+
+        let mut keys = vec![];
+        if let Some(id) = app_info.id() {
+            let id = id.trim_end_matches(".desktop").to_lowercase();
+            // `org.gnome.Nautilus` is also known as `nautilus`
+            if let Some(last) = id.rsplit('.').next() {
+                keys.push(last.to_owned());
+            }
+            keys.push(id);
+        }
+        if let Some(executable) = app_info.executable().file_name() {
+            keys.push(executable.to_string_lossy().to_lowercase());
+        }
+        if let Some(wm_class) = app_info
+            .downcast_ref::<gio::DesktopAppInfo>()
+            .and_then(|app_info| app_info.startup_wm_class())
+        {
+            keys.push(wm_class.to_lowercase());
+        }
+
+        for key in keys {
+            icons.entry(key).or_insert_with(|| icon.clone());
+        }
+    }
+
+    icons
+}
+
+fn icon_name(icon: &gio::Icon) -> Option<String> {
+    if let Some(themed_icon) = icon.downcast_ref::<gio::ThemedIcon>() {
+        return themed_icon.names().first().map(|name| name.to_string());
+    }
+
+    icon.downcast_ref::<gio::FileIcon>()
+        .and_then(|file_icon| file_icon.file().path())
+        .map(|path| path.to_string_lossy().into_owned())
+}
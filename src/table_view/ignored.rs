@@ -0,0 +1,67 @@
+/* table_view/ignored.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use gtk::glib::g_critical;
+use gtk::prelude::*;
+
+use crate::magpie_client::{IgnoreList, KERNEL_THREADS_PID};
+use crate::settings;
+use crate::table_view::{ContentType, RowModel};
+
+/// The entry of the ignore list that hides the process or service of the row, apps are made
+/// of several processes and can't be ignored as a whole. Neither can the row of the kernel
+/// threads, they are hidden with a setting of their own.
+pub fn ignore_entry(row_model: &RowModel) -> Option<String> {
+    match row_model.content_type() {
+        ContentType::Process if row_model.pid() == KERNEL_THREADS_PID => None,
+        ContentType::Process => IgnoreList::process_entry(
+            &row_model.exe(),
+            &row_model.command_line(),
+            &row_model.name(),
+        ),
+        ContentType::Service => Some(IgnoreList::service_entry(&row_model.name())),
+        _ => None,
+    }
+}
+
+pub fn can_ignore(row_model: &RowModel) -> bool {
+    ignore_entry(row_model).is_some()
+}
+
+pub fn set_ignored(entry: &str, ignore: bool) {
+    let settings = settings!();
+    let mut entries = settings
+        .strv("ignore-list")
+        .iter()
+        .map(|ignored| ignored.to_string())
+        .filter(|ignored| ignored != entry)
+        .collect::<Vec<_>>();
+    if ignore {
+        entries.push(entry.to_owned());
+    }
+
+    if let Err(e) = settings.set_strv("ignore-list", entries) {
+        g_critical!(
+            "MissionCenter::TableView",
+            "Failed to set ignore-list setting: {}",
+            e
+        );
+    }
+}
@@ -20,7 +20,9 @@
 
 use std::cell::RefCell;
 use std::cell::{Cell, OnceCell};
+use std::collections::{HashMap, HashSet};
 use std::fmt::Write;
+use std::rc::Rc;
 
 use adw::prelude::*;
 use arrayvec::ArrayString;
@@ -30,25 +32,49 @@ use gtk::glib::{g_warning, VariantTy, WeakRef};
 use gtk::{gdk, gio, glib, subclass::prelude::*};
 use textdistance::{Algorithm, Levenshtein};
 
-use crate::i18n::i18n;
+use crate::i18n::{i18n, i18n_f, ni18n_f};
+use crate::magpie_client::ProcField;
 use crate::{app, settings, DataType};
 
+pub use column_visibility::wait_channels_shown;
 use columns::*;
 pub use models::*;
 pub use process_action_bar::ProcessActionBar;
+pub use process_details::ProcessDetails;
 pub use process_details_dialog::ProcessDetailsDialog;
 pub use row_model::{ContentType, RowModel, RowModelBuilder, SectionType};
 pub use service_action_bar::ServiceActionBar;
 pub use service_details_dialog::ServiceDetailsDialog;
+pub use unit_origin::UnitOrigin;
 
+pub mod actions;
+mod app_windows;
+mod column_visibility;
 pub mod columns;
+mod copy_table;
+mod debug_tools;
+mod dependency_graph;
+mod file_location;
+pub mod filter_presets;
+mod flatpak_permissions;
+pub mod highlight_rules;
+mod icon_resolver;
+pub mod ignored;
 mod models;
+pub mod pinned;
+mod proc_fs;
 mod process_action_bar;
+mod process_details;
 mod process_details_dialog;
+mod row_diff;
 mod row_model;
+mod sections;
 mod service_action_bar;
 mod service_details_dialog;
 mod settings;
+mod terminal;
+mod unit_origin;
+mod zoom;
 
 #[derive(Copy, Clone, Default)]
 pub enum SettingsNamespace {
@@ -78,6 +104,15 @@ pub enum SettingsValues {
     SortingColumnName,
     SortingOrder,
     ColumnOrder,
+    HideSections,
+    FilterPresets,
+    HiddenColumns,
+    ColumnWidths,
+    Pinned,
+    SectionSorting,
+    CollapsedSections,
+    SelectedRow,
+    Zoom,
 }
 
 impl SettingsValues {
@@ -86,6 +121,15 @@ impl SettingsValues {
             SettingsValues::SortingColumnName => "sorting-column-name",
             SettingsValues::SortingOrder => "sorting-order",
             SettingsValues::ColumnOrder => "column-order",
+            SettingsValues::HideSections => "hide-sections",
+            SettingsValues::FilterPresets => "filter-presets",
+            SettingsValues::HiddenColumns => "hidden-columns",
+            SettingsValues::ColumnWidths => "column-widths",
+            SettingsValues::Pinned => "pinned",
+            SettingsValues::SectionSorting => "section-sorting",
+            SettingsValues::CollapsedSections => "collapsed-sections",
+            SettingsValues::SelectedRow => "selected-row",
+            SettingsValues::Zoom => "zoom",
         }
     }
 }
@@ -104,42 +148,170 @@ mod imp {
         #[template_child]
         pub pid_column: TemplateChild<gtk::ColumnViewColumn>,
         #[template_child]
+        pub command_line_column: TemplateChild<gtk::ColumnViewColumn>,
+        #[template_child]
         pub cpu_column: TemplateChild<gtk::ColumnViewColumn>,
         #[template_child]
+        pub cpu_time_column: TemplateChild<gtk::ColumnViewColumn>,
+        #[template_child]
+        pub context_switches_column: TemplateChild<gtk::ColumnViewColumn>,
+        #[template_child]
         pub memory_column: TemplateChild<gtk::ColumnViewColumn>,
         #[template_child]
+        pub memory_growth_column: TemplateChild<gtk::ColumnViewColumn>,
+        #[template_child]
         pub shared_memory_column: TemplateChild<gtk::ColumnViewColumn>,
         #[template_child]
+        pub swap_column: TemplateChild<gtk::ColumnViewColumn>,
+        #[template_child]
+        pub major_faults_column: TemplateChild<gtk::ColumnViewColumn>,
+        #[template_child]
+        pub wait_channel_column: TemplateChild<gtk::ColumnViewColumn>,
+        #[template_child]
+        pub unit_origin_column: TemplateChild<gtk::ColumnViewColumn>,
+        #[template_child]
+        pub start_time_column: TemplateChild<gtk::ColumnViewColumn>,
+        #[template_child]
+        pub restarts_column: TemplateChild<gtk::ColumnViewColumn>,
+        #[template_child]
+        pub state_column: TemplateChild<gtk::ColumnViewColumn>,
+        #[template_child]
         pub drive_column: TemplateChild<gtk::ColumnViewColumn>,
         #[template_child]
+        pub io_wait_column: TemplateChild<gtk::ColumnViewColumn>,
+        #[template_child]
         pub network_usage_column: TemplateChild<gtk::ColumnViewColumn>,
         #[template_child]
         pub gpu_usage_column: TemplateChild<gtk::ColumnViewColumn>,
         #[template_child]
         pub gpu_memory_column: TemplateChild<gtk::ColumnViewColumn>,
         #[template_child]
+        pub gpu_device_column: TemplateChild<gtk::ColumnViewColumn>,
+        #[template_child]
+        pub summary_cpu: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub summary_memory: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub summary_swap_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub summary_swap: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub summary_network_sent: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub summary_network_received: TemplateChild<gtk::Label>,
+        #[template_child]
         pub context_menu: TemplateChild<gtk::PopoverMenu>,
         #[template_child]
         pub app_menu_model: TemplateChild<gio::MenuModel>,
         #[template_child]
         pub service_menu_model: TemplateChild<gio::MenuModel>,
+        #[template_child]
+        pub totals_title: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub total_cpu_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub total_cpu: TemplateChild<LabelCell>,
+        #[template_child]
+        pub total_memory_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub total_memory: TemplateChild<LabelCell>,
+        #[template_child]
+        pub total_drive_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub total_drive: TemplateChild<LabelCell>,
+        #[template_child]
+        pub total_network_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub total_network: TemplateChild<LabelCell>,
+        #[template_child]
+        pub total_gpu_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub total_gpu: TemplateChild<LabelCell>,
+        #[template_child]
+        pub total_gpu_memory_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub total_gpu_memory: TemplateChild<LabelCell>,
 
         #[property(get, set)]
         pub show_column_separators: Cell<bool>,
+        #[property(get, set = Self::set_hide_sections)]
+        pub hide_sections: Cell<bool>,
+        #[property(get, set = Self::set_stable_sorting)]
+        pub stable_sorting: Cell<bool>,
+        pub sort_flags: Rc<SortFlags>,
+        #[property(get, set)]
+        pub show_totals: Cell<bool>,
+        #[property(get, set)]
+        pub show_summary: Cell<bool>,
+        #[property(get, set = Self::set_compact)]
+        pub compact: Cell<bool>,
+        /// Only the name column is shown, with the CPU and memory usage below the names, for
+        /// windows too narrow for the columns
+        #[property(get, set = Self::set_narrow)]
+        pub narrow: Cell<bool>,
+        /// The search also looks at the unit files and commands of services
+        #[property(get, set)]
+        pub search_unit_files: Cell<bool>,
+        #[property(get, set)]
+        pub search_command_line: Cell<bool>,
+        #[property(get, set)]
+        pub search_user: Cell<bool>,
+        #[property(get, set)]
+        pub search_app_id: Cell<bool>,
+        /// The PCI address of the GPU whose users are the only ones shown, empty to show all
+        #[property(get, set)]
+        pub gpu_filter: RefCell<String>,
+        /// A file or directory, only the processes using something under it are shown while set
+        #[property(get, set)]
+        pub path_filter: RefCell<String>,
         #[property(get)]
         pub selected_item: RefCell<RowModel>,
         #[property(get)]
         pub selected_item_running: Cell<bool>,
         #[property(get)]
         pub selected_item_enabled: Cell<bool>,
+        /// The number of rows the search matches, 0 while not searching
+        #[property(get)]
+        pub search_match_count: Cell<u32>,
 
         pub row_sorter: OnceCell<gtk::TreeListRowSorter>,
+        /// Sections sorted by a column of their own, the others follow the column headers
+        pub section_sorting: RefCell<HashMap<SectionType, sections::SectionSort>>,
+        pub root_model: OnceCell<gio::ListStore>,
+        pub filter_list_model: OnceCell<gtk::FilterListModel>,
+        pub custom_columns: RefCell<Vec<gtk::ColumnViewColumn>>,
+        /// The columns of the template in their original order, with their original width
+        pub default_column_layout: RefCell<Vec<(gtk::ColumnViewColumn, i32)>>,
+        pub filter_toggles: RefCell<Vec<WeakRef<gtk::ToggleButton>>>,
+        /// Lists the GPUs in the header menu of the GPU device column
+        pub gpu_filter_menu: gio::Menu,
+        /// The processes found using the path filter, `None` if they couldn't be looked for
+        pub path_filter_pids: RefCell<Option<HashSet<u32>>>,
+        /// Set from when the path filter changed until the readings have its processes
+        pub path_filter_pending: Cell<bool>,
+        /// Shown above the sections of the page, the pages fill it with the pinned entries
+        pub pinned_section: RowModel,
 
         pub use_merged_stats: Cell<bool>,
+        pub cpu_usage_mode: Cell<CpuUsageMode>,
+        core_count: Cell<u32>,
+
+        /// The icons of the apps that processes belong to, filled in by the Apps page
+        pub app_icons: RefCell<HashMap<u32, String>>,
+        /// The process rows by PID, including the ones populated when a row got expanded
+        pub process_rows: RefCell<HashMap<u32, RowModel>>,
+
+        /// Set while the pages update the models, selection changes are not made by the user then
+        pub updating: Cell<bool>,
+        /// The ID of the row the user selected last, it is selected again after each update
+        pub selected_id: RefCell<String>,
+        /// Set once the row selected in the last session was looked for, the selection is only
+        /// remembered from then on so the first row selected by default doesn't replace it
+        pub selection_restored: Cell<bool>,
 
         pub settings_namespace: Cell<SettingsNamespace>,
 
-        service_state_connections: RefCell<[Option<glib::SignalHandlerId>; 2]>,
+        service_state_connections: RefCell<[Option<glib::SignalHandlerId>; 3]>,
     }
 
     impl Default for TableView {
@@ -148,29 +320,98 @@ mod imp {
                 column_view: Default::default(),
                 name_column: Default::default(),
                 pid_column: Default::default(),
+                command_line_column: Default::default(),
                 cpu_column: Default::default(),
+                cpu_time_column: Default::default(),
+                context_switches_column: Default::default(),
                 memory_column: Default::default(),
+                memory_growth_column: Default::default(),
                 shared_memory_column: Default::default(),
+                swap_column: Default::default(),
+                major_faults_column: Default::default(),
+                wait_channel_column: Default::default(),
+                unit_origin_column: Default::default(),
+                start_time_column: Default::default(),
+                restarts_column: Default::default(),
+                state_column: Default::default(),
                 drive_column: Default::default(),
+                io_wait_column: Default::default(),
                 network_usage_column: Default::default(),
                 gpu_usage_column: Default::default(),
                 gpu_memory_column: Default::default(),
+                gpu_device_column: Default::default(),
+                summary_cpu: Default::default(),
+                summary_memory: Default::default(),
+                summary_swap_box: Default::default(),
+                summary_swap: Default::default(),
+                summary_network_sent: Default::default(),
+                summary_network_received: Default::default(),
                 context_menu: Default::default(),
                 app_menu_model: Default::default(),
                 service_menu_model: Default::default(),
+                totals_title: Default::default(),
+                total_cpu_box: Default::default(),
+                total_cpu: Default::default(),
+                total_memory_box: Default::default(),
+                total_memory: Default::default(),
+                total_drive_box: Default::default(),
+                total_drive: Default::default(),
+                total_network_box: Default::default(),
+                total_network: Default::default(),
+                total_gpu_box: Default::default(),
+                total_gpu: Default::default(),
+                total_gpu_memory_box: Default::default(),
+                total_gpu_memory: Default::default(),
 
                 show_column_separators: Cell::new(false),
+                hide_sections: Cell::new(false),
+                stable_sorting: Cell::new(true),
+                sort_flags: Rc::new(SortFlags::default()),
+                show_totals: Cell::new(false),
+                show_summary: Cell::new(true),
+                compact: Cell::new(false),
+                narrow: Cell::new(false),
+                search_unit_files: Cell::new(false),
+                search_command_line: Cell::new(false),
+                search_user: Cell::new(false),
+                search_app_id: Cell::new(false),
+                gpu_filter: RefCell::new(String::new()),
+                path_filter: RefCell::new(String::new()),
                 selected_item: RefCell::new(RowModelBuilder::new().build()),
                 selected_item_running: Cell::new(false),
                 selected_item_enabled: Cell::new(false),
+                search_match_count: Cell::new(0),
 
                 row_sorter: OnceCell::new(),
+                section_sorting: RefCell::new(HashMap::new()),
+                root_model: OnceCell::new(),
+                filter_list_model: OnceCell::new(),
+                custom_columns: RefCell::new(Vec::new()),
+                default_column_layout: RefCell::new(Vec::new()),
+                filter_toggles: RefCell::new(Vec::new()),
+                gpu_filter_menu: gio::Menu::new(),
+                path_filter_pids: RefCell::new(None),
+                path_filter_pending: Cell::new(false),
+                pinned_section: RowModelBuilder::new()
+                    .name(&i18n("Pinned"))
+                    .content_type(ContentType::SectionHeader)
+                    .section_type(SectionType::PinnedSection)
+                    .build(),
 
                 use_merged_stats: Cell::new(false),
+                cpu_usage_mode: Cell::new(CpuUsageMode::Normalized),
+                core_count: Cell::new(1),
+
+                app_icons: RefCell::new(HashMap::new()),
+                process_rows: RefCell::new(HashMap::new()),
+
+                updating: Cell::new(false),
+                selected_id: RefCell::new(String::new()),
+                selection_restored: Cell::new(false),
 
                 settings_namespace: Cell::new(Default::default()),
 
-                service_state_connections: RefCell::new([const { None }; 2]),
+                service_state_connections: RefCell::new([const { None }; 3]),
             }
         }
     }
@@ -182,6 +423,8 @@ mod imp {
         type ParentType = gtk::Box;
 
         fn class_init(klass: &mut Self::Class) {
+            LabelCell::ensure_type();
+
             klass.bind_template();
         }
 
@@ -206,48 +449,153 @@ mod imp {
         fn constructed(&self) {
             self.parent_constructed();
 
+            // Before the sorting models are set up, so that the order is known by the time they
+            // sort again
+            if let Some(column_view_sorter) = self.column_view.sorter() {
+                let sort_flags = self.sort_flags.clone();
+                column_view_sorter.connect_changed(move |sorter, _| {
+                    if let Some(sorter) = sorter.downcast_ref::<gtk::ColumnViewSorter>() {
+                        sort_flags.sort_order.set(sorter.primary_sort_order());
+                    }
+                });
+            }
+
             self.name_column
-                .set_factory(Some(&name_list_item_factory()));
+                .set_factory(Some(&name_list_item_factory(&self.obj())));
             self.name_column
-                .set_sorter(Some(&name_sorter(&self.column_view)));
+                .set_sorter(Some(&name_sorter(&self.sort_flags)));
 
             self.pid_column.set_factory(Some(&pid_list_item_factory()));
             self.pid_column
-                .set_sorter(Some(&pid_sorter(&self.column_view)));
+                .set_sorter(Some(&pid_sorter(&self.sort_flags)));
 
-            self.cpu_column.set_factory(Some(&cpu_list_item_factory()));
+            self.command_line_column
+                .set_factory(Some(&command_line_list_item_factory()));
+            self.command_line_column
+                .set_sorter(Some(&command_line_sorter(&self.sort_flags)));
+
+            let cpu_usage_mode = self.cpu_usage_mode.get();
+            self.cpu_column
+                .set_factory(Some(&cpu_list_item_factory(cpu_usage_mode)));
             self.cpu_column
-                .set_sorter(Some(&cpu_sorter(&self.column_view)));
+                .set_sorter(Some(&cpu_sorter(&self.sort_flags, cpu_usage_mode)));
 
             self.memory_column
                 .set_factory(Some(&memory_list_item_factory()));
             self.memory_column
-                .set_sorter(Some(&memory_sorter(&self.column_view)));
+                .set_sorter(Some(&memory_sorter(&self.sort_flags)));
+
+            self.cpu_time_column
+                .set_factory(Some(&cpu_time_list_item_factory()));
+            self.cpu_time_column
+                .set_sorter(Some(&cpu_time_sorter(&self.sort_flags)));
+
+            self.context_switches_column
+                .set_factory(Some(&context_switches_list_item_factory()));
+            self.context_switches_column
+                .set_sorter(Some(&context_switches_sorter(&self.sort_flags)));
+
+            self.memory_growth_column
+                .set_factory(Some(&memory_growth_list_item_factory()));
+            self.memory_growth_column
+                .set_sorter(Some(&memory_growth_sorter(&self.sort_flags)));
 
             self.shared_memory_column
                 .set_factory(Some(&shared_memory_list_item_factory()));
             self.shared_memory_column
-                .set_sorter(Some(&shared_memory_sorter(&self.column_view)));
+                .set_sorter(Some(&shared_memory_sorter(&self.sort_flags)));
+
+            self.swap_column
+                .set_factory(Some(&swap_list_item_factory()));
+            self.swap_column
+                .set_sorter(Some(&swap_sorter(&self.sort_flags)));
+
+            self.major_faults_column
+                .set_factory(Some(&major_faults_list_item_factory()));
+            self.major_faults_column
+                .set_sorter(Some(&major_faults_sorter(&self.sort_flags)));
+
+            self.state_column
+                .set_factory(Some(&state_list_item_factory()));
+            self.state_column
+                .set_sorter(Some(&state_sorter(&self.sort_flags)));
+
+            self.wait_channel_column
+                .set_factory(Some(&wait_channel_list_item_factory()));
+            self.wait_channel_column
+                .set_sorter(Some(&wait_channel_sorter(&self.sort_flags)));
+
+            self.unit_origin_column
+                .set_factory(Some(&unit_origin_list_item_factory()));
+            self.unit_origin_column
+                .set_sorter(Some(&unit_origin_sorter(&self.sort_flags)));
+
+            self.start_time_column
+                .set_factory(Some(&start_time_list_item_factory()));
+            self.start_time_column
+                .set_sorter(Some(&start_time_sorter(&self.sort_flags)));
+
+            self.restarts_column
+                .set_factory(Some(&restarts_list_item_factory()));
+            self.restarts_column
+                .set_sorter(Some(&restarts_sorter(&self.sort_flags)));
 
             self.drive_column
                 .set_factory(Some(&drive_list_item_factory()));
             self.drive_column
-                .set_sorter(Some(&drive_sorter(&self.column_view)));
+                .set_sorter(Some(&drive_sorter(&self.sort_flags)));
+
+            self.io_wait_column
+                .set_factory(Some(&io_wait_list_item_factory()));
+            self.io_wait_column
+                .set_sorter(Some(&io_wait_sorter(&self.sort_flags)));
 
             self.network_usage_column
                 .set_factory(Some(&network_list_item_factory()));
             self.network_usage_column
-                .set_sorter(Some(&network_sorter(&self.column_view)));
+                .set_sorter(Some(&network_sorter(&self.sort_flags)));
 
             self.gpu_usage_column
                 .set_factory(Some(&gpu_list_item_factory()));
             self.gpu_usage_column
-                .set_sorter(Some(&gpu_sorter(&self.column_view)));
+                .set_sorter(Some(&gpu_sorter(&self.sort_flags)));
 
             self.gpu_memory_column
                 .set_factory(Some(&gpu_memory_list_item_factory()));
             self.gpu_memory_column
-                .set_sorter(Some(&gpu_memory_sorter(&self.column_view)));
+                .set_sorter(Some(&gpu_memory_sorter(&self.sort_flags)));
+
+            self.gpu_device_column
+                .set_factory(Some(&gpu_device_list_item_factory()));
+            self.gpu_device_column
+                .set_sorter(Some(&gpu_device_sorter(&self.sort_flags)));
+
+            // Totals are only shown for the columns that are shown
+            for (column, total) in [
+                (&self.cpu_column, &self.total_cpu_box),
+                (&self.memory_column, &self.total_memory_box),
+                (&self.drive_column, &self.total_drive_box),
+                (&self.network_usage_column, &self.total_network_box),
+                (&self.gpu_usage_column, &self.total_gpu_box),
+                (&self.gpu_memory_column, &self.total_gpu_memory_box),
+            ] {
+                column
+                    .bind_property("visible", &total.get(), "visible")
+                    .sync_create()
+                    .build();
+            }
+
+            self.obj()
+                .connect_show_totals_notify(|this| this.update_totals());
+
+            connect_header_tooltips(&self.column_view, {
+                let this = self.obj().downgrade();
+                move || {
+                    this.upgrade()
+                        .map(|this| this.imp().cpu_usage_mode.get())
+                        .unwrap_or(CpuUsageMode::Normalized)
+                }
+            });
 
             let action_group = gio::SimpleActionGroup::new();
 
@@ -330,15 +678,65 @@ mod imp {
             service_toggle_group: Option<[WeakRef<gtk::ToggleButton>; TOGGLE_COUNT]>,
         ) {
             self.settings_namespace.set(settings_namespace);
+            self.section_sorting
+                .replace(sections::load_sorting(&self.obj()));
+
+            let columns = self.column_view.columns();
+            let default_column_layout = (0..columns.n_items())
+                .filter_map(|i| columns.item(i))
+                .filter_map(|item| item.downcast::<gtk::ColumnViewColumn>().ok())
+                .map(|column| {
+                    let width = column.fixed_width();
+                    (column, width)
+                })
+                .collect::<Vec<_>>();
+            for (column, _) in &default_column_layout {
+                self.restore_column_width(column);
+            }
+            self.default_column_layout.replace(default_column_layout);
 
-            self.update_column_order();
-
+            // Created first, the header menus list the sections by name
             let model = gio::ListStore::new::<RowModel>();
+            model.append(&self.pinned_section);
             model.append(section_item_1);
             model.append(section_item_2);
+            let _ = self.root_model.set(model.clone());
 
-            let tree_model = Self::create_tree_model(model);
+            self.update_custom_columns();
+            self.update_column_order();
+
+            if let Some(toggles) = service_toggle_group.as_ref() {
+                self.filter_toggles.replace(toggles.to_vec());
+            }
+
+            let tree_model = self.create_root_tree_model(&model);
             let filter_list_model = self.configure_filter(tree_model, service_toggle_group);
+            let _ = self.filter_list_model.set(filter_list_model.clone());
+            if let Some(filter) = filter_list_model.filter() {
+                filter.connect_changed({
+                    let this = self.obj().downgrade();
+                    move |_, _| {
+                        if let Some(this) = this.upgrade() {
+                            this.update_totals();
+                            this.imp().update_search_match_count();
+                        }
+                    }
+                });
+
+                // The header of the pinned section is only shown while something is pinned
+                self.pinned_section.children().connect_items_changed({
+                    let filter = filter.downgrade();
+                    move |children, _, removed, added| {
+                        let n_items = children.n_items();
+                        let was_empty = n_items + removed - added == 0;
+                        if was_empty != (n_items == 0) {
+                            if let Some(filter) = filter.upgrade() {
+                                filter.changed(gtk::FilterChange::Different);
+                            }
+                        }
+                    }
+                });
+            }
             let (sort_list_model, row_sorter) = self.setup_filter_model(filter_list_model);
             let selection_model = self.setup_selection_model(sort_list_model);
             self.column_view.set_model(Some(&selection_model));
@@ -358,6 +756,139 @@ mod imp {
             settings::configure(&self.obj());
         }
 
+        fn set_stable_sorting(&self, stable_sorting: bool) {
+            self.stable_sorting.set(stable_sorting);
+            self.sort_flags.stable_sorting.set(stable_sorting);
+        }
+
+        fn set_hide_sections(&self, hide_sections: bool) {
+            if self.hide_sections.get() == hide_sections {
+                return;
+            }
+            self.hide_sections.set(hide_sections);
+
+            if let (Some(root_model), Some(filter_list_model)) =
+                (self.root_model.get(), self.filter_list_model.get())
+            {
+                let tree_model = self.create_root_tree_model(root_model);
+                filter_list_model.set_model(Some(&tree_model));
+            }
+        }
+
+        /// Formats the cells again after the units of memory, drives or the network were changed
+        /// in the preferences. Values are only formatted when they change otherwise.
+        pub fn update_units(&self) {
+            for (column, factory) in [
+                (&self.memory_column, memory_list_item_factory()),
+                (
+                    &self.memory_growth_column,
+                    memory_growth_list_item_factory(),
+                ),
+                (
+                    &self.shared_memory_column,
+                    shared_memory_list_item_factory(),
+                ),
+                (&self.swap_column, swap_list_item_factory()),
+                (&self.drive_column, drive_list_item_factory()),
+                (&self.network_usage_column, network_list_item_factory()),
+                (&self.gpu_memory_column, gpu_memory_list_item_factory()),
+            ] {
+                column.set_factory(Some(&factory));
+            }
+            if self.narrow.get() {
+                self.name_column
+                    .set_factory(Some(&name_list_item_factory(&self.obj())));
+            }
+
+            self.obj().update_totals();
+        }
+
+        /// Shows the CPU usage per core or normalized, as configured in the settings
+        pub fn update_cpu_usage_mode(&self) {
+            let mode = CpuUsageMode::new(
+                settings!().boolean("apps-page-core-count-affects-percentages"),
+                self.core_count.get(),
+            );
+            if self.cpu_usage_mode.get() == mode {
+                return;
+            }
+            self.cpu_usage_mode.set(mode);
+
+            // The mode is captured by the cells and the sorter when they are created
+            self.cpu_column
+                .set_factory(Some(&cpu_list_item_factory(mode)));
+            self.cpu_column
+                .set_sorter(Some(&cpu_sorter(&self.sort_flags, mode)));
+            update_header_tooltips(&self.column_view, mode);
+            if self.narrow.get() {
+                self.name_column
+                    .set_factory(Some(&name_list_item_factory(&self.obj())));
+            }
+
+            self.obj().update_totals();
+        }
+
+        fn set_compact(&self, compact: bool) {
+            if self.compact.get() == compact {
+                return;
+            }
+            self.compact.set(compact);
+
+            let this = self.obj();
+            if compact {
+                this.add_css_class("compact");
+            } else {
+                this.remove_css_class("compact");
+            }
+
+            // Icons are shown or hidden when the cells are created, so recreate them
+            self.name_column
+                .set_factory(Some(&name_list_item_factory(&this)));
+        }
+
+        fn set_narrow(&self, narrow: bool) {
+            if self.narrow.get() == narrow {
+                return;
+            }
+            self.narrow.set(narrow);
+
+            let this = self.obj();
+            self.name_column
+                .set_factory(Some(&name_list_item_factory(&this)));
+            // Applies the hidden columns setting again, every other column is hidden while
+            // narrow. Before the setup the page the settings belong to is not known yet, the
+            // setup applies them then.
+            if self.filter_list_model.get().is_some() {
+                column_visibility::update(&this);
+            }
+        }
+
+        // When sections are hidden the children of both section headers are shown as a
+        // single list, so that sorting is no longer constrained by the section they belong to
+        fn create_root_tree_model(&self, root_model: &gio::ListStore) -> gtk::TreeListModel {
+            if !self.hide_sections.get() {
+                let tree_model = Self::create_tree_model(root_model.clone());
+                sections::restore_expansion(&self.obj(), &tree_model);
+                return tree_model;
+            }
+
+            let sections = gio::ListStore::new::<gio::ListModel>();
+            for section in root_model.iter::<RowModel>().flatten() {
+                // Pinned entries are duplicates of entries in the other sections, so they keep
+                // their header to tell them apart
+                if section.section_type() == SectionType::PinnedSection {
+                    let pinned_section = gio::ListStore::new::<RowModel>();
+                    pinned_section.append(&section);
+                    sections.append(&pinned_section);
+                    continue;
+                }
+
+                sections.append(&section.children());
+            }
+
+            Self::create_tree_model(gtk::FlattenListModel::new(Some(sections)))
+        }
+
         fn create_tree_model(model: impl IsA<gio::ListModel>) -> gtk::TreeListModel {
             gtk::TreeListModel::new(model, false, true, move |model_entry| {
                 let Some(row_model) = model_entry.downcast_ref::<RowModel>() else {
@@ -372,7 +903,7 @@ mod imp {
             tree_list_model: impl IsA<gio::ListModel>,
             group: Option<[WeakRef<gtk::ToggleButton>; TOGGLE_COUNT]>,
         ) -> gtk::FilterListModel {
-            let Some(window) = app!().window() else {
+            let Some(window) = self.obj().main_window() else {
                 g_critical!(
                     "MissionCenter::ProcessTree",
                     "Failed to get MissionCenterWindow instance; searching and filtering will not function"
@@ -380,9 +911,8 @@ mod imp {
                 return gtk::FilterListModel::new(Some(tree_list_model), None::<gtk::CustomFilter>);
             };
 
-            let group_clone = group.clone();
             let filter = gtk::CustomFilter::new({
-                let window = window.downgrade();
+                let this = self.obj().downgrade();
                 move |obj| {
                     let Some(row_model) = obj
                         .downcast_ref::<gtk::TreeListRow>()
@@ -392,108 +922,46 @@ mod imp {
                         return false;
                     };
 
-                    let search = || {
-                        let Some(window) = window.upgrade() else {
-                            return true;
-                        };
-
-                        let window = window.imp();
-
-                        if !window.search_button.is_active() {
-                            return true;
-                        }
-
-                        if window.header_search_entry.text().is_empty() {
-                            return true;
-                        }
-
-                        if row_model.content_type() == ContentType::SectionHeader {
-                            return true;
-                        }
-
-                        let entry_name = row_model.name().to_lowercase();
-                        let pid = row_model.pid().to_string();
-                        let search_query = window.header_search_entry.text().to_lowercase();
-
-                        if entry_name.contains(&search_query) || pid.contains(&search_query) {
-                            return true;
-                        }
-
-                        if search_query.contains(&entry_name) || search_query.contains(&pid) {
-                            return true;
-                        }
-
-                        let str_distance = Levenshtein::default()
-                            .for_str(&entry_name, &search_query)
-                            .ndist();
-                        if str_distance <= 0.6 {
-                            return true;
-                        }
-
-                        false
-                    };
-
-                    let group = group_clone.clone();
-                    let row_model_clone = row_model.clone();
-                    let filter = move || {
-                        let Some(group) = group else {
-                            return true;
-                        };
-
-                        if row_model_clone.content_type() == ContentType::SectionHeader {
-                            return true;
-                        }
-
-                        if group.iter().all(|toggle| {
-                            toggle
-                                .upgrade()
-                                .map(|toggle| !toggle.is_active())
-                                .unwrap_or(true)
-                        }) {
-                            return true;
-                        }
-
-                        let mut visible = [false; TOGGLE_COUNT];
-                        for (i, toggle) in group.iter().enumerate() {
-                            if let Some(toggle) = toggle.upgrade() {
-                                let name = toggle.widget_name();
-                                match name.as_str() {
-                                    "toggle_running" => {
-                                        visible[i] =
-                                            toggle.is_active() && row_model_clone.service_running()
-                                    }
-                                    "toggle_failed" => {
-                                        visible[i] =
-                                            toggle.is_active() && row_model_clone.service_failed()
-                                    }
-                                    "toggle_stopped" => {
-                                        visible[i] =
-                                            toggle.is_active() && row_model_clone.service_stopped()
-                                    }
-                                    "toggle_disabled" => {
-                                        visible[i] = toggle.is_active()
-                                            && !row_model_clone.service_enabled()
-                                            && !row_model_clone.service_running()
-                                            && !row_model_clone.service_failed();
-                                    }
-                                    _ => {
-                                        g_warning!(
-                                            "MissionCenter::TableView",
-                                            "Unknown toggle button: {}",
-                                            name
-                                        );
-                                    }
-                                };
-                            }
-                        }
+                    this.upgrade()
+                        .map_or(true, |this| this.imp().filter_row(&row_model))
+                }
+            });
 
-                        visible.iter().any(|b| *b)
-                    };
+            self.obj().connect_gpu_filter_notify({
+                let filter = filter.downgrade();
+                move |_| {
+                    if let Some(filter) = filter.upgrade() {
+                        filter.changed(gtk::FilterChange::Different);
+                    }
+                }
+            });
 
-                    search() && filter()
+            self.obj().connect_path_filter_notify({
+                let filter = filter.downgrade();
+                move |this| {
+                    this.path_filter_changed();
+                    if let Some(filter) = filter.upgrade() {
+                        filter.changed(gtk::FilterChange::Different);
+                    }
                 }
             });
 
+            for property in [
+                "search-unit-files",
+                "search-command-line",
+                "search-user",
+                "search-app-id",
+            ] {
+                self.obj().connect_notify_local(Some(property), {
+                    let filter = filter.downgrade();
+                    move |_, _| {
+                        if let Some(filter) = filter.upgrade() {
+                            filter.changed(gtk::FilterChange::Different);
+                        }
+                    }
+                });
+            }
+
             window.imp().header_search_entry.connect_search_changed({
                 let filter = filter.downgrade();
                 move |_| {
@@ -521,6 +989,224 @@ mod imp {
             gtk::FilterListModel::new(Some(tree_list_model), Some(filter))
         }
 
+        /// Whether the row passes the search, the GPU and path filters and the filter toggles
+        fn filter_row(&self, row_model: &RowModel) -> bool {
+            if row_model.content_type() == ContentType::SectionHeader {
+                return row_model.section_type() != SectionType::PinnedSection
+                    || row_model.children().n_items() > 0;
+            }
+
+            if let Some(search_query) = self.search_query() {
+                if !matches_search(row_model, &search_query, self.search_scope()) {
+                    return false;
+                }
+            }
+
+            self.matches_gpu_filter(row_model)
+                && self.matches_path_filter(row_model)
+                && self.matches_filter_toggles(row_model)
+        }
+
+        fn search_scope(&self) -> SearchScope {
+            SearchScope {
+                unit_file: self.search_unit_files.get(),
+                command_line: self.search_command_line.get(),
+                user: self.search_user.get(),
+                app_id: self.search_app_id.get(),
+            }
+        }
+
+        /// The lowercase search text, `None` while there is nothing to search for
+        fn search_query(&self) -> Option<String> {
+            let window = self.obj().main_window()?;
+            let window = window.imp();
+
+            if !window.search_button.is_active() {
+                return None;
+            }
+
+            let text = window.header_search_entry.text();
+            if text.is_empty() {
+                return None;
+            }
+
+            Some(text.to_lowercase())
+        }
+
+        fn matches_gpu_filter(&self, row_model: &RowModel) -> bool {
+            let gpu_filter = self.gpu_filter.borrow();
+            gpu_filter.is_empty()
+                || row_model
+                    .gpu_devices()
+                    .split(',')
+                    .any(|device| device == gpu_filter.as_str())
+        }
+
+        // Apps and parent processes pass when one of their processes uses the path, so that the
+        // processes stay in the tree
+        fn matches_path_filter(&self, row_model: &RowModel) -> bool {
+            if self.path_filter.borrow().is_empty() {
+                return true;
+            }
+            let pids = self.path_filter_pids.borrow();
+            let Some(pids) = pids.as_ref() else {
+                return false;
+            };
+
+            fn uses_path(row_model: &RowModel, pids: &HashSet<u32>) -> bool {
+                pids.contains(&row_model.pid())
+                    || row_model
+                        .children()
+                        .iter::<RowModel>()
+                        .flatten()
+                        .any(|child| uses_path(&child, pids))
+            }
+            row_model.content_type() != ContentType::Service && uses_path(row_model, pids)
+        }
+
+        // Rows pass if they are in the state of one of the active toggles, or if none is active.
+        // The session toggle is not a state, rows have to be in the session on top of that.
+        fn matches_filter_toggles(&self, row_model: &RowModel) -> bool {
+            let (session_toggles, active_toggles) = self
+                .filter_toggles
+                .borrow()
+                .iter()
+                .filter_map(|toggle| toggle.upgrade())
+                .filter(|toggle| toggle.is_active())
+                .partition::<Vec<_>, _>(|toggle| toggle.widget_name() == "toggle_session");
+            if !session_toggles.is_empty() && !row_model.in_login_session() {
+                return false;
+            }
+
+            // Origins are a filter of their own, rows must match one of them and one of the
+            // other toggles
+            let (origin_toggles, active_toggles) =
+                active_toggles.into_iter().partition::<Vec<_>, _>(|toggle| {
+                    toggle.widget_name().starts_with("toggle_origin_")
+                });
+            if !origin_toggles.is_empty()
+                && !origin_toggles.iter().any(|toggle| {
+                    let origin = match toggle.widget_name().as_str() {
+                        "toggle_origin_vendor" => UnitOrigin::Vendor,
+                        "toggle_origin_local" => UnitOrigin::Local,
+                        "toggle_origin_user" => UnitOrigin::User,
+                        "toggle_origin_masked" => UnitOrigin::Masked,
+                        _ => return false,
+                    };
+                    row_model.unit_origin() == origin
+                })
+            {
+                return false;
+            }
+            if active_toggles.is_empty() {
+                return true;
+            }
+
+            active_toggles.iter().any(|toggle| {
+                let name = toggle.widget_name();
+                match name.as_str() {
+                    "toggle_running" => row_model.service_running(),
+                    "toggle_failed" => row_model.service_failed(),
+                    "toggle_stopped" => row_model.service_stopped(),
+                    "toggle_problem_states" => row_model.problem_state(),
+                    "toggle_disabled" => {
+                        !row_model.service_enabled()
+                            && !row_model.service_running()
+                            && !row_model.service_failed()
+                            && !row_model.service_activating()
+                            && !row_model.service_deactivating()
+                    }
+                    "toggle_activating" => row_model.service_activating(),
+                    "toggle_deactivating" => row_model.service_deactivating(),
+                    _ => {
+                        g_warning!(
+                            "MissionCenter::TableView",
+                            "Unknown toggle button: {}",
+                            name
+                        );
+                        false
+                    }
+                }
+            })
+        }
+
+        /// The rows that match the search and pass the other filters, in the order they are
+        /// shown in, including those below collapsed rows
+        pub fn search_matches(&self) -> Vec<RowModel> {
+            let mut matches = Vec::new();
+            if self.search_query().is_none() {
+                return matches;
+            }
+
+            let Some(root_model) = self.root_model.get() else {
+                return matches;
+            };
+
+            let sorter = self
+                .row_sorter
+                .get()
+                .and_then(|row_sorter| row_sorter.sorter());
+            let mut top_level_rows = Vec::new();
+            for section in root_model.iter::<RowModel>().flatten() {
+                // Pinned rows are listed in their own section too
+                if section.section_type() == SectionType::PinnedSection {
+                    continue;
+                }
+
+                let rows = section.children().iter::<RowModel>().flatten();
+                if self.hide_sections.get() {
+                    // Both sections are sorted together
+                    top_level_rows.extend(rows);
+                } else {
+                    self.collect_search_matches(rows.collect(), sorter.as_ref(), &mut matches);
+                }
+            }
+            if !top_level_rows.is_empty() {
+                self.collect_search_matches(top_level_rows, sorter.as_ref(), &mut matches);
+            }
+
+            matches
+        }
+
+        fn collect_search_matches(
+            &self,
+            mut rows: Vec<RowModel>,
+            sorter: Option<&gtk::Sorter>,
+            matches: &mut Vec<RowModel>,
+        ) {
+            if let Some(sorter) = sorter {
+                rows.sort_by(|lhs, rhs| sorter.compare(lhs, rhs).into());
+            }
+
+            for row_model in rows {
+                if self.filter_row(&row_model) {
+                    matches.push(row_model.clone());
+                }
+
+                let children = row_model.children();
+                if children.n_items() > 0 {
+                    self.collect_search_matches(
+                        children.iter::<RowModel>().flatten().collect(),
+                        sorter,
+                        matches,
+                    );
+                }
+            }
+        }
+
+        fn update_search_match_count(&self) {
+            let count = if self.search_query().is_some() {
+                self.search_matches().len() as u32
+            } else {
+                0
+            };
+
+            if self.search_match_count.get() != count {
+                self.search_match_count.set(count);
+                self.obj().notify_search_match_count();
+            }
+        }
+
         fn setup_filter_model(
             &self,
             filter_list_model: impl IsA<gio::ListModel>,
@@ -563,17 +1249,62 @@ mod imp {
                 });
             }
 
-            let tree_list_sorter = gtk::TreeListRowSorter::new(column_view_sorter);
-            (
-                gtk::SortListModel::new(Some(filter_list_model), Some(tree_list_sorter.clone())),
-                tree_list_sorter,
-            )
-        }
+            // Sections keep their order whatever the sorting, rows of a section with a sorting of
+            // its own are compared by it, all the others by the column headers
+            let row_sorter = gtk::CustomSorter::new({
+                let this = self.obj().downgrade();
+                move |lhs, rhs| {
+                    let Some(this) = this.upgrade() else {
+                        return gtk::Ordering::Equal;
+                    };
+                    let imp = this.imp();
 
-        fn setup_selection_model(
-            &self,
-            sort_list_model: impl IsA<gio::ListModel>,
-        ) -> gtk::SingleSelection {
+                    if let Some(order) =
+                        sections::compare_sections(lhs, rhs, imp.hide_sections.get())
+                    {
+                        return order;
+                    }
+
+                    if !imp.hide_sections.get() {
+                        if let Some(order) = sections::compare(
+                            &imp.section_sorting.borrow(),
+                            &imp.column_view.columns(),
+                            lhs,
+                            rhs,
+                        ) {
+                            return order;
+                        }
+                    }
+
+                    imp.column_view
+                        .sorter()
+                        .map(|sorter| sorter.compare(lhs, rhs))
+                        .unwrap_or(gtk::Ordering::Equal)
+                }
+            });
+            if let Some(column_view_sorter) = column_view_sorter.as_ref() {
+                column_view_sorter.connect_changed({
+                    let row_sorter = row_sorter.downgrade();
+                    move |_, _| {
+                        // Not inverted as a whole anymore when some sections keep their order
+                        if let Some(row_sorter) = row_sorter.upgrade() {
+                            row_sorter.changed(gtk::SorterChange::Different);
+                        }
+                    }
+                });
+            }
+
+            let tree_list_sorter = gtk::TreeListRowSorter::new(Some(row_sorter));
+            (
+                gtk::SortListModel::new(Some(filter_list_model), Some(tree_list_sorter.clone())),
+                tree_list_sorter,
+            )
+        }
+
+        fn setup_selection_model(
+            &self,
+            sort_list_model: impl IsA<gio::ListModel>,
+        ) -> gtk::SingleSelection {
             let selection_model = gtk::SingleSelection::new(Some(sort_list_model));
             selection_model.set_autoselect(true);
 
@@ -633,6 +1364,18 @@ mod imp {
                                         this.notify_selected_item_enabled();
                                     }
                                 }));
+                            // The actions are unavailable while a request is pending
+                            service_state_connections[2] =
+                                Some(row_model.connect_service_pending_notify({
+                                    let this = this.downgrade();
+                                    move |_| {
+                                        let Some(this) = this.upgrade() else {
+                                            return;
+                                        };
+
+                                        this.notify_selected_item_running();
+                                    }
+                                }));
 
                             imp.selected_item_running.set(row_model.service_running());
                             imp.selected_item_enabled.set(row_model.service_enabled());
@@ -642,6 +1385,13 @@ mod imp {
                         }
                     }
 
+                    if !imp.updating.get() {
+                        imp.selected_id.replace(row_model.id().to_string());
+                        if imp.selection_restored.get() {
+                            imp.save_selected_row(&row_model);
+                        }
+                    }
+
                     imp.selected_item.replace(row_model);
                     this.notify_selected_item();
                     this.notify_selected_item_running();
@@ -655,10 +1405,20 @@ mod imp {
         pub fn update_column_titles(&self, readings: &crate::magpie_client::Readings) {
             let mut buffer = ArrayString::<128>::new();
 
+            let core_count = readings.cpu.core_usage_percent.len() as u32;
+            if core_count > 0 && self.core_count.get() != core_count {
+                self.core_count.set(core_count);
+                self.update_cpu_usage_mode();
+            }
+
             let cpu_usage = readings.cpu.total_usage_percent.round() as u32;
             let _ = write!(&mut buffer, "{}\n{}%", i18n("CPU"), cpu_usage);
             self.cpu_column.set_title(Some(buffer.as_str()));
 
+            buffer.clear();
+            let _ = write!(&mut buffer, "{}%", cpu_usage);
+            self.summary_cpu.set_label(buffer.as_str());
+
             buffer.clear();
 
             let mem_total = if readings.mem_info.mem_total > 0 {
@@ -680,6 +1440,43 @@ mod imp {
             let _ = write!(&mut buffer, "{}\n{}%", i18n("Memory"), memory_usage);
             self.memory_column.set_title(Some(buffer.as_str()));
 
+            buffer.clear();
+            let _ = write!(&mut buffer, "{}%", memory_usage);
+            self.summary_memory.set_label(buffer.as_str());
+
+            let swap_total = readings.mem_info.swap_total;
+            self.summary_swap_box.set_visible(swap_total > 0);
+            if swap_total > 0 {
+                let swap_used = swap_total.saturating_sub(readings.mem_info.swap_free);
+                let swap_usage = (swap_used as f32 * 100. / swap_total as f32).round() as u32;
+
+                buffer.clear();
+                let _ = write!(&mut buffer, "{}%", swap_usage);
+                self.summary_swap.set_label(buffer.as_str());
+            }
+
+            // The rates of the interfaces, unlike the network column which only sums up the
+            // traffic that could be attributed to a process
+            let (sent, received) = readings.network_connections.iter().fold(
+                (0., 0.),
+                |(sent, received), connection| {
+                    (
+                        sent + connection.tx_rate_bytes_ps as f32,
+                        received + connection.rx_rate_bytes_ps as f32,
+                    )
+                },
+            );
+            self.summary_network_sent
+                .set_label(&crate::to_human_readable_nice(
+                    sent,
+                    &DataType::NetworkBytesPerSecond,
+                ));
+            self.summary_network_received
+                .set_label(&crate::to_human_readable_nice(
+                    received,
+                    &DataType::NetworkBytesPerSecond,
+                ));
+
             buffer.clear();
             if readings.disks_info.is_empty() {
                 let _ = write!(&mut buffer, "{}\n0%", i18n("Drive"));
@@ -696,7 +1493,8 @@ mod imp {
 
             buffer.clear();
             if readings.running_processes.is_empty() {
-                let _ = write!(&mut buffer, "{}\n0", i18n("Network"));
+                let label = crate::to_human_readable_nice(0., &DataType::NetworkBytesPerSecond);
+                let _ = write!(&mut buffer, "{}\n{}", i18n("Network"), label);
             } else {
                 let mut sum = 0.;
                 for proc in readings.running_processes.values() {
@@ -737,6 +1535,114 @@ mod imp {
                 let _ = write!(&mut buffer, "{}\n{}%", i18n("GPU Memory"), gpu_mem_usage);
                 self.gpu_memory_column.set_title(Some(buffer.as_str()));
             }
+
+            if update_gpu_labels(&readings.gpus) {
+                self.update_gpu_filter_menu(&readings.gpus);
+            }
+
+            self.update_window_title(readings, cpu_usage);
+        }
+
+        // Task switchers and taskbars show the title, so the busiest process can be spotted
+        // without switching to the window
+        fn update_window_title(&self, readings: &crate::magpie_client::Readings, cpu_usage: u32) {
+            let Some(window) = self.obj().root().and_downcast::<gtk::Window>() else {
+                return;
+            };
+
+            let top_process = if settings!().boolean("apps-page-show-top-process-in-title") {
+                readings.running_processes.values().max_by(|lhs, rhs| {
+                    lhs.usage_stats
+                        .cpu_usage
+                        .total_cmp(&rhs.usage_stats.cpu_usage)
+                })
+            } else {
+                None
+            };
+            let title = match top_process {
+                Some(process) => i18n_f(
+                    "Mission Center — {}% CPU, {}",
+                    &[&cpu_usage.to_string(), &process.name],
+                ),
+                None => "Mission Center".to_owned(),
+            };
+
+            if window.title().as_deref() != Some(title.as_str()) {
+                window.set_title(Some(&title));
+            }
+        }
+
+        fn update_gpu_filter_menu(&self, gpus: &HashMap<String, crate::magpie_client::Gpu>) {
+            let menu = &self.gpu_filter_menu;
+            menu.remove_all();
+
+            let mut ids = gpus.keys().collect::<Vec<_>>();
+            ids.sort();
+
+            let item = gio::MenuItem::new(Some(&i18n("All GPUs")), None);
+            item.set_action_and_target_value(Some("columns.filter-gpu"), Some(&"".to_variant()));
+            menu.append_item(&item);
+            for id in ids {
+                let item = gio::MenuItem::new(Some(&gpu_label(id)), None);
+                item.set_action_and_target_value(
+                    Some("columns.filter-gpu"),
+                    Some(&id.to_variant()),
+                );
+                menu.append_item(&item);
+            }
+        }
+
+        /// Replaces the extra columns with the ones currently configured in the settings
+        pub fn update_custom_columns(&self) {
+            for column in self.custom_columns.take() {
+                self.column_view.remove_column(&column);
+            }
+
+            let custom_columns = ProcField::enabled(&settings!())
+                .into_iter()
+                .map(|field| custom_column(&self.sort_flags, field))
+                .collect::<Vec<_>>();
+            for column in &custom_columns {
+                self.restore_column_width(column);
+                self.column_view.append_column(column);
+            }
+
+            self.custom_columns.replace(custom_columns);
+
+            column_visibility::update(&self.obj());
+        }
+
+        /// Applies the width the user last gave `column`, and remembers any new width
+        fn restore_column_width(&self, column: &gtk::ColumnViewColumn) {
+            let Some(id) = column.id() else {
+                return;
+            };
+
+            let widths_key = self.format_settings_key(&SettingsValues::ColumnWidths);
+            if let Some(width) = column_widths(&widths_key).get(id.as_str()) {
+                column.set_fixed_width(*width);
+            }
+
+            column.connect_fixed_width_notify(move |column| {
+                let Some(id) = column.id() else {
+                    return;
+                };
+
+                let mut widths = column_widths(&widths_key);
+                if widths.get(id.as_str()) == Some(&column.fixed_width()) {
+                    return;
+                }
+                widths.insert(id.to_string(), column.fixed_width());
+
+                if let Err(e) = settings!().set_value(&widths_key, &widths.to_variant()) {
+                    g_critical!(
+                        "MissionCenter::TableView",
+                        "Failed to set {} setting: {}",
+                        widths_key,
+                        e
+                    );
+                }
+            });
         }
 
         pub fn update_column_order(&self) {
@@ -813,6 +1719,24 @@ mod imp {
             });
         }
 
+        /// Remembers the selected row for the next start. Processes are remembered by their
+        /// executable since their PID changes, apps by their ID and services by their name.
+        fn save_selected_row(&self, row_model: &RowModel) {
+            let Some(saved) = saved_row(row_model) else {
+                return;
+            };
+
+            let settings = settings!();
+            let key = self.format_settings_key(&SettingsValues::SelectedRow);
+            if settings.value(&key).get::<(String, String)>().as_ref() == Some(&saved) {
+                return;
+            }
+
+            if let Err(e) = settings.set_value(&key, &saved.to_variant()) {
+                g_warning!("MissionCenter::TableView", "Failed to save the selected row: {}", e);
+            }
+        }
+
         #[inline]
         pub fn format_settings_key(&self, key: &SettingsValues) -> String {
             self.settings_namespace.get().format_value(key)
@@ -827,10 +1751,94 @@ glib::wrapper! {
 }
 
 impl TableView {
+    /// The window the view is shown in, which holds the search entry and the last readings
+    fn main_window(&self) -> Option<crate::MissionCenterWindow> {
+        self.root()
+            .and_then(|root| root.downcast::<crate::MissionCenterWindow>().ok())
+            .or_else(|| app!().window())
+    }
+
+    /// Whether the window of the view shows a snapshot instead of the readings of this machine
+    pub fn is_showing_snapshot(&self) -> bool {
+        self.root()
+            .and_downcast::<crate::MissionCenterWindow>()
+            .is_some_and(|window| window.is_showing_snapshot())
+    }
+
     pub fn set_use_merged_stats(&self, use_merged: bool) {
         self.imp().use_merged_stats.set(use_merged);
     }
 
+    /// The GPUs the rows can be filtered by, kept up to date with the readings
+    pub fn gpu_filter_menu(&self) -> &gio::Menu {
+        &self.imp().gpu_filter_menu
+    }
+
+    // The processes using a path are looked for on the gather thread, so the ones using the new
+    // path are only known with the next readings. No process is shown until then.
+    fn path_filter_changed(&self) {
+        let imp = self.imp();
+
+        let path_filter = imp.path_filter.borrow().clone();
+        app!().update_path_filters();
+
+        let pending = !path_filter.is_empty() && !self.is_showing_snapshot();
+        imp.path_filter_pending.set(pending);
+        imp.path_filter_pids.replace(pending.then(HashSet::new));
+    }
+
+    /// Takes over the processes using the path filter from `readings`, as files are opened and
+    /// closed all the time. Returns whether they changed, the rows are filtered again if so.
+    pub fn refresh_path_filter(&self, readings: &crate::magpie_client::Readings) -> bool {
+        let imp = self.imp();
+
+        let path_filter = imp.path_filter.borrow().clone();
+        // A snapshot does not say which files its processes had open
+        if path_filter.is_empty() || self.is_showing_snapshot() {
+            return false;
+        }
+        // Gathered before the path filter changed, or by a client that replaced the one that
+        // was told about it
+        let Some(pids) = readings.path_filter_pids.get(&path_filter) else {
+            app!().update_path_filters();
+            return false;
+        };
+
+        if !imp.path_filter_pending.replace(false) && *imp.path_filter_pids.borrow() == *pids {
+            return false;
+        }
+        imp.path_filter_pids.replace(pids.clone());
+
+        if let Some(filter) = imp
+            .filter_list_model
+            .get()
+            .and_then(|filter_list_model| filter_list_model.filter())
+        {
+            filter.changed(gtk::FilterChange::Different);
+        }
+        true
+    }
+
+    /// The file or directory that only the processes using it are shown for, empty while none is
+    /// set
+    pub fn path_filter(&self) -> String {
+        self.imp().path_filter.borrow().clone()
+    }
+
+    /// Whether the processes using the path filter are still being looked for
+    pub fn path_filter_pending(&self) -> bool {
+        self.imp().path_filter_pending.get()
+    }
+
+    /// How many processes use the path filter, `None` if that can't be told on this machine
+    pub fn path_filter_match_count(&self) -> Option<usize> {
+        self.imp()
+            .path_filter_pids
+            .borrow()
+            .as_ref()
+            .map(|pids| pids.len())
+    }
+
     pub fn column_view(&self) -> &gtk::ColumnView {
         &self.imp().column_view
     }
@@ -839,6 +1847,622 @@ impl TableView {
     pub fn format_settings_key(&self, key: &SettingsValues) -> String {
         self.imp().format_settings_key(key)
     }
+
+    /// Makes the font of the table bigger or smaller by `steps`, for the whole page
+    pub fn zoom(&self, steps: i32) {
+        zoom::zoom_by(self, steps as f64 * zoom::ZOOM_STEP);
+    }
+
+    pub fn reset_zoom(&self) {
+        zoom::reset(self);
+    }
+
+    /// Copies the visible rows as a tab-separated table
+    pub fn copy_visible_rows(&self) {
+        copy_table::copy_visible_rows(self);
+    }
+
+    /// The search text, state toggles and sorting currently in use, as an unnamed preset
+    pub fn current_filter_preset(&self) -> filter_presets::FilterPreset {
+        let imp = self.imp();
+
+        let search = self
+            .main_window()
+            .filter(|window| window.imp().search_button.is_active())
+            .map(|window| window.imp().header_search_entry.text().to_string())
+            .unwrap_or_default();
+
+        let active_toggles = imp
+            .filter_toggles
+            .borrow()
+            .iter()
+            .filter_map(|toggle| toggle.upgrade())
+            .filter(|toggle| toggle.is_active())
+            .map(|toggle| toggle.widget_name().to_string())
+            .collect();
+
+        let sorter = imp
+            .column_view
+            .sorter()
+            .and_then(|sorter| sorter.downcast::<gtk::ColumnViewSorter>().ok());
+        let sort_column = sorter
+            .as_ref()
+            .and_then(|sorter| sorter.primary_sort_column())
+            .and_then(|column| column.id())
+            .map(|id| id.to_string())
+            .unwrap_or_default();
+        let sort_descending = sorter
+            .map(|sorter| sorter.primary_sort_order() == gtk::SortType::Descending)
+            .unwrap_or(false);
+
+        filter_presets::FilterPreset {
+            name: String::new(),
+            search,
+            active_toggles,
+            sort_column,
+            sort_descending,
+        }
+    }
+
+    pub fn apply_filter_preset(&self, preset: &filter_presets::FilterPreset) {
+        let imp = self.imp();
+
+        if let Some(window) = self.main_window() {
+            let window = window.imp();
+            window.header_search_entry.set_text(&preset.search);
+            window.search_button.set_active(!preset.search.is_empty());
+        }
+
+        for toggle in imp.filter_toggles.borrow().iter() {
+            if let Some(toggle) = toggle.upgrade() {
+                toggle.set_active(
+                    preset
+                        .active_toggles
+                        .contains(&toggle.widget_name().to_string()),
+                );
+            }
+        }
+
+        let columns = imp.column_view.columns();
+        let column = (0..columns.n_items())
+            .filter_map(|i| columns.item(i))
+            .filter_map(|item| item.downcast::<gtk::ColumnViewColumn>().ok())
+            .find(|column| column.id().as_deref() == Some(preset.sort_column.as_str()));
+        let order = if preset.sort_descending {
+            gtk::SortType::Descending
+        } else {
+            gtk::SortType::Ascending
+        };
+        imp.column_view.sort_by_column(column.as_ref(), order);
+    }
+
+    /// Restores the original order and width of the columns, the extra columns are placed after
+    /// the built-in ones
+    pub fn reset_column_layout(&self) {
+        let imp = self.imp();
+        let column_view = &imp.column_view;
+
+        let widths_key = imp.format_settings_key(&SettingsValues::ColumnWidths);
+        settings!().reset(&widths_key);
+
+        let default_column_layout = imp.default_column_layout.borrow();
+        let custom_columns = imp.custom_columns.borrow();
+
+        for (column, _) in default_column_layout.iter() {
+            column_view.remove_column(column);
+        }
+        for column in custom_columns.iter() {
+            column_view.remove_column(column);
+        }
+
+        for (column, width) in default_column_layout.iter() {
+            column.set_fixed_width(*width);
+            column_view.append_column(column);
+        }
+        for column in custom_columns.iter() {
+            column.set_fixed_width(-1);
+            column_view.append_column(column);
+        }
+    }
+
+    /// Shows the combined usage of the entries that are currently listed. Entries whose usage
+    /// is already part of the usage of another listed entry, like the processes of an app, are
+    /// only counted once.
+    pub fn update_totals(&self) {
+        let imp = self.imp();
+
+        if !self.show_totals() {
+            return;
+        }
+
+        let Some(filter_list_model) = imp.filter_list_model.get() else {
+            return;
+        };
+
+        let mut aggregated_rows = HashSet::new();
+        let mut counted_pids = HashSet::new();
+
+        let mut entry_count = 0_u32;
+        let mut cpu_usage = 0_f32;
+        let mut memory_usage = 0_u64;
+        let mut disk_usage = 0_f32;
+        let mut network_usage = 0_f32;
+        let mut gpu_usage = 0_f32;
+        let mut gpu_memory_usage = 0_u64;
+
+        for row in filter_list_model.iter::<gtk::TreeListRow>().flatten() {
+            let Some(row_model) = row.item().and_downcast::<RowModel>() else {
+                continue;
+            };
+
+            // Pinned entries are also listed in one of the other sections
+            let content_type = row_model.content_type();
+            if content_type == ContentType::SectionHeader
+                || row_model.section_type() == SectionType::PinnedSection
+            {
+                continue;
+            }
+
+            // Already counted as part of an app, a service or a merged process
+            let mut parent = row.parent();
+            let mut is_included = false;
+            while let Some(parent_row) = parent {
+                if parent_row
+                    .item()
+                    .is_some_and(|item| aggregated_rows.contains(&item))
+                {
+                    is_included = true;
+                    break;
+                }
+                parent = parent_row.parent();
+            }
+            if is_included {
+                continue;
+            }
+
+            if content_type == ContentType::Process && !counted_pids.insert(row_model.pid()) {
+                continue;
+            }
+
+            entry_count += 1;
+            cpu_usage += row_model.cpu_usage();
+            memory_usage += row_model.memory_usage();
+            disk_usage += row_model.disk_usage();
+            network_usage += row_model.network_usage();
+            gpu_usage += row_model.gpu_usage();
+            gpu_memory_usage += row_model.gpu_memory_usage();
+
+            let includes_children = match content_type {
+                ContentType::App | ContentType::Service => true,
+                // The kernel threads row is merged even when the other rows are not
+                ContentType::Process => row_model.merged(),
+                ContentType::SectionHeader => false,
+            };
+            if includes_children {
+                collect_pids(&row_model, &mut counted_pids);
+                aggregated_rows.insert(row_model.upcast::<glib::Object>());
+            }
+        }
+
+        let mut buffer = ArrayString::<64>::new();
+        let _ = write!(&mut buffer, "{}", entry_count);
+        imp.totals_title.set_label(&ni18n_f(
+            "Total of {} Entry",
+            "Total of {} Entries",
+            entry_count,
+            &[buffer.as_str()],
+        ));
+
+        imp.cpu_usage_mode.get().format(&imp.total_cpu, cpu_usage);
+        memory_label_formatter(&imp.total_memory, memory_usage.into());
+        drive_label_formatter(&imp.total_drive, disk_usage.into());
+        network_label_formatter(&imp.total_network, network_usage.into());
+        gpu_label_formatter(&imp.total_gpu, gpu_usage.into());
+        gpu_memory_label_formatter(&imp.total_gpu_memory, gpu_memory_usage.into());
+    }
+
+    /// Called when a row is expanded, fills in the children of process rows that were not kept
+    /// up to date while the row was collapsed
+    pub fn row_expanded(&self, row_model: &RowModel) {
+        row_model.set_expanded(true);
+
+        if row_model.content_type() != ContentType::Process
+            || !row_model.has_children()
+            || row_model.children().n_items() > 0
+        {
+            return;
+        }
+
+        let Some(window) = self.main_window() else {
+            return;
+        };
+
+        let imp = self.imp();
+        // Processes of a service are attributed to it on the Services page
+        let parent_service = match row_model.service_id() {
+            0 => None,
+            service_id => window.service(service_id),
+        };
+        populate_children(
+            &window.running_processes(),
+            &window.process_changes(),
+            row_model,
+            &imp.app_icons.borrow(),
+            imp.use_merged_stats.get(),
+            parent_service
+                .as_ref()
+                .map(|(service, transition)| (service, *transition)),
+            &mut imp.process_rows.borrow_mut(),
+            // New rows are sorted in place, the view does not have to be sorted again
+            &mut ChangedStats::default(),
+        );
+    }
+
+    /// The processes above process `pid` in the tree, as of the last update
+    fn process_ancestors(&self, pid: u32) -> HashSet<u32> {
+        self.main_window()
+            .map(|window| window.process_changes().ancestors([pid]))
+            .unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn cpu_usage_mode(&self) -> CpuUsageMode {
+        self.imp().cpu_usage_mode.get()
+    }
+
+    pub fn section_name(&self, section_type: SectionType) -> Option<glib::GString> {
+        self.imp()
+            .root_model
+            .get()?
+            .iter::<RowModel>()
+            .flatten()
+            .find(|section| section.section_type() == section_type)
+            .map(|section| section.name())
+    }
+
+    /// Call before the models are updated, together with `end_update()` once they are
+    pub fn begin_update(&self) {
+        let imp = self.imp();
+        imp.updating.set(true);
+        // Rows that were populated since the last update are found again by the update
+        imp.process_rows.borrow_mut().clear();
+    }
+
+    /// Selects the row the user selected last again, in case it moved or was removed and
+    /// re-added to the model (e.g. a process that got a new parent) during the update
+    pub fn end_update(&self) {
+        let imp = self.imp();
+        imp.updating.set(false);
+        imp.update_search_match_count();
+
+        let selected_id = imp.selected_id.borrow().clone();
+        if selected_id.is_empty() || imp.selected_item.borrow().id().as_str() == selected_id {
+            return;
+        }
+
+        let Some(model) = imp.column_view.model() else {
+            return;
+        };
+
+        // The row is gone for good or hidden in a collapsed parent, keep what got selected instead
+        if !select_item(&model, &selected_id) {
+            imp.selected_id
+                .replace(imp.selected_item.borrow().id().to_string());
+        }
+    }
+
+    /// Re-sorts the view, but only if the values of the column it is sorted by changed during
+    /// the last update. New and removed rows are sorted in place by the model on their own.
+    /// Rows are filtered again when process states or the GPUs in use changed while they are
+    /// filtered by them.
+    pub fn sort_if_changed(&self, changed_stats: ChangedStats) {
+        let imp = self.imp();
+
+        if changed_stats.is_empty() {
+            return;
+        }
+
+        let filtering_by_state = changed_stats.contains(ChangedStats::STATE)
+            && imp
+                .filter_toggles
+                .borrow()
+                .iter()
+                .filter_map(|toggle| toggle.upgrade())
+                .any(|toggle| toggle.is_active());
+        let filtering_by_gpu = changed_stats.contains(ChangedStats::GPU_DEVICES)
+            && !imp.gpu_filter.borrow().is_empty();
+        if filtering_by_state || filtering_by_gpu {
+            if let Some(filter) = imp
+                .filter_list_model
+                .get()
+                .and_then(|filter_list_model| filter_list_model.filter())
+            {
+                filter.changed(gtk::FilterChange::Different);
+            }
+        }
+
+        let Some(row_sorter) = imp.row_sorter.get() else {
+            return;
+        };
+
+        let sorted_column_id = imp
+            .column_view
+            .sorter()
+            .and_downcast_ref::<gtk::ColumnViewSorter>()
+            .and_then(|sorter| sorter.primary_sort_column())
+            .and_then(|column| column.id());
+
+        let mut sort_keys = sorted_column_id
+            .as_deref()
+            .and_then(sort_key_of_column)
+            .into_iter()
+            .collect::<Vec<_>>();
+        if !imp.hide_sections.get() {
+            sort_keys.extend(
+                imp.section_sorting
+                    .borrow()
+                    .values()
+                    .filter_map(|sort| sort_key_of_column(&sort.column_id)),
+            );
+        }
+
+        if sort_keys
+            .into_iter()
+            .any(|sort_key| changed_stats.contains(sort_key))
+        {
+            row_sorter.changed(gtk::SorterChange::Different);
+        }
+    }
+
+    /// How the rows of `section_type` are sorted when it doesn't follow the column headers
+    pub fn section_sort(&self, section_type: SectionType) -> Option<sections::SectionSort> {
+        self.imp()
+            .section_sorting
+            .borrow()
+            .get(&section_type)
+            .cloned()
+    }
+
+    /// Sorts the rows of one section by `sort`, or by the column headers again with `None`
+    pub fn set_section_sort(&self, section_type: SectionType, sort: Option<sections::SectionSort>) {
+        let imp = self.imp();
+        if sections::section_key(section_type).is_none() {
+            return;
+        }
+
+        {
+            let mut section_sorting = imp.section_sorting.borrow_mut();
+            match sort {
+                Some(sort) => section_sorting.insert(section_type, sort),
+                None => section_sorting.remove(&section_type),
+            };
+            sections::store_sorting(self, &section_sorting);
+        }
+
+        if let Some(row_sorter) = imp.row_sorter.get() {
+            row_sorter.changed(gtk::SorterChange::Different);
+        }
+    }
+
+    /// Expands all the ancestors of the row with the given `id`, scrolls it into view and
+    /// selects it. Returns `false` if the row does not exist or is hidden by the current filter.
+    pub fn scroll_to_and_select(&self, id: &str) -> bool {
+        let Some(root_model) = self.imp().root_model.get() else {
+            return false;
+        };
+
+        // Process rows use the PID as their ID
+        let ancestors = id
+            .parse()
+            .map(|pid| self.process_ancestors(pid))
+            .unwrap_or_default();
+
+        let mut path = Vec::new();
+        if !find_row_path(
+            self,
+            root_model,
+            &ancestors,
+            &|row_model| {
+                row_model.content_type() != ContentType::SectionHeader && row_model.id() == id
+            },
+            &mut path,
+        ) {
+            return false;
+        }
+
+        self.select_row_path(path)
+    }
+
+    /// The row with the ID `id`, `None` if the view doesn't have it. Rows under collapsed
+    /// processes are not looked for.
+    pub fn find_row(&self, id: &str) -> Option<RowModel> {
+        let root_model = self.imp().root_model.get()?;
+
+        let mut path = Vec::new();
+        find_row_path(
+            self,
+            root_model,
+            &HashSet::new(),
+            &|row_model| {
+                row_model.content_type() != ContentType::SectionHeader && row_model.id() == id
+            },
+            &mut path,
+        );
+        path.pop()
+    }
+
+    /// Selects the row that was selected when the app was closed, if it is still there. Called by
+    /// the pages once the view is filled with the first readings.
+    pub fn restore_selection(&self) {
+        let imp = self.imp();
+        if imp.selection_restored.replace(true) {
+            return;
+        }
+
+        let key = imp.format_settings_key(&SettingsValues::SelectedRow);
+        let Some(saved) = settings!().value(&key).get::<(String, String)>() else {
+            return;
+        };
+        if saved.1.is_empty() {
+            return;
+        }
+
+        let Some(root_model) = imp.root_model.get() else {
+            return;
+        };
+
+        // The process may be below collapsed rows that were not populated yet
+        let pid = match (saved.0.as_str(), self.main_window()) {
+            ("process", Some(window)) => window
+                .running_processes()
+                .values()
+                .filter(|process| process.exe == saved.1)
+                .map(|process| process.pid)
+                .min(),
+            _ => None,
+        };
+        let ancestors = pid
+            .map(|pid| self.process_ancestors(pid))
+            .unwrap_or_default();
+
+        let mut path = Vec::new();
+        if find_row_path(
+            self,
+            root_model,
+            &ancestors,
+            &|row_model| saved_row(row_model).as_ref() == Some(&saved),
+            &mut path,
+        ) {
+            self.select_row_path(path);
+        }
+    }
+
+    /// Selects the row the search matches after the selected one, or before it if `forward` is
+    /// not set, wrapping around at the ends. Returns `false` if nothing matches.
+    pub fn select_search_match(&self, forward: bool) -> bool {
+        let imp = self.imp();
+
+        let matches = imp.search_matches();
+        if matches.is_empty() {
+            return false;
+        }
+
+        let count = matches.len();
+        let selected = imp.selected_item.borrow().clone();
+        let index = match (matches.iter().position(|m| *m == selected), forward) {
+            (Some(index), true) => (index + 1) % count,
+            (Some(index), false) => (index + count - 1) % count,
+            (None, true) => 0,
+            (None, false) => count - 1,
+        };
+
+        let Some(root_model) = imp.root_model.get() else {
+            return false;
+        };
+        let ancestors = match matches[index].content_type() {
+            ContentType::Process => self.process_ancestors(matches[index].pid()),
+            _ => HashSet::new(),
+        };
+        let mut path = Vec::new();
+        if !find_row_path(
+            self,
+            root_model,
+            &ancestors,
+            &|row_model| *row_model == matches[index],
+            &mut path,
+        ) {
+            return false;
+        }
+
+        self.select_row_path(path)
+    }
+
+    // Ancestors are expanded in the unfiltered model, they may not match the search themselves
+    fn select_row_path(&self, mut path: Vec<RowModel>) -> bool {
+        let imp = self.imp();
+
+        let Some(target) = path.pop() else {
+            return false;
+        };
+
+        let Some(model) = imp.column_view.model() else {
+            g_critical!(
+                "MissionCenter::TableView",
+                "Failed to get model for `select_row_path`"
+            );
+            return false;
+        };
+        let Some(tree_model) = imp
+            .filter_list_model
+            .get()
+            .and_then(|filter_list_model| filter_list_model.model())
+        else {
+            return false;
+        };
+
+        for ancestor in &path {
+            if self.hide_sections() && ancestor.content_type() == ContentType::SectionHeader {
+                continue;
+            }
+
+            let Some(row) = find_row_position(&tree_model, ancestor)
+                .and_then(|i| tree_model.item(i))
+                .and_then(|item| item.downcast::<gtk::TreeListRow>().ok())
+            else {
+                return false;
+            };
+            self.row_expanded(ancestor);
+            row.set_expanded(true);
+        }
+
+        let Some(position) = find_row_position(&model, &target) else {
+            return false;
+        };
+
+        imp.column_view.scroll_to(
+            position,
+            None::<&gtk::ColumnViewColumn>,
+            gtk::ListScrollFlags::FOCUS | gtk::ListScrollFlags::SELECT,
+            None,
+        );
+
+        true
+    }
+}
+
+fn column_widths(widths_key: &str) -> HashMap<String, i32> {
+    settings!()
+        .value(widths_key)
+        .get::<HashMap<String, i32>>()
+        .unwrap_or_default()
+}
+
+/// The statistic the rows of a column are sorted by. Names, PIDs and command lines don't change
+/// during the lifetime of a row, so sorting by them never needs to be redone.
+fn sort_key_of_column(column_id: &str) -> Option<ChangedStats> {
+    let sort_key = match column_id {
+        "cpu" => ChangedStats::CPU,
+        "cpu_time" => ChangedStats::CPU_TIME,
+        "context_switches" => ChangedStats::CONTEXT_SWITCHES,
+        "memory" => ChangedStats::MEMORY,
+        "memory_growth" => ChangedStats::MEMORY_GROWTH,
+        "shared_memory" => ChangedStats::SHARED_MEMORY,
+        "swap" => ChangedStats::SWAP,
+        "major_faults" => ChangedStats::MAJOR_FAULTS,
+        "state" => ChangedStats::STATE,
+        "wait_channel" => ChangedStats::WAIT_CHANNEL,
+        "start_time" | "restarts" => ChangedStats::SERVICE_STATS,
+        "drive" => ChangedStats::DRIVE,
+        "io_wait" => ChangedStats::IO_WAIT,
+        "network" => ChangedStats::NETWORK,
+        "gpu" => ChangedStats::GPU,
+        "gpu_memory" => ChangedStats::GPU_MEMORY,
+        "gpu_device" => ChangedStats::GPU_DEVICES,
+        id if id.starts_with(CUSTOM_COLUMN_ID_PREFIX) => ChangedStats::CUSTOM,
+        _ => return None,
+    };
+
+    Some(sort_key)
 }
 
 fn upgrade_weak_ptr(ptr: usize) -> Option<gtk::Widget> {
@@ -893,6 +2517,122 @@ fn calculate_anchor_point(
     }
 }
 
+fn collect_pids(row_model: &RowModel, pids: &mut HashSet<u32>) {
+    for child in row_model.children().iter::<RowModel>().flatten() {
+        pids.insert(child.pid());
+        collect_pids(&child, pids);
+    }
+}
+
+/// The fields the search looks at besides the name and the PID, chosen next to the search entry
+#[derive(Debug, Copy, Clone, Default)]
+struct SearchScope {
+    unit_file: bool,
+    command_line: bool,
+    user: bool,
+    app_id: bool,
+}
+
+fn matches_search(row_model: &RowModel, search_query: &str, scope: SearchScope) -> bool {
+    let entry_name = row_model.name().to_lowercase();
+    let pid = row_model.pid().to_string();
+
+    if entry_name.contains(search_query) || pid.contains(search_query) {
+        return true;
+    }
+
+    // The fields are only read when they are searched
+    let matches = |field: glib::GString| field.to_lowercase().contains(search_query);
+    let content_type = row_model.content_type();
+    if scope.unit_file
+        && content_type == ContentType::Service
+        && (matches(row_model.file_path()) || matches(row_model.exec_start()))
+    {
+        return true;
+    }
+    if (scope.command_line && matches(row_model.command_line()))
+        || (scope.user && matches(row_model.user()))
+        || (scope.app_id && content_type == ContentType::App && matches(row_model.id()))
+    {
+        return true;
+    }
+
+    if search_query.contains(&entry_name) || search_query.contains(&pid) {
+        return true;
+    }
+
+    let str_distance = Levenshtein::default()
+        .for_str(&entry_name, search_query)
+        .ndist();
+    str_distance <= 0.6
+}
+
+/// How a row is remembered between sessions, `None` for rows that can't be found again
+fn saved_row(row_model: &RowModel) -> Option<(String, String)> {
+    let (kind, key) = match row_model.content_type() {
+        ContentType::SectionHeader => return None,
+        ContentType::App => ("app", row_model.id()),
+        ContentType::Process => ("process", row_model.exe()),
+        ContentType::Service => ("service", row_model.name()),
+    };
+
+    if key.is_empty() {
+        return None;
+    }
+
+    Some((kind.to_string(), key.to_string()))
+}
+
+// The rows of the processes in `ancestors` are populated and expanded on the way, since the
+// children of collapsed rows are only there once they are expanded
+fn find_row_path(
+    table_view: &TableView,
+    list: &gio::ListStore,
+    ancestors: &HashSet<u32>,
+    is_target: &dyn Fn(&RowModel) -> bool,
+    path: &mut Vec<RowModel>,
+) -> bool {
+    for row_model in list.iter::<RowModel>().flatten() {
+        path.push(row_model.clone());
+
+        if is_target(&row_model) {
+            return true;
+        }
+
+        if row_model.content_type() == ContentType::Process
+            && ancestors.contains(&row_model.pid())
+        {
+            table_view.row_expanded(&row_model);
+        }
+
+        if find_row_path(table_view, &row_model.children(), ancestors, is_target, path) {
+            return true;
+        }
+
+        path.pop();
+    }
+
+    false
+}
+
+fn find_row_position(model: &impl IsA<gio::ListModel>, row_model: &RowModel) -> Option<u32> {
+    for i in 0..model.n_items() {
+        let Some(item) = model
+            .item(i)
+            .and_then(|i| i.downcast::<gtk::TreeListRow>().ok())
+            .and_then(|row| row.item())
+        else {
+            continue;
+        };
+
+        if &item == row_model.upcast_ref::<Object>() {
+            return Some(i);
+        }
+    }
+
+    None
+}
+
 fn select_item(model: &gtk::SelectionModel, id: &str) -> bool {
     for i in 0..model.n_items() {
         if let Some(item) = model
@@ -18,7 +18,9 @@
  * SPDX-License-Identifier: GPL-3.0-or-later
  */
 
-use std::collections::{HashMap, HashSet};
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::time::Duration;
 
 use gtk::gio;
 use gtk::glib::g_critical;
@@ -29,75 +31,162 @@ use magpie_types::apps::App;
 use magpie_types::processes::{Process, ProcessUsageStats};
 use magpie_types::services::Service;
 
+use crate::i18n::i18n;
+use crate::magpie_client::{
+    is_kernel_threads_parent, ProcessChangeSet, ProcessState, ServiceStats, ServiceTransition,
+};
+use crate::table_view::icon_resolver;
+use crate::table_view::row_diff::{RowAction, RowDiff};
 use crate::table_view::row_model::{ContentType, RowModel, RowModelBuilder, SectionType};
 
+// Processes come and go all the time, their rows are kept around for the next ones instead of
+// being freed and allocated again
+const ROW_MODEL_POOL_CAPACITY: usize = 512;
+
+thread_local! {
+    static ROW_MODEL_POOL: RefCell<Vec<RowModel>> = RefCell::new(Vec::new());
+}
+
+/// The usage statistics that changed for at least one row of a view while it was updated. Each
+/// view collects its own, they are used to skip re-sorting views whose sort key did not change.
+#[derive(Copy, Clone, Default, Debug, Eq, PartialEq)]
+pub struct ChangedStats(u32);
+
+impl ChangedStats {
+    pub const CPU: Self = Self(1 << 0);
+    pub const MEMORY: Self = Self(1 << 1);
+    pub const SHARED_MEMORY: Self = Self(1 << 2);
+    pub const DRIVE: Self = Self(1 << 3);
+    pub const NETWORK: Self = Self(1 << 4);
+    pub const GPU: Self = Self(1 << 5);
+    pub const GPU_MEMORY: Self = Self(1 << 6);
+    pub const CUSTOM: Self = Self(1 << 7);
+    pub const SWAP: Self = Self(1 << 8);
+    pub const STATE: Self = Self(1 << 9);
+    pub const GPU_DEVICES: Self = Self(1 << 10);
+    pub const MEMORY_GROWTH: Self = Self(1 << 11);
+    pub const WAIT_CHANNEL: Self = Self(1 << 12);
+    pub const CPU_TIME: Self = Self(1 << 13);
+    pub const CONTEXT_SWITCHES: Self = Self(1 << 14);
+    pub const SERVICE_STATS: Self = Self(1 << 15);
+    pub const MAJOR_FAULTS: Self = Self(1 << 16);
+    pub const IO_WAIT: Self = Self(1 << 17);
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    #[inline]
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    #[inline]
+    fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+}
+
 pub fn update_apps(
     app_map: &HashMap<String, App>,
     process_map: &HashMap<u32, Process>,
+    changes: &ProcessChangeSet,
     process_model_map: &HashMap<u32, RowModel>,
     app_icons: &mut HashMap<u32, String>,
     list: &gio::ListStore,
+    changed_stats: &mut ChangedStats,
 ) {
     app_icons.clear();
 
-    let mut has_died = HashSet::new();
-    let mut does_exist = HashSet::new();
-
-    list.iter::<RowModel>().flatten().for_each(|row_model| {
-        let app_id = row_model.id();
-        let app_id = app_id.to_string();
-        if let Some(app) = app_map.get(&app_id) {
-            update_app(app, process_map, process_model_map, app_icons, row_model);
+    let diff = RowDiff::new(
+        list.iter::<RowModel>()
+            .flatten()
+            .map(|row_model| row_model.id().to_string()),
+        app_map.keys().cloned(),
+    );
 
-            does_exist.insert(app_id);
-        } else {
-            has_died.insert(app_id);
-        }
-    });
+    list.iter::<RowModel>()
+        .flatten()
+        .zip(&diff.rows)
+        .filter(|(_, action)| **action == RowAction::Update)
+        .for_each(|(row_model, _)| {
+            let Some(app) = app_map.get(row_model.id().as_str()) else {
+                return;
+            };
+            update_app(
+                app,
+                process_map,
+                changes,
+                process_model_map,
+                app_icons,
+                row_model,
+                changed_stats,
+            );
+        });
 
-    list.retain(|object| {
-        object
-            .downcast_ref::<RowModel>()
-            .map(|rm| !has_died.contains(rm.id().as_str()))
-            .unwrap_or(false)
-    });
+    let mut retained = diff.retained();
+    list.retain(|object| object.is::<RowModel>() && retained());
 
-    for (_, app) in app_map
-        .iter()
-        .filter(|(id, _)| !does_exist.contains(id.as_str()))
-    {
+    for app in diff.added.iter().filter_map(|id| app_map.get(id)) {
         let row_model = RowModelBuilder::new()
             .content_type(ContentType::App)
             .section_type(SectionType::FirstSection)
             .id(&app.id)
             .name(&app.name)
+            .in_login_session(app.pids.iter().any(|pid| changes.in_login_session(*pid)))
             .build();
         list.append(&row_model);
 
-        update_app(app, process_map, process_model_map, app_icons, row_model);
+        update_app(
+            app,
+            process_map,
+            changes,
+            process_model_map,
+            app_icons,
+            row_model,
+            changed_stats,
+        );
     }
 }
 
 pub fn update_processes(
     process_map: &HashMap<u32, Process>,
-    pids: HashSet<u32>,
+    changes: &ProcessChangeSet,
+    pids: &[u32],
     list: &gio::ListStore,
     app_icons: &HashMap<u32, String>,
     icon: &str,
     use_merged_stats: bool,
     section_type: SectionType,
-    parent_service: Option<&Service>,
+    parent_service: Option<(&Service, ServiceTransition)>,
+    visible: bool,
+    always_populate: &HashSet<u32>,
     model_map: &mut HashMap<u32, RowModel>,
+    changed_stats: &mut ChangedStats,
 ) {
-    let mut does_exist = HashSet::new();
-    let mut has_died = HashSet::new();
-
-    list.iter::<RowModel>().flatten().for_each(|row_model| {
-        let pid = row_model.pid();
-        if pids.contains(&pid) {
-            if let Some(process) = process_map.get(&pid) {
-                update_process(
+    let diff = RowDiff::new(
+        list.iter::<RowModel>()
+            .flatten()
+            .map(|row_model| row_model.pid()),
+        pids.iter()
+            .copied()
+            .filter(|pid| process_map.contains_key(pid)),
+    );
+
+    let mut dead_rows = vec![];
+    list.iter::<RowModel>()
+        .flatten()
+        .zip(&diff.rows)
+        .for_each(|(row_model, action)| {
+            let process = match action {
+                RowAction::Update => process_map.get(&row_model.pid()),
+                RowAction::Remove => None,
+            };
+            match process {
+                Some(process) => update_process(
                     process_map,
+                    changes,
                     &process,
                     row_model,
                     app_icons,
@@ -105,33 +194,30 @@ pub fn update_processes(
                     use_merged_stats,
                     section_type,
                     parent_service,
+                    visible,
+                    always_populate,
                     model_map,
-                );
-
-                does_exist.insert(pid);
-            } else {
-                has_died.insert(pid);
+                    changed_stats,
+                ),
+                None => dead_rows.push(row_model),
             }
-        } else {
-            has_died.insert(pid);
-        }
-    });
+        });
 
-    list.retain(|object| {
-        object
-            .downcast_ref::<RowModel>()
-            .map(|rm| !has_died.contains(&rm.pid()))
-            .unwrap_or(false)
-    });
+    let mut retained = diff.retained();
+    list.retain(|object| object.is::<RowModel>() && retained());
+    for row_model in dead_rows {
+        recycle_row_model(row_model);
+    }
 
-    for process in pids
-        .iter()
-        .filter(|pid| !does_exist.contains(pid))
-        .filter_map(|pid| process_map.get(&pid))
-    {
+    for process in diff.added.iter().filter_map(|pid| process_map.get(pid)) {
         let command_line = process.cmd.join(" ");
 
-        let pretty_name = if process.exe.is_empty() {
+        let kernel_name;
+        let pretty_name = if is_kernel_threads_parent(process) {
+            // The row groups all kernel threads
+            kernel_name = i18n("Kernel");
+            kernel_name.as_str()
+        } else if process.exe.is_empty() {
             if let Some(cmd) = process.cmd.first() {
                 let mut cmd = cmd
                     .split_ascii_whitespace()
@@ -165,6 +251,9 @@ pub fn update_processes(
             }
         };
 
+        // Neither permissions, the user nor the session change during the lifetime of a process, so only check them once
+        let field_availability = changes.field_availability(process.pid);
+
         let row_model = RowModelBuilder::new()
             .content_type(ContentType::Process)
             .section_type(section_type)
@@ -172,11 +261,20 @@ pub fn update_processes(
             .pid(process.pid)
             .name(pretty_name)
             .command_line(&command_line)
-            .build();
+            .exe(&process.exe)
+            .command_line_restricted(!field_availability.command_line)
+            .io_restricted(!field_availability.io_stats)
+            .in_login_session(changes.in_login_session(process.pid))
+            .user(changes.user_name(process.pid))
+            .build_reusing(pooled_row_model());
+        if is_kernel_threads_parent(process) {
+            row_model.start_collapsed();
+        }
         list.append(&row_model);
 
         update_process(
             process_map,
+            changes,
             &process,
             row_model,
             app_icons,
@@ -184,49 +282,244 @@ pub fn update_processes(
             use_merged_stats,
             section_type,
             parent_service,
+            visible,
+            always_populate,
             model_map,
+            changed_stats,
         );
     }
 }
 
+/// Populates the children of a process row that were not kept up to date while the row was
+/// collapsed, so that they are shown right away once it is expanded
+pub fn populate_children(
+    process_map: &HashMap<u32, Process>,
+    changes: &ProcessChangeSet,
+    row_model: &RowModel,
+    app_icons: &HashMap<u32, String>,
+    use_merged_stats: bool,
+    parent_service: Option<(&Service, ServiceTransition)>,
+    model_map: &mut HashMap<u32, RowModel>,
+    changed_stats: &mut ChangedStats,
+) {
+    let Some(process) = process_map.get(&row_model.pid()) else {
+        return;
+    };
+
+    update_processes(
+        process_map,
+        changes,
+        &process.children,
+        &row_model.children(),
+        app_icons,
+        &row_model.icon(),
+        use_merged_stats,
+        row_model.section_type(),
+        parent_service,
+        true,
+        &HashSet::new(),
+        model_map,
+        changed_stats,
+    );
+}
+
 pub fn update_services(
     process_map: &HashMap<u32, Process>,
+    changes: &ProcessChangeSet,
     services: &HashMap<u64, Service>,
+    service_commands: &HashMap<u64, String>,
+    service_transitions: &HashMap<u64, ServiceTransition>,
+    service_stats: &HashMap<u64, ServiceStats>,
+    masked_services: &HashSet<u64>,
     list: &gio::ListStore,
     app_icons: &HashMap<u32, String>,
     icon: &str,
     use_merged_stats: bool,
     section_type: SectionType,
+    changed_stats: &mut ChangedStats,
 ) {
-    let mut has_died = HashSet::new();
-    let mut does_exist = HashSet::new();
+    let (standalone, templates) = group_template_instances(services);
 
-    list.iter::<RowModel>().flatten().for_each(|row_model| {
-        let service_id = row_model.service_id();
-        if let Some(service) = services.get(&service_id) {
-            update_service(
-                process_map,
-                &row_model,
-                service,
-                app_icons,
-                icon,
-                use_merged_stats,
-            );
+    let update_rows = |services: &HashMap<u64, &Service>,
+                       list: &gio::ListStore,
+                       changed_stats: &mut ChangedStats| {
+        update_service_rows(
+            process_map,
+            changes,
+            services,
+            service_commands,
+            service_transitions,
+            service_stats,
+            masked_services,
+            list,
+            app_icons,
+            icon,
+            use_merged_stats,
+            section_type,
+            changed_stats,
+        )
+    };
 
-            does_exist.insert(service_id);
-        } else {
-            has_died.insert(service_id);
+    update_rows(&standalone, list, changed_stats);
+
+    let diff = RowDiff::new(
+        list.iter::<RowModel>()
+            .flatten()
+            .filter(|row_model| row_model.service_template())
+            .map(|row_model| row_model.name().to_string()),
+        templates.keys().map(|template| template.to_string()),
+    );
+
+    list.iter::<RowModel>()
+        .flatten()
+        .filter(|row_model| row_model.service_template())
+        .zip(&diff.rows)
+        .filter(|(_, action)| **action == RowAction::Update)
+        .for_each(|(row_model, _)| {
+            if let Some(instances) = templates.get(row_model.name().as_str()) {
+                update_rows(instances, &row_model.children(), changed_stats);
+                update_template(
+                    &row_model,
+                    changes,
+                    service_transitions,
+                    true,
+                    changed_stats,
+                );
+            }
+        });
+
+    let mut retained = diff.retained();
+    list.retain(|object| {
+        let row_model = object.downcast_ref::<RowModel>().unwrap();
+        !row_model.service_template() || retained()
+    });
+
+    for template in &diff.added {
+        let Some(instances) = templates.get(template.as_str()) else {
+            continue;
+        };
+
+        let first_instance = instances.values().next();
+        let file_path = first_instance
+            .map(|service| service.file_path())
+            .unwrap_or_default();
+        let row_model = RowModelBuilder::new()
+            .id(&format!("template:{}", template))
+            .content_type(ContentType::Service)
+            .section_type(section_type)
+            .template(true)
+            .name(template)
+            .file_path(file_path)
+            .masked(first_instance.is_some_and(|service| masked_services.contains(&service.id)))
+            .build();
+        list.append(&row_model);
+
+        update_rows(instances, &row_model.children(), changed_stats);
+        update_template(
+            &row_model,
+            changes,
+            service_transitions,
+            false,
+            changed_stats,
+        );
+    }
+}
+
+/// Splits the instances of template units, like `getty@tty1`, from the other services. They are
+/// only grouped under their template when it has more than one of them, a lone instance is shown
+/// like any other service.
+fn group_template_instances(
+    services: &HashMap<u64, Service>,
+) -> (HashMap<u64, &Service>, HashMap<&str, HashMap<u64, &Service>>) {
+    let mut standalone = HashMap::new();
+    let mut templates = HashMap::<&str, HashMap<u64, &Service>>::new();
+    for (id, service) in services {
+        match template_name(&service.name) {
+            Some(template) => {
+                templates.entry(template).or_default().insert(*id, service);
+            }
+            None => {
+                standalone.insert(*id, service);
+            }
         }
+    }
+
+    templates.retain(|_, instances| {
+        if instances.len() > 1 {
+            return true;
+        }
+        standalone.extend(instances.drain());
+        false
     });
 
+    (standalone, templates)
+}
+
+// The template of `getty@tty1.service` is `getty@`, `getty@.service` is the template itself
+fn template_name(service_name: &str) -> Option<&str> {
+    let (template, instance) = service_name.split_once('@')?;
+    let instance = instance.strip_suffix(".service").unwrap_or(instance);
+    if template.is_empty() || instance.is_empty() {
+        return None;
+    }
+
+    Some(&service_name[..template.len() + 1])
+}
+
+fn update_service_rows(
+    process_map: &HashMap<u32, Process>,
+    changes: &ProcessChangeSet,
+    services: &HashMap<u64, &Service>,
+    service_commands: &HashMap<u64, String>,
+    service_transitions: &HashMap<u64, ServiceTransition>,
+    service_stats: &HashMap<u64, ServiceStats>,
+    masked_services: &HashSet<u64>,
+    list: &gio::ListStore,
+    app_icons: &HashMap<u32, String>,
+    icon: &str,
+    use_merged_stats: bool,
+    section_type: SectionType,
+    changed_stats: &mut ChangedStats,
+) {
+    let diff = RowDiff::new(
+        list.iter::<RowModel>()
+            .flatten()
+            .filter(|row_model| !row_model.service_template())
+            .map(|row_model| row_model.service_id()),
+        services.keys().copied(),
+    );
+
+    list.iter::<RowModel>()
+        .flatten()
+        .filter(|row_model| !row_model.service_template())
+        .zip(&diff.rows)
+        .filter(|(_, action)| **action == RowAction::Update)
+        .for_each(|(row_model, _)| {
+            if let Some(service) = services.get(&row_model.service_id()) {
+                update_service(
+                    process_map,
+                    changes,
+                    &row_model,
+                    service,
+                    service_commands,
+                    service_transitions,
+                    service_stats,
+                    app_icons,
+                    icon,
+                    use_merged_stats,
+                    true,
+                    changed_stats,
+                );
+            }
+        });
+
+    let mut retained = diff.retained();
     list.retain(|object| {
-        !has_died.contains(&object.downcast_ref::<RowModel>().unwrap().service_id())
+        let row_model = object.downcast_ref::<RowModel>().unwrap();
+        row_model.service_template() || retained()
     });
 
-    for (_, service) in services
-        .iter()
-        .filter(|(_, serv)| !does_exist.contains(&serv.id))
-    {
+    for service in diff.added.iter().filter_map(|id| services.get(id)) {
         let row_model = RowModelBuilder::new()
             .id(&service.id.to_string())
             .content_type(ContentType::Service)
@@ -234,6 +527,7 @@ pub fn update_services(
             .service_id(service.id)
             .name(&service.name)
             .file_path(&service.file_path())
+            .masked(masked_services.contains(&service.id))
             .user(&service.user.clone().unwrap_or("".to_string()))
             .group(&service.group.clone().unwrap_or("".to_string()))
             .build();
@@ -241,21 +535,117 @@ pub fn update_services(
 
         update_service(
             process_map,
+            changes,
             &row_model,
             service,
+            service_commands,
+            service_transitions,
+            service_stats,
             app_icons,
             icon,
             use_merged_stats,
+            false,
+            changed_stats,
         )
     }
 }
 
+// Template rows show what their instances add up to, and are running, failed or enabled as
+// soon as one of their instances is
+fn update_template(
+    row_model: &RowModel,
+    changes: &ProcessChangeSet,
+    service_transitions: &HashMap<u64, ServiceTransition>,
+    highlight_changes: bool,
+    changed_stats: &mut ChangedStats,
+) {
+    let instances = row_model
+        .children()
+        .iter::<RowModel>()
+        .flatten()
+        .collect::<Vec<_>>();
+
+    let aggregate = Service {
+        running: instances.iter().any(|instance| instance.service_running()),
+        failed: instances.iter().any(|instance| instance.service_failed()),
+        enabled: instances.iter().any(|instance| instance.service_enabled()),
+        ..Default::default()
+    };
+    let transition = instances
+        .iter()
+        .filter_map(|instance| service_transitions.get(&instance.service_id()))
+        .copied()
+        .find(|transition| *transition != ServiceTransition::None)
+        .unwrap_or_default();
+
+    let state_changed = set_service(row_model, &aggregate, transition, changed_stats);
+    row_model.update_recently_changed(highlight_changes && state_changed);
+    row_model.set_icon(service_icon(&aggregate, transition));
+
+    let mut usage_stats = ProcessUsageStats::default();
+    let mut swap_usage = 0;
+    let mut cpu_time = 0.;
+    let mut context_switch_rate = 0.;
+    let mut major_fault_rate = 0.;
+    let mut io_wait = 0.;
+    let mut gpu_devices = BTreeSet::new();
+    let mut problem_state = false;
+    let mut descendant_count = 0;
+    // The slowest start of an instance, and the restarts of all of them
+    let mut stats = ServiceStats::default();
+    for instance in &instances {
+        usage_stats.cpu_usage += instance.cpu_usage();
+        usage_stats.memory_usage += instance.memory_usage();
+        usage_stats.shared_memory_usage += instance.shared_memory_usage();
+        usage_stats.disk_usage += instance.disk_usage();
+        usage_stats.network_usage += instance.network_usage();
+        usage_stats.gpu_usage += instance.gpu_usage();
+        usage_stats.gpu_memory_usage += instance.gpu_memory_usage();
+        swap_usage += instance.swap_usage();
+        cpu_time += instance.cpu_time();
+        context_switch_rate += instance.context_switch_rate();
+        major_fault_rate += instance.major_fault_rate();
+        io_wait += instance.io_wait();
+        gpu_devices.extend(
+            instance
+                .gpu_devices()
+                .split(',')
+                .filter(|device| !device.is_empty())
+                .map(str::to_owned),
+        );
+        problem_state |= instance.problem_state();
+        descendant_count += 1 + instance.descendant_count();
+
+        let start_time = Duration::from_micros(instance.service_start_time());
+        if instance.service_start_time() > 0 && stats.start_time < Some(start_time) {
+            stats.start_time = Some(start_time);
+        }
+        stats.restarts += instance.service_restarts();
+    }
+
+    set_stats(row_model, &usage_stats, changes, changed_stats);
+    set_swap_usage(row_model, swap_usage, changed_stats);
+    set_scheduling_stats(row_model, cpu_time, context_switch_rate, changed_stats);
+    set_wait_stats(row_model, major_fault_rate, io_wait, changed_stats);
+    set_gpu_devices(row_model, &gpu_devices, changed_stats);
+    set_state(
+        row_model,
+        ProcessState::Unknown,
+        problem_state,
+        changed_stats,
+    );
+    set_descendant_count(row_model, descendant_count);
+    set_service_stats(row_model, stats, changed_stats);
+}
+
 fn update_app(
     app: &App,
     process_map: &HashMap<u32, Process>,
+    changes: &ProcessChangeSet,
     process_model_map: &HashMap<u32, RowModel>,
     app_icons: &mut HashMap<u32, String>,
     row_model: RowModel,
+    changed_stats: &mut ChangedStats,
 ) {
     let primary_processes = primary_processes(app, process_map);
 
@@ -273,159 +663,573 @@ fn update_app(
         return;
     }
 
-    let icon = app
-        .icon
-        .as_ref()
-        .map(|i| match &i.icon {
-            Some(Icon::Path(p)) => p,
-            Some(Icon::Id(i)) => i,
-            _ => "application-x-executable",
-        })
-        .unwrap_or("application-x-executable");
+    let resolved_icon;
+    let icon = match app.icon.as_ref().and_then(|i| i.icon.as_ref()) {
+        Some(Icon::Path(p)) => p.as_str(),
+        Some(Icon::Id(i)) => i.as_str(),
+        None => {
+            resolved_icon = icon_resolver::app_icon(&app.id);
+            resolved_icon.as_deref().unwrap_or("application-x-executable")
+        }
+    };
 
     row_model.set_icon(icon);
 
-    let mut has_died = HashSet::new();
-    let mut does_exist = HashSet::new();
-
-    list.iter::<RowModel>().flatten().for_each(|row_model| {
-        if primary_processes.contains(&row_model.pid()) {
-            does_exist.insert(row_model.pid());
-        } else {
-            has_died.insert(row_model.pid());
-        }
-    });
+    let diff = RowDiff::new(
+        list.iter::<RowModel>()
+            .flatten()
+            .map(|row_model| row_model.pid()),
+        primary_processes.iter().copied(),
+    );
 
-    list.retain(|row_model| {
-        row_model
-            .downcast_ref::<RowModel>()
-            .map(|rm| !has_died.contains(&rm.pid()))
-            .unwrap_or(false)
-    });
+    let mut retained = diff.retained();
+    list.retain(|object| object.is::<RowModel>() && retained());
 
+    // App rows always hold the total usage of their processes and all of their descendants,
+    // whether or not merged stats are shown for processes, so that apps sort by what they
+    // use as a whole instead of by what their, often idle, wrapper processes use
     let mut usage_stats = ProcessUsageStats::default();
+    let mut swap_usage = 0;
+    let mut cpu_time = 0.;
+    let mut context_switch_rate = 0.;
+    let mut major_fault_rate = 0.;
+    let mut io_wait = 0.;
+    let mut gpu_devices = BTreeSet::new();
+    let mut problem_state = false;
+    let mut descendant_count = 0;
 
     for process in primary_processes
         .iter()
         .filter_map(|pid| process_map.get(pid))
     {
-        usage_stats.merge(&process.merged_usage_stats(&process_map));
+        usage_stats.merge(changes.merged_usage_stats(process));
+        swap_usage += changes.merged_swap_usage(process.pid);
+        cpu_time += changes.merged_cpu_time(process.pid);
+        context_switch_rate += changes.merged_context_switch_rate(process.pid);
+        major_fault_rate += changes.merged_major_fault_rate(process.pid);
+        io_wait += changes.merged_io_wait(process.pid);
+        gpu_devices.extend(changes.merged_gpu_devices(process.pid));
+        problem_state |= changes.has_problem_state(process.pid);
+        descendant_count += 1 + changes.descendant_count(process.pid);
         app_icons.insert(process.pid, icon.to_string());
 
-        if !does_exist.contains(&process.pid) {
+        if !diff.keeps(&process.pid) {
             if let Some(process_model) = process_model_map.get(&process.pid) {
                 list.append(process_model);
             }
         }
     }
 
-    set_stats(&row_model, &usage_stats);
+    set_stats(&row_model, &usage_stats, changes, changed_stats);
+    set_swap_usage(&row_model, swap_usage, changed_stats);
+    set_scheduling_stats(&row_model, cpu_time, context_switch_rate, changed_stats);
+    set_wait_stats(&row_model, major_fault_rate, io_wait, changed_stats);
+    set_gpu_devices(&row_model, gpu_devices, changed_stats);
+    set_state(
+        &row_model,
+        ProcessState::Unknown,
+        problem_state,
+        changed_stats,
+    );
+    set_descendant_count(&row_model, descendant_count);
 }
 
 fn update_process(
     process_map: &HashMap<u32, Process>,
+    changes: &ProcessChangeSet,
     process: &Process,
     row_model: RowModel,
     app_icons: &HashMap<u32, String>,
     icon: &str,
     use_merged_stats: bool,
     section_type: SectionType,
-    parent_service: Option<&Service>,
+    parent_service: Option<(&Service, ServiceTransition)>,
+    visible: bool,
+    always_populate: &HashSet<u32>,
     model_map: &mut HashMap<u32, RowModel>,
+    changed_stats: &mut ChangedStats,
 ) {
-    let usage_stats = if use_merged_stats {
-        &process.merged_usage_stats(&process_map)
+    // Like apps, the row of the kernel threads always shows what they use together
+    let is_kernel = is_kernel_threads_parent(process);
+    let merged = use_merged_stats || is_kernel;
+    if row_model.merged() != merged {
+        row_model.set_merged(merged);
+    }
+    let (usage_stats, swap_usage, cpu_time, context_switch_rate) = if merged {
+        (
+            changes.merged_usage_stats(process),
+            changes.merged_swap_usage(process.pid),
+            changes.merged_cpu_time(process.pid),
+            changes.merged_context_switch_rate(process.pid),
+        )
     } else {
-        &process.usage_stats
+        (
+            &process.usage_stats,
+            changes.swap_usage(process.pid),
+            changes.cpu_time(process.pid),
+            changes.context_switch_rate(process.pid),
+        )
     };
-
-    let icon = if let Some(icon) = app_icons.get(&process.pid) {
-        icon.as_str()
+    let (major_fault_rate, io_wait) = if merged {
+        (
+            changes.merged_major_fault_rate(process.pid),
+            changes.merged_io_wait(process.pid),
+        )
     } else {
-        icon
+        (
+            changes.major_fault_rate(process.pid),
+            changes.io_wait(process.pid),
+        )
     };
 
-    row_model.set_icon(icon);
-
-    set_stats(&row_model, usage_stats);
-    if let Some(parent_service) = parent_service {
-        set_service(&row_model, parent_service);
+    // Processes of apps share the icon of their app, others get the icon of the app they are
+    // from if one can be found
+    match app_icons.get(&process.pid) {
+        Some(app_icon) => row_model.set_icon(app_icon.as_str()),
+        None if is_kernel => row_model.set_icon("computer-symbolic"),
+        None => match icon_resolver::process_icon(process) {
+            Some(process_icon) => row_model.set_icon(&*process_icon),
+            None => row_model.set_icon(icon),
+        },
     }
 
-    update_processes(
-        process_map,
-        process.children.clone().drain(..).collect(),
-        &row_model.children(),
-        app_icons,
-        icon,
-        use_merged_stats,
-        section_type,
-        parent_service,
-        model_map,
+    set_stats(&row_model, usage_stats, changes, changed_stats);
+    set_swap_usage(&row_model, swap_usage, changed_stats);
+    set_scheduling_stats(&row_model, cpu_time, context_switch_rate, changed_stats);
+    set_wait_stats(&row_model, major_fault_rate, io_wait, changed_stats);
+    // Always includes the descendants, so that filtering by GPU keeps the path to its users
+    set_gpu_devices(
+        &row_model,
+        changes.merged_gpu_devices(process.pid),
+        changed_stats,
+    );
+    set_state(
+        &row_model,
+        changes.state(process.pid),
+        changes.has_problem_state(process.pid),
+        changed_stats,
     );
+    set_descendant_count(&row_model, changes.descendant_count(process.pid));
+    if visible {
+        set_wait_channel(&row_model, changes.wait_channel(process.pid), changed_stats);
+    }
+    if row_model.set_custom_values(changes.custom_values(process.pid)) {
+        changed_stats.insert(ChangedStats::CUSTOM);
+    }
+    if let Some((parent_service, transition)) = parent_service {
+        // Remembered for populating the children of the row when it gets expanded
+        if row_model.service_id() != parent_service.id {
+            row_model.set_service_id(parent_service.id);
+        }
+        set_service(&row_model, parent_service, transition, changed_stats);
+    }
+
+    let has_children = !process.children.is_empty();
+    if row_model.has_children() != has_children {
+        row_model.set_has_children(has_children);
+    }
+
+    // Children are only kept up to date while they can be shown, or when they are also listed
+    // elsewhere; the children of collapsed rows are populated once the row is expanded
+    let children_visible = visible && row_model.children_requested();
+    let children = row_model.children();
+    if !children_visible && !always_populate.contains(&process.pid) {
+        if children.n_items() > 0 {
+            children.remove_all();
+        }
+    } else if !changes.children_changed(process.pid)
+        && children.n_items() as usize == process.children.len()
+    {
+        // No child was added or removed since the last update, so there is no need to work out
+        // which rows to add or remove
+        for child_model in children.iter::<RowModel>().flatten() {
+            let Some(child) = process_map.get(&child_model.pid()) else {
+                continue;
+            };
+            update_process(
+                process_map,
+                changes,
+                child,
+                child_model,
+                app_icons,
+                icon,
+                use_merged_stats,
+                section_type,
+                parent_service,
+                children_visible,
+                always_populate,
+                model_map,
+                changed_stats,
+            );
+        }
+    } else {
+        update_processes(
+            process_map,
+            changes,
+            &process.children,
+            &children,
+            app_icons,
+            icon,
+            use_merged_stats,
+            section_type,
+            parent_service,
+            children_visible,
+            always_populate,
+            model_map,
+            changed_stats,
+        );
+    }
 
     model_map.insert(process.pid, row_model);
 }
 
 fn update_service(
     process_map: &HashMap<u32, Process>,
+    changes: &ProcessChangeSet,
     row_model: &RowModel,
     service: &Service,
+    service_commands: &HashMap<u64, String>,
+    service_transitions: &HashMap<u64, ServiceTransition>,
+    service_stats: &HashMap<u64, ServiceStats>,
     app_icons: &HashMap<u32, String>,
     icon: &str,
     use_merged_stats: bool,
+    highlight_changes: bool,
+    changed_stats: &mut ChangedStats,
 ) {
-    set_service(&row_model, service);
-    row_model.set_icon(service_icon(&service));
+    let transition = service_transitions
+        .get(&service.id)
+        .copied()
+        .unwrap_or_default();
+
+    // A restart can be over before the service is seen stopping, only its main process changes
+    let pid_changed = row_model.pid() != service.pid.unwrap_or_default();
+    let state_changed = set_service(&row_model, service, transition, changed_stats);
+    // New rows have nothing to compare against
+    row_model.update_recently_changed(highlight_changes && state_changed);
+    row_model.update_service_pending(state_changed || pid_changed);
+
+    row_model.set_icon(service_icon(&service, transition));
+    row_model.set_exec_start(
+        service_commands
+            .get(&service.id)
+            .map(String::as_str)
+            .unwrap_or_default(),
+    );
+
+    set_service_stats(
+        row_model,
+        service_stats.get(&service.id).copied().unwrap_or_default(),
+        changed_stats,
+    );
 
     row_model.set_pid(service.pid.clone().unwrap_or_default());
     row_model.set_user(service.user.clone().unwrap_or_default());
     row_model.set_group(service.group.clone().unwrap_or_default());
 
-    if let Some(pid) = service.pid {
-        if let Some(process) = process_map.get(&pid) {
-            let usage_stats = process.merged_usage_stats(&process_map);
+    let unit_pids = changes.service_processes(&service.name);
+    let pids = if !unit_pids.is_empty() {
+        // Attribute every process in the cgroup of the service to it, this includes workers
+        // that are not descendants of the main process
+        let unit_pid_set = unit_pids.iter().copied().collect::<HashSet<_>>();
 
-            set_stats(&row_model, &usage_stats);
-        } // else clear usage stats?
+        let mut usage_stats = ProcessUsageStats::default();
+        let mut swap_usage = 0;
+        let mut cpu_time = 0.;
+        let mut context_switch_rate = 0.;
+        let mut major_fault_rate = 0.;
+        let mut io_wait = 0.;
+        let mut gpu_devices = BTreeSet::new();
+        let mut problem_state = false;
+        let mut pids = Vec::new();
+        for process in unit_pids.iter().filter_map(|pid| process_map.get(pid)) {
+            usage_stats.merge(&process.usage_stats);
+            swap_usage += changes.swap_usage(process.pid);
+            cpu_time += changes.cpu_time(process.pid);
+            context_switch_rate += changes.context_switch_rate(process.pid);
+            major_fault_rate += changes.major_fault_rate(process.pid);
+            io_wait += changes.io_wait(process.pid);
+            gpu_devices.extend(changes.gpu_devices(process.pid));
+            problem_state |= changes.state(process.pid).is_problem();
 
-        let app_children = row_model.children();
+            let parent_in_unit = changes
+                .parent(process.pid)
+                .is_some_and(|parent| unit_pid_set.contains(&parent));
+            if !parent_in_unit {
+                pids.push(process.pid);
+            }
+        }
+        set_stats(&row_model, &usage_stats, changes, changed_stats);
+        set_swap_usage(&row_model, swap_usage, changed_stats);
+        set_scheduling_stats(&row_model, cpu_time, context_switch_rate, changed_stats);
+        set_wait_stats(&row_model, major_fault_rate, io_wait, changed_stats);
+        set_gpu_devices(&row_model, gpu_devices, changed_stats);
+        set_state(
+            &row_model,
+            ProcessState::Unknown,
+            problem_state,
+            changed_stats,
+        );
+        set_descendant_count(
+            &row_model,
+            pids.iter()
+                .map(|pid| 1 + changes.descendant_count(*pid))
+                .sum(),
+        );
 
-        app_children.retain(|child| {
-            child
-                .downcast_ref::<RowModel>()
-                .map(|rm| rm.pid() == pid)
-                .unwrap_or(false)
-        });
+        pids
+    } else if let Some(pid) = service.pid {
+        if let Some(process) = process_map.get(&pid) {
+            set_stats(
+                &row_model,
+                changes.merged_usage_stats(process),
+                changes,
+                changed_stats,
+            );
+            set_swap_usage(&row_model, changes.merged_swap_usage(pid), changed_stats);
+            set_scheduling_stats(
+                &row_model,
+                changes.merged_cpu_time(pid),
+                changes.merged_context_switch_rate(pid),
+                changed_stats,
+            );
+            set_wait_stats(
+                &row_model,
+                changes.merged_major_fault_rate(pid),
+                changes.merged_io_wait(pid),
+                changed_stats,
+            );
+            set_gpu_devices(&row_model, changes.merged_gpu_devices(pid), changed_stats);
+            set_state(
+                &row_model,
+                ProcessState::Unknown,
+                changes.has_problem_state(pid),
+                changed_stats,
+            );
+            set_descendant_count(&row_model, 1 + changes.descendant_count(pid));
+        } // else clear usage stats?
 
-        update_processes(
-            process_map,
-            HashSet::from([pid]),
-            &app_children,
-            app_icons,
-            icon,
-            use_merged_stats,
-            row_model.section_type(),
-            Some(service),
-            &mut HashMap::new(),
-        );
+        vec![pid]
     } else {
+        Vec::new()
+    };
+
+    if pids.is_empty() {
+        set_descendant_count(&row_model, 0);
         row_model.children().remove_all();
+        return;
+    }
+
+    update_processes(
+        process_map,
+        changes,
+        &pids,
+        &row_model.children(),
+        app_icons,
+        icon,
+        use_merged_stats,
+        row_model.section_type(),
+        Some((service, transition)),
+        row_model.children_requested(),
+        &HashSet::new(),
+        &mut HashMap::new(),
+        changed_stats,
+    );
+}
+
+// Only touch the properties that actually changed, every `set_*` call emits a notification
+// which in turn updates the bound cells
+fn set_stats(
+    row_model: &RowModel,
+    usage_stats: &ProcessUsageStats,
+    changes: &ProcessChangeSet,
+    changed_stats: &mut ChangedStats,
+) {
+    let mut changed = ChangedStats::default();
+
+    // Before the CPU usage, which the tooltip of its cells is updated along with
+    row_model.update_thermal_load(usage_stats.cpu_usage, changes.thermal_pressure());
+    if row_model.cpu_usage() != usage_stats.cpu_usage {
+        row_model.set_cpu_usage(usage_stats.cpu_usage);
+        changed.insert(ChangedStats::CPU);
+    }
+    if row_model.memory_usage() != usage_stats.memory_usage {
+        row_model.set_memory_usage(usage_stats.memory_usage);
+        changed.insert(ChangedStats::MEMORY);
+    }
+    if row_model.update_memory_growth() {
+        changed.insert(ChangedStats::MEMORY_GROWTH);
+    }
+    if row_model.shared_memory_usage() != usage_stats.shared_memory_usage {
+        row_model.set_shared_memory_usage(usage_stats.shared_memory_usage);
+        changed.insert(ChangedStats::SHARED_MEMORY);
+    }
+    if row_model.disk_usage() != usage_stats.disk_usage {
+        row_model.set_disk_usage(usage_stats.disk_usage);
+        changed.insert(ChangedStats::DRIVE);
+    }
+    if row_model.network_usage() != usage_stats.network_usage {
+        row_model.set_network_usage(usage_stats.network_usage);
+        changed.insert(ChangedStats::NETWORK);
+    }
+    if row_model.gpu_usage() != usage_stats.gpu_usage {
+        row_model.set_gpu_usage(usage_stats.gpu_usage);
+        changed.insert(ChangedStats::GPU);
+    }
+    if row_model.gpu_memory_usage() != usage_stats.gpu_memory_usage {
+        row_model.set_gpu_memory_usage(usage_stats.gpu_memory_usage);
+        changed.insert(ChangedStats::GPU_MEMORY);
+    }
+
+    changed_stats.insert(changed);
+}
+
+fn set_swap_usage(row_model: &RowModel, swap_usage: u64, changed_stats: &mut ChangedStats) {
+    if row_model.swap_usage() != swap_usage {
+        row_model.set_swap_usage(swap_usage);
+        changed_stats.insert(ChangedStats::SWAP);
+    }
+}
+
+fn set_scheduling_stats(
+    row_model: &RowModel,
+    cpu_time: f64,
+    context_switch_rate: f32,
+    changed_stats: &mut ChangedStats,
+) {
+    if row_model.cpu_time() != cpu_time {
+        row_model.set_cpu_time(cpu_time);
+        changed_stats.insert(ChangedStats::CPU_TIME);
+    }
+    if row_model.context_switch_rate() != context_switch_rate {
+        row_model.set_context_switch_rate(context_switch_rate);
+        changed_stats.insert(ChangedStats::CONTEXT_SWITCHES);
+    }
+}
+
+fn set_wait_stats(
+    row_model: &RowModel,
+    major_fault_rate: f32,
+    io_wait: f32,
+    changed_stats: &mut ChangedStats,
+) {
+    if row_model.major_fault_rate() != major_fault_rate {
+        row_model.set_major_fault_rate(major_fault_rate);
+        changed_stats.insert(ChangedStats::MAJOR_FAULTS);
+    }
+    if row_model.io_wait() != io_wait {
+        row_model.set_io_wait(io_wait);
+        changed_stats.insert(ChangedStats::IO_WAIT);
+    }
+}
+
+fn set_gpu_devices<'a>(
+    row_model: &RowModel,
+    devices: impl IntoIterator<Item = &'a String>,
+    changed_stats: &mut ChangedStats,
+) {
+    let gpu_devices = devices
+        .into_iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(",");
+    if row_model.gpu_devices() != gpu_devices {
+        row_model.set_gpu_devices(&gpu_devices);
+        changed_stats.insert(ChangedStats::GPU_DEVICES);
+    }
+}
+
+fn set_wait_channel(row_model: &RowModel, wait_channel: &str, changed_stats: &mut ChangedStats) {
+    if row_model.wait_channel() != wait_channel {
+        row_model.set_wait_channel(wait_channel);
+        changed_stats.insert(ChangedStats::WAIT_CHANNEL);
+    }
+}
+
+fn set_service_stats(row_model: &RowModel, stats: ServiceStats, changed_stats: &mut ChangedStats) {
+    let start_time = stats
+        .start_time
+        .map(|time| time.as_micros() as u64)
+        .unwrap_or_default();
+    let mut changed = false;
+    if row_model.service_start_time() != start_time {
+        row_model.set_service_start_time(start_time);
+        changed = true;
+    }
+    if row_model.service_restarts() != stats.restarts {
+        row_model.set_service_restarts(stats.restarts);
+        changed = true;
+    }
+
+    if changed {
+        changed_stats.insert(ChangedStats::SERVICE_STATS);
     }
 }
 
-fn set_stats(row_model: &RowModel, usage_stats: &ProcessUsageStats) {
-    row_model.set_cpu_usage(usage_stats.cpu_usage);
-    row_model.set_memory_usage(usage_stats.memory_usage);
-    row_model.set_shared_memory_usage(usage_stats.shared_memory_usage);
-    row_model.set_disk_usage(usage_stats.disk_usage);
-    row_model.set_network_usage(usage_stats.network_usage);
-    row_model.set_gpu_usage(usage_stats.gpu_usage);
-    row_model.set_gpu_memory_usage(usage_stats.gpu_memory_usage);
+fn set_descendant_count(row_model: &RowModel, descendant_count: u32) {
+    if row_model.descendant_count() != descendant_count {
+        row_model.set_descendant_count(descendant_count);
+    }
+}
+
+// Apps and services have no state of their own, they only track whether one of their
+// processes is in a problem state
+fn set_state(
+    row_model: &RowModel,
+    state: ProcessState,
+    problem_state: bool,
+    changed_stats: &mut ChangedStats,
+) {
+    let mut changed = false;
+    if row_model.process_state() != state {
+        row_model.set_process_state(state);
+        changed = true;
+    }
+    if row_model.problem_state() != problem_state {
+        row_model.set_problem_state(problem_state);
+        changed = true;
+    }
+
+    if changed {
+        changed_stats.insert(ChangedStats::STATE);
+    }
 }
 
-fn service_icon(service: &Service) -> String {
-    if service.running {
+fn pooled_row_model() -> Option<RowModel> {
+    let row_model = ROW_MODEL_POOL.with(|pool| pool.borrow_mut().pop());
+    crate::benchmark::count_row_model_reuse(row_model.is_some());
+
+    row_model
+}
+
+/// Puts a row that was taken out of its list, and its children, into the pool. Rows that are
+/// still referred to elsewhere, e.g. by a cell that was not unbound yet, are left alone.
+fn recycle_row_model(row_model: RowModel) {
+    if row_model.ref_count() != 1 {
+        return;
+    }
+
+    let children = row_model.children();
+    let child_rows = children.iter::<RowModel>().flatten().collect::<Vec<_>>();
+    children.remove_all();
+    for child in child_rows {
+        recycle_row_model(child);
+    }
+
+    ROW_MODEL_POOL.with(|pool| {
+        let mut pool = pool.borrow_mut();
+        if pool.len() < ROW_MODEL_POOL_CAPACITY {
+            pool.push(row_model);
+        }
+    });
+}
+
+fn service_icon(service: &Service, transition: ServiceTransition) -> String {
+    if transition == ServiceTransition::Activating {
+        "service-activating".into()
+    } else if transition == ServiceTransition::Deactivating {
+        "service-deactivating".into()
+    } else if service.running {
         "service-running".into()
     } else {
         if service.failed {
@@ -438,11 +1242,40 @@ fn service_icon(service: &Service) -> String {
     }
 }
 
-fn set_service(row_model: &RowModel, service: &Service) {
-    row_model.set_service_running(service.running);
+fn service_state(row_model: &RowModel) -> [bool; 5] {
+    [
+        row_model.service_running(),
+        row_model.service_failed(),
+        row_model.service_enabled(),
+        row_model.service_activating(),
+        row_model.service_deactivating(),
+    ]
+}
+
+// Starting and stopping services are neither counted as running nor as stopped. Returns
+// whether the state changed, the filters by state need to run again if it did.
+fn set_service(
+    row_model: &RowModel,
+    service: &Service,
+    transition: ServiceTransition,
+    changed_stats: &mut ChangedStats,
+) -> bool {
+    let previous_state = service_state(row_model);
+
+    let settled = transition == ServiceTransition::None;
+    row_model.set_service_running(service.running && settled);
     row_model.set_service_enabled(service.enabled);
     row_model.set_service_failed(service.failed);
-    row_model.set_service_stopped(!service.running && !service.failed && service.enabled);
+    row_model
+        .set_service_stopped(!service.running && !service.failed && service.enabled && settled);
+    row_model.set_service_activating(transition == ServiceTransition::Activating);
+    row_model.set_service_deactivating(transition == ServiceTransition::Deactivating);
+
+    let changed = service_state(row_model) != previous_state;
+    if changed {
+        changed_stats.insert(ChangedStats::STATE);
+    }
+    changed
 }
 
 fn primary_processes(app: &App, process_map: &HashMap<u32, Process>) -> HashSet<u32> {
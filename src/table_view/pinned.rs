@@ -0,0 +1,85 @@
+/* table_view/pinned.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::HashSet;
+
+use gtk::glib::{self, g_critical};
+use gtk::prelude::*;
+
+use crate::settings;
+use crate::table_view::{ContentType, RowModel, SettingsNamespace, SettingsValues};
+
+/// What identifies a pinned entry across restarts: the path of the executable of a process, or
+/// the name of a service. Other entries can't be pinned.
+fn pin_value(row_model: &RowModel) -> Option<(SettingsNamespace, glib::GString)> {
+    match row_model.content_type() {
+        ContentType::Process => {
+            let exe = row_model.exe();
+            (!exe.is_empty()).then_some((SettingsNamespace::AppsPage, exe))
+        }
+        ContentType::Service => Some((SettingsNamespace::ServicesPage, row_model.name())),
+        _ => None,
+    }
+}
+
+/// The executables or service names pinned on the page `namespace` belongs to
+pub fn pinned(namespace: SettingsNamespace) -> HashSet<String> {
+    settings!()
+        .strv(&namespace.format_value(&SettingsValues::Pinned))
+        .iter()
+        .map(|value| value.to_string())
+        .collect()
+}
+
+pub fn can_pin(row_model: &RowModel) -> bool {
+    pin_value(row_model)
+        .is_some_and(|(namespace, value)| !pinned(namespace).contains(value.as_str()))
+}
+
+pub fn can_unpin(row_model: &RowModel) -> bool {
+    pin_value(row_model)
+        .is_some_and(|(namespace, value)| pinned(namespace).contains(value.as_str()))
+}
+
+pub fn set_pinned(row_model: &RowModel, pin: bool) {
+    let Some((namespace, value)) = pin_value(row_model) else {
+        return;
+    };
+
+    let key = namespace.format_value(&SettingsValues::Pinned);
+    let mut pinned = settings!()
+        .strv(&key)
+        .iter()
+        .map(|pinned| pinned.to_string())
+        .filter(|pinned| pinned != value.as_str())
+        .collect::<Vec<_>>();
+    if pin {
+        pinned.push(value.to_string());
+    }
+
+    if let Err(e) = settings!().set_strv(&key, pinned) {
+        g_critical!(
+            "MissionCenter::TableView",
+            "Failed to set {} setting: {}",
+            key,
+            e
+        );
+    }
+}
@@ -0,0 +1,241 @@
+/* table_view/proc_fs.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::ffi::OsStr;
+
+use gtk::{gio, glib};
+
+/// The details of a process that only its owner or an administrator can read
+#[derive(Debug, Default, Clone)]
+pub struct RestrictedDetails {
+    pub executable: Option<String>,
+    pub bytes_read: Option<u64>,
+    pub bytes_written: Option<u64>,
+}
+
+/// Reads the details `ProcessChangeSet::field_availability` found restricted, with
+/// administrator rights asked for with `pkexec`
+pub fn read_restricted_details(
+    pid: u32,
+    on_done: impl FnOnce(Result<RestrictedDetails, glib::Error>) + 'static,
+) {
+    let pid = pid.to_string();
+    let script = r#"readlink "/proc/$1/exe"; cat "/proc/$1/io""#;
+    let argv = ["pkexec", "sh", "-c", script, "sh", &pid].map(OsStr::new);
+    let subprocess = match gio::Subprocess::newv(
+        &argv,
+        gio::SubprocessFlags::STDOUT_PIPE | gio::SubprocessFlags::STDERR_SILENCE,
+    ) {
+        Ok(subprocess) => subprocess,
+        Err(e) => {
+            on_done(Err(e));
+            return;
+        }
+    };
+
+    subprocess.communicate_utf8_async(None, None::<&gio::Cancellable>, {
+        let subprocess = subprocess.clone();
+        move |result| {
+            let details = result.and_then(|(stdout, _)| {
+                // Also when the password dialog was dismissed
+                if !subprocess.is_successful() {
+                    return Err(glib::Error::new(
+                        gio::IOErrorEnum::PermissionDenied,
+                        "Administrator rights were not granted",
+                    ));
+                }
+                Ok(parse_restricted_details(stdout.as_deref().unwrap_or_default()))
+            });
+            on_done(details);
+        }
+    });
+}
+
+// The executable, if there is one, followed by the contents of `/proc/<pid>/io`
+fn parse_restricted_details(output: &str) -> RestrictedDetails {
+    let mut details = RestrictedDetails::default();
+    for line in output.lines() {
+        match line.split_once(": ") {
+            Some(("read_bytes", value)) => details.bytes_read = value.trim().parse().ok(),
+            Some(("write_bytes", value)) => details.bytes_written = value.trim().parse().ok(),
+            Some(_) => {}
+            None if line.starts_with('/') => details.executable = Some(line.to_owned()),
+            None => {}
+        }
+    }
+
+    details
+}
+
+/// The directory the process works in, `None` if it exited, belongs to another user or can't be
+/// inspected
+pub fn working_directory(pid: u32) -> Option<std::path::PathBuf> {
+    if !crate::magpie_client::can_inspect_host() {
+        return None;
+    }
+
+    std::fs::read_link(format!("/proc/{}/cwd", pid))
+        .ok()
+        .filter(|path| path.is_dir())
+}
+
+/// The kinds of namespaces shown for a process, as named in `/proc/<pid>/ns`
+pub const NAMESPACE_KINDS: [&str; 4] = ["pid", "net", "mnt", "user"];
+
+/// A namespace a process lives in and the other processes that live in it too
+#[derive(Debug, Clone)]
+pub struct Namespace {
+    pub kind: &'static str,
+    /// The inode of the namespace, which tells namespaces apart
+    pub inode: u64,
+    /// Whether it is the namespace of the init process, i.e. not one of a container or sandbox.
+    /// `None` when the namespaces of the init process can't be read.
+    pub is_host: Option<bool>,
+    /// The PIDs and names of the other processes in the namespace
+    pub shared_with: Vec<(u32, String)>,
+}
+
+// The link reads e.g. `net:[4026531840]`
+fn namespace_inode(proc_path: &std::path::Path, kind: &str) -> Option<u64> {
+    let target = std::fs::read_link(proc_path.join("ns").join(kind)).ok()?;
+    target
+        .to_str()?
+        .strip_prefix(kind)?
+        .strip_prefix(":[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+/// The PID, network, mount and user namespaces of the process. `None` if they can't be read,
+/// which is the case for the processes of other users unless Mission Center runs as root.
+/// Processes of other users are not seen sharing them for the same reason.
+pub fn namespaces(pid: u32) -> Option<Vec<Namespace>> {
+    if !crate::magpie_client::can_inspect_host() {
+        return None;
+    }
+
+    let proc_path = std::path::PathBuf::from(format!("/proc/{}", pid));
+    let init_path = std::path::Path::new("/proc/1");
+    let mut namespaces = NAMESPACE_KINDS
+        .iter()
+        .map(|kind| {
+            let inode = namespace_inode(&proc_path, kind)?;
+            let is_host = namespace_inode(init_path, kind).map(|init| init == inode);
+            Some(Namespace {
+                kind,
+                inode,
+                is_host,
+                shared_with: vec![],
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    for entry in std::fs::read_dir("/proc").ok()?.flatten() {
+        let Some(other_pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        if other_pid == pid {
+            continue;
+        }
+
+        let other_path = entry.path();
+        let mut name = None;
+        for namespace in &mut namespaces {
+            if namespace_inode(&other_path, namespace.kind) != Some(namespace.inode) {
+                continue;
+            }
+
+            let name = name.get_or_insert_with(|| {
+                std::fs::read_to_string(other_path.join("comm"))
+                    .map(|comm| comm.trim_end().to_owned())
+                    .unwrap_or_default()
+            });
+            namespace.shared_with.push((other_pid, name.clone()));
+        }
+    }
+
+    Some(namespaces)
+}
+
+/// Where the files of the Flatpak app the process runs in and the ones of its runtime are, as
+/// the paths on the host the sandbox mounts at `/app` and `/usr`. `None` for processes that
+/// are not sandboxed by Flatpak.
+pub fn flatpak_instance_paths(pid: u32) -> Option<(std::path::PathBuf, std::path::PathBuf)> {
+    if !crate::magpie_client::can_inspect_host() {
+        return None;
+    }
+
+    let info = ini::Ini::load_from_file(format!("/proc/{}/root/.flatpak-info", pid)).ok()?;
+    let instance = info.section(Some("Instance"))?;
+
+    Some((
+        instance.get("app-path")?.into(),
+        instance.get("runtime-path")?.into(),
+    ))
+}
+
+/// The `oom_score_adj` of a process, `None` if it can't be read
+pub fn oom_score_adj(pid: u32) -> Option<i32> {
+    if !crate::magpie_client::can_inspect_host() {
+        return None;
+    }
+
+    std::fs::read_to_string(format!("/proc/{}/oom_score_adj", pid))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Sets the `oom_score_adj` of a process. Lowering it, or changing it for a process of another
+/// user, needs administrator rights, which are then asked for with `pkexec`.
+pub fn set_oom_score_adj(
+    pid: u32,
+    value: i32,
+    on_done: impl FnOnce(Result<(), glib::Error>) + 'static,
+) {
+    match std::fs::write(format!("/proc/{}/oom_score_adj", pid), value.to_string()) {
+        Ok(()) => {
+            on_done(Ok(()));
+            return;
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {}
+        Err(e) => {
+            on_done(Err(glib::Error::new(
+                gio::IOErrorEnum::Failed,
+                &e.to_string(),
+            )));
+            return;
+        }
+    }
+
+    let pid = pid.to_string();
+    let value = value.to_string();
+    let argv = ["pkexec", "choom", "-p", &pid, "-n", &value].map(OsStr::new);
+    match gio::Subprocess::newv(&argv, gio::SubprocessFlags::STDERR_SILENCE) {
+        Ok(subprocess) => subprocess.wait_check_async(None::<&gio::Cancellable>, on_done),
+        Err(e) => on_done(Err(e)),
+    }
+}
@@ -20,6 +20,7 @@
 
 use crate::table_view::row_model::ContentType;
 use crate::table_view::TableView;
+use crate::widgets::OverflowBox;
 use adw::prelude::*;
 use gtk::{gio, glib, subclass::prelude::*};
 
@@ -54,6 +55,8 @@ mod imp {
         type ParentType = gtk::Box;
 
         fn class_init(klass: &mut Self::Class) {
+            OverflowBox::ensure_type();
+
             klass.bind_template();
         }
 
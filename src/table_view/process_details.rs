@@ -0,0 +1,627 @@
+/* table_view/process_details.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::cell::{Cell, RefCell};
+
+use adw::prelude::{ActionRowExt, ExpanderRowExt, PreferencesGroupExt};
+use adw::subclass::prelude::*;
+use adw::PreferencesRow;
+use gtk::glib::{self, g_warning};
+use gtk::prelude::{ButtonExt, RangeExt, StaticTypeExt, WidgetExt};
+
+use crate::app;
+use crate::i18n::{i18n, i18n_f, ni18n_f};
+use crate::table_view::app_windows::{self, AppWindow};
+use crate::table_view::columns::*;
+use crate::table_view::row_model::{ContentType, RowModel};
+use crate::table_view::{actions, flatpak_permissions, proc_fs};
+
+mod imp {
+    use super::*;
+
+    #[derive(gtk::CompositeTemplate)]
+    #[template(resource = "/io/missioncenter/MissionCenter/ui/table_view/process_details.ui")]
+    pub struct ProcessDetails {
+        #[template_child]
+        id_value: TemplateChild<gtk::Label>,
+        #[template_child]
+        app_id_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pid_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        kind: TemplateChild<gtk::Label>,
+        #[template_child]
+        command_line_row: TemplateChild<PreferencesRow>,
+        #[template_child]
+        command_line: TemplateChild<gtk::Label>,
+
+        #[template_child]
+        windows_group: TemplateChild<adw::PreferencesGroup>,
+        window_rows: RefCell<Vec<adw::ActionRow>>,
+        namespace_rows: RefCell<Vec<adw::ExpanderRow>>,
+
+        #[template_child]
+        flatpak_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        namespaces_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        manage_permissions_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        network_permission: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        filesystem_permission: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        device_permission: TemplateChild<adw::ActionRow>,
+
+        #[template_child]
+        cpu: TemplateChild<LabelCell>,
+        #[template_child]
+        memory: TemplateChild<LabelCell>,
+        #[template_child]
+        shared_memory: TemplateChild<LabelCell>,
+        #[template_child]
+        swap: TemplateChild<LabelCell>,
+        #[template_child]
+        drives: TemplateChild<LabelCell>,
+        #[template_child]
+        network: TemplateChild<LabelCell>,
+        #[template_child]
+        gpu: TemplateChild<LabelCell>,
+        #[template_child]
+        gpu_memory: TemplateChild<LabelCell>,
+
+        #[template_child]
+        thermal_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        thermal_share: TemplateChild<LabelCell>,
+        #[template_child]
+        thermal_rise: TemplateChild<LabelCell>,
+
+        #[template_child]
+        oom_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        oom_score_adj_apply: TemplateChild<gtk::Button>,
+        #[template_child]
+        oom_score_adj_scale: TemplateChild<gtk::Scale>,
+        #[template_child]
+        oom_score_adj_warning: TemplateChild<gtk::Label>,
+
+        oom_score_adj: Cell<i32>,
+
+        pub model: RefCell<RowModel>,
+        pub cpu_usage_mode: Cell<CpuUsageMode>,
+        // The process of a snapshot is not the one that has its PID now
+        pub showing_snapshot: Cell<bool>,
+    }
+
+    impl Default for ProcessDetails {
+        fn default() -> Self {
+            Self {
+                id_value: TemplateChild::default(),
+                app_id_label: TemplateChild::default(),
+                pid_label: TemplateChild::default(),
+                kind: TemplateChild::default(),
+                command_line_row: TemplateChild::default(),
+                command_line: TemplateChild::default(),
+
+                windows_group: TemplateChild::default(),
+                window_rows: RefCell::new(vec![]),
+                namespace_rows: RefCell::new(vec![]),
+
+                flatpak_group: TemplateChild::default(),
+                namespaces_group: TemplateChild::default(),
+                manage_permissions_button: TemplateChild::default(),
+                network_permission: TemplateChild::default(),
+                filesystem_permission: TemplateChild::default(),
+                device_permission: TemplateChild::default(),
+
+                cpu: TemplateChild::default(),
+                memory: TemplateChild::default(),
+                shared_memory: TemplateChild::default(),
+                swap: TemplateChild::default(),
+                drives: TemplateChild::default(),
+                network: TemplateChild::default(),
+                gpu: TemplateChild::default(),
+                gpu_memory: TemplateChild::default(),
+
+                thermal_group: TemplateChild::default(),
+                thermal_share: TemplateChild::default(),
+                thermal_rise: TemplateChild::default(),
+
+                oom_group: TemplateChild::default(),
+                oom_score_adj_apply: TemplateChild::default(),
+                oom_score_adj_scale: TemplateChild::default(),
+                oom_score_adj_warning: TemplateChild::default(),
+
+                oom_score_adj: Cell::new(0),
+
+                model: RefCell::new(RowModel::new(ContentType::SectionHeader)),
+                cpu_usage_mode: Cell::new(CpuUsageMode::Normalized),
+                showing_snapshot: Cell::new(false),
+            }
+        }
+    }
+
+    impl ProcessDetails {
+        pub fn bind(&self) {
+            let model = self.model.borrow();
+
+            match model.content_type() {
+                ContentType::App => {
+                    self.app_id_label.set_visible(true);
+                    self.pid_label.set_visible(false);
+                }
+                ContentType::Process => {
+                    self.app_id_label.set_visible(false);
+                    self.pid_label.set_visible(true);
+                }
+                _ => {} // should never happen
+            }
+
+            self.id_value.set_label(&model.id());
+
+            let content_type: String = model.content_type().into();
+            self.kind.set_label(&content_type);
+
+            if model.command_line_restricted() {
+                self.command_line.set_label(&i18n("Restricted"));
+                self.command_line_row.set_visible(true);
+            } else {
+                let cli: String = model.command_line().into();
+                self.command_line.set_label(&cli);
+
+                self.command_line_row.set_visible(!cli.is_empty());
+            }
+
+            let cpu_usage_mode = self.cpu_usage_mode.get();
+            cpu_usage_mode.format(&*self.cpu, model.cpu_usage());
+            self.cpu
+                .bind(&*model, "cpu-usage", move |label, value: glib::Value| {
+                    cpu_usage_mode.format(label, value.get().unwrap_or_default())
+                });
+
+            memory_label_formatter(&*self.memory, model.memory_usage().into());
+            self.memory
+                .bind(&*model, "memory-usage", memory_label_formatter);
+
+            shared_memory_label_formatter(&*self.shared_memory, model.shared_memory_usage().into());
+            self.shared_memory.bind(
+                &*model,
+                "shared-memory-usage",
+                shared_memory_label_formatter,
+            );
+
+            swap_label_formatter(&*self.swap, model.swap_usage().into());
+            self.swap.bind(&*model, "swap-usage", swap_label_formatter);
+
+            if model.io_restricted() {
+                self.drives.set_restricted(true);
+            } else {
+                self.drives.set_restricted(false);
+                drive_label_formatter(&*self.drives, model.disk_usage().into());
+                self.drives
+                    .bind(&*model, "disk-usage", drive_label_formatter);
+            }
+
+            network_label_formatter(&*self.network, model.network_usage().into());
+            self.network
+                .bind(&*model, "network-usage", network_label_formatter);
+
+            gpu_label_formatter(&*self.gpu, model.gpu_usage().into());
+            self.gpu.bind(&*model, "gpu-usage", gpu_label_formatter);
+
+            gpu_memory_label_formatter(&*self.gpu_memory, model.gpu_memory_usage().into());
+            self.gpu_memory
+                .bind(&*model, "gpu-memory-usage", gpu_memory_label_formatter);
+        }
+
+        /// Only shown while the CPU temperature is known
+        pub fn bind_thermal_load(&self) {
+            let known = app!()
+                .window()
+                .is_some_and(|window| window.process_changes().thermal_pressure().is_some());
+            self.thermal_group.set_visible(known);
+            if !known {
+                return;
+            }
+
+            fn share_formatter(label: &LabelCell, value: glib::Value) {
+                let share: f32 = value.get().unwrap_or_default();
+                label.set_label(&format!("{}%", (share * 100.).round()));
+            }
+
+            fn rise_formatter(label: &LabelCell, value: glib::Value) {
+                let rise: f32 = value.get().unwrap_or_default();
+                label.set_label(&format!("{:.1} °C", rise));
+            }
+
+            let model = self.model.borrow();
+
+            share_formatter(&*self.thermal_share, model.thermal_share().into());
+            self.thermal_share
+                .bind(&*model, "thermal-share", share_formatter);
+
+            rise_formatter(&*self.thermal_rise, model.thermal_rise().into());
+            self.thermal_rise
+                .bind(&*model, "thermal-rise", rise_formatter);
+        }
+
+        /// Lists the windows of apps, each with a button to bring it to the front
+        pub fn bind_windows(&self) {
+            let model = self.model.borrow();
+            if model.content_type() != ContentType::App || !app_windows::is_supported() {
+                self.set_windows(vec![]);
+                return;
+            }
+
+            app_windows::list_windows(actions::process_pids(&model), {
+                let this = self.obj().downgrade();
+                let model = model.clone();
+                move |windows| {
+                    let Some(this) = this.upgrade() else {
+                        return;
+                    };
+                    // Another row was selected while the windows were listed
+                    if *this.imp().model.borrow() == model {
+                        this.imp().set_windows(windows);
+                    }
+                }
+            });
+        }
+
+        fn set_windows(&self, windows: Vec<AppWindow>) {
+            for row in self.window_rows.take() {
+                self.windows_group.remove(&row);
+            }
+
+            let mut window_rows = Vec::with_capacity(windows.len());
+            for window in windows {
+                let title = if window.title.is_empty() {
+                    i18n("Untitled Window")
+                } else {
+                    window.title.clone()
+                };
+                let row = adw::ActionRow::builder()
+                    .title(glib::markup_escape_text(&title))
+                    .build();
+
+                let button = gtk::Button::builder()
+                    .icon_name("view-reveal-symbolic")
+                    .tooltip_text(i18n("Bring to Front"))
+                    .valign(gtk::Align::Center)
+                    .css_classes(["flat"])
+                    .build();
+                button.connect_clicked(move |_| app_windows::activate_window(&window));
+                row.add_suffix(&button);
+
+                self.windows_group.add(&row);
+                window_rows.push(row);
+            }
+
+            self.windows_group.set_visible(!window_rows.is_empty());
+            self.window_rows.replace(window_rows);
+        }
+
+        /// Shows what the sandbox of Flatpak apps lets them access
+        pub fn bind_flatpak_permissions(&self) {
+            let model = self.model.borrow();
+            let permissions = if model.content_type() == ContentType::App {
+                flatpak_permissions::permissions(&model.id())
+            } else {
+                None
+            };
+
+            let Some(permissions) = permissions else {
+                self.flatpak_group.set_visible(false);
+                return;
+            };
+
+            fn describe(entries: impl Iterator<Item = String>) -> String {
+                let entries = entries.collect::<Vec<_>>();
+                if entries.is_empty() {
+                    i18n("None")
+                } else {
+                    entries.join(", ")
+                }
+            }
+
+            self.network_permission
+                .set_subtitle(&if permissions.network() {
+                    i18n("Allowed")
+                } else {
+                    i18n("Not allowed")
+                });
+            self.filesystem_permission.set_subtitle(&describe(
+                permissions
+                    .filesystems
+                    .iter()
+                    .map(|filesystem| flatpak_permissions::filesystem_label(filesystem)),
+            ));
+            self.device_permission.set_subtitle(&describe(
+                permissions
+                    .devices
+                    .iter()
+                    .map(|device| flatpak_permissions::device_label(device)),
+            ));
+            self.flatpak_group.set_visible(true);
+        }
+
+        /// Lists the namespaces of single processes and the other processes in each of them, which
+        /// tells whether the process runs in a container or sandbox and what else runs there
+        pub fn bind_namespaces(&self) {
+            const MAX_LISTED_PROCESSES: usize = 100;
+
+            let model = self.model.borrow();
+            let namespaces =
+                if model.content_type() == ContentType::Process && !self.showing_snapshot.get() {
+                    proc_fs::namespaces(model.pid())
+                } else {
+                    None
+                };
+
+            for row in self.namespace_rows.take() {
+                self.namespaces_group.remove(&row);
+            }
+
+            let Some(namespaces) = namespaces else {
+                self.namespaces_group.set_visible(false);
+                return;
+            };
+
+            let mut namespace_rows = Vec::with_capacity(namespaces.len());
+            for namespace in namespaces {
+                let title = match namespace.kind {
+                    "pid" => i18n("Processes"),
+                    "net" => i18n("Network"),
+                    "mnt" => i18n("Mounts"),
+                    "user" => i18n("Users"),
+                    kind => kind.to_owned(),
+                };
+                let origin = match namespace.is_host {
+                    Some(true) => i18n("Same as the system"),
+                    Some(false) => i18n("Separate from the system"),
+                    None => i18n("Unknown"),
+                };
+                let shared = ni18n_f(
+                    "Shared with {} other process",
+                    "Shared with {} other processes",
+                    namespace.shared_with.len() as u32,
+                    &[&namespace.shared_with.len().to_string()],
+                );
+
+                let row = adw::ExpanderRow::builder()
+                    .title(title)
+                    .subtitle(format!("{} · {} · {}", origin, shared, namespace.inode))
+                    .enable_expansion(!namespace.shared_with.is_empty())
+                    .show_enable_switch(false)
+                    .build();
+
+                for (pid, name) in namespace.shared_with.iter().take(MAX_LISTED_PROCESSES) {
+                    let process_row = adw::ActionRow::builder()
+                        .title(glib::markup_escape_text(name))
+                        .subtitle(pid.to_string())
+                        .build();
+                    row.add_row(&process_row);
+                }
+
+                let hidden_count =
+                    namespace.shared_with.len().saturating_sub(MAX_LISTED_PROCESSES);
+                if hidden_count > 0 {
+                    let more_row = adw::ActionRow::builder()
+                        .title(ni18n_f(
+                            "And {} more process",
+                            "And {} more processes",
+                            hidden_count as u32,
+                            &[&hidden_count.to_string()],
+                        ))
+                        .css_classes(["dim-label"])
+                        .build();
+                    row.add_row(&more_row);
+                }
+
+                self.namespaces_group.add(&row);
+                namespace_rows.push(row);
+            }
+            self.namespace_rows.replace(namespace_rows);
+
+            self.namespaces_group.set_visible(true);
+        }
+
+        /// Only single processes have a score, apps are made up of several of them
+        pub fn bind_oom_score_adj(&self) {
+            let model = self.model.borrow();
+            let value =
+                if model.content_type() == ContentType::Process && !self.showing_snapshot.get() {
+                    proc_fs::oom_score_adj(model.pid())
+                } else {
+                    None
+                };
+
+            let Some(value) = value else {
+                self.oom_group.set_visible(false);
+                return;
+            };
+
+            self.oom_score_adj.set(value);
+            self.oom_score_adj_scale.set_value(value as f64);
+            self.oom_group.set_visible(true);
+            self.update_oom_score_adj_warning();
+        }
+
+        fn update_oom_score_adj_warning(&self) {
+            let value = self.oom_score_adj_scale.value().round() as i32;
+            let current = self.oom_score_adj.get();
+
+            self.oom_score_adj_apply.set_sensitive(value != current);
+
+            let mut warnings = vec![];
+            if value == -1000 {
+                warnings.push(i18n(
+                    "The process will never be ended to free memory, which can leave the system unresponsive",
+                ));
+            } else if value < 0 && value < current {
+                warnings.push(i18n(
+                    "Other processes will be ended before this one, even if they use less memory",
+                ));
+            }
+            if value < current {
+                warnings.push(i18n("Lowering the value requires administrator rights"));
+            }
+
+            self.oom_score_adj_warning.set_label(&warnings.join("\n"));
+            self.oom_score_adj_warning.set_visible(!warnings.is_empty());
+        }
+
+        fn apply_oom_score_adj(&self) {
+            let pid = self.model.borrow().pid();
+            let value = self.oom_score_adj_scale.value().round() as i32;
+
+            self.oom_score_adj_apply.set_sensitive(false);
+            proc_fs::set_oom_score_adj(pid, value, {
+                let this = self.obj().downgrade();
+                move |result| {
+                    let Some(this) = this.upgrade() else {
+                        return;
+                    };
+                    let this = this.imp();
+
+                    if let Err(e) = result {
+                        g_warning!(
+                            "MissionCenter::ProcessDetails",
+                            "Failed to set the OOM score adjustment of {}: {}",
+                            pid,
+                            e
+                        );
+                        this.oom_score_adj_warning.set_label(&i18n_f(
+                            "Failed to change the value: {}",
+                            &[&e.to_string()],
+                        ));
+                        this.oom_score_adj_warning.set_visible(true);
+                        this.oom_score_adj_apply.set_sensitive(true);
+                        return;
+                    }
+
+                    // Read back, in case the process exited in the meantime
+                    this.bind_oom_score_adj();
+                }
+            });
+        }
+
+        pub fn unbind(&self) {
+            self.cpu.unbind();
+            self.memory.unbind();
+            self.shared_memory.unbind();
+            self.swap.unbind();
+            self.drives.unbind();
+            self.gpu.unbind();
+            self.gpu_memory.unbind();
+            self.thermal_share.unbind();
+            self.thermal_rise.unbind();
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ProcessDetails {
+        const NAME: &'static str = "ProcessDetails";
+        type Type = super::ProcessDetails;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            LabelCell::ensure_type();
+
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ProcessDetails {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.oom_score_adj_scale.connect_value_changed({
+                let this = self.obj().downgrade();
+                move |_| {
+                    if let Some(this) = this.upgrade() {
+                        this.imp().update_oom_score_adj_warning();
+                    }
+                }
+            });
+
+            self.manage_permissions_button.connect_clicked({
+                let this = self.obj().downgrade();
+                move |_| {
+                    if let Some(this) = this.upgrade() {
+                        flatpak_permissions::manage_permissions(&this.imp().model.borrow().id());
+                    }
+                }
+            });
+
+            self.oom_score_adj_apply.connect_clicked({
+                let this = self.obj().downgrade();
+                move |_| {
+                    if let Some(this) = this.upgrade() {
+                        this.imp().apply_oom_score_adj();
+                    }
+                }
+            });
+        }
+    }
+
+    impl WidgetImpl for ProcessDetails {}
+
+    impl BinImpl for ProcessDetails {}
+}
+
+glib::wrapper! {
+    pub struct ProcessDetails(ObjectSubclass<imp::ProcessDetails>)
+        @extends adw::Bin, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl ProcessDetails {
+    pub fn new() -> Self {
+        glib::Object::builder().build()
+    }
+
+    /// Shows the details of `model`, and keeps them up to date while it changes. Nothing is read
+    /// from `/proc` for the rows of a snapshot.
+    pub fn set_model(&self, model: RowModel, cpu_usage_mode: CpuUsageMode, showing_snapshot: bool) {
+        let imp = self.imp();
+
+        imp.unbind();
+        imp.model.replace(model);
+        imp.cpu_usage_mode.set(cpu_usage_mode);
+        imp.showing_snapshot.set(showing_snapshot);
+        imp.bind();
+        imp.bind_thermal_load();
+        imp.bind_oom_score_adj();
+        imp.bind_windows();
+        imp.bind_flatpak_permissions();
+        imp.bind_namespaces();
+    }
+
+    /// Stops following the model, e.g. when the details are no longer shown
+    pub fn unbind(&self) {
+        self.imp().unbind();
+    }
+}
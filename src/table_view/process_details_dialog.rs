@@ -21,11 +21,11 @@
 use std::cell::RefCell;
 
 use adw::subclass::prelude::*;
-use adw::PreferencesRow;
-use gtk::glib::{self};
+use gtk::glib;
 use gtk::prelude::{StaticTypeExt, WidgetExt};
 
-use crate::table_view::columns::*;
+use crate::table_view::columns::CpuUsageMode;
+use crate::table_view::process_details::ProcessDetails;
 use crate::table_view::row_model::{ContentType, RowModel};
 
 mod imp {
@@ -37,37 +37,11 @@ mod imp {
     )]
     pub struct ProcessDetailsDialog {
         #[template_child]
-        icon: TemplateChild<gtk::Image>,
+        pub icon: TemplateChild<gtk::Image>,
         #[template_child]
-        title: TemplateChild<gtk::Label>,
-
-        #[template_child]
-        id_value: TemplateChild<gtk::Label>,
-        #[template_child]
-        app_id_label: TemplateChild<gtk::Label>,
-        #[template_child]
-        pid_label: TemplateChild<gtk::Label>,
-        #[template_child]
-        kind: TemplateChild<gtk::Label>,
-        #[template_child]
-        command_line_row: TemplateChild<PreferencesRow>,
-        #[template_child]
-        command_line: TemplateChild<gtk::Label>,
-
-        #[template_child]
-        cpu: TemplateChild<LabelCell>,
-        #[template_child]
-        memory: TemplateChild<LabelCell>,
-        #[template_child]
-        shared_memory: TemplateChild<LabelCell>,
+        pub title: TemplateChild<gtk::Label>,
         #[template_child]
-        drives: TemplateChild<LabelCell>,
-        #[template_child]
-        network: TemplateChild<LabelCell>,
-        #[template_child]
-        gpu: TemplateChild<LabelCell>,
-        #[template_child]
-        gpu_memory: TemplateChild<LabelCell>,
+        pub details: TemplateChild<ProcessDetails>,
 
         pub model: RefCell<RowModel>,
     }
@@ -77,21 +51,7 @@ mod imp {
             Self {
                 icon: TemplateChild::default(),
                 title: TemplateChild::default(),
-
-                id_value: TemplateChild::default(),
-                app_id_label: TemplateChild::default(),
-                pid_label: TemplateChild::default(),
-                kind: TemplateChild::default(),
-                command_line_row: TemplateChild::default(),
-                command_line: TemplateChild::default(),
-
-                cpu: TemplateChild::default(),
-                memory: TemplateChild::default(),
-                shared_memory: TemplateChild::default(),
-                drives: TemplateChild::default(),
-                network: TemplateChild::default(),
-                gpu: TemplateChild::default(),
-                gpu_memory: TemplateChild::default(),
+                details: TemplateChild::default(),
 
                 model: RefCell::new(RowModel::new(ContentType::SectionHeader)),
             }
@@ -99,85 +59,11 @@ mod imp {
     }
 
     impl ProcessDetailsDialog {
-        pub fn bind(&self) {
+        pub fn bind_header(&self) {
             let model = self.model.borrow();
 
-            match model.content_type() {
-                ContentType::App => {
-                    self.app_id_label.set_visible(true);
-                    self.pid_label.set_visible(false);
-                }
-                ContentType::Process => {
-                    self.app_id_label.set_visible(false);
-                    self.pid_label.set_visible(true);
-                }
-                _ => {} // should never happen
-            }
-
-            let icon = model.icon();
-            let icon_path = std::path::Path::new(icon.as_str());
-            if icon_path.exists() {
-                self.icon.set_from_file(Some(&icon_path));
-                return;
-            }
-
-            let display = gtk::gdk::Display::default().unwrap();
-            let icon_theme = gtk::IconTheme::for_display(&display);
-            if icon_theme.has_icon(&icon) {
-                self.icon.set_icon_name(Some(&icon));
-            } else {
-                self.icon.set_icon_name(None);
-            }
-
             self.title.set_label(&model.name());
-
-            self.id_value.set_label(&model.id());
-
-            let content_type: String = model.content_type().into();
-            self.kind.set_label(&content_type);
-
-            let cli: String = model.command_line().into();
-            self.command_line.set_label(&cli);
-
-            self.command_line_row.set_visible(!cli.is_empty());
-
-            cpu_label_formatter(&*self.cpu, model.cpu_usage().into());
-            self.cpu.bind(&*model, "cpu-usage", cpu_label_formatter);
-
-            memory_label_formatter(&*self.memory, model.memory_usage().into());
-            self.memory
-                .bind(&*model, "memory-usage", memory_label_formatter);
-
-            shared_memory_label_formatter(&*self.shared_memory, model.shared_memory_usage().into());
-            self.shared_memory.bind(
-                &*model,
-                "shared-memory-usage",
-                shared_memory_label_formatter,
-            );
-
-            drive_label_formatter(&*self.drives, model.disk_usage().into());
-            self.drives
-                .bind(&*model, "disk-usage", drive_label_formatter);
-
-            network_label_formatter(&*self.network, model.network_usage().into());
-            self.network
-                .bind(&*model, "network-usage", network_label_formatter);
-
-            gpu_label_formatter(&*self.gpu, model.gpu_usage().into());
-            self.gpu.bind(&*model, "gpu-usage", gpu_label_formatter);
-
-            gpu_memory_label_formatter(&*self.gpu_memory, model.gpu_memory_usage().into());
-            self.gpu_memory
-                .bind(&*model, "gpu-memory-usage", gpu_memory_label_formatter);
-        }
-
-        fn unbind(&self) {
-            self.cpu.unbind();
-            self.memory.unbind();
-            self.shared_memory.unbind();
-            self.drives.unbind();
-            self.gpu.unbind();
-            self.gpu_memory.unbind();
+            set_icon(&self.icon, &model.icon());
         }
     }
 
@@ -188,7 +74,7 @@ mod imp {
         type ParentType = adw::Dialog;
 
         fn class_init(klass: &mut Self::Class) {
-            LabelCell::ensure_type();
+            ProcessDetails::ensure_type();
 
             klass.bind_template();
         }
@@ -198,21 +84,13 @@ mod imp {
         }
     }
 
-    impl ObjectImpl for ProcessDetailsDialog {
-        fn constructed(&self) {
-            self.parent_constructed();
-        }
-    }
+    impl ObjectImpl for ProcessDetailsDialog {}
 
-    impl WidgetImpl for ProcessDetailsDialog {
-        fn realize(&self) {
-            self.parent_realize();
-        }
-    }
+    impl WidgetImpl for ProcessDetailsDialog {}
 
     impl AdwDialogImpl for ProcessDetailsDialog {
         fn closed(&self) {
-            self.unbind();
+            self.details.unbind();
         }
     }
 }
@@ -224,16 +102,35 @@ glib::wrapper! {
 }
 
 impl ProcessDetailsDialog {
-    pub fn new(model: RowModel) -> Self {
+    pub fn new(model: RowModel, cpu_usage_mode: CpuUsageMode, showing_snapshot: bool) -> Self {
         let this: Self = glib::Object::builder()
             .property("follows-content-size", true)
             .build();
 
         let imp = this.imp();
 
-        imp.model.replace(model);
-        imp.bind();
+        imp.model.replace(model.clone());
+        imp.bind_header();
+        imp.details
+            .set_model(model, cpu_usage_mode, showing_snapshot);
 
         this
     }
 }
+
+// The icon of a row is either a file or the name of an icon of the theme
+fn set_icon(image: &gtk::Image, icon: &str) {
+    let icon_path = std::path::Path::new(icon);
+    if icon_path.exists() {
+        image.set_from_file(Some(&icon_path));
+        return;
+    }
+
+    let display = gtk::gdk::Display::default().unwrap();
+    let icon_theme = gtk::IconTheme::for_display(&display);
+    if icon_theme.has_icon(icon) {
+        image.set_icon_name(Some(icon));
+    } else {
+        image.set_icon_name(None);
+    }
+}
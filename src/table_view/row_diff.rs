@@ -0,0 +1,263 @@
+/* table_view/row_diff.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::HashSet;
+use std::hash::Hash;
+
+/// What happens to a row of a list with the latest readings
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum RowAction {
+    /// Its entry is still there, the row is updated in place
+    Update,
+    /// Its entry is gone, the row is removed
+    Remove,
+}
+
+/// How the rows of a list compare to the entries of the latest readings, by the key that
+/// identifies both, e.g. the PID of a process. Works on keys alone, without any rows or widgets,
+/// so the models only carry out what was decided here.
+#[derive(Debug, Eq, PartialEq)]
+pub struct RowDiff<K: Hash + Eq> {
+    /// What happens to each row, in the order of the rows
+    pub rows: Vec<RowAction>,
+    /// Rows whose entry is still there
+    pub kept: HashSet<K>,
+    /// Entries without a row yet, in the order they were given, each one once. They are appended
+    /// after the remaining rows.
+    pub added: Vec<K>,
+}
+
+impl<K: Hash + Eq + Clone> RowDiff<K> {
+    /// Compares the keys of the rows of a list, in order, with the keys of the current entries
+    pub fn new(
+        row_keys: impl IntoIterator<Item = K>,
+        entry_keys: impl IntoIterator<Item = K>,
+    ) -> Self {
+        let mut entries = HashSet::new();
+        let mut ordered_entries = vec![];
+        for key in entry_keys {
+            if entries.insert(key.clone()) {
+                ordered_entries.push(key);
+            }
+        }
+
+        let mut rows = vec![];
+        let mut kept = HashSet::new();
+        for key in row_keys {
+            if entries.contains(&key) {
+                rows.push(RowAction::Update);
+                kept.insert(key);
+            } else {
+                rows.push(RowAction::Remove);
+            }
+        }
+
+        let added = ordered_entries
+            .into_iter()
+            .filter(|key| !kept.contains(key))
+            .collect();
+
+        Self { rows, kept, added }
+    }
+
+    #[inline]
+    pub fn keeps(&self, key: &K) -> bool {
+        self.kept.contains(key)
+    }
+
+    /// Tells for each row, in the order of the rows, whether it stays in the list. Meant for
+    /// retaining the rows the diff was made from.
+    pub fn retained(&self) -> impl FnMut() -> bool + '_ {
+        let mut rows = self.rows.iter();
+        move || rows.next() == Some(&RowAction::Update)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::RowAction::{Remove, Update};
+    use super::*;
+
+    fn set(keys: &[u32]) -> HashSet<u32> {
+        keys.iter().copied().collect()
+    }
+
+    // Carries out the diff on a list of keys the way the models do on their lists
+    fn apply(rows: &mut Vec<u32>, diff: &RowDiff<u32>) {
+        let mut retained = diff.retained();
+        rows.retain(|_| retained());
+        rows.extend(diff.added.iter().copied());
+    }
+
+    #[test]
+    fn keeps_rows_that_still_have_an_entry() {
+        let diff = RowDiff::new([1, 2, 3], [3, 2, 4]);
+
+        assert_eq!(diff.kept, set(&[2, 3]));
+        assert_eq!(diff.rows, vec![Remove, Update, Update]);
+        assert!(diff.keeps(&2));
+        assert!(!diff.keeps(&1));
+    }
+
+    #[test]
+    fn removes_rows_without_an_entry() {
+        let diff = RowDiff::new([1, 2, 3], [2]);
+
+        assert_eq!(diff.rows, vec![Remove, Update, Remove]);
+        assert!(!diff.keeps(&1));
+        assert!(diff.keeps(&2));
+    }
+
+    #[test]
+    fn adds_entries_in_the_order_they_were_given() {
+        let diff = RowDiff::new([2], [5, 1, 2, 4, 3]);
+
+        assert_eq!(diff.added, vec![5, 1, 4, 3]);
+    }
+
+    #[test]
+    fn adds_duplicate_entries_once() {
+        let diff = RowDiff::new([1], [3, 1, 3, 2, 1, 2]);
+
+        assert_eq!(diff.kept, set(&[1]));
+        assert_eq!(diff.added, vec![3, 2]);
+    }
+
+    #[test]
+    fn duplicate_rows_share_what_happens_to_them() {
+        let diff = RowDiff::new([1, 2, 1, 2], [1]);
+
+        assert_eq!(diff.kept, set(&[1]));
+        assert_eq!(diff.rows, vec![Update, Remove, Update, Remove]);
+        assert!(diff.added.is_empty());
+    }
+
+    #[test]
+    fn empty_lists() {
+        let diff = RowDiff::<u32>::new([], []);
+        assert!(diff.rows.is_empty() && diff.kept.is_empty() && diff.added.is_empty());
+
+        let diff = RowDiff::new([], [1, 2]);
+        assert_eq!(diff.added, vec![1, 2]);
+
+        let diff = RowDiff::new([1, 2], []);
+        assert_eq!(diff.rows, vec![Remove, Remove]);
+    }
+
+    #[test]
+    fn remaining_rows_keep_their_order_and_new_ones_go_last() {
+        let mut rows = vec![4, 1, 7, 3];
+        let diff = RowDiff::new(rows.iter().copied(), [9, 3, 1, 8]);
+        apply(&mut rows, &diff);
+
+        assert_eq!(rows, vec![1, 3, 9, 8]);
+    }
+
+    #[test]
+    fn applying_twice_changes_nothing() {
+        let mut rows = vec![2, 6, 5];
+        let entries = [5, 1, 2];
+        let diff = RowDiff::new(rows.clone(), entries);
+        apply(&mut rows, &diff);
+        let updated = rows.clone();
+
+        let diff = RowDiff::new(rows.clone(), entries);
+        assert!(diff.rows.iter().all(|action| *action == Update));
+        assert!(diff.added.is_empty());
+
+        apply(&mut rows, &diff);
+        assert_eq!(rows, updated);
+    }
+
+    // Like the process lists, where only the PIDs that are in the readings get a row
+    #[test]
+    fn entries_missing_from_the_readings() {
+        let processes = set(&[1, 2, 3, 5]);
+        let pids = [5, 4, 2];
+
+        let mut rows = vec![3, 2, 4];
+        let diff = RowDiff::new(
+            rows.iter().copied(),
+            pids.iter().copied().filter(|pid| processes.contains(pid)),
+        );
+        apply(&mut rows, &diff);
+
+        assert_eq!(rows, vec![2, 5]);
+    }
+
+    // Small key ranges make overlaps and duplicates likely
+    #[test]
+    fn random_keys() {
+        let mut rng = StdRng::seed_from_u64(0x5eed);
+
+        for _ in 0..1000 {
+            let max_key = rng.random_range(1..64);
+            let row_keys = (0..rng.random_range(0..48))
+                .map(|_| rng.random_range(0..max_key))
+                .collect::<Vec<u32>>();
+            let entry_keys = (0..rng.random_range(0..48))
+                .map(|_| rng.random_range(0..max_key))
+                .collect::<Vec<u32>>();
+
+            let diff = RowDiff::new(row_keys.iter().copied(), entry_keys.iter().copied());
+
+            let rows = set(&row_keys);
+            let entries = set(&entry_keys);
+
+            // Every row is either updated or removed, depending on whether its entry is there
+            assert_eq!(diff.rows.len(), row_keys.len());
+            for (key, action) in row_keys.iter().zip(&diff.rows) {
+                let expected = if entries.contains(key) {
+                    Update
+                } else {
+                    Remove
+                };
+                assert_eq!(*action, expected);
+            }
+            assert_eq!(diff.kept, &rows & &entries);
+
+            // Every entry without a row is added once, where it first showed up
+            let mut seen = HashSet::new();
+            let expected_added = entry_keys
+                .iter()
+                .copied()
+                .filter(|key| !rows.contains(key) && seen.insert(*key))
+                .collect::<Vec<_>>();
+            assert_eq!(diff.added, expected_added);
+
+            // Afterwards the list has the remaining rows in their order, then the new ones, and
+            // a row for every entry
+            let mut list = row_keys.clone();
+            apply(&mut list, &diff);
+
+            let expected_list = row_keys
+                .iter()
+                .copied()
+                .filter(|key| entries.contains(key))
+                .chain(expected_added.iter().copied())
+                .collect::<Vec<_>>();
+            assert_eq!(list, expected_list);
+            assert_eq!(set(&list), entries);
+        }
+    }
+}
@@ -19,8 +19,12 @@
  */
 
 use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
 
 use crate::i18n::i18n;
+use crate::magpie_client::{smooth, ProcField, ProcessState, ThermalPressure};
+use crate::table_view::UnitOrigin;
 use gtk::{
     gio, glib,
     glib::{prelude::*, subclass::prelude::*, ParamSpec, Properties, Value},
@@ -45,6 +49,9 @@ mod imp {
         pub icon: Cell<glib::GString>,
         #[property(get = Self::name, set = Self::set_name)]
         pub name: Cell<glib::GString>,
+        /// Shown next to the name of section headers, such as the number of entries
+        #[property(get = Self::summary, set = Self::set_summary)]
+        pub summary: Cell<glib::GString>,
 
         #[property(get, type = ContentType, builder(ContentType::SectionHeader))]
         pub content_type: Cell<ContentType>,
@@ -58,6 +65,8 @@ mod imp {
         #[property(get, set)]
         pub shared_memory_usage: Cell<u64>,
         #[property(get, set)]
+        pub swap_usage: Cell<u64>,
+        #[property(get, set)]
         pub disk_usage: Cell<f32>,
         #[property(get, set)]
         pub network_usage: Cell<f32>,
@@ -65,6 +74,36 @@ mod imp {
         pub gpu_usage: Cell<f32>,
         #[property(get, set)]
         pub gpu_memory_usage: Cell<u64>,
+        /// Seconds of CPU time used since the process started
+        #[property(get, set)]
+        pub cpu_time: Cell<f64>,
+        #[property(get, set)]
+        pub context_switch_rate: Cell<f32>,
+        /// Page faults per second that had to read from disk
+        #[property(get, set)]
+        pub major_fault_rate: Cell<f32>,
+        /// The percentage of the time spent waiting for block I/O
+        #[property(get, set)]
+        pub io_wait: Cell<f32>,
+        /// How many bytes per minute the memory usage grew by over the last few minutes, 0
+        /// unless it only went up in that time
+        #[property(get)]
+        pub memory_growth: Cell<f32>,
+        /// Set once the memory usage kept growing for long enough to look like a leak
+        #[property(get)]
+        pub memory_leak_suspected: Cell<bool>,
+        pub memory_samples: RefCell<VecDeque<(Instant, u64)>>,
+        /// The share of the CPU heating up put down to the entry, from 0 to 1
+        #[property(get)]
+        pub thermal_share: Cell<f32>,
+        /// How many °C of the CPU temperature the share amounts to
+        #[property(get)]
+        pub thermal_rise: Cell<f32>,
+        pub smoothed_cpu_usage: Cell<Option<f32>>,
+        /// The PCI addresses of the GPUs used by the entry or its descendants, separated by
+        /// commas
+        #[property(get = Self::gpu_devices, set = Self::set_gpu_devices)]
+        pub gpu_devices: Cell<glib::GString>,
 
         #[property(get, set)]
         pub service_enabled: Cell<bool>,
@@ -74,6 +113,23 @@ mod imp {
         pub service_failed: Cell<bool>,
         #[property(get, set)]
         pub service_stopped: Cell<bool>,
+        #[property(get, set)]
+        pub service_activating: Cell<bool>,
+        #[property(get, set)]
+        pub service_deactivating: Cell<bool>,
+        /// The row of a template unit, its children are the instances of the template
+        #[property(get, set)]
+        pub service_template: Cell<bool>,
+        /// How long the last start of the service took, in microseconds, 0 when unknown
+        #[property(get, set)]
+        pub service_start_time: Cell<u64>,
+        /// Set from when the service was asked to start, stop or restart until the change shows
+        #[property(get)]
+        pub service_pending: Cell<bool>,
+        pub pending_since: Cell<Option<Instant>>,
+        /// How often systemd restarted the service since boot, after it failed or exited
+        #[property(get, set)]
+        pub service_restarts: Cell<u32>,
 
         #[property(get = Self::user, set = Self::set_user)]
         pub user: Cell<glib::GString>,
@@ -83,11 +139,61 @@ mod imp {
         pub description: Cell<glib::GString>,
         #[property(get = Self::file_path, set = Self::set_file_path)]
         pub file_path: Cell<glib::GString>,
+        /// The `ExecStart` lines of the unit file of a service
+        #[property(get = Self::exec_start, set = Self::set_exec_start)]
+        pub exec_start: Cell<glib::GString>,
 
         #[property(get = Self::command_line, set = Self::set_command_line)]
         pub command_line: Cell<glib::GString>,
+        /// The path of the executable of a process
+        #[property(get = Self::exe, set = Self::set_exe)]
+        pub exe: Cell<glib::GString>,
+
+        #[property(get, set)]
+        pub command_line_restricted: Cell<bool>,
+        #[property(get, set)]
+        pub io_restricted: Cell<bool>,
+        /// Whether the process, or one of the processes of the app, belongs to the login
+        /// session of the user
+        #[property(get, set)]
+        pub in_login_session: Cell<bool>,
+        #[property(get, set, builder(UnitOrigin::Unknown))]
+        pub unit_origin: Cell<UnitOrigin>,
+
+        #[property(get, set)]
+        pub has_children: Cell<bool>,
+        /// Whether the usage of the process includes what its descendants use
+        #[property(get, set)]
+        pub merged: Cell<bool>,
+        /// The number of processes shown below the entry when it is fully expanded
+        #[property(get, set)]
+        pub descendant_count: Cell<u32>,
+
+        #[property(get, set, builder(ProcessState::Unknown))]
+        pub process_state: Cell<ProcessState>,
+        /// The kernel function the process sleeps in, and for processes stuck waiting for I/O
+        /// the file they wait on when it can be told. Only kept up to date while shown.
+        #[property(get = Self::wait_channel, set = Self::set_wait_channel)]
+        pub wait_channel: Cell<glib::GString>,
+        /// Whether the entry, or one of the processes it is made of, is a zombie or stuck
+        /// waiting for I/O
+        #[property(get, set)]
+        pub problem_state: Cell<bool>,
+
+        /// Changes whenever one of the values of the extra columns changes
+        #[property(get)]
+        pub custom_values_serial: Cell<u32>,
+        pub custom_values: RefCell<Vec<(ProcField, i64)>>,
 
         pub children: RefCell<gio::ListStore>,
+
+        pub expanded: Cell<bool>,
+        pub collapsed_at: Cell<Option<Instant>>,
+
+        /// Set for a while after a service started, stopped or failed
+        #[property(get)]
+        pub recently_changed: Cell<bool>,
+        pub changed_at: Cell<Option<Instant>>,
     }
 
     impl Default for RowModel {
@@ -101,6 +207,7 @@ mod imp {
 
                 icon: Cell::new(glib::GString::default()),
                 name: Cell::new(glib::GString::default()),
+                summary: Cell::new(glib::GString::default()),
 
                 content_type: Cell::new(ContentType::SectionHeader),
                 section_type: Cell::new(SectionType::FirstSection),
@@ -108,29 +215,141 @@ mod imp {
                 cpu_usage: Cell::new(0.),
                 memory_usage: Cell::new(0),
                 shared_memory_usage: Cell::new(0),
+                swap_usage: Cell::new(0),
                 disk_usage: Cell::new(0.),
                 network_usage: Cell::new(0.),
                 gpu_usage: Cell::new(0.),
                 gpu_memory_usage: Cell::new(0),
+                cpu_time: Cell::new(0.),
+                context_switch_rate: Cell::new(0.),
+                major_fault_rate: Cell::new(0.),
+                io_wait: Cell::new(0.),
+                memory_growth: Cell::new(0.),
+                memory_leak_suspected: Cell::new(false),
+                memory_samples: RefCell::new(VecDeque::new()),
+                thermal_share: Cell::new(0.),
+                thermal_rise: Cell::new(0.),
+                smoothed_cpu_usage: Cell::new(None),
+                gpu_devices: Cell::new(Default::default()),
 
                 service_enabled: Cell::new(false),
                 service_running: Cell::new(false),
                 service_failed: Cell::new(false),
                 service_stopped: Cell::new(false),
+                service_activating: Cell::new(false),
+                service_deactivating: Cell::new(false),
+                service_template: Cell::new(false),
+                service_start_time: Cell::new(0),
+                service_pending: Cell::new(false),
+                pending_since: Cell::new(None),
+                service_restarts: Cell::new(0),
 
                 user: Cell::new(Default::default()),
                 group: Cell::new(Default::default()),
                 description: Cell::new(Default::default()),
                 file_path: Cell::new(Default::default()),
+                exec_start: Cell::new(Default::default()),
 
                 command_line: Cell::new(Default::default()),
+                exe: Cell::new(Default::default()),
+
+                command_line_restricted: Cell::new(false),
+                io_restricted: Cell::new(false),
+                in_login_session: Cell::new(false),
+                unit_origin: Cell::new(UnitOrigin::Unknown),
+
+                has_children: Cell::new(false),
+                merged: Cell::new(false),
+                descendant_count: Cell::new(0),
+
+                process_state: Cell::new(ProcessState::Unknown),
+                wait_channel: Cell::new(Default::default()),
+                problem_state: Cell::new(false),
+
+                custom_values_serial: Cell::new(0),
+                custom_values: RefCell::new(Vec::new()),
 
                 children: RefCell::new(gio::ListStore::new::<super::RowModel>()),
+
+                expanded: Cell::new(true),
+                collapsed_at: Cell::new(None),
+
+                recently_changed: Cell::new(false),
+                changed_at: Cell::new(None),
             }
         }
     }
 
     impl RowModel {
+        /// Puts every value back to where a new row starts out, without notifying anyone.
+        /// Only meant for rows that nothing refers to anymore.
+        pub fn reset(&self) {
+            // The values are put back one by one, and the collections emptied rather than
+            // replaced, so that recycling a row allocates nothing
+            self.id.take();
+            self.pid.set(0);
+            self.service_id.set(0);
+            self.icon.take();
+            self.name.take();
+            self.summary.take();
+            self.content_type.set(ContentType::SectionHeader);
+            self.section_type.set(SectionType::FirstSection);
+            self.cpu_usage.set(0.);
+            self.memory_usage.set(0);
+            self.shared_memory_usage.set(0);
+            self.swap_usage.set(0);
+            self.disk_usage.set(0.);
+            self.network_usage.set(0.);
+            self.gpu_usage.set(0.);
+            self.gpu_memory_usage.set(0);
+            self.cpu_time.set(0.);
+            self.context_switch_rate.set(0.);
+            self.major_fault_rate.set(0.);
+            self.io_wait.set(0.);
+            self.memory_growth.set(0.);
+            self.memory_leak_suspected.set(false);
+            self.memory_samples.borrow_mut().clear();
+            self.thermal_share.set(0.);
+            self.thermal_rise.set(0.);
+            self.smoothed_cpu_usage.set(None);
+            self.gpu_devices.take();
+            self.service_enabled.set(false);
+            self.service_running.set(false);
+            self.service_failed.set(false);
+            self.service_stopped.set(false);
+            self.service_activating.set(false);
+            self.service_deactivating.set(false);
+            self.service_template.set(false);
+            self.service_start_time.set(0);
+            self.service_pending.set(false);
+            self.pending_since.set(None);
+            self.service_restarts.set(0);
+            self.user.take();
+            self.group.take();
+            self.description.take();
+            self.file_path.take();
+            self.exec_start.take();
+            self.command_line.take();
+            self.exe.take();
+            self.command_line_restricted.set(false);
+            self.io_restricted.set(false);
+            self.in_login_session.set(false);
+            self.unit_origin.set(UnitOrigin::Unknown);
+            self.has_children.set(false);
+            self.merged.set(false);
+            self.descendant_count.set(0);
+            self.process_state.set(ProcessState::Unknown);
+            self.wait_channel.take();
+            self.problem_state.set(false);
+            self.custom_values_serial.set(0);
+            self.custom_values.borrow_mut().clear();
+            self.children.borrow().remove_all();
+            self.expanded.set(true);
+            self.collapsed_at.set(None);
+            self.recently_changed.set(false);
+            self.changed_at.set(None);
+        }
+
         pub fn id(&self) -> glib::GString {
             let id = self.id.take();
             self.id.set(id.clone());
@@ -215,6 +434,23 @@ mod imp {
             self.file_path.set(glib::GString::from(file_path));
         }
 
+        pub fn exec_start(&self) -> glib::GString {
+            let exec_start = self.exec_start.take();
+            self.exec_start.set(exec_start.clone());
+
+            exec_start
+        }
+
+        pub fn set_exec_start(&self, exec_start: &str) {
+            let current_exec_start = self.exec_start.take();
+            if current_exec_start == exec_start {
+                self.exec_start.set(current_exec_start);
+                return;
+            }
+
+            self.exec_start.set(glib::GString::from(exec_start));
+        }
+
         pub fn command_line(&self) -> glib::GString {
             let command_line = self.command_line.take();
             self.command_line.set(command_line.clone());
@@ -225,6 +461,50 @@ mod imp {
         pub fn set_command_line(&self, command_line: &str) {
             self.command_line.set(glib::GString::from(command_line));
         }
+
+        pub fn summary(&self) -> glib::GString {
+            let summary = self.summary.take();
+            self.summary.set(summary.clone());
+
+            summary
+        }
+
+        pub fn set_summary(&self, summary: &str) {
+            self.summary.set(glib::GString::from(summary));
+        }
+
+        pub fn exe(&self) -> glib::GString {
+            let exe = self.exe.take();
+            self.exe.set(exe.clone());
+
+            exe
+        }
+
+        pub fn set_exe(&self, exe: &str) {
+            self.exe.set(glib::GString::from(exe));
+        }
+
+        pub fn wait_channel(&self) -> glib::GString {
+            let wait_channel = self.wait_channel.take();
+            self.wait_channel.set(wait_channel.clone());
+
+            wait_channel
+        }
+
+        pub fn set_wait_channel(&self, wait_channel: &str) {
+            self.wait_channel.set(glib::GString::from(wait_channel));
+        }
+
+        pub fn gpu_devices(&self) -> glib::GString {
+            let gpu_devices = self.gpu_devices.take();
+            self.gpu_devices.set(gpu_devices.clone());
+
+            gpu_devices
+        }
+
+        pub fn set_gpu_devices(&self, gpu_devices: &str) {
+            self.gpu_devices.set(glib::GString::from(gpu_devices));
+        }
     }
 
     #[glib::object_subclass]
@@ -273,9 +553,11 @@ impl From<ContentType> for String {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, glib::Enum, Ord, PartialOrd)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, glib::Enum, Ord, PartialOrd)]
 #[enum_type(name = "SectionType")]
 pub enum SectionType {
+    /// Entries the user pinned, shown above the other sections regardless of sorting
+    PinnedSection,
     FirstSection,
     SecondSection,
 }
@@ -290,6 +572,7 @@ pub struct RowModelBuilder {
     icon: glib::GString,
     name: glib::GString,
     command_line: glib::GString,
+    exe: glib::GString,
 
     content_type: ContentType,
     section_type: SectionType,
@@ -307,11 +590,17 @@ pub struct RowModelBuilder {
     running: bool,
     stopped: bool,
     failed: bool,
+    template: bool,
 
     user: glib::GString,
     group: glib::GString,
     file_path: glib::GString,
+    masked: bool,
     description: glib::GString,
+
+    command_line_restricted: bool,
+    io_restricted: bool,
+    in_login_session: bool,
 }
 
 #[allow(unused)]
@@ -327,6 +616,7 @@ impl RowModelBuilder {
             icon: "application-x-executable-symbolic".into(),
             name: glib::GString::default(),
             command_line: Default::default(),
+            exe: Default::default(),
 
             content_type: ContentType::SectionHeader,
             section_type: SectionType::FirstSection,
@@ -343,11 +633,17 @@ impl RowModelBuilder {
             running: false,
             stopped: false,
             failed: false,
+            template: false,
 
             user: Default::default(),
             group: Default::default(),
             file_path: Default::default(),
+            masked: false,
             description: Default::default(),
+
+            command_line_restricted: false,
+            io_restricted: false,
+            in_login_session: false,
         }
     }
 
@@ -381,6 +677,11 @@ impl RowModelBuilder {
         self
     }
 
+    pub fn exe(mut self, exe: &str) -> Self {
+        self.exe = exe.into();
+        self
+    }
+
     pub fn content_type(mut self, content_type: ContentType) -> Self {
         self.content_type = content_type;
         self
@@ -446,6 +747,11 @@ impl RowModelBuilder {
         self
     }
 
+    pub fn template(mut self, template: bool) -> Self {
+        self.template = template;
+        self
+    }
+
     pub fn user(mut self, user: &str) -> Self {
         self.user = user.into();
         self
@@ -461,13 +767,45 @@ impl RowModelBuilder {
         self
     }
 
+    pub fn masked(mut self, masked: bool) -> Self {
+        self.masked = masked;
+        self
+    }
+
     pub fn description(mut self, description: &str) -> Self {
         self.description = description.into();
         self
     }
 
+    pub fn command_line_restricted(mut self, command_line_restricted: bool) -> Self {
+        self.command_line_restricted = command_line_restricted;
+        self
+    }
+
+    pub fn io_restricted(mut self, io_restricted: bool) -> Self {
+        self.io_restricted = io_restricted;
+        self
+    }
+
+    pub fn in_login_session(mut self, in_login_session: bool) -> Self {
+        self.in_login_session = in_login_session;
+        self
+    }
+
     pub fn build(self) -> RowModel {
-        let this = RowModel::new(self.content_type);
+        self.build_reusing(None)
+    }
+
+    /// Builds the row out of `recycled` if there is one, which must not be used for anything
+    /// else anymore
+    pub fn build_reusing(self, recycled: Option<RowModel>) -> RowModel {
+        let this = match recycled {
+            Some(recycled) => {
+                recycled.reset(self.content_type);
+                recycled
+            }
+            None => RowModel::new(self.content_type),
+        };
 
         {
             let this = this.imp();
@@ -492,11 +830,23 @@ impl RowModelBuilder {
             this.service_running.set(self.running);
             this.service_stopped.set(self.stopped);
             this.service_failed.set(self.failed);
+            this.service_template.set(self.template);
 
             this.user.set(self.user);
             this.group.set(self.group);
+            if self.content_type == ContentType::Service {
+                this.unit_origin
+                    .set(UnitOrigin::of(&self.file_path, self.masked));
+            }
             this.file_path.set(self.file_path);
             this.description.set(self.description);
+
+            this.command_line.set(self.command_line);
+            this.exe.set(self.exe);
+            this.command_line_restricted
+                .set(self.command_line_restricted);
+            this.io_restricted.set(self.io_restricted);
+            this.in_login_session.set(self.in_login_session);
         }
 
         this
@@ -507,14 +857,50 @@ glib::wrapper! {
     pub struct RowModel(ObjectSubclass<imp::RowModel>);
 }
 
+// How long the children of a collapsed row are kept up to date, so that quickly toggling a
+// row does not rebuild its children every time
+const COLLAPSED_CHILDREN_GRACE_PERIOD: Duration = Duration::from_secs(30);
+
+// Matches the length of the `recently-changed` animation in the style sheet
+const RECENTLY_CHANGED_DURATION: Duration = Duration::from_secs(4);
+
+// How long a service request is waited for before the actions are available again
+const SERVICE_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Memory growth is measured over this much time
+const MEMORY_GROWTH_WINDOW: Duration = Duration::from_secs(5 * 60);
+const MEMORY_SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+/// Growth is not shown before the memory usage went up for at least this long...
+const MEMORY_GROWTH_MIN_DURATION: Duration = Duration::from_secs(60);
+/// ...and only counts as a likely leak after this long, if it is fast enough
+const MEMORY_LEAK_MIN_DURATION: Duration = Duration::from_secs(3 * 60);
+const MEMORY_LEAK_MIN_GROWTH: f32 = 1024. * 1024.;
+
 impl RowModel {
     pub fn new(content_type: ContentType) -> Self {
         let this: Self = glib::Object::builder().build();
-        this.imp().content_type.set(content_type);
+        this.init(content_type);
 
         this
     }
 
+    /// Makes the row look like one just made with `RowModel::new`
+    pub fn reset(&self, content_type: ContentType) {
+        self.imp().reset();
+        self.init(content_type);
+    }
+
+    fn init(&self, content_type: ContentType) {
+        self.imp().content_type.set(content_type);
+
+        // Apps and services are collapsed as soon as they are shown, everything else is
+        // expanded automatically by the tree model
+        self.imp().expanded.set(match content_type {
+            ContentType::App | ContentType::Service => false,
+            ContentType::SectionHeader | ContentType::Process => true,
+        });
+    }
+
     pub fn children(&self) -> gio::ListStore {
         self.imp().children.borrow().clone()
     }
@@ -522,4 +908,188 @@ impl RowModel {
     pub fn set_children(&self, children: gio::ListStore) {
         self.imp().children.replace(children);
     }
+
+    pub fn custom_value(&self, field: ProcField) -> Option<i64> {
+        self.imp()
+            .custom_values
+            .borrow()
+            .iter()
+            .find(|(f, _)| *f == field)
+            .map(|(_, value)| *value)
+    }
+
+    /// Returns `true` if any of the values changed
+    pub fn set_custom_values(&self, values: &[(ProcField, i64)]) -> bool {
+        let imp = self.imp();
+
+        if imp.custom_values.borrow().as_slice() == values {
+            return false;
+        }
+
+        imp.custom_values.replace(values.to_vec());
+        imp.custom_values_serial
+            .set(imp.custom_values_serial.get().wrapping_add(1));
+        self.notify_custom_values_serial();
+
+        true
+    }
+
+    /// Makes a new row start out collapsed, like the rows of apps and services
+    pub fn start_collapsed(&self) {
+        self.imp().expanded.set(false);
+    }
+
+    /// Records whether the row is expanded in the view
+    pub fn set_expanded(&self, expanded: bool) {
+        let imp = self.imp();
+
+        if imp.expanded.replace(expanded) && !expanded {
+            imp.collapsed_at.set(Some(Instant::now()));
+        }
+        if expanded {
+            imp.collapsed_at.set(None);
+        }
+    }
+
+    /// Whether the children of the row need to be kept up to date. This is the case while the
+    /// row is expanded and for a while after it was collapsed.
+    pub fn children_requested(&self) -> bool {
+        let imp = self.imp();
+
+        imp.expanded.get()
+            || imp
+                .collapsed_at
+                .get()
+                .map(|collapsed_at| collapsed_at.elapsed() < COLLAPSED_CHILDREN_GRACE_PERIOD)
+                .unwrap_or(false)
+    }
+
+    /// Highlights the row for a while, `changed` is whether the state of the service it
+    /// shows changed since the last update
+    pub fn update_recently_changed(&self, changed: bool) {
+        let imp = self.imp();
+
+        if changed {
+            imp.changed_at.set(Some(Instant::now()));
+        }
+
+        let recently_changed = imp
+            .changed_at
+            .get()
+            .is_some_and(|changed_at| changed_at.elapsed() < RECENTLY_CHANGED_DURATION);
+        if imp.recently_changed.replace(recently_changed) != recently_changed {
+            self.notify_recently_changed();
+        }
+    }
+
+    /// Marks the service as waiting for a start, stop or restart to take effect
+    pub fn begin_service_request(&self) {
+        let imp = self.imp();
+
+        imp.pending_since.set(Some(Instant::now()));
+        if !imp.service_pending.replace(true) {
+            self.notify_service_pending();
+        }
+    }
+
+    /// Ends the wait for a request once the service changed, `changed` is whether it did since
+    /// the last update. Requests that never show, e.g. restarting a service that is too quick
+    /// to be seen stopping, end after a while.
+    pub fn update_service_pending(&self, changed: bool) {
+        let imp = self.imp();
+
+        let Some(pending_since) = imp.pending_since.get() else {
+            return;
+        };
+        if !changed && pending_since.elapsed() < SERVICE_REQUEST_TIMEOUT {
+            return;
+        }
+
+        imp.pending_since.set(None);
+        if imp.service_pending.replace(false) {
+            self.notify_service_pending();
+        }
+    }
+
+    /// Measures how fast the memory usage grows, from samples taken while it only went up.
+    /// Call after every update of the memory usage, returns `true` if the growth changed.
+    pub fn update_memory_growth(&self) -> bool {
+        let imp = self.imp();
+
+        let now = Instant::now();
+        let memory_usage = imp.memory_usage.get();
+
+        let (growth, leak_suspected) = {
+            let mut samples = imp.memory_samples.borrow_mut();
+
+            // Memory that was given back is not leaking, start over
+            if samples
+                .back()
+                .is_some_and(|(_, previous)| memory_usage < *previous)
+            {
+                samples.clear();
+            }
+            if samples.back().map_or(true, |(sampled_at, _)| {
+                now.duration_since(*sampled_at) >= MEMORY_SAMPLE_INTERVAL
+            }) {
+                samples.push_back((now, memory_usage));
+            }
+            while samples.len() > 1
+                && samples.front().is_some_and(|(sampled_at, _)| {
+                    now.duration_since(*sampled_at) > MEMORY_GROWTH_WINDOW
+                })
+            {
+                samples.pop_front();
+            }
+
+            let Some(&(first_sampled_at, first_usage)) = samples.front() else {
+                return false;
+            };
+            let duration = now.duration_since(first_sampled_at);
+            if duration < MEMORY_GROWTH_MIN_DURATION || memory_usage <= first_usage {
+                (0., false)
+            } else {
+                let growth = (memory_usage - first_usage) as f32 / duration.as_secs_f32() * 60.;
+                (
+                    growth,
+                    duration >= MEMORY_LEAK_MIN_DURATION && growth >= MEMORY_LEAK_MIN_GROWTH,
+                )
+            }
+        };
+
+        if imp.memory_leak_suspected.replace(leak_suspected) != leak_suspected {
+            self.notify_memory_leak_suspected();
+        }
+
+        if imp.memory_growth.get() == growth {
+            return false;
+        }
+        imp.memory_growth.set(growth);
+        self.notify_memory_growth();
+
+        true
+    }
+
+    /// Puts a share of the CPU heating up down to the entry, by its CPU usage smoothed the same
+    /// way as the temperature. Call with every new CPU usage, before it is set.
+    pub fn update_thermal_load(&self, cpu_usage: f32, thermal_pressure: Option<&ThermalPressure>) {
+        let imp = self.imp();
+
+        let cpu_usage = smooth(imp.smoothed_cpu_usage.get(), cpu_usage);
+        imp.smoothed_cpu_usage.set(Some(cpu_usage));
+
+        let (share, rise) =
+            thermal_pressure.map_or((0., 0.), |pressure| pressure.attribute(cpu_usage));
+        if imp.thermal_share.replace(share) != share {
+            self.notify_thermal_share();
+        }
+        if imp.thermal_rise.replace(rise) != rise {
+            self.notify_thermal_rise();
+        }
+    }
+
+    /// Whether some of the details of the process could not be read due to missing permissions
+    pub fn is_restricted(&self) -> bool {
+        self.command_line_restricted() || self.io_restricted()
+    }
 }
@@ -0,0 +1,213 @@
+/* table_view/sections.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::collections::HashMap;
+
+use gtk::gio;
+use gtk::glib::{self, g_critical};
+use gtk::prelude::*;
+
+use crate::settings;
+use crate::table_view::{ContentType, RowModel, SectionType, SettingsValues, TableView};
+
+/// The column and direction one section is sorted by, instead of by the column headers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionSort {
+    pub column_id: String,
+    pub order: gtk::SortType,
+}
+
+/// How sections are named in the settings, the pinned one has no state of its own
+pub fn section_key(section_type: SectionType) -> Option<&'static str> {
+    match section_type {
+        SectionType::PinnedSection => None,
+        SectionType::FirstSection => Some("first"),
+        SectionType::SecondSection => Some("second"),
+    }
+}
+
+pub fn section_from_key(key: &str) -> Option<SectionType> {
+    match key {
+        "first" => Some(SectionType::FirstSection),
+        "second" => Some(SectionType::SecondSection),
+        _ => None,
+    }
+}
+
+pub fn load_sorting(table_view: &TableView) -> HashMap<SectionType, SectionSort> {
+    let key = table_view.format_settings_key(&SettingsValues::SectionSorting);
+
+    settings!()
+        .value(&key)
+        .get::<Vec<(String, String, bool)>>()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(section, column_id, descending)| {
+            let sort = SectionSort {
+                column_id,
+                order: if descending {
+                    gtk::SortType::Descending
+                } else {
+                    gtk::SortType::Ascending
+                },
+            };
+            Some((section_from_key(&section)?, sort))
+        })
+        .collect()
+}
+
+pub fn store_sorting(table_view: &TableView, sorting: &HashMap<SectionType, SectionSort>) {
+    let key = table_view.format_settings_key(&SettingsValues::SectionSorting);
+
+    let mut value = sorting
+        .iter()
+        .filter_map(|(section_type, sort)| {
+            Some((
+                section_key(*section_type)?.to_owned(),
+                sort.column_id.clone(),
+                sort.order == gtk::SortType::Descending,
+            ))
+        })
+        .collect::<Vec<_>>();
+    value.sort_unstable();
+
+    if let Err(e) = settings!().set_value(&key, &value.to_variant()) {
+        g_critical!(
+            "MissionCenter::TableView",
+            "Failed to set {} setting: {}",
+            key,
+            e
+        );
+    }
+}
+
+/// Expands or collapses the section headers at the top of `tree_model` as they were left, and
+/// remembers when they are expanded or collapsed from then on
+pub fn restore_expansion(table_view: &TableView, tree_model: &gtk::TreeListModel) {
+    let key = table_view.format_settings_key(&SettingsValues::CollapsedSections);
+    let collapsed = settings!().strv(&key);
+
+    let root_model = tree_model.model();
+    for position in 0..root_model.n_items() {
+        let Some(section_key) = root_model
+            .item(position)
+            .and_downcast::<RowModel>()
+            .filter(|row_model| row_model.content_type() == ContentType::SectionHeader)
+            .and_then(|row_model| section_key(row_model.section_type()))
+        else {
+            continue;
+        };
+        let Some(row) = tree_model.child_row(position) else {
+            continue;
+        };
+
+        row.set_expanded(!collapsed.iter().any(|entry| entry.as_str() == section_key));
+        row.connect_expanded_notify({
+            let key = key.clone();
+            move |row| store_expansion(&key, section_key, row.is_expanded())
+        });
+    }
+}
+
+fn store_expansion(key: &str, section_key: &str, expanded: bool) {
+    let settings = settings!();
+
+    let mut collapsed = settings
+        .strv(key)
+        .iter()
+        .map(|entry| entry.to_string())
+        .filter(|entry| entry != section_key)
+        .collect::<Vec<_>>();
+    if !expanded {
+        collapsed.push(section_key.to_owned());
+    }
+
+    if let Err(e) = settings.set_strv(key, collapsed) {
+        g_critical!(
+            "MissionCenter::TableView",
+            "Failed to set {} setting: {}",
+            key,
+            e
+        );
+    }
+}
+
+/// Orders rows by the section they are in, pinned rows first, then each section with its header
+/// on top. Returns `None` for rows of the same section, which the columns decide the order of.
+///
+/// This is done before any column sorter is asked, in the same direction for every column and
+/// sort order, so that sorting never moves a section.
+pub fn compare_sections(
+    lhs: &glib::Object,
+    rhs: &glib::Object,
+    sections_hidden: bool,
+) -> Option<gtk::Ordering> {
+    let lhs_model = lhs.downcast_ref::<RowModel>()?;
+    let rhs_model = rhs.downcast_ref::<RowModel>()?;
+
+    // Pinned entries stay on top, even when sections are hidden
+    let lhs_pinned = lhs_model.section_type() == SectionType::PinnedSection;
+    let rhs_pinned = rhs_model.section_type() == SectionType::PinnedSection;
+    let mut order = rhs_pinned.cmp(&lhs_pinned);
+
+    // With section headers hidden, entries from both sections are sorted together
+    if !sections_hidden {
+        order = order
+            .then_with(|| lhs_model.section_type().cmp(&rhs_model.section_type()))
+            .then_with(|| lhs_model.content_type().cmp(&rhs_model.content_type()));
+    }
+
+    (order != std::cmp::Ordering::Equal).then(|| order.into())
+}
+
+/// Compares two rows of a section that is sorted on its own. Returns `None` for rows the column
+/// headers decide the order of.
+pub fn compare(
+    sorting: &HashMap<SectionType, SectionSort>,
+    columns: &gio::ListModel,
+    lhs: &glib::Object,
+    rhs: &glib::Object,
+) -> Option<gtk::Ordering> {
+    let lhs_model = lhs.downcast_ref::<RowModel>()?;
+    let rhs_model = rhs.downcast_ref::<RowModel>()?;
+
+    // Section headers are always ordered by the column headers, so that they stay in place
+    let section_type = lhs_model.section_type();
+    if section_type != rhs_model.section_type()
+        || lhs_model.content_type() == ContentType::SectionHeader
+        || rhs_model.content_type() == ContentType::SectionHeader
+    {
+        return None;
+    }
+
+    let sort = sorting.get(&section_type)?;
+    let sorter = (0..columns.n_items())
+        .filter_map(|i| columns.item(i))
+        .filter_map(|item| item.downcast::<gtk::ColumnViewColumn>().ok())
+        .find(|column| column.id().as_deref() == Some(sort.column_id.as_str()))?
+        .sorter()?;
+
+    let order = std::cmp::Ordering::from(sorter.compare(lhs, rhs));
+    Some(if sort.order == gtk::SortType::Descending {
+        order.reverse().into()
+    } else {
+        order.into()
+    })
+}
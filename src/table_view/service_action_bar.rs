@@ -26,6 +26,7 @@ use gtk::{gio, glib, subclass::prelude::*};
 
 use crate::table_view::row_model::{ContentType, RowModel};
 use crate::table_view::TableView;
+use crate::widgets::OverflowBox;
 
 mod imp {
     use super::*;
@@ -68,6 +69,8 @@ mod imp {
         type ParentType = gtk::Box;
 
         fn class_init(klass: &mut Self::Class) {
+            OverflowBox::ensure_type();
+
             klass.bind_template();
         }
 
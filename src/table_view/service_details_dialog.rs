@@ -25,7 +25,13 @@ use adw::gio;
 use adw::{prelude::*, subclass::prelude::*};
 use gtk::glib::{self, g_warning, ParamSpec, Properties, SignalHandlerId, Value};
 
-use crate::services_page::actions;
+use crate::magpie_client::{
+    service_dependencies, service_resource_limits, service_unit_file, set_service_resource_limits,
+    ResourceLimits,
+};
+use crate::table_view::actions::{create_action_group, ActionTarget, SERVICE_ACTIONS};
+use crate::table_view::columns::format_start_time;
+use crate::table_view::dependency_graph::DependencyGraph;
 use crate::table_view::row_model::RowModel;
 use crate::table_view::TableView;
 use crate::{app, i18n::*};
@@ -54,6 +60,10 @@ mod imp {
         label_running: TemplateChild<gtk::Label>,
         #[template_child]
         switch_enabled: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        label_start_time: TemplateChild<gtk::Label>,
+        #[template_child]
+        label_restarts: TemplateChild<gtk::Label>,
 
         #[template_child]
         group_process: TemplateChild<adw::PreferencesGroup>,
@@ -66,6 +76,27 @@ mod imp {
         #[template_child]
         file_location: TemplateChild<gtk::Label>,
 
+        #[template_child]
+        group_limits: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        apply_limits: TemplateChild<gtk::Button>,
+        #[template_child]
+        memory_max: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        cpu_quota: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        tasks_max: TemplateChild<adw::SpinRow>,
+
+        #[template_child]
+        unit_file_expander: TemplateChild<gtk::Expander>,
+        #[template_child]
+        unit_file_buffer: TemplateChild<gtk::TextBuffer>,
+
+        #[template_child]
+        dependencies_expander: TemplateChild<gtk::Expander>,
+        #[template_child]
+        dependency_graph: TemplateChild<DependencyGraph>,
+
         #[template_child]
         logs_overlay: TemplateChild<gtk::Overlay>,
         #[template_child]
@@ -82,6 +113,9 @@ mod imp {
 
         copy_logs_button: gtk::Button,
 
+        // The limits as systemd reported them, before they were edited
+        limits: Cell<Option<ResourceLimits>>,
+
         list_item_running_notify: Cell<u64>,
         list_item_enabled_notify: Cell<u64>,
         list_item_enabled_user_change: Cell<bool>,
@@ -104,6 +138,15 @@ mod imp {
                 label_group: TemplateChild::default(),
 
                 file_location: TemplateChild::default(),
+                group_limits: TemplateChild::default(),
+                apply_limits: TemplateChild::default(),
+                memory_max: TemplateChild::default(),
+                cpu_quota: TemplateChild::default(),
+                tasks_max: TemplateChild::default(),
+                unit_file_expander: TemplateChild::default(),
+                unit_file_buffer: TemplateChild::default(),
+                dependencies_expander: TemplateChild::default(),
+                dependency_graph: TemplateChild::default(),
                 logs_overlay: TemplateChild::default(),
                 logs_expander: TemplateChild::default(),
                 logs_buffer: TemplateChild::default(),
@@ -115,6 +158,8 @@ mod imp {
 
                 copy_logs_button: gtk::Button::new(),
 
+                limits: Cell::new(None),
+
                 list_item_running_notify: Cell::new(0),
                 list_item_enabled_notify: Cell::new(0),
                 list_item_enabled_user_change: Cell::new(true),
@@ -126,6 +171,140 @@ mod imp {
         fn list_item(&self) -> RowModel {
             unsafe { self.list_item.get().unwrap_unchecked().clone() }
         }
+
+        fn edited_limits(&self) -> Option<ResourceLimits> {
+            let limits = self.limits.get()?;
+            let limit = |value: f64| (value >= 1.).then_some(value.round());
+
+            Some(ResourceLimits {
+                memory_max: limit(self.memory_max.value()).map(|mib| mib as u64 * 1024 * 1024),
+                cpu_quota: limit(self.cpu_quota.value()),
+                tasks_max: limit(self.tasks_max.value()).map(|tasks| tasks as u64),
+                user: limits.user,
+            })
+        }
+
+        fn set_limits(&self, limits: Option<ResourceLimits>) {
+            self.limits.set(limits);
+            self.group_limits.set_visible(limits.is_some());
+
+            let Some(limits) = limits else {
+                return;
+            };
+            self.memory_max.set_value(
+                limits
+                    .memory_max
+                    .map(|bytes| (bytes as f64 / (1024. * 1024.)).ceil())
+                    .unwrap_or_default(),
+            );
+            self.cpu_quota.set_value(limits.cpu_quota.unwrap_or_default());
+            self.tasks_max.set_value(limits.tasks_max.unwrap_or_default() as f64);
+            self.apply_limits.set_sensitive(false);
+        }
+
+        fn load_limits(&self) {
+            service_resource_limits(&self.list_item().name(), {
+                let this = self.obj().downgrade();
+                move |limits| {
+                    if let Some(this) = this.upgrade() {
+                        this.imp().set_limits(limits);
+                    }
+                }
+            });
+        }
+
+        // The limits, unit file, dependencies and logs are asked from the system for the service
+        fn load_from_system(&self, list_item: &RowModel) {
+            let pid = list_item.pid();
+
+            self.load_limits();
+
+            service_unit_file(&list_item.name(), {
+                let this = self.obj().downgrade();
+                move |contents| {
+                    let (Some(this), Some(contents)) = (this.upgrade(), contents) else {
+                        return;
+                    };
+
+                    let this = this.imp();
+                    this.set_unit_file(&contents);
+                    this.unit_file_expander.set_visible(true);
+                }
+            });
+
+            service_dependencies(&list_item.name(), {
+                let this = self.obj().downgrade();
+                move |units| {
+                    let Some(this) = this.upgrade() else {
+                        return;
+                    };
+                    // Just the service itself
+                    if units.len() <= 1 {
+                        return;
+                    }
+
+                    let this = this.imp();
+                    let linked = units
+                        .iter()
+                        .map(|unit| service_id(&unit.name).is_some())
+                        .collect();
+                    this.dependency_graph.set_units(units, linked);
+                    this.dependencies_expander.set_visible(true);
+                }
+            });
+
+            let logs = app!().sys_info().and_then(|sys_info| {
+                Ok(sys_info.service_logs(list_item.service_id(), NonZeroU32::new(pid)))
+            });
+
+            match logs {
+                Ok(logs) => {
+                    if !logs.is_empty() {
+                        self.logs_buffer.set_text(&logs);
+                        self.logs_expander.set_visible(true);
+                    }
+                }
+                Err(e) => {
+                    g_warning!(
+                        "MissionCenter::ServiceDetailsDialog",
+                        "Failed to get `sys_info`: {}",
+                        e
+                    );
+                }
+            }
+        }
+
+        fn set_unit_file(&self, contents: &str) {
+            let buffer = &self.unit_file_buffer;
+            buffer.set_text(contents);
+
+            let mut line_start = buffer.start_iter();
+            while !line_start.is_end() {
+                let mut line_end = line_start.clone();
+                if !line_end.ends_line() {
+                    line_end.forward_to_line_end();
+                }
+
+                let line = buffer.text(&line_start, &line_end, false);
+                let trimmed = line.trim_start();
+                if trimmed.starts_with("# /") {
+                    // The path of the file that follows, as printed by `systemctl cat`
+                    buffer.apply_tag_by_name("file", &line_start, &line_end);
+                } else if trimmed.starts_with(['#', ';']) {
+                    buffer.apply_tag_by_name("comment", &line_start, &line_end);
+                } else if trimmed.starts_with('[') {
+                    buffer.apply_tag_by_name("section", &line_start, &line_end);
+                } else if let Some(key_len) = line.find('=') {
+                    let mut key_end = line_start.clone();
+                    key_end.forward_chars(line[..key_len].chars().count() as i32);
+                    buffer.apply_tag_by_name("key", &line_start, &key_end);
+                }
+
+                if !line_start.forward_line() {
+                    break;
+                }
+            }
+        }
     }
 
     #[glib::object_subclass]
@@ -135,6 +314,8 @@ mod imp {
         type ParentType = adw::Dialog;
 
         fn class_init(klass: &mut Self::Class) {
+            DependencyGraph::ensure_type();
+
             klass.bind_template();
         }
 
@@ -161,6 +342,7 @@ mod imp {
 
             if let Some(_) = std::env::var_os("SNAP_CONTEXT") {
                 self.switch_enabled.set_sensitive(false);
+                self.group_limits.set_sensitive(false);
                 self.box_buttons.set_visible(false);
                 self.restart.set_visible(false);
             }
@@ -199,6 +381,57 @@ mod imp {
                 }
             });
 
+            for spin_row in [&*self.memory_max, &*self.cpu_quota, &*self.tasks_max] {
+                spin_row.connect_value_notify({
+                    let this = self.obj().downgrade();
+                    move |_| {
+                        if let Some(this) = this.upgrade() {
+                            let this = this.imp();
+                            let edited = this.edited_limits();
+                            // Values in between whole MiB show up as edited otherwise
+                            let current = this.limits.get().map(|limits| ResourceLimits {
+                                memory_max: limits
+                                    .memory_max
+                                    .map(|bytes| bytes.div_ceil(1024 * 1024) * 1024 * 1024),
+                                ..limits
+                            });
+                            this.apply_limits.set_sensitive(edited != current);
+                        }
+                    }
+                });
+            }
+
+            self.apply_limits.connect_clicked({
+                let this = self.obj().downgrade();
+                move |button| {
+                    let Some(this) = this.upgrade() else {
+                        return;
+                    };
+                    let this = this.imp();
+                    let Some(limits) = this.edited_limits() else {
+                        return;
+                    };
+
+                    button.set_sensitive(false);
+                    let this_weak = this.obj().downgrade();
+                    let name = this.list_item().name();
+                    set_service_resource_limits(&name, limits, move |changed| {
+                        if !changed {
+                            g_warning!(
+                                "MissionCenter::ServiceDetailsDialog",
+                                "Failed to change the resource limits of {}",
+                                name
+                            );
+                        }
+
+                        // Shows what systemd made of the values, or the old ones if it failed
+                        if let Some(this) = this_weak.upgrade() {
+                            this.imp().load_limits();
+                        }
+                    });
+                }
+            });
+
             self.copy_logs_button.set_margin_top(14);
             self.copy_logs_button.set_margin_end(2);
             self.copy_logs_button.set_valign(gtk::Align::Start);
@@ -224,15 +457,76 @@ mod imp {
 
             self.logs_overlay.add_overlay(&self.copy_logs_button);
 
+            let key_color = adw::StyleManager::default().accent_color_rgba();
+            self.unit_file_buffer
+                .create_tag(Some("key"), &[("foreground-rgba", &key_color)]);
+            self.unit_file_buffer
+                .create_tag(Some("section"), &[("weight", &700)]);
+            self.unit_file_buffer
+                .create_tag(Some("comment"), &[("style", &gtk::pango::Style::Italic)]);
+            self.unit_file_buffer.create_tag(
+                Some("file"),
+                &[("weight", &700), ("style", &gtk::pango::Style::Italic)],
+            );
+
+            self.file_location.connect_activate_link({
+                let this = self.obj().downgrade();
+                move |_, _| {
+                    if let Some(this) = this.upgrade() {
+                        let path = this.imp().list_item().file_path();
+                        let file = gio::File::for_path(path.as_str());
+                        let window = this.root().and_downcast::<gtk::Window>();
+                        gtk::FileLauncher::new(Some(&file)).open_containing_folder(
+                            window.as_ref(),
+                            None::<&gio::Cancellable>,
+                            |result| {
+                                if let Err(e) = result {
+                                    g_warning!(
+                                        "MissionCenter::ServiceDetailsDialog",
+                                        "Failed to open the folder of the unit file: {}",
+                                        e
+                                    );
+                                }
+                            },
+                        );
+                    }
+
+                    glib::Propagation::Stop
+                }
+            });
+
+            self.dependency_graph.connect_unit_activated({
+                let this = self.obj().downgrade();
+                move |_, name| {
+                    let Some(this) = this.upgrade() else {
+                        return;
+                    };
+
+                    let Some(id) = service_id(name) else {
+                        return;
+                    };
+                    if this
+                        .imp()
+                        .column_view
+                        .borrow()
+                        .scroll_to_and_select(&id.to_string())
+                    {
+                        this.close();
+                    }
+                }
+            });
+
             let column_view = self.column_view.borrow();
             let column_view = &*column_view;
 
-            let service_actions = gio::SimpleActionGroup::new();
-            service_actions.add_action(&actions::action_start(column_view));
-            service_actions.add_action(&actions::action_stop(column_view));
-            service_actions.add_action(&actions::action_restart(column_view));
+            let service_actions = create_action_group(
+                column_view,
+                SERVICE_ACTIONS
+                    .iter()
+                    .filter(|action| action.name != "details"),
+            );
             self.obj()
-                .insert_action_group("service", Some(&service_actions));
+                .insert_action_group(ActionTarget::Service.group_name(), Some(&service_actions));
         }
     }
 
@@ -240,6 +534,15 @@ mod imp {
         fn realize(&self) {
             self.parent_realize();
 
+            self.unit_file_buffer.set_text("");
+            self.unit_file_expander.set_visible(false);
+            self.unit_file_expander.set_expanded(false);
+
+            self.dependencies_expander.set_visible(false);
+            self.dependencies_expander.set_expanded(false);
+
+            self.set_limits(None);
+
             self.logs_buffer.set_text("");
             self.logs_expander.set_visible(false);
 
@@ -261,6 +564,16 @@ mod imp {
             self.label_running.set_text(&running);
             self.switch_enabled.set_active(list_item.service_enabled());
 
+            // Only systemd keeps track of these
+            let start_time = list_item.service_start_time();
+            if start_time > 0 {
+                self.label_start_time.set_text(&format_start_time(start_time));
+            } else {
+                self.label_start_time.set_text(&i18n("N/A"));
+            }
+            self.label_restarts
+                .set_text(&list_item.service_restarts().to_string());
+
             let mut group_empty = true;
             let pid = list_item.pid();
             if pid > 0 {
@@ -289,9 +602,16 @@ mod imp {
             let location = list_item.file_path();
             if !location.is_empty() {
                 group_empty = false;
-                self.file_location.set_text(&list_item.file_path());
+                self.file_location.set_markup(&format!(
+                    "<a href=\"{}\">{}</a>",
+                    glib::markup_escape_text(&gio::File::for_path(location.as_str()).uri()),
+                    glib::markup_escape_text(&location)
+                ));
+                self.file_location
+                    .set_tooltip_text(Some(&i18n("Open Containing Folder")));
             } else {
                 self.file_location.set_text(&i18n("Unknown"));
+                self.file_location.set_tooltip_text(None);
             }
 
             if group_empty {
@@ -300,24 +620,13 @@ mod imp {
                 self.group_process.set_visible(true);
             }
 
-            let logs = app!().sys_info().and_then(|sys_info| {
-                Ok(sys_info.service_logs(list_item.service_id(), NonZeroU32::new(pid)))
-            });
-
-            match logs {
-                Ok(logs) => {
-                    if !logs.is_empty() {
-                        self.logs_buffer.set_text(&logs);
-                        self.logs_expander.set_visible(true);
-                    }
-                }
-                Err(e) => {
-                    g_warning!(
-                        "MissionCenter::ServiceDetailsDialog",
-                        "Failed to get `sys_info`: {}",
-                        e
-                    );
-                }
+            // What the system tells now is not about the service as it was when the snapshot was
+            // taken, and the service can't be changed from a snapshot
+            if self.column_view.borrow().is_showing_snapshot() {
+                self.switch_enabled.set_sensitive(false);
+                self.group_limits.set_sensitive(false);
+            } else {
+                self.load_from_system(&list_item);
             }
 
             let notify = list_item.connect_service_running_notify({
@@ -383,6 +692,15 @@ impl ServiceDetailsDialog {
     }
 }
 
+// Units are listed with their suffix, which Magpie leaves off of some service names
+fn service_id(unit: &str) -> Option<u64> {
+    let window = app!().window()?;
+    window.service_id(unit).or_else(|| {
+        unit.strip_suffix(".service")
+            .and_then(|name| window.service_id(name))
+    })
+}
+
 fn to_signal_id(id: u64) -> SignalHandlerId {
     unsafe { std::mem::transmute(id) }
 }
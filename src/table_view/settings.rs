@@ -4,8 +4,12 @@ use gtk::subclass::prelude::*;
 use gtk::{gio, glib};
 
 use crate::settings;
+use crate::table_view::columns::HeatMapPalette;
 use crate::table_view::SettingsValues::*;
-use crate::table_view::TableView;
+use crate::table_view::{zoom, SettingsNamespace, TableView};
+
+// Value of `compact` in the `io.missioncenter.RowDensity` enum
+const ROW_DENSITY_COMPACT: i32 = 1;
 
 pub fn configure(table_view: &TableView) {
     let settings = settings!();
@@ -18,6 +22,16 @@ pub fn configure(table_view: &TableView) {
         )
         .build();
 
+    table_view.imp().update_cpu_usage_mode();
+    settings.connect_changed(Some("apps-page-core-count-affects-percentages"), {
+        let this = table_view.downgrade();
+        move |_, _| {
+            if let Some(this) = this.upgrade() {
+                this.imp().update_cpu_usage_mode();
+            }
+        }
+    });
+
     table_view.set_use_merged_stats(settings.boolean("apps-page-merged-process-stats"));
     settings.connect_changed(Some("apps-page-merged-process-stats"), {
         let this = table_view.downgrade();
@@ -30,9 +44,109 @@ pub fn configure(table_view: &TableView) {
         }
     });
 
+    settings
+        .bind("apps-page-stable-sorting", &*table_view, "stable-sorting")
+        .build();
+    table_view.connect_stable_sorting_notify(|table_view| {
+        if let Some(row_sorter) = table_view.imp().row_sorter.get() {
+            row_sorter.changed(gtk::SorterChange::Different);
+        }
+    });
+
+    settings
+        .bind("apps-page-show-totals", &*table_view, "show-totals")
+        .build();
+
+    settings
+        .bind("apps-page-show-summary", &*table_view, "show-summary")
+        .build();
+
+    table_view.set_compact(settings.enum_("apps-page-row-density") == ROW_DENSITY_COMPACT);
+    settings.connect_changed(Some("apps-page-row-density"), {
+        let this = table_view.downgrade();
+        move |settings, _| {
+            if let Some(this) = this.upgrade() {
+                this.set_compact(settings.enum_("apps-page-row-density") == ROW_DENSITY_COMPACT);
+            }
+        }
+    });
+
+    zoom::configure(table_view, &settings);
+
+    set_heat_map_palette(table_view, settings.enum_("apps-page-heat-map"));
+    settings.connect_changed(Some("apps-page-heat-map"), {
+        let this = table_view.downgrade();
+        move |settings, _| {
+            if let Some(this) = this.upgrade() {
+                set_heat_map_palette(&this, settings.enum_("apps-page-heat-map"));
+            }
+        }
+    });
+
+    for key in [
+        "performance-page-memory2-use-bytes",
+        "performance-page-memory2-use-base2",
+        "performance-page-drive-use-bytes",
+        "performance-page-drive-use-base2",
+        "performance-page-network-use-bytes",
+        "performance-page-network-use-base2",
+    ] {
+        settings.connect_changed(Some(key), {
+            let this = table_view.downgrade();
+            move |_, _| {
+                if let Some(this) = this.upgrade() {
+                    this.imp().update_units();
+                }
+            }
+        });
+    }
+
+    settings.connect_changed(Some("apps-page-custom-columns"), {
+        let this = table_view.downgrade();
+        move |_, _| {
+            if let Some(this) = this.upgrade() {
+                this.imp().update_custom_columns();
+            }
+        }
+    });
+
+    for (key, property) in [
+        ("search-command-line", "search-command-line"),
+        ("search-user", "search-user"),
+        ("search-app-id", "search-app-id"),
+    ] {
+        settings.bind(key, &*table_view, property).build();
+    }
+
+    // Only services have unit files
+    if let SettingsNamespace::ServicesPage = table_view.imp().settings_namespace.get() {
+        settings
+            .bind(
+                "services-page-search-unit-files",
+                &*table_view,
+                "search-unit-files",
+            )
+            .build();
+    }
+
+    let hide_sections_key = table_view.format_settings_key(&HideSections);
+    settings
+        .bind(&hide_sections_key, &*table_view, "hide-sections")
+        .build();
+
     configure_sorting(table_view, &settings);
 }
 
+fn set_heat_map_palette(table_view: &TableView, value: i32) {
+    for css_class in HeatMapPalette::CSS_CLASSES {
+        table_view.remove_css_class(css_class);
+    }
+
+    if let Some(css_class) = HeatMapPalette::from_setting(value).css_class() {
+        table_view.add_css_class(css_class);
+    }
+}
+
 fn configure_sorting(table_view: &TableView, settings: &gio::Settings) {
     let column_view = table_view.column_view();
 
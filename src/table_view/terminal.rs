@@ -0,0 +1,86 @@
+/* table_view/terminal.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use gtk::gio;
+use gtk::glib::{self, g_debug};
+
+// Tried in order when `xdg-terminal-exec`, which knows the terminal the user prefers, is not
+// installed. The ones of the common desktops come first. Next to each terminal are the
+// arguments that make it run the command that follows them instead of a shell.
+const TERMINALS: &[(&str, &[&str])] = &[
+    ("ptyxis", &["--"]),
+    ("kgx", &["--"]),
+    ("gnome-terminal", &["--"]),
+    ("konsole", &["-e"]),
+    ("xfce4-terminal", &["-x"]),
+    ("mate-terminal", &["-x"]),
+    ("lxterminal", &["-e"]),
+    ("x-terminal-emulator", &["-e"]),
+    ("alacritty", &["-e"]),
+    ("kitty", &[]),
+    ("foot", &[]),
+    ("wezterm", &["start", "--"]),
+    ("xterm", &["-e"]),
+];
+
+fn find_terminal() -> Result<(PathBuf, &'static [&'static str]), glib::Error> {
+    std::iter::once(("xdg-terminal-exec", &[] as &[&str]))
+        .chain(TERMINALS.iter().copied())
+        .find_map(|(name, exec_args)| {
+            glib::find_program_in_path(name).map(|terminal| (terminal, exec_args))
+        })
+        .ok_or_else(|| {
+            glib::Error::new(gio::IOErrorEnum::NotFound, "No terminal emulator was found")
+        })
+}
+
+/// Opens the preferred terminal of the user in `directory`. Terminals start their shell in
+/// the directory they were started in, so no terminal specific arguments are needed.
+pub fn open(directory: &Path) -> Result<(), glib::Error> {
+    let (terminal, _) = find_terminal()?;
+
+    g_debug!(
+        "MissionCenter::Terminal",
+        "Opening {} in {}",
+        terminal.display(),
+        directory.display()
+    );
+
+    let launcher = gio::SubprocessLauncher::new(gio::SubprocessFlags::NONE);
+    launcher.set_cwd(directory);
+    launcher.spawn(&[terminal.as_os_str()]).map(|_| ())
+}
+
+/// Runs `command` in a new window of the preferred terminal of the user
+pub fn run(command: &[impl AsRef<OsStr>]) -> Result<(), glib::Error> {
+    let (terminal, exec_args) = find_terminal()?;
+
+    let argv = std::iter::once(terminal.as_os_str())
+        .chain(exec_args.iter().map(OsStr::new))
+        .chain(command.iter().map(AsRef::as_ref))
+        .collect::<Vec<_>>();
+
+    g_debug!("MissionCenter::Terminal", "Running {:?}", argv);
+
+    gio::Subprocess::newv(&argv, gio::SubprocessFlags::NONE).map(|_| ())
+}
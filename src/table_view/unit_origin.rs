@@ -0,0 +1,81 @@
+/* table_view/unit_origin.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use gtk::glib;
+
+use crate::i18n::i18n;
+
+/// Where the unit file of a service comes from, going by the directory it is in
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, glib::Enum, Ord, PartialOrd)]
+#[enum_type(name = "UnitOrigin")]
+pub enum UnitOrigin {
+    /// Installed along with a package, below `/usr/lib/systemd`
+    Vendor,
+    /// Written or customized by the administrator, below `/etc/systemd`
+    Local,
+    /// Created by the user, below `~/.config/systemd` or `~/.local/share/systemd`
+    User,
+    /// Generated or transient, below `/run`
+    Runtime,
+    /// Linked to `/dev/null`, so that it can't be started
+    Masked,
+    #[default]
+    Unknown,
+}
+
+impl UnitOrigin {
+    /// `masked` tells whether the unit file is linked to `/dev/null`, which is found out on the
+    /// gather thread
+    pub fn of(file_path: &str, masked: bool) -> Self {
+        if file_path.is_empty() {
+            return Self::Unknown;
+        }
+        if file_path == "/dev/null" || masked {
+            return Self::Masked;
+        }
+
+        let home = glib::home_dir();
+        let under_home = |dir: &str| std::path::Path::new(file_path).starts_with(home.join(dir));
+
+        if file_path.starts_with("/etc/") || file_path.starts_with("/usr/local/") {
+            Self::Local
+        } else if file_path.starts_with("/usr/") || file_path.starts_with("/lib/") {
+            Self::Vendor
+        } else if file_path.starts_with("/run/") {
+            Self::Runtime
+        } else if under_home(".config/systemd") || under_home(".local/share/systemd") {
+            Self::User
+        } else {
+            Self::Unknown
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Self::Vendor => i18n("Vendor"),
+            Self::Local => i18n("Local"),
+            Self::User => i18n("User"),
+            Self::Runtime => i18n("Runtime"),
+            Self::Masked => i18n("Masked"),
+            Self::Unknown => String::new(),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+/* table_view/zoom.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use gtk::glib::g_critical;
+use gtk::prelude::*;
+use gtk::{gdk, gio};
+
+use crate::settings;
+use crate::table_view::SettingsValues::Zoom;
+use crate::table_view::TableView;
+
+pub const ZOOM_STEP: f64 = 0.1;
+
+// Same as the range of the settings
+const MIN_ZOOM: f64 = 0.5;
+const MAX_ZOOM: f64 = 2.;
+
+thread_local! {
+    // One provider for each page, shared by the tables of all windows
+    static PROVIDERS: RefCell<HashMap<String, gtk::CssProvider>> = RefCell::new(HashMap::new());
+}
+
+/// Scales the font of the table to the zoom level of its page. Rows grow and shrink with the
+/// text they show.
+pub fn configure(table_view: &TableView, settings: &gio::Settings) {
+    let key = table_view.format_settings_key(&Zoom);
+    let css_class = key.clone();
+    table_view.add_css_class(&css_class);
+
+    apply(&css_class, settings.double(&key));
+    settings.connect_changed(Some(&key), move |settings, key| {
+        apply(&css_class, settings.double(key));
+    });
+}
+
+pub fn zoom_by(table_view: &TableView, step: f64) {
+    let settings = settings!();
+    let key = table_view.format_settings_key(&Zoom);
+
+    // Rounded, so that steps add up to the default level again
+    let zoom = ((settings.double(&key) + step) * 10.).round() / 10.;
+    set_zoom(&settings, &key, zoom.clamp(MIN_ZOOM, MAX_ZOOM));
+}
+
+pub fn reset(table_view: &TableView) {
+    let settings = settings!();
+    let key = table_view.format_settings_key(&Zoom);
+    set_zoom(&settings, &key, 1.);
+}
+
+fn set_zoom(settings: &gio::Settings, key: &str, zoom: f64) {
+    if let Err(e) = settings.set_double(key, zoom) {
+        g_critical!("MissionCenter::TableView", "Failed to set {} setting: {}", key, e);
+    }
+}
+
+fn apply(css_class: &str, zoom: f64) {
+    let Some(display) = gdk::Display::default() else {
+        return;
+    };
+
+    PROVIDERS.with(|providers| {
+        let mut providers = providers.borrow_mut();
+        let provider = providers.entry(css_class.to_owned()).or_insert_with(|| {
+            let provider = gtk::CssProvider::new();
+            gtk::style_context_add_provider_for_display(
+                &display,
+                &provider,
+                gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+            );
+            provider
+        });
+
+        // Set on the table as a whole, the compact row density scales the font of its rows
+        // relative to it
+        provider.load_from_string(&format!(".{} {{ font-size: {:.2}em; }}", css_class, zoom));
+    });
+}
@@ -0,0 +1,128 @@
+/* usage_history_dialog.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use crate::i18n::i18n_f;
+use crate::magpie_client::{app_totals, AppUsage};
+use crate::DataType;
+
+const DAYS: u32 = 7;
+const MAX_APPS: usize = 25;
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/missioncenter/MissionCenter/ui/usage_history_dialog.ui")]
+    pub struct UsageHistoryDialog {
+        #[template_child]
+        order: TemplateChild<adw::ToggleGroup>,
+        #[template_child]
+        app_list: TemplateChild<gtk::ListBox>,
+    }
+
+    impl UsageHistoryDialog {
+        fn populate(&self) {
+            self.app_list.remove_all();
+
+            let mut totals = app_totals(DAYS);
+            let key: fn(&AppUsage) -> f64 = match self.order.active_name().as_deref() {
+                Some("disk") => |usage| usage.disk_written,
+                Some("network") => |usage| usage.network_transferred,
+                _ => |usage| usage.cpu_seconds,
+            };
+            totals.sort_unstable_by(|a, b| key(b).total_cmp(&key(a)));
+
+            for usage in totals
+                .iter()
+                .filter(|usage| key(usage) >= 1.)
+                .take(MAX_APPS)
+            {
+                let cpu_time = crate::to_long_human_readable_time(usage.cpu_seconds as u64);
+                let disk_written =
+                    crate::to_human_readable_nice(usage.disk_written as f32, &DataType::DriveBytes);
+                let network_transferred = crate::to_human_readable_nice(
+                    usage.network_transferred as f32,
+                    &DataType::NetworkBytes,
+                );
+
+                let row = adw::ActionRow::builder()
+                    .title(glib::markup_escape_text(&usage.name))
+                    .subtitle(glib::markup_escape_text(&i18n_f(
+                        "{} of CPU time, {} written, {} sent and received",
+                        &[&cpu_time, &disk_written, &network_transferred],
+                    )))
+                    .build();
+                self.app_list.append(&row);
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for UsageHistoryDialog {
+        const NAME: &'static str = "UsageHistoryDialog";
+        type Type = super::UsageHistoryDialog;
+        type ParentType = adw::Dialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for UsageHistoryDialog {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.order.connect_active_name_notify({
+                let this = self.obj().downgrade();
+                move |_| {
+                    if let Some(this) = this.upgrade() {
+                        this.imp().populate();
+                    }
+                }
+            });
+
+            self.populate();
+        }
+    }
+
+    impl WidgetImpl for UsageHistoryDialog {}
+
+    impl AdwDialogImpl for UsageHistoryDialog {}
+}
+
+glib::wrapper! {
+    pub struct UsageHistoryDialog(ObjectSubclass<imp::UsageHistoryDialog>)
+        @extends adw::Dialog, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl UsageHistoryDialog {
+    pub fn new() -> Self {
+        glib::Object::builder().build()
+    }
+}
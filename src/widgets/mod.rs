@@ -1,5 +1,7 @@
 mod list_cell;
+mod overflow_box;
 mod theme_selector;
 
 pub use list_cell::ListCell;
+pub use overflow_box::OverflowBox;
 pub use theme_selector::ThemeSelector;
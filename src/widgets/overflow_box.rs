@@ -0,0 +1,339 @@
+/* widgets/overflow_box.rs
+ *
+ * Copyright 2025 Mission Center Developers
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ *
+ * SPDX-License-Identifier: GPL-3.0-or-later
+ */
+
+use std::cell::{Cell, OnceCell, RefCell};
+
+use glib::{ParamSpec, Properties, Value};
+use gtk::{gio, glib, prelude::*, subclass::prelude::*};
+
+use crate::i18n::i18n;
+
+mod imp {
+    use super::*;
+
+    /// Lays out its children in a row, like a horizontal `gtk::Box`. The children that don't fit
+    /// are left out, and the buttons among them are offered in a "…" menu at the end instead.
+    #[derive(Properties)]
+    #[properties(wrapper_type = super::OverflowBox)]
+    pub struct OverflowBox {
+        #[property(get, set = Self::set_spacing)]
+        spacing: Cell<i32>,
+
+        pub overflow_button: OnceCell<gtk::MenuButton>,
+        // The children that were left out with the last allocation, and the handlers that keep
+        // the menu up to date with changes to them or to their buttons
+        overflowed: RefCell<Vec<gtk::Widget>>,
+        overflowed_handlers: RefCell<Vec<(gtk::Widget, glib::SignalHandlerId)>>,
+        menu_update_queued: Cell<bool>,
+    }
+
+    impl Default for OverflowBox {
+        fn default() -> Self {
+            Self {
+                spacing: Cell::new(0),
+
+                overflow_button: OnceCell::new(),
+                overflowed: RefCell::new(vec![]),
+                overflowed_handlers: RefCell::new(vec![]),
+                menu_update_queued: Cell::new(false),
+            }
+        }
+    }
+
+    impl OverflowBox {
+        fn set_spacing(&self, spacing: i32) {
+            if self.spacing.replace(spacing) != spacing {
+                self.obj().queue_resize();
+            }
+        }
+
+        fn overflow_button(&self) -> &gtk::MenuButton {
+            self.overflow_button
+                .get()
+                .expect("The overflow button is created on construction")
+        }
+
+        // The children besides the overflow button, hidden ones take no room at all
+        fn content_children(&self) -> Vec<gtk::Widget> {
+            let overflow_button = self.overflow_button().upcast_ref::<gtk::Widget>();
+
+            let mut children = vec![];
+            let mut child = self.obj().first_child();
+            while let Some(widget) = child {
+                child = widget.next_sibling();
+                if &widget != overflow_button && widget.is_visible() {
+                    children.push(widget);
+                }
+            }
+            children
+        }
+
+        fn set_overflowed(&self, overflowed: &[gtk::Widget]) {
+            if self.overflowed.borrow().as_slice() == overflowed {
+                return;
+            }
+
+            self.disconnect_overflowed();
+
+            let mut handlers = vec![];
+            for widget in overflowed {
+                let mut watched = buttons(widget)
+                    .into_iter()
+                    .map(Cast::upcast)
+                    .collect::<Vec<gtk::Widget>>();
+                if !watched.contains(widget) {
+                    watched.push(widget.clone());
+                }
+
+                for watched in watched {
+                    let this = self.obj().downgrade();
+                    let handler = watched.connect_notify_local(None, move |_, _| {
+                        if let Some(this) = this.upgrade() {
+                            this.imp().queue_menu_update();
+                        }
+                    });
+                    handlers.push((watched, handler));
+                }
+            }
+
+            self.overflowed_handlers.replace(handlers);
+            self.overflowed.replace(overflowed.to_vec());
+            self.queue_menu_update();
+        }
+
+        fn disconnect_overflowed(&self) {
+            for (widget, handler) in self.overflowed_handlers.take() {
+                widget.disconnect(handler);
+            }
+        }
+
+        // Changing the menu model while allocating would modify the widget tree in the middle of
+        // a layout pass, so the menu is rebuilt once the main loop is idle, at most once for any
+        // number of changes
+        fn queue_menu_update(&self) {
+            if self.menu_update_queued.replace(true) {
+                return;
+            }
+
+            let this = self.obj().downgrade();
+            glib::idle_add_local_once(move || {
+                if let Some(this) = this.upgrade() {
+                    let this = this.imp();
+                    this.menu_update_queued.set(false);
+                    this.update_menu();
+                }
+            });
+        }
+
+        fn update_menu(&self) {
+            let menu = gio::Menu::new();
+            for widget in self.overflowed.borrow().iter() {
+                for button in buttons(widget) {
+                    let Some(action_name) = button.action_name() else {
+                        continue;
+                    };
+                    let label = label_of(button.upcast_ref()).unwrap_or_default();
+                    let item = gio::MenuItem::new(Some(&label), None);
+                    item.set_action_and_target_value(
+                        Some(&action_name),
+                        button.action_target_value().as_ref(),
+                    );
+                    menu.append_item(&item);
+                }
+            }
+
+            self.overflow_button().set_menu_model(Some(&menu));
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for OverflowBox {
+        const NAME: &'static str = "OverflowBox";
+        type Type = super::OverflowBox;
+        type ParentType = gtk::Widget;
+        type Interfaces = (gtk::Buildable,);
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.set_css_name("overflowbox");
+            klass.set_accessible_role(gtk::AccessibleRole::Group);
+        }
+    }
+
+    impl ObjectImpl for OverflowBox {
+        fn properties() -> &'static [ParamSpec] {
+            Self::derived_properties()
+        }
+
+        fn set_property(&self, id: usize, value: &Value, pspec: &ParamSpec) {
+            self.derived_set_property(id, value, pspec);
+        }
+
+        fn property(&self, id: usize, pspec: &ParamSpec) -> Value {
+            self.derived_property(id, pspec)
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let overflow_button = gtk::MenuButton::builder()
+                .icon_name("view-more-symbolic")
+                .tooltip_text(i18n("More Actions"))
+                .build();
+            overflow_button.set_parent(&*self.obj());
+            overflow_button.set_child_visible(false);
+            let _ = self.overflow_button.set(overflow_button);
+        }
+
+        fn dispose(&self) {
+            self.disconnect_overflowed();
+            self.overflowed.take();
+
+            while let Some(child) = self.obj().first_child() {
+                child.unparent();
+            }
+        }
+    }
+
+    impl WidgetImpl for OverflowBox {
+        fn measure(&self, orientation: gtk::Orientation, for_size: i32) -> (i32, i32, i32, i32) {
+            let children = self.content_children();
+
+            if orientation == gtk::Orientation::Vertical {
+                let (minimum, natural) = children
+                    .iter()
+                    .chain(std::iter::once(self.overflow_button().upcast_ref()))
+                    .map(|child| {
+                        let (minimum, natural, _, _) = child.measure(orientation, for_size);
+                        (minimum, natural)
+                    })
+                    .fold((0, 0), |(min, nat), (minimum, natural)| {
+                        (min.max(minimum), nat.max(natural))
+                    });
+                return (minimum, natural, -1, -1);
+            }
+
+            // Everything can go into the menu, so the menu button is all that has to fit
+            let (minimum, _, _, _) = self.overflow_button().measure(orientation, -1);
+            let natural = children
+                .iter()
+                .map(|child| child.measure(orientation, -1).1)
+                .sum::<i32>()
+                + self.spacing.get() * (children.len() as i32 - 1).max(0);
+
+            (minimum, natural.max(minimum), -1, -1)
+        }
+
+        fn size_allocate(&self, width: i32, height: i32, baseline: i32) {
+            let spacing = self.spacing.get();
+            let children = self.content_children();
+            let widths = children
+                .iter()
+                .map(|child| child.measure(gtk::Orientation::Horizontal, -1).1)
+                .collect::<Vec<_>>();
+
+            let total = widths.iter().sum::<i32>() + spacing * (widths.len() as i32 - 1).max(0);
+            let overflow_button = self.overflow_button();
+            let overflows = total > width;
+
+            let mut available = width;
+            if overflows {
+                available -= overflow_button.measure(gtk::Orientation::Horizontal, -1).1 + spacing;
+            }
+
+            let mut x = 0;
+            let mut shown_count = 0;
+            for (index, (child, child_width)) in children.iter().zip(&widths).enumerate() {
+                // Once a child doesn't fit, the ones after it go into the menu too, so that the
+                // actions keep their order
+                let fits = index == shown_count && x + child_width <= available;
+                child.set_child_visible(fits);
+                if !fits {
+                    continue;
+                }
+
+                child.size_allocate(&gtk::Allocation::new(x, 0, *child_width, height), baseline);
+                x += child_width + spacing;
+                shown_count += 1;
+            }
+
+            overflow_button.set_child_visible(overflows);
+            if overflows {
+                let button_width = overflow_button.measure(gtk::Orientation::Horizontal, -1).1;
+                overflow_button.size_allocate(
+                    &gtk::Allocation::new(width - button_width, 0, button_width, height),
+                    baseline,
+                );
+            }
+
+            self.set_overflowed(&children[shown_count..]);
+        }
+    }
+
+    impl BuildableImpl for OverflowBox {
+        fn add_child(&self, builder: &gtk::Builder, child: &glib::Object, type_: Option<&str>) {
+            let Some(widget) = child.downcast_ref::<gtk::Widget>() else {
+                self.parent_add_child(builder, child, type_);
+                return;
+            };
+
+            // Children always go before the overflow button, which stays at the end
+            widget.insert_before(&*self.obj(), Some(self.overflow_button()));
+        }
+    }
+}
+
+glib::wrapper! {
+    pub struct OverflowBox(ObjectSubclass<imp::OverflowBox>)
+        @extends gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+// The buttons in `widget`, or `widget` itself if it is one, in the order they are shown
+fn buttons(widget: &gtk::Widget) -> Vec<gtk::Button> {
+    if let Some(button) = widget.downcast_ref::<gtk::Button>() {
+        return vec![button.clone()];
+    }
+
+    let mut result = vec![];
+    let mut child = widget.first_child();
+    while let Some(widget) = child {
+        child = widget.next_sibling();
+        if widget.is_visible() {
+            result.extend(buttons(&widget));
+        }
+    }
+    result
+}
+
+// Buttons are offered in the menu with the text of their label, even when it is hidden
+fn label_of(widget: &gtk::Widget) -> Option<glib::GString> {
+    if let Some(label) = widget.downcast_ref::<gtk::Label>() {
+        return Some(label.label());
+    }
+
+    let mut child = widget.first_child();
+    while let Some(widget) = child {
+        if let Some(label) = label_of(&widget) {
+            return Some(label);
+        }
+        child = widget.next_sibling();
+    }
+    None
+}
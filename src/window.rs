@@ -18,19 +18,29 @@
  * SPDX-License-Identifier: GPL-3.0-or-later
  */
 
-use std::cell::Cell;
+use std::cell::{Cell, Ref, RefCell};
 use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::rc::Rc;
 use std::sync::OnceLock;
 use std::time::Duration;
 
 use adw::{prelude::*, subclass::prelude::*};
-use glib::{g_critical, idle_add_local_once, ParamSpec, Propagation, Properties, Value};
+use glib::{g_critical, g_warning, idle_add_local_once, ParamSpec, Propagation, Properties, Value};
 use gtk::glib::ControlFlow;
 use gtk::{gdk, gio, glib};
 
+use crate::diagnostic_report::SystemSnapshot;
+use crate::i18n::{i18n, i18n_f, ni18n_f};
+use crate::magpie_client::{
+    IgnoreList, ProcField, Process, ProcessChangeSet, Readings, RemoteHost, Service,
+    ServiceTransition, Snapshot,
+};
+use crate::session_recording::SessionRecording;
+use crate::table_view::{wait_channels_shown, ContentType, RowModelBuilder};
 use crate::widgets::ListCell;
 use crate::widgets::ThemeSelector;
-use crate::{app, magpie_client::Readings, settings};
+use crate::{app, settings};
 
 fn special_shortcuts(
 ) -> &'static HashMap<gdk::ModifierType, HashMap<gdk::Key, fn(&MissionCenterWindow) -> bool>> {
@@ -93,18 +103,25 @@ fn special_shortcuts(
         result
     }
 
-    fn graph_copy(window: &MissionCenterWindow) -> bool {
+    fn ctrl_c(window: &MissionCenterWindow) -> bool {
         let imp = window.imp();
 
-        let result = window.performance_page_active();
-        if result {
+        if window.performance_page_active() {
             let Some(visible_child) = imp.performance_page.imp().page_stack.visible_child() else {
                 return false;
             };
 
             let _ = WidgetExt::activate_action(&visible_child, "graph.copy", None);
+            return true;
+        } else if window.apps_page_active() {
+            imp.apps_page.copy_visible_rows();
+            return true;
+        } else if window.services_page_active() {
+            imp.services_page.copy_visible_rows();
+            return true;
         }
-        result
+
+        false
     }
 
     fn graph_summary(window: &MissionCenterWindow) -> bool {
@@ -196,6 +213,31 @@ fn special_shortcuts(
         false
     }
 
+    fn select_search_match(window: &MissionCenterWindow, forward: bool) -> bool {
+        let imp = window.imp();
+        if !imp.search_button.is_active() {
+            return false;
+        }
+
+        if window.apps_page_active() {
+            imp.apps_page.select_search_match(forward);
+            return true;
+        } else if window.services_page_active() {
+            imp.services_page.select_search_match(forward);
+            return true;
+        }
+
+        false
+    }
+
+    fn next_search_match(window: &MissionCenterWindow) -> bool {
+        select_search_match(window, true)
+    }
+
+    fn previous_search_match(window: &MissionCenterWindow) -> bool {
+        select_search_match(window, false)
+    }
+
     fn services_restart(window: &MissionCenterWindow) -> bool {
         let imp = window.imp();
 
@@ -208,6 +250,42 @@ fn special_shortcuts(
         result
     }
 
+    fn zoom(window: &MissionCenterWindow, steps: i32) -> bool {
+        let imp = window.imp();
+
+        if window.apps_page_active() {
+            imp.apps_page.zoom(steps);
+            return true;
+        } else if window.services_page_active() {
+            imp.services_page.zoom(steps);
+            return true;
+        }
+
+        false
+    }
+
+    fn zoom_in(window: &MissionCenterWindow) -> bool {
+        zoom(window, 1)
+    }
+
+    fn zoom_out(window: &MissionCenterWindow) -> bool {
+        zoom(window, -1)
+    }
+
+    fn reset_zoom(window: &MissionCenterWindow) -> bool {
+        let imp = window.imp();
+
+        if window.apps_page_active() {
+            imp.apps_page.reset_zoom();
+            return true;
+        } else if window.services_page_active() {
+            imp.services_page.reset_zoom();
+            return true;
+        }
+
+        false
+    }
+
     static SHORTCUTS: OnceLock<
         HashMap<gdk::ModifierType, HashMap<gdk::Key, fn(&MissionCenterWindow) -> bool>>,
     > = OnceLock::new();
@@ -232,8 +310,8 @@ fn special_shortcuts(
         ctrl_shortcuts.insert(gdk::Key::f, toggle_search);
         ctrl_shortcuts.insert(gdk::Key::M, graph_summary);
         ctrl_shortcuts.insert(gdk::Key::m, graph_summary);
-        ctrl_shortcuts.insert(gdk::Key::C, graph_copy);
-        ctrl_shortcuts.insert(gdk::Key::c, graph_copy);
+        ctrl_shortcuts.insert(gdk::Key::C, ctrl_c);
+        ctrl_shortcuts.insert(gdk::Key::c, ctrl_c);
         ctrl_shortcuts.insert(gdk::Key::L, ctrl_l);
         ctrl_shortcuts.insert(gdk::Key::l, ctrl_l);
         ctrl_shortcuts.insert(gdk::Key::E, ctrl_e);
@@ -246,8 +324,27 @@ fn special_shortcuts(
         ctrl_shortcuts.insert(gdk::Key::s, services_start);
         ctrl_shortcuts.insert(gdk::Key::R, services_restart);
         ctrl_shortcuts.insert(gdk::Key::r, services_restart);
+        ctrl_shortcuts.insert(gdk::Key::G, next_search_match);
+        ctrl_shortcuts.insert(gdk::Key::g, next_search_match);
+        ctrl_shortcuts.insert(gdk::Key::equal, zoom_in);
+        ctrl_shortcuts.insert(gdk::Key::plus, zoom_in);
+        ctrl_shortcuts.insert(gdk::Key::KP_Add, zoom_in);
+        ctrl_shortcuts.insert(gdk::Key::minus, zoom_out);
+        ctrl_shortcuts.insert(gdk::Key::KP_Subtract, zoom_out);
+        ctrl_shortcuts.insert(gdk::Key::_0, reset_zoom);
+        ctrl_shortcuts.insert(gdk::Key::KP_0, reset_zoom);
         shortcuts.insert(gdk::ModifierType::CONTROL_MASK, ctrl_shortcuts);
 
+        let mut ctrl_shift_shortcuts = HashMap::<gdk::Key, fn(&MissionCenterWindow) -> bool>::new();
+        ctrl_shift_shortcuts.insert(gdk::Key::G, previous_search_match);
+        ctrl_shift_shortcuts.insert(gdk::Key::g, previous_search_match);
+        // Plus is typed with Shift on most layouts
+        ctrl_shift_shortcuts.insert(gdk::Key::plus, zoom_in);
+        shortcuts.insert(
+            gdk::ModifierType::CONTROL_MASK | gdk::ModifierType::SHIFT_MASK,
+            ctrl_shift_shortcuts,
+        );
+
         shortcuts
     })
 }
@@ -263,8 +360,12 @@ mod imp {
         #[template_child]
         pub breakpoint: TemplateChild<adw::Breakpoint>,
         #[template_child]
+        pub narrow_breakpoint: TemplateChild<adw::Breakpoint>,
+        #[template_child]
         pub split_view: TemplateChild<adw::OverlaySplitView>,
         #[template_child]
+        pub host_button: TemplateChild<gtk::MenuButton>,
+        #[template_child]
         pub menu_button: TemplateChild<gtk::MenuButton>,
         #[template_child]
         pub window_content: TemplateChild<adw::ToolbarView>,
@@ -297,6 +398,16 @@ mod imp {
         #[template_child]
         pub header_search_entry: TemplateChild<gtk::SearchEntry>,
         #[template_child]
+        pub search_command_line_check: TemplateChild<gtk::CheckButton>,
+        #[template_child]
+        pub search_user_check: TemplateChild<gtk::CheckButton>,
+        #[template_child]
+        pub search_app_id_check: TemplateChild<gtk::CheckButton>,
+        #[template_child]
+        pub search_unit_files_check: TemplateChild<gtk::CheckButton>,
+        #[template_child]
+        pub search_match_count: TemplateChild<gtk::Label>,
+        #[template_child]
         pub search_button: TemplateChild<gtk::ToggleButton>,
         #[template_child]
         pub loading_box: TemplateChild<gtk::Box>,
@@ -304,6 +415,14 @@ mod imp {
         pub loading_spinner: TemplateChild<adw::Spinner>,
         #[template_child]
         pub stack: TemplateChild<adw::ViewStack>,
+        #[template_child]
+        pub snapshot_banner: TemplateChild<adw::Banner>,
+        #[template_child]
+        pub replay_revealer: TemplateChild<gtk::Revealer>,
+        #[template_child]
+        pub replay_scale: TemplateChild<gtk::Scale>,
+        #[template_child]
+        pub replay_time: TemplateChild<gtk::Label>,
 
         #[property(get)]
         performance_page_active: Cell<bool>,
@@ -323,14 +442,36 @@ mod imp {
         summary_mode: Cell<bool>,
         #[property(get, set)]
         collapse_threshold: Cell<i32>,
+
+        /// The readings shown, shared with the other windows unless they are of a snapshot
+        pub readings: RefCell<Rc<Readings>>,
+        pub system_snapshot: RefCell<SystemSnapshot>,
+
+        /// Where the next readings are saved to as a snapshot
+        pub snapshot_file: RefCell<Option<gio::File>>,
+        /// Set when a snapshot was closed, so that the next readings replace all of its rows
+        pub leaving_snapshot: Cell<bool>,
+
+        pub recording_session: Cell<bool>,
+        pub session_recording: RefCell<SessionRecording>,
+        /// The frame of the recording the tables show while replaying it
+        pub replayed_frame: Cell<Option<u64>>,
+
+        /// Minimized, on another workspace or closed to the background
+        pub hidden: Cell<bool>,
+        /// Set when the tables missed updates while the window was hidden, so that the next
+        /// readings replace all of their rows
+        pub tables_outdated: Cell<bool>,
     }
 
     impl Default for MissionCenterWindow {
         fn default() -> Self {
             Self {
                 breakpoint: TemplateChild::default(),
+                narrow_breakpoint: TemplateChild::default(),
                 split_view: TemplateChild::default(),
                 window_content: TemplateChild::default(),
+                host_button: TemplateChild::default(),
                 menu_button: TemplateChild::default(),
                 bottom_bar: TemplateChild::default(),
                 sidebar_edit_mode_enable_all: TemplateChild::default(),
@@ -346,10 +487,19 @@ mod imp {
                 header_stack: TemplateChild::default(),
                 header_tabs: TemplateChild::default(),
                 header_search_entry: TemplateChild::default(),
+                search_command_line_check: TemplateChild::default(),
+                search_user_check: TemplateChild::default(),
+                search_app_id_check: TemplateChild::default(),
+                search_unit_files_check: TemplateChild::default(),
+                search_match_count: TemplateChild::default(),
                 search_button: TemplateChild::default(),
                 loading_box: TemplateChild::default(),
                 loading_spinner: TemplateChild::default(),
                 stack: TemplateChild::default(),
+                snapshot_banner: TemplateChild::default(),
+                replay_revealer: TemplateChild::default(),
+                replay_scale: TemplateChild::default(),
+                replay_time: TemplateChild::default(),
 
                 performance_page_active: Cell::new(true),
                 apps_page_active: Cell::new(false),
@@ -361,11 +511,50 @@ mod imp {
 
                 summary_mode: Cell::new(false),
                 collapse_threshold: Cell::new(0),
+
+                readings: RefCell::new(Rc::new(Readings::new())),
+                system_snapshot: RefCell::new(SystemSnapshot::default()),
+
+                snapshot_file: RefCell::new(None),
+                leaving_snapshot: Cell::new(false),
+
+                recording_session: Cell::new(false),
+                session_recording: RefCell::new(SessionRecording::default()),
+                replayed_frame: Cell::new(None),
+
+                hidden: Cell::new(false),
+                tables_outdated: Cell::new(false),
             }
         }
     }
 
     impl MissionCenterWindow {
+        // Readings are gathered less often while nobody can see them
+        fn update_hidden(&self) {
+            let obj = self.obj();
+
+            let minimized = obj
+                .surface()
+                .and_downcast::<gdk::Toplevel>()
+                .map(|toplevel| toplevel.state().contains(gdk::ToplevelState::MINIMIZED))
+                .unwrap_or(false);
+            let hidden = minimized || obj.is_suspended() || !obj.is_visible();
+            if self.hidden.replace(hidden) == hidden {
+                return;
+            }
+
+            match app!().sys_info() {
+                Ok(sys_info) => sys_info.set_throttled(hidden),
+                Err(e) => {
+                    g_critical!(
+                        "MissionCenter",
+                        "Failed to get sys_info from MissionCenterApplication: {}",
+                        e
+                    );
+                }
+            }
+        }
+
         fn info_button_visible(&self) -> bool {
             if self.performance_page.is_bound() {
                 self.performance_page_active.get() && self.performance_page.info_button_visible()
@@ -380,6 +569,26 @@ mod imp {
     }
 
     impl MissionCenterWindow {
+        fn update_search_match_count(&self) {
+            let count = if self.apps_page_active.get() {
+                self.apps_page.search_match_count()
+            } else if self.services_page_active.get() {
+                self.services_page.search_match_count()
+            } else {
+                0
+            };
+
+            let searching =
+                self.search_button.is_active() && !self.header_search_entry.text().is_empty();
+            self.search_match_count.set_visible(searching);
+            self.search_match_count.set_label(&ni18n_f(
+                "{} match",
+                "{} matches",
+                count,
+                &[&count.to_string()],
+            ));
+        }
+
         fn update_active_page(&self) {
             use glib::g_critical;
 
@@ -417,6 +626,8 @@ mod imp {
                     return;
                 }
 
+                self.services_page.request_initialization();
+
                 self.performance_page_active.set(false);
                 self.obj().notify_performance_page_active();
 
@@ -566,6 +777,45 @@ mod imp {
             self.obj().add_action(&action);
             app.set_accels_for_action("win.select-tab-services", &["<Control>3"]);
 
+            let action = gio::SimpleAction::new("select-pid", Some(glib::VariantTy::UINT32));
+            action.connect_activate({
+                let this = self.obj().downgrade();
+                move |_, param| {
+                    let Some(this) = this.upgrade() else {
+                        return;
+                    };
+                    let imp = this.imp();
+
+                    let Some(pid) = param.and_then(|p| p.get::<u32>()) else {
+                        g_critical!(
+                            "MissionCenter",
+                            "Invalid or missing PID parameter for `select-pid` action"
+                        );
+                        return;
+                    };
+                    let id = pid.to_string();
+
+                    if imp.services_page_active.get() && imp.services_page.scroll_to_and_select(&id)
+                    {
+                        return;
+                    }
+
+                    if imp.summary_mode.get() {
+                        return;
+                    }
+
+                    imp.stack.set_visible_child_name("apps-page");
+                    if !imp.apps_page.scroll_to_and_select(&id) {
+                        g_critical!(
+                            "MissionCenter",
+                            "Process with PID {} could not be found or is filtered out",
+                            pid
+                        );
+                    }
+                }
+            });
+            self.obj().add_action(&action);
+
             let action =
                 gio::SimpleAction::new_stateful("toggle-sidebar", None, &true.to_variant());
             action.connect_activate({
@@ -618,6 +868,92 @@ mod imp {
             });
             self.obj().add_action(&action);
             app.set_accels_for_action("win.close", &["<Control>W"]);
+
+            let action = gio::SimpleAction::new("save-snapshot", None);
+            action.connect_activate({
+                let this = self.obj().downgrade();
+                move |_, _| {
+                    if let Some(this) = this.upgrade() {
+                        this.choose_snapshot_file();
+                    }
+                }
+            });
+            self.obj().add_action(&action);
+
+            let action = gio::SimpleAction::new("open-snapshot", None);
+            action.connect_activate({
+                let this = self.obj().downgrade();
+                move |_, _| {
+                    if let Some(this) = this.upgrade() {
+                        this.open_snapshot();
+                    }
+                }
+            });
+            self.obj().add_action(&action);
+
+            let action = gio::SimpleAction::new("close-snapshot", None);
+            action.set_enabled(false);
+            action.connect_activate({
+                let this = self.obj().downgrade();
+                move |_, _| {
+                    if let Some(this) = this.upgrade() {
+                        this.close_snapshot();
+                    }
+                }
+            });
+            self.obj().add_action(&action);
+
+            let action =
+                gio::SimpleAction::new_stateful("record-session", None, &false.to_variant());
+            action.connect_activate({
+                let this = self.obj().downgrade();
+                move |action, _| {
+                    let Some(this) = this.upgrade() else {
+                        return;
+                    };
+                    let imp = this.imp();
+
+                    let recording = !imp.recording_session.get();
+                    action.set_state(&recording.to_variant());
+                    imp.recording_session.set(recording);
+                    app!().update_session_recording();
+                    // Each recording starts from scratch
+                    if recording && imp.replayed_frame.get().is_none() {
+                        imp.session_recording.replace(SessionRecording::default());
+                        this.update_replay_range();
+                    }
+                }
+            });
+            self.obj().add_action(&action);
+
+            let action = gio::SimpleAction::new("replay-session", None);
+            action.set_enabled(false);
+            action.connect_activate({
+                let this = self.obj().downgrade();
+                move |_, _| {
+                    if let Some(this) = this.upgrade() {
+                        this.replay_session();
+                    }
+                }
+            });
+            self.obj().add_action(&action);
+
+            let action = gio::SimpleAction::new("replay-step", Some(glib::VariantTy::INT32));
+            action.connect_activate({
+                let this = self.obj().downgrade();
+                move |_, step| {
+                    let Some(this) = this.upgrade() else {
+                        return;
+                    };
+                    let Some(step) = step.and_then(|step| step.get::<i32>()) else {
+                        return;
+                    };
+
+                    let adjustment = this.imp().replay_scale.adjustment();
+                    adjustment.set_value(adjustment.value() + step as f64);
+                }
+            });
+            self.obj().add_action(&action);
         }
 
         fn configure_theme_selection(&self) {
@@ -743,6 +1079,11 @@ mod imp {
             self.configure_actions();
             self.configure_theme_selection();
 
+            self.obj()
+                .connect_suspended_notify(|this| this.imp().update_hidden());
+            self.obj()
+                .connect_visible_notify(|this| this.imp().update_hidden());
+
             idle_add_local_once({
                 let this = self.obj().downgrade();
                 move || {
@@ -753,6 +1094,15 @@ mod imp {
                 }
             });
 
+            self.replay_scale.connect_value_changed({
+                let this = self.obj().downgrade();
+                move |scale| {
+                    if let Some(this) = this.upgrade() {
+                        this.show_replay_frame(scale.value().round() as u64);
+                    }
+                }
+            });
+
             self.sidebar.connect_row_activated({
                 let this = self.obj().downgrade();
                 move |_, _| {
@@ -843,6 +1193,16 @@ mod imp {
             self.header_search_entry
                 .set_key_capture_widget(Some(&self.header_search_entry.get()));
 
+            let settings = settings!();
+            for (check_button, key) in [
+                (&self.search_command_line_check, "search-command-line"),
+                (&self.search_user_check, "search-user"),
+                (&self.search_app_id_check, "search-app-id"),
+                (&self.search_unit_files_check, "services-page-search-unit-files"),
+            ] {
+                settings.bind(key, &**check_button, "active").build();
+            }
+
             self.header_search_entry.connect_search_started({
                 let this = self.obj().downgrade();
                 move |_| {
@@ -860,6 +1220,32 @@ mod imp {
                 }
             });
 
+            self.header_search_entry.connect_search_changed({
+                let this = self.obj().downgrade();
+                move |_| {
+                    if let Some(this) = this.upgrade() {
+                        this.imp().update_search_match_count();
+                    }
+                }
+            });
+
+            self.apps_page.connect_search_match_count_notify({
+                let this = self.obj().downgrade();
+                move |_| {
+                    if let Some(this) = this.upgrade() {
+                        this.imp().update_search_match_count();
+                    }
+                }
+            });
+            self.services_page.connect_search_match_count_notify({
+                let this = self.obj().downgrade();
+                move |_| {
+                    if let Some(this) = this.upgrade() {
+                        this.imp().update_search_match_count();
+                    }
+                }
+            });
+
             self.header_search_entry.connect_stop_search({
                 let this = self.obj().downgrade();
                 move |_| {
@@ -926,6 +1312,32 @@ mod imp {
                 }
             });
 
+            // Phones and tiled windows don't have room for the columns, the tables turn into a
+            // list of names with their usage below them
+            self.narrow_breakpoint.set_condition(Some(
+                &adw::BreakpointCondition::parse("max-width: 600sp").unwrap(),
+            ));
+            self.narrow_breakpoint.connect_apply({
+                let this = self.obj().downgrade();
+                move |_| {
+                    if let Some(this) = this.upgrade() {
+                        let this = this.imp();
+                        this.apps_page.set_narrow(true);
+                        this.services_page.set_narrow(true);
+                    }
+                }
+            });
+            self.narrow_breakpoint.connect_unapply({
+                let this = self.obj().downgrade();
+                move |_| {
+                    if let Some(this) = this.upgrade() {
+                        let this = this.imp();
+                        this.apps_page.set_narrow(false);
+                        this.services_page.set_narrow(false);
+                    }
+                }
+            });
+
             self.obj().connect_performance_page_active_notify({
                 let this = self.obj().downgrade();
                 move |_| {
@@ -980,6 +1392,18 @@ mod imp {
         fn realize(&self) {
             self.parent_realize();
 
+            // Minimizing is only known from the state of the surface
+            if let Some(surface) = self.obj().surface() {
+                surface.connect_notify_local(Some("state"), {
+                    let this = self.obj().downgrade();
+                    move |_, _| {
+                        if let Some(this) = this.upgrade() {
+                            this.imp().update_hidden();
+                        }
+                    }
+                });
+            }
+
             self.stack
                 .set_visible_child_name(settings!().string("window-selected-page").as_str());
         }
@@ -1012,9 +1436,90 @@ impl MissionCenterWindow {
             .build();
 
         sys_info.set_update_speed(settings.uint64("app-update-interval-u64"));
-        sys_info.set_core_count_affects_percentages(
-            settings.boolean("apps-page-core-count-affects-percentages"),
-        );
+        sys_info.set_custom_fields(ProcField::enabled(settings));
+        sys_info.set_read_wait_channels(wait_channels_shown(settings));
+        sys_info.set_ignore_list(IgnoreList::from_settings(settings));
+        update_metrics_exporter(settings, sys_info);
+        this.update_host_button(settings);
+
+        settings.connect_changed(Some("remote-hosts"), {
+            let this = this.downgrade();
+            move |settings, _| {
+                if let Some(this) = this.upgrade() {
+                    this.update_host_button(settings);
+                }
+            }
+        });
+
+        settings.connect_changed(None, |settings, key| {
+            if !key.starts_with("metrics-exporter-") {
+                return;
+            }
+
+            match app!().sys_info() {
+                Ok(sys_info) => update_metrics_exporter(settings, &sys_info),
+                Err(e) => {
+                    g_critical!(
+                        "MissionCenter",
+                        "Failed to get sys_info from MissionCenterApplication: {}",
+                        e
+                    );
+                }
+            };
+        });
+
+        settings.connect_changed(Some("apps-page-custom-columns"), |settings, _| {
+            match app!().sys_info() {
+                Ok(sys_info) => {
+                    sys_info.set_custom_fields(ProcField::enabled(settings));
+                }
+                Err(e) => {
+                    g_critical!(
+                        "MissionCenter",
+                        "Failed to get sys_info from MissionCenterApplication: {}",
+                        e
+                    );
+                }
+            };
+        });
+
+        settings.connect_changed(None, |settings, key| {
+            if !key.ends_with("-hidden-columns") {
+                return;
+            }
+
+            match app!().sys_info() {
+                Ok(sys_info) => {
+                    sys_info.set_read_wait_channels(wait_channels_shown(settings));
+                }
+                Err(e) => {
+                    g_critical!(
+                        "MissionCenter",
+                        "Failed to get sys_info from MissionCenterApplication: {}",
+                        e
+                    );
+                }
+            };
+        });
+
+        settings.connect_changed(None, |settings, key| {
+            if key != "ignore-list" && key != "apps-page-hide-kernel-threads" {
+                return;
+            }
+
+            match app!().sys_info() {
+                Ok(sys_info) => {
+                    sys_info.set_ignore_list(IgnoreList::from_settings(settings));
+                }
+                Err(e) => {
+                    g_critical!(
+                        "MissionCenter",
+                        "Failed to get sys_info from MissionCenterApplication: {}",
+                        e
+                    );
+                }
+            };
+        });
 
         settings.connect_changed(Some("app-update-interval-u64"), |settings, _| {
             let update_speed = settings.uint64("app-update-interval-u64");
@@ -1040,21 +1545,23 @@ impl MissionCenterWindow {
             let this = self.downgrade();
 
             move || {
-                if let Some(this) = this.upgrade() {
-                    this.update_animations();
-                }
+                let Some(this) = this.upgrade() else {
+                    // The window was replaced, when switching to another machine
+                    return ControlFlow::Break;
+                };
+                this.update_animations();
 
                 ControlFlow::Continue
             }
         });
     }
 
-    pub fn set_initial_readings(&self, mut readings: Readings) {
+    pub fn set_initial_readings(&self, readings: &Rc<Readings>) {
         use gtk::glib::*;
 
         self.add_css_class("mission-center-window");
 
-        let ok = self.imp().performance_page.set_initial_readings(&readings);
+        let ok = self.imp().performance_page.set_initial_readings(readings);
         if !ok {
             g_critical!(
                 "MissionCenter",
@@ -1066,7 +1573,7 @@ impl MissionCenterWindow {
             .performance_page
             .add_css_class("mission-center-performance-page");
 
-        let ok = self.imp().apps_page.set_initial_readings(&mut readings);
+        let ok = self.imp().apps_page.set_initial_readings(readings);
         if !ok {
             g_critical!(
                 "MissionCenter",
@@ -1078,18 +1585,26 @@ impl MissionCenterWindow {
             .apps_page
             .add_css_class("mission-center-apps-page");
 
-        let ok = self.imp().services_page.set_initial_readings(&mut readings);
-        if !ok {
-            g_critical!(
-                "MissionCenter",
-                "Failed to set initial readings for services page"
-            );
+        // The Services page is only populated once it is shown for the first time
+        if self.imp().stack.visible_child_name().as_deref() == Some("services-page") {
+            let ok = self.imp().services_page.set_initial_readings(readings);
+            if !ok {
+                g_critical!(
+                    "MissionCenter",
+                    "Failed to set initial readings for services page"
+                );
+            }
         }
 
         self.imp()
             .services_page
             .add_css_class("mission-center-services-page");
 
+        self.imp()
+            .system_snapshot
+            .replace(SystemSnapshot::new(readings));
+        self.imp().readings.replace(Rc::clone(readings));
+
         self.imp().loading_box.set_visible(false);
         self.imp().header_bar.set_visible(true);
         self.imp().stack.set_visible(true);
@@ -1102,35 +1617,403 @@ impl MissionCenterWindow {
         self.imp()
             .split_view
             .set_collapsed(self.imp().should_hide_sidebar());
+    }
 
-        if let Ok(sys_info) = app!().sys_info() {
-            sys_info.continue_reading();
-        } else {
-            g_critical!(
-                "MissionCenter",
-                "Failed to get sys_info from MissionCenterApplication"
-            );
-        }
+    /// The path filter of the Apps page, empty while none is set
+    pub fn path_filter(&self) -> String {
+        self.imp().apps_page.path_filter()
+    }
+
+    pub fn is_recording_session(&self) -> bool {
+        self.imp().recording_session.get()
     }
 
-    pub fn update_readings(&self, readings: &mut Readings) -> bool {
+    /// Whether the window is still waiting for its first readings
+    pub fn is_loading(&self) -> bool {
+        self.imp().loading_box.is_visible()
+    }
+
+    pub fn update_readings(&self, readings: &Rc<Readings>) -> bool {
         let mut result = true;
 
         let this = self.imp();
 
+        if let Some(file) = this.snapshot_file.take() {
+            self.save_snapshot(&file, readings);
+        }
+
+        if this.recording_session.get() {
+            if let Some(frame) = readings.session_frame.clone() {
+                this.session_recording.borrow_mut().record(frame);
+            }
+            self.update_replay_range();
+        }
+
+        // The graphs keep their history, the tables catch up once the window is shown again
+        let hidden = this.hidden.get();
+
         result &= this.performance_page.update_readings(readings);
-        result &= this.apps_page.update_readings(readings);
+
+        // The tables keep showing the snapshot until it is closed
+        if this.snapshot_banner.is_revealed() {
+            return result;
+        }
+        // Rows only know which children they have from the previous update they saw. This
+        // only happens once after the window is shown again, it then gets its own copy of the
+        // readings instead of changing the ones the other windows are shown.
+        let mut readings = Rc::clone(readings);
+        let tables_outdated = !hidden && this.tables_outdated.take();
+        if this.leaving_snapshot.take() || tables_outdated {
+            let readings = Rc::make_mut(&mut readings);
+            readings
+                .process_changes
+                .mark_children_changed(&readings.running_processes);
+        }
+
+        if hidden {
+            this.tables_outdated.set(true);
+            this.apps_page.update_events(&readings);
+        } else {
+            result &= this.apps_page.update_readings(&readings);
+        }
 
         if !readings.system_services.is_empty() || !readings.user_services.is_empty() {
             this.services_stack_page.set_visible(true);
-            result &= this.services_page.update_readings(readings);
+            if !hidden {
+                result &= this.services_page.update_readings(&readings);
+            }
         } else {
             this.services_stack_page.set_visible(false);
         }
 
+        this.system_snapshot.replace(SystemSnapshot::new(&readings));
+
+        // Kept for populating rows that get expanded between updates, and for answering D-Bus
+        // calls. The previous readings are handed back once no window shows them anymore, to be
+        // freed off the main thread.
+        this.readings.replace(readings);
+
         result
     }
 
+    fn choose_snapshot_file(&self) {
+        let date = glib::DateTime::now_local()
+            .and_then(|now| now.format("%F"))
+            .unwrap_or_default();
+
+        let file_dialog = gtk::FileDialog::builder()
+            .title(i18n("Save Snapshot"))
+            .initial_name(format!("{}-{}.mcsnapshot", snapshot_host_name(), date))
+            .build();
+
+        file_dialog.save(Some(self), None::<&gio::Cancellable>, {
+            let this = self.downgrade();
+            move |file| {
+                // Also the case when the dialog is dismissed
+                let (Some(this), Ok(file)) = (this.upgrade(), file) else {
+                    return;
+                };
+                // Written with the next readings, which have everything a snapshot needs
+                this.imp().snapshot_file.replace(Some(file));
+            }
+        });
+    }
+
+    fn save_snapshot(&self, file: &gio::File, readings: &Readings) {
+        let taken_at = glib::DateTime::now_local()
+            .and_then(|now| now.format_iso8601())
+            .unwrap_or_default();
+        let contents = Snapshot::encode(readings, &snapshot_host_name(), &taken_at);
+
+        file.replace_contents_async(
+            contents,
+            None,
+            false,
+            gio::FileCreateFlags::REPLACE_DESTINATION,
+            None::<&gio::Cancellable>,
+            {
+                let this = self.downgrade();
+                move |result| {
+                    let Err((_, e)) = result else {
+                        return;
+                    };
+                    g_critical!("MissionCenter", "Failed to save snapshot: {}", e);
+                    if let Some(this) = this.upgrade() {
+                        this.show_snapshot_error(&i18n("Failed to Save Snapshot"), e.message());
+                    }
+                }
+            },
+        );
+    }
+
+    fn open_snapshot(&self) {
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some(&i18n("Snapshots")));
+        filter.add_suffix("mcsnapshot");
+
+        let file_dialog = gtk::FileDialog::builder()
+            .title(i18n("Open Snapshot"))
+            .default_filter(&filter)
+            .build();
+
+        file_dialog.open(Some(self), None::<&gio::Cancellable>, {
+            let this = self.downgrade();
+            move |file| {
+                let Ok(file) = file else {
+                    return;
+                };
+                file.load_contents_async(None::<&gio::Cancellable>, move |result| {
+                    let Some(this) = this.upgrade() else {
+                        return;
+                    };
+
+                    let snapshot = result.map_err(|e| e.to_string()).and_then(|(contents, _)| {
+                        Snapshot::decode(&contents).map_err(|e| e.to_string())
+                    });
+                    match snapshot {
+                        Ok(snapshot) => this.show_snapshot(snapshot),
+                        Err(e) => {
+                            g_warning!("MissionCenter", "Failed to open snapshot: {}", e);
+                            this.show_snapshot_error(&i18n("Failed to Open Snapshot"), &e);
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    /// Shows the processes and services of `snapshot` instead of the ones of the monitored
+    /// machine, the performance page stays live
+    fn show_snapshot(&self, snapshot: Snapshot) {
+        let this = self.imp();
+
+        let Snapshot {
+            host,
+            taken_at,
+            readings,
+        } = snapshot;
+
+        let taken_at = glib::DateTime::from_iso8601(&taken_at, None)
+            .and_then(|time| time.to_local())
+            .and_then(|time| time.format("%c"))
+            .map(|time| time.to_string())
+            .unwrap_or(taken_at);
+        this.replay_revealer.set_reveal_child(false);
+        this.replayed_frame.set(None);
+
+        self.show_snapshot_readings(
+            &i18n_f("Showing a snapshot of {} taken {}", &[&host, &taken_at]),
+            readings,
+        );
+    }
+
+    fn replay_session(&self) {
+        let this = self.imp();
+
+        let Some((_, last)) = this.session_recording.borrow().range() else {
+            return;
+        };
+
+        this.replay_revealer.set_reveal_child(true);
+        self.update_replay_range();
+        // Starts with the latest readings, stepping back from there
+        this.replay_scale.set_value(last as f64);
+        self.show_replay_frame(last);
+    }
+
+    fn show_replay_frame(&self, frame: u64) {
+        let this = self.imp();
+
+        if !this.replay_revealer.reveals_child() || this.replayed_frame.get() == Some(frame) {
+            return;
+        }
+
+        let recording = this.session_recording.borrow();
+        let Some(snapshot) = recording.load(frame) else {
+            return;
+        };
+        let taken_at = recording
+            .taken_at(frame)
+            .and_then(|time| time.format("%X").ok())
+            .map(|time| time.to_string())
+            .unwrap_or_default();
+        drop(recording);
+
+        this.replayed_frame.set(Some(frame));
+        this.replay_time.set_label(&taken_at);
+
+        self.show_snapshot_readings(
+            &i18n_f("Replaying the readings of {}", &[&taken_at]),
+            snapshot.readings,
+        );
+    }
+
+    fn update_replay_range(&self) {
+        let this = self.imp();
+
+        let range = this.session_recording.borrow().range();
+        if let Some((first, last)) = range {
+            let adjustment = this.replay_scale.adjustment();
+            // Frames dropped from the recording can't be shown anymore
+            let value = adjustment.value().clamp(first as f64, last as f64);
+            adjustment.configure(value, first as f64, last as f64, 1., 10., 0.);
+        }
+
+        if let Some(action) = self
+            .lookup_action("replay-session")
+            .and_downcast::<gio::SimpleAction>()
+        {
+            action.set_enabled(range.is_some() && !this.snapshot_banner.is_revealed());
+        }
+    }
+
+    /// Shows `readings` in the tables until the snapshot is closed, with `title` on the banner
+    fn show_snapshot_readings(&self, title: &str, readings: Readings) {
+        let this = self.imp();
+
+        this.snapshot_banner.set_title(title);
+        this.snapshot_banner.set_revealed(true);
+        self.update_snapshot_actions();
+
+        this.apps_page.update_readings(&readings);
+        if !readings.system_services.is_empty() || !readings.user_services.is_empty() {
+            this.services_stack_page.set_visible(true);
+            this.services_page.request_initialization();
+            this.services_page.update_readings(&readings);
+        } else {
+            this.services_stack_page.set_visible(false);
+        }
+
+        this.system_snapshot.replace(SystemSnapshot::new(&readings));
+        this.readings.replace(Rc::new(readings));
+    }
+
+    fn close_snapshot(&self) {
+        let this = self.imp();
+
+        this.snapshot_banner.set_revealed(false);
+        this.replay_revealer.set_reveal_child(false);
+        this.replayed_frame.set(None);
+        this.leaving_snapshot.set(true);
+        self.update_snapshot_actions();
+    }
+
+    fn update_snapshot_actions(&self) {
+        let this = self.imp();
+        let showing = this.snapshot_banner.is_revealed();
+
+        for (name, enabled) in [("save-snapshot", !showing), ("close-snapshot", showing)] {
+            if let Some(action) = self.lookup_action(name).and_downcast::<gio::SimpleAction>() {
+                action.set_enabled(enabled);
+            }
+        }
+        self.update_replay_range();
+
+        // Only some of the row actions apply to a snapshot
+        this.apps_page.imp().table_view.notify_selected_item();
+        this.services_page.imp().table_view.notify_selected_item();
+    }
+
+    fn show_snapshot_error(&self, heading: &str, message: &str) {
+        let dialog = adw::AlertDialog::new(Some(heading), Some(message));
+        dialog.add_response("close", &i18n("_Close"));
+        dialog.present(Some(self));
+    }
+
+    /// Whether the window shows a snapshot instead of the readings of this machine. Nothing
+    /// about the processes in it can be read from `/proc` then, and none of them can be acted on.
+    pub fn is_showing_snapshot(&self) -> bool {
+        self.imp().snapshot_banner.is_revealed()
+    }
+
+    /// The processes as of the last update
+    pub fn running_processes(&self) -> Ref<'_, HashMap<u32, Process>> {
+        Ref::map(self.imp().readings.borrow(), |readings| {
+            &readings.running_processes
+        })
+    }
+
+    pub fn process_changes(&self) -> Ref<'_, ProcessChangeSet> {
+        Ref::map(self.imp().readings.borrow(), |readings| {
+            &readings.process_changes
+        })
+    }
+
+    /// The ID of the user or system service called `name`, as of the last update
+    pub fn service_id(&self, name: &str) -> Option<u64> {
+        let readings = self.imp().readings.borrow();
+        readings
+            .user_services
+            .values()
+            .chain(readings.system_services.values())
+            .find(|service| {
+                service.name == name || service.name.strip_suffix(".service") == Some(name)
+            })
+            .map(|service| service.id)
+    }
+
+    /// The user or system service with the given ID and how it is changing, as of the last update
+    pub fn service(&self, id: u64) -> Option<(Service, ServiceTransition)> {
+        let readings = self.imp().readings.borrow();
+        let service = readings
+            .user_services
+            .get(&id)
+            .or_else(|| readings.system_services.get(&id))?;
+        let transition = readings
+            .service_transitions
+            .get(&id)
+            .copied()
+            .unwrap_or_default();
+
+        Some((service.clone(), transition))
+    }
+
+    /// Runs the action called `name` from the menu of process rows on the process `pid`, like
+    /// the Apps page does. `None` if there is no such process as of the last update, `false` if
+    /// the action does not apply to it.
+    pub fn execute_process_action(&self, name: &str, pid: u32) -> Option<bool> {
+        let row_model = {
+            let running_processes = self.running_processes();
+            let process = running_processes.get(&pid)?;
+            RowModelBuilder::new()
+                .id(&pid.to_string())
+                .content_type(ContentType::Process)
+                .pid(pid)
+                .name(&process.name)
+                .exe(&process.exe)
+                .build()
+        };
+
+        Some(self.imp().apps_page.execute_action(name, &row_model))
+    }
+
+    /// Like `execute_process_action`, for the service `service_id`. Its row on the Services page
+    /// is used when there is one, so that it shows the request as pending.
+    pub fn execute_service_action(&self, name: &str, service_id: u64) -> Option<bool> {
+        let services_page = &self.imp().services_page;
+
+        let row_model = match services_page.service_row(service_id) {
+            Some(row_model) => row_model,
+            None => {
+                let (service, transition) = self.service(service_id)?;
+                RowModelBuilder::new()
+                    .id(&service_id.to_string())
+                    .content_type(ContentType::Service)
+                    .service_id(service_id)
+                    .name(&service.name)
+                    .running(service.running && transition == ServiceTransition::None)
+                    .build()
+            }
+        };
+
+        Some(services_page.execute_action(name, &row_model))
+    }
+
+    /// What the diagnostic report needs from the last update, besides the processes
+    pub fn system_snapshot(&self) -> Ref<'_, SystemSnapshot> {
+        self.imp().system_snapshot.borrow()
+    }
+
     pub fn update_animations(&self) -> bool {
         let mut result = true;
 
@@ -1140,4 +2023,75 @@ impl MissionCenterWindow {
 
         result
     }
+
+    /// The header button is only shown once there is more than one machine to pick from
+    fn update_host_button(&self, settings: &gio::Settings) {
+        let host_button = &self.imp().host_button;
+
+        let hosts = RemoteHost::all(settings);
+        host_button.set_visible(!hosts.is_empty());
+
+        let label = RemoteHost::active(settings)
+            .map(|host| host.name)
+            .unwrap_or_else(|| i18n("This Computer"));
+        host_button.set_label(&label);
+
+        let host_section = gio::Menu::new();
+        let local_item = gio::MenuItem::new(Some(&i18n("This Computer")), None);
+        local_item
+            .set_action_and_target_value(Some("app.active-remote-host"), Some(&"".to_variant()));
+        host_section.append_item(&local_item);
+        for host in &hosts {
+            let item = gio::MenuItem::new(Some(&host.name), None);
+            item.set_action_and_target_value(
+                Some("app.active-remote-host"),
+                Some(&host.name.to_variant()),
+            );
+            host_section.append_item(&item);
+        }
+
+        let manage_section = gio::Menu::new();
+        manage_section.append(
+            Some(&i18n("_Manage Remote Hosts…")),
+            Some("app.remote-hosts"),
+        );
+
+        let menu = gio::Menu::new();
+        menu.append_section(None, &host_section);
+        menu.append_section(None, &manage_section);
+        host_button.set_menu_model(Some(&menu));
+    }
+}
+
+fn update_metrics_exporter(
+    settings: &gio::Settings,
+    sys_info: &crate::magpie_client::MagpieClient,
+) {
+    let address = settings.boolean("metrics-exporter-enabled").then(|| {
+        let ip = if settings.boolean("metrics-exporter-listen-all") {
+            Ipv4Addr::UNSPECIFIED
+        } else {
+            Ipv4Addr::LOCALHOST
+        };
+        let port = settings.uint("metrics-exporter-port").min(u16::MAX as u32) as u16;
+
+        SocketAddr::new(IpAddr::V4(ip), port)
+    });
+
+    if let Err(e) = sys_info.set_metrics_exporter(address) {
+        g_critical!(
+            "MissionCenter",
+            "Failed to start the metrics exporter on {:?}: {}",
+            address,
+            e
+        );
+    }
+}
+
+/// The machine new snapshots are named after
+/// The name the readings of the current data source are saved under
+pub fn snapshot_host_name() -> String {
+    RemoteHost::active(&settings!())
+        .map(|host| host.name)
+        .unwrap_or_else(|| glib::host_name().to_string())
 }